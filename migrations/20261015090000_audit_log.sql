@@ -0,0 +1,22 @@
+-- Journal d'audit des actions sensibles (révélation de secrets, actions administratives, ...).
+CREATE TABLE audit_logs
+(
+    id BIGSERIAL PRIMARY KEY,
+
+    -- Le login de l'utilisateur à l'origine de l'action.
+    actor_login VARCHAR(255) NOT NULL,
+
+    -- Identifiant technique de l'action (ex: 'database.credentials_revealed').
+    action VARCHAR(255) NOT NULL,
+
+    -- Projet concerné, si applicable. Conservé après suppression du projet pour garder l'historique.
+    project_id INTEGER NULL,
+
+    -- Informations complémentaires propres à l'action.
+    details JSONB NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_logs_project_id ON audit_logs(project_id);
+CREATE INDEX idx_audit_logs_actor_login ON audit_logs(actor_login);
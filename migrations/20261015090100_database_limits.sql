@@ -0,0 +1,5 @@
+-- Limites de ressources appliquées à l'utilisateur MariaDB de chaque base.
+-- 0 signifie "illimité" (sémantique MariaDB), ce qui reflète l'état des bases existantes.
+ALTER TABLE databases
+    ADD COLUMN max_user_connections INTEGER NOT NULL DEFAULT 0 CHECK (max_user_connections >= 0),
+    ADD COLUMN max_queries_per_hour INTEGER NOT NULL DEFAULT 0 CHECK (max_queries_per_hour >= 0);
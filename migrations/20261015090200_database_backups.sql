@@ -0,0 +1,26 @@
+-- Résultat d'une sauvegarde de base de données MariaDB.
+CREATE TYPE database_backup_status AS ENUM ('succeeded', 'failed');
+
+-- Historique des sauvegardes nocturnes des bases de données gérées.
+CREATE TABLE database_backups
+(
+    id SERIAL PRIMARY KEY,
+
+    -- La base sauvegardée. Si la base est supprimée, son historique de sauvegardes l'est aussi.
+    database_id INTEGER NOT NULL REFERENCES databases(id) ON DELETE CASCADE,
+
+    status database_backup_status NOT NULL,
+
+    -- Chemin du dump compressé (gzip) sur l'hôte. NULL si la sauvegarde a échoué.
+    file_path VARCHAR(2048) NULL,
+
+    -- Taille du fichier compressé, en octets.
+    size_bytes BIGINT NULL,
+
+    -- Message d'erreur en cas d'échec.
+    error TEXT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_database_backups_database_id ON database_backups(database_id, created_at DESC);
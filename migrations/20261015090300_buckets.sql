@@ -0,0 +1,20 @@
+-- Table pour gérer les buckets S3 (MinIO) provisionnés pour les projets.
+CREATE TABLE buckets
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Le projet propriétaire du bucket. Un seul bucket par projet.
+    -- La suppression du bucket côté MinIO est gérée par la logique applicative lors de la purge.
+    project_id INTEGER NOT NULL UNIQUE REFERENCES projects(id) ON DELETE CASCADE,
+
+    -- Le nom du bucket sur le serveur MinIO. Doit être unique.
+    bucket_name VARCHAR(63) NOT NULL UNIQUE,
+
+    -- La clé d'accès de l'utilisateur MinIO dédié, limité à ce bucket par une politique.
+    access_key VARCHAR(128) NOT NULL UNIQUE,
+
+    -- La clé secrète de cet utilisateur, stockée sous forme chiffrée.
+    encrypted_secret_key TEXT NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,46 @@
+-- Tâches planifiées (cron) exécutées dans des conteneurs éphémères à partir de l'image déployée du projet.
+CREATE TABLE project_jobs
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Le projet propriétaire. Les tâches sont supprimées avec le projet.
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+
+    -- Expression cron à 5 champs, évaluée en UTC.
+    schedule VARCHAR(255) NOT NULL,
+
+    -- Commande exécutée à la place de la commande par défaut de l'image.
+    command TEXT[] NOT NULL,
+
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+
+    -- Minute de la dernière exécution déclenchée. Sert aussi de verrou pour éviter les doubles exécutions.
+    last_run_at TIMESTAMPTZ NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_jobs_project_id ON project_jobs(project_id);
+
+CREATE TYPE job_run_status AS ENUM ('running', 'succeeded', 'failed', 'timed_out');
+
+-- Historique des exécutions d'une tâche planifiée.
+CREATE TABLE job_runs
+(
+    id SERIAL PRIMARY KEY,
+
+    job_id INTEGER NOT NULL REFERENCES project_jobs(id) ON DELETE CASCADE,
+
+    status job_run_status NOT NULL DEFAULT 'running',
+
+    -- Code de sortie du conteneur. NULL tant que l'exécution est en cours ou si elle a expiré.
+    exit_code INTEGER NULL,
+
+    -- Dernières lignes de la sortie standard et d'erreur.
+    output TEXT NULL,
+
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX idx_job_runs_job_id ON job_runs(job_id, started_at DESC);
@@ -0,0 +1,2 @@
+-- Délai (en secondes) entre le SIGTERM et le SIGKILL lors de l'arrêt du conteneur d'un projet.
+ALTER TABLE projects ADD COLUMN stop_grace_seconds INTEGER NOT NULL DEFAULT 10;
@@ -0,0 +1,23 @@
+-- Clés d'API limitées à un projet, utilisées par les deploy hooks (CI/CD).
+CREATE TABLE project_api_keys
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Le projet concerné. Les clés sont révoquées avec le projet.
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+
+    -- Début de la clé en clair, pour l'identifier dans l'interface.
+    key_prefix VARCHAR(16) NOT NULL,
+
+    -- Empreinte SHA-256 (hexadécimale) de la clé. La clé en clair n'est jamais stockée.
+    key_hash CHAR(64) NOT NULL UNIQUE,
+
+    -- Login de l'owner ayant créé la clé.
+    created_by VARCHAR(255) NOT NULL,
+
+    last_used_at TIMESTAMPTZ NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_api_keys_project_id ON project_api_keys(project_id);
@@ -0,0 +1,39 @@
+-- Historique des déploiements de mise à jour (image ou reconstruction) d'un projet.
+-- Conserve temporairement les artefacts intermédiaires pour reprendre un déploiement échoué.
+CREATE TYPE deployment_kind AS ENUM ('image_update', 'rebuild');
+CREATE TYPE deployment_status AS ENUM ('running', 'succeeded', 'failed');
+
+CREATE TABLE deployments
+(
+    id SERIAL PRIMARY KEY,
+
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+
+    kind deployment_kind NOT NULL,
+    status deployment_status NOT NULL DEFAULT 'running',
+
+    -- Login de l'utilisateur, ou identifiant de la clé d'API, ayant déclenché le déploiement.
+    triggered_by VARCHAR(255) NOT NULL,
+
+    -- Image demandée pour une mise à jour d'image, afin de pouvoir relancer le déploiement entièrement.
+    requested_image TEXT NULL,
+
+    -- Déploiement échoué dont celui-ci est la reprise.
+    retry_of INTEGER NULL REFERENCES deployments(id) ON DELETE SET NULL,
+
+    -- Opération ayant échoué (ex. "Image scan", "Health check").
+    failed_stage VARCHAR(64) NULL,
+
+    -- Image construite ou récupérée, et si elle a passé le scan de sécurité.
+    image_tag TEXT NULL,
+    image_scanned BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- Au-delà de cette date, l'image intermédiaire est supprimée et la reprise n'est plus possible.
+    artifacts_expire_at TIMESTAMPTZ NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX idx_deployments_project_id ON deployments(project_id, created_at DESC);
+CREATE INDEX idx_deployments_artifacts_expire_at ON deployments(artifacts_expire_at) WHERE artifacts_expire_at IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Surcharge de l'entrypoint et de la commande de l'image, réappliquée à chaque recréation du conteneur.
+-- NULL : valeur définie par l'image.
+ALTER TABLE projects ADD COLUMN entrypoint TEXT[] NULL;
+ALTER TABLE projects ADD COLUMN command TEXT[] NULL;
@@ -0,0 +1,3 @@
+-- Fuseau horaire IANA injecté dans la variable TZ du conteneur.
+-- NULL : fuseau par défaut de l'instance (DEFAULT_TIMEZONE), sinon celui de l'image.
+ALTER TABLE projects ADD COLUMN timezone VARCHAR(64) NULL;
@@ -0,0 +1,29 @@
+-- Utilisateurs connus de l'application, mis à jour à chaque connexion CAS.
+-- Permet de repérer les projets dont le propriétaire n'a plus accès (compte désactivé, ancien élève).
+CREATE TABLE users
+(
+    login VARCHAR(255) PRIMARY KEY,
+    name VARCHAR(255) NULL,
+    email VARCHAR(255) NULL,
+    last_login_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Les propriétaires existants sont considérés comme actifs à la date de la migration,
+-- pour ne pas signaler tous les projets dès la mise en production.
+INSERT INTO users (login) SELECT DISTINCT owner FROM projects ON CONFLICT DO NOTHING;
+
+-- Projets signalés pour nettoyage par un administrateur.
+-- Le signalement peut être annulé (suppression de la ligne) tant que le projet n'est pas archivé.
+CREATE TABLE project_cleanups
+(
+    project_id INTEGER PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    flagged_by VARCHAR(255) NOT NULL,
+    reason TEXT NULL,
+    flagged_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Fin du délai de grâce : le projet est archivé (conteneur arrêté) automatiquement après cette date.
+    archive_after TIMESTAMPTZ NOT NULL,
+    -- Archivage définitif, le signalement ne peut plus être annulé.
+    archived_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX idx_project_cleanups_pending ON project_cleanups(archive_after) WHERE archived_at IS NULL;
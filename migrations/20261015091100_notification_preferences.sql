@@ -0,0 +1,19 @@
+-- Canal de livraison d'un type de notification pour un utilisateur.
+-- Sans ligne, le canal par défaut est le SSE (comportement historique).
+CREATE TYPE notification_channel AS ENUM ('sse', 'webhook', 'none');
+
+CREATE TABLE notification_preferences
+(
+    login VARCHAR(255) NOT NULL,
+    event_type VARCHAR(64) NOT NULL,
+    channel notification_channel NOT NULL,
+    PRIMARY KEY (login, event_type)
+);
+
+-- Webhook personnel vers lequel sont envoyées les notifications du canal `webhook`.
+CREATE TABLE notification_webhooks
+(
+    login VARCHAR(255) PRIMARY KEY,
+    url TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,25 @@
+-- Liens de partage en lecture seule de la page de statut d'un projet.
+-- Le jeton est signé (HMAC) et porte sa date d'expiration ; seul son identifiant (jti) est stocké,
+-- pour pouvoir le révoquer avant expiration.
+CREATE TABLE project_share_links
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Le projet partagé. Les liens disparaissent avec le projet.
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+
+    jti VARCHAR(64) NOT NULL UNIQUE,
+
+    -- Login de l'owner ayant créé le lien.
+    created_by VARCHAR(255) NOT NULL,
+
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NULL,
+
+    last_accessed_at TIMESTAMPTZ NULL,
+    access_count INTEGER NOT NULL DEFAULT 0,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_share_links_project_id ON project_share_links(project_id);
@@ -0,0 +1,18 @@
+-- Horodatages homogènes sur les tables récentes : `created_at` à l'insertion,
+-- `updated_at` à chaque modification par l'application.
+ALTER TABLE users
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+ALTER TABLE project_cleanups
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+ALTER TABLE notification_preferences
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+ALTER TABLE notification_webhooks
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+ALTER TABLE project_share_links
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
@@ -0,0 +1,3 @@
+-- Adresse du client à l'origine de l'action, résolue derrière le reverse proxy.
+-- NULL pour les actions du système et les entrées antérieures.
+ALTER TABLE audit_logs ADD COLUMN client_ip VARCHAR(45) NULL;
@@ -0,0 +1,35 @@
+-- Historique d'utilisation des ressources, agrégé par propriétaire dans les rapports d'administration.
+-- Les lignes ne référencent pas `projects` : elles survivent à la suppression du projet jusqu'à
+-- leur purge (USAGE_RETENTION_DAYS).
+
+-- Transitions démarré/arrêté du conteneur courant d'un projet, relevées par l'écoute des événements Docker.
+CREATE TABLE project_status_transitions
+(
+    id BIGSERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL,
+    owner VARCHAR(255) NOT NULL,
+    is_running BOOLEAN NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_status_transitions_project_time ON project_status_transitions(project_id, occurred_at);
+CREATE INDEX idx_project_status_transitions_time ON project_status_transitions(occurred_at);
+
+-- Échantillons périodiques des projets démarrés : CPU et mémoire sur l'intervalle, stockage à l'instant du relevé.
+CREATE TABLE project_usage_samples
+(
+    id BIGSERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL,
+    owner VARCHAR(255) NOT NULL,
+    cpu_percent DOUBLE PRECISION NOT NULL,
+    memory_bytes BIGINT NOT NULL,
+    -- Volume persistant du projet, NULL s'il n'en a pas.
+    volume_bytes BIGINT NULL,
+    -- Base MariaDB du propriétaire, NULL s'il n'en a pas.
+    database_bytes BIGINT NULL,
+    -- Durée couverte par l'échantillon, pour convertir le pourcentage CPU en secondes.
+    interval_seconds INTEGER NOT NULL,
+    sampled_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_usage_samples_time ON project_usage_samples(sampled_at);
@@ -0,0 +1,2 @@
+-- Projets GitHub construits avec le Dockerfile de leur dépôt plutôt qu'avec l'image de base générée.
+ALTER TABLE projects ADD COLUMN use_repo_dockerfile BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Image déployée avant la dernière mise à jour, conservée pour pouvoir y revenir.
+ALTER TABLE projects ADD COLUMN previous_image_tag TEXT NULL;
+ALTER TABLE projects ADD COLUMN previous_image_digest TEXT NULL;
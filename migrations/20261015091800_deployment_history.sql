@@ -0,0 +1,12 @@
+-- Historique complet des déploiements : création, mise à jour des variables d'environnement et retour arrière,
+-- en plus des mises à jour d'image et reconstructions déjà suivies.
+ALTER TYPE deployment_kind ADD VALUE 'creation';
+ALTER TYPE deployment_kind ADD VALUE 'env_update';
+ALTER TYPE deployment_kind ADD VALUE 'rollback';
+
+-- Source du projet, image obtenue et commit construit (projets GitHub), lorsqu'ils sont connus.
+ALTER TABLE deployments ADD COLUMN source_type project_source_type NULL;
+ALTER TABLE deployments ADD COLUMN image_digest TEXT NULL;
+ALTER TABLE deployments ADD COLUMN commit_sha VARCHAR(40) NULL;
+
+CREATE INDEX idx_deployments_created_at ON deployments(created_at DESC);
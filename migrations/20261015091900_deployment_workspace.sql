@@ -0,0 +1,2 @@
+-- Espace de travail d'un build GitHub, sous BUILD_WORKDIR, pour le rattacher à son déploiement.
+ALTER TABLE deployments ADD COLUMN workspace_path TEXT NULL;
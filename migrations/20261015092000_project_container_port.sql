@@ -0,0 +1,3 @@
+-- Port HTTP écouté par le conteneur du projet, cible du service Traefik (80 jusqu'ici pour tous les projets).
+ALTER TABLE projects ADD COLUMN container_port INTEGER NOT NULL DEFAULT 80
+    CHECK (container_port BETWEEN 1 AND 65535);
@@ -0,0 +1,25 @@
+-- Invitations à participer à un projet, en attente de réponse de l'invité (PARTICIPANT_INVITATIONS_ENABLED).
+-- Seule l'acceptation crée la ligne de `project_participants` ; un refus ou une acceptation supprime l'invitation.
+CREATE TABLE project_invitations
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Le projet concerné. Les invitations disparaissent avec le projet.
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+
+    -- Login de l'utilisateur invité.
+    invitee VARCHAR(255) NOT NULL,
+
+    -- Login de l'owner ayant envoyé l'invitation.
+    invited_by VARCHAR(255) NOT NULL,
+
+    -- Passé cette date, l'invitation ne peut plus être acceptée.
+    expires_at TIMESTAMPTZ NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Une seule invitation par utilisateur et par projet : une nouvelle invitation remplace la précédente.
+    UNIQUE (project_id, invitee)
+);
+
+CREATE INDEX idx_project_invitations_invitee ON project_invitations(invitee);
@@ -0,0 +1,2 @@
+-- Vulnérabilités (CVE-..., GHSA-...) ignorées par le scan grype du projet, pour les failles connues sans correctif.
+ALTER TABLE projects ADD COLUMN scan_ignore_cves TEXT[] NOT NULL DEFAULT '{}';
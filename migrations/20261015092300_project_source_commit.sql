@@ -0,0 +1,3 @@
+-- Commit du dépôt dont est issue l'image déployée (projets github) ; NULL pour les images directes
+-- ou lorsque le commit n'est pas connu (retour arrière, projets antérieurs).
+ALTER TABLE projects ADD COLUMN source_commit_sha TEXT;
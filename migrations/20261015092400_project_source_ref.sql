@@ -0,0 +1,2 @@
+-- Tag ou SHA de commit auquel un projet github est épinglé ; NULL : dernier commit de sa branche.
+ALTER TABLE projects ADD COLUMN source_ref TEXT;
@@ -0,0 +1,2 @@
+-- Profil de ressources (ulimits, pids, /tmp) du conteneur, choisi par un administrateur parmi RESOURCE_PROFILES.
+ALTER TABLE projects ADD COLUMN resource_profile TEXT NOT NULL DEFAULT 'default';
@@ -0,0 +1,2 @@
+-- Projet mis en pause par son owner : conteneur supprimé, volume, base et image conservés.
+ALTER TABLE projects ADD COLUMN archived BOOLEAN NOT NULL DEFAULT FALSE;
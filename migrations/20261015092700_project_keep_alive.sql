@@ -0,0 +1,2 @@
+-- Projet exclu de l'arrêt automatique des projets inactifs, à la demande de son owner.
+ALTER TABLE projects ADD COLUMN keep_alive BOOLEAN NOT NULL DEFAULT FALSE;
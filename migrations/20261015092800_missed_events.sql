@@ -0,0 +1,14 @@
+-- Événements émis sans abonné (résultats de scan, pannes...), rejoués à la prochaine connexion.
+-- Un événement est destiné soit à un projet, soit au canal personnel d'un utilisateur.
+CREATE TABLE missed_events
+(
+    id BIGSERIAL PRIMARY KEY,
+    project_id INTEGER NULL REFERENCES projects(id) ON DELETE CASCADE,
+    login VARCHAR(255) NULL,
+    event JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((project_id IS NULL) <> (login IS NULL))
+);
+
+CREATE INDEX idx_missed_events_project ON missed_events(project_id, id) WHERE project_id IS NOT NULL;
+CREATE INDEX idx_missed_events_login ON missed_events(login, id) WHERE login IS NOT NULL;
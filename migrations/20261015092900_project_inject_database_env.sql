@@ -0,0 +1,2 @@
+-- Identifiants de la base liée injectés dans le conteneur (`HANGAR_DB_*`), à la demande de l'owner.
+ALTER TABLE projects ADD COLUMN inject_database_env BOOLEAN NOT NULL DEFAULT FALSE;
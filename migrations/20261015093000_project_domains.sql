@@ -0,0 +1,28 @@
+-- Domaines personnalisés d'un projet, servis en plus de {projet}.{suffixe} une fois vérifiés.
+CREATE TABLE project_domains
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Le projet servi. Les domaines disparaissent avec le projet.
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+
+    -- Nom de domaine complet, en minuscules et sans point final. Plusieurs projets peuvent le revendiquer,
+    -- mais un seul peut le faire vérifier (voir l'index partiel ci-dessous).
+    fqdn VARCHAR(253) NOT NULL,
+
+    -- Valeur attendue dans l'enregistrement TXT de vérification.
+    verification_token VARCHAR(64) NOT NULL,
+
+    -- Seuls les domaines vérifiés (DNS ou administrateur) sont ajoutés aux règles Traefik.
+    verified BOOLEAN NOT NULL DEFAULT FALSE,
+    verified_at TIMESTAMPTZ NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (project_id, fqdn)
+);
+
+-- Une revendication non vérifiée ne bloque personne : seul le projet qui prouve la possession obtient le domaine.
+CREATE UNIQUE INDEX idx_project_domains_verified_fqdn ON project_domains(fqdn) WHERE verified;
+
+CREATE INDEX idx_project_domains_project_id ON project_domains(project_id);
@@ -0,0 +1,29 @@
+-- Modèles de projets proposés au déploiement en un clic (site statique, WordPress + base, application Node...).
+CREATE TABLE templates
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Nom affiché dans le catalogue.
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+
+    -- 'direct' : source_url est une image ; 'github' : un dépôt, construit depuis source_branch.
+    source_type project_source_type NOT NULL,
+    source_url TEXT NOT NULL,
+    source_branch VARCHAR(255) NULL,
+
+    -- Variables par défaut. Les valeurs peuvent contenir des champs {{NOM}} renseignés au déploiement.
+    default_env_vars JSONB NOT NULL DEFAULT '{}',
+
+    -- Crée une base MariaDB avec le projet et injecte ses identifiants (HANGAR_DB_*).
+    create_database BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- Chemin suggéré du volume persistant dans le conteneur.
+    persistent_volume_path VARCHAR(255) NULL,
+
+    -- Port HTTP écouté par l'application (80 par défaut).
+    container_port INTEGER NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- 'upload' : le projet est un site statique construit à partir d'une archive envoyée.
+-- source_url contient alors le nom d'origine de l'archive et son empreinte (site.zip#sha256:...).
+ALTER TYPE project_source_type ADD VALUE 'upload';
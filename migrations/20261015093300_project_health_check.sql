@@ -0,0 +1,4 @@
+-- Surcharges par projet de la vérification de santé d'un nouveau conteneur.
+-- NULL : valeurs de l'instance (HEALTH_CHECK_MAX_ATTEMPTS, HEALTH_CHECK_INTERVAL_MS).
+ALTER TABLE projects ADD COLUMN health_check_max_attempts INTEGER;
+ALTER TABLE projects ADD COLUMN health_check_interval_ms INTEGER;
@@ -0,0 +1,12 @@
+-- Échantillons agrégés de la plateforme, lus par GET /api/admin/metrics/history.
+CREATE TABLE metrics_history
+(
+    -- Un échantillon toutes les METRICS_HISTORY_INTERVAL_MINUTES, purgé après METRICS_HISTORY_RETENTION_DAYS.
+    sampled_at TIMESTAMPTZ PRIMARY KEY DEFAULT NOW(),
+
+    running_containers INTEGER NOT NULL,
+    -- Somme des CPU des conteneurs, en % d'un cœur.
+    total_cpu_usage DOUBLE PRECISION NOT NULL,
+    total_memory_usage_mb DOUBLE PRECISION NOT NULL,
+    total_projects INTEGER NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Périodes de fonctionnement du backend : hors de ces périodes, les transitions des conteneurs
+-- n'ont pas été relevées et la disponibilité des projets est inconnue.
+CREATE TABLE backend_sessions
+(
+    id BIGSERIAL PRIMARY KEY,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Mis à jour à chaque battement ; la session est close au dernier battement reçu.
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_backend_sessions_last_seen ON backend_sessions(last_seen_at);
@@ -0,0 +1,41 @@
+-- Webhooks d'un projet, appelés sur les déploiements et les arrêts inattendus de son conteneur.
+CREATE TABLE project_webhooks
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Le projet notifié. Les webhooks disparaissent avec le projet.
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+
+    url TEXT NOT NULL,
+
+    -- Secret de signature HMAC-SHA256 des requêtes, chiffré avec ENCRYPTION_KEY puis encodé en base64.
+    encrypted_secret TEXT NOT NULL,
+
+    -- Types d'événements transmis (`deployment_succeeded`, `deployment_failed`, `container_died`).
+    events TEXT[] NOT NULL,
+
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_webhooks_project_id ON project_webhooks(project_id);
+
+-- Tentatives de livraison, réussies ou non, consultables par les membres du projet.
+CREATE TABLE project_webhook_deliveries
+(
+    id BIGSERIAL PRIMARY KEY,
+    webhook_id INTEGER NOT NULL REFERENCES project_webhooks(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+
+    -- Numéro de la tentative pour cet événement, à partir de 1.
+    attempt INTEGER NOT NULL,
+
+    -- Statut HTTP reçu ; NULL si aucune réponse (erreur réseau, circuit ouvert).
+    status_code INTEGER NULL,
+    success BOOLEAN NOT NULL,
+    error TEXT NULL,
+
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_webhook_deliveries_webhook ON project_webhook_deliveries(webhook_id, attempted_at DESC);
@@ -0,0 +1,2 @@
+-- Les e-mails au propriétaire sont un canal des préférences de notification.
+ALTER TYPE notification_channel ADD VALUE 'email';
@@ -0,0 +1,658 @@
+//! Autorisation centralisée des accès aux ressources du Hangar.
+//!
+//! Toutes les vérifications "propriétaire / participant / admin" passent par ce module,
+//! afin que le contournement administrateur ne soit implémenté qu'à un seul endroit.
+//!
+//! Matrice des droits sur un projet :
+//! - owner et admin : toutes les opérations (variables d'environnement, image, rebuild, rollback, purge,
+//!   participants, base de données, clés d'API, tâches, liens de partage...) ;
+//! - participant : démarrer, arrêter et redémarrer le conteneur, et consulter le projet (détails sans
+//!   les valeurs des variables d'environnement, logs, métriques, déploiements, chronologie).
+//!
+//! Le rôle exigé par une opération est donné par [`ProjectMutation::required_role`].
+
+use std::collections::BTreeMap;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::
+{
+    error::AppError,
+    model::{database::Database, project::Project},
+    services::{audit_service, database_service, jwt::Claims, project_service},
+    sse::{emitter::emit_project_event, types::SystemEvent},
+    state::AppState,
+};
+
+/// Identité de l'appelant, construite à partir des `Claims` du JWT.
+#[derive(Debug, Clone)]
+pub struct AccessContext
+{
+    pub login: String,
+    pub is_admin: bool,
+}
+
+impl From<Claims> for AccessContext
+{
+    fn from(claims: Claims) -> Self
+    {
+        Self
+        {
+            login: claims.sub,
+            is_admin: claims.is_admin,
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for AccessContext where S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection>
+    {
+        let claims = Claims::from_request_parts(parts, state).await?;
+        Ok(Self::from(claims))
+    }
+}
+
+/// Niveau d'accès minimal exigé par une opération sur un projet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequiredRole
+{
+    /// Owner ou participant (lecture, contrôle du conteneur).
+    Participant,
+    /// Owner uniquement (suppression, gestion des participants, ...).
+    Owner,
+}
+
+/// Rôle effectif de l'appelant sur un projet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectRole
+{
+    Admin,
+    Owner,
+    Participant,
+}
+
+impl ProjectRole
+{
+    #[must_use]
+    pub const fn satisfies(self, required: RequiredRole) -> bool
+    {
+        match (self, required)
+        {
+            (Self::Admin | Self::Owner, _) | (Self::Participant, RequiredRole::Participant) => true,
+            (Self::Participant, RequiredRole::Owner) => false,
+        }
+    }
+}
+
+/// Opération modifiant un projet, signalée à son propriétaire lorsqu'un administrateur l'effectue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectMutation
+{
+    Start,
+    Stop,
+    Restart,
+    Purge,
+    Archive,
+    Unarchive,
+    UpdateStopGrace,
+    UpdateKeepAlive,
+    UpdateHealthCheck,
+    UpdateImage,
+    Rebuild,
+    Rollback,
+    RetryDeployment,
+    UpdateEnvVars,
+    UpdateCommand,
+    UpdatePort,
+    UpdateResourceProfile,
+    UpdateScanIgnores,
+    ManageParticipants,
+    ManageDatabase,
+    CreateBucket,
+    ManageApiKeys,
+    ManageJobs,
+    RunCommand,
+    ManageShareLinks,
+    ManageDomains,
+    ManageWebhooks,
+}
+
+impl ProjectMutation
+{
+    pub const ALL: [Self; 27] =
+    [
+        Self::Start, Self::Stop, Self::Restart, Self::Purge, Self::Archive, Self::Unarchive, Self::UpdateStopGrace, Self::UpdateKeepAlive,
+        Self::UpdateHealthCheck, Self::UpdateImage, Self::Rebuild,
+        Self::Rollback, Self::RetryDeployment, Self::UpdateEnvVars, Self::UpdateCommand, Self::UpdatePort,
+        Self::UpdateResourceProfile, Self::UpdateScanIgnores, Self::ManageParticipants, Self::ManageDatabase, Self::CreateBucket, Self::ManageApiKeys, Self::ManageJobs,
+        Self::RunCommand, Self::ManageShareLinks, Self::ManageDomains, Self::ManageWebhooks,
+    ];
+
+    /// Rôle minimal exigé : les participants ne peuvent qu'opérer le conteneur, tout le reste revient à l'owner.
+    #[must_use]
+    pub const fn required_role(self) -> RequiredRole
+    {
+        match self
+        {
+            Self::Start | Self::Stop | Self::Restart => RequiredRole::Participant,
+            Self::Purge
+            | Self::Archive
+            | Self::Unarchive
+            | Self::UpdateStopGrace
+            | Self::UpdateKeepAlive
+            | Self::UpdateHealthCheck
+            | Self::UpdateImage
+            | Self::Rebuild
+            | Self::Rollback
+            | Self::RetryDeployment
+            | Self::UpdateEnvVars
+            | Self::UpdateCommand
+            | Self::UpdatePort
+            | Self::UpdateResourceProfile
+            | Self::UpdateScanIgnores
+            | Self::ManageParticipants
+            | Self::ManageDatabase
+            | Self::CreateBucket
+            | Self::ManageApiKeys
+            | Self::ManageJobs
+            | Self::RunCommand
+            | Self::ManageShareLinks
+            | Self::ManageDomains
+            | Self::ManageWebhooks => RequiredRole::Owner,
+        }
+    }
+
+    /// Identifiant de l'opération dans le journal d'audit et le contexte de l'événement.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+            Self::Purge => "purge",
+            Self::Archive => "archive",
+            Self::Unarchive => "unarchive",
+            Self::UpdateStopGrace => "update_stop_grace",
+            Self::UpdateKeepAlive => "update_keep_alive",
+            Self::UpdateHealthCheck => "update_health_check",
+            Self::UpdateImage => "update_image",
+            Self::Rebuild => "rebuild",
+            Self::Rollback => "rollback",
+            Self::RetryDeployment => "retry_deployment",
+            Self::UpdateEnvVars => "update_env_vars",
+            Self::UpdateCommand => "update_command",
+            Self::UpdatePort => "update_port",
+            Self::UpdateResourceProfile => "update_resource_profile",
+            Self::UpdateScanIgnores => "update_scan_ignores",
+            Self::ManageParticipants => "manage_participants",
+            Self::ManageDatabase => "manage_database",
+            Self::CreateBucket => "create_bucket",
+            Self::ManageApiKeys => "manage_api_keys",
+            Self::ManageJobs => "manage_jobs",
+            Self::RunCommand => "run_command",
+            Self::ManageShareLinks => "manage_share_links",
+            Self::ManageDomains => "manage_domains",
+            Self::ManageWebhooks => "manage_webhooks",
+        }
+    }
+
+    const fn description(self) -> &'static str
+    {
+        match self
+        {
+            Self::Start => "a start",
+            Self::Stop => "a stop",
+            Self::Restart => "a restart",
+            Self::Purge => "the deletion",
+            Self::Archive => "the archiving",
+            Self::Unarchive => "the unarchiving",
+            Self::UpdateStopGrace => "a stop grace period change",
+            Self::UpdateKeepAlive => "a keep-alive change",
+            Self::UpdateHealthCheck => "a health check change",
+            Self::UpdateImage => "an image update",
+            Self::Rebuild => "a rebuild",
+            Self::Rollback => "a rollback",
+            Self::RetryDeployment => "a deployment retry",
+            Self::UpdateEnvVars => "an environment variables update",
+            Self::UpdateCommand => "a container command update",
+            Self::UpdatePort => "a container port update",
+            Self::UpdateResourceProfile => "a resource profile change",
+            Self::UpdateScanIgnores => "a scan ignore list change",
+            Self::ManageParticipants => "a participants change",
+            Self::ManageDatabase => "a database change",
+            Self::CreateBucket => "a bucket creation",
+            Self::ManageApiKeys => "an API keys change",
+            Self::ManageJobs => "a scheduled jobs change",
+            Self::RunCommand => "a one-off command",
+            Self::ManageShareLinks => "a share links change",
+            Self::ManageDomains => "a custom domains change",
+            Self::ManageWebhooks => "a webhooks change",
+        }
+    }
+}
+
+/// Raison typée d'un refus d'accès.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessDenied
+{
+    /// La ressource n'existe pas.
+    NotFound,
+    /// La ressource existe mais l'appelant n'a pas les droits suffisants.
+    Forbidden,
+}
+
+/// Détermine le rôle de l'appelant sur un projet, s'il en a un.
+#[must_use]
+pub fn resolve_project_role(ctx: &AccessContext, project: &Project, is_participant: bool) -> Option<ProjectRole>
+{
+    if ctx.is_admin
+    {
+        Some(ProjectRole::Admin)
+    }
+    else if project.owner == ctx.login
+    {
+        Some(ProjectRole::Owner)
+    }
+    else if is_participant
+    {
+        Some(ProjectRole::Participant)
+    }
+    else
+    {
+        None
+    }
+}
+
+/// Décide si l'appelant peut effectuer une opération exigeant `required` sur `project`.
+///
+/// # Errors
+/// Retourne [`AccessDenied::Forbidden`] si l'appelant n'a aucun rôle sur le projet
+/// ou un rôle insuffisant.
+pub fn authorize_project(
+    ctx: &AccessContext,
+    project: &Project,
+    is_participant: bool,
+    required: RequiredRole,
+) -> Result<ProjectRole, AccessDenied>
+{
+    match resolve_project_role(ctx, project, is_participant)
+    {
+        Some(role) if role.satisfies(required) => Ok(role),
+        _ => Err(AccessDenied::Forbidden),
+    }
+}
+
+/// Décide si l'appelant peut gérer une base de données (owner ou admin uniquement).
+///
+/// # Errors
+/// Retourne [`AccessDenied::Forbidden`] si l'appelant n'est ni owner ni admin.
+pub fn authorize_database(ctx: &AccessContext, database: &Database) -> Result<(), AccessDenied>
+{
+    if ctx.is_admin || database.owner_login == ctx.login
+    {
+        Ok(())
+    }
+    else
+    {
+        Err(AccessDenied::Forbidden)
+    }
+}
+
+/// Convertit un refus d'accès en erreur HTTP.
+///
+/// Quand `expose_forbidden` est désactivé, un refus est volontairement rendu
+/// indiscernable d'une ressource inexistante (404) pour ne rien divulguer.
+#[must_use]
+pub fn access_error(denial: AccessDenied, expose_forbidden: bool, not_found: String, forbidden: String) -> AppError
+{
+    match denial
+    {
+        AccessDenied::Forbidden if expose_forbidden => AppError::Forbidden(forbidden),
+        AccessDenied::NotFound | AccessDenied::Forbidden => AppError::NotFound(not_found),
+    }
+}
+
+fn project_access_error(project_id: i32, denial: AccessDenied, expose_forbidden: bool) -> AppError
+{
+    access_error(
+        denial,
+        expose_forbidden,
+        format!("Project with ID {project_id} not found or you don't have access."),
+        format!("You don't have sufficient rights on project {project_id}."),
+    )
+}
+
+fn database_access_error(db_id: i32, denial: AccessDenied, expose_forbidden: bool) -> AppError
+{
+    access_error(
+        denial,
+        expose_forbidden,
+        format!("Database with ID {db_id} not found or you are not the owner."),
+        format!("You are not the owner of database {db_id}."),
+    )
+}
+
+/// Indique si le mot de passe de la base liée à un projet peut être révélé à l'appelant.
+///
+/// La base appartient conceptuellement au owner : les participants n'y ont accès
+/// que si l'installation l'autorise explicitement.
+#[must_use]
+pub const fn can_reveal_db_credentials(role: ProjectRole, participants_allowed: bool) -> bool
+{
+    match role
+    {
+        ProjectRole::Admin | ProjectRole::Owner => true,
+        ProjectRole::Participant => participants_allowed,
+    }
+}
+
+/// Indique si les valeurs des variables d'environnement du projet peuvent être montrées à l'appelant.
+#[must_use]
+pub const fn can_view_env_vars(role: ProjectRole) -> bool
+{
+    matches!(role, ProjectRole::Admin | ProjectRole::Owner)
+}
+
+/// Droits de l'appelant sur un projet, pour que l'interface n'affiche que les actions permises.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ProjectPermissions
+{
+    pub role: ProjectRole,
+    /// Chaque opération de [`ProjectMutation`], par identifiant, et si l'appelant peut l'effectuer.
+    pub actions: BTreeMap<&'static str, bool>,
+    pub view_env_vars: bool,
+    pub view_db_credentials: bool,
+}
+
+#[must_use]
+pub fn project_permissions(role: ProjectRole, db_credentials_for_participants: bool) -> ProjectPermissions
+{
+    ProjectPermissions
+    {
+        role,
+        actions: ProjectMutation::ALL.into_iter()
+            .map(|mutation| (mutation.as_str(), role.satisfies(mutation.required_role())))
+            .collect(),
+        view_env_vars: can_view_env_vars(role),
+        view_db_credentials: can_reveal_db_credentials(role, db_credentials_for_participants),
+    }
+}
+
+/// Charge un projet et vérifie que l'appelant possède le rôle requis.
+pub async fn load_project(
+    state: &AppState,
+    ctx: &AccessContext,
+    project_id: i32,
+    required: RequiredRole,
+) -> Result<Project, AppError>
+{
+    load_project_with_role(state, ctx, project_id, required).await.map(|(project, _)| project)
+}
+
+/// Comme [`load_project`], mais renvoie aussi le rôle effectif de l'appelant.
+pub async fn load_project_with_role(
+    state: &AppState,
+    ctx: &AccessContext,
+    project_id: i32,
+    required: RequiredRole,
+) -> Result<(Project, ProjectRole), AppError>
+{
+    let project = project_service::get_project_by_id(&state.db_pool, project_id).await?
+        .ok_or_else(|| project_access_error(project_id, AccessDenied::NotFound, state.config.expose_forbidden))?;
+
+    let needs_membership_lookup = !ctx.is_admin && project.owner != ctx.login && required == RequiredRole::Participant;
+    let is_participant = if needs_membership_lookup
+    {
+        project_service::is_project_participant(&state.db_pool, project_id, &ctx.login).await?
+    }
+    else
+    {
+        false
+    };
+
+    let role = authorize_project(ctx, &project, is_participant, required)
+        .map_err(|denial| project_access_error(project_id, denial, state.config.expose_forbidden))?;
+
+    Ok((project, role))
+}
+
+/// Comme [`load_project`], pour une opération qui modifie le projet ; le rôle exigé est celui de `mutation`.
+///
+/// Lorsqu'un administrateur agit sur le projet d'un autre utilisateur, le propriétaire en est prévenu
+/// sur le canal du projet et l'opération est tracée dans le journal d'audit.
+pub async fn load_project_for(
+    state: &AppState,
+    ctx: &AccessContext,
+    project_id: i32,
+    mutation: ProjectMutation,
+) -> Result<Project, AppError>
+{
+    let project = load_project(state, ctx, project_id, mutation.required_role()).await?;
+
+    if let Some(event) = admin_action_event(ctx, &project, mutation)
+    {
+        audit_service::record(
+            state,
+            &ctx.login,
+            audit_service::ACTION_ADMIN_ACTION,
+            Some(project.id),
+            Some(json!({ "action": mutation.as_str(), "owner": project.owner })),
+        ).await;
+        emit_project_event(state, project.id, event).await;
+    }
+
+    Ok(project)
+}
+
+/// Événement adressé au propriétaire lorsqu'un administrateur modifie son projet (`None` sinon).
+#[must_use]
+pub fn admin_action_event(ctx: &AccessContext, project: &Project, mutation: ProjectMutation) -> Option<SystemEvent>
+{
+    if !ctx.is_admin || ctx.login == project.owner
+    {
+        return None;
+    }
+
+    let message = format!("An administrator initiated {} of your project.", mutation.description());
+    Some(SystemEvent::warning(message).with_context(json!({ "action": mutation.as_str(), "admin": ctx.login })))
+}
+
+/// Charge une base de données et vérifie que l'appelant en est owner (ou admin).
+pub async fn load_database(
+    state: &AppState,
+    ctx: &AccessContext,
+    db_id: i32,
+) -> Result<Database, AppError>
+{
+    let database = database_service::get_database_by_id(&state.db_pool, db_id).await?
+        .ok_or_else(|| database_access_error(db_id, AccessDenied::NotFound, state.config.expose_forbidden))?;
+
+    authorize_database(ctx, &database)
+        .map_err(|denial| database_access_error(db_id, denial, state.config.expose_forbidden))?;
+
+    Ok(database)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use axum::{http::StatusCode, response::IntoResponse};
+    use time::OffsetDateTime;
+
+    fn ctx(login: &str, is_admin: bool) -> AccessContext
+    {
+        AccessContext { login: login.into(), is_admin }
+    }
+
+    #[test]
+    fn test_owner_has_every_access()
+    {
+        let project = test_support::project(1, "alice");
+        let alice = ctx("alice", false);
+
+        assert_eq!(authorize_project(&alice, &project, false, RequiredRole::Participant), Ok(ProjectRole::Owner));
+        assert_eq!(authorize_project(&alice, &project, false, RequiredRole::Owner), Ok(ProjectRole::Owner));
+    }
+
+    #[test]
+    fn test_admin_bypasses_ownership()
+    {
+        let project = test_support::project(1, "alice");
+        let admin = ctx("root", true);
+
+        assert_eq!(authorize_project(&admin, &project, false, RequiredRole::Participant), Ok(ProjectRole::Admin));
+        assert_eq!(authorize_project(&admin, &project, false, RequiredRole::Owner), Ok(ProjectRole::Admin));
+    }
+
+    #[test]
+    fn test_participant_is_limited_to_participant_routes()
+    {
+        let project = test_support::project(1, "alice");
+        let bob = ctx("bob", false);
+
+        assert_eq!(authorize_project(&bob, &project, true, RequiredRole::Participant), Ok(ProjectRole::Participant));
+        assert_eq!(authorize_project(&bob, &project, true, RequiredRole::Owner), Err(AccessDenied::Forbidden));
+    }
+
+    #[test]
+    fn test_stranger_is_denied()
+    {
+        let project = test_support::project(1, "alice");
+        let eve = ctx("eve", false);
+
+        assert_eq!(authorize_project(&eve, &project, false, RequiredRole::Participant), Err(AccessDenied::Forbidden));
+        assert_eq!(authorize_project(&eve, &project, false, RequiredRole::Owner), Err(AccessDenied::Forbidden));
+    }
+
+    #[test]
+    fn test_admin_stop_notifies_owner()
+    {
+        let project = test_support::project(1, "alice");
+
+        let event = admin_action_event(&ctx("root", true), &project, ProjectMutation::Stop).unwrap();
+        assert_eq!(event.message, "An administrator initiated a stop of your project.");
+        assert_eq!(event.context, Some(json!({ "action": "stop", "admin": "root" })));
+    }
+
+    #[test]
+    fn test_owner_stop_is_not_notified()
+    {
+        let project = test_support::project(1, "alice");
+
+        assert!(admin_action_event(&ctx("alice", false), &project, ProjectMutation::Stop).is_none());
+        // Un administrateur agissant sur son propre projet n'est pas signalé.
+        assert!(admin_action_event(&ctx("alice", true), &project, ProjectMutation::Stop).is_none());
+        assert!(admin_action_event(&ctx("bob", false), &project, ProjectMutation::Stop).is_none());
+    }
+
+    #[test]
+    fn test_database_access()
+    {
+        let database = Database
+        {
+            id: 1,
+            owner_login: "alice".into(),
+            database_name: "hangardb_alice".into(),
+            username: "alice".into(),
+            encrypted_password: String::new(),
+            project_id: None,
+            max_user_connections: 10,
+            max_queries_per_hour: 0,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        };
+
+        assert!(authorize_database(&ctx("alice", false), &database).is_ok());
+        assert!(authorize_database(&ctx("root", true), &database).is_ok());
+        assert_eq!(authorize_database(&ctx("bob", false), &database), Err(AccessDenied::Forbidden));
+    }
+
+    /// Les rôles exigés par les routes elles-mêmes sont vérifiés à travers le routeur (`router::tests`).
+    #[test]
+    fn test_forbidden_is_hidden_unless_exposed()
+    {
+        let status = |denial, expose_forbidden| project_access_error(1, denial, expose_forbidden).into_response().status();
+
+        assert_eq!(status(AccessDenied::Forbidden, false), StatusCode::NOT_FOUND);
+        assert_eq!(status(AccessDenied::Forbidden, true), StatusCode::FORBIDDEN);
+        assert_eq!(status(AccessDenied::NotFound, false), StatusCode::NOT_FOUND);
+        assert_eq!(status(AccessDenied::NotFound, true), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_participants_may_only_operate_the_container()
+    {
+        let project = test_support::project(1, "alice");
+        let operations = [ProjectMutation::Start, ProjectMutation::Stop, ProjectMutation::Restart];
+
+        for mutation in ProjectMutation::ALL
+        {
+            let required = mutation.required_role();
+            assert_eq!(authorize_project(&ctx("alice", false), &project, false, required), Ok(ProjectRole::Owner), "{mutation:?}");
+            assert_eq!(authorize_project(&ctx("root", true), &project, false, required), Ok(ProjectRole::Admin), "{mutation:?}");
+            assert_eq!(authorize_project(&ctx("eve", false), &project, false, required), Err(AccessDenied::Forbidden), "{mutation:?}");
+
+            let participant = authorize_project(&ctx("bob", false), &project, true, required);
+            if operations.contains(&mutation)
+            {
+                assert_eq!(participant, Ok(ProjectRole::Participant), "{mutation:?}");
+            }
+            else
+            {
+                assert_eq!(participant, Err(AccessDenied::Forbidden), "{mutation:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_owner_only_mutations()
+    {
+        for mutation in [
+            ProjectMutation::UpdateEnvVars,
+            ProjectMutation::UpdateImage,
+            ProjectMutation::Rebuild,
+            ProjectMutation::Rollback,
+            ProjectMutation::RetryDeployment,
+            ProjectMutation::Purge,
+            ProjectMutation::ManageParticipants,
+        ]
+        {
+            assert_eq!(mutation.required_role(), RequiredRole::Owner, "{mutation:?}");
+        }
+    }
+
+    #[test]
+    fn test_project_permissions()
+    {
+        let participant = project_permissions(ProjectRole::Participant, false);
+        assert_eq!(participant.actions.len(), ProjectMutation::ALL.len());
+        assert_eq!(participant.actions.iter().filter(|(_, allowed)| **allowed).map(|(action, _)| *action).collect::<Vec<_>>(), vec!["restart", "start", "stop"]);
+        assert!(!participant.view_env_vars);
+        assert!(!participant.view_db_credentials);
+
+        let owner = project_permissions(ProjectRole::Owner, false);
+        assert!(owner.actions.values().all(|allowed| *allowed));
+        assert!(owner.view_env_vars && owner.view_db_credentials);
+
+        let json = serde_json::to_value(project_permissions(ProjectRole::Admin, false)).unwrap();
+        assert_eq!(json["role"], "admin");
+        assert_eq!(json["actions"]["update_env_vars"], true);
+    }
+
+    #[test]
+    fn test_db_credentials_visibility()
+    {
+        assert!(can_reveal_db_credentials(ProjectRole::Owner, false));
+        assert!(can_reveal_db_credentials(ProjectRole::Admin, false));
+        assert!(!can_reveal_db_credentials(ProjectRole::Participant, false));
+        assert!(can_reveal_db_credentials(ProjectRole::Participant, true));
+    }
+}
@@ -0,0 +1,253 @@
+//! Adresse IP du client derrière le reverse proxy.
+//!
+//! Traefik est le pair TCP de toutes les requêtes : l'adresse réelle n'est lue dans
+//! `X-Forwarded-For` / `X-Real-IP` que si le pair appartient à `TRUSTED_PROXY_CIDRS`.
+//! Sinon ces en-têtes sont ignorés, n'importe quel client pouvant les forger.
+
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+use axum::{extract::FromRequestParts, http::{HeaderMap, request::Parts}};
+
+use crate::error::AppError;
+
+/// Plage d'adresses au format CIDR (`10.0.0.0/8`, `fd00::/8`) ; une adresse seule vaut `/32` ou `/128`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr
+{
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpCidr
+{
+    #[must_use]
+    pub fn contains(&self, ip: IpAddr) -> bool
+    {
+        match (self.network, ip.to_canonical())
+        {
+            (IpAddr::V4(network), IpAddr::V4(ip)) =>
+                prefix_matches(u128::from(u32::from(network)), u128::from(u32::from(ip)), self.prefix_len, 32),
+            (IpAddr::V6(network), IpAddr::V6(ip)) =>
+                prefix_matches(u128::from(network), u128::from(ip), self.prefix_len, 128),
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(network: u128, ip: u128, prefix_len: u8, bits: u32) -> bool
+{
+    let host_bits = bits - u32::from(prefix_len);
+    network.checked_shr(host_bits).unwrap_or(0) == ip.checked_shr(host_bits).unwrap_or(0)
+}
+
+impl FromStr for IpCidr
+{
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err>
+    {
+        let (address, prefix) = value.split_once('/').map_or((value, None), |(address, prefix)| (address, Some(prefix)));
+        let network = address.parse::<IpAddr>().map_err(|_| format!("invalid address in '{value}'"))?.to_canonical();
+        let max_len = if network.is_ipv4() { 32 } else { 128 };
+
+        let prefix_len = match prefix
+        {
+            Some(prefix) => prefix.parse::<u8>().ok().filter(|len| *len <= max_len)
+                .ok_or_else(|| format!("invalid prefix length in '{value}'"))?,
+            None => max_len,
+        };
+
+        Ok(Self { network, prefix_len })
+    }
+}
+
+/// Reverse proxies dont les en-têtes de transfert sont crus. Vide par défaut : aucun.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedProxies(Vec<IpCidr>);
+
+impl TrustedProxies
+{
+    #[must_use]
+    pub fn contains(&self, ip: IpAddr) -> bool
+    {
+        self.0.iter().any(|cidr| cidr.contains(ip))
+    }
+}
+
+/// Liste séparée par des virgules, par exemple `172.18.0.0/16,127.0.0.1`.
+impl FromStr for TrustedProxies
+{
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err>
+    {
+        value.split(',')
+            .map(str::trim)
+            .filter(|cidr| !cidr.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Adresse du client, déposée dans les extensions de la requête par `middleware::client_ip`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl std::fmt::Display for ClientIp
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        self.0.fmt(f)
+    }
+}
+
+/// Détermine l'adresse du client à partir du pair TCP et des en-têtes de transfert.
+///
+/// `X-Forwarded-For` est parcouru de droite à gauche en sautant les proxies de confiance :
+/// la première adresse hors de ces plages est le client. Les entrées plus à gauche ont pu
+/// être ajoutées par le client lui-même et ne sont jamais retenues avant elle.
+#[must_use]
+pub fn resolve(peer: IpAddr, headers: &HeaderMap, trusted: &TrustedProxies) -> IpAddr
+{
+    let peer = peer.to_canonical();
+    if !trusted.contains(peer)
+    {
+        return peer;
+    }
+
+    let forwarded: Vec<&str> = headers.get_all("x-forwarded-for").iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+
+    if forwarded.is_empty()
+    {
+        return headers.get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_forwarded_ip(value.trim()))
+            .unwrap_or(peer);
+    }
+
+    let mut client = peer;
+    for entry in forwarded.iter().rev()
+    {
+        let Some(ip) = parse_forwarded_ip(entry) else { break };
+        client = ip;
+        if !trusted.contains(ip)
+        {
+            break;
+        }
+    }
+
+    client
+}
+
+/// Accepte `ip` ou `ip:port` (`[ipv6]:port`), tels qu'écrits par les proxies usuels.
+fn parse_forwarded_ip(value: &str) -> Option<IpAddr>
+{
+    value.parse::<IpAddr>().ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .map(|ip| ip.to_canonical())
+}
+
+impl<S> FromRequestParts<S> for ClientIp where S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection>
+    {
+        parts.extensions.get::<Self>().copied().ok_or_else(||
+        {
+            tracing::error!("The ClientIp extractor was used on a route without the client_ip middleware.");
+            AppError::InternalServerError
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn trusted() -> TrustedProxies
+    {
+        "172.18.0.0/16, 127.0.0.1".parse().unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap
+    {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs
+        {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn ip(value: &str) -> IpAddr
+    {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cidr_parsing_and_matching()
+    {
+        let cidr: IpCidr = "10.1.0.0/16".parse().unwrap();
+        assert!(cidr.contains(ip("10.1.255.3")));
+        assert!(!cidr.contains(ip("10.2.0.1")));
+        assert!(cidr.contains(ip("::ffff:10.1.0.9")));
+
+        assert!("fd00::/8".parse::<IpCidr>().unwrap().contains(ip("fd12::1")));
+        assert!("0.0.0.0/0".parse::<IpCidr>().unwrap().contains(ip("203.0.113.7")));
+        assert!("::/0".parse::<IpCidr>().unwrap().contains(ip("2001:db8::1")));
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("traefik/16".parse::<IpCidr>().is_err());
+        assert_eq!("".parse::<TrustedProxies>().unwrap(), TrustedProxies::default());
+    }
+
+    #[test]
+    fn test_headers_from_untrusted_peer_are_ignored()
+    {
+        let spoofed = headers(&[("x-forwarded-for", "1.2.3.4"), ("x-real-ip", "5.6.7.8")]);
+
+        assert_eq!(resolve(ip("203.0.113.7"), &spoofed, &trusted()), ip("203.0.113.7"));
+        assert_eq!(resolve(ip("203.0.113.7"), &spoofed, &TrustedProxies::default()), ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn test_forwarded_for_from_trusted_proxy()
+    {
+        let forwarded = headers(&[("x-forwarded-for", "198.51.100.4")]);
+        assert_eq!(resolve(ip("172.18.0.2"), &forwarded, &trusted()), ip("198.51.100.4"));
+
+        let real_ip = headers(&[("x-real-ip", "198.51.100.4")]);
+        assert_eq!(resolve(ip("172.18.0.2"), &real_ip, &trusted()), ip("198.51.100.4"));
+
+        assert_eq!(resolve(ip("172.18.0.2"), &HeaderMap::new(), &trusted()), ip("172.18.0.2"));
+    }
+
+    #[test]
+    fn test_client_supplied_forwarded_entries_are_skipped()
+    {
+        // Le client envoie lui-même `X-Forwarded-For: 1.2.3.4`, Traefik y ajoute sa véritable adresse.
+        let forwarded = headers(&[("x-forwarded-for", "1.2.3.4, 198.51.100.4, 127.0.0.1")]);
+        assert_eq!(resolve(ip("172.18.0.2"), &forwarded, &trusted()), ip("198.51.100.4"));
+
+        let split = headers(&[("x-forwarded-for", "1.2.3.4"), ("x-forwarded-for", "198.51.100.4")]);
+        assert_eq!(resolve(ip("172.18.0.2"), &split, &trusted()), ip("198.51.100.4"));
+    }
+
+    #[test]
+    fn test_garbage_forwarded_entry_stops_the_walk()
+    {
+        let forwarded = headers(&[("x-forwarded-for", "1.2.3.4, not-an-ip, 127.0.0.1")]);
+        assert_eq!(resolve(ip("172.18.0.2"), &forwarded, &trusted()), ip("127.0.0.1"));
+
+        let with_port = headers(&[("x-forwarded-for", "[2001:db8::1]:4711")]);
+        assert_eq!(resolve(ip("172.18.0.2"), &with_port, &trusted()), ip("2001:db8::1"));
+    }
+}
@@ -1,7 +1,7 @@
-use crate::error::ConfigError;
-use serde::Deserialize;
+use crate::{client_ip::TrustedProxies, error::ConfigError, units::{CpuQuota, MemoryMb, Seconds}};
+use serde::{Deserialize, Serialize};
 use base64::prelude::*;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Deserialize, Clone)]
 pub struct Config
@@ -18,20 +18,116 @@ pub struct Config
     pub cas_validation_url: String,
     pub app_prefix: String,
     pub app_domain_suffix: String,
+    /// Résolveur DNS-over-HTTPS (format JSON) utilisé pour vérifier les domaines personnalisés.
+    pub dns_resolver_url: String,
     pub build_base_image: String,
     pub github_app_id: String,
     pub github_private_key: Vec<u8>,
     pub docker_network: String,
     pub traefik_entrypoint: String,
     pub traefik_cert_resolver: String,
-    pub container_memory_mb: i64,
-    pub container_cpu_quota: i64,
+    /// Limite mémoire des conteneurs de projet ; convertir avec [`MemoryMb::to_bytes`].
+    pub container_memory_mb: MemoryMb,
+    pub container_cpu_quota: CpuQuota,
     pub grype_enabled: bool,
     pub grype_fail_on_severity: String,
     pub db_max_connections: u32,
     pub timeout_normal: u64,
     pub timeout_long: u64,
+    pub timeout_deploy: u64,
+    pub timeout_rebuild: u64,
+    pub timeout_image_update: u64,
+    pub timeout_env_update: u64,
     pub admin_logins: HashSet<String>,
+    /// Routeurs Traefik statiques (file provider) dont les projets ne peuvent pas prendre le nom.
+    pub traefik_reserved_routers: HashSet<String>,
+    /// Hôtes connus (GitHub, GitLab) dispensés de la vérification d'adresse des requêtes sortantes (`safe_http`).
+    pub safe_http_allowed_hosts: HashSet<String>,
+    pub expose_forbidden: bool,
+    pub log_max_field_length: usize,
+    /// Flux SSE ouverts simultanément au maximum, tous utilisateurs confondus.
+    pub max_sse_connections: usize,
+    /// Types d'événements conservés lorsqu'ils sont émis sans abonné ; vide : aucun.
+    pub missed_event_types: HashSet<String>,
+    /// Événements conservés par projet ou par utilisateur, les plus anciens étant supprimés au-delà.
+    pub missed_events_max: i64,
+    /// Nombre maximal de lignes demandées via `tail` sur l'endpoint des logs d'un projet.
+    pub log_max_tail: u32,
+    /// Délai avant le retour automatique au filtre de logs du démarrage après une modification à chaud.
+    pub log_filter_revert_minutes: u64,
+    /// Reverse proxies autorisés à transmettre l'adresse du client (`X-Forwarded-For`, `X-Real-IP`).
+    #[serde(skip)]
+    pub trusted_proxies: TrustedProxies,
+    pub db_credentials_for_participants: bool,
+    /// Ajouter un participant lui envoie une invitation à accepter au lieu de lui donner accès directement.
+    pub participant_invitations_enabled: bool,
+    pub participant_invitation_ttl_days: i64,
+    /// Participants au plus par projet, invitations acceptées comprises.
+    pub participants_max_per_project: i64,
+    pub mariadb_max_user_connections: u32,
+    pub mariadb_max_queries_per_hour: u32,
+    pub backup_enabled: bool,
+    pub backup_hour_utc: u8,
+    pub backup_dir: String,
+    pub backup_retention: i64,
+    /// Maintient les images de base des builds présentes sur le démon.
+    pub base_image_warm_enabled: bool,
+    /// Intervalle entre deux téléchargements des images de base.
+    pub base_image_refresh_minutes: u64,
+    /// Répertoire des espaces de travail des builds GitHub.
+    pub build_workdir: String,
+    /// Âge au-delà duquel un espace de travail de build est supprimé par le balayage.
+    pub build_workspace_max_age_minutes: u64,
+    /// Espace libre minimal dans `build_workdir` avant de cloner un dépôt.
+    pub build_workdir_min_free_mb: u64,
+    /// Taille maximale, décompressée, d'une archive de projet importée.
+    pub project_archive_max_mb: u64,
+    /// Taille maximale d'une archive de site statique envoyée, puis une fois décompressée.
+    pub upload_max_mb: u64,
+    pub upload_max_unpacked_mb: u64,
+    /// Profils de ressources (ulimits, pids, `/tmp`) des conteneurs, dont toujours [`DEFAULT_RESOURCE_PROFILE`].
+    pub resource_profiles: BTreeMap<String, ResourceProfile>,
+    pub jobs_max_per_project: i64,
+    pub jobs_min_interval_minutes: u64,
+    pub jobs_timeout_seconds: Seconds,
+    pub max_stop_grace_seconds: i32,
+    /// Vérifications de santé d'un nouveau conteneur avant de le déclarer en échec, et délai initial
+    /// entre deux vérifications, doublé à chaque tentative. Un projet peut surcharger les deux.
+    pub health_check_max_attempts: u32,
+    pub health_check_interval_ms: u64,
+    /// Délai pendant lequel un arrêt de conteneur suivi d'un démarrage est publié comme un redémarrage.
+    pub container_restart_window_seconds: u64,
+    pub hook_max_calls_per_hour: usize,
+    /// Délai minimal entre deux réinitialisations d'une même base.
+    pub database_reset_cooldown_seconds: u64,
+    /// Durée maximale d'un dump (téléchargement, sauvegarde) ou d'une restauration, au-delà de laquelle
+    /// mysqldump ou mysql est arrêté.
+    pub database_dump_timeout_seconds: u64,
+    pub deployment_artifacts_ttl_minutes: u64,
+    /// Âge minimal d'une image non référencée avant sa suppression par le ramasse-miettes.
+    pub image_gc_max_age_hours: i64,
+    pub image_gc_interval_secs: u64,
+    /// Fuseau horaire injecté dans `TZ` pour les projets qui n'en définissent pas.
+    pub default_timezone: Option<String>,
+    /// Nombre de jours sans connexion au-delà duquel un propriétaire est considéré comme inactif.
+    pub owner_inactivity_days: i32,
+    /// Délai entre le signalement d'un projet pour nettoyage et son archivage.
+    pub cleanup_grace_days: i32,
+    /// Durée de conservation de l'historique d'utilisation (échantillons et transitions démarré/arrêté).
+    pub usage_retention_days: i32,
+    /// Intervalle entre deux échantillons de l'historique des métriques de la plateforme, et leur conservation.
+    pub metrics_history_interval_minutes: u64,
+    pub metrics_history_retention_days: i32,
+    /// Heures d'inactivité après lesquelles le conteneur d'un projet est arrêté ; 0 désactive l'arrêt automatique.
+    pub idle_stop_after_hours: u64,
+    /// CPU (en % d'un cœur) sous lequel un projet est considéré comme inactif.
+    pub idle_cpu_threshold_percent: f64,
+    /// Image déployée par le selftest d'administration ; elle doit servir une page HTTP sur le port 80.
+    pub selftest_image: String,
+    #[cfg(feature = "object_storage")]
+    pub object_storage: Option<ObjectStorageConfig>,
+    /// Serveur d'envoi des notifications par e-mail ; les e-mails sont désactivés si absent.
+    pub smtp: Option<SmtpConfig>,
     pub encryption_key: Vec<u8>,
 }
 
@@ -79,6 +175,7 @@ impl Config
 
         let app_prefix = std::env::var("APP_PREFIX").map_err(|_| ConfigError::Missing("APP_PREFIX".to_string()))?;
         let app_domain_suffix = std::env::var("APP_DOMAIN_SUFFIX").map_err(|_| ConfigError::Missing("APP_DOMAIN_SUFFIX".to_string()))?;
+        let dns_resolver_url: String = optional_env("DNS_RESOLVER_URL", "https://cloudflare-dns.com/dns-query".to_string())?;
 
         let build_base_image = std::env::var("BUILD_BASE_IMAGE")
             .map_err(|_| ConfigError::Missing("BUILD_BASE_IMAGE".to_string()))?;
@@ -108,13 +205,13 @@ impl Config
         let grype_fail_on_severity = std::env::var("GRYPE_FAIL_ON_SEVERITY")
             .map_err(|_| ConfigError::Missing("GRYPE_FAIL_ON_SEVERITY".to_string()))?;
 
-        let container_memory_mb = std::env::var("DOCKER_CONTAINER_MEMORY_MB")
+        let container_memory_mb = MemoryMb::new(std::env::var("DOCKER_CONTAINER_MEMORY_MB")
             .map_err(|_| ConfigError::Missing("DOCKER_CONTAINER_MEMORY_MB".to_string()))?
-            .parse().map_err(|_| ConfigError::Invalid("DOCKER_CONTAINER_MEMORY_MB".to_string(), "Invalid number".to_string()))?;
+            .parse().map_err(|_| ConfigError::Invalid("DOCKER_CONTAINER_MEMORY_MB".to_string(), "Invalid number".to_string()))?);
 
-        let container_cpu_quota = std::env::var("DOCKER_CONTAINER_CPU_QUOTA")
+        let container_cpu_quota = CpuQuota::new(std::env::var("DOCKER_CONTAINER_CPU_QUOTA")
             .map_err(|_| ConfigError::Missing("DOCKER_CONTAINER_CPU_QUOTA".to_string()))?
-            .parse().map_err(|_| ConfigError::Invalid("DOCKER_CONTAINER_CPU_QUOTA".to_string(), "Invalid number".to_string()))?;
+            .parse().map_err(|_| ConfigError::Invalid("DOCKER_CONTAINER_CPU_QUOTA".to_string(), "Invalid number".to_string()))?);
 
         let db_max_connections = std::env::var("DB_MAX_CONNECTIONS")
             .map_err(|_| ConfigError::Missing("DB_MAX_CONNECTIONS".to_string()))?
@@ -128,6 +225,11 @@ impl Config
             .map_err(|_| ConfigError::Missing("TIMEOUT_SECONDS_LONG".to_string()))?
             .parse().map_err(|_| ConfigError::Invalid("TIMEOUT_SECONDS_LONG".to_string(), "Invalid number".to_string()))?;
 
+        let timeout_deploy = optional_env("TIMEOUT_DEPLOY", timeout_long)?;
+        let timeout_rebuild = optional_env("TIMEOUT_REBUILD", timeout_long)?;
+        let timeout_image_update = optional_env("TIMEOUT_IMAGE_UPDATE", timeout_long)?;
+        let timeout_env_update = optional_env("TIMEOUT_ENV_UPDATE", timeout_long)?;
+
         let admin_logins = std::env::var("APP_ADMINS")
             .map_err(|_| ConfigError::Missing("APP_ADMINS".to_string()))?
             .split(',')
@@ -135,6 +237,236 @@ impl Config
             .filter(|s| !s.is_empty())
             .collect::<HashSet<String>>();
 
+        let traefik_reserved_routers = std::env::var("TRAEFIK_RESERVED_ROUTERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect::<HashSet<String>>();
+
+        let safe_http_allowed_hosts = std::env::var("SAFE_HTTP_ALLOWED_HOSTS")
+            .unwrap_or_else(|_| "api.github.com,github.com,gitlab.com".to_string())
+            .split(',')
+            .map(|s| s.trim().trim_end_matches('.').to_ascii_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect::<HashSet<String>>();
+
+        let expose_forbidden = optional_env("EXPOSE_FORBIDDEN", false)?;
+
+        let missed_event_types = std::env::var("MISSED_EVENT_TYPES")
+            .unwrap_or_else(|_| "deployment,system".to_string())
+            .split(',')
+            .map(|s| s.trim().to_ascii_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect::<HashSet<String>>();
+        if let Some(unknown) = missed_event_types.iter().find(|event_type| !crate::sse::manager::PERSISTABLE_EVENT_TYPES.contains(&event_type.as_str()))
+        {
+            return Err(ConfigError::Invalid("MISSED_EVENT_TYPES".to_string(), unknown.clone()));
+        }
+        let missed_events_max: i64 = optional_env("MISSED_EVENTS_MAX", 50)?;
+        if missed_events_max < 1
+        {
+            return Err(ConfigError::Invalid("MISSED_EVENTS_MAX".to_string(), missed_events_max.to_string()));
+        }
+
+        let log_max_field_length = optional_env("LOG_MAX_FIELD_LENGTH", crate::logging::DEFAULT_MAX_LOG_FIELD_LENGTH)?;
+
+        let log_filter_revert_minutes: u64 = optional_env("LOG_FILTER_REVERT_MINUTES", 30)?;
+        if log_filter_revert_minutes < 1
+        {
+            return Err(ConfigError::Invalid("LOG_FILTER_REVERT_MINUTES".to_string(), log_filter_revert_minutes.to_string()));
+        }
+
+        let max_sse_connections: usize = optional_env("MAX_SSE_CONNECTIONS", crate::sse::manager::DEFAULT_MAX_CONNECTIONS)?;
+        if max_sse_connections < 1
+        {
+            return Err(ConfigError::Invalid("MAX_SSE_CONNECTIONS".to_string(), max_sse_connections.to_string()));
+        }
+
+        let log_max_tail: u32 = optional_env("LOG_MAX_TAIL", 5000)?;
+        if log_max_tail < 1
+        {
+            return Err(ConfigError::Invalid("LOG_MAX_TAIL".to_string(), log_max_tail.to_string()));
+        }
+
+        let trusted_proxies: TrustedProxies = optional_env("TRUSTED_PROXY_CIDRS", TrustedProxies::default())?;
+
+        let db_credentials_for_participants = optional_env("DB_CREDENTIALS_FOR_PARTICIPANTS", false)?;
+
+        let participant_invitations_enabled = optional_env("PARTICIPANT_INVITATIONS_ENABLED", false)?;
+        let participant_invitation_ttl_days: i64 = optional_env("PARTICIPANT_INVITATION_TTL_DAYS", 7)?;
+        if participant_invitation_ttl_days < 1
+        {
+            return Err(ConfigError::Invalid("PARTICIPANT_INVITATION_TTL_DAYS".to_string(), participant_invitation_ttl_days.to_string()));
+        }
+        let participants_max_per_project: i64 = optional_env("PARTICIPANTS_MAX_PER_PROJECT", 20)?;
+        if participants_max_per_project < 0
+        {
+            return Err(ConfigError::Invalid("PARTICIPANTS_MAX_PER_PROJECT".to_string(), participants_max_per_project.to_string()));
+        }
+
+        // Colonnes INTEGER côté PostgreSQL : au-delà de i32::MAX, la valeur ne pourrait pas être enregistrée.
+        let mariadb_max_user_connections: u32 = optional_env("MARIADB_MAX_USER_CONNECTIONS", 10)?;
+        if i32::try_from(mariadb_max_user_connections).is_err()
+        {
+            return Err(ConfigError::Invalid("MARIADB_MAX_USER_CONNECTIONS".to_string(), mariadb_max_user_connections.to_string()));
+        }
+        let mariadb_max_queries_per_hour: u32 = optional_env("MARIADB_MAX_QUERIES_PER_HOUR", 0)?;
+        if i32::try_from(mariadb_max_queries_per_hour).is_err()
+        {
+            return Err(ConfigError::Invalid("MARIADB_MAX_QUERIES_PER_HOUR".to_string(), mariadb_max_queries_per_hour.to_string()));
+        }
+
+        let backup_enabled = optional_env("BACKUP_ENABLED", false)?;
+        let backup_hour_utc: u8 = optional_env("BACKUP_HOUR_UTC", 3)?;
+        if backup_hour_utc > 23
+        {
+            return Err(ConfigError::Invalid("BACKUP_HOUR_UTC".to_string(), backup_hour_utc.to_string()));
+        }
+        let backup_dir = optional_env("BACKUP_DIR", "/var/lib/hangar/backups".to_string())?;
+        let backup_retention: i64 = optional_env("BACKUP_RETENTION", 7)?;
+        if backup_retention < 1
+        {
+            return Err(ConfigError::Invalid("BACKUP_RETENTION".to_string(), backup_retention.to_string()));
+        }
+
+        let base_image_warm_enabled = optional_env("BASE_IMAGE_WARM_ENABLED", true)?;
+        let base_image_refresh_minutes: u64 = optional_env("BASE_IMAGE_REFRESH_MINUTES", 360)?;
+        if base_image_refresh_minutes == 0
+        {
+            return Err(ConfigError::Invalid("BASE_IMAGE_REFRESH_MINUTES".to_string(), base_image_refresh_minutes.to_string()));
+        }
+
+        let build_workdir = optional_env("BUILD_WORKDIR", "/tmp/hangar-builds".to_string())?;
+        if build_workdir.trim().is_empty()
+        {
+            return Err(ConfigError::Invalid("BUILD_WORKDIR".to_string(), build_workdir));
+        }
+        let build_workspace_max_age_minutes: u64 = optional_env("BUILD_WORKSPACE_MAX_AGE_MINUTES", 180)?;
+        if build_workspace_max_age_minutes == 0
+        {
+            return Err(ConfigError::Invalid("BUILD_WORKSPACE_MAX_AGE_MINUTES".to_string(), build_workspace_max_age_minutes.to_string()));
+        }
+        let build_workdir_min_free_mb: u64 = optional_env("BUILD_WORKDIR_MIN_FREE_MB", 1024)?;
+        let project_archive_max_mb: u64 = optional_env("PROJECT_ARCHIVE_MAX_MB", 1024)?;
+        let resource_profiles = resource_profiles_from_env()?;
+        if project_archive_max_mb == 0
+        {
+            return Err(ConfigError::Invalid("PROJECT_ARCHIVE_MAX_MB".to_string(), project_archive_max_mb.to_string()));
+        }
+        let upload_max_mb: u64 = optional_env("UPLOAD_MAX_MB", 50)?;
+        if upload_max_mb == 0
+        {
+            return Err(ConfigError::Invalid("UPLOAD_MAX_MB".to_string(), upload_max_mb.to_string()));
+        }
+        let upload_max_unpacked_mb: u64 = optional_env("UPLOAD_MAX_UNPACKED_MB", 200)?;
+        if upload_max_unpacked_mb == 0
+        {
+            return Err(ConfigError::Invalid("UPLOAD_MAX_UNPACKED_MB".to_string(), upload_max_unpacked_mb.to_string()));
+        }
+
+        let jobs_max_per_project: i64 = optional_env("JOBS_MAX_PER_PROJECT", 5)?;
+        let jobs_min_interval_minutes: u64 = optional_env("JOBS_MIN_INTERVAL_MINUTES", 10)?;
+        let jobs_timeout_seconds: u64 = optional_env("JOBS_TIMEOUT_SECONDS", 300)?;
+        if jobs_timeout_seconds == 0
+        {
+            return Err(ConfigError::Invalid("JOBS_TIMEOUT_SECONDS".to_string(), jobs_timeout_seconds.to_string()));
+        }
+        let jobs_timeout_seconds = Seconds::new(jobs_timeout_seconds);
+
+        let max_stop_grace_seconds: i32 = optional_env("MAX_STOP_GRACE_SECONDS", 120)?;
+        if max_stop_grace_seconds < 1
+        {
+            return Err(ConfigError::Invalid("MAX_STOP_GRACE_SECONDS".to_string(), max_stop_grace_seconds.to_string()));
+        }
+
+        let health_check_max_attempts: u32 = optional_env("HEALTH_CHECK_MAX_ATTEMPTS", 10)?;
+        if health_check_max_attempts == 0
+        {
+            return Err(ConfigError::Invalid("HEALTH_CHECK_MAX_ATTEMPTS".to_string(), health_check_max_attempts.to_string()));
+        }
+        let health_check_interval_ms: u64 = optional_env("HEALTH_CHECK_INTERVAL_MS", 1000)?;
+        if health_check_interval_ms == 0
+        {
+            return Err(ConfigError::Invalid("HEALTH_CHECK_INTERVAL_MS".to_string(), health_check_interval_ms.to_string()));
+        }
+
+        let container_restart_window_seconds: u64 = optional_env("CONTAINER_RESTART_WINDOW_SECONDS", 5)?;
+        let hook_max_calls_per_hour: usize = optional_env("HOOK_MAX_CALLS_PER_HOUR", 20)?;
+        let database_reset_cooldown_seconds: u64 = optional_env("DATABASE_RESET_COOLDOWN_SECONDS", 300)?;
+        let database_dump_timeout_seconds: u64 = optional_env("DATABASE_DUMP_TIMEOUT_SECONDS", 1800)?;
+        let deployment_artifacts_ttl_minutes: u64 = optional_env("DEPLOYMENT_ARTIFACTS_TTL_MINUTES", 60)?;
+
+        let image_gc_max_age_hours: i64 = optional_env("IMAGE_GC_MAX_AGE_HOURS", 24)?;
+        if image_gc_max_age_hours < 1
+        {
+            return Err(ConfigError::Invalid("IMAGE_GC_MAX_AGE_HOURS".to_string(), image_gc_max_age_hours.to_string()));
+        }
+        let image_gc_interval_secs: u64 = optional_env("IMAGE_GC_INTERVAL_SECS", 3600)?;
+        if image_gc_interval_secs < 60
+        {
+            return Err(ConfigError::Invalid("IMAGE_GC_INTERVAL_SECS".to_string(), image_gc_interval_secs.to_string()));
+        }
+
+        let default_timezone = std::env::var("DEFAULT_TIMEZONE").ok().filter(|tz| !tz.is_empty());
+        if let Some(tz) = &default_timezone
+            && !crate::timezones::is_known_timezone(tz)
+        {
+            return Err(ConfigError::Invalid("DEFAULT_TIMEZONE".to_string(), tz.clone()));
+        }
+
+        let owner_inactivity_days: i32 = optional_env("OWNER_INACTIVITY_DAYS", 365)?;
+        if owner_inactivity_days < 1
+        {
+            return Err(ConfigError::Invalid("OWNER_INACTIVITY_DAYS".to_string(), owner_inactivity_days.to_string()));
+        }
+
+        let cleanup_grace_days: i32 = optional_env("CLEANUP_GRACE_DAYS", 30)?;
+        if cleanup_grace_days < 1
+        {
+            return Err(ConfigError::Invalid("CLEANUP_GRACE_DAYS".to_string(), cleanup_grace_days.to_string()));
+        }
+
+        let usage_retention_days: i32 = optional_env("USAGE_RETENTION_DAYS", 400)?;
+        if usage_retention_days < 1
+        {
+            return Err(ConfigError::Invalid("USAGE_RETENTION_DAYS".to_string(), usage_retention_days.to_string()));
+        }
+
+        let metrics_history_interval_minutes: u64 = optional_env("METRICS_HISTORY_INTERVAL_MINUTES", 5)?;
+        // Au-delà d'un jour, l'historique n'a plus la granularité d'un graphe.
+        if metrics_history_interval_minutes == 0 || metrics_history_interval_minutes > 24 * 60
+        {
+            return Err(ConfigError::Invalid("METRICS_HISTORY_INTERVAL_MINUTES".to_string(), metrics_history_interval_minutes.to_string()));
+        }
+        let metrics_history_retention_days: i32 = optional_env("METRICS_HISTORY_RETENTION_DAYS", 30)?;
+        if metrics_history_retention_days < 1
+        {
+            return Err(ConfigError::Invalid("METRICS_HISTORY_RETENTION_DAYS".to_string(), metrics_history_retention_days.to_string()));
+        }
+
+        let idle_stop_after_hours: u64 = optional_env("IDLE_STOP_AFTER_HOURS", 0)?;
+        // Au-delà d'un an, l'arrêt automatique n'a plus de sens.
+        if idle_stop_after_hours > 24 * 366
+        {
+            return Err(ConfigError::Invalid("IDLE_STOP_AFTER_HOURS".to_string(), idle_stop_after_hours.to_string()));
+        }
+        let idle_cpu_threshold_percent: f64 = optional_env("IDLE_CPU_THRESHOLD_PERCENT", 1.0)?;
+        if !(0.0..=100.0).contains(&idle_cpu_threshold_percent)
+        {
+            return Err(ConfigError::Invalid("IDLE_CPU_THRESHOLD_PERCENT".to_string(), idle_cpu_threshold_percent.to_string()));
+        }
+
+        let selftest_image: String = optional_env("SELFTEST_IMAGE", "traefik/whoami:latest".to_string())?;
+        if selftest_image.trim().is_empty()
+        {
+            return Err(ConfigError::Invalid("SELFTEST_IMAGE".to_string(), selftest_image));
+        }
+
+        #[cfg(feature = "object_storage")]
+        let object_storage = ObjectStorageConfig::from_env()?;
+        let smtp = SmtpConfig::from_env()?;
+
         let encryption_key_hex = std::env::var("APP_ENCRYPTION_KEY")
             .map_err(|_| ConfigError::Missing("APP_ENCRYPTION_KEY".to_string()))?;
 
@@ -167,6 +499,7 @@ impl Config
             cas_validation_url,
             app_prefix,
             app_domain_suffix,
+            dns_resolver_url,
             build_base_image,
             github_app_id,
             github_private_key,
@@ -180,8 +513,328 @@ impl Config
             db_max_connections,
             timeout_normal,
             timeout_long,
+            timeout_deploy,
+            timeout_rebuild,
+            timeout_image_update,
+            timeout_env_update,
             admin_logins,
+            traefik_reserved_routers,
+            safe_http_allowed_hosts,
+            expose_forbidden,
+            log_max_field_length,
+            max_sse_connections,
+            missed_event_types,
+            missed_events_max,
+            log_max_tail,
+            log_filter_revert_minutes,
+            trusted_proxies,
+            db_credentials_for_participants,
+            participant_invitations_enabled,
+            participant_invitation_ttl_days,
+            participants_max_per_project,
+            mariadb_max_user_connections,
+            mariadb_max_queries_per_hour,
+            backup_enabled,
+            backup_hour_utc,
+            backup_dir,
+            backup_retention,
+            base_image_warm_enabled,
+            base_image_refresh_minutes,
+            build_workdir,
+            build_workspace_max_age_minutes,
+            build_workdir_min_free_mb,
+            project_archive_max_mb,
+            upload_max_mb,
+            upload_max_unpacked_mb,
+            resource_profiles,
+            jobs_max_per_project,
+            jobs_min_interval_minutes,
+            jobs_timeout_seconds,
+            max_stop_grace_seconds,
+            health_check_max_attempts,
+            health_check_interval_ms,
+            container_restart_window_seconds,
+            hook_max_calls_per_hour,
+            database_reset_cooldown_seconds,
+            database_dump_timeout_seconds,
+            deployment_artifacts_ttl_minutes,
+            image_gc_max_age_hours,
+            image_gc_interval_secs,
+            default_timezone,
+            owner_inactivity_days,
+            cleanup_grace_days,
+            usage_retention_days,
+            metrics_history_interval_minutes,
+            metrics_history_retention_days,
+            idle_stop_after_hours,
+            idle_cpu_threshold_percent,
+            selftest_image,
+            #[cfg(feature = "object_storage")]
+            object_storage,
+            smtp,
             encryption_key
         })
     }
-}
\ No newline at end of file
+}
+
+impl Config
+{
+    /// Profil de ressources `name`, ou le profil par défaut s'il n'est plus défini.
+    #[must_use]
+    pub fn resource_profile(&self, name: &str) -> &ResourceProfile
+    {
+        self.resource_profiles.get(name)
+            .or_else(|| self.resource_profiles.get(DEFAULT_RESOURCE_PROFILE))
+            .unwrap_or(&DEFAULT_PROFILE)
+    }
+}
+
+/// Profil appliqué aux projets auxquels un administrateur n'en a pas attribué d'autre.
+pub const DEFAULT_RESOURCE_PROFILE: &str = "default";
+
+/// Maxima de la plateforme pour les profils de ressources.
+const MAX_NOFILE: i64 = 65_536;
+const MAX_NPROC: i64 = 8192;
+const MAX_PIDS: i64 = 8192;
+const MAX_TMP_SIZE_MB: u64 = 2048;
+
+const DEFAULT_PROFILE: ResourceProfile = ResourceProfile
+{
+    nofile_soft: 1024,
+    nofile_hard: 2048,
+    nproc_soft: 512,
+    nproc_hard: 1024,
+    pids_limit: 1024,
+    tmp_size_mb: 100,
+};
+
+/// Limites système d'un conteneur de projet. Un champ absent de la configuration prend la valeur du profil par défaut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResourceProfile
+{
+    pub nofile_soft: i64,
+    pub nofile_hard: i64,
+    pub nproc_soft: i64,
+    pub nproc_hard: i64,
+    pub pids_limit: i64,
+    /// Taille du tmpfs monté sur `/tmp`.
+    pub tmp_size_mb: u64,
+}
+
+impl Default for ResourceProfile
+{
+    fn default() -> Self
+    {
+        DEFAULT_PROFILE
+    }
+}
+
+impl ResourceProfile
+{
+    /// Vérifie que chaque limite souple reste sous sa limite dure, et toutes sous les maxima de la plateforme.
+    fn validate(&self) -> Result<(), String>
+    {
+        let ulimits = [("nofile", self.nofile_soft, self.nofile_hard, MAX_NOFILE), ("nproc", self.nproc_soft, self.nproc_hard, MAX_NPROC)];
+        for (name, soft, hard, max) in ulimits
+        {
+            if soft < 1 || soft > hard
+            {
+                return Err(format!("{name}: soft limit {soft} must be between 1 and the hard limit {hard}"));
+            }
+            if hard > max
+            {
+                return Err(format!("{name}: hard limit {hard} exceeds the platform maximum {max}"));
+            }
+        }
+        if !(1..=MAX_PIDS).contains(&self.pids_limit)
+        {
+            return Err(format!("pids_limit {} must be between 1 and {MAX_PIDS}", self.pids_limit));
+        }
+        if !(1..=MAX_TMP_SIZE_MB).contains(&self.tmp_size_mb)
+        {
+            return Err(format!("tmp_size_mb {} must be between 1 and {MAX_TMP_SIZE_MB}", self.tmp_size_mb));
+        }
+        Ok(())
+    }
+}
+
+/// `RESOURCE_PROFILES` : objet JSON `{"nom": {"nofile_soft": ..., ...}}`. Le profil `default` reprend
+/// les limites historiques s'il n'est pas redéfini.
+fn resource_profiles_from_env() -> Result<BTreeMap<String, ResourceProfile>, ConfigError>
+{
+    let mut profiles = match std::env::var("RESOURCE_PROFILES")
+    {
+        Ok(value) if !value.trim().is_empty() => serde_json::from_str::<BTreeMap<String, ResourceProfile>>(&value)
+            .map_err(|e| ConfigError::Invalid("RESOURCE_PROFILES".to_string(), e.to_string()))?,
+        _ => BTreeMap::new(),
+    };
+    profiles.entry(DEFAULT_RESOURCE_PROFILE.to_string()).or_insert(DEFAULT_PROFILE);
+
+    for (name, profile) in &profiles
+    {
+        profile.validate().map_err(|reason| ConfigError::Invalid("RESOURCE_PROFILES".to_string(), format!("{name}: {reason}")))?;
+    }
+    Ok(profiles)
+}
+
+/// Accès administrateur au serveur MinIO utilisé pour les buckets des projets.
+#[cfg(feature = "object_storage")]
+#[derive(Deserialize, Clone, Debug)]
+pub struct ObjectStorageConfig
+{
+    /// URL utilisée par le backend pour les API S3 et d'administration.
+    pub admin_url: String,
+    pub access_key: String,
+    pub secret_key: String,
+    pub region: String,
+    /// URL injectée dans les conteneurs (`HANGAR_S3_ENDPOINT`).
+    pub public_endpoint: String,
+}
+
+#[cfg(feature = "object_storage")]
+impl ObjectStorageConfig
+{
+    /// Le stockage objet est désactivé si `MINIO_URL` n'est pas défini.
+    fn from_env() -> Result<Option<Self>, ConfigError>
+    {
+        let Some(admin_url) = std::env::var("MINIO_URL").ok().filter(|url| !url.is_empty()) else
+        {
+            return Ok(None);
+        };
+
+        let access_key = std::env::var("MINIO_ADMIN_ACCESS_KEY")
+            .map_err(|_| ConfigError::Missing("MINIO_ADMIN_ACCESS_KEY".to_string()))?;
+        let secret_key = std::env::var("MINIO_ADMIN_SECRET_KEY")
+            .map_err(|_| ConfigError::Missing("MINIO_ADMIN_SECRET_KEY".to_string()))?;
+        let region = optional_env("MINIO_REGION", "us-east-1".to_string())?;
+        let public_endpoint = std::env::var("MINIO_PUBLIC_ENDPOINT")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| admin_url.clone());
+
+        Ok(Some(Self { admin_url, access_key, secret_key, region, public_endpoint }))
+    }
+}
+
+/// Chiffrement de la connexion au serveur SMTP.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmtpTls
+{
+    /// Connexion en clair puis passage en TLS (port 587).
+    StartTls,
+    /// TLS dès la connexion (port 465).
+    Tls,
+    /// Aucun chiffrement, pour un relais local uniquement.
+    None,
+}
+
+impl std::str::FromStr for SmtpTls
+{
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err>
+    {
+        match value
+        {
+            "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Tls),
+            "none" => Ok(Self::None),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Serveur SMTP utilisé pour les notifications par e-mail.
+#[derive(Deserialize, Clone)]
+pub struct SmtpConfig
+{
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    /// Identifiants, si le serveur exige une authentification.
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Expéditeur des e-mails (`Hangar <hangar@garageisep.com>`).
+    pub from: String,
+    /// Nombre maximal d'e-mails envoyés à un même utilisateur par heure.
+    pub max_emails_per_hour: usize,
+}
+
+impl SmtpConfig
+{
+    /// Les e-mails sont désactivés si `SMTP_HOST` n'est pas défini.
+    fn from_env() -> Result<Option<Self>, ConfigError>
+    {
+        let Some(host) = std::env::var("SMTP_HOST").ok().filter(|host| !host.is_empty()) else
+        {
+            return Ok(None);
+        };
+
+        let tls: SmtpTls = optional_env("SMTP_TLS", SmtpTls::StartTls)?;
+        let default_port = match tls
+        {
+            SmtpTls::StartTls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        };
+        let port = optional_env("SMTP_PORT", default_port)?;
+
+        let username = std::env::var("SMTP_USERNAME").ok().filter(|username| !username.is_empty());
+        let password = std::env::var("SMTP_PASSWORD").ok().filter(|password| !password.is_empty());
+        if username.is_some() != password.is_some()
+        {
+            let missing = if username.is_some() { "SMTP_PASSWORD" } else { "SMTP_USERNAME" };
+            return Err(ConfigError::Missing(missing.to_string()));
+        }
+
+        let from = std::env::var("SMTP_FROM").map_err(|_| ConfigError::Missing("SMTP_FROM".to_string()))?;
+        if from.parse::<lettre::message::Mailbox>().is_err()
+        {
+            return Err(ConfigError::Invalid("SMTP_FROM".to_string(), from));
+        }
+
+        let max_emails_per_hour: usize = optional_env("SMTP_MAX_EMAILS_PER_HOUR", 10)?;
+        if max_emails_per_hour < 1
+        {
+            return Err(ConfigError::Invalid("SMTP_MAX_EMAILS_PER_HOUR".to_string(), max_emails_per_hour.to_string()));
+        }
+
+        Ok(Some(Self { host, port, tls, username, password, from, max_emails_per_hour }))
+    }
+}
+
+/// Lit une variable d'environnement optionnelle, en retombant sur `default` si elle est absente.
+fn optional_env<T: std::str::FromStr>(name: &str, default: T) -> Result<T, ConfigError>
+{
+    match std::env::var(name)
+    {
+        Ok(value) => value.parse::<T>().map_err(|_| ConfigError::Invalid(name.to_string(), value)),
+        Err(_) => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_profile_validation()
+    {
+        assert!(DEFAULT_PROFILE.validate().is_ok());
+        assert!(ResourceProfile { nofile_soft: 4096, nofile_hard: 2048, ..DEFAULT_PROFILE }.validate().is_err());
+        assert!(ResourceProfile { nproc_soft: 0, ..DEFAULT_PROFILE }.validate().is_err());
+        assert!(ResourceProfile { nofile_soft: 4096, nofile_hard: MAX_NOFILE + 1, ..DEFAULT_PROFILE }.validate().is_err());
+        assert!(ResourceProfile { pids_limit: MAX_PIDS + 1, ..DEFAULT_PROFILE }.validate().is_err());
+        assert!(ResourceProfile { tmp_size_mb: 0, ..DEFAULT_PROFILE }.validate().is_err());
+    }
+
+    #[test]
+    fn test_partial_profile_inherits_defaults()
+    {
+        let profiles: BTreeMap<String, ResourceProfile> = serde_json::from_str(r#"{"node": {"nofile_soft": 8192, "nofile_hard": 16384}}"#).unwrap();
+
+        assert_eq!(profiles["node"], ResourceProfile { nofile_soft: 8192, nofile_hard: 16384, ..DEFAULT_PROFILE });
+        assert!(serde_json::from_str::<ResourceProfile>(r#"{"nofile": 1}"#).is_err());
+    }
+}
@@ -0,0 +1,322 @@
+//! Expressions cron à 5 champs (`minute heure jour-du-mois mois jour-de-la-semaine`), évaluées en UTC.
+//!
+//! Chaque champ accepte `*`, une valeur, un intervalle `a-b`, un pas `*/n` ou `a-b/n`,
+//! et des listes séparées par des virgules. Le dimanche vaut `0` ou `7`.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use time::{Date, OffsetDateTime, Time};
+
+/// Nombre maximal d'itérations pour trouver la prochaine exécution (garde-fou).
+const MAX_SEARCH_STEPS: usize = 100_000;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Nombre de jours examinés pour savoir si deux jours consécutifs peuvent correspondre.
+const CONSECUTIVE_DAYS_SCAN: usize = 4 * 366;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule
+{
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+    expression: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronParseError(String);
+
+impl fmt::Display for CronParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CronParseError {}
+
+impl FromStr for Schedule
+{
+    type Err = CronParseError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err>
+    {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields.as_slice() else
+        {
+            return Err(CronParseError(format!("expected 5 fields, found {}", fields.len())));
+        };
+
+        let mut days_of_week = parse_field(day_of_week, 0, 7, "day of week")?;
+        // 7 est un alias du dimanche.
+        if days_of_week & (1 << 7) != 0
+        {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(Self
+        {
+            minutes: parse_field(minute, 0, 59, "minute")?,
+            hours: parse_field(hour, 0, 23, "hour")?,
+            days_of_month: parse_field(day_of_month, 1, 31, "day of month")?,
+            months: parse_field(month, 1, 12, "month")?,
+            days_of_week,
+            day_of_month_restricted: *day_of_month != "*",
+            day_of_week_restricted: *day_of_week != "*",
+            expression: fields.join(" "),
+        })
+    }
+}
+
+impl fmt::Display for Schedule
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.write_str(&self.expression)
+    }
+}
+
+impl Schedule
+{
+    /// Indique si la minute contenant `at` correspond à l'expression.
+    #[must_use]
+    pub fn matches(&self, at: OffsetDateTime) -> bool
+    {
+        self.matches_date(at.date())
+            && bit(self.hours, at.hour())
+            && bit(self.minutes, at.minute())
+    }
+
+    /// Prochaine minute strictement postérieure à `after` correspondant à l'expression.
+    #[must_use]
+    pub fn next_after(&self, after: OffsetDateTime) -> Option<OffsetDateTime>
+    {
+        let mut candidate = truncate_to_minute(after) + time::Duration::MINUTE;
+
+        for _ in 0..MAX_SEARCH_STEPS
+        {
+            if !bit(self.months, u8::from(candidate.month()))
+            {
+                let (year, month) = match candidate.month().next()
+                {
+                    time::Month::January => (candidate.year() + 1, time::Month::January),
+                    next => (candidate.year(), next),
+                };
+                candidate = Date::from_calendar_date(year, month, 1).ok()?.midnight().assume_utc();
+            }
+            else if !self.matches_date(candidate.date())
+            {
+                candidate = candidate.date().next_day()?.midnight().assume_utc();
+            }
+            else if !bit(self.hours, candidate.hour())
+            {
+                candidate = candidate.replace_time(Time::from_hms(candidate.hour(), 0, 0).ok()?) + time::Duration::HOUR;
+            }
+            else if !bit(self.minutes, candidate.minute())
+            {
+                candidate += time::Duration::MINUTE;
+            }
+            else
+            {
+                return Some(candidate);
+            }
+        }
+
+        None
+    }
+
+    /// Plus petit écart possible entre deux exécutions consécutives, déduit des masques
+    /// minute/heure plutôt que d'une simulation bornée : une expression qui ne se déclenche
+    /// que dans plusieurs mois est évaluée comme celle qui se déclenche demain.
+    /// Une expression qui s'exécute au plus une fois par jour, jamais deux jours de suite,
+    /// est bornée à deux jours. Renvoie `None` si l'expression ne se déclenche jamais.
+    #[must_use]
+    pub fn min_interval(&self, from: OffsetDateTime) -> Option<Duration>
+    {
+        let first = self.next_after(from)?;
+
+        let minutes_of_day: Vec<u32> = (0..24u8)
+            .filter(|&hour| bit(self.hours, hour))
+            .flat_map(|hour| (0..60u8).filter(|&minute| bit(self.minutes, minute)).map(move |minute| u32::from(hour) * 60 + u32::from(minute)))
+            .collect();
+
+        // Tout jour qui correspond contient toutes les minutes du masque.
+        let same_day = minutes_of_day.windows(2).map(|pair| pair[1] - pair[0]).min();
+
+        // Dernière exécution d'un jour puis première du lendemain, si deux jours consécutifs
+        // peuvent correspondre (quatre ans couvrent les 29 février).
+        let consecutive_days = (0..CONSECUTIVE_DAYS_SCAN)
+            .scan(first.date(), |date, _|
+            {
+                let current = *date;
+                *date = current.next_day()?;
+                Some((current, *date))
+            })
+            .any(|(day, next)| self.matches_date(day) && self.matches_date(next));
+        let across_days = match (consecutive_days, minutes_of_day.first(), minutes_of_day.last())
+        {
+            (true, Some(first_minute), Some(last_minute)) => Some(first_minute + MINUTES_PER_DAY - last_minute),
+            _ => None,
+        };
+
+        let minutes = match (same_day, across_days)
+        {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(gap), None) | (None, Some(gap)) => gap,
+            (None, None) => 2 * MINUTES_PER_DAY,
+        };
+
+        Some(Duration::from_secs(u64::from(minutes) * 60))
+    }
+
+    fn matches_date(&self, date: Date) -> bool
+    {
+        if !bit(self.months, u8::from(date.month()))
+        {
+            return false;
+        }
+
+        let day_of_month = bit(self.days_of_month, date.day());
+        let day_of_week = bit(self.days_of_week, date.weekday().number_days_from_sunday());
+
+        // Sémantique cron classique : si les deux champs sont restreints, l'un OU l'autre suffit.
+        match (self.day_of_month_restricted, self.day_of_week_restricted)
+        {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+}
+
+fn truncate_to_minute(at: OffsetDateTime) -> OffsetDateTime
+{
+    at.replace_second(0).and_then(|t| t.replace_nanosecond(0)).unwrap_or(at)
+}
+
+const fn bit(mask: u64, value: u8) -> bool
+{
+    mask & (1 << value) != 0
+}
+
+fn parse_field(field: &str, min: u8, max: u8, name: &str) -> Result<u64, CronParseError>
+{
+    let invalid = || CronParseError(format!("invalid {name} field '{field}'"));
+    let mut mask = 0u64;
+
+    for part in field.split(',')
+    {
+        let (range, step) = match part.split_once('/')
+        {
+            Some((range, step)) => (range, step.parse::<u8>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0
+        {
+            return Err(invalid());
+        }
+
+        let (start, end) = if range == "*"
+        {
+            (min, max)
+        }
+        else if let Some((start, end)) = range.split_once('-')
+        {
+            (start.parse::<u8>().map_err(|_| invalid())?, end.parse::<u8>().map_err(|_| invalid())?)
+        }
+        else
+        {
+            let value = range.parse::<u8>().map_err(|_| invalid())?;
+            // `a/n` signifie "de a jusqu'au maximum, par pas de n".
+            if part.contains('/') { (value, max) } else { (value, value) }
+        };
+
+        if start < min || end > max || start > end
+        {
+            return Err(CronParseError(format!("{name} field '{field}' is out of range {min}-{max}")));
+        }
+
+        for value in (start..=end).step_by(usize::from(step))
+        {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    fn at(day: u8, hour: u8, minute: u8) -> OffsetDateTime
+    {
+        // Le 1er mars 2025 est un samedi.
+        Date::from_calendar_date(2025, Month::March, day).unwrap().with_hms(hour, minute, 0).unwrap().assume_utc()
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_expressions()
+    {
+        assert!("* * * *".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        assert!("5-1 * * * *".parse::<Schedule>().is_err());
+        assert!("a * * * *".parse::<Schedule>().is_err());
+    }
+
+    #[test]
+    fn test_next_after()
+    {
+        let schedule: Schedule = "30 3 * * *".parse().unwrap();
+        assert_eq!(schedule.next_after(at(1, 1, 0)), Some(at(1, 3, 30)));
+        assert_eq!(schedule.next_after(at(1, 3, 30)), Some(at(2, 3, 30)));
+    }
+
+    #[test]
+    fn test_day_of_week_and_sunday_alias()
+    {
+        let schedule: Schedule = "0 9 * * 7".parse().unwrap();
+        assert_eq!(schedule.next_after(at(1, 0, 0)), Some(at(2, 9, 0)));
+        assert!(schedule.matches(at(9, 9, 0)));
+    }
+
+    #[test]
+    fn test_min_interval()
+    {
+        let every_five: Schedule = "*/5 * * * *".parse().unwrap();
+        assert_eq!(every_five.min_interval(at(1, 0, 0)), Some(Duration::from_secs(300)));
+
+        let uneven: Schedule = "0,10 8 * * *".parse().unwrap();
+        assert_eq!(uneven.min_interval(at(1, 0, 0)), Some(Duration::from_secs(600)));
+
+        let around_midnight: Schedule = "0 0,23 * * *".parse().unwrap();
+        assert_eq!(around_midnight.min_interval(at(1, 0, 0)), Some(Duration::from_secs(3600)));
+
+        let single_day: Schedule = "0 0,23 1 * *".parse().unwrap();
+        assert_eq!(single_day.min_interval(at(5, 0, 0)), Some(Duration::from_secs(23 * 3600)));
+
+        let never: Schedule = "0 0 31 2 *".parse().unwrap();
+        assert_eq!(never.min_interval(at(1, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_min_interval_when_first_run_is_far_away()
+    {
+        // Le 1er du mois suivant est à plus de 8 jours du 5 mars.
+        let first_of_month: Schedule = "* * 1 * *".parse().unwrap();
+        assert_eq!(first_of_month.min_interval(at(5, 12, 0)), Some(Duration::from_secs(60)));
+
+        let december: Schedule = "* * * 12 *".parse().unwrap();
+        let january = Date::from_calendar_date(2025, Month::January, 15).unwrap().midnight().assume_utc();
+        assert_eq!(december.min_interval(january), Some(Duration::from_secs(60)));
+    }
+}
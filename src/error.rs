@@ -1,9 +1,14 @@
-use axum::{http::StatusCode, response::{IntoResponse, Response}, Json};
+use axum::{http::{StatusCode, header::{RETRY_AFTER, WWW_AUTHENTICATE}}, response::{IntoResponse, Response}, Json};
 use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
 use tracing::{error, trace};
 
+use crate::model::scan::ScanFindings;
+
+/// Défi renvoyé avec toute réponse 401.
+const UNAUTHORIZED_CHALLENGE: &str = "Bearer realm=\"hangar\"";
+
 #[derive(Debug, Error)]
 pub enum AppError
 {
@@ -16,6 +21,13 @@ pub enum AppError
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
+    /// Jeton d'authentification absent ou rejeté : le code indique au client s'il doit se réauthentifier.
+    #[error("Unauthorized: {0}")]
+    TokenError(#[from] TokenErrorCode),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Error occurred while calling external service")]
     ExternalServiceError(#[from] reqwest::Error),
 
@@ -25,11 +37,21 @@ pub enum AppError
     #[error("Bad Request: {0}")]
     BadRequest(String),
 
+    #[error("Too Many Requests: {0}")]
+    TooManyRequests(String),
+
+    /// Capacité saturée : le client peut réessayer après `retry_after_seconds` (en-tête `Retry-After`).
+    #[error("Service Unavailable: {message}")]
+    ServiceUnavailable { message: String, retry_after_seconds: u64 },
+
     #[error("Project operation failed: {0}")]
     ProjectError(#[from] ProjectErrorCode),
 
     #[error("Database operation failed: {0}")]
     DatabaseError(#[from] DatabaseErrorCode),
+
+    #[error("Bucket operation failed: {0}")]
+    BucketError(#[from] BucketErrorCode),
 }
 
 #[derive(Debug, Error)]
@@ -54,14 +76,28 @@ pub enum ProjectErrorCode
     OwnerCannotBeParticipant,
     #[error("The project name is invalid. It must be 1-63 characters, contain only a-z, 0-9, or '-', and not start/end with a hyphen.")]
     InvalidProjectName,
-    #[error("The provided Docker image URL is invalid or contains forbidden characters.")]
-    InvalidImageUrl,
+    #[error("The project name is invalid: its hostname '{0}' exceeds DNS limits (63 characters per label, 253 in total).")]
+    InvalidProjectHostname(String),
+    #[error("The project name is not available: its Traefik router '{0}' is reserved.")]
+    ReservedRouterName(String),
+    #[error("The provided Docker image reference is invalid: {0}.")]
+    InvalidImageUrl(String),
     #[error("Failed to pull the Docker image. Please check the URL and registry access.")]
     ImagePullFailed,
     #[error("Security scan failed: vulnerabilities were found in the image.")]
-    ImageScanFailed(String),
+    ImageScanFailed(ScanFindings),
     #[error("Failed to create the project container.")]
     ContainerCreationFailed,
+    #[error("A container with the same name already exists.")]
+    ContainerNameConflict,
+    #[error("The Traefik router '{router}' is already declared by container '{container}' of another project.")]
+    RoutingConflict { router: String, container: String },
+    #[error("This project is archived. Unarchive it first.")]
+    ProjectArchived,
+    #[error("The Docker network of the platform does not exist. Administrators have been notified.")]
+    DockerNetworkNotFound,
+    #[error("The project container did not become healthy in time.")]
+    HealthCheckFailed,
     #[error("Failed to delete the project.")]
     DeleteFailed,
     #[error("The provided GitHub URL is invalid or unsupported.")]
@@ -80,6 +116,52 @@ pub enum ProjectErrorCode
     ProjectCreationFailedWithDatabaseError,
     #[error("The specified source root directory is invalid.")]
     InvalidSourceRootDir,
+    #[error("The Git reference is invalid. It must be a tag name or a full 40-character commit SHA.")]
+    InvalidGitRef,
+    #[error("No Dockerfile was found in the repository (under the source root directory, if set).")]
+    RepoDockerfileNotFound,
+    #[error("The container entrypoint or command is invalid. Each must contain between 1 and 64 arguments without control characters.")]
+    InvalidContainerCommand,
+    #[error("The container port must be between 1 and 65535.")]
+    InvalidContainerPort,
+    #[error("The timezone '{0}' is not a known IANA timezone.")]
+    InvalidTimezone(String),
+    #[error("The scan ignore list is invalid. It must contain at most 50 vulnerability identifiers (CVE-..., GHSA-...).")]
+    InvalidScanIgnoreList,
+    #[error("The cron schedule is invalid: {0}")]
+    InvalidJobSchedule(String),
+    #[error("The cron schedule runs more often than the minimum allowed interval.")]
+    JobIntervalTooShort,
+    #[error("The maximum number of jobs for this project has been reached.")]
+    JobLimitReached,
+    #[error("A project cannot have more than {0} participants.")]
+    ParticipantLimitReached(i64),
+    #[error("The job command is invalid. It must contain between 1 and 64 non-empty arguments.")]
+    InvalidJobCommand,
+    #[error("A task is already running for this project.")]
+    TaskAlreadyRunning,
+    #[error("The project archive is invalid: {0}.")]
+    InvalidProjectArchive(String),
+    #[error("The custom domain is invalid: {0}.")]
+    InvalidCustomDomain(String),
+    #[error("This domain is already attached to a project.")]
+    DomainAlreadyClaimed,
+    #[error("A project cannot have more than {0} custom domains.")]
+    DomainLimitReached(i64),
+    #[error("The domain could not be verified: {0}.")]
+    DomainVerificationFailed(String),
+    #[error("A template with this name already exists.")]
+    TemplateNameTaken,
+    #[error("The template is invalid: {0}.")]
+    InvalidTemplate(String),
+    #[error("Values are missing for the template fields: {0}.")]
+    MissingTemplateValues(String),
+    #[error("The uploaded archive is invalid: {0}.")]
+    InvalidUploadArchive(String),
+    #[error("A project cannot have more than {0} webhooks.")]
+    WebhookLimitReached(i64),
+    #[error("The deployed image of this project is no longer available and cannot be reproduced: {0}.")]
+    DeployedImageUnavailable(String),
 }
 
 #[derive(Debug, Error, Serialize, PartialEq, Eq)]
@@ -94,8 +176,81 @@ pub enum DatabaseErrorCode
     DeprovisioningFailed,
     #[error("Database not found.")]
     NotFound,
+    #[error("Session not found for this database user.")]
+    SessionNotFound,
+    #[error("Failed to restore the database backup.")]
+    RestoreFailed,
+    #[error("Failed to reset the database.")]
+    ResetFailed,
+    #[error("Failed to dump the database.")]
+    DumpFailed,
 }
 
+#[derive(Debug, Error, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TokenErrorCode
+{
+    #[error("Authentication token missing.")]
+    Missing,
+    #[error("Authentication token expired.")]
+    Expired,
+    #[error("Invalid authentication token.")]
+    Invalid,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BucketErrorCode
+{
+    #[error("Object storage is not enabled on this instance.")]
+    NotConfigured,
+    #[error("This project already has a bucket.")]
+    BucketAlreadyExists,
+    #[error("Failed to provision the bucket.")]
+    ProvisioningFailed,
+    #[error("Failed to deprovision the bucket.")]
+    DeprovisioningFailed,
+    #[error("Bucket not found.")]
+    NotFound,
+}
+
+impl AppError
+{
+    /// Code d'erreur renvoyé au client, identique à celui de la réponse HTTP.
+    #[must_use]
+    pub const fn error_code(&self) -> &'static str
+    {
+        match self
+        {
+            Self::InternalServerError | Self::ExternalServiceError(_) | Self::ParsingError(_) => "INTERNAL_SERVER_ERROR",
+            Self::Unauthorized(_) => "UNAUTHORIZED",
+            Self::TokenError(code) => code.as_str(),
+            Self::Forbidden(_) => "FORBIDDEN",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::BadRequest(_) => "BAD_REQUEST",
+            Self::TooManyRequests(_) => "TOO_MANY_REQUESTS",
+            Self::ServiceUnavailable { .. } => "SERVICE_UNAVAILABLE",
+            Self::ProjectError(code) => code.as_str(),
+            Self::DatabaseError(code) => code.as_str(),
+            Self::BucketError(code) => code.as_str(),
+        }
+    }
+
+    /// Message renvoyé au client ; les erreurs internes restent génériques.
+    #[must_use]
+    pub fn public_message(&self) -> String
+    {
+        match self
+        {
+            Self::InternalServerError | Self::ExternalServiceError(_) | Self::ParsingError(_) => "An internal error has occurred".to_string(),
+            Self::Unauthorized(message) | Self::Forbidden(message) | Self::NotFound(message) | Self::BadRequest(message) | Self::TooManyRequests(message) | Self::ServiceUnavailable { message, .. } => message.clone(),
+            Self::ProjectError(code) => code.to_string(),
+            Self::DatabaseError(code) => code.to_string(),
+            Self::TokenError(code) => code.to_string(),
+            Self::BucketError(code) => code.to_string(),
+        }
+    }
+}
 
 impl ProjectErrorCode 
 {
@@ -106,20 +261,49 @@ impl ProjectErrorCode
             Self::ProjectNameTaken => "PROJECT_NAME_TAKEN",
             Self::OwnerAlreadyExists => "OWNER_ALREADY_EXISTS",
             Self::OwnerCannotBeParticipant => "OWNER_CANNOT_BE_PARTICIPANT",
-            Self::InvalidProjectName => "INVALID_PROJECT_NAME",
-            Self::InvalidImageUrl => "INVALID_IMAGE_URL",
+            Self::InvalidProjectName | Self::InvalidProjectHostname(_) => "INVALID_PROJECT_NAME",
+            Self::ReservedRouterName(_) => "RESERVED_ROUTER_NAME",
+            Self::InvalidImageUrl(_) => "INVALID_IMAGE_URL",
             Self::ImagePullFailed => "IMAGE_PULL_FAILED",
             Self::ImageScanFailed(_) => "IMAGE_SCAN_FAILED",
             Self::ContainerCreationFailed => "CONTAINER_CREATION_FAILED",
+            Self::ContainerNameConflict => "CONTAINER_NAME_CONFLICT",
+            Self::RoutingConflict { .. } => "ROUTING_CONFLICT",
+            Self::ProjectArchived => "PROJECT_ARCHIVED",
+            Self::DockerNetworkNotFound => "DOCKER_NETWORK_NOT_FOUND",
+            Self::HealthCheckFailed => "HEALTH_CHECK_FAILED",
             Self::DeleteFailed => "DELETE_FAILED",
             Self::GithubAccountNotLinked => "GITHUB_ACCOUNT_NOT_LINKED",
             Self::GithubRepoNotAccessible => "GITHUB_REPO_NOT_ACCESSIBLE",
             Self::GithubPackageNotPublic => "GITHUB_PACKAGE_NOT_PUBLIC",
             Self::ForbiddenEnvVar(_) => "FORBIDDEN_ENV_VAR",
             Self::InvalidVolumePath => "INVALID_VOLUME_PATH",
+            Self::InvalidContainerCommand => "INVALID_CONTAINER_COMMAND",
+            Self::InvalidContainerPort => "INVALID_CONTAINER_PORT",
+            Self::InvalidTimezone(_) => "INVALID_TIMEZONE",
+            Self::InvalidScanIgnoreList => "INVALID_SCAN_IGNORE_LIST",
             Self::InvalidGithubUrl => "INVALID_GITHUB_URL",
             Self::ProjectCreationFailedWithDatabaseError => "PROJECT_CREATION_FAILED_WITH_DATABASE_ERROR",
             Self::InvalidSourceRootDir => "INVALID_SOURCE_ROOT_DIR",
+            Self::InvalidGitRef => "INVALID_GIT_REF",
+            Self::RepoDockerfileNotFound => "REPO_DOCKERFILE_NOT_FOUND",
+            Self::InvalidJobSchedule(_) => "INVALID_JOB_SCHEDULE",
+            Self::JobIntervalTooShort => "JOB_INTERVAL_TOO_SHORT",
+            Self::JobLimitReached => "JOB_LIMIT_REACHED",
+            Self::ParticipantLimitReached(_) => "PARTICIPANT_LIMIT_REACHED",
+            Self::InvalidJobCommand => "INVALID_JOB_COMMAND",
+            Self::TaskAlreadyRunning => "TASK_ALREADY_RUNNING",
+            Self::InvalidProjectArchive(_) => "INVALID_PROJECT_ARCHIVE",
+            Self::InvalidCustomDomain(_) => "INVALID_CUSTOM_DOMAIN",
+            Self::DomainAlreadyClaimed => "DOMAIN_ALREADY_CLAIMED",
+            Self::DomainLimitReached(_) => "DOMAIN_LIMIT_REACHED",
+            Self::DomainVerificationFailed(_) => "DOMAIN_VERIFICATION_FAILED",
+            Self::TemplateNameTaken => "TEMPLATE_NAME_TAKEN",
+            Self::InvalidTemplate(_) => "INVALID_TEMPLATE",
+            Self::MissingTemplateValues(_) => "MISSING_TEMPLATE_VALUES",
+            Self::InvalidUploadArchive(_) => "INVALID_UPLOAD_ARCHIVE",
+            Self::WebhookLimitReached(_) => "WEBHOOK_LIMIT_REACHED",
+            Self::DeployedImageUnavailable(_) => "DEPLOYED_IMAGE_UNAVAILABLE",
         }
     }
 }
@@ -134,6 +318,50 @@ impl DatabaseErrorCode
             Self::ProvisioningFailed => "PROVISIONING_FAILED",
             Self::DeprovisioningFailed => "DEPROVISIONING_FAILED",
             Self::NotFound => "NOT_FOUND",
+            Self::SessionNotFound => "SESSION_NOT_FOUND",
+            Self::RestoreFailed => "RESTORE_FAILED",
+            Self::ResetFailed => "RESET_FAILED",
+            Self::DumpFailed => "DUMP_FAILED",
+        }
+    }
+}
+
+impl TokenErrorCode
+{
+    const fn as_str(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Missing => "TOKEN_MISSING",
+            Self::Expired => "TOKEN_EXPIRED",
+            Self::Invalid => "TOKEN_INVALID",
+        }
+    }
+
+    /// Défi `WWW-Authenticate` (RFC 6750) : sans paramètre `error` lorsqu'aucun jeton n'a été fourni.
+    fn challenge(&self) -> String
+    {
+        match self
+        {
+            Self::Missing => UNAUTHORIZED_CHALLENGE.to_string(),
+            Self::Expired | Self::Invalid => format!(
+                "{UNAUTHORIZED_CHALLENGE}, error=\"invalid_token\", error_description=\"{self}\""
+            ),
+        }
+    }
+}
+
+impl BucketErrorCode 
+{
+    const fn as_str(&self) -> &'static str 
+    {
+        match self 
+        {
+            Self::NotConfigured => "OBJECT_STORAGE_NOT_CONFIGURED",
+            Self::BucketAlreadyExists => "BUCKET_ALREADY_EXISTS",
+            Self::ProvisioningFailed => "PROVISIONING_FAILED",
+            Self::DeprovisioningFailed => "DEPROVISIONING_FAILED",
+            Self::NotFound => "NOT_FOUND",
         }
     }
 }
@@ -158,9 +386,29 @@ impl IntoResponse for AppError
             Self::Unauthorized(message) =>
             {
                 trace!("--> NOT AUTHORIZED (401): {}", message);
-                (
+                return (
                     StatusCode::UNAUTHORIZED,
+                    [(WWW_AUTHENTICATE, UNAUTHORIZED_CHALLENGE.to_string())],
                     Json(json!({ "error_code": "UNAUTHORIZED", "message": message })),
+                ).into_response();
+            }
+
+            Self::TokenError(code) =>
+            {
+                trace!("--> NOT AUTHORIZED (401): {}", code);
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    [(WWW_AUTHENTICATE, code.challenge())],
+                    Json(json!({ "error_code": code.as_str(), "message": code.to_string() })),
+                ).into_response();
+            }
+
+            Self::Forbidden(message) =>
+            {
+                trace!("--> FORBIDDEN (403): {}", message);
+                (
+                    StatusCode::FORBIDDEN,
+                    Json(json!({ "error_code": "FORBIDDEN", "message": message })),
                 )
             }
 
@@ -182,12 +430,36 @@ impl IntoResponse for AppError
                 )
             }
 
+            Self::TooManyRequests(message) =>
+            {
+                trace!("--> TOO MANY REQUESTS (429): {}", message);
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(json!({ "error_code": "TOO_MANY_REQUESTS", "message": message })),
+                )
+            }
+
+            Self::ServiceUnavailable { message, retry_after_seconds } =>
+            {
+                trace!("--> SERVICE UNAVAILABLE (503): {}", message);
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(RETRY_AFTER, retry_after_seconds.to_string())],
+                    Json(json!({ "error_code": "SERVICE_UNAVAILABLE", "message": message, "retry_after_seconds": retry_after_seconds })),
+                ).into_response();
+            }
+
             Self::DatabaseError(code) =>
             {
                 trace!("--> DATABASE ERROR (400): {}", code);
                 let status = match code 
                 {
-                    DatabaseErrorCode::ProvisioningFailed | DatabaseErrorCode::DeprovisioningFailed => StatusCode::INTERNAL_SERVER_ERROR,
+                    DatabaseErrorCode::ProvisioningFailed
+                    | DatabaseErrorCode::DeprovisioningFailed
+                    | DatabaseErrorCode::RestoreFailed
+                    | DatabaseErrorCode::ResetFailed
+                    | DatabaseErrorCode::DumpFailed => StatusCode::INTERNAL_SERVER_ERROR,
+                    DatabaseErrorCode::SessionNotFound => StatusCode::NOT_FOUND,
                     _ => StatusCode::BAD_REQUEST
                 };
 
@@ -202,13 +474,33 @@ impl IntoResponse for AppError
                     Json(error_json),
                 )
             }
+
+            Self::BucketError(code) =>
+            {
+                trace!("--> BUCKET ERROR (400): {}", code);
+                let status = match code 
+                {
+                    BucketErrorCode::ProvisioningFailed | BucketErrorCode::DeprovisioningFailed => StatusCode::INTERNAL_SERVER_ERROR,
+                    BucketErrorCode::NotFound => StatusCode::NOT_FOUND,
+                    _ => StatusCode::BAD_REQUEST
+                };
+
+                (
+                    status,
+                    Json(json!({ "error_code": code.as_str(), "message": code.to_string() })),
+                )
+            }
             
             Self::ProjectError(code) =>
             {
                 trace!("--> PROJECT ERROR (400): {}", code);
                 let status = match code 
                 {
-                    ProjectErrorCode::ImagePullFailed | ProjectErrorCode::ContainerCreationFailed => StatusCode::INTERNAL_SERVER_ERROR,
+                    ProjectErrorCode::ImagePullFailed | ProjectErrorCode::ContainerCreationFailed | ProjectErrorCode::DockerNetworkNotFound
+                        | ProjectErrorCode::HealthCheckFailed => StatusCode::INTERNAL_SERVER_ERROR,
+                    ProjectErrorCode::TaskAlreadyRunning | ProjectErrorCode::ContainerNameConflict | ProjectErrorCode::RoutingConflict { .. }
+                        | ProjectErrorCode::ProjectArchived | ProjectErrorCode::DeployedImageUnavailable(_)
+                        | ProjectErrorCode::DomainAlreadyClaimed | ProjectErrorCode::TemplateNameTaken => StatusCode::CONFLICT,
                     _ => StatusCode::BAD_REQUEST
                 };
 
@@ -230,6 +522,18 @@ impl IntoResponse for AppError
                         {
                              obj.insert("details".to_string(), json!({ "variable": var }));
                         }
+                        ProjectErrorCode::InvalidProjectHostname(hostname) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "hostname": hostname }));
+                        }
+                        ProjectErrorCode::ReservedRouterName(router) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "router": router }));
+                        }
+                        ProjectErrorCode::RoutingConflict { router, container } =>
+                        {
+                            obj.insert("details".to_string(), json!({ "router": router, "container": container }));
+                        }
                         _ => {}
                     }
                 }
@@ -1,15 +1,34 @@
-use axum::{extract::State, response::Json, response::IntoResponse};
+use axum::{extract::{Path, Query, State}, http::{StatusCode, header}, response::Json, response::{IntoResponse, Response}};
+use rand::distr::{Alphanumeric, SampleString};
+use serde::Deserialize;
 use serde_json::json;
-use crate::{error::AppError, services::{docker_service, project_service}, state::AppState};
-use time::{OffsetDateTime, format_description::well_known::Rfc3339};
-use crate::model::project::DownProjectInfo;
+use crate::{error::AppError, handlers::{deployment_handler::{self, DeploymentsQuery}, project_handler::{self, DeployPayload, perform_deploy, perform_purge}}, logging, model::{database::DatabaseLimits, docker::LogsFilter, label_sync::{LabelResyncResult, LabelResyncStatus}, notification::NotificationKind, offboarding::OffboardPayload, project::Project, response::{ActionResponse, AdminProjectListResponse, CleanupFlaggedResponse, DatabaseDryRunResponse, DatabaseLimitsResponse, DatabaseSessionsResponse, DeploymentPage, DownProjectsResponse, LabelResyncResponse, LabelResyncStartedResponse, LogFilterResponse, MetricsHistoryResponse, OrphanCleanupResponse, OwnerlessProjectsResponse, ProjectWithMembers, SelftestOutcome, SelftestResponse, SelftestStep, TraefikRoutesResponse, UnlabeledContainer, UnlabeledContainersResponse}}, services::{audit_service, base_image_service, blue_green, cleanup_service, crypto_audit_service, database_service, deployment_service, docker_service, import_service, jwt::Claims, label_sync_service, metrics_history_service, notification_service, offboarding_service, orphan_service, project_service, usage_service, user_service}, sse::{emitter::emit_admin_event, types::SystemEvent}, state::AppState};
+use std::time::{Duration, Instant};
+use time::format_description::well_known::Rfc3339;
 
 pub async fn list_all_projects_handler(
     State(state): State<AppState>
 ) -> Result<impl IntoResponse, AppError> 
 {
     let projects = project_service::get_all_projects(&state.db_pool).await?;
-    Ok(Json(json!({ "projects": projects })))
+
+    let ids: Vec<i32> = projects.iter().map(|p| p.id).collect();
+    let mut owners: Vec<String> = projects.iter().map(|p| p.owner.clone()).collect();
+    owners.sort();
+    owners.dedup();
+
+    let mut participants = project_service::get_participants_by_project(&state.db_pool, &ids).await?;
+    let owner_names = user_service::get_display_names(&state.db_pool, &owners).await?;
+
+    let (archived_projects, projects): (Vec<_>, Vec<_>) = projects.into_iter().map(|project| ProjectWithMembers
+    {
+        owner_display: owner_names.get(&project.owner).cloned().unwrap_or_else(|| project.owner.clone()),
+        participants: participants.remove(&project.id).unwrap_or_default(),
+        scan_ignore_cves: project.scan_ignore_cves.clone(),
+        project,
+    }).partition(|entry| entry.project.archived);
+
+    Ok(Json(AdminProjectListResponse { projects, archived_projects }))
 }
 
 pub async fn get_global_metrics_handler(
@@ -28,35 +47,845 @@ pub async fn get_global_metrics_handler(
     Ok(Json(metrics))
 }
 
+/// Durée de validité du cache de `docker system df`.
+const DOCKER_DF_CACHE_TTL: Duration = Duration::from_secs(60);
+
+pub async fn get_docker_info_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let cached = state.docker_df_cache.lock().ok()
+        .and_then(|cache| *cache)
+        .filter(|(fetched_at, _)| fetched_at.elapsed() < DOCKER_DF_CACHE_TTL)
+        .map(|(_, usage)| usage);
+
+    let disk_usage = match cached
+    {
+        Some(usage) => usage,
+        None =>
+        {
+            let usage = docker_service::get_disk_usage(&state.docker_client).await?;
+            if let Ok(mut cache) = state.docker_df_cache.lock()
+            {
+                *cache = Some((Instant::now(), usage));
+            }
+            usage
+        }
+    };
+
+    let base_images = base_image_service::base_image_statuses(&state).await?;
+    let info = docker_service::get_daemon_info(&state.docker_client, disk_usage, base_images).await?;
+
+    Ok(Json(info))
+}
+
+/// Conteneurs sans label `hangar.project-id` : ils sont absents des statistiques globales
+/// tant que le projet n'a pas été redéployé.
+pub async fn list_unlabeled_containers_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let names = docker_service::list_unlabeled_project_containers(&state.docker_client, &state.config.app_prefix).await?;
+
+    let mut containers = Vec::with_capacity(names.len());
+    for name in names
+    {
+        let project = project_service::get_project_by_container_name(&state.db_pool, &name).await?;
+        containers.push(UnlabeledContainer
+        {
+            container_name: name,
+            project_id: project.as_ref().map(|p| p.id),
+            project_name: project.map(|p| p.name),
+        });
+    }
+
+    Ok(Json(UnlabeledContainersResponse { containers }))
+}
+
+/// Routeurs et noms d'hôte déclarés auprès de Traefik par les conteneurs, pour auditer les conflits de routage.
+pub async fn list_traefik_routes_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let routes = docker_service::list_traefik_routes(&state.docker_client, &state.config.app_prefix).await?;
+    let conflicts = docker_service::conflicting_routers(&routes);
+
+    Ok(Json(TraefikRoutesResponse { routes, conflicts }))
+}
+
+/// Conteneurs, volumes et images de la plateforme qui ne correspondent à aucun projet.
+pub async fn list_orphans_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    Ok(Json(orphan_service::find_orphans(&state).await?))
+}
+
+#[derive(Deserialize)]
+pub struct RemoveOrphansQuery
+{
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Supprime les ressources orphelines (ou les liste seulement avec `dry_run=true`).
+pub async fn remove_orphans_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<RemoveOrphansQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let orphans = orphan_service::find_orphans(&state).await?;
+
+    if query.dry_run
+    {
+        emit_admin_event(&state, SystemEvent::info(format!(
+            "Orphan cleanup dry run: {} orphaned Docker resource(s) would be removed.", orphans.count()
+        )).with_context(json!({ "orphans": orphans })));
+
+        return Ok(Json(OrphanCleanupResponse { dry_run: true, orphans, removed: Default::default(), failed: Default::default() }));
+    }
+
+    tracing::info!("Admin '{}' removing {} orphaned Docker resource(s)", claims.sub, orphans.count());
+    let (removed, failed) = orphan_service::remove_orphans(&state, orphans.clone()).await;
+
+    audit_service::record(
+        &state,
+        &claims.sub,
+        audit_service::ACTION_ORPHANS_REMOVED,
+        None,
+        Some(json!({ "removed": removed, "failed": failed })),
+    ).await;
+
+    let message = format!("Orphan cleanup removed {} Docker resource(s), {} failed.", removed.count(), failed.count());
+    let event = if failed.count() > 0 { SystemEvent::warning(message) } else { SystemEvent::info(message) };
+    emit_admin_event(&state, event.with_context(json!({ "removed": removed, "failed": failed })));
+
+    Ok(Json(OrphanCleanupResponse { dry_run: false, orphans, removed, failed }))
+}
+
+#[derive(Deserialize)]
+pub struct ResyncLabelsQuery
+{
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Recrée le conteneur du projet si ses labels ne correspondent plus à la configuration actuelle
+/// (ou liste seulement les écarts avec `dry_run=true`).
+pub async fn resync_project_labels_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<ResyncLabelsQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = project_service::get_project_by_id(&state.db_pool, project_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with ID {project_id} not found.")))?;
+
+    let result = resync_labels(&state, &claims.sub, &project, query.dry_run).await;
+    record_labels_resync(&state, &claims.sub, Some(project_id), None, query.dry_run, std::slice::from_ref(&result)).await;
+
+    Ok(Json(LabelResyncResponse { dry_run: query.dry_run, projects: vec![result] }))
+}
+
+/// Longueur de l'identifiant renvoyé au lancement d'une resynchronisation de tous les projets.
+const LABEL_RESYNC_ID_LENGTH: usize = 12;
+
+/// Resynchronise les labels de tous les projets, un par un pour ne pas recréer tous les conteneurs à la fois.
+/// Seul le `dry_run` répond directement ; sinon la resynchronisation tourne en tâche de fond et
+/// renvoie `202` avec un identifiant repris dans les événements SSE admin de progression.
+pub async fn resync_all_labels_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<ResyncLabelsQuery>,
+) -> Result<Response, AppError>
+{
+    let projects = project_service::get_all_projects(&state.db_pool).await?;
+    tracing::info!("Admin '{}' resyncing container labels of {} project(s) (dry run: {})", claims.sub, projects.len(), query.dry_run);
+
+    if query.dry_run
+    {
+        let mut results = Vec::with_capacity(projects.len());
+        for project in &projects
+        {
+            results.push(resync_labels(&state, &claims.sub, project, true).await);
+        }
+
+        record_labels_resync(&state, &claims.sub, None, None, true, &results).await;
+
+        return Ok(Json(LabelResyncResponse { dry_run: true, projects: results }).into_response());
+    }
+
+    let resync_id = Alphanumeric.sample_string(&mut rand::rng(), LABEL_RESYNC_ID_LENGTH);
+    let started = LabelResyncStartedResponse { resync_id: resync_id.clone(), projects: projects.len() };
+
+    tokio::spawn(async move
+    {
+        let total = projects.len();
+        let mut results = Vec::with_capacity(total);
+        for (index, project) in projects.iter().enumerate()
+        {
+            let result = resync_labels(&state, &claims.sub, project, false).await;
+            let message = format!("Label resync {resync_id}: project '{}' processed ({}/{total}).", project.name, index + 1);
+            let event = if result.status == LabelResyncStatus::Failed { SystemEvent::warning(message) } else { SystemEvent::info(message) };
+            emit_admin_event(&state, event.with_context(json!({ "resync_id": resync_id, "project": result })));
+            results.push(result);
+        }
+
+        record_labels_resync(&state, &claims.sub, None, Some(&resync_id), false, &results).await;
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(started)).into_response())
+}
+
+async fn resync_labels(state: &AppState, admin_login: &str, project: &Project, dry_run: bool) -> LabelResyncResult
+{
+    let result = |status, diffs, error| LabelResyncResult
+    {
+        project_id: project.id,
+        project_name: project.name.clone(),
+        status,
+        diffs,
+        error,
+    };
+
+    let diffs = match label_sync_service::label_diff(state, project).await
+    {
+        Ok(Some(diffs)) => diffs,
+        Ok(None) => return result(LabelResyncStatus::Skipped, Vec::new(), None),
+        Err(e) => return result(LabelResyncStatus::Failed, Vec::new(), Some(e.to_string())),
+    };
+
+    if diffs.is_empty()
+    {
+        return result(LabelResyncStatus::InSync, diffs, None);
+    }
+    if dry_run
+    {
+        return result(LabelResyncStatus::OutOfSync, diffs, None);
+    }
+
+    tracing::info!("Recreating container of project '{}' to apply {} label change(s)", project.name, diffs.len());
+    match project_handler::recreate_for_platform_change(state, admin_login, project).await
+    {
+        Ok(_) => result(LabelResyncStatus::Recreated, diffs, None),
+        Err(e) =>
+        {
+            tracing::warn!("Failed to recreate container of project '{}' for label resync: {}", project.name, e);
+            result(LabelResyncStatus::Failed, diffs, Some(e.to_string()))
+        }
+    }
+}
+
+async fn record_labels_resync(state: &AppState, admin_login: &str, project_id: Option<i32>, resync_id: Option<&str>, dry_run: bool, results: &[LabelResyncResult])
+{
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    let (out_of_sync, recreated, failed) = (count(LabelResyncStatus::OutOfSync), count(LabelResyncStatus::Recreated), count(LabelResyncStatus::Failed));
+
+    if dry_run
+    {
+        emit_admin_event(state, SystemEvent::info(format!(
+            "Label resync dry run: {out_of_sync} container(s) would be recreated."
+        )).with_context(json!({ "projects": results })));
+        return;
+    }
+
+    audit_service::record(
+        state,
+        admin_login,
+        audit_service::ACTION_LABELS_RESYNCED,
+        project_id,
+        Some(json!({ "recreated": recreated, "failed": failed })),
+    ).await;
+
+    let message = format!("Label resync recreated {recreated} container(s), {failed} failed.");
+    let event = if failed > 0 { SystemEvent::warning(message) } else { SystemEvent::info(message) };
+    emit_admin_event(state, event.with_context(json!({ "resync_id": resync_id, "projects": results })));
+}
+
+#[derive(Deserialize)]
+pub struct ImportProjectPayload
+{
+    container_name: String,
+    owner: String,
+    /// Nom du projet ; dérivé du nom du conteneur par défaut.
+    project_name: Option<String>,
+}
+
+/// Reprend sous gestion un conteneur lancé à la main et indique ce qui n'a pas pu être repris.
+pub async fn import_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<ImportProjectPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let owner = payload.owner.trim();
+    if owner.is_empty()
+    {
+        return Err(AppError::BadRequest("owner is required.".to_string()));
+    }
+
+    let report = import_service::import_container(&state, payload.container_name.trim(), owner, payload.project_name.as_deref()).await?;
+
+    audit_service::record(
+        &state,
+        &claims.sub,
+        audit_service::ACTION_PROJECT_IMPORTED,
+        Some(report.project.id),
+        Some(json!({ "original_container": payload.container_name, "owner": owner, "unmapped": report.unmapped })),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateLogFilterPayload
+{
+    /// Directives au format `RUST_LOG`, par exemple `info,hangar_back::services=debug`.
+    filter: String,
+    /// Délai avant retour au filtre du démarrage ; `LOG_FILTER_REVERT_MINUTES` par défaut et au maximum.
+    revert_after_minutes: Option<u64>,
+}
+
+fn log_filter_response(state: &AppState) -> LogFilterResponse
+{
+    LogFilterResponse
+    {
+        filter: state.log_filter.current(),
+        startup_filter: state.log_filter.startup().to_string(),
+        revert_at: state.log_filter.revert_at(),
+    }
+}
+
+pub async fn get_log_filter_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    Ok(Json(log_filter_response(&state)))
+}
+
+/// Remplace le filtre de logs sans redémarrage ; le filtre du démarrage revient automatiquement.
+pub async fn update_log_filter_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(payload): Json<UpdateLogFilterPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let directives = payload.filter.trim();
+    if directives.is_empty()
+    {
+        return Err(AppError::BadRequest("filter is required.".to_string()));
+    }
+
+    let filter = logging::parse_filter(directives)
+        .map_err(|e| AppError::BadRequest(format!("Invalid log filter: {e}")))?;
+
+    let max_minutes = state.config.log_filter_revert_minutes;
+    let minutes = payload.revert_after_minutes.unwrap_or(max_minutes);
+    if !(1..=max_minutes).contains(&minutes)
+    {
+        return Err(AppError::BadRequest(format!("revert_after_minutes must be between 1 and {max_minutes}.")));
+    }
+    let revert_after = Duration::from_secs(minutes * 60);
+
+    let generation = state.log_filter.apply(filter, revert_after)
+        .map_err(|e|
+        {
+            tracing::error!("Failed to reload log filter: {}", e);
+            AppError::InternalServerError
+        })?;
+
+    tracing::info!("Log filter set to '{}' by '{}' for {} minutes.", logging::log_safe(directives), claims.sub, minutes);
+
+    audit_service::record(
+        &state,
+        &claims.sub,
+        audit_service::ACTION_LOG_FILTER_CHANGED,
+        None,
+        Some(json!({ "filter": directives, "revert_after_minutes": minutes })),
+    ).await;
+
+    let revert_state = state.clone();
+    tokio::spawn(async move
+    {
+        tokio::time::sleep(revert_after).await;
+        if revert_state.log_filter.revert(generation)
+        {
+            tracing::info!("Log filter reverted to startup filter '{}'.", revert_state.log_filter.startup());
+        }
+    });
+
+    Ok(Json(log_filter_response(&state)))
+}
+
 pub async fn get_down_projects_handler(
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> 
 {
-    let all_projects = project_service::get_all_projects(&state.db_pool).await?;
-    let mut down_projects: Vec<DownProjectInfo> = Vec::new();
+    let down_projects = project_service::list_down_projects(&state).await?;
+
+    Ok(Json(DownProjectsResponse { down_projects }))
+}
 
-    let now = OffsetDateTime::now_utc();
+pub async fn list_all_database_sessions_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let usernames = database_service::get_all_database_usernames(&state.db_pool).await?;
+    let sessions = database_service::list_mariadb_sessions(&state.mariadb_pool, &usernames).await?;
 
-    for project in all_projects 
+    Ok(Json(DatabaseSessionsResponse { sessions }))
+}
+
+pub async fn kill_any_database_session_handler(
+    State(state): State<AppState>,
+    Path(session_id): Path<u64>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let usernames = database_service::get_all_database_usernames(&state.db_pool).await?;
+    database_service::kill_mariadb_session(&state.mariadb_pool, session_id, &usernames).await?;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Session killed successfully."))))
+}
+
+#[derive(Deserialize)]
+pub struct DatabaseDryRunQuery
+{
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Modifie les limites d'une base (ou renvoie seulement les instructions MariaDB avec `dry_run=true`).
+pub async fn update_database_limits_handler(
+    State(state): State<AppState>,
+    Path(db_id): Path<i32>,
+    Query(query): Query<DatabaseDryRunQuery>,
+    Json(limits): Json<DatabaseLimits>,
+) -> Result<Response, AppError>
+{
+    let database = database_service::get_database_by_id(&state.db_pool, db_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Database with ID {db_id} not found.")))?;
+
+    if query.dry_run
     {
-        if let Some(details) = docker_service::inspect_container_details(&state.docker_client, &project.container_name).await?
-            && let Some(container_state) = details.state
-                && let Some(is_running) = container_state.running
-                    && !is_running
-                        && let Some(finished_at_str) = container_state.finished_at
-                            && let Ok(stopped_at) = OffsetDateTime::parse(&finished_at_str, &Rfc3339)
-                            {
-                                let downtime_seconds = (now - stopped_at).as_seconds_f64() as i64;
-                                down_projects.push(DownProjectInfo 
-                                {
-                                    project: project.clone(),
-                                    stopped_at: finished_at_str,
-                                    downtime_seconds,
-                                });
-                            }
+        let statements = database_service::preview_database_limits(&database, limits).await?;
+        return Ok(Json(DatabaseDryRunResponse { dry_run: true, statements }).into_response());
     }
 
-    down_projects.sort_by(|a, b| b.downtime_seconds.cmp(&a.downtime_seconds));
+    let updated = database_service::update_database_limits(&state.db_pool, &state.mariadb_pool, &database, limits).await?;
+
+    Ok(Json(DatabaseLimitsResponse { database_id: updated.id, limits: updated.limits() }).into_response())
+}
+
+pub async fn list_ownerless_projects_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let projects = cleanup_service::list_ownerless_projects(&state.db_pool, state.config.owner_inactivity_days).await?;
+
+    Ok(Json(OwnerlessProjectsResponse
+    {
+        inactivity_days: state.config.owner_inactivity_days,
+        projects,
+    }))
+}
+
+#[derive(Deserialize, Default)]
+pub struct FlagForCleanupPayload
+{
+    reason: Option<String>,
+}
+
+/// Démarre le délai de grâce avant archivage et prévient les participants du projet.
+pub async fn flag_project_for_cleanup_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    payload: Option<Json<FlagForCleanupPayload>>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = project_service::get_project_by_id(&state.db_pool, project_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with ID {project_id} not found.")))?;
+    let Json(payload) = payload.unwrap_or_default();
+
+    let cleanup = cleanup_service::flag_project(
+        &state.db_pool,
+        project.id,
+        &claims.sub,
+        payload.reason.as_deref(),
+        state.config.cleanup_grace_days,
+    ).await?;
+    let archive_after = cleanup.archive_after.format(&Rfc3339).unwrap_or_default();
+
+    audit_service::record(
+        &state,
+        &claims.sub,
+        audit_service::ACTION_PROJECT_CLEANUP_FLAGGED,
+        Some(project.id),
+        Some(json!({ "owner": project.owner, "reason": cleanup.reason, "archive_after": archive_after })),
+    ).await;
+
+    let participants: Vec<String> = project_service::get_project_participants(&state.db_pool, project.id).await?
+        .into_iter()
+        .map(|participant| participant.login)
+        .collect();
+    notification_service::notify_project_members(
+        &state,
+        &project,
+        NotificationKind::ProjectCleanup,
+        SystemEvent::warning(format!(
+            "Project '{}' has been flagged for cleanup because its owner is no longer active. It will be archived after {}.",
+            project.name, archive_after
+        ))
+        .with_context(json!({ "source": "ownerless_cleanup", "archive_after": archive_after })),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(CleanupFlaggedResponse { cleanup, notified: participants })))
+}
+
+pub async fn cancel_project_cleanup_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = project_service::get_project_by_id(&state.db_pool, project_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with ID {project_id} not found.")))?;
+    let cleanup = cleanup_service::cancel_flag(&state.db_pool, project_id).await?;
 
-    Ok(Json(json!({ "down_projects": down_projects })))
+    audit_service::record(
+        &state,
+        &claims.sub,
+        audit_service::ACTION_PROJECT_CLEANUP_CANCELLED,
+        Some(project_id),
+        Some(json!({ "flagged_by": cleanup.flagged_by })),
+    ).await;
+
+    notification_service::notify_project_members(
+        &state,
+        &project,
+        NotificationKind::ProjectCleanup,
+        SystemEvent::info("The cleanup of this project has been cancelled.".to_string()),
+    ).await;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Cleanup cancelled."))))
+}
+
+/// Vérifie que chaque secret chiffré se déchiffre avec la clé configurée, sans renvoyer de valeur en clair.
+pub async fn verify_encryption_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    let report = crypto_audit_service::verify_all(&state.db_pool, &state.config.encryption_key).await?;
+    crypto_audit_service::notify_failures(&state, &report);
+    audit_service::record(
+        &state,
+        &claims.sub,
+        audit_service::ACTION_CRYPTO_VERIFIED,
+        None,
+        Some(json!({ "checked": report.checked, "failed": report.failed })),
+    ).await;
+    Ok(Json(report))
+}
+
+/// Tout ce qui est rattaché à un utilisateur, avec le jeton de confirmation de son départ.
+pub async fn get_user_footprint_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(login): Path<String>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let footprint = offboarding_service::get_footprint(&state, &login, &claims.sub).await?;
+    Ok(Json(footprint))
+}
+
+/// Départ d'un utilisateur : renvoie le rapport étape par étape, même incomplet.
+pub async fn offboard_user_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(login): Path<String>,
+    Json(payload): Json<OffboardPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let report = offboarding_service::offboard(&state, &login, &claims.sub, &payload.confirmation_token, payload.project.as_ref()).await?;
+    Ok(Json(report))
+}
+
+/// Période maximale d'un rapport d'utilisation, pour borner le coût des agrégations.
+const MAX_USAGE_REPORT_DAYS: i64 = 366;
+
+/// Historique des déploiements de tous les projets, du plus récent au plus ancien.
+pub async fn list_all_deployments_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DeploymentsQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let (page, per_page) = query.page()?;
+    let (limit, offset) = query.limit_offset()?;
+
+    let deployments = deployment_service::list_all_deployments(&state.db_pool, limit, offset).await?;
+    let (deployments, has_more) = deployment_handler::split_page(deployments, per_page);
+
+    Ok(Json(DeploymentPage { deployments, page, per_page, has_more }))
+}
+
+/// Période de l'historique des métriques affichée par défaut.
+const DEFAULT_METRICS_HISTORY_HOURS: i64 = 24;
+
+#[derive(Deserialize)]
+pub struct MetricsHistoryQuery
+{
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<time::OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    to: Option<time::OffsetDateTime>,
+    /// Pas des points, en secondes.
+    step: Option<i64>,
+}
+
+/// Historique des métriques de la plateforme, rééchantillonné (24 dernières heures par défaut).
+pub async fn get_metrics_history_handler(
+    State(state): State<AppState>,
+    Query(query): Query<MetricsHistoryQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let to = query.to.unwrap_or_else(time::OffsetDateTime::now_utc);
+    let from = query.from.unwrap_or(to - time::Duration::hours(DEFAULT_METRICS_HISTORY_HOURS));
+    if from >= to
+    {
+        return Err(AppError::BadRequest("from must be earlier than to.".to_string()));
+    }
+
+    let sample_interval_seconds = i64::try_from(state.config.metrics_history_interval_minutes.saturating_mul(60)).unwrap_or(i64::MAX);
+    let step_seconds = metrics_history_service::resolve_step((to - from).whole_seconds(), query.step, sample_interval_seconds)?;
+    let points = metrics_history_service::history(&state.db_pool, from, to, step_seconds).await?;
+
+    Ok(Json(MetricsHistoryResponse { from, to, step_seconds, points }))
+}
+
+#[derive(Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageReportFormat
+{
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Deserialize)]
+pub struct UsageReportQuery
+{
+    #[serde(with = "time::serde::rfc3339")]
+    from: time::OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    to: time::OffsetDateTime,
+    #[serde(default)]
+    format: UsageReportFormat,
+}
+
+/// Consommation par propriétaire sur une période (CPU, mémoire, heures de conteneur, stockage), en JSON ou CSV.
+pub async fn get_usage_report_handler(
+    State(state): State<AppState>,
+    Query(query): Query<UsageReportQuery>,
+) -> Result<Response, AppError>
+{
+    if query.from >= query.to
+    {
+        return Err(AppError::BadRequest("from must be earlier than to.".to_string()));
+    }
+    if query.to - query.from > time::Duration::days(MAX_USAGE_REPORT_DAYS)
+    {
+        return Err(AppError::BadRequest(format!("A usage report cannot span more than {MAX_USAGE_REPORT_DAYS} days.")));
+    }
+
+    let report = usage_service::usage_report(&state.db_pool, query.from, query.to).await?;
+
+    if query.format == UsageReportFormat::Csv
+    {
+        let filename = format!("attachment; filename=\"usage-{}-{}.csv\"", query.from.date(), query.to.date());
+        return Ok((
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()), (header::CONTENT_DISPOSITION, filename)],
+            usage_service::report_to_csv(&report),
+        ).into_response());
+    }
+
+    Ok(Json(report).into_response())
+}
+
+
+/// Vérifications de santé laissées au conteneur du selftest pour démarrer.
+const SELFTEST_HEALTH_ATTEMPTS: u32 = 30;
+/// Délai de la requête HTTP adressée au conteneur du selftest.
+const SELFTEST_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Vérifications exécutées sur le projet du selftest, dans l'ordre, jusqu'au premier échec.
+#[derive(Clone, Copy)]
+enum SelftestCheck
+{
+    Health,
+    HttpProbe,
+    Logs,
+    Metrics,
+}
+
+impl SelftestCheck
+{
+    const ALL: [Self; 4] = [Self::Health, Self::HttpProbe, Self::Logs, Self::Metrics];
+
+    const fn name(self) -> &'static str
+    {
+        match self
+        {
+            Self::Health => "health",
+            Self::HttpProbe => "http_probe",
+            Self::Logs => "logs",
+            Self::Metrics => "metrics",
+        }
+    }
+
+    async fn run(self, state: &AppState, container_name: &str) -> Result<(), String>
+    {
+        match self
+        {
+            Self::Health =>
+            {
+                let policy = blue_green::HealthCheckPolicy { max_attempts: SELFTEST_HEALTH_ATTEMPTS, ..blue_green::HealthCheckPolicy::from_config(&state.config) };
+                blue_green::wait_for_container_health(state, container_name, policy, None).await
+                    .map_err(|e| e.public_message())
+            }
+            Self::HttpProbe => probe_container_page(state, container_name).await,
+            Self::Logs => docker_service::get_container_logs(&state.docker_client, container_name, &LogsFilter::default()).await
+                .map(|_| ())
+                .map_err(|e| e.public_message()),
+            Self::Metrics => docker_service::get_container_metrics(&state.docker_client, container_name).await
+                .map(|_| ())
+                .map_err(|e| e.into_user_error().public_message()),
+        }
+    }
+}
+
+/// Déploie `SELFTEST_IMAGE` sous un nom réservé, vérifie sa page, ses logs et ses métriques puis purge tout.
+/// La purge est tentée dès que le projet a été créé, quel que soit le résultat des vérifications.
+pub async fn run_selftest_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    if let Some(previous) = project_service::get_projects_by_owner(&state.db_pool, project_service::SELFTEST_OWNER).await?.first()
+    {
+        return Err(AppError::BadRequest(format!(
+            "A previous selftest project (ID {}) still exists. Purge it before running a new selftest.", previous.id
+        )));
+    }
+
+    let image = state.config.selftest_image.clone();
+    tracing::info!("Deployment selftest with image '{}' started by '{}'.", image, claims.sub);
+
+    let started = Instant::now();
+    let mut steps = Vec::new();
+
+    let payload = DeployPayload::from_image(project_service::SELFTEST_PROJECT_NAME, &image);
+    let deployed = run_selftest_step(&mut steps, "deploy", async
+    {
+        perform_deploy(&state, project_service::SELFTEST_OWNER.to_string(), payload).await
+            .map_err(|e| e.public_message())
+    }).await;
+
+    match deployed
+    {
+        Some(deployed) =>
+        {
+            let project = deployed.project;
+            let mut checks_passed = true;
+            for check in SelftestCheck::ALL
+            {
+                if !checks_passed
+                {
+                    steps.push(skipped_step(check.name()));
+                    continue;
+                }
+                checks_passed = run_selftest_step(&mut steps, check.name(), check.run(&state, &project.container_name)).await.is_some();
+            }
+
+            run_selftest_step(&mut steps, "purge", async
+            {
+                perform_purge(&state, &project, true).await.map_err(|e| e.public_message())
+            }).await;
+        }
+        None =>
+        {
+            steps.extend(SelftestCheck::ALL.iter().map(|check| skipped_step(check.name())));
+            steps.push(skipped_step("purge"));
+        }
+    }
+
+    let passed = steps.iter().all(|step| step.outcome == SelftestOutcome::Passed);
+    let duration_ms = elapsed_ms(started);
+    tracing::info!("Deployment selftest finished in {} ms (passed: {}).", duration_ms, passed);
+
+    audit_service::record(
+        &state,
+        &claims.sub,
+        audit_service::ACTION_SELFTEST_RUN,
+        None,
+        Some(json!({ "image": image, "passed": passed })),
+    ).await;
+
+    Ok(Json(SelftestResponse { passed, image, duration_ms, steps }))
+}
+
+async fn run_selftest_step<T>(
+    steps: &mut Vec<SelftestStep>,
+    step: &'static str,
+    action: impl Future<Output = Result<T, String>>,
+) -> Option<T>
+{
+    let started = Instant::now();
+    let result = action.await;
+    let duration_ms = elapsed_ms(started);
+
+    match result
+    {
+        Ok(value) =>
+        {
+            steps.push(SelftestStep { step, outcome: SelftestOutcome::Passed, duration_ms, message: None });
+            Some(value)
+        }
+        Err(message) =>
+        {
+            tracing::warn!("Selftest step '{}' failed: {}", step, message);
+            steps.push(SelftestStep { step, outcome: SelftestOutcome::Failed, duration_ms, message: Some(message) });
+            None
+        }
+    }
+}
+
+const fn skipped_step(step: &'static str) -> SelftestStep
+{
+    SelftestStep { step, outcome: SelftestOutcome::Skipped, duration_ms: 0, message: None }
+}
+
+/// Requête directe au conteneur sur le réseau Docker de l'instance, sans passer par Traefik.
+async fn probe_container_page(state: &AppState, container_name: &str) -> Result<(), String>
+{
+    let response = state.http_client
+        .get(format!("http://{container_name}/"))
+        .timeout(SELFTEST_PROBE_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("The project page could not be reached: {e}"))?;
+
+    if !response.status().is_success()
+    {
+        return Err(format!("The project page answered with HTTP status {}.", response.status()));
+    }
+
+    Ok(())
+}
+
+fn elapsed_ms(started: Instant) -> u64
+{
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
 }
\ No newline at end of file
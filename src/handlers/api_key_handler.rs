@@ -0,0 +1,164 @@
+use std::time::Instant;
+
+use axum::
+{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::
+{
+    authz::{self, AccessContext, ProjectMutation, RequiredRole},
+    client_ip::ClientIp,
+    error::AppError,
+    handlers::project_handler::{perform_image_update, perform_rebuild},
+    model::project::ProjectSourceType,
+    services::{api_key_service, audit_service, project_service, validation_service},
+    state::AppState,
+};
+
+/// En-tête portant la clé d'API d'un projet.
+const API_KEY_HEADER: &str = "x-hangar-key";
+
+#[derive(Deserialize)]
+pub struct HookRedeployPayload
+{
+    /// Nouveau tag de l'image (projets `direct` uniquement).
+    tag: Option<String>,
+}
+
+pub async fn list_api_keys_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Owner).await?;
+
+    let keys = api_key_service::list_keys(&state.db_pool, project.id).await?;
+
+    Ok(Json(json!({ "api_keys": keys })))
+}
+
+pub async fn create_api_key_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageApiKeys).await?;
+
+    let created = api_key_service::create_key(&state.db_pool, project.id, &ctx.login).await?;
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_API_KEY_CREATED,
+        Some(project.id),
+        Some(json!({ "key_id": created.key.id, "key_prefix": created.key.key_prefix })),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+pub async fn revoke_api_key_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, key_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageApiKeys).await?;
+
+    if !api_key_service::revoke_key(&state.db_pool, project.id, key_id).await?
+    {
+        return Err(AppError::NotFound(format!("API key with ID {key_id} not found.")));
+    }
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_API_KEY_REVOKED,
+        Some(project.id),
+        Some(json!({ "key_id": key_id })),
+    ).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Deploy hook authentifié par une clé d'API de projet.
+///
+/// Ne permet que la reconstruction (source `github`) ou la mise à jour de l'image
+/// (source `direct`, avec un nouveau tag optionnel) du projet de la clé.
+/// Endpoint: POST /`api/hooks/projects/{project_id}/redeploy`
+pub async fn hook_redeploy_handler(
+    State(state): State<AppState>,
+    Path(project_id): Path<i32>,
+    client_ip: ClientIp,
+    headers: HeaderMap,
+    payload: Option<Json<HookRedeployPayload>>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let key = headers.get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| AppError::Unauthorized("Missing X-Hangar-Key header.".to_string()))?;
+
+    let Some(api_key) = api_key_service::verify_key(&state.db_pool, project_id, key).await? else
+    {
+        warn!("Rejected deploy hook call for project ID {} with an invalid API key from {}", project_id, client_ip);
+        return Err(AppError::Unauthorized("Invalid API key.".to_string()));
+    };
+
+    if !api_key_service::check_rate_limit(&state.hook_calls, api_key.id, state.config.hook_max_calls_per_hour, Instant::now())
+    {
+        warn!("Deploy hook rate limit reached for API key ID {} (project ID {}, client {})", api_key.id, project_id, client_ip);
+        return Err(AppError::TooManyRequests("This API key has reached its hourly deploy hook limit.".to_string()));
+    }
+
+    let project = project_service::get_project_by_id(&state.db_pool, project_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with ID {project_id} not found.")))?;
+
+    let tag = payload.and_then(|Json(payload)| payload.tag);
+    let actor = format!("api-key:{}", api_key.key_prefix);
+
+    audit_service::record_from(
+        &state,
+        &api_key.created_by,
+        client_ip,
+        audit_service::ACTION_PROJECT_HOOK_REDEPLOY,
+        Some(project.id),
+        Some(json!({ "key_id": api_key.id, "tag": tag })),
+    ).await;
+
+    info!("Deploy hook triggered for project '{}' with API key ID {}", project.name, api_key.id);
+
+    match project.source
+    {
+        ProjectSourceType::Github =>
+        {
+            if tag.is_some()
+            {
+                return Err(AppError::BadRequest("A tag can only be provided for projects deployed from an image.".to_string()));
+            }
+            perform_rebuild(&state, &project, &actor, None).await
+        }
+        ProjectSourceType::Direct =>
+        {
+            let reference = validation_service::validate_image_url(&project.source_url)?;
+            let reference = match tag
+            {
+                Some(tag) => reference.with_tag(&tag)
+                    .map_err(|e| AppError::BadRequest(format!("Invalid image tag: {e}.")))?,
+                None => reference,
+            };
+            perform_image_update(&state, &project, &actor, &reference.to_string()).await
+        }
+        ProjectSourceType::Upload =>
+        {
+            Err(AppError::BadRequest("Projects deployed from an uploaded archive are redeployed with a new upload.".to_string()))
+        }
+    }
+}
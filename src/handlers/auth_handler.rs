@@ -9,7 +9,7 @@ use serde::Deserialize;
 use serde_json::json;
 use time::OffsetDateTime;
 
-use crate::{error::AppError, state::AppState};
+use crate::{client_ip::ClientIp, error::AppError, state::AppState};
 use crate::services::jwt::Claims;
 
 #[derive(Debug, Deserialize)]
@@ -20,13 +20,18 @@ pub struct AuthCallbackQuery
 
 pub async fn auth_callback_handler(State(state): State<AppState>, 
                                    Query(query): Query<AuthCallbackQuery>, 
+                                   client_ip: ClientIp,
                                    jar: CookieJar) -> Result<impl IntoResponse, AppError>
 {
     let service = format!("{}/auth/callback", state.config.public_address);
 
     let url = format!("{}?service={}&ticket={}", state.config.cas_validation_url, service, &query.ticket);
     tracing::debug!("Validating CAS ticket at URL: {}", url);
-    let user = crate::services::auth_service::validate_ticket(&url, &state.http_client).await?;
+    let user = crate::services::auth_service::validate_ticket(&url, &state.http_client).await
+        .inspect_err(|e| tracing::warn!("CAS ticket validation failed for client {}: {}", client_ip, e))?;
+    tracing::info!("User '{}' logged in from {}", user.login, client_ip);
+
+    crate::services::user_service::record_login(&state.db_pool, &user).await;
 
     let is_admin = state.config.admin_logins.contains(&user.login);
 
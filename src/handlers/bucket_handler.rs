@@ -0,0 +1,48 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+use crate::
+{
+    authz::{self, AccessContext, ProjectMutation},
+    error::{AppError, BucketErrorCode},
+    services::object_storage_service,
+    state::AppState,
+};
+
+pub async fn create_project_bucket_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let storage = state.config.object_storage.as_ref().ok_or(BucketErrorCode::NotConfigured)?;
+
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::CreateBucket).await?;
+
+    let (bucket, secret_key) = object_storage_service::provision_bucket(
+        &state.http_client,
+        storage,
+        &state.db_pool,
+        &state.config.app_prefix,
+        project.id,
+        &state.config.encryption_key,
+    ).await?;
+
+    let response = json!({
+        "message": "Bucket created successfully. Credentials will be injected as HANGAR_S3_* variables at the next container recreation.",
+        "bucket": {
+            "id": bucket.id,
+            "bucket_name": bucket.bucket_name,
+            "access_key": bucket.access_key,
+            "secret_key": secret_key,
+            "endpoint": storage.public_endpoint,
+            "region": storage.region,
+        }
+    });
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
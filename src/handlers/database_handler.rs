@@ -1,129 +1,447 @@
 use axum::
 {
+    body::Body,
     extract::{Path, State},
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Json},
 };
+use serde::Deserialize;
 use serde_json::json;
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
 use crate::
 {
+    authz::{self, AccessContext, ProjectMutation, RequiredRole},
     error::AppError,
-    services::{database_service, jwt::Claims, project_service},
+    handlers::project_handler,
+    model::database::{Database, DatabaseDetailsResponse},
+    model::response::{ActionResponse, CreatedDatabase, DatabaseBackupsResponse, DatabaseCreatedResponse, DatabasePasswordRotatedResponse, DatabaseResponse, DatabaseSessionsResponse},
+    services::{audit_service, backup_service, database_service},
+    sse::types::{SseEvent, SystemEvent},
     state::AppState,
 };
 
 pub async fn create_database_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
 ) -> Result<impl IntoResponse, AppError>
 {
     let (db_record, password) = database_service::provision_database(
         &state.db_pool,
         &state.mariadb_pool,
-        &claims.sub,
+        &ctx.login,
         &state.config.encryption_key,
+        database_service::default_limits(&state.config),
     ).await?;
 
-    let response = json!({
-        "message": "Database created successfully.",
-        "database": {
-            "id": db_record.id,
-            "database_name": db_record.database_name,
-            "username": db_record.username,
-            "password": password,
-            "host": state.config.mariadb_public_host,
-            "port": state.config.mariadb_public_port,
-        }
-    });
+    let response = DatabaseCreatedResponse
+    {
+        message: "Database created successfully.".to_string(),
+        database: CreatedDatabase
+        {
+            id: db_record.id,
+            limits: db_record.limits(),
+            database_name: db_record.database_name,
+            username: db_record.username,
+            password,
+            host: state.config.mariadb_public_host.clone(),
+            port: state.config.mariadb_public_port,
+        },
+    };
 
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+/// Base de l'appelant, sans son mot de passe (voir [`reveal_database_credentials_handler`]).
 pub async fn get_my_database_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
 ) -> Result<impl IntoResponse, AppError>
 {
-    match database_service::get_database_by_owner(&state.db_pool, &claims.sub).await?
+    match database_service::get_database_by_owner(&state.db_pool, &ctx.login).await?
     {
         Some(db) =>
         {
-            let details = database_service::create_db_details_response(db, &state.config, &state.config.encryption_key)?;
-            Ok(Json(json!({ "database": details })))
+            let details = database_service::create_db_details_response(db, &state.config, &state.config.encryption_key, false)?;
+            Ok(Json(DatabaseResponse { database: details }))
         }
         None => Err(AppError::NotFound("No database found for the current user.".to_string())),
     }
 }
 
+/// Révèle les identifiants de la base, mot de passe compris ; chaque révélation est tracée dans le journal d'audit.
+pub async fn reveal_database_credentials_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(db_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+    let details = reveal_credentials(&state, &ctx, database).await?;
+
+    Ok(Json(DatabaseResponse { database: details }))
+}
+
+/// Comme [`reveal_database_credentials_handler`], pour la base liée au projet ; les participants n'y ont
+/// droit que si `DB_CREDENTIALS_FOR_PARTICIPANTS` est activé.
+pub async fn reveal_linked_database_credentials_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let (project, role) = authz::load_project_with_role(&state, &ctx, project_id, RequiredRole::Participant).await?;
+    if !authz::can_reveal_db_credentials(role, state.config.db_credentials_for_participants)
+    {
+        return Err(AppError::Forbidden("Only the project owner can reveal the database credentials.".to_string()));
+    }
+
+    let database = database_service::get_database_by_project_id(&state.db_pool, project.id).await?
+        .ok_or(AppError::NotFound("No database linked to this project.".to_string()))?;
+    let details = reveal_credentials(&state, &ctx, database).await?;
+
+    Ok(Json(DatabaseResponse { database: details }))
+}
+
+async fn reveal_credentials(state: &AppState, ctx: &AccessContext, database: Database) -> Result<DatabaseDetailsResponse, AppError>
+{
+    let (db_id, project_id) = (database.id, database.project_id);
+    let details = database_service::create_db_details_response(database, &state.config, &state.config.encryption_key, true)?;
+
+    audit_service::record(
+        state,
+        &ctx.login,
+        audit_service::ACTION_DB_CREDENTIALS_REVEALED,
+        project_id,
+        Some(json!({ "database_id": db_id })),
+    ).await;
+
+    Ok(details)
+}
+
 pub async fn delete_my_database_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(db_id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    database_service::deprovision_database(
-        &state.db_pool,
-        &state.mariadb_pool,
-        db_id,
-        &claims.sub,
-        claims.is_admin
-    ).await?;
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+
+    database_service::deprovision_database(&state.db_pool, &state.mariadb_pool, &database).await?;
 
-    Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Database deleted successfully."}))))
+    Ok((StatusCode::OK, Json(ActionResponse::success("Database deleted successfully."))))
 }
 
 pub async fn delete_linked_database_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    project_service::get_project_by_id_and_owner(&state.db_pool, project_id, &claims.sub, claims.is_admin).await?
-    .ok_or(AppError::NotFound("Project not found or you are not the owner.".to_string()))?;
+    authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageDatabase).await?;
 
     let db = database_service::get_database_by_project_id(&state.db_pool, project_id).await?
         .ok_or(AppError::NotFound("No database linked to this project.".to_string()))?;
 
-    database_service::deprovision_database(
-        &state.db_pool,
-        &state.mariadb_pool,
-        db.id,
-        &db.owner_login,
-        claims.is_admin,
-    ).await?;
+    database_service::deprovision_database(&state.db_pool, &state.mariadb_pool, &db).await?;
 
-    Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Linked database deleted successfully."}))))
+    Ok((StatusCode::OK, Json(ActionResponse::success("Linked database deleted successfully."))))
 }
 
 pub async fn link_database_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path((project_id, db_id)): Path<(i32, i32)>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let project = project_service::get_project_by_id_and_owner(
-        &state.db_pool, project_id, &claims.sub, claims.is_admin
-    ).await?.ok_or(AppError::NotFound("Project not found or you are not the owner.".to_string()))?;
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageDatabase).await?;
 
-    let database = database_service::get_database_by_id_and_owner(
-        &state.db_pool, db_id, &claims.sub, claims.is_admin
-    ).await?.ok_or(AppError::NotFound("Database not found or you are not the owner.".to_string()))?;
+    let database = authz::load_database(&state, &ctx, db_id).await?;
 
     database_service::link_database_to_project(&state.db_pool, database.id, project.id, &database.owner_login).await?;
 
-    Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Database linked to project successfully."}))))
+    Ok((StatusCode::OK, Json(ActionResponse::success("Database linked to project successfully."))))
 }
 
 pub async fn unlink_database_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let project = project_service::get_project_by_id_and_owner(&state.db_pool, project_id, &claims.sub, claims.is_admin).await?
-    .ok_or(AppError::NotFound("Project not found or you are not the owner.".to_string()))?;
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageDatabase).await?;
 
     database_service::unlink_database_from_project(&state.db_pool, project_id, &project.owner).await?;
     
-    Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Database unlinked from project successfully."}))))
-}
\ No newline at end of file
+    Ok((StatusCode::OK, Json(ActionResponse::success("Database unlinked from project successfully."))))
+}
+
+pub async fn list_database_sessions_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(db_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+
+    let sessions = database_service::list_mariadb_sessions(&state.mariadb_pool, std::slice::from_ref(&database.username)).await?;
+
+    Ok(Json(DatabaseSessionsResponse { sessions }))
+}
+
+pub async fn kill_database_session_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((db_id, session_id)): Path<(i32, u64)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+
+    database_service::kill_mariadb_session(&state.mariadb_pool, session_id, std::slice::from_ref(&database.username)).await?;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Session killed successfully."))))
+}
+
+#[derive(Deserialize)]
+pub struct RestoreBackupPayload
+{
+    /// Le nom de la base, ressaisi par l'utilisateur pour confirmer l'écrasement.
+    confirm_database_name: String,
+}
+
+pub async fn list_database_backups_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(db_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+
+    let backups = backup_service::list_backups(&state.db_pool, database.id).await?;
+
+    Ok(Json(DatabaseBackupsResponse { backups }))
+}
+
+pub async fn restore_database_backup_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((db_id, backup_id)): Path<(i32, i32)>,
+    Json(payload): Json<RestoreBackupPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+
+    if payload.confirm_database_name != database.database_name
+    {
+        return Err(AppError::BadRequest("Confirmation does not match the database name.".to_string()));
+    }
+
+    let backup = backup_service::get_backup(&state.db_pool, database.id, backup_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Backup with ID {backup_id} not found.")))?;
+
+    backup_service::restore_backup(&state, &database, &backup).await?;
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_DB_BACKUP_RESTORED,
+        database.project_id,
+        Some(json!({ "database_id": database.id, "backup_id": backup.id })),
+    ).await;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Backup restored successfully."))))
+}
+
+#[derive(Deserialize)]
+pub struct ResetDatabasePayload
+{
+    /// Le nom de la base, ressaisi par l'utilisateur pour confirmer l'effacement de son contenu.
+    confirm_database_name: String,
+}
+
+/// Télécharge un dump SQL de la base, relayé depuis mysqldump sans être mis en mémoire.
+/// Le corps est compressé en gzip par la couche de compression quand le client l'accepte.
+/// Endpoint: GET /`api/databases/{db_id}/dump`
+pub async fn download_database_dump_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(db_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+
+    let dump = backup_service::stream_dump(&state, &database).await?;
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_DB_DUMPED,
+        database.project_id,
+        Some(json!({ "database_id": database.id })),
+    ).await;
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        backup_service::dump_file_name(&database.database_name, OffsetDateTime::now_utc())
+    );
+    Ok((
+        [(header::CONTENT_TYPE, "application/sql".to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        Body::from_stream(dump),
+    ))
+}
+
+/// Efface tout le contenu de la base en conservant son utilisateur, ses droits et ses identifiants.
+pub async fn reset_database_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(db_id): Path<i32>,
+    Json(payload): Json<ResetDatabasePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+
+    if payload.confirm_database_name != database.database_name
+    {
+        return Err(AppError::BadRequest("Confirmation does not match the database name.".to_string()));
+    }
+
+    let cooldown = Duration::from_secs(state.config.database_reset_cooldown_seconds);
+    if let Err(remaining) = database_service::claim_reset_slot(&state.database_resets, database.id, cooldown, Instant::now())
+    {
+        return Err(AppError::TooManyRequests(format!(
+            "This database was reset recently. Try again in {} seconds.", remaining.as_secs().max(1)
+        )));
+    }
+
+    database_service::reset_database(&state.mariadb_pool, &database).await?;
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_DB_RESET,
+        database.project_id,
+        Some(json!({ "database_id": database.id })),
+    ).await;
+
+    if let Some(project_id) = database.project_id
+    {
+        let event = SystemEvent::warning(format!("The database '{}' was reset by '{}': all its tables were deleted.", database.database_name, ctx.login))
+            .with_context(json!({ "database_id": database.id }));
+        state.sse_manager.emit_to_project(project_id, SseEvent::System(event)).await;
+    }
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Database reset successfully."))))
+}
+
+/// Remplace le mot de passe d'une base, par exemple après une fuite dans un dépôt public.
+/// Le projet lié qui reçoit les identifiants est recréé avec le nouveau mot de passe.
+/// Endpoint: POST /`api/databases/{db_id}/rotate-password`
+pub async fn rotate_database_password_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(db_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let database = authz::load_database(&state, &ctx, db_id).await?;
+
+    let password = database_service::rotate_database_password(
+        &state.db_pool,
+        &state.mariadb_pool,
+        &database,
+        &state.config.encryption_key,
+    ).await?;
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_DB_PASSWORD_ROTATED,
+        database.project_id,
+        Some(json!({ "database_id": database.id })),
+    ).await;
+
+    let project_redeployed = match database.project_id
+    {
+        Some(project_id) => project_handler::redeploy_with_rotated_credentials(&state, &ctx.login, project_id).await,
+        None => false,
+    };
+
+    Ok((StatusCode::OK, Json(DatabasePasswordRotatedResponse
+    {
+        message: "Database password rotated successfully. Update it wherever the previous one was used.".to_string(),
+        password,
+        project_redeployed,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::test_support;
+
+    fn ctx(login: &str) -> AccessContext
+    {
+        AccessContext { login: login.to_string(), is_admin: false }
+    }
+
+    async fn json_body(response: impl IntoResponse) -> serde_json::Value
+    {
+        let bytes = to_bytes(response.into_response().into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    async fn reveal_count(pool: &PgPool) -> i64
+    {
+        sqlx::query_scalar("SELECT COUNT(*) FROM audit_logs WHERE action = $1")
+            .bind(audit_service::ACTION_DB_CREDENTIALS_REVEALED)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_only_explicit_reveal_is_audited(pool: PgPool)
+    {
+        let project_id = test_support::insert_project(&pool, "demo", "alice", &["bob"]).await;
+        let db_id = test_support::insert_database(&pool, "alice", Some(project_id), "s3cret").await;
+        let state = test_support::state_with(test_support::config(), pool.clone(), test_support::UNREACHABLE_DOCKER);
+
+        let mine = json_body(get_my_database_handler(State(state.clone()), ctx("alice")).await.unwrap()).await;
+        let project = json_body(project_handler::get_project_details_handler(State(state.clone()), ctx("alice"), Path(project_id)).await.unwrap()).await;
+        assert_eq!(mine["database"]["username"], "alice_user");
+        assert!(mine["database"]["password"].is_null());
+        assert_eq!(project["project"]["database"]["username"], "alice_user");
+        assert!(project["project"]["database"]["password"].is_null());
+        assert_eq!(reveal_count(&pool).await, 0);
+
+        let revealed = json_body(reveal_database_credentials_handler(State(state.clone()), ctx("alice"), Path(db_id)).await.unwrap()).await;
+        assert_eq!(revealed["database"]["password"], "s3cret");
+        assert_eq!(reveal_count(&pool).await, 1);
+
+        let linked = json_body(reveal_linked_database_credentials_handler(State(state.clone()), ctx("alice"), Path(project_id)).await.unwrap()).await;
+        assert_eq!(linked["database"]["password"], "s3cret");
+        assert_eq!(reveal_count(&pool).await, 2);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_participant_reveal_requires_opt_in(pool: PgPool)
+    {
+        let project_id = test_support::insert_project(&pool, "demo", "alice", &["bob"]).await;
+        test_support::insert_database(&pool, "alice", Some(project_id), "s3cret").await;
+        let state = test_support::state_with(test_support::config(), pool.clone(), test_support::UNREACHABLE_DOCKER);
+
+        let refused = reveal_linked_database_credentials_handler(State(state), ctx("bob"), Path(project_id)).await;
+        assert!(matches!(refused, Err(AppError::Forbidden(_))));
+        assert_eq!(reveal_count(&pool).await, 0);
+
+        let config = crate::config::Config { db_credentials_for_participants: true, ..test_support::config() };
+        let state = test_support::state_with(config, pool.clone(), test_support::UNREACHABLE_DOCKER);
+        let revealed = json_body(reveal_linked_database_credentials_handler(State(state), ctx("bob"), Path(project_id)).await.unwrap()).await;
+        assert_eq!(revealed["database"]["password"], "s3cret");
+        assert_eq!(reveal_count(&pool).await, 1);
+    }
+}
@@ -0,0 +1,152 @@
+use axum::
+{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use tracing::info;
+
+use crate::
+{
+    authz::{self, AccessContext, ProjectMutation, RequiredRole},
+    error::AppError,
+    handlers::project_handler::perform_retry,
+    model::{deployment::{DeploymentResponse, DeploymentStatus}, response::DeploymentPage},
+    services::deployment_service,
+    state::AppState,
+};
+
+const DEFAULT_DEPLOYMENTS_PER_PAGE: u32 = 20;
+const MAX_DEPLOYMENTS_PER_PAGE: u32 = 100;
+
+/// Pagination de l'historique des déploiements, du plus récent au plus ancien.
+#[derive(Deserialize)]
+pub struct DeploymentsQuery
+{
+    /// Numéro de page, à partir de 1.
+    page: Option<u32>,
+    /// 20 par défaut, 100 au maximum.
+    per_page: Option<u32>,
+}
+
+impl DeploymentsQuery
+{
+    /// Page et taille de page validées.
+    pub(crate) fn page(&self) -> Result<(u32, u32), AppError>
+    {
+        let page = self.page.unwrap_or(1);
+        let per_page = self.per_page.unwrap_or(DEFAULT_DEPLOYMENTS_PER_PAGE);
+
+        if page == 0
+        {
+            return Err(AppError::BadRequest("page starts at 1.".to_string()));
+        }
+        if per_page == 0 || per_page > MAX_DEPLOYMENTS_PER_PAGE
+        {
+            return Err(AppError::BadRequest(format!("per_page must be between 1 and {MAX_DEPLOYMENTS_PER_PAGE}.")));
+        }
+
+        Ok((page, per_page))
+    }
+
+    /// `LIMIT` et `OFFSET` de la page ; une ligne de plus est demandée pour savoir s'il en reste.
+    pub(crate) fn limit_offset(&self) -> Result<(i64, i64), AppError>
+    {
+        let (page, per_page) = self.page()?;
+        Ok((i64::from(per_page) + 1, i64::from(page - 1) * i64::from(per_page)))
+    }
+}
+
+/// Retire la ligne supplémentaire de la page et indique s'il existe une page suivante.
+pub(crate) fn split_page<T>(mut rows: Vec<T>, per_page: u32) -> (Vec<T>, bool)
+{
+    let per_page = per_page as usize;
+    let has_more = rows.len() > per_page;
+    rows.truncate(per_page);
+    (rows, has_more)
+}
+
+pub async fn list_deployments_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Query(query): Query<DeploymentsQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let (page, per_page) = query.page()?;
+    let (limit, offset) = query.limit_offset()?;
+
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+
+    let deployments = deployment_service::list_deployments(&state.db_pool, project.id, limit, offset).await?;
+    let (deployments, has_more) = split_page(deployments, per_page);
+
+    let mut response = Vec::with_capacity(deployments.len());
+    for deployment in deployments
+    {
+        let resume_from = deployment_service::resolve_resume_stage(&state, &deployment).await;
+        response.push(DeploymentResponse { deployment, resume_from });
+    }
+
+    Ok(Json(DeploymentPage { deployments: response, page, per_page, has_more }))
+}
+
+/// Relance un déploiement échoué, à partir de l'étape échouée si ses artefacts sont encore présents.
+/// Endpoint: POST /`api/deployments/{deployment_id}/retry`
+pub async fn retry_deployment_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(deployment_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let deployment = deployment_service::get_deployment(&state.db_pool, deployment_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Deployment with ID {deployment_id} not found.")))?;
+
+    let project = authz::load_project_for(&state, &ctx, deployment.project_id, ProjectMutation::RetryDeployment).await?;
+
+    if deployment.status != DeploymentStatus::Failed
+    {
+        return Err(AppError::BadRequest("Only failed deployments can be retried.".to_string()));
+    }
+
+    if !deployment.kind.is_retryable()
+    {
+        return Err(AppError::BadRequest("Only image updates and rebuilds can be retried.".to_string()));
+    }
+
+    let resume = deployment_service::resolve_resume_stage(&state, &deployment).await;
+    info!(
+        "User '{}' retrying deployment ID {} of project '{}' (resume from: {:?})",
+        ctx.login, deployment.id, project.name, resume
+    );
+
+    perform_retry(&state, &project, &deployment, resume, &ctx.login).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Uri;
+
+    use super::*;
+
+    fn query(uri: &str) -> DeploymentsQuery
+    {
+        Query::<DeploymentsQuery>::try_from_uri(&uri.parse::<Uri>().unwrap()).unwrap().0
+    }
+
+    #[test]
+    fn test_pages_request_one_extra_row()
+    {
+        assert_eq!(query("/").limit_offset().unwrap(), (21, 0));
+        assert_eq!(query("/?page=3&per_page=50").limit_offset().unwrap(), (51, 100));
+        assert!(query("/?page=0").page().is_err());
+        assert!(query("/?per_page=101").page().is_err());
+    }
+
+    #[test]
+    fn test_split_page_detects_next_page()
+    {
+        assert_eq!(split_page(vec![1, 2, 3], 2), (vec![1, 2], true));
+        assert_eq!(split_page(vec![1, 2], 2), (vec![1, 2], false));
+    }
+}
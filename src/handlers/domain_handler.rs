@@ -0,0 +1,167 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::info;
+
+use crate::
+{
+    authz::{self, AccessContext, ProjectMutation, RequiredRole},
+    error::AppError,
+    handlers::project_handler,
+    model::{domain::ProjectDomain, project::Project, response::{DomainChangeResponse, ProjectDomainListResponse}},
+    services::{audit_service, domain_service, validation_service},
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct AddDomainPayload
+{
+    fqdn: String,
+}
+
+fn primary_hostname(state: &AppState, project: &Project) -> String
+{
+    format!("{}.{}", project.name, state.config.app_domain_suffix)
+}
+
+async fn load_domain(state: &AppState, project_id: i32, domain_id: i32) -> Result<ProjectDomain, AppError>
+{
+    domain_service::get_domain(&state.db_pool, project_id, domain_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Domain with ID {domain_id} not found.")))
+}
+
+/// Domaines personnalisés du projet, avec les enregistrements DNS attendus pour les vérifier.
+pub async fn list_domains_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Owner).await?;
+    let hostname = primary_hostname(&state, &project);
+
+    let domains: Vec<_> = domain_service::list_domains(&state.db_pool, project.id).await?
+        .into_iter()
+        .map(|domain| domain_service::with_verification(domain, &hostname))
+        .collect();
+
+    Ok(Json(ProjectDomainListResponse { domains }))
+}
+
+/// Ajoute un domaine non vérifié : il n'est servi qu'une fois sa possession prouvée.
+pub async fn add_domain_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<AddDomainPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let fqdn = validation_service::validate_custom_domain(&payload.fqdn, &state.config.app_domain_suffix)?;
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageDomains).await?;
+
+    let domain = domain_service::add_domain(&state.db_pool, project.id, &fqdn).await?;
+    info!("User '{}' added domain '{}' to project '{}'", ctx.login, domain.fqdn, project.name);
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_DOMAIN_ADDED,
+        Some(project.id),
+        Some(json!({ "domain_id": domain.id, "fqdn": domain.fqdn })),
+    ).await;
+
+    let hostname = primary_hostname(&state, &project);
+    Ok((StatusCode::CREATED, Json(domain_service::with_verification(domain, &hostname))))
+}
+
+/// Supprime un domaine. Le conteneur est recréé si le domaine était servi.
+pub async fn remove_domain_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, domain_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageDomains).await?;
+
+    let domain = domain_service::remove_domain(&state.db_pool, project.id, domain_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Domain with ID {domain_id} not found.")))?;
+    info!("User '{}' removed domain '{}' from project '{}'", ctx.login, domain.fqdn, project.name);
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_DOMAIN_REMOVED,
+        Some(project.id),
+        Some(json!({ "domain_id": domain.id, "fqdn": domain.fqdn })),
+    ).await;
+
+    let project_redeployed = domain.verified && project_handler::redeploy_with_domains(&state, &ctx.login, project.id).await;
+
+    Ok(Json(DomainChangeResponse { domain, project_redeployed }))
+}
+
+/// Vérifie le DNS du domaine puis recrée le conteneur pour le servir.
+pub async fn verify_domain_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, domain_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageDomains).await?;
+    let domain = load_domain(&state, project.id, domain_id).await?;
+
+    if !domain.verified
+    {
+        domain_service::check_dns(&state, &domain, &primary_hostname(&state, &project)).await?;
+    }
+
+    mark_verified_and_redeploy(&state, &ctx, &project, &domain, false).await
+}
+
+/// Valide un domaine sans vérification DNS (domaine géré par l'école, propagation en cours...).
+pub async fn force_verify_domain_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, domain_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageDomains).await?;
+    let domain = load_domain(&state, project.id, domain_id).await?;
+    info!("Admin '{}' force-verifying domain '{}' of project '{}'", ctx.login, domain.fqdn, project.name);
+
+    mark_verified_and_redeploy(&state, &ctx, &project, &domain, true).await
+}
+
+async fn mark_verified_and_redeploy(
+    state: &AppState,
+    ctx: &AccessContext,
+    project: &Project,
+    domain: &ProjectDomain,
+    forced: bool,
+) -> Result<Json<DomainChangeResponse>, AppError>
+{
+    if domain.verified
+    {
+        return Ok(Json(DomainChangeResponse { domain: domain.clone(), project_redeployed: false }));
+    }
+
+    let domain = domain_service::mark_verified(&state.db_pool, domain.id).await?;
+    info!("Domain '{}' of project '{}' verified", domain.fqdn, project.name);
+
+    audit_service::record(
+        state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_DOMAIN_VERIFIED,
+        Some(project.id),
+        Some(json!({ "domain_id": domain.id, "fqdn": domain.fqdn, "forced": forced })),
+    ).await;
+
+    let project_redeployed = project_handler::redeploy_with_domains(state, &ctx.login, project.id).await;
+
+    Ok(Json(DomainChangeResponse { domain, project_redeployed }))
+}
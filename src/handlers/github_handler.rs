@@ -0,0 +1,53 @@
+use axum::{extract::{Query, State}, response::{IntoResponse, Json}};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::
+{
+    error::{AppError, ProjectErrorCode},
+    handlers::project_handler::get_repository_token,
+    logging::log_safe,
+    safe_http::SafeHttpPolicy,
+    services::{github_service, jwt::Claims},
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct BranchesQuery
+{
+    repo_url: String,
+}
+
+/// Branches d'un dépôt GitHub, pour choisir celle à déployer. Un dépôt privé est lu avec le jeton
+/// de la GitHub App installée par son propriétaire.
+/// Endpoint: GET /api/github/branches?repo_url=...
+pub async fn list_branches_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<BranchesQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let (owner, repo) = github_service::extract_repo_owner_and_name(&query.repo_url).await?;
+    let policy = SafeHttpPolicy::new(state.config.safe_http_allowed_hosts.clone());
+
+    debug!("User '{}' lists the branches of '{}/{}'", claims.sub, log_safe(&owner), log_safe(&repo));
+
+    let branches = match github_service::list_repo_branches(&policy, None, &owner, &repo).await
+    {
+        Ok(branches) => branches,
+        Err(public_error) =>
+        {
+            debug!("Public listing of '{}/{}' failed ({}), trying the GitHub App installation", log_safe(&owner), log_safe(&repo), public_error);
+            let token = match get_repository_token(&state, &query.repo_url).await
+            {
+                Ok(token) => token,
+                // Sans installation de l'App, l'échec de l'accès public fait foi (dépôt privé ou inexistant).
+                Err(AppError::ProjectError(ProjectErrorCode::GithubAccountNotLinked)) => return Err(public_error),
+                Err(e) => return Err(e),
+            };
+            github_service::list_repo_branches(&policy, Some(&token), &owner, &repo).await?
+        }
+    };
+
+    Ok(Json(branches))
+}
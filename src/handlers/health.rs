@@ -4,7 +4,10 @@ use time::OffsetDateTime;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, warn};
 
-use crate::{error::AppError, state::AppState};
+use crate::{error::AppError, services::docker_service, state::AppState};
+
+/// Proportion (en %) de places SSE occupées au-delà de laquelle le service est dégradé.
+const SSE_DEGRADED_PERCENT: usize = 90;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -30,7 +33,8 @@ pub struct ComponentHealth
 pub struct HealthCheckResponse
 {
     pub status: HealthStatus,
-    pub timestamp: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
     pub components: HealthComponents,
 }
 
@@ -40,6 +44,7 @@ pub struct HealthComponents
     pub postgres: ComponentHealth,
     pub mariadb: ComponentHealth,
     pub docker: ComponentHealth,
+    pub sse: ComponentHealth,
 }
 
 impl HealthCheckResponse
@@ -48,7 +53,8 @@ impl HealthCheckResponse
     {
         let statuses = [components.postgres.status,
             components.mariadb.status,
-            components.docker.status];
+            components.docker.status,
+            components.sse.status];
 
         if statuses.contains(&HealthStatus::Unhealthy)
         {
@@ -84,6 +90,7 @@ pub async fn health_check_handler(
         postgres: postgres_health,
         mariadb: mariadb_health,
         docker: docker_health,
+        sse: check_sse_health(&state),
     };
 
     let global_status = HealthCheckResponse::compute_global_status(&components);
@@ -91,9 +98,7 @@ pub async fn health_check_handler(
     let response = HealthCheckResponse
     {
         status: global_status,
-        timestamp: OffsetDateTime::now_utc()
-            .format(&time::format_description::well_known::Rfc3339)
-            .unwrap_or_else(|_| "unknown".to_string()),
+        timestamp: OffsetDateTime::now_utc(),
         components,
     };
 
@@ -230,22 +235,33 @@ async fn check_mariadb_health(state: &AppState) -> ComponentHealth
     }
 }
 
+/// Vérifie aussi que le réseau des projets existe toujours : sans lui, le démon répond mais aucun déploiement n'aboutit.
 async fn check_docker_health(state: &AppState) -> ComponentHealth
 {
     let start = Instant::now();
+    let network = &state.config.docker_network;
 
     match tokio::time::timeout(
         Duration::from_secs(5),
-        state.docker_client.ping(),
+        async
+        {
+            state.docker_client.ping().await.map_err(|e| docker_service::DockerError::new("ping", "daemon", e))?;
+            docker_service::network_exists(&state.docker_client, network).await
+        },
     )
     .await
     {
-        Ok(Ok(_)) =>
+        Ok(Ok(network_found)) =>
         {
             let response_time_us = start.elapsed().as_micros() as u64;
             debug!("Docker health check passed in {}µs", response_time_us);
 
-            let status = if response_time_us > 2_000_000
+            let status = if !network_found
+            {
+                warn!("Docker network '{}' does not exist", network);
+                HealthStatus::Degraded
+            }
+            else if response_time_us > 2_000_000
             {
                 warn!("Docker response time is slow: {}µs", response_time_us);
                 HealthStatus::Degraded
@@ -260,7 +276,7 @@ async fn check_docker_health(state: &AppState) -> ComponentHealth
                 status,
                 response_time_us,
                 details: None,
-                error: None,
+                error: (!network_found).then(|| format!("Docker network '{network}' not found")),
             }
         }
         Ok(Err(e)) =>
@@ -287,3 +303,27 @@ async fn check_docker_health(state: &AppState) -> ComponentHealth
         }
     }
 }
+
+fn check_sse_health(state: &AppState) -> ComponentHealth
+{
+    let active = state.sse_manager.active_connections();
+    let max = state.sse_manager.max_connections();
+
+    let status = if active * 100 >= max * SSE_DEGRADED_PERCENT
+    {
+        warn!("SSE connections near the limit: {}/{}", active, max);
+        HealthStatus::Degraded
+    }
+    else
+    {
+        HealthStatus::Healthy
+    };
+
+    ComponentHealth
+    {
+        status,
+        response_time_us: 0,
+        details: Some(format!("{active}/{max} SSE connections")),
+        error: None,
+    }
+}
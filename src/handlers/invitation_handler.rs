@@ -0,0 +1,51 @@
+use axum::{extract::{Path, State}, http::StatusCode, response::{IntoResponse, Json}};
+use tracing::info;
+
+use crate::
+{
+    error::AppError,
+    model::response::ActionResponse,
+    services::{invitation_service, jwt::Claims},
+    state::AppState,
+};
+
+/// Invitations à participer à un projet en attente de réponse de l'utilisateur connecté.
+/// Endpoint: GET /api/me/invitations
+pub async fn list_my_invitations_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    let invitations = invitation_service::list_for_invitee(&state.db_pool, &claims.sub).await?;
+    Ok(Json(invitations))
+}
+
+/// Endpoint: POST /`api/me/invitations/{invitation_id}/accept`
+pub async fn accept_invitation_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(invitation_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project_id = invitation_service::accept(
+        &state.db_pool, invitation_id, &claims.sub, state.config.participants_max_per_project
+    ).await?;
+
+    info!("User '{}' accepted invitation {} and joined project {}", claims.sub, invitation_id, project_id);
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Invitation accepted."))))
+}
+
+/// Endpoint: POST /`api/me/invitations/{invitation_id}/decline`
+pub async fn decline_invitation_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(invitation_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    invitation_service::decline(&state.db_pool, invitation_id, &claims.sub).await?;
+
+    info!("User '{}' declined invitation {}", claims.sub, invitation_id);
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Invitation declined."))))
+}
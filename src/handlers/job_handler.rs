@@ -0,0 +1,233 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::error;
+use crate::
+{
+    authz::{self, AccessContext, ProjectMutation, RequiredRole},
+    error::{AppError, ProjectErrorCode},
+    model::job::ProjectJob,
+    services::{audit_service, job_service},
+    sse::{emitter::emit_project_event, types::SystemEvent},
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct JobPayload
+{
+    schedule: String,
+    command: Vec<String>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+}
+
+const fn default_enabled() -> bool
+{
+    true
+}
+
+#[derive(Deserialize)]
+pub struct RunCommandPayload
+{
+    command: Vec<String>,
+    timeout_secs: Option<u64>,
+}
+
+/// Libère l'emplacement d'exécution du projet, y compris si la tâche est interrompue.
+struct RunningTaskGuard
+{
+    state: AppState,
+    project_id: i32,
+}
+
+impl RunningTaskGuard
+{
+    fn acquire(state: &AppState, project_id: i32) -> Result<Self, AppError>
+    {
+        let mut running = state.running_tasks.lock().map_err(|_| AppError::InternalServerError)?;
+        if !running.insert(project_id)
+        {
+            return Err(ProjectErrorCode::TaskAlreadyRunning.into());
+        }
+        Ok(Self { state: state.clone(), project_id })
+    }
+}
+
+impl Drop for RunningTaskGuard
+{
+    fn drop(&mut self)
+    {
+        if let Ok(mut running) = self.state.running_tasks.lock()
+        {
+            running.remove(&self.project_id);
+        }
+    }
+}
+
+pub async fn list_jobs_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+
+    let jobs = job_service::list_jobs(&state.db_pool, project.id).await?;
+
+    Ok(Json(json!({ "jobs": jobs })))
+}
+
+pub async fn create_job_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<JobPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageJobs).await?;
+
+    let schedule = job_service::validate_schedule(&payload.schedule, state.config.jobs_min_interval_minutes)?;
+    job_service::validate_command(&payload.command)?;
+
+    let job = job_service::create_job(
+        &state.db_pool,
+        project.id,
+        &schedule,
+        &payload.command,
+        payload.enabled,
+        state.config.jobs_max_per_project,
+    ).await?;
+
+    Ok((StatusCode::CREATED, Json(job)))
+}
+
+pub async fn update_job_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, job_id)): Path<(i32, i32)>,
+    Json(payload): Json<JobPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageJobs).await?;
+    let job = load_job(&state, project.id, job_id).await?;
+
+    let schedule = job_service::validate_schedule(&payload.schedule, state.config.jobs_min_interval_minutes)?;
+    job_service::validate_command(&payload.command)?;
+
+    let job = job_service::update_job(&state.db_pool, job.id, &schedule, &payload.command, payload.enabled).await?;
+
+    Ok(Json(job))
+}
+
+pub async fn delete_job_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, job_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageJobs).await?;
+    let job = load_job(&state, project.id, job_id).await?;
+
+    job_service::delete_job(&state.db_pool, job.id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_job_runs_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, job_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+    let job = load_job(&state, project.id, job_id).await?;
+
+    let runs = job_service::list_runs(&state.db_pool, job.id).await?;
+
+    Ok(Json(json!({ "runs": runs })))
+}
+
+async fn load_job(state: &AppState, project_id: i32, job_id: i32) -> Result<ProjectJob, AppError>
+{
+    job_service::get_job(&state.db_pool, project_id, job_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Job with ID {job_id} not found.")))
+}
+
+/// Exécute une commande ponctuelle (ex. `php artisan migrate`) dans un conteneur éphémère
+/// créé à partir de l'image déployée. La sortie est diffusée ligne par ligne sur le canal SSE du projet.
+pub async fn run_project_command_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<RunCommandPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::RunCommand).await?;
+
+    job_service::validate_command(&payload.command)?;
+
+    let max_timeout = state.config.jobs_timeout_seconds.get();
+    let timeout_secs = payload.timeout_secs.unwrap_or(max_timeout);
+    if timeout_secs == 0 || timeout_secs > max_timeout
+    {
+        return Err(AppError::BadRequest(format!("timeout_secs must be between 1 and {max_timeout}.")));
+    }
+
+    let guard = RunningTaskGuard::acquire(&state, project.id)?;
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_COMMAND_RUN,
+        Some(project.id),
+        Some(json!({ "command": payload.command, "timeout_secs": timeout_secs })),
+    ).await;
+
+    let (line_tx, mut line_rx) = mpsc::unbounded_channel::<String>();
+    let forward_state = state.clone();
+    let forwarder = tokio::spawn(async move
+    {
+        while let Some(line) = line_rx.recv().await
+        {
+            let event = SystemEvent::info(line).with_context(json!({ "source": "run_command" }));
+            emit_project_event(&forward_state, project_id, event).await;
+        }
+    });
+
+    // Exécution détachée : si la requête HTTP expire, le conteneur est tout de même nettoyé.
+    let run_state = state.clone();
+    let container_name = format!("{}-{}-run-{}", state.config.app_prefix, project.name, time::OffsetDateTime::now_utc().unix_timestamp());
+    let outcome = tokio::spawn(async move
+    {
+        let _guard = guard;
+        job_service::run_in_project_image(
+            &run_state,
+            &project,
+            &container_name,
+            &payload.command,
+            Duration::from_secs(timeout_secs),
+            Some(line_tx),
+        ).await
+    })
+    .await
+    .map_err(|e|
+    {
+        error!("One-off task of project ID {} panicked: {}", project_id, e);
+        AppError::InternalServerError
+    })??;
+
+    let _ = forwarder.await;
+
+    Ok(Json(json!({
+        "exit_code": outcome.exit_code,
+        "timed_out": outcome.timed_out,
+        "output": outcome.output_tail,
+    })))
+}
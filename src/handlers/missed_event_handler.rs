@@ -0,0 +1,26 @@
+use axum::{extract::{Path, State}, response::{IntoResponse, Json}};
+
+use crate::
+{
+    authz::{self, AccessContext, RequiredRole},
+    error::AppError,
+    model::response::MissedEventsResponse,
+    services::missed_event_service,
+    state::AppState,
+};
+
+/// Livre, du plus ancien au plus récent, les événements du projet émis alors que personne n'était
+/// connecté. Ils ne sont livrés qu'une fois : ici ou au début du prochain flux SSE du projet.
+/// Endpoint: GET /api/projects/{project_id}/events/missed
+pub async fn list_missed_events_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+
+    let events = missed_event_service::take_for_project(&state.db_pool, project.id).await?;
+
+    Ok(Json(MissedEventsResponse { events }))
+}
@@ -3,4 +3,22 @@ pub mod auth_handler;
 pub mod project_handler;
 pub mod admin_handler;
 pub mod database_handler;
-pub mod sse_handler;
\ No newline at end of file
+pub mod sse_handler;
+#[cfg(feature = "object_storage")]
+pub mod bucket_handler;
+pub mod job_handler;
+pub mod api_key_handler;
+pub mod deployment_handler;
+pub mod notification_handler;
+pub mod share_link_handler;
+pub mod timeline_handler;
+pub mod invitation_handler;
+pub mod github_handler;
+pub mod project_archive_handler;
+pub mod platform_handler;
+pub mod missed_event_handler;
+pub mod domain_handler;
+pub mod template_handler;
+pub mod upload_handler;
+pub mod uptime_handler;
+pub mod webhook_handler;
\ No newline at end of file
@@ -0,0 +1,43 @@
+use axum::{extract::State, response::{IntoResponse, Json}};
+
+use crate::
+{
+    error::AppError,
+    model::notification::{NotificationChannel, NotificationSettings},
+    services::{jwt::Claims, notification_service, validation_service},
+    state::AppState,
+};
+
+pub async fn get_notification_settings_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    let mut settings = notification_service::get_settings(&state.db_pool, &claims.sub).await?;
+    settings.email_enabled = state.mailer.is_some();
+    Ok(Json(settings))
+}
+
+/// Remplace toutes les préférences : un type de notification absent de la requête revient à son canal par défaut.
+pub async fn update_notification_settings_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(mut payload): Json<NotificationSettings>,
+) -> Result<impl IntoResponse, AppError>
+{
+    payload.webhook_url = payload.webhook_url.filter(|url| !url.trim().is_empty());
+
+    if let Some(url) = &payload.webhook_url
+    {
+        validation_service::validate_webhook_url(url)?;
+    }
+    else if payload.preferences.values().any(|channel| *channel == NotificationChannel::Webhook)
+    {
+        return Err(AppError::BadRequest("A webhook URL is required to use the webhook channel.".to_string()));
+    }
+
+    notification_service::update_settings(&state.db_pool, &claims.sub, &payload).await?;
+
+    payload.email_enabled = state.mailer.is_some();
+    Ok(Json(payload.with_defaults()))
+}
@@ -0,0 +1,10 @@
+use axum::{extract::State, response::{IntoResponse, Json}};
+
+use crate::{model::platform::PublicLimits, state::AppState};
+
+/// Limites et capacités de l'instance, pour que le frontend n'ait pas à les dupliquer.
+/// Endpoint: GET /api/platform/limits
+pub async fn get_platform_limits_handler(State(state): State<AppState>) -> impl IntoResponse
+{
+    Json(PublicLimits::from_config(&state.config))
+}
@@ -0,0 +1,237 @@
+use std::path::Path as FsPath;
+
+use axum::{body::Bytes, extract::{Path, Query, State}, http::{header, StatusCode}, response::{IntoResponse, Json}};
+use serde::Deserialize;
+use serde_json::json;
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    authz::{self, AccessContext, ProjectRole, RequiredRole},
+    error::AppError,
+    handlers::project_handler::{perform_deploy, DeployPayload},
+    model::{project::{Project, ProjectSourceType}, response::ArchiveImportResponse},
+    services::
+    {
+        audit_service, backup_service, database_service, docker_service,
+        project_archive_service::{self, ProjectArchive, ProjectArchiveMetadata},
+        project_service,
+    },
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct ExportQuery
+{
+    /// Inclut les valeurs des variables d'environnement (owner uniquement).
+    #[serde(default)]
+    include_secrets: bool,
+}
+
+/// Exporte le projet (configuration, volume persistant, dump de la base liée) en une archive portable.
+/// Endpoint: GET /api/projects/{project_id}/export?include_secrets=true
+pub async fn export_project_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let (project, role) = authz::load_project_with_role(&state, &ctx, project_id, RequiredRole::Owner).await?;
+    info!("User '{}' exporting project '{}' (secrets: {})", ctx.login, project.name, query.include_secrets);
+
+    // Les valeurs des variables ne quittent la plateforme qu'à la demande de l'owner lui-même.
+    if query.include_secrets && role != ProjectRole::Owner
+    {
+        return Err(AppError::Forbidden("Only the project owner can export its secrets.".to_string()));
+    }
+    let env_vars = if query.include_secrets
+    {
+        project_service::get_decrypted_env_vars(&project, &state.config.encryption_key)?
+    }
+    else
+    {
+        None
+    };
+
+    let participants = project_service::get_project_participants(&state.db_pool, project.id).await?
+        .into_iter()
+        .map(|participant| participant.login)
+        .collect();
+
+    let database = match database_service::get_database_by_project_id(&state.db_pool, project.id).await?
+    {
+        Some(database) => Some(backup_service::dump_database(&state, &database).await.map_err(|reason|
+        {
+            error!("Export of project '{}': dump of database '{}' failed: {}", project.name, database.database_name, reason);
+            AppError::InternalServerError
+        })?),
+        None => None,
+    };
+
+    let volume = match &project.persistent_volume_path
+    {
+        Some(path) => Some(docker_service::download_container_path(&state.docker_client, &project.container_name, path).await.map_err(|e|
+        {
+            error!("Export of project '{}': copy of volume path '{}' failed: {}", project.name, path, e);
+            AppError::InternalServerError
+        })?),
+        None => None,
+    };
+
+    // Une image construite par la plateforme n'a pas de digest de registre : elle est reconstruite à l'import.
+    let image_repo_digest = if project.source == ProjectSourceType::Direct
+    {
+        docker_service::get_image_repo_digest(&state.docker_client, &project.deployed_image_tag).await.unwrap_or_else(|e|
+        {
+            warn!("Export of project '{}': could not read the digest of image '{}': {}", project.name, project.deployed_image_tag, e);
+            None
+        })
+    }
+    else
+    {
+        None
+    };
+
+    let archive = ProjectArchive
+    {
+        metadata: ProjectArchiveMetadata::from_project(&project, participants, env_vars, image_repo_digest),
+        volume,
+        database,
+    };
+    let has_database = archive.database.is_some();
+    let has_volume = archive.volume.is_some();
+    let bytes = tokio::task::spawn_blocking(move || archive.pack())
+        .await
+        .map_err(|_| AppError::InternalServerError)??;
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_EXPORTED,
+        Some(project.id),
+        Some(json!({ "include_secrets": query.include_secrets, "database": has_database, "volume": has_volume, "size_bytes": bytes.len() })),
+    ).await;
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        project_archive_service::archive_file_name(&project.name, OffsetDateTime::now_utc())
+    );
+    Ok(([(header::CONTENT_TYPE, "application/gzip".to_string()), (header::CONTENT_DISPOSITION, disposition)], bytes))
+}
+
+/// Recrée un projet exporté : déploiement de l'image enregistrée (ou rebuild de sa source),
+/// base provisionnée puis restaurée, volume créé puis rempli. Un administrateur restaure le projet
+/// pour son owner d'origine ; un utilisateur, pour lui-même, dans la limite de son quota.
+/// Endpoint: POST /api/projects/import (corps : l'archive)
+pub async fn import_project_archive_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    body: Bytes,
+) -> Result<impl IntoResponse, AppError>
+{
+    let max_unpacked_bytes = state.config.project_archive_max_mb.saturating_mul(1024 * 1024);
+    let archive = tokio::task::spawn_blocking(move || ProjectArchive::unpack(&body, max_unpacked_bytes))
+        .await
+        .map_err(|_| AppError::InternalServerError)??;
+    let ProjectArchive { metadata, volume, database } = archive;
+
+    if metadata.source == ProjectSourceType::Upload
+    {
+        return Err(AppError::BadRequest("A project deployed from an uploaded archive cannot be restored: deploy a new upload instead.".to_string()));
+    }
+
+    let owner = if ctx.is_admin { metadata.owner.clone() } else { ctx.login.clone() };
+    info!("User '{}' restoring project '{}' from an archive for '{}'", ctx.login, metadata.name, owner);
+
+    let payload = DeployPayload::from_archive(&metadata, &owner, database.is_some());
+    let deployed = perform_deploy(&state, owner, payload).await?;
+
+    let mut warnings = Vec::new();
+
+    let database_restored = match &database
+    {
+        Some(dump) => match restore_database(&state, &deployed.project, dump).await
+        {
+            Ok(()) => true,
+            Err(warning) =>
+            {
+                warnings.push(warning);
+                false
+            }
+        },
+        None => false,
+    };
+
+    let volume_restored = match volume
+    {
+        Some(volume) => match seed_volume(&state, &deployed.project, volume).await
+        {
+            Ok(()) => true,
+            Err(warning) =>
+            {
+                warnings.push(warning);
+                false
+            }
+        },
+        None => false,
+    };
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_RESTORED,
+        Some(deployed.project.id),
+        Some(json!({ "owner": deployed.project.owner, "database": database_restored, "volume": volume_restored, "warnings": warnings })),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(ArchiveImportResponse
+    {
+        project: deployed,
+        database_restored,
+        volume_restored,
+        missing_env_vars: metadata.redacted_env_vars,
+        warnings,
+    })))
+}
+
+/// Rejoue le dump dans la base provisionnée par le déploiement.
+async fn restore_database(state: &AppState, project: &Project, dump: &[u8]) -> Result<(), String>
+{
+    let database = database_service::get_database_by_project_id(&state.db_pool, project.id).await
+        .ok()
+        .flatten()
+        .ok_or_else(|| "The database was not provisioned; its dump was not restored.".to_string())?;
+
+    backup_service::restore_dump(state, &database, dump).await.map_err(|reason|
+    {
+        error!("Restore of project '{}': replay of the database dump failed: {}", project.name, reason);
+        "The database dump could not be restored.".to_string()
+    })
+}
+
+/// Extrait le contenu exporté dans le volume, puis redémarre le conteneur pour qu'il le prenne en compte.
+async fn seed_volume(state: &AppState, project: &Project, volume: Vec<u8>) -> Result<(), String>
+{
+    let Some(path) = project.persistent_volume_path.as_deref() else
+    {
+        return Err("The project has no persistent volume; its content was not restored.".to_string());
+    };
+
+    // L'archive a pour racine le dernier segment du chemin : elle est extraite dans son parent.
+    let parent = FsPath::new(path).parent().and_then(|parent| parent.to_str()).unwrap_or("/");
+    if let Err(e) = docker_service::upload_container_archive(&state.docker_client, &project.container_name, parent, volume).await
+    {
+        error!("Restore of project '{}': copy into volume path '{}' failed: {}", project.name, path, e);
+        return Err("The volume content could not be restored.".to_string());
+    }
+
+    if docker_service::restart_container_by_name(&state.docker_client, &project.container_name, project.stop_grace_seconds).await.is_err()
+    {
+        warn!("Restore of project '{}': container restart after seeding the volume failed", project.name);
+        return Err("The volume was restored but the container could not be restarted.".to_string());
+    }
+
+    Ok(())
+}
@@ -2,34 +2,114 @@ use std::
 {
     collections::{HashMap, HashSet},
     fs,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    future::Future,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use axum::
 {
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::{IntoResponse, Json},
+    response::{IntoResponse, Json, Response},
 };
-use base64::prelude::*;
 use serde::Deserialize;
 use serde_json::json;
-use tempfile::Builder as TempBuilder;
-use tokio::time::sleep;
+use time::OffsetDateTime;
 use tracing::{debug, error, info, warn};
 
 use crate::
 {
-    error::{AppError, DatabaseErrorCode, ProjectErrorCode}, model::project::{ProjectDetailsResponse, ProjectSourceType}, services::
+    authz::{self, AccessContext, ProjectMutation, ProjectRole, RequiredRole},
+    config::{Config, DEFAULT_RESOURCE_PROFILE},
+    error::{AppError, DatabaseErrorCode, ProjectErrorCode}, logging::log_safe, safe_http::SafeHttpPolicy, model::{deployment::{Deployment, DeploymentKind, ResumeStage}, docker::{ContainerInspectSummary, LogsFilter}, project::{ContainerCommand, DEFAULT_CONTAINER_PORT, PreviousImage, Project, ProjectDetailsResponse, ProjectRuntime, ProjectSourceType}, template::Template, response::{ActionResponse, ContainerResponse, DeployResponse, DeployedProject, DeploymentCheckResult, DryRunResponse, HealthCheckResponse, KeepAliveResponse, MembershipsResponse, ParticipantsPageResponse, ProjectListResponse, ProjectMembership, ProjectResponse, ScanIgnoresResponse, StopGraceResponse, TextLogsResponse}}, services::
     {
-        crypto_service, database_service, deployment_orchestrator::DeploymentOrchestrator, docker_service, github_service, jwt::Claims, project_service, validation_service
-    }, sse::types::DeploymentStage, state::AppState
+        base_image_service, blue_green::{self, MetadataUpdate, NewContainerSpec}, build_workspace_service, database_service, deployment_orchestrator::DeploymentOrchestrator, deployment_service, docker_service, domain_service, github_service::{self, Checkout}, invitation_service, project_archive_service::ProjectArchiveMetadata, project_service, template_service, upload_service::{self, UploadedArchive}, user_service, validation_service
+    }, sse::{emitter::emit_if_network_missing, types::{DeploymentStage, SseEvent, SystemEvent}}, state::AppState
 };
+#[cfg(feature = "object_storage")]
+use crate::services::object_storage_service;
 
 // ============================================================================
 // Request/Response Types
 // ============================================================================
 
+#[derive(Deserialize)]
+pub struct PurgeQuery
+{
+    /// Supprime le bucket sans le vider au préalable.
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RebuildQuery
+{
+    /// Reconstruit même si le dernier commit de la branche est déjà déployé (image de base mise à jour...).
+    #[serde(default)]
+    force: bool,
+    /// Tag ou SHA de commit à construire ; le projet y reste ensuite épinglé.
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+}
+
+#[derive(Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogsFormat
+{
+    #[default]
+    Json,
+    /// Rendu historique : une seule chaîne avec les lignes horodatées concaténées.
+    Text,
+}
+
+#[derive(Deserialize, Default)]
+pub struct LogsQuery
+{
+    #[serde(default)]
+    format: LogsFormat,
+    /// Dernières lignes renvoyées (200 par défaut).
+    tail: Option<u32>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    since: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    until: Option<OffsetDateTime>,
+    stdout: Option<bool>,
+    stderr: Option<bool>,
+    timestamps: Option<bool>,
+}
+
+impl LogsQuery
+{
+    fn to_filter(&self, max_tail: u32) -> Result<LogsFilter, AppError>
+    {
+        let defaults = LogsFilter::default();
+        let filter = LogsFilter
+        {
+            tail: self.tail.unwrap_or(defaults.tail),
+            since: self.since,
+            until: self.until,
+            stdout: self.stdout.unwrap_or(defaults.stdout),
+            stderr: self.stderr.unwrap_or(defaults.stderr),
+            timestamps: self.timestamps.unwrap_or(defaults.timestamps),
+        };
+
+        if filter.tail > max_tail
+        {
+            return Err(AppError::BadRequest(format!("tail must not exceed {max_tail} lines.")));
+        }
+        if !filter.stdout && !filter.stderr
+        {
+            return Err(AppError::BadRequest("At least one of stdout and stderr must be enabled.".to_string()));
+        }
+        if filter.since.zip(filter.until).is_some_and(|(since, until)| since > until)
+        {
+            return Err(AppError::BadRequest("since must be earlier than until.".to_string()));
+        }
+
+        Ok(filter)
+    }
+}
+
 #[derive(Deserialize)]
 pub struct DeployPayload
 {
@@ -37,11 +117,157 @@ pub struct DeployPayload
     image_url: Option<String>,
     github_repo_url: Option<String>,
     github_branch: Option<String>,
+    /// Tag ou SHA de commit complet à construire à la place de la tête de `github_branch`.
+    github_ref: Option<String>,
     github_root_dir: Option<String>,
+    /// Construit l'image avec le `Dockerfile` du dépôt (sous `github_root_dir` s'il est défini)
+    /// au lieu du Dockerfile généré à partir de `BUILD_BASE_IMAGE`.
+    use_repo_dockerfile: Option<bool>,
     participants: Vec<String>,
     env_vars: Option<HashMap<String, String>>,
     persistent_volume_path: Option<String>,
     create_database: Option<bool>,
+    /// Injecte les identifiants de la base liée dans le conteneur (`HANGAR_DB_*`).
+    inject_database_env: Option<bool>,
+    /// Surcharges optionnelles `entrypoint` et `command` de l'image.
+    #[serde(flatten)]
+    container_command: ContainerCommand,
+    /// Fuseau horaire IANA injecté dans `TZ` (par défaut : celui de l'instance).
+    timezone: Option<String>,
+    /// Port HTTP écouté par l'application (par défaut : 80).
+    container_port: Option<u16>,
+    /// Vulnérabilités ignorées par le scan de l'image (`CVE-...`, `GHSA-...`).
+    #[serde(default)]
+    scan_ignore_cves: Vec<String>,
+    /// Valeurs des champs `{{NOM}}` des variables d'un modèle, remplies après leur validation.
+    #[serde(skip)]
+    template_values: Option<HashMap<String, String>>,
+    /// Archive d'un site statique, reçue à côté du payload (`POST /api/projects/deploy-upload`).
+    #[serde(skip)]
+    upload: Option<UploadedArchive>,
+}
+
+impl DeployPayload
+{
+    /// Déploiement `direct` minimal, sans participant ni option.
+    pub(crate) fn from_image(project_name: &str, image_url: &str) -> Self
+    {
+        Self
+        {
+            project_name: project_name.to_string(),
+            image_url: Some(image_url.to_string()),
+            github_repo_url: None,
+            github_branch: None,
+            github_ref: None,
+            github_root_dir: None,
+            use_repo_dockerfile: None,
+            participants: Vec::new(),
+            env_vars: None,
+            persistent_volume_path: None,
+            create_database: None,
+            inject_database_env: None,
+            container_command: ContainerCommand::default(),
+            timezone: None,
+            container_port: None,
+            scan_ignore_cves: Vec::new(),
+            template_values: None,
+            upload: None,
+        }
+    }
+
+    /// Redéploiement d'un projet exporté : l'image enregistrée, épinglée par son digest si l'archive
+    /// le contient, ou le dépôt reconstruit depuis sa référence épinglée, à défaut le commit déployé,
+    /// à défaut la tête de sa branche.
+    pub(crate) fn from_archive(metadata: &ProjectArchiveMetadata, owner: &str, create_database: bool) -> Self
+    {
+        let is_github = metadata.source == ProjectSourceType::Github;
+        let github = |value: &Option<String>| value.clone().filter(|_| is_github);
+        let github_ref = github(&metadata.source_ref).or_else(|| github(&metadata.source_commit_sha));
+
+        Self
+        {
+            project_name: metadata.name.clone(),
+            image_url: (!is_github).then(|| metadata.image_repo_digest.clone().unwrap_or_else(|| metadata.source_url.clone())),
+            github_repo_url: is_github.then(|| metadata.source_url.clone()),
+            github_branch: github(&metadata.source_branch).filter(|_| github_ref.is_none()),
+            github_ref,
+            github_root_dir: github(&metadata.source_root_dir),
+            use_repo_dockerfile: is_github.then_some(metadata.use_repo_dockerfile),
+            participants: metadata.participants.iter().filter(|login| *login != owner).cloned().collect(),
+            env_vars: metadata.env_vars.clone(),
+            persistent_volume_path: metadata.persistent_volume_path.clone(),
+            create_database: Some(create_database),
+            inject_database_env: Some(metadata.inject_database_env),
+            container_command: metadata.command.clone(),
+            timezone: metadata.timezone.clone(),
+            container_port: Some(metadata.container_port),
+            scan_ignore_cves: metadata.scan_ignore_cves.clone(),
+            template_values: None,
+            upload: None,
+        }
+    }
+
+    /// Déploiement d'un site statique envoyé sous forme d'archive.
+    pub(crate) fn with_upload(mut self, archive: UploadedArchive) -> Self
+    {
+        self.upload = Some(archive);
+        self
+    }
+
+    /// Déploiement d'un modèle du catalogue. La base éventuelle est créée et ses identifiants injectés.
+    pub(crate) fn from_template(
+        template: &Template,
+        project_name: String,
+        participants: Vec<String>,
+        template_values: HashMap<String, String>,
+    ) -> Self
+    {
+        let is_github = template.source_type == ProjectSourceType::Github;
+
+        Self
+        {
+            project_name,
+            image_url: (!is_github).then(|| template.source_url.clone()),
+            github_repo_url: is_github.then(|| template.source_url.clone()),
+            github_branch: template.source_branch.clone().filter(|_| is_github),
+            github_ref: None,
+            github_root_dir: None,
+            use_repo_dockerfile: None,
+            participants,
+            env_vars: Some(template.default_env_vars.0.clone()),
+            persistent_volume_path: template.persistent_volume_path.clone(),
+            create_database: Some(template.create_database),
+            inject_database_env: Some(template.create_database),
+            container_command: ContainerCommand::default(),
+            timezone: None,
+            container_port: template.container_port.and_then(|port| u16::try_from(port).ok()),
+            scan_ignore_cves: Vec::new(),
+            template_values: Some(template_values),
+            upload: None,
+        }
+    }
+
+    fn container_port(&self) -> u16
+    {
+        self.container_port.unwrap_or(DEFAULT_CONTAINER_PORT)
+    }
+
+    /// Variables du premier conteneur : la base créée avec le projet n'est enregistrée qu'après lui,
+    /// ses identifiants sont donc injectés à partir du mot de passe généré d'avance.
+    fn creation_env_vars(&self, config: &Config, user_login: &str, database_password: Option<&str>) -> Option<HashMap<String, String>>
+    {
+        match database_password.filter(|_| self.inject_database_env.unwrap_or(false))
+        {
+            Some(password) =>
+            {
+                let database_name = database_service::project_database_name(user_login);
+                let mut env_vars = self.env_vars.clone().unwrap_or_default();
+                env_vars.extend(database_service::credentials_env_vars(config, &database_name, &database_name, password.to_string()));
+                Some(env_vars)
+            }
+            None => self.env_vars.clone(),
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -56,6 +282,66 @@ pub struct UpdateImagePayload
     new_image_url: String,
 }
 
+/// Nouvelle surcharge complète : un champ absent ou `null` rétablit la valeur de l'image.
+#[derive(Deserialize)]
+pub struct UpdateCommandPayload
+{
+    #[serde(flatten)]
+    container_command: ContainerCommand,
+}
+
+#[derive(Deserialize)]
+pub struct UpdatePortPayload
+{
+    container_port: u16,
+}
+
+#[derive(Deserialize)]
+pub struct KeepAlivePayload
+{
+    keep_alive: bool,
+}
+
+/// Participants renvoyés par page, et directement dans les détails du projet.
+const PARTICIPANTS_PER_PAGE: u32 = 50;
+
+#[derive(Deserialize)]
+pub struct ParticipantsQuery
+{
+    /// Numéro de page, à partir de 1.
+    page: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct ResourceProfilePayload
+{
+    profile: String,
+}
+
+#[derive(Deserialize)]
+pub struct ScanIgnoresPayload
+{
+    scan_ignore_cves: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct StopGracePayload
+{
+    stop_grace_seconds: i32,
+}
+
+/// Surcharges de la vérification de santé ; `null` revient à la valeur de l'instance.
+#[derive(Deserialize)]
+pub struct HealthCheckPayload
+{
+    health_check_max_attempts: Option<i32>,
+    health_check_interval_ms: Option<i32>,
+}
+
+/// Bornes des surcharges de la vérification de santé d'un projet.
+const MAX_HEALTH_CHECK_ATTEMPTS: i32 = 120;
+const HEALTH_CHECK_INTERVAL_MS_RANGE: std::ops::RangeInclusive<i32> = 100..=10_000;
+
 #[derive(Deserialize)]
 pub struct ParticipantPayload
 {
@@ -80,27 +366,258 @@ impl ProjectAction
         self,
         docker: bollard::Docker,
         container_name: String,
+        stop_grace_seconds: i32,
     ) -> Result<(), AppError>
     {
+        // Action demandée par l'utilisateur : un conteneur introuvable ou dans un état incompatible lui est signalé en 4xx.
         match self
         {
             Self::Start => docker_service::start_container_by_name(&docker, &container_name).await,
-            Self::Stop => docker_service::stop_container_by_name(&docker, &container_name).await,
-            Self::Restart => docker_service::restart_container_by_name(&docker, &container_name).await,
+            Self::Stop => docker_service::stop_container_by_name(&docker, &container_name, stop_grace_seconds).await,
+            Self::Restart => docker_service::restart_container_by_name(&docker, &container_name, stop_grace_seconds).await,
+        }
+        .map_err(docker_service::DockerError::into_user_error)
+    }
+
+    const fn mutation(self) -> ProjectMutation
+    {
+        match self
+        {
+            Self::Start => ProjectMutation::Start,
+            Self::Stop => ProjectMutation::Stop,
+            Self::Restart => ProjectMutation::Restart,
+        }
+    }
+}
+
+/// Résultat d'une vérification effectuée lors de la planification d'un déploiement.
+struct DeploymentCheck
+{
+    name: &'static str,
+    result: Result<(), AppError>,
+}
+
+/// Source d'un déploiement, déterminée une seule fois à partir du payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DeploymentSourceSpec
+{
+    Direct
+    {
+        image: String,
+    },
+    Github
+    {
+        repo: String,
+        branch: Option<String>,
+        /// Référence épinglée, exclusive de `branch`.
+        git_ref: Option<String>,
+        root_dir: Option<String>,
+        use_repo_dockerfile: bool,
+    },
+    Upload
+    {
+        /// Nom d'origine et empreinte de l'archive (`site.zip#sha256:...`).
+        source_label: String,
+        /// Absente pour un projet existant : ses fichiers ne sont pas conservés après le build.
+        archive: Option<UploadedArchive>,
+    },
+}
+
+impl DeploymentSourceSpec
+{
+    /// Exige exactement une source et rejette les champs propres à l'autre source.
+    fn from_payload(payload: &DeployPayload) -> Result<Self, AppError>
+    {
+        if let Some(archive) = &payload.upload
+        {
+            let conflicts: Vec<&str> = [
+                ("image_url", payload.image_url.is_some()),
+                ("github_repo_url", payload.github_repo_url.is_some()),
+                ("github_branch", payload.github_branch.is_some()),
+                ("github_ref", payload.github_ref.is_some()),
+                ("github_root_dir", payload.github_root_dir.is_some()),
+                ("use_repo_dockerfile", payload.use_repo_dockerfile.is_some()),
+            ]
+            .into_iter()
+            .filter_map(|(field, set)| set.then_some(field))
+            .collect();
+
+            if !conflicts.is_empty()
+            {
+                return Err(AppError::BadRequest(format!(
+                    "An uploaded archive cannot be combined with: {}.",
+                    conflicts.join(", ")
+                )));
+            }
+
+            return Ok(Self::Upload { source_label: archive.source_label(), archive: Some(archive.clone()) });
+        }
+
+        match (&payload.image_url, &payload.github_repo_url)
+        {
+            (Some(image), None) =>
+            {
+                let conflicts: Vec<&str> = [
+                    ("github_branch", payload.github_branch.is_some()),
+                    ("github_ref", payload.github_ref.is_some()),
+                    ("github_root_dir", payload.github_root_dir.is_some()),
+                    ("use_repo_dockerfile", payload.use_repo_dockerfile.is_some()),
+                ]
+                .into_iter()
+                .filter_map(|(field, set)| set.then_some(field))
+                .collect();
+
+                if !conflicts.is_empty()
+                {
+                    return Err(AppError::BadRequest(format!(
+                        "'image_url' cannot be combined with: {}.",
+                        conflicts.join(", ")
+                    )));
+                }
+
+                Ok(Self::Direct { image: image.clone() })
+            }
+            (None, Some(_)) if payload.github_branch.is_some() && payload.github_ref.is_some() => Err(AppError::BadRequest(
+                "'github_branch' and 'github_ref' cannot be combined: a pinned reference does not follow a branch.".to_string()
+            )),
+            (None, Some(repo)) => Ok(Self::Github
+            {
+                repo: repo.clone(),
+                branch: payload.github_branch.clone(),
+                git_ref: payload.github_ref.clone(),
+                root_dir: payload.github_root_dir.clone(),
+                use_repo_dockerfile: payload.use_repo_dockerfile.unwrap_or(false),
+            }),
+            (Some(_), Some(_)) => Err(AppError::BadRequest(
+                "Conflicting sources: 'image_url' and 'github_repo_url' are both set. Provide exactly one.".to_string()
+            )),
+            (None, None) => Err(AppError::BadRequest(
+                "You must provide either an 'image_url' or a 'github_repo_url'.".to_string()
+            )),
+        }
+    }
+
+    /// Source enregistrée d'un projet existant, pour reproduire son image.
+    fn from_project(project: &Project) -> Self
+    {
+        match project.source
+        {
+            ProjectSourceType::Direct => Self::Direct { image: project.source_url.clone() },
+            ProjectSourceType::Github => Self::Github
+            {
+                repo: project.source_url.clone(),
+                branch: project.source_branch.clone(),
+                git_ref: project.source_ref.clone(),
+                root_dir: project.source_root_dir.clone(),
+                use_repo_dockerfile: project.use_repo_dockerfile,
+            },
+            ProjectSourceType::Upload => Self::Upload { source_label: project.source_url.clone(), archive: None },
+        }
+    }
+
+    /// Source qui reproduit l'image déployée d'un projet : le commit enregistré pour une source GitHub, et
+    /// non la tête de la branche. Un projet GitHub dont le commit n'a pas été enregistré ne peut pas l'être.
+    fn pinned_to_deployment(project: &Project) -> Result<Self, ProjectErrorCode>
+    {
+        match Self::from_project(project)
+        {
+            Self::Github { repo, root_dir, use_repo_dockerfile, .. } =>
+            {
+                let commit_sha = project.source_commit_sha.clone().ok_or_else(|| ProjectErrorCode::DeployedImageUnavailable(
+                    "the deployed commit was not recorded, redeploy the project".to_string()
+                ))?;
+                Ok(Self::Github { repo, branch: None, git_ref: Some(commit_sha), root_dir, use_repo_dockerfile })
+            }
+            spec => Ok(spec),
+        }
+    }
+
+    const fn source_type(&self) -> ProjectSourceType
+    {
+        match self
+        {
+            Self::Direct { .. } => ProjectSourceType::Direct,
+            Self::Github { .. } => ProjectSourceType::Github,
+            Self::Upload { .. } => ProjectSourceType::Upload,
+        }
+    }
+
+    fn source_url(&self) -> &str
+    {
+        match self
+        {
+            Self::Direct { image } => image,
+            Self::Github { repo, .. } => repo,
+            Self::Upload { source_label, .. } => source_label,
+        }
+    }
+}
+
+/// Résultat de la phase de validation d'un déploiement, consommé par le déploiement réel et par le dry-run.
+struct DeploymentPlan
+{
+    container_name: String,
+    participants: Vec<String>,
+    source: Option<DeploymentSourceSpec>,
+    checks: Vec<DeploymentCheck>,
+}
+
+/// Plan dont toutes les vérifications sont passées.
+struct ReadyDeployment
+{
+    container_name: String,
+    participants: Vec<String>,
+    source: DeploymentSourceSpec,
+}
+
+impl DeploymentPlan
+{
+    /// Renvoie le plan validé, sinon l'erreur de la première vérification en échec.
+    fn into_ready(mut self) -> Result<ReadyDeployment, AppError>
+    {
+        if let Some(index) = self.checks.iter().position(|check| check.result.is_err())
+            && let Err(e) = self.checks.swap_remove(index).result
+        {
+            return Err(e);
         }
+
+        // Une source absente fait toujours échouer la vérification "source".
+        let source = self.source.ok_or(AppError::InternalServerError)?;
+
+        Ok(ReadyDeployment
+        {
+            container_name: self.container_name,
+            participants: self.participants,
+            source,
+        })
+    }
+
+    fn check_results(&self) -> Vec<DeploymentCheckResult>
+    {
+        self.checks.iter().map(|check| match &check.result
+        {
+            Ok(()) => DeploymentCheckResult { check: check.name, passed: true, error_code: None, message: None },
+            Err(e) => DeploymentCheckResult
+            {
+                check: check.name,
+                passed: false,
+                error_code: Some(e.error_code()),
+                message: Some(e.public_message()),
+            },
+        }).collect()
     }
 }
 
 struct DeploymentSource
 {
-    source_type: ProjectSourceType,
-    source_url: String,
+    spec: DeploymentSourceSpec,
     image_tag: String,
+    /// Commit construit, pour une source `github`.
+    commit_sha: Option<String>,
 }
 
 struct BlueGreenDeployment
 {
-    old_container_name: String,
     new_container_name: String,
     new_image_tag: String,
     new_image_digest: String,
@@ -112,100 +629,83 @@ struct BlueGreenDeployment
 
 pub async fn deploy_project_handler(
     State(state): State<AppState>,
-    claims: Claims,
-    Json(mut payload): Json<DeployPayload>,
+    ctx: AccessContext,
+    Json(payload): Json<DeployPayload>,
 ) -> Result<impl IntoResponse, AppError>
+{
+    let project = perform_deploy(&state, ctx.login, payload).await?;
+
+    Ok((StatusCode::CREATED, Json(DeployResponse { project })))
+}
+
+/// Déploiement complet d'un nouveau projet, partagé avec le selftest d'administration.
+pub(crate) async fn perform_deploy(
+    state: &AppState,
+    user_login: String,
+    mut payload: DeployPayload,
+) -> Result<DeployedProject, AppError>
 {
     let orchestrator = DeploymentOrchestrator::for_creation
     (
-        &state,
+        state,
         payload.project_name.clone(),
-        claims.sub.clone(),
+        user_login.clone(),
     );
     
     orchestrator.emit_stage(DeploymentStage::Started).await;
 
-    orchestrator.with_stage
-    (
-        DeploymentStage::ValidatingInput,
-        "Input validation",
-        validate_deploy_payload(&mut payload),
-    ).await?;
-
-    
-    let user_login = claims.sub;
-
-    orchestrator.with_stage
+    let plan = orchestrator.with_stage
     (
         DeploymentStage::ValidatingInput,
-        "Preconditions check",
-        check_deployment_preconditions(&state, &user_login, &payload),
+        "Deployment planning",
+        async { plan_deployment(state, &mut payload, &user_login).await.into_ready() },
     ).await?;
 
-    let participants = prepare_participants(payload.participants.clone(), &user_login)?;
+    let ReadyDeployment { container_name, participants, source } = plan;
 
     let deployment_source = prepare_deployment_source_with_events
     (
-        &state, 
-        &payload, 
+        state, 
+        &payload.project_name, 
+        source,
+        &payload.scan_ignore_cves,
         &orchestrator
     ).await?;
 
+    if let Some(volume_path) = &payload.persistent_volume_path
+    {
+        let is_generated_build = matches!(deployment_source.spec, DeploymentSourceSpec::Github { use_repo_dockerfile: false, .. } | DeploymentSourceSpec::Upload { .. });
+        warn_if_volume_shadows_image(state, &orchestrator, &deployment_source.image_tag, volume_path, is_generated_build).await;
+    }
+
     let deployed_image_digest = orchestrator.with_stage
     (
         DeploymentStage::GettingImageDigest,
         "Image digest retrieval",
-        get_image_digest(&state, &deployment_source.image_tag),
+        get_image_digest(state, &deployment_source.image_tag),
     ).await?;
+    orchestrator.record_digest(&deployed_image_digest);
 
-    let container_name = format!("{}-{}", state.config.app_prefix, payload.project_name);
+    let project_id = project_service::reserve_project_id(&state.db_pool).await?;
+    let database_password = payload.create_database.unwrap_or(false).then(database_service::generate_password);
+    let env_vars = payload.creation_env_vars(&state.config, &user_login, database_password.as_deref());
     
-    let volume_name = orchestrator.with_stages
-    (
-        DeploymentStage::CreatingContainer,
-        DeploymentStage::ContainerCreated,
-        "Container creation",
-        create_container_with_rollback
-        (
-            &state,
-            &container_name,
-            &payload.project_name,
-            &deployed_image_digest,
-            &payload.env_vars,
-            &payload.persistent_volume_path,
-            &deployment_source.image_tag,
-        ),
-    ).await?;
-
-    if let Err(e) = orchestrator.with_stages
-    (
-        DeploymentStage::WaitingHealthCheck,
-        DeploymentStage::HealthCheckPassed,
-        "Health check",
-        wait_for_container_health(&state, &container_name, 10),
-    ).await
+    let runtime = DockerCreationRuntime
     {
-        warn!("Health check failed : {}, rolling back container '{}'", e, container_name);
-        let _ = docker_service::remove_container(&state.docker_client, &container_name).await;
-        if let Some(volume_name) = &volume_name
-        {
-            let _ = docker_service::remove_volume_by_name(&state.docker_client, volume_name).await;
-        }
-        remove_image_best_effort(&state, &deployed_image_digest).await;
-        return Err(e);
-    }
-
-    let new_project = persist_project_with_rollback_and_events(
-        &state,
-        &orchestrator,
-        &payload,
-        &user_login,
-        &container_name,
-        &deployment_source,
-        &deployed_image_digest,
-        &volume_name,
-        &participants,
-    ).await?;
+        state,
+        orchestrator: &orchestrator,
+        project_id,
+        payload: &payload,
+        env_vars: &env_vars,
+        database_password: database_password.as_deref(),
+        user_login: &user_login,
+        container_name: &container_name,
+        deployment_source: &deployment_source,
+        image_digest: &deployed_image_digest,
+        participants: &participants,
+    };
+    let new_project = finish_creation(&runtime, &container_name).await?;
+    orchestrator.record_creation(new_project.id).await;
 
     orchestrator.emit_completed(container_name, new_project.id).await;
 
@@ -214,163 +714,436 @@ pub async fn deploy_project_handler(
         payload.project_name, user_login
     );
 
-    Ok(create_deploy_response(new_project, participants))
+    Ok(DeployedProject { project: new_project, participants })
+}
+
+/// Exécute toutes les vérifications d'un déploiement sans toucher à Docker (ni pull, ni build, ni conteneur).
+pub async fn deploy_dry_run_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Json(mut payload): Json<DeployPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let plan = plan_deployment(&state, &mut payload, &ctx.login).await;
+    let checks = plan.check_results();
+    let valid = plan.checks.iter().all(|check| check.result.is_ok());
+
+    Ok(Json(DryRunResponse
+    {
+        valid,
+        project_name: payload.project_name,
+        container_name: plan.container_name,
+        participants: plan.participants,
+        checks,
+    }))
 }
 
 pub async fn purge_project_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
+    Query(query): Query<PurgeQuery>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let user_login = claims.sub;
+    let user_login = &ctx.login;
     info!("User '{}' initiated purge for project ID: {}", user_login, project_id);
 
-    let project = get_project_for_owner(&state, project_id, &user_login, claims.is_admin).await?;
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::Purge).await?;
+
+    perform_purge(&state, &project, query.force).await?;
+
+    info!("Successfully purged project '{}' for user '{}'.", project.name, user_login);
 
-    deprovision_linked_database(&state, project_id, &user_login, claims.is_admin).await?;
+    Ok((StatusCode::OK, Json(ActionResponse::success("Project purged successfully."))))
+}
 
-    docker_service::remove_container(&state.docker_client, &project.container_name).await?;
+/// Supprime un projet et toutes ses ressources, partagé avec le selftest d'administration.
+pub(crate) async fn perform_purge(state: &AppState, project: &Project, force_bucket: bool) -> Result<(), AppError>
+{
+    let previous_image = project_service::get_previous_image(&state.db_pool, project.id).await?;
 
-    remove_persistent_volume(&state, &project).await?;
+    deprovision_linked_database(state, project.id).await?;
 
-    remove_image_best_effort(&state, &project.deployed_image_tag).await;
+    deprovision_project_bucket(state, project.id, force_bucket).await?;
 
-    project_service::delete_project_by_id(&state.db_pool, project.id).await?;
+    docker_service::remove_container(&state.docker_client, &project.container_name, project.stop_grace_seconds).await?;
 
-    info!("Successfully purged project '{}' for user '{}'.", project.name, user_login);
+    remove_persistent_volume(state, project).await?;
 
-    Ok((
-        StatusCode::OK,
-        Json(json!({
-            "status": "success",
-            "message": "Project purged successfully."
-        })),
-    ))
+    remove_image_best_effort(state, &project.deployed_image_tag).await;
+
+    if let Some(previous) = previous_image
+        && previous.tag != project.deployed_image_tag
+    {
+        remove_image_best_effort(state, &previous.tag).await;
+    }
+
+    project_service::delete_project_by_id(&state.db_pool, project.id).await?;
+
+    Ok(())
 }
 
 pub async fn list_owned_projects_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let user_login = claims.sub;
+    let user_login = ctx.login;
     info!("Fetching owned projects for user '{}'", user_login);
     
     let projects = project_service::get_projects_by_owner(&state.db_pool, &user_login).await?;
     
-    Ok((StatusCode::OK, Json(json!({ "projects": projects }))))
+    Ok((StatusCode::OK, Json(ProjectListResponse { projects })))
 }
 
 pub async fn list_participating_projects_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let user_login = claims.sub;
+    let user_login = ctx.login;
     info!("Fetching projects where user '{}' is a participant", user_login);
     
     let projects = project_service::get_participating_projects(&state.db_pool, &user_login).await?;
     
-    Ok((StatusCode::OK, Json(json!({ "projects": projects }))))
+    Ok((StatusCode::OK, Json(ProjectListResponse { projects })))
 }
 
-pub async fn get_project_details_handler(
+/// Projets possédés et projets auxquels l'utilisateur participe, avec son rôle, pour l'accueil du dashboard.
+/// Endpoint: GET /api/me/memberships
+pub async fn list_my_memberships_handler(
     State(state): State<AppState>,
-    claims: Claims,
-    Path(project_id): Path<i32>,
+    ctx: AccessContext,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let user_login = claims.sub;
-    debug!("User '{}' fetching details for project ID: {}", user_login, project_id);
-
-    let project = get_project_for_user(&state, project_id, &user_login, claims.is_admin).await?;
-
-    let mut project_data = project;
-    decrypt_project_env_vars(&mut project_data, &state.config.encryption_key)?;
-
-    let database_details = get_database_details(&state, project_data.id).await?;
-    let participants = project_service::get_project_participants(&state.db_pool, project_data.id).await?;
+    let projects = project_service::get_member_projects(&state.db_pool, &ctx.login).await?;
 
-    let response = ProjectDetailsResponse
-    {
-        project: project_data,
-        participants,
-        database: database_details,
-    };
+    let memberships = projects
+        .into_iter()
+        .map(|project|
+        {
+            let role = if project.owner == ctx.login { ProjectRole::Owner } else { ProjectRole::Participant };
+            ProjectMembership { project, role }
+        })
+        .collect();
 
-    Ok((StatusCode::OK, Json(json!({ "project": response }))))
+    Ok(Json(MembershipsResponse { memberships }))
 }
 
-pub async fn start_project_handler(
+/// Endpoint: GET /`api/projects/{project_id}/participants?page=`
+pub async fn list_participants_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
+    Query(query): Query<ParticipantsQuery>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    project_control_handler(state, claims, project_id, ProjectAction::Start).await
+    let page = query.page.unwrap_or(1);
+    if page == 0
+    {
+        return Err(AppError::BadRequest("page starts at 1.".to_string()));
+    }
+
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+
+    let per_page = i64::from(PARTICIPANTS_PER_PAGE);
+    let participants = project_service::get_project_participants_page(&state.db_pool, project.id, per_page, i64::from(page - 1) * per_page).await?;
+    let total = project_service::count_project_participants(&state.db_pool, project.id).await?;
+
+    Ok(Json(ParticipantsPageResponse { participants, page, per_page: PARTICIPANTS_PER_PAGE, total }))
+}
+
+pub async fn get_project_details_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &ctx.login;
+    debug!("User '{}' fetching details for project ID: {}", user_login, project_id);
+
+    let (project, role) = authz::load_project_with_role(&state, &ctx, project_id, RequiredRole::Participant).await?;
+
+    let mut project_data = project;
+    if authz::can_view_env_vars(role)
+    {
+        decrypt_project_env_vars(&mut project_data, &state.config.encryption_key)?;
+    }
+    else
+    {
+        redact_project_env_vars(&mut project_data);
+    }
+
+    let database_details = get_database_details(&state, project_data.id).await?;
+    let participants = project_service::get_project_participants_page(&state.db_pool, project_data.id, i64::from(PARTICIPANTS_PER_PAGE), 0).await?;
+    let participants_total = project_service::count_project_participants(&state.db_pool, project_data.id).await?;
+    let pending_participants = invitation_service::list_for_project(&state.db_pool, project_data.id).await?;
+    let owner_display = user_service::get_display_names(&state.db_pool, std::slice::from_ref(&project_data.owner)).await?
+        .remove(&project_data.owner)
+        .unwrap_or_else(|| project_data.owner.clone());
+
+    let clock_drift_ms = docker_service::get_container_clock_drift(&state.docker_client, &project_data.container_name)
+        .await
+        .unwrap_or_default();
+
+    let runtime = ProjectRuntime
+    {
+        stop_grace_seconds: project_data.stop_grace_seconds,
+        max_stop_grace_seconds: state.config.max_stop_grace_seconds,
+        command: project_data.container_command(),
+        timezone: project_data.timezone.clone(),
+        default_timezone: state.config.default_timezone.clone(),
+        container_port: project_data.http_port(),
+        health_check_max_attempts: project_data.health_check_max_attempts,
+        health_check_interval_ms: project_data.health_check_interval_ms,
+        resource_profile: project_data.resource_profile.clone(),
+        resources: *state.config.resource_profile(&project_data.resource_profile),
+        clock_drift_ms,
+    };
+
+    let response = ProjectDetailsResponse
+    {
+        owner_display,
+        participants,
+        participants_total,
+        pending_participants,
+        scan_ignore_cves: project_data.scan_ignore_cves.clone(),
+        project: project_data,
+        database: database_details,
+        runtime,
+    };
+
+    Ok((StatusCode::OK, Json(ProjectResponse { project: response })))
+}
+
+/// Actions permises à l'appelant sur le projet, pour griser les boutons côté interface.
+/// Endpoint: GET /`api/projects/{project_id}/permissions`
+pub async fn get_project_permissions_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let (_, role) = authz::load_project_with_role(&state, &ctx, project_id, RequiredRole::Participant).await?;
+    Ok(Json(authz::project_permissions(role, state.config.db_credentials_for_participants)))
+}
+
+pub async fn start_project_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    project_control_handler(state, ctx, project_id, ProjectAction::Start).await
 }
 
 pub async fn stop_project_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    project_control_handler(state, claims, project_id, ProjectAction::Stop).await
+    project_control_handler(state, ctx, project_id, ProjectAction::Stop).await
 }
 
 pub async fn restart_project_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    project_control_handler(state, claims, project_id, ProjectAction::Restart).await
+    project_control_handler(state, ctx, project_id, ProjectAction::Restart).await
 }
 
 pub async fn get_project_logs_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
-) -> Result<impl IntoResponse, AppError>
+    Query(query): Query<LogsQuery>,
+) -> Result<Response, AppError>
 {
-    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+    let filter = query.to_filter(state.config.log_max_tail)?;
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+    project.ensure_not_archived()?;
     
-    let logs = docker_service::get_container_logs(&state.docker_client, &project.container_name, "200").await?;
+    let logs = docker_service::get_container_logs(&state.docker_client, &project.container_name, &filter).await?;
     
-    Ok(Json(json!({ "logs": logs })))
+    if query.format == LogsFormat::Text
+    {
+        return Ok(Json(TextLogsResponse { logs: logs.to_text() }).into_response());
+    }
+
+    Ok(Json(logs).into_response())
+}
+
+/// Le délai est lu à chaque arrêt : il s'applique dès le prochain stop, redémarrage ou redéploiement.
+pub async fn update_stop_grace_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<StopGracePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdateStopGrace).await?;
+
+    let max = state.config.max_stop_grace_seconds;
+    if !(1..=max).contains(&payload.stop_grace_seconds)
+    {
+        return Err(AppError::BadRequest(format!("stop_grace_seconds must be between 1 and {max}.")));
+    }
+
+    project_service::update_project_stop_grace(&state.db_pool, project.id, payload.stop_grace_seconds).await?;
+
+    Ok(Json(StopGraceResponse { stop_grace_seconds: payload.stop_grace_seconds }))
+}
+
+/// Les surcharges s'appliquent à la prochaine création de conteneur (redéploiement, recréation, désarchivage).
+/// Endpoint: PUT /api/projects/{project_id}/health-check
+pub async fn update_health_check_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<HealthCheckPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdateHealthCheck).await?;
+
+    if payload.health_check_max_attempts.is_some_and(|attempts| !(1..=MAX_HEALTH_CHECK_ATTEMPTS).contains(&attempts))
+    {
+        return Err(AppError::BadRequest(format!("health_check_max_attempts must be between 1 and {MAX_HEALTH_CHECK_ATTEMPTS}.")));
+    }
+    if payload.health_check_interval_ms.is_some_and(|interval| !HEALTH_CHECK_INTERVAL_MS_RANGE.contains(&interval))
+    {
+        return Err(AppError::BadRequest(format!(
+            "health_check_interval_ms must be between {} and {}.",
+            HEALTH_CHECK_INTERVAL_MS_RANGE.start(), HEALTH_CHECK_INTERVAL_MS_RANGE.end()
+        )));
+    }
+
+    project_service::update_project_health_check(
+        &state.db_pool,
+        project.id,
+        payload.health_check_max_attempts,
+        payload.health_check_interval_ms,
+    ).await?;
+    info!(
+        "User '{}' set health check to {:?} attempts every {:?} ms on project {}",
+        ctx.login, payload.health_check_max_attempts, payload.health_check_interval_ms, project.id
+    );
+
+    Ok(Json(HealthCheckResponse
+    {
+        health_check_max_attempts: payload.health_check_max_attempts,
+        health_check_interval_ms: payload.health_check_interval_ms,
+    }))
+}
+
+/// Exclut le projet de l'arrêt automatique des projets inactifs, ou l'y soumet de nouveau.
+/// Endpoint: PUT /api/projects/{project_id}/keep-alive
+pub async fn update_keep_alive_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<KeepAlivePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdateKeepAlive).await?;
+
+    project_service::update_project_keep_alive(&state.db_pool, project.id, payload.keep_alive).await?;
+    info!("User '{}' set keep-alive to {} on project {}", ctx.login, payload.keep_alive, project.id);
+
+    Ok(Json(KeepAliveResponse { keep_alive: payload.keep_alive }))
+}
+
+/// Remplace la liste des vulnérabilités ignorées par le scan ; elle s'applique au prochain scan
+/// (mise à jour d'image, rebuild, reprise d'un déploiement).
+pub async fn update_scan_ignores_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<ScanIgnoresPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdateScanIgnores).await?;
+
+    let scan_ignore_cves = validation_service::validate_scan_ignore_cves(&payload.scan_ignore_cves)?;
+
+    project_service::update_project_scan_ignores(&state.db_pool, project.id, &scan_ignore_cves).await?;
+    info!("User '{}' set {} ignored vulnerabilities on project {}", ctx.login, scan_ignore_cves.len(), project.id);
+
+    Ok(Json(ScanIgnoresResponse { scan_ignore_cves }))
+}
+
+pub async fn get_project_container_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Owner).await?;
+    project.ensure_not_archived()?;
+
+    let details = docker_service::inspect_container_details(&state.docker_client, &project.container_name).await?
+        .ok_or_else(|| AppError::NotFound(format!("Container for project {project_id} not found.")))?;
+
+    let summary = ContainerInspectSummary::from_inspect(details, &state.config.docker_network, &state.config.app_prefix);
+
+    Ok(Json(ContainerResponse { container: summary }))
 }
 
 pub async fn update_project_image_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
     Json(payload): Json<UpdateImagePayload>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let user_login = &claims.sub;
+    let user_login = &ctx.login;
     info!("User '{}' initiated blue-green image update for project ID: {}", user_login, project_id);
 
-    let project = get_project_for_user(&state, project_id, user_login, claims.is_admin).await?;
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdateImage).await?;
+
+    perform_image_update(&state, &project, user_login, &payload.new_image_url).await
+}
 
+/// Mise à jour blue-green de l'image d'un projet `direct`, partagée avec les deploy hooks.
+pub(crate) async fn perform_image_update(
+    state: &AppState,
+    project: &Project,
+    user_login: &str,
+    new_image_url: &str,
+) -> Result<(StatusCode, Json<ActionResponse>), AppError>
+{
     validate_project_source(&project.source, ProjectSourceType::Direct, "Image update")?;
 
-    let orchestrator = DeploymentOrchestrator::for_update
+    let mut orchestrator = DeploymentOrchestrator::for_update
     (
-        &state,
+        state,
         project.name.clone(),
-        user_login.clone(),
+        user_login.to_string(),
         project.id,
     );
+    orchestrator.start_tracking(DeploymentKind::ImageUpdate, Some(new_image_url), None).await?;
+
+    let result = run_image_update(state, &orchestrator, project, new_image_url).await;
+    orchestrator.finish_tracking(result.is_ok()).await;
+    result
+}
 
+async fn run_image_update(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project: &Project,
+    new_image_url: &str,
+) -> Result<(StatusCode, Json<ActionResponse>), AppError>
+{
     orchestrator.emit_stage(DeploymentStage::Started).await;
 
     let deployment = prepare_blue_green_deployment_with_events(
-        &state,
-        &orchestrator,
-        &project,
-        &payload.new_image_url,
+        state,
+        orchestrator,
+        project,
+        new_image_url,
         None,
     ).await?;
 
@@ -379,62 +1152,144 @@ pub async fn update_project_image_handler(
         info!
         (
             "Project '{}' is already running the latest version of '{}'",
-            project.name, payload.new_image_url
+            project.name, log_safe(new_image_url)
         );
-        return Ok(create_no_change_response("The project is already running the latest version of the image."));
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The project is already running the latest version of the image."))));
     }
 
-    let env_vars = get_decrypted_env_vars(&project, &state.config.encryption_key)?;
+    if let Some(volume_path) = &project.persistent_volume_path
+    {
+        warn_if_volume_shadows_image(state, orchestrator, &deployment.new_image_tag, volume_path, false).await;
+    }
 
-    execute_blue_green_deployment_with_events(
-        &state,
-        &orchestrator,
-        &project,
-        &deployment,
-        env_vars.as_ref(),
-        &deployment.new_image_tag,
-    ).await?;
+    deploy_new_image_with_events(state, orchestrator, project, &deployment, Some(&deployment.new_image_tag), None, None, None).await?;
 
-    orchestrator.emit_completed(deployment.new_container_name, project_id).await;
-    Ok(create_success_response("Project image updated successfully without downtime."))
+    orchestrator.emit_completed(deployment.new_container_name, project.id).await;
+    Ok((StatusCode::OK, Json(ActionResponse::success("Project image updated successfully without downtime."))))
 }
 
 pub async fn rebuild_project_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
+    Query(query): Query<RebuildQuery>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let user_login = &claims.sub;
+    let user_login = &ctx.login;
     info!("User '{}' initiated source rebuild for project ID: {}", user_login, project_id);
 
-    let project = get_project_for_user(&state, project_id, user_login, claims.is_admin).await?;
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::Rebuild).await?;
+
+    match (&query.git_ref, project.source_ref.as_deref())
+    {
+        (Some(git_ref), _) => validation_service::validate_git_ref(git_ref)?,
+        // Un commit épinglé n'a pas de « dernière version » : la référence à construire doit être explicite.
+        (None, Some(pinned)) if github_service::is_commit_sha(pinned) =>
+        {
+            return Err(AppError::BadRequest(format!(
+                "This project is pinned to commit {pinned}. Pass a 'ref' to rebuild from another tag or commit."
+            )));
+        }
+        (None, _) =>
+        {
+            if !query.force && source_is_current(&state, &project).await
+            {
+                info!("Project '{}' already runs the latest commit of its branch, skipping rebuild", project.name);
+                return Ok((StatusCode::OK, Json(ActionResponse::no_change("The latest commit is already deployed."))));
+            }
+        }
+    }
+
+    perform_rebuild(&state, &project, user_login, query.git_ref.as_deref()).await
+}
+
+/// Le dernier commit de la branche suivie est-il celui déployé ? Dans le doute (commit inconnu,
+/// dépôt injoignable, tag épinglé susceptible d'avoir été déplacé), la reconstruction a lieu.
+async fn source_is_current(state: &AppState, project: &Project) -> bool
+{
+    let (Some(deployed_sha), None) = (project.source_commit_sha.as_deref(), &project.source_ref) else
+    {
+        return false;
+    };
+    let branch = project.source_branch.as_deref();
+
+    let head = match github_service::remote_head_sha(&project.source_url, None, branch).await
+    {
+        Err(AppError::ProjectError(ProjectErrorCode::GithubAccountNotLinked | ProjectErrorCode::InvalidGithubUrl)) =>
+        {
+            match get_repository_token(state, &project.source_url).await
+            {
+                Ok(token) => github_service::remote_head_sha(&project.source_url, Some(&token), branch).await,
+                Err(e) => Err(e),
+            }
+        }
+        result => result,
+    };
+
+    match head
+    {
+        Ok(head) => head.as_deref() == Some(deployed_sha),
+        Err(e) =>
+        {
+            debug!("Could not resolve remote head of project '{}': {}", project.name, e);
+            false
+        }
+    }
+}
 
+/// Reconstruction blue-green d'un projet `github` depuis sa source, partagée avec les deploy hooks.
+/// `git_ref` remplace la référence épinglée (ou la branche) du projet.
+pub(crate) async fn perform_rebuild(
+    state: &AppState,
+    project: &Project,
+    user_login: &str,
+    git_ref: Option<&str>,
+) -> Result<(StatusCode, Json<ActionResponse>), AppError>
+{
     validate_project_source(&project.source, ProjectSourceType::Github, "Source rebuild")?;
 
-    let orchestrator = DeploymentOrchestrator::for_update
+    let mut orchestrator = DeploymentOrchestrator::for_update
     (
-        &state,
+        state,
         project.name.clone(),
-        user_login.clone(),
+        user_login.to_string(),
         project.id,
     );
+    orchestrator.start_tracking(DeploymentKind::Rebuild, None, None).await?;
 
+    let result = run_rebuild(state, &orchestrator, project, git_ref).await;
+    orchestrator.finish_tracking(result.is_ok()).await;
+    result
+}
+
+async fn run_rebuild(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project: &Project,
+    git_ref: Option<&str>,
+) -> Result<(StatusCode, Json<ActionResponse>), AppError>
+{
     orchestrator.emit_stage(DeploymentStage::Started).await;
 
-    let new_image_tag = build_image_from_github_source_with_events(
-        &state,
-        &orchestrator,
+    // Référence à enregistrer avec la nouvelle image, si elle change.
+    let new_ref = git_ref.filter(|git_ref| project.source_ref.as_deref() != Some(*git_ref));
+    let checkout = Checkout::new(project.source_branch.as_deref(), git_ref.or(project.source_ref.as_deref()));
+
+    let (new_image_tag, commit_sha) = build_image_from_github_source_with_events(
+        state,
+        orchestrator,
         &project.name,
         &project.source_url,
-        project.source_branch.as_deref(),
+        checkout,
         project.source_root_dir.as_deref(),
+        project.use_repo_dockerfile,
+        &project.scan_ignore_cves,
     ).await?;
 
     let deployment = prepare_blue_green_deployment_with_events(
-        &state,
-        &orchestrator,
-        &project,
+        state,
+        orchestrator,
+        project,
         &new_image_tag,
         Some(&project.deployed_image_tag),
     ).await?;
@@ -447,142 +1302,861 @@ pub async fn rebuild_project_handler(
             project.name, project.deployed_image_digest
         );
         let _ = docker_service::remove_image(&state.docker_client, &new_image_tag).await;
-        return Ok(create_no_change_response("The project source is already up to date."));
+        // Un commit sans effet sur l'image (documentation...) est tout de même celui déployé.
+        if project.source_commit_sha.as_deref() != Some(commit_sha.as_str())
+        {
+            project_service::update_project_source_commit(&state.db_pool, project.id, Some(&commit_sha)).await?;
+        }
+        if let Some(new_ref) = new_ref
+        {
+            project_service::update_project_source_ref(&state.db_pool, project.id, new_ref).await?;
+        }
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The project source is already up to date."))));
     }
 
-    let env_vars = get_decrypted_env_vars(&project, &state.config.encryption_key)?;
+    if let Some(volume_path) = &project.persistent_volume_path
+    {
+        warn_if_volume_shadows_image(state, orchestrator, &new_image_tag, volume_path, !project.use_repo_dockerfile).await;
+    }
 
-    execute_blue_green_deployment_with_events(
-        &state,
-        &orchestrator,
-        &project,
-        &deployment,
-        env_vars.as_ref(),
-        &project.deployed_image_tag,
-    ).await?;
+    let evicted_image = image_leaving_rollback_slot(state, project).await?;
+    deploy_new_image_with_events(state, orchestrator, project, &deployment, evicted_image.as_deref(), Some(&commit_sha), new_ref, None).await?;
 
-    orchestrator.emit_completed(deployment.new_container_name, project_id).await;
+    orchestrator.emit_completed(deployment.new_container_name, project.id).await;
 
-    Ok(create_success_response("Project rebuilt and updated successfully from the latest source."))
+    Ok((StatusCode::OK, Json(ActionResponse::success("Project rebuilt and updated successfully from the latest source."))))
 }
 
-pub async fn add_participant_handler(
-    State(state): State<AppState>,
-    claims: Claims,
-    Path(project_id): Path<i32>,
-    Json(payload): Json<ParticipantPayload>,
-) -> Result<impl IntoResponse, AppError>
+/// Reconstruit un projet `upload` à partir d'une nouvelle archive, puis le bascule en blue-green.
+pub(crate) async fn perform_upload_rebuild(
+    state: &AppState,
+    project: &Project,
+    user_login: &str,
+    archive: &UploadedArchive,
+) -> Result<(StatusCode, Json<ActionResponse>), AppError>
 {
-    let user_login = &claims.sub;
-    info!(
-        "User '{}' trying to add participant '{}' to project {}",
-        user_login, payload.participant_id, project_id
+    validate_project_source(&project.source, ProjectSourceType::Upload, "Upload redeployment")?;
+
+    let mut orchestrator = DeploymentOrchestrator::for_update
+    (
+        state,
+        project.name.clone(),
+        user_login.to_string(),
+        project.id,
     );
+    orchestrator.start_tracking(DeploymentKind::Rebuild, None, None).await?;
 
-    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+    let result = run_upload_rebuild(state, &orchestrator, project, archive).await;
+    orchestrator.finish_tracking(result.is_ok()).await;
+    result
+}
 
-    if project.owner == payload.participant_id
-    {
-        return Err(ProjectErrorCode::OwnerCannotBeParticipant.into());
-    }
+async fn run_upload_rebuild(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project: &Project,
+    archive: &UploadedArchive,
+) -> Result<(StatusCode, Json<ActionResponse>), AppError>
+{
+    orchestrator.emit_stage(DeploymentStage::Started).await;
 
-    project_service::add_participant_to_project(&state.db_pool, project_id, &payload.participant_id).await?;
+    let new_image_tag = build_image_from_upload_with_events(state, orchestrator, &project.name, archive, &project.scan_ignore_cves).await?;
 
-    info!("Participant '{}' added successfully to project {}", payload.participant_id, project_id);
-    
-    Ok((
-        StatusCode::CREATED,
-        Json(json!({"status": "success", "message": "Participant added."})),
-    ))
-}
+    let deployment = prepare_blue_green_deployment_with_events(
+        state,
+        orchestrator,
+        project,
+        &new_image_tag,
+        Some(&project.deployed_image_tag),
+    ).await?;
 
-pub async fn remove_participant_handler(
-    State(state): State<AppState>,
-    claims: Claims,
-    Path((project_id, participant_id)): Path<(i32, String)>,
-) -> Result<impl IntoResponse, AppError>
-{
-    let user_login = &claims.sub;
-    info!(
-        "User '{}' trying to remove participant '{}' from project {}",
-        user_login, participant_id, project_id
-    );
+    let source_label = archive.source_label();
+    if project.deployed_image_digest == deployment.new_image_digest
+    {
+        info!("Project '{}' already serves the uploaded files (digest: {})", project.name, project.deployed_image_digest);
+        let _ = docker_service::remove_image(&state.docker_client, &new_image_tag).await;
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The uploaded files are already deployed."))));
+    }
 
-    get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+    if let Some(volume_path) = &project.persistent_volume_path
+    {
+        warn_if_volume_shadows_image(state, orchestrator, &new_image_tag, volume_path, true).await;
+    }
 
-    project_service::remove_participant_from_project(&state.db_pool, project_id, &participant_id).await?;
+    let evicted_image = image_leaving_rollback_slot(state, project).await?;
+    deploy_new_image_with_events(state, orchestrator, project, &deployment, evicted_image.as_deref(), None, None, Some(&source_label)).await?;
 
-    info!("Participant '{}' removed successfully from project {}", participant_id, project_id);
-    
-    Ok((
-        StatusCode::OK,
-        Json(json!({"status": "success", "message": "Participant removed."})),
-    ))
+    orchestrator.emit_completed(deployment.new_container_name, project.id).await;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Project rebuilt and updated successfully from the uploaded archive."))))
 }
 
-pub async fn update_env_vars_handler(
+/// Revient à l'image remplacée par la dernière mise à jour ou reconstruction, en blue-green.
+/// L'image quittée devient à son tour la cible d'un retour arrière.
+pub async fn rollback_project_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
-    Json(payload): Json<UpdateEnvPayload>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let user_login = &claims.sub;
-    info!("User '{}' initiated blue-green env var update for project ID: {}", user_login, project_id);
+    let user_login = &ctx.login;
+    info!("User '{}' initiated rollback for project ID: {}", user_login, project_id);
 
-    validation_service::validate_env_vars(&payload.env_vars)?;
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::Rollback).await?;
 
-    let project = get_project_for_user(&state, project_id, user_login, claims.is_admin).await?;
+    let Some(previous) = project_service::get_previous_image(&state.db_pool, project.id).await?
+        .filter(|previous| previous.digest != project.deployed_image_digest)
+    else
+    {
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("There is no previous image to roll back to."))));
+    };
 
-    let orchestrator = DeploymentOrchestrator::for_update
+    if docker_service::get_image_digest(&state.docker_client, &previous.digest).await?.is_none()
+    {
+        warn!("Previous image '{}' of project '{}' is no longer available", log_safe(&previous.tag), project.name);
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The previous image is no longer available on this host."))));
+    }
+
+    let mut orchestrator = DeploymentOrchestrator::for_update
     (
         &state,
         project.name.clone(),
-        user_login.clone(),
+        user_login.to_string(),
         project.id,
     );
+    orchestrator.start_tracking(DeploymentKind::Rollback, Some(&previous.tag), None).await?;
+    orchestrator.record_digest(&previous.digest);
 
-    orchestrator.emit_stage(DeploymentStage::Started).await;
+    let result = run_rollback(&state, &orchestrator, &project, &previous).await;
+    orchestrator.finish_tracking(result.is_ok()).await;
+    result?;
 
-    let deployment = create_blue_green_deployment_for_env_update(&state, &project);
+    info!("Project '{}' rolled back to image '{}'", project.name, log_safe(&previous.tag));
 
-    execute_env_vars_blue_green_deployment_with_events(
-        &state,
-        &orchestrator,
-        &project,
-        &deployment,
-        &payload.env_vars,
+    Ok((StatusCode::OK, Json(ActionResponse::success("Project rolled back to its previous image."))))
+}
+
+async fn run_rollback(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project: &Project,
+    previous: &PreviousImage,
+) -> Result<(), AppError>
+{
+    orchestrator.emit_stage(DeploymentStage::Started).await;
+
+    let env_vars = project_service::get_decrypted_env_vars(project, &state.config.encryption_key)?;
+    let spec = NewContainerSpec
+    {
+        container_name: blue_green::new_container_name(state, project),
+        image: previous.digest.clone(),
+        env_vars: project_service::runtime_env_vars(state, project, env_vars).await?,
+        command: project.container_command(),
+        container_port: project.http_port(),
+        resource_profile: project.resource_profile.clone(),
+        // L'image cible et l'image quittée restent toutes deux disponibles pour un nouveau retour arrière.
+        rollback_image: None,
+        replaced_image: None,
+    };
+    let new_container_name = spec.container_name.clone();
+
+    let update = MetadataUpdate::Image
+    {
+        tag: &previous.tag,
+        digest: &previous.digest,
+        source_url: (project.source == ProjectSourceType::Direct).then_some(previous.tag.as_str()),
+        // Le commit de l'image précédente n'est pas conservé.
+        commit_sha: None,
+    };
+
+    blue_green::execute(state, orchestrator, project, spec, &[update]).await?;
+
+    orchestrator.emit_completed(new_container_name, project.id).await;
+    Ok(())
+}
+
+/// Reprend un déploiement échoué à l'étape `resume`, ou le relance entièrement si ses artefacts ont disparu.
+pub(crate) async fn perform_retry(
+    state: &AppState,
+    project: &Project,
+    failed: &Deployment,
+    resume: Option<ResumeStage>,
+    user_login: &str,
+) -> Result<(StatusCode, Json<ActionResponse>), AppError>
+{
+    let (Some(resume), Some(image_tag)) = (resume, failed.image_tag.as_deref()) else
+    {
+        return match failed.kind
+        {
+            DeploymentKind::Rebuild => perform_rebuild(state, project, user_login, None).await,
+            DeploymentKind::ImageUpdate =>
+            {
+                let image = failed.requested_image.as_deref()
+                    .ok_or_else(|| AppError::BadRequest("This deployment cannot be retried.".to_string()))?;
+                perform_image_update(state, project, user_login, image).await
+            }
+            DeploymentKind::Creation | DeploymentKind::EnvUpdate | DeploymentKind::Rollback =>
+            {
+                Err(AppError::BadRequest("This deployment cannot be retried.".to_string()))
+            }
+        };
+    };
+
+    let expected_source = match failed.kind
+    {
+        DeploymentKind::Rebuild => ProjectSourceType::Github,
+        DeploymentKind::ImageUpdate => ProjectSourceType::Direct,
+        DeploymentKind::Creation | DeploymentKind::EnvUpdate | DeploymentKind::Rollback =>
+        {
+            return Err(AppError::BadRequest("This deployment cannot be retried.".to_string()));
+        }
+    };
+    validate_project_source(&project.source, expected_source, "Deployment retry")?;
+
+    let mut orchestrator = DeploymentOrchestrator::for_update
+    (
+        state,
+        project.name.clone(),
+        user_login.to_string(),
+        project.id,
+    );
+    orchestrator.start_tracking(failed.kind, failed.requested_image.as_deref(), Some(failed.id)).await?;
+    // L'image est désormais rattachée à la reprise, qui la conservera à son tour en cas d'échec.
+    deployment_service::release_artifacts(&state.db_pool, failed.id).await?;
+
+    let result = resume_deployment(state, &orchestrator, project, failed.kind, image_tag, failed.commit_sha.as_deref(), resume).await;
+    orchestrator.finish_tracking(result.is_ok()).await;
+    result
+}
+
+async fn resume_deployment(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project: &Project,
+    kind: DeploymentKind,
+    image_tag: &str,
+    commit_sha: Option<&str>,
+    resume: ResumeStage,
+) -> Result<(StatusCode, Json<ActionResponse>), AppError>
+{
+    orchestrator.emit_stage(DeploymentStage::Started).await;
+    orchestrator.emit_resuming(resume).await;
+    orchestrator.record_image(image_tag, resume == ResumeStage::Deploy);
+    if let Some(commit_sha) = commit_sha
+    {
+        orchestrator.record_commit(commit_sha);
+    }
+
+    if resume == ResumeStage::Scan
+    {
+        orchestrator.with_stages
+        (
+            DeploymentStage::ScanningImage,
+            DeploymentStage::ImageScanned,
+            "Image scan",
+            docker_service::scan_image_with_grype(image_tag, &project.scan_ignore_cves, &state.config),
+        ).await?;
+        orchestrator.record_image(image_tag, true);
+    }
+
+    let deployment = prepare_blue_green_deployment_with_events(
+        state,
+        orchestrator,
+        project,
+        image_tag,
+        Some(&project.deployed_image_tag),
+    ).await?;
+
+    if project.deployed_image_digest == deployment.new_image_digest
+    {
+        info!("Project '{}' is already running image '{}'", project.name, log_safe(image_tag));
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The project is already running this image."))));
+    }
+
+    let old_image_to_cleanup = if kind == DeploymentKind::Rebuild
+    {
+        image_leaving_rollback_slot(state, project).await?
+    }
+    else
+    {
+        Some(deployment.new_image_tag.clone())
+    };
+
+    deploy_new_image_with_events(state, orchestrator, project, &deployment, old_image_to_cleanup.as_deref(), commit_sha, None, None).await?;
+
+    orchestrator.emit_completed(deployment.new_container_name, project.id).await;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Deployment resumed and completed successfully."))))
+}
+
+pub async fn add_participant_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<ParticipantPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &ctx.login;
+    info!(
+        "User '{}' trying to add participant '{}' to project {}",
+        user_login, log_safe(&payload.participant_id), project_id
+    );
+
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageParticipants).await?;
+
+    if project.owner == payload.participant_id
+    {
+        return Err(ProjectErrorCode::OwnerCannotBeParticipant.into());
+    }
+
+    if state.config.participant_invitations_enabled
+    {
+        if project_service::is_project_participant(&state.db_pool, project_id, &payload.participant_id).await?
+        {
+            return Err(AppError::BadRequest(format!("'{}' is already a participant of this project.", payload.participant_id)));
+        }
+
+        // L'acceptation vérifie de nouveau la limite : d'autres invitations ont pu être acceptées entre-temps.
+        let max_participants = state.config.participants_max_per_project;
+        if project_service::count_project_participants(&state.db_pool, project_id).await? >= max_participants
+        {
+            return Err(ProjectErrorCode::ParticipantLimitReached(max_participants).into());
+        }
+
+        let invitation = invitation_service::invite(
+            &state.db_pool, project_id, &payload.participant_id, user_login, state.config.participant_invitation_ttl_days
+        ).await?;
+
+        info!("Participant '{}' invited to project {} until {}", log_safe(&payload.participant_id), project_id, invitation.expires_at);
+
+        return Ok((StatusCode::CREATED, Json(ActionResponse::success("Invitation sent."))));
+    }
+
+    project_service::add_participant_to_project(
+        &state.db_pool, project_id, &payload.participant_id, state.config.participants_max_per_project
+    ).await?;
+
+    info!("Participant '{}' added successfully to project {}", log_safe(&payload.participant_id), project_id);
+    
+    Ok((
+        StatusCode::CREATED,
+        Json(ActionResponse::success("Participant added.")),
+    ))
+}
+
+pub async fn remove_participant_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, participant_id)): Path<(i32, String)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &ctx.login;
+    info!(
+        "User '{}' trying to remove participant '{}' from project {}",
+        user_login, log_safe(&participant_id), project_id
+    );
+
+    authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageParticipants).await?;
+
+    // Retirer un participant en attente annule son invitation.
+    if invitation_service::cancel(&state.db_pool, project_id, &participant_id).await?
+    {
+        info!("Pending invitation of '{}' to project {} cancelled", log_safe(&participant_id), project_id);
+    }
+
+    project_service::remove_participant_from_project(&state.db_pool, project_id, &participant_id).await?;
+
+    info!("Participant '{}' removed successfully from project {}", log_safe(&participant_id), project_id);
+    
+    Ok((
+        StatusCode::OK,
+        Json(ActionResponse::success("Participant removed.")),
+    ))
+}
+
+pub async fn update_env_vars_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<UpdateEnvPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &ctx.login;
+    info!("User '{}' initiated blue-green env var update for project ID: {}", user_login, project_id);
+
+    validation_service::validate_env_vars(&payload.env_vars)?;
+
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdateEnvVars).await?;
+
+    let mut orchestrator = DeploymentOrchestrator::for_update
+    (
+        &state,
+        project.name.clone(),
+        user_login.clone(),
+        project.id,
+    );
+    orchestrator.start_tracking(DeploymentKind::EnvUpdate, None, None).await?;
+    orchestrator.record_digest(&project.deployed_image_digest);
+
+    orchestrator.emit_stage(DeploymentStage::Started).await;
+
+    let result = recreate_container_with_events(
+        &state,
+        &orchestrator,
+        &project,
+        RecreationChange::EnvVars(&payload.env_vars),
+    ).await;
+    orchestrator.finish_tracking(result.is_ok()).await;
+    let new_container_name = result?;
+
+    orchestrator.emit_completed(new_container_name, project_id).await;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Environment variables updated successfully. The project has been restarted."))))
+}
+
+/// Recrée le conteneur d'un projet qui reçoit les identifiants de sa base (`HANGAR_DB_*`), après leur rotation.
+/// Renvoie `true` si le conteneur a été recréé ; un échec est signalé aux membres du projet sans annuler la rotation.
+pub(crate) async fn redeploy_with_rotated_credentials(state: &AppState, user_login: &str, project_id: i32) -> bool
+{
+    let project = match project_service::get_project_by_id(&state.db_pool, project_id).await
+    {
+        Ok(Some(project)) if project.inject_database_env && !project.archived => project,
+        Ok(_) => return false,
+        Err(e) =>
+        {
+            error!("Failed to load project ID {} after a database password rotation: {}", project_id, e);
+            return false;
+        }
+    };
+
+    redeploy_after_platform_change(
+        state,
+        user_login,
+        &project,
+        "after a database password rotation",
+        "The database password was rotated but the container could not be recreated: it still uses the previous password until the next redeployment.",
+    ).await
+}
+
+/// Recrée le conteneur d'un projet dont les domaines vérifiés ont changé, pour mettre à jour sa règle Traefik.
+/// Renvoie `true` si le conteneur a été recréé ; un projet archivé recevra ses domaines à sa restauration.
+pub(crate) async fn redeploy_with_domains(state: &AppState, user_login: &str, project_id: i32) -> bool
+{
+    let project = match project_service::get_project_by_id(&state.db_pool, project_id).await
+    {
+        Ok(Some(project)) if !project.archived => project,
+        Ok(_) => return false,
+        Err(e) =>
+        {
+            error!("Failed to load project ID {} after a custom domain change: {}", project_id, e);
+            return false;
+        }
+    };
+
+    redeploy_after_platform_change(
+        state,
+        user_login,
+        &project,
+        "after a custom domain change",
+        "The custom domains were updated but the container could not be recreated: they take effect at the next redeployment.",
+    ).await
+}
+
+/// Recrée le conteneur sans modifier le projet (`RecreationChange::Platform`). Un échec est signalé
+/// aux membres du projet par `failure_warning`.
+async fn redeploy_after_platform_change(
+    state: &AppState,
+    user_login: &str,
+    project: &Project,
+    reason: &str,
+    failure_warning: &str,
+) -> bool
+{
+    match recreate_for_platform_change(state, user_login, project).await
+    {
+        Ok(_) =>
+        {
+            info!("Project '{}' redeployed {}", project.name, reason);
+            true
+        }
+        Err(e) =>
+        {
+            warn!("Failed to redeploy project '{}' {}: {}", project.name, reason, e);
+            let event = SystemEvent::warning(failure_warning.to_string());
+            state.sse_manager.emit_to_project(project.id, SseEvent::System(event)).await;
+            false
+        }
+    }
+}
+
+/// Recrée le conteneur en blue-green avec la configuration actuelle de la plateforme, sans modifier
+/// le projet. Renvoie le nom du nouveau conteneur.
+pub(crate) async fn recreate_for_platform_change(
+    state: &AppState,
+    user_login: &str,
+    project: &Project,
+) -> Result<String, AppError>
+{
+    let mut orchestrator = DeploymentOrchestrator::for_update
+    (
+        state,
+        project.name.clone(),
+        user_login.to_string(),
+        project.id,
+    );
+
+    orchestrator.start_tracking(DeploymentKind::EnvUpdate, None, None).await?;
+    orchestrator.record_digest(&project.deployed_image_digest);
+    orchestrator.emit_stage(DeploymentStage::Started).await;
+
+    let result = recreate_container_with_events(state, &orchestrator, project, RecreationChange::Platform).await;
+    orchestrator.finish_tracking(result.is_ok()).await;
+    let new_container_name = result?;
+
+    orchestrator.emit_completed(new_container_name.clone(), project.id).await;
+    Ok(new_container_name)
+}
+
+/// Remplace la surcharge de l'entrypoint et de la commande, puis recrée le conteneur sans interruption.
+pub async fn update_container_command_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<UpdateCommandPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &ctx.login;
+    info!("User '{}' initiated blue-green container command update for project ID: {}", user_login, project_id);
+
+    validation_service::validate_container_command(&payload.container_command)?;
+
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdateCommand).await?;
+
+    if project.container_command() == payload.container_command
+    {
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The container command is unchanged."))));
+    }
+
+    let orchestrator = DeploymentOrchestrator::for_update
+    (
+        &state,
+        project.name.clone(),
+        user_login.clone(),
+        project.id,
+    );
+
+    orchestrator.emit_stage(DeploymentStage::Started).await;
+
+    let new_container_name = recreate_container_with_events(
+        &state,
+        &orchestrator,
+        &project,
+        RecreationChange::Command(&payload.container_command),
+    ).await?;
+
+    orchestrator.emit_completed(new_container_name, project_id).await;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Container command updated successfully. The project has been restarted."))))
+}
+
+/// Change le port HTTP ciblé par Traefik, puis recrée le conteneur sans interruption.
+pub async fn update_container_port_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<UpdatePortPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &ctx.login;
+    info!("User '{}' initiated blue-green container port update for project ID: {}", user_login, project_id);
+
+    validation_service::validate_container_port(payload.container_port)?;
+
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdatePort).await?;
+
+    if project.http_port() == payload.container_port
+    {
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The container port is unchanged."))));
+    }
+
+    let orchestrator = DeploymentOrchestrator::for_update
+    (
+        &state,
+        project.name.clone(),
+        user_login.clone(),
+        project.id,
+    );
+
+    orchestrator.emit_stage(DeploymentStage::Started).await;
+
+    let new_container_name = recreate_container_with_events(
+        &state,
+        &orchestrator,
+        &project,
+        RecreationChange::Port(payload.container_port),
+    ).await?;
+
+    orchestrator.emit_completed(new_container_name, project_id).await;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Container port updated successfully. The project has been restarted."))))
+}
+
+/// Applique au projet un autre profil de ressources (ulimits, pids, /tmp), puis recrée le conteneur
+/// sans interruption. Réservé aux administrateurs.
+/// Endpoint: PUT /api/admin/projects/{project_id}/resource-profile
+pub async fn update_resource_profile_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<ResourceProfilePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &ctx.login;
+    info!("Admin '{}' changing the resource profile of project ID {} to '{}'", user_login, project_id, payload.profile);
+
+    if !state.config.resource_profiles.contains_key(&payload.profile)
+    {
+        return Err(AppError::BadRequest(format!("Unknown resource profile '{}'.", payload.profile)));
+    }
+
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::UpdateResourceProfile).await?;
+
+    if project.resource_profile == payload.profile
+    {
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The resource profile is unchanged."))));
+    }
+
+    let orchestrator = DeploymentOrchestrator::for_update
+    (
+        &state,
+        project.name.clone(),
+        user_login.clone(),
+        project.id,
+    );
+
+    orchestrator.emit_stage(DeploymentStage::Started).await;
+
+    let new_container_name = recreate_container_with_events(
+        &state,
+        &orchestrator,
+        &project,
+        RecreationChange::ResourceProfile(&payload.profile),
     ).await?;
 
-    orchestrator.emit_completed(deployment.new_container_name, project_id).await;
+    orchestrator.emit_completed(new_container_name, project_id).await;
 
-    Ok(create_success_response("Environment variables updated successfully. The project has been restarted."))
+    Ok((StatusCode::OK, Json(ActionResponse::success("Resource profile updated successfully. The project has been restarted."))))
+}
+
+/// Met le projet en pause : le conteneur est arrêté puis supprimé, le volume, la base et l'image sont conservés.
+/// Endpoint: POST /api/projects/{project_id}/archive
+pub async fn archive_project_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::Archive).await?;
+
+    if project.archived
+    {
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The project is already archived."))));
+    }
+
+    info!("User '{}' archiving project '{}'", ctx.login, project.name);
+
+    // Le drapeau est posé avant l'arrêt : celui-ci n'est pas signalé comme une panne.
+    project_service::update_project_archived(&state.db_pool, project.id, true).await?;
+
+    if let Err(e) = docker_service::remove_container(&state.docker_client, &project.container_name, project.stop_grace_seconds).await
+    {
+        error!("Failed to remove container '{}' while archiving project '{}'", project.container_name, project.name);
+        if let Err(revert) = project_service::update_project_archived(&state.db_pool, project.id, false).await
+        {
+            error!("Failed to revert archived flag of project '{}': {}", project.name, revert);
+        }
+        return Err(e.into());
+    }
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Project archived. Its volume, database and image are kept."))))
+}
+
+/// Recrée le conteneur d'un projet archivé depuis l'image déployée, reconstruite depuis la source
+/// si elle a disparu du démon.
+/// Endpoint: POST /api/projects/{project_id}/unarchive
+pub async fn unarchive_project_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &ctx.login;
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::Unarchive).await?;
+
+    if !project.archived
+    {
+        return Ok((StatusCode::OK, Json(ActionResponse::no_change("The project is not archived."))));
+    }
+
+    info!("User '{}' unarchiving project '{}'", user_login, project.name);
+
+    let orchestrator = DeploymentOrchestrator::for_update
+    (
+        &state,
+        project.name.clone(),
+        user_login.clone(),
+        project.id,
+    );
+
+    orchestrator.emit_stage(DeploymentStage::Started).await;
+
+    let rebuilt = if docker_service::get_image_digest(&state.docker_client, &project.deployed_image_digest).await?.is_some()
+    {
+        None
+    }
+    else
+    {
+        Some(rebuild_missing_image_with_events(&state, &orchestrator, &project).await?)
+    };
+    let image = rebuilt.as_ref().map_or(project.deployed_image_digest.as_str(), |(_, digest)| digest.as_str());
+
+    if let Err(e) = restore_container_with_events(&state, &orchestrator, &project, image).await
+    {
+        if let Some((source, _)) = &rebuilt
+        {
+            remove_image_best_effort(&state, &source.image_tag).await;
+        }
+        return Err(e);
+    }
+
+    let mut tx = state.db_pool.begin()
+        .await
+        .map_err(|_| AppError::InternalServerError)?;
+    project_service::update_project_archived(&mut *tx, project.id, false).await?;
+    if let Some((source, digest)) = &rebuilt
+    {
+        project_service::update_project_image(&mut *tx, project.id, &source.image_tag, digest, source.commit_sha.as_deref(), None).await?;
+    }
+    tx.commit().await.map_err(|e|
+    {
+        error!("Failed to commit unarchiving of project '{}': {}", project.name, e);
+        AppError::InternalServerError
+    })?;
+
+    orchestrator.emit_completed(project.container_name.clone(), project_id).await;
+
+    Ok((StatusCode::OK, Json(ActionResponse::success("Project unarchived. Its container has been recreated."))))
 }
 
 // ============================================================================
 // Private Helper Functions - Validation
 // ============================================================================
 
-async fn validate_deploy_payload(payload: &mut DeployPayload) -> Result<(), AppError>
+/// Phase de validation d'un déploiement, partagée par le déploiement réel et le dry-run.
+/// Toutes les vérifications sont exécutées, sans aucune opération Docker.
+async fn plan_deployment(
+    state: &AppState,
+    payload: &mut DeployPayload,
+    user_login: &str,
+) -> DeploymentPlan
 {
-    payload.project_name = validation_service::validate_project_name(&payload.project_name)?;
+    let mut checks = Vec::new();
+
+    let name_result = validation_service::validate_project_name(&payload.project_name)
+        .map(|name| payload.project_name = name);
+    let name_is_valid = name_result.is_ok();
+    checks.push(DeploymentCheck { name: "project_name", result: name_result });
 
     if let Some(vars) = &payload.env_vars
     {
-        validation_service::validate_env_vars(vars)?;
+        checks.push(DeploymentCheck { name: "env_vars", result: validation_service::validate_env_vars(vars) });
+    }
+
+    if let Some(values) = &payload.template_values
+    {
+        let vars = payload.env_vars.get_or_insert_default();
+        let result = template_service::fill_placeholders(vars, values).map(|filled| *vars = filled);
+        checks.push(DeploymentCheck { name: "template_values", result });
     }
 
     if let Some(path) = &payload.persistent_volume_path
     {
-        validation_service::validate_volume_path(path)?;
+        checks.push(DeploymentCheck { name: "persistent_volume_path", result: validation_service::validate_volume_path(path) });
     }
 
     if let Some(root_dir) = &payload.github_root_dir
     {
-        validation_service::validate_source_root_dir(root_dir)?;
+        checks.push(DeploymentCheck { name: "github_root_dir", result: validation_service::validate_source_root_dir(root_dir) });
     }
 
-    Ok(())
+    if let Some(git_ref) = &payload.github_ref
+    {
+        checks.push(DeploymentCheck { name: "github_ref", result: validation_service::validate_git_ref(git_ref) });
+    }
+
+    if payload.container_command != ContainerCommand::default()
+    {
+        checks.push(DeploymentCheck { name: "container_command", result: validation_service::validate_container_command(&payload.container_command) });
+    }
+
+    if let Some(timezone) = &payload.timezone
+    {
+        checks.push(DeploymentCheck { name: "timezone", result: validation_service::validate_timezone(timezone) });
+    }
+
+    if let Some(port) = payload.container_port
+    {
+        checks.push(DeploymentCheck { name: "container_port", result: validation_service::validate_container_port(port) });
+    }
+
+    if !payload.scan_ignore_cves.is_empty()
+    {
+        let result = validation_service::validate_scan_ignore_cves(&payload.scan_ignore_cves)
+            .map(|ids| payload.scan_ignore_cves = ids);
+        checks.push(DeploymentCheck { name: "scan_ignore_cves", result });
+    }
+
+    let (source, source_result) = match DeploymentSourceSpec::from_payload(payload)
+    {
+        Ok(spec) =>
+        {
+            let result = check_deployment_source(state, &spec).await;
+            (Some(spec), result)
+        }
+        Err(e) => (None, Err(e)),
+    };
+    checks.push(DeploymentCheck { name: "source", result: source_result });
+
+    checks.push(DeploymentCheck
+    {
+        name: "preconditions",
+        result: check_deployment_preconditions(state, user_login, payload).await,
+    });
+
+    let (participants, participants_result) = match prepare_participants(payload.participants.clone(), user_login, state.config.participants_max_per_project)
+    {
+        Ok(participants) => (participants, Ok(())),
+        Err(e) => (Vec::new(), Err(e)),
+    };
+    checks.push(DeploymentCheck { name: "participants", result: participants_result });
+
+    let container_name = format!("{}-{}", state.config.app_prefix, payload.project_name);
+    if name_is_valid
+    {
+        checks.push(DeploymentCheck
+        {
+            name: "container_name",
+            result: check_container_name_available(state, &container_name).await,
+        });
+    }
+
+    DeploymentPlan
+    {
+        container_name,
+        participants,
+        source,
+        checks,
+    }
 }
 
 fn validate_project_source(
@@ -597,6 +2171,7 @@ fn validate_project_source(
         {
             ProjectSourceType::Direct => "direct",
             ProjectSourceType::Github => "github",
+            ProjectSourceType::Upload => "upload",
         };
         
         return Err(AppError::BadRequest(
@@ -617,12 +2192,20 @@ async fn check_deployment_preconditions(
     payload: &DeployPayload,
 ) -> Result<(), AppError>
 {
+    validation_service::validate_project_hostname(&payload.project_name, &state.config.app_domain_suffix)?;
+    validation_service::validate_router_name(
+        &docker_service::traefik_router_name(&state.config.app_prefix, &payload.project_name),
+        &state.config.traefik_reserved_routers,
+    )?;
+
     if project_service::check_owner_exists(&state.db_pool, user_login).await?
     {
         return Err(ProjectErrorCode::OwnerAlreadyExists.into());
     }
 
-    if project_service::check_project_name_exists(&state.db_pool, &payload.project_name).await?
+    let name_is_reserved = payload.project_name == project_service::SELFTEST_PROJECT_NAME
+        && user_login != project_service::SELFTEST_OWNER;
+    if name_is_reserved || project_service::check_project_name_exists(&state.db_pool, &payload.project_name).await?
     {
         return Err(ProjectErrorCode::ProjectNameTaken.into());
     }
@@ -636,9 +2219,42 @@ async fn check_deployment_preconditions(
     Ok(())
 }
 
+/// Valide la source sans la récupérer : référence d'image, ou dépôt GitHub joignable avec sa branche.
+async fn check_deployment_source(state: &AppState, source: &DeploymentSourceSpec) -> Result<(), AppError>
+{
+    match source
+    {
+        DeploymentSourceSpec::Direct { image } => validation_service::validate_image_url(image).map(|_| ()),
+        DeploymentSourceSpec::Github { repo, branch, .. } =>
+        {
+            match github_service::check_remote_branch(repo, None, branch.as_deref()).await
+            {
+                Err(AppError::ProjectError(ProjectErrorCode::GithubAccountNotLinked | ProjectErrorCode::InvalidGithubUrl)) =>
+                {
+                    let token = get_repository_token(state, repo).await?;
+                    github_service::check_remote_branch(repo, Some(&token), branch.as_deref()).await
+                }
+                result => result,
+            }
+        }
+        // Le format de l'archive est vérifié à sa réception, son contenu à l'extraction.
+        DeploymentSourceSpec::Upload { .. } => Ok(()),
+    }
+}
+
+async fn check_container_name_available(state: &AppState, container_name: &str) -> Result<(), AppError>
+{
+    match docker_service::inspect_container_details(&state.docker_client, container_name).await?
+    {
+        Some(_) => Err(ProjectErrorCode::ProjectNameTaken.into()),
+        None => Ok(()),
+    }
+}
+
 fn prepare_participants(
     participants: Vec<String>,
     user_login: &str,
+    max_participants: i64,
 ) -> Result<Vec<String>, AppError>
 {
     let participants_set: HashSet<String> = participants.into_iter().collect();
@@ -647,74 +2263,87 @@ fn prepare_participants(
     {
         return Err(ProjectErrorCode::OwnerCannotBeParticipant.into());
     }
+
+    if i64::try_from(participants_set.len()).unwrap_or(i64::MAX) > max_participants
+    {
+        return Err(ProjectErrorCode::ParticipantLimitReached(max_participants).into());
+    }
     
     Ok(participants_set.into_iter().collect())
 }
 
 async fn prepare_deployment_source_with_events(
     state: &AppState,
-    payload: &DeployPayload,
+    project_name: &str,
+    spec: DeploymentSourceSpec,
+    scan_ignore_cves: &[String],
     orchestrator: &DeploymentOrchestrator<'_>,
 ) -> Result<DeploymentSource, AppError>
 {
-    if let Some(image_url) = &payload.image_url
+    let (image_tag, commit_sha) = match &spec
     {
-        let tag = prepare_direct_source_with_events(state, image_url, orchestrator).await?;
-        return Ok(DeploymentSource
+        DeploymentSourceSpec::Direct { image } =>
         {
-            source_type: ProjectSourceType::Direct,
-            source_url: image_url.clone(),
-            image_tag: tag,
-        });
-    }
-
-    if let Some(github_repo_url) = &payload.github_repo_url
-    {
-        let tag = build_image_from_github_source_with_events(
-            state,
-            orchestrator,
-            &payload.project_name,
-            github_repo_url,
-            payload.github_branch.as_deref(),
-            payload.github_root_dir.as_deref(),
-        ).await?;
-        
-        return Ok(DeploymentSource
+            (prepare_direct_source_with_events(state, image, scan_ignore_cves, orchestrator).await?, None)
+        }
+        DeploymentSourceSpec::Github { repo, branch, git_ref, root_dir, use_repo_dockerfile } =>
         {
-            source_type: ProjectSourceType::Github,
-            source_url: github_repo_url.clone(),
-            image_tag: tag,
-        });
-    }
+            build_image_from_github_source_with_events(
+                state,
+                orchestrator,
+                project_name,
+                repo,
+                Checkout::new(branch.as_deref(), git_ref.as_deref()),
+                root_dir.as_deref(),
+                *use_repo_dockerfile,
+                scan_ignore_cves,
+            ).await.map(|(image_tag, commit_sha)| (image_tag, Some(commit_sha)))?
+        }
+        DeploymentSourceSpec::Upload { archive: Some(archive), .. } =>
+        {
+            (build_image_from_upload_with_events(state, orchestrator, project_name, archive, scan_ignore_cves).await?, None)
+        }
+        DeploymentSourceSpec::Upload { archive: None, .. } =>
+        {
+            let message = "The uploaded files of this project are not kept after the build: upload them again.".to_string();
+            orchestrator.emit_failed(message.clone(), "Upload rebuild".to_string()).await;
+            return Err(AppError::BadRequest(message));
+        }
+    };
 
-    Err(AppError::BadRequest("You must provide either an 'image_url' or a 'github_repo_url'.".to_string()))
+    Ok(DeploymentSource { spec, image_tag, commit_sha })
 }
 
 // ============================================================================
 // Private Helper Functions - GitHub Operations
 // ============================================================================
 
+/// Renvoie le tag de l'image construite et le commit dont elle est issue.
 async fn build_image_from_github_source_with_events
 (
     state: &AppState,
     orchestrator: &DeploymentOrchestrator<'_>,
     project_name: &str,
     repo_url: &str,
-    branch: Option<&str>,
+    checkout: Checkout<'_>,
     root_dir: Option<&str>,
-) -> Result<String, AppError>
+    use_repo_dockerfile: bool,
+    scan_ignore_cves: &[String],
+) -> Result<(String, String), AppError>
 {
+    let (branch, pinned) = match checkout
+    {
+        Checkout::Branch(branch) => (branch, None),
+        Checkout::Pinned(git_ref) => (None, Some(git_ref)),
+    };
     info!(
-        "Building from GitHub source for project '{}'. Repo: '{}', Branch: {:?}, Root Dir: {:?}",
-        project_name, repo_url, branch, root_dir
+        "Building from GitHub source for project '{}'. Repo: '{}', Branch: {:?}, Ref: {:?}, Root Dir: {:?}, Repo Dockerfile: {}",
+        project_name, log_safe(repo_url), branch.map(log_safe), pinned, root_dir.map(log_safe), use_repo_dockerfile
     );
 
-    let temp_dir = TempBuilder::new()
-        .prefix("hangar-build-")
-        .tempdir()
-        .map_err(|_| AppError::InternalServerError)?;
+    let temp_dir = create_build_workspace(state, orchestrator, project_name).await?;
 
-    orchestrator.with_stages
+    let commit_sha = orchestrator.with_stages
     (
         DeploymentStage::CloningRepository 
         {
@@ -722,14 +2351,96 @@ async fn build_image_from_github_source_with_events
         },
         DeploymentStage::RepositoryCloned,
         "Repository clone",
-        clone_repository(state, repo_url, temp_dir.path(), branch),
+        clone_repository(state, repo_url, temp_dir.path(), checkout),
     ).await?;
+    orchestrator.record_commit(&commit_sha);
+
+    // L'image issue du Dockerfile du dépôt passe par le même scan et reçoit les mêmes limites à la création du conteneur.
+    let build_context = if use_repo_dockerfile
+    {
+        repo_build_context(temp_dir.path(), root_dir)?
+    }
+    else
+    {
+        create_dockerfile(&state.config.build_base_image, root_dir, temp_dir.path())?;
+        temp_dir.path().to_path_buf()
+    };
+    // Le Dockerfile du dépôt peut partir de n'importe quelle image : seule l'image de base générée est suivie.
+    let base_image_warm = if use_repo_dockerfile { None } else { Some(base_image_service::is_warm(state, &state.config.build_base_image).await) };
+
+    let image_tag = build_and_scan_image_with_events(state, orchestrator, project_name, &build_context, base_image_warm, scan_ignore_cves).await?;
 
-    create_dockerfile(&state.config.build_base_image, root_dir, temp_dir.path())?;
+    Ok((image_tag, commit_sha))
+}
 
-    let tarball = docker_service::create_tarball(temp_dir.path())?;
+/// Construit un site statique envoyé avec le Dockerfile généré, comme un projet GitHub sans Dockerfile.
+async fn build_image_from_upload_with_events(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project_name: &str,
+    archive: &UploadedArchive,
+    scan_ignore_cves: &[String],
+) -> Result<String, AppError>
+{
+    info!(
+        "Building from uploaded archive '{}' ({}) for project '{}'",
+        archive.file_name, archive.sha256, project_name
+    );
+
+    let temp_dir = create_build_workspace(state, orchestrator, project_name).await?;
+    let max_unpacked_bytes = state.config.upload_max_unpacked_mb.saturating_mul(1024 * 1024);
+
+    let root_dir = orchestrator.with_stages
+    (
+        DeploymentStage::ExtractingArchive
+        {
+            file_name: archive.file_name.clone(),
+        },
+        DeploymentStage::ArchiveExtracted,
+        "Archive extraction",
+        upload_service::extract_archive(archive.clone(), temp_dir.path().to_path_buf(), max_unpacked_bytes),
+    ).await?;
+
+    create_dockerfile(&state.config.build_base_image, root_dir.as_deref(), temp_dir.path())?;
+    let base_image_warm = Some(base_image_service::is_warm(state, &state.config.build_base_image).await);
+
+    build_and_scan_image_with_events(state, orchestrator, project_name, temp_dir.path(), base_image_warm, scan_ignore_cves).await
+}
+
+async fn create_build_workspace(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project_name: &str,
+) -> Result<tempfile::TempDir, AppError>
+{
+    let temp_dir = match build_workspace_service::create_workspace(state, orchestrator.deployment_id(), project_name).await
+    {
+        Ok(temp_dir) => temp_dir,
+        Err(e) =>
+        {
+            orchestrator.emit_failed(e.to_string(), "Build workspace".to_string()).await;
+            return Err(e);
+        }
+    };
+    orchestrator.record_workspace(temp_dir.path()).await;
+
+    Ok(temp_dir)
+}
+
+/// Construit l'image à partir de `build_context` puis la scanne. Renvoie son tag.
+async fn build_and_scan_image_with_events(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project_name: &str,
+    build_context: &std::path::Path,
+    base_image_warm: Option<bool>,
+    scan_ignore_cves: &[String],
+) -> Result<String, AppError>
+{
+    let tarball = docker_service::create_tarball(build_context)?;
     let image_tag = generate_image_tag(project_name);
     
+    let build_started = std::time::Instant::now();
     orchestrator.with_stages
     (
         DeploymentStage::BuildingImage,
@@ -737,19 +2448,28 @@ async fn build_image_from_github_source_with_events
         "Image build",
         docker_service::build_image_from_tar(&state.docker_client, tarball, &image_tag),
     ).await?;
+    info!(
+        "Image build for project '{}' took {}ms (base image warm: {})",
+        project_name, build_started.elapsed().as_millis(), base_image_warm.map_or("n/a", |warm| if warm { "yes" } else { "no" })
+    );
+    orchestrator.record_image(&image_tag, false);
 
     if let Err(scan_error) = orchestrator.with_stages
     (
         DeploymentStage::ScanningImage,
         DeploymentStage::ImageScanned,
         "Image scan",
-        docker_service::scan_image_with_grype(&image_tag, &state.config),
+        docker_service::scan_image_with_grype(&image_tag, scan_ignore_cves, &state.config),
     ).await
     {
-        warn!("Image scan failed, rolling back by removing built image '{}'", image_tag);
-        let _ = docker_service::remove_image(&state.docker_client, &image_tag).await;
+        if !orchestrator.keeps_artifacts()
+        {
+            warn!("Image scan failed, rolling back by removing built image '{}'", image_tag);
+            let _ = docker_service::remove_image(&state.docker_client, &image_tag).await;
+        }
         return Err(scan_error);
     }
+    orchestrator.record_image(&image_tag, true);
 
     Ok(image_tag)
 }
@@ -758,24 +2478,24 @@ async fn clone_repository(
     state: &AppState,
     repo_url: &str,
     destination: &std::path::Path,
-    branch: Option<&str>,
-) -> Result<(), AppError>
+    checkout: Checkout<'_>,
+) -> Result<String, AppError>
 {
-    match github_service::clone_repo(repo_url, destination, None, branch).await
+    match github_service::clone_repo(repo_url, destination, None, checkout).await
     {
-        Ok(()) =>
+        Ok(commit_sha) =>
         {
-            info!("Successfully cloned public repository '{}'", repo_url);
-            Ok(())
+            info!("Successfully cloned public repository '{}'", log_safe(repo_url));
+            Ok(commit_sha)
         }
         Err(AppError::ProjectError(ProjectErrorCode::GithubAccountNotLinked |
 ProjectErrorCode::InvalidGithubUrl)) =>
         {
             warn!(
                 "Public clone failed for '{}'. Assuming private repo and trying authenticated clone.",
-                repo_url
+                log_safe(repo_url)
             );
-            clone_private_repository(state, repo_url, destination, branch).await
+            clone_private_repository(state, repo_url, destination, checkout).await
         }
         Err(e) => Err(e),
     }
@@ -785,8 +2505,20 @@ async fn clone_private_repository(
     state: &AppState,
     repo_url: &str,
     destination: &std::path::Path,
-    branch: Option<&str>,
-) -> Result<(), AppError>
+    checkout: Checkout<'_>,
+) -> Result<String, AppError>
+{
+    let token = get_repository_token(state, repo_url).await?;
+    
+    let commit_sha = github_service::clone_repo(repo_url, destination, Some(&token), checkout).await?;
+    
+    info!("Successfully cloned private repository '{}' using GitHub App token", log_safe(repo_url));
+    
+    Ok(commit_sha)
+}
+
+/// Jeton d'installation de la GitHub App, après vérification de son accès au dépôt.
+pub(crate) async fn get_repository_token(state: &AppState, repo_url: &str) -> Result<String, AppError>
 {
     let (github_owner, repo_name) = github_service::extract_repo_owner_and_name(repo_url).await?;
     
@@ -803,19 +2535,18 @@ async fn clone_private_repository(
     ).await?;
     
     github_service::check_repo_accessibility(
-        &state.http_client,
+        &SafeHttpPolicy::new(state.config.safe_http_allowed_hosts.clone()),
         &token,
         &github_owner,
         &repo_name,
     ).await?;
-    
-    github_service::clone_repo(repo_url, destination, Some(&token), branch).await?;
-    
-    info!("Successfully cloned private repository '{}' using GitHub App token", repo_url);
-    
-    Ok(())
+
+    Ok(token)
 }
 
+/// Répertoire où les sources GitHub sont copiées dans l'image construite.
+const BUILD_WEBROOT: &str = "/var/www/html";
+
 fn create_dockerfile(
     base_image: &str,
     root_dir: Option<&str>,
@@ -823,13 +2554,13 @@ fn create_dockerfile(
 ) -> Result<(), AppError>
 {
     let dockerfile_content = format!(
-        "FROM {base_image}\nCOPY --chown=appuser:appgroup . /var/www/html/\n"
+        "FROM {base_image}\nCOPY --chown=appuser:appgroup . {BUILD_WEBROOT}/\n"
     );
 
     let dockerfile_content = if let Some(dir) = root_dir 
     {
         format!(
-            "{dockerfile_content}ENV HANGAR_WEBROOT_DIR=/var/www/html/{dir}\n"
+            "{dockerfile_content}ENV HANGAR_WEBROOT_DIR={BUILD_WEBROOT}/{dir}\n"
         )
     } 
     else 
@@ -843,6 +2574,76 @@ fn create_dockerfile(
     Ok(())
 }
 
+/// Contexte de build d'un projet utilisant le Dockerfile de son dépôt : `root_dir` s'il est défini, sinon la racine.
+///
+/// Le `Dockerfile` doit être un fichier ordinaire et le contexte rester dans le dépôt : un lien
+/// symbolique pourrait sinon faire lire au build un fichier de l'hôte.
+fn repo_build_context(repo_dir: &std::path::Path, root_dir: Option<&str>) -> Result<std::path::PathBuf, AppError>
+{
+    let context = match root_dir.filter(|dir| !dir.is_empty())
+    {
+        Some(dir) => repo_dir.join(dir),
+        None => repo_dir.to_path_buf(),
+    };
+
+    let (Ok(repo_dir), Ok(canonical_context)) = (repo_dir.canonicalize(), context.canonicalize()) else
+    {
+        return Err(ProjectErrorCode::RepoDockerfileNotFound.into());
+    };
+    if !canonical_context.starts_with(&repo_dir)
+    {
+        return Err(ProjectErrorCode::InvalidSourceRootDir.into());
+    }
+
+    let is_regular_file = fs::symlink_metadata(canonical_context.join("Dockerfile")).is_ok_and(|metadata| metadata.file_type().is_file());
+    if !is_regular_file
+    {
+        return Err(ProjectErrorCode::RepoDockerfileNotFound.into());
+    }
+
+    Ok(canonical_context)
+}
+
+/// Avertit lorsque le volume persistant masque du contenu fourni par l'image.
+///
+/// Docker ne copie le contenu de l'image dans un volume qu'à la création de celui-ci :
+/// les déploiements suivants ne mettent plus à jour les fichiers situés sous le point de montage.
+async fn warn_if_volume_shadows_image(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    image_tag: &str,
+    volume_path: &str,
+    is_generated_build: bool,
+)
+{
+    let Ok(mut content_paths) = docker_service::get_image_content_paths(&state.docker_client, image_tag).await else
+    {
+        return;
+    };
+    if is_generated_build
+    {
+        content_paths.push(BUILD_WEBROOT.to_string());
+    }
+
+    let Some(shadowed_path) = validation_service::find_shadowed_path(volume_path, &content_paths) else
+    {
+        return;
+    };
+
+    warn!("Persistent volume '{}' shadows image content at '{}'", log_safe(volume_path), shadowed_path);
+
+    let message = format!(
+        "The persistent volume mounted at '{volume_path}' hides the image content at '{shadowed_path}'. \
+         The volume only receives the image files when it is first created, so code from later deployments \
+         will not be served from this path. Mount a dedicated subdirectory instead (e.g. '{shadowed_path}/uploads')."
+    );
+    orchestrator.emit_event(SystemEvent::warning(message).with_context(json!({
+        "source": "volume_shadowing",
+        "volume_path": volume_path,
+        "shadowed_path": shadowed_path,
+    }))).await;
+}
+
 // ============================================================================
 // Private Helper Functions - Direct Source Operations
 // ============================================================================
@@ -851,12 +2652,13 @@ async fn prepare_direct_source_with_events
 (
     state: &AppState, 
     image_url: &str,
+    scan_ignore_cves: &[String],
     orchestrator: &DeploymentOrchestrator<'_>,
 ) -> Result<String, AppError>
 {
-    info!("Preparing 'direct' source from image '{}'", image_url);
+    info!("Preparing 'direct' source from image '{}'", log_safe(image_url));
     
-    validation_service::validate_image_url(image_url)?;
+    let image_url = &validation_service::validate_image_url(image_url)?.to_string();
 
     orchestrator.with_stages
     (
@@ -868,15 +2670,16 @@ async fn prepare_direct_source_with_events
         "Image pull",
         pull_image_with_error_handling(state, image_url),
     ).await?;
+    orchestrator.record_image(image_url, false);
 
     orchestrator.with_stages
     (
         DeploymentStage::ScanningImage,
         DeploymentStage::ImageScanned,
         "Image scan",
-        scan_image_with_rollback(state, image_url),
+        scan_image_with_rollback(state, image_url, scan_ignore_cves, !orchestrator.keeps_artifacts()),
     ).await?;
-
+    orchestrator.record_image(image_url, true);
 
     Ok(image_url.to_string())
 }
@@ -887,31 +2690,33 @@ async fn pull_image_with_error_handling(state: &AppState, image_url: &str) -> Re
     {
         Ok(()) =>
         {
-            info!("Successfully pulled public image '{}'", image_url);
+            info!("Successfully pulled public image '{}'", log_safe(image_url));
             Ok(())
         }
         Err(e) =>
         {
             if image_url.starts_with("ghcr.io/")
-                && let bollard::errors::Error::DockerResponseServerError { status_code, .. } = &e
-                    && (*status_code == 401 || *status_code == 403)
+                && matches!(e.status(), Some(401 | 403))
                     {
-                        warn!("Failed to pull private image from ghcr.io: {}", image_url);
+                        warn!("Failed to pull private image from ghcr.io: {}", log_safe(image_url));
                         return Err(ProjectErrorCode::GithubPackageNotPublic.into());
                     }
 
-            error!("Failed to pull image '{}': {}", image_url, e);
+            error!("Failed to pull image '{}': {}", log_safe(image_url), e);
             Err(ProjectErrorCode::ImagePullFailed.into())
         }
     }
 }
 
-async fn scan_image_with_rollback(state: &AppState, image_url: &str) -> Result<(), AppError>
+async fn scan_image_with_rollback(state: &AppState, image_url: &str, scan_ignore_cves: &[String], remove_on_failure: bool) -> Result<(), AppError>
 {
-    if let Err(scan_error) = docker_service::scan_image_with_grype(image_url, &state.config).await
+    if let Err(scan_error) = docker_service::scan_image_with_grype(image_url, scan_ignore_cves, &state.config).await
     {
-        warn!("Image scan failed, rolling back by removing pulled image '{}'", image_url);
-        let _ = docker_service::remove_image(&state.docker_client, image_url).await;
+        if remove_on_failure
+        {
+            warn!("Image scan failed, rolling back by removing pulled image '{}'", log_safe(image_url));
+            let _ = docker_service::remove_image(&state.docker_client, image_url).await;
+        }
         return Err(scan_error);
     }
     
@@ -919,39 +2724,194 @@ async fn scan_image_with_rollback(state: &AppState, image_url: &str) -> Result<(
 }
 
 // ============================================================================
-// Private Helper Functions - Container & Image Operations
+// Private Helper Functions - Project Creation
 // ============================================================================
 
-async fn create_container_with_rollback(
-    state: &AppState,
-    container_name: &str,
-    project_name: &str,
-    image_digest: &str,
-    env_vars: &Option<HashMap<String, String>>,
-    persistent_volume_path: &Option<String>,
-    image_tag: &str,
-) -> Result<Option<String>, AppError>
+/// Fin d'une création (conteneur, vérification, enregistrement), séparée de Docker et de la base pour être testée.
+trait CreationRuntime
+{
+    async fn run_stage<T>(
+        &self,
+        before: DeploymentStage,
+        after: DeploymentStage,
+        operation_name: &str,
+        step: impl Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError>;
+    /// Crée et démarre le conteneur ; renvoie le volume créé avec lui.
+    async fn create_container(&self) -> Result<Option<String>, AppError>;
+    async fn wait_healthy(&self) -> Result<(), AppError>;
+    /// Enregistre le projet, sa base et ses participants en une transaction.
+    async fn persist(&self, volume_name: &Option<String>) -> Result<Project, AppError>;
+    async fn remove_container(&self) -> Result<(), AppError>;
+    async fn remove_volume(&self, volume_name: &str) -> Result<(), AppError>;
+    async fn remove_image(&self) -> Result<(), AppError>;
+}
+
+/// Crée le conteneur, attend qu'il soit sain puis enregistre le projet.
+///
+/// Une erreur à n'importe quelle étape supprime les ressources Docker déjà créées et est renvoyée
+/// telle quelle : aucun projet n'est enregistré pour un conteneur supprimé.
+async fn finish_creation(runtime: &impl CreationRuntime, container_name: &str) -> Result<Project, AppError>
 {
-    match docker_service::create_project_container(
-        &state.docker_client,
-        container_name,
-        project_name,
-        image_digest,
-        &state.config,
-        env_vars,
-        persistent_volume_path,
+    let volume_name = match runtime.run_stage
+    (
+        DeploymentStage::CreatingContainer,
+        DeploymentStage::ContainerCreated,
+        "Container creation",
+        runtime.create_container(),
+    ).await
+    {
+        Ok(volume_name) => volume_name,
+        Err(e) =>
+        {
+            // Un conteneur dont le démarrage échoue est supprimé par `create_project_container`.
+            warn!("Container creation failed, rolling back image of '{}'", container_name);
+            rollback_creation(runtime, container_name, false, None).await;
+            return Err(e);
+        }
+    };
+
+    if let Err(e) = runtime.run_stage
+    (
+        DeploymentStage::WaitingHealthCheck,
+        DeploymentStage::HealthCheckPassed,
+        "Health check",
+        runtime.wait_healthy(),
     ).await
     {
-        Ok(volume_name) => Ok(volume_name),
+        warn!("Health check failed: {}, rolling back container '{}'", e, container_name);
+        rollback_creation(runtime, container_name, true, volume_name.as_deref()).await;
+        return Err(e);
+    }
+
+    match runtime.persist(&volume_name).await
+    {
+        Ok(project) => Ok(project),
         Err(e) =>
         {
-            warn!("Container creation failed, rolling back image '{}'", image_tag);
-            let _ = docker_service::remove_image(&state.docker_client, image_tag).await;
+            warn!("Database transaction failed. Rolling back Docker resources for container '{}'...", container_name);
+            rollback_creation(runtime, container_name, true, volume_name.as_deref()).await;
             Err(e)
         }
     }
 }
 
+/// Supprime ce qui a été créé ; un échec est journalisé sans masquer l'erreur d'origine.
+async fn rollback_creation(runtime: &impl CreationRuntime, container_name: &str, container_created: bool, volume_name: Option<&str>)
+{
+    if container_created
+        && let Err(e) = runtime.remove_container().await
+    {
+        warn!("ROLLBACK FAILED: Could not remove container '{}': {}", container_name, e);
+    }
+
+    if let Some(volume_name) = volume_name
+        && let Err(e) = runtime.remove_volume(volume_name).await
+    {
+        warn!("ROLLBACK FAILED: Could not remove volume '{}': {}", volume_name, e);
+    }
+
+    if let Err(e) = runtime.remove_image().await
+    {
+        warn!("ROLLBACK FAILED: Could not remove image of '{}': {}", container_name, e);
+    }
+}
+
+struct DockerCreationRuntime<'a>
+{
+    state: &'a AppState,
+    orchestrator: &'a DeploymentOrchestrator<'a>,
+    project_id: i32,
+    payload: &'a DeployPayload,
+    /// Variables du conteneur, identifiants de la base compris si demandé.
+    env_vars: &'a Option<HashMap<String, String>>,
+    database_password: Option<&'a str>,
+    user_login: &'a str,
+    container_name: &'a str,
+    deployment_source: &'a DeploymentSource,
+    image_digest: &'a str,
+    participants: &'a [String],
+}
+
+impl CreationRuntime for DockerCreationRuntime<'_>
+{
+    async fn run_stage<T>(
+        &self,
+        before: DeploymentStage,
+        after: DeploymentStage,
+        operation_name: &str,
+        step: impl Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError>
+    {
+        self.orchestrator.with_stages(before, after, operation_name, step).await
+    }
+
+    async fn create_container(&self) -> Result<Option<String>, AppError>
+    {
+        let owners = project_service::routing_owners(&self.state.db_pool, self.container_name).await;
+        docker_service::create_project_container(
+            &self.state.docker_client,
+            self.project_id,
+            self.container_name,
+            &self.payload.project_name,
+            self.image_digest,
+            &self.state.config,
+            self.env_vars,
+            &self.payload.persistent_volume_path,
+            &self.payload.container_command,
+            self.payload.timezone.as_deref(),
+            self.payload.container_port(),
+            &[],
+            self.state.config.resource_profile(DEFAULT_RESOURCE_PROFILE),
+            &owners,
+        ).await
+        .inspect_err(|e| emit_if_network_missing(self.state, e))
+    }
+
+    async fn wait_healthy(&self) -> Result<(), AppError>
+    {
+        let policy = blue_green::HealthCheckPolicy::from_config(&self.state.config);
+        blue_green::wait_for_container_health(self.state, self.container_name, policy, Some(self.orchestrator)).await
+    }
+
+    async fn persist(&self, volume_name: &Option<String>) -> Result<Project, AppError>
+    {
+        persist_project_with_events(
+            self.state,
+            self.orchestrator,
+            self.project_id,
+            self.payload,
+            self.user_login,
+            self.container_name,
+            self.deployment_source,
+            self.image_digest,
+            volume_name,
+            self.database_password,
+            self.participants,
+        ).await
+    }
+
+    async fn remove_container(&self) -> Result<(), AppError>
+    {
+        docker_service::remove_container(&self.state.docker_client, self.container_name, docker_service::DEFAULT_STOP_GRACE_SECONDS).await
+            .map_err(AppError::from)
+    }
+
+    async fn remove_volume(&self, volume_name: &str) -> Result<(), AppError>
+    {
+        docker_service::remove_volume_by_name(&self.state.docker_client, volume_name).await.map_err(AppError::from)
+    }
+
+    async fn remove_image(&self) -> Result<(), AppError>
+    {
+        docker_service::remove_image(&self.state.docker_client, &self.deployment_source.image_tag).await.map_err(AppError::from)
+    }
+}
+
+// ============================================================================
+// Private Helper Functions - Container & Image Operations
+// ============================================================================
+
 async fn get_image_digest(state: &AppState, image_tag: &str) -> Result<String, AppError>
 {
     match docker_service::get_image_digest(&state.docker_client, image_tag).await
@@ -975,7 +2935,8 @@ async fn get_image_digest(state: &AppState, image_tag: &str) -> Result<String, A
 fn generate_image_tag(project_name: &str) -> String
 {
     format!(
-        "hangar-local/{}:{}",
+        "{}{}:{}",
+        docker_service::LOCAL_IMAGE_REPOSITORY,
         project_name,
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -984,38 +2945,6 @@ fn generate_image_tag(project_name: &str) -> String
     )
 }
 
-async fn wait_for_container_health(
-    state: &AppState,
-    container_name: &str,
-    max_attempts: u32,
-) -> Result<(), AppError>
-{
-    info!("Waiting for new container '{}' to be healthy...", container_name);
-
-    for _ in 0..max_attempts
-    {
-        if is_container_healthy(state, container_name).await?
-        {
-            info!("Container '{}' is healthy", container_name);
-            return Ok(());
-        }
-        sleep(Duration::from_secs(1)).await;
-    }
-
-    error!("Container '{}' did not become healthy in time", container_name);
-    Err(AppError::InternalServerError)
-}
-
-async fn is_container_healthy(state: &AppState, container_name: &str) -> Result<bool, AppError>
-{
-    if let Ok(Some(details)) = docker_service::inspect_container_details(&state.docker_client, container_name).await
-        && let Some(container_state) = details.state
-        {
-            return Ok(container_state.running.unwrap_or(false));
-        }
-    Ok(false)
-}
-
 async fn remove_image_best_effort(state: &AppState, image_tag: &str)
 {
     match docker_service::remove_image(&state.docker_client, image_tag).await
@@ -1032,15 +2961,17 @@ async fn remove_image_best_effort(state: &AppState, image_tag: &str)
 // Private Helper Functions - Database Operations
 // ============================================================================
 
-async fn persist_project_with_rollback_and_events(
+async fn persist_project_with_events(
     state: &AppState,
     orchestrator: &DeploymentOrchestrator<'_>,
+    project_id: i32,
     payload: &DeployPayload,
     user_login: &str,
     container_name: &str,
     deployment_source: &DeploymentSource,
     deployed_image_digest: &str,
     volume_name: &Option<String>,
+    database_password: Option<&str>,
     participants: &[String],
 ) -> Result<crate::model::project::Project, AppError>
 {
@@ -1053,6 +2984,7 @@ async fn persist_project_with_rollback_and_events(
         let new_project = create_project_in_transaction(
             &mut tx,
             state,
+            project_id,
             payload,
             user_login,
             container_name,
@@ -1061,47 +2993,32 @@ async fn persist_project_with_rollback_and_events(
             volume_name,
         ).await?;
 
-        if payload.create_database.unwrap_or(false)
+        if let Some(password) = database_password
         {
             orchestrator.with_stages
             (
                 DeploymentStage::ProvisioningDatabase,
                 DeploymentStage::DatabaseProvisioned,
                 "Database provisioning",
-                provision_database_in_transaction(&mut tx, state, user_login, new_project.id),
+                provision_database_in_transaction(&mut tx, state, user_login, new_project.id, password),
             ).await?;
         }
 
-        add_participants_in_transaction(&mut tx, new_project.id, participants).await?;
+        add_participants_in_transaction(&mut tx, state, new_project.id, participants).await?;
 
-        Ok(new_project)
+        Ok::<_, AppError>(new_project)
     };
 
-    match db_operations.await 
-    {
-        Ok(project) => 
-        {
-            tx.commit().await.map_err(|_| AppError::InternalServerError)?;
-            Ok(project)
-        }
-        Err(e) => 
-        {
-            warn!("Database transaction failed. Rolling back Docker resources for container '{}'...", container_name);
-            let _ = docker_service::remove_container(&state.docker_client, container_name).await;
-            if let Some(vol) = volume_name 
-            {
-                let _ = docker_service::remove_volume_by_name(&state.docker_client, vol).await;
-            }
-            remove_image_best_effort(state, &deployment_source.image_tag).await;
-            
-            Err(e)
-        }
-    }
+    // Le rollback de la transaction est implicite ; celui des ressources Docker revient à `finish_creation`.
+    let project = db_operations.await?;
+    tx.commit().await.map_err(|_| AppError::InternalServerError)?;
+    Ok(project)
 }
 
 async fn create_project_in_transaction(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     state: &AppState,
+    project_id: i32,
     payload: &DeployPayload,
     user_login: &str,
     container_name: &str,
@@ -1110,20 +3027,38 @@ async fn create_project_in_transaction(
     volume_name: &Option<String>,
 ) -> Result<crate::model::project::Project, AppError>
 {
+    let (branch, git_ref, root_dir, use_repo_dockerfile) = match &deployment_source.spec
+    {
+        DeploymentSourceSpec::Github { branch, git_ref, root_dir, use_repo_dockerfile, .. } =>
+        {
+            (branch.clone(), git_ref.as_deref(), root_dir.clone(), *use_repo_dockerfile)
+        }
+        DeploymentSourceSpec::Direct { .. } | DeploymentSourceSpec::Upload { .. } => (None, None, None, false),
+    };
+
     project_service::create_project(
         tx,
+        project_id,
         &payload.project_name,
         user_login,
         container_name,
-        deployment_source.source_type,
-        &deployment_source.source_url,
-        &payload.github_branch,
-        &payload.github_root_dir,
+        deployment_source.spec.source_type(),
+        deployment_source.spec.source_url(),
+        &branch,
+        &root_dir,
+        use_repo_dockerfile,
         &deployment_source.image_tag,
         deployed_image_digest,
         &payload.env_vars,
         &payload.persistent_volume_path,
         volume_name,
+        &payload.container_command,
+        &payload.timezone,
+        payload.container_port(),
+        &payload.scan_ignore_cves,
+        deployment_source.commit_sha.as_deref(),
+        git_ref,
+        payload.inject_database_env.unwrap_or(false),
         &state.config.encryption_key,
     ).await.map_err(|e| 
     {
@@ -1137,6 +3072,7 @@ async fn provision_database_in_transaction(
     state: &AppState,
     user_login: &str,
     project_id: i32,
+    password: &str,
 ) -> Result<(), AppError>
 {
     if let Err(db_error) = database_service::provision_and_link_database_tx(
@@ -1144,7 +3080,9 @@ async fn provision_database_in_transaction(
         &state.mariadb_pool,
         user_login,
         project_id,
+        password,
         &state.config.encryption_key,
+        database_service::default_limits(&state.config),
     ).await
     {
         warn!("Database provisioning failed during project creation, rolling back transaction...");
@@ -1158,11 +3096,12 @@ async fn provision_database_in_transaction(
 
 async fn add_participants_in_transaction(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    state: &AppState,
     project_id: i32,
     participants: &[String],
 ) -> Result<(), AppError>
 {
-    if let Err(e) = project_service::add_project_participants(tx, project_id, participants).await
+    if let Err(e) = project_service::add_project_participants(tx, project_id, participants, state.config.participants_max_per_project).await
     {
         warn!("Failed to add participants, rolling back transaction...");
         Err(e)
@@ -1177,21 +3116,13 @@ async fn add_participants_in_transaction(
 async fn deprovision_linked_database(
     state: &AppState,
     project_id: i32,
-    user_login: &str,
-    is_admin: bool,
 ) -> Result<(), AppError>
 {
     if let Some(db) = database_service::get_database_by_project_id(&state.db_pool, project_id).await?
     {
         info!("Project has a linked database (ID: {}). Deprovisioning it.", db.id);
         
-        database_service::deprovision_database(
-            &state.db_pool,
-            &state.mariadb_pool,
-            db.id,
-            user_login,
-            is_admin,
-        ).await?;
+        database_service::deprovision_database(&state.db_pool, &state.mariadb_pool, &db).await?;
         
         info!("Linked database deprovisioned successfully.");
     }
@@ -1201,7 +3132,7 @@ async fn deprovision_linked_database(
 
 async fn remove_persistent_volume(
     state: &AppState,
-    project: &crate::model::project::Project,
+    project: &Project,
 ) -> Result<(), AppError>
 {
     if project.persistent_volume_path.is_some()
@@ -1220,62 +3151,40 @@ async fn remove_persistent_volume(
     Ok(())
 }
 
-async fn get_database_details(
-    state: &AppState,
-    project_id: i32,
-) -> Result<Option<crate::model::database::DatabaseDetailsResponse>, AppError>
+#[cfg(feature = "object_storage")]
+async fn deprovision_project_bucket(state: &AppState, project_id: i32, force: bool) -> Result<(), AppError>
 {
-    match database_service::get_database_by_project_id(&state.db_pool, project_id).await?
+    let Some(storage) = &state.config.object_storage else
     {
-        Some(db) =>
-        {
-            let details = database_service::create_db_details_response(
-                db,
-                &state.config,
-                &state.config.encryption_key,
-            )?;
-            Ok(Some(details))
-        }
-        None => Ok(None),
+        return Ok(());
+    };
+
+    if let Some(bucket) = object_storage_service::get_bucket_by_project_id(&state.db_pool, project_id).await?
+    {
+        info!("Deprovisioning bucket '{}' for project {} (force: {})", bucket.bucket_name, project_id, force);
+        object_storage_service::deprovision_bucket(&state.http_client, storage, &state.db_pool, &bucket, force).await?;
     }
-}
 
-// ============================================================================
-// Private Helper Functions - Project Retrieval
-// ============================================================================
+    Ok(())
+}
 
-async fn get_project_for_owner(
-    state: &AppState,
-    project_id: i32,
-    user_login: &str,
-    is_admin: bool,
-) -> Result<crate::model::project::Project, AppError>
+#[cfg(not(feature = "object_storage"))]
+#[allow(clippy::unused_async)]
+async fn deprovision_project_bucket(_state: &AppState, _project_id: i32, _force: bool) -> Result<(), AppError>
 {
-    project_service::get_project_by_id_and_owner(&state.db_pool, project_id, user_login, is_admin)
-        .await?
-        .ok_or_else(||
-        {
-            AppError::NotFound(format!(
-                "Project with ID {project_id} not found or you don't have access."
-            ))
-        })
+    Ok(())
 }
 
-async fn get_project_for_user(
+/// Base liée au projet, sans son mot de passe : il n'est révélé que sur demande explicite
+/// (`POST /api/projects/{project_id}/database/credentials`).
+async fn get_database_details(
     state: &AppState,
     project_id: i32,
-    user_login: &str,
-    is_admin: bool,
-) -> Result<crate::model::project::Project, AppError>
+) -> Result<Option<crate::model::database::DatabaseDetailsResponse>, AppError>
 {
-    project_service::get_project_by_id_for_user(&state.db_pool, project_id, user_login, is_admin)
-        .await?
-        .ok_or_else(||
-        {
-            AppError::NotFound(format!(
-                "Project with ID {project_id} not found or you don't have access."
-            ))
-        })
+    database_service::get_database_by_project_id(&state.db_pool, project_id).await?
+        .map(|db| database_service::create_db_details_response(db, &state.config, &state.config.encryption_key, false))
+        .transpose()
 }
 
 // ============================================================================
@@ -1284,23 +3193,24 @@ async fn get_project_for_user(
 
 async fn project_control_handler(
     state: AppState,
-    claims: Claims,
+    ctx: AccessContext,
     project_id: i32,
     action: ProjectAction,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+    let project = authz::load_project_for(&state, &ctx, project_id, action.mutation()).await?;
+    project.ensure_not_archived()?;
 
     validate_container_exists_for_action(&state, &project, action).await?;
 
-    action.execute(state.docker_client.clone(), project.container_name).await?;
+    action.execute(state.docker_client.clone(), project.container_name, project.stop_grace_seconds).await?;
 
     Ok(StatusCode::OK)
 }
 
 async fn validate_container_exists_for_action(
     state: &AppState,
-    project: &crate::model::project::Project,
+    project: &Project,
     action: ProjectAction,
 ) -> Result<(), AppError>
 {
@@ -1332,299 +3242,304 @@ async fn validate_container_exists_for_action(
 async fn prepare_blue_green_deployment_with_events(
     state: &AppState,
     orchestrator: &DeploymentOrchestrator<'_>,
-    project: &crate::model::project::Project,
+    project: &Project,
     new_image_url: &str,
     old_image_tag: Option<&str>,
 ) -> Result<BlueGreenDeployment, AppError>
 {
-    if old_image_tag.is_none()
+    let new_image_url = if old_image_tag.is_none()
     {
-        prepare_direct_source_with_events(state, new_image_url, orchestrator).await?;
+        prepare_direct_source_with_events(state, new_image_url, &project.scan_ignore_cves, orchestrator).await?
     }
+    else
+    {
+        new_image_url.to_string()
+    };
 
     let new_image_digest = orchestrator.with_stage
     (
         DeploymentStage::GettingImageDigest,
         "Image digest retrieval",
-        get_image_digest(state, new_image_url),
+        get_image_digest(state, &new_image_url),
     ).await?;
-
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    orchestrator.record_digest(&new_image_digest);
 
     Ok(BlueGreenDeployment
     {
-        old_container_name: project.container_name.clone(),
-        new_container_name: format!("{}-{}-{}", state.config.app_prefix, project.name, timestamp),
-        new_image_tag: new_image_url.to_string(),
+        new_container_name: blue_green::new_container_name(state, project),
+        new_image_tag: new_image_url,
         new_image_digest,
     })
 }
 
-fn create_blue_green_deployment_for_env_update(
-    state: &AppState,
-    project: &crate::model::project::Project,
-) -> BlueGreenDeployment
-{
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-
-    BlueGreenDeployment
-    {
-        old_container_name: project.container_name.clone(),
-        new_container_name: format!("{}-{}-{}", state.config.app_prefix, project.name, timestamp),
-        new_image_tag: project.deployed_image_tag.clone(),
-        new_image_digest: project.deployed_image_digest.clone(),
-    }
-}
-
-async fn execute_blue_green_deployment_with_events(
+/// Bascule le projet sur l'image préparée ; `replaced_image` est supprimée après la bascule.
+/// `commit_sha` est le commit dont l'image est issue, pour un projet `github`, `new_ref` la
+/// référence à laquelle le projet est désormais épinglé et `upload_source` l'archive envoyée
+/// d'un projet `upload`.
+async fn deploy_new_image_with_events(
     state: &AppState,
     orchestrator: &DeploymentOrchestrator<'_>,
-    project: &crate::model::project::Project,
+    project: &Project,
     deployment: &BlueGreenDeployment,
-    env_vars: Option<&HashMap<String, String>>,
-    old_image_to_cleanup: &str,
+    replaced_image: Option<&str>,
+    commit_sha: Option<&str>,
+    new_ref: Option<&str>,
+    upload_source: Option<&str>,
 ) -> Result<(), AppError>
 {
-    info!("Creating new container '{}' for project '{}'", deployment.new_container_name, project.name);
-
-    orchestrator.with_stages
-    (
-        DeploymentStage::CreatingContainer,
-        DeploymentStage::ContainerCreated,
-        "New container creation",
-        create_new_container_for_deployment(state, project, deployment, env_vars),
-    ).await?;
+    let env_vars = project_service::get_decrypted_env_vars(project, &state.config.encryption_key)?;
+    let env_vars = project_service::runtime_env_vars(state, project, env_vars).await?;
 
+    let spec = NewContainerSpec
+    {
+        container_name: deployment.new_container_name.clone(),
+        image: deployment.new_image_digest.clone(),
+        env_vars,
+        command: project.container_command(),
+        container_port: project.http_port(),
+        resource_profile: project.resource_profile.clone(),
+        // Un déploiement suivi garde son image en cas d'échec, pour pouvoir être repris.
+        rollback_image: (!orchestrator.keeps_artifacts()).then(|| deployment.new_image_tag.clone()),
+        replaced_image: replaced_image.map(str::to_string),
+    };
 
-    orchestrator.with_stages
-    (
-        DeploymentStage::WaitingHealthCheck,
-        DeploymentStage::HealthCheckPassed,
-        "Health check",
-        wait_for_container_health(state, &deployment.new_container_name, 10),
-    ).await.inspect_err(|_|
+    let update = MetadataUpdate::Image
     {
-        let docker = state.docker_client.clone();
-        let container = deployment.new_container_name.clone();
-        let image = deployment.new_image_tag.clone();
-        
-        tokio::spawn(async move
+        tag: &deployment.new_image_tag,
+        digest: &deployment.new_image_digest,
+        source_url: match project.source
         {
-            let _ = docker_service::remove_container(&docker, &container).await;
-            let _ = docker_service::remove_image(&docker, &image).await;
-        });
-    })?;
+            ProjectSourceType::Direct => Some(deployment.new_image_tag.as_str()),
+            ProjectSourceType::Github => None,
+            ProjectSourceType::Upload => upload_source,
+        },
+        commit_sha,
+    };
 
-    update_project_metadata(state, project.id, deployment, &project.source).await
-        .inspect_err(|_| 
-        {
-            error!("Failed to update project metadata. Rolling back new container...");
-            
-            let docker = state.docker_client.clone();
-            let container = deployment.new_container_name.clone();
-            let image = deployment.new_image_tag.clone();
-            
-            tokio::spawn(async move 
-            {
-                let _ = docker_service::remove_container(&docker, &container).await;
-                let _ = docker_service::remove_image(&docker, &image).await;
-            });
-        })?;
+    let mut updates = vec![update];
+    if let Some(new_ref) = new_ref
+    {
+        updates.push(MetadataUpdate::SourceRef(new_ref));
+    }
 
-    orchestrator.emit_stage(DeploymentStage::CleaningUp).await;
-    cleanup_old_deployment(state, &deployment.old_container_name, old_image_to_cleanup).await;
+    blue_green::execute(state, orchestrator, project, spec, &updates).await
+}
 
-    info!(
-        "Project '{}' deployment completed successfully. New container is '{}'.",
-        project.name, deployment.new_container_name
-    );
+/// L'image déployée est conservée comme cible de retour arrière : seule l'image qu'elle remplace
+/// à cet emplacement est à supprimer après la bascule.
+async fn image_leaving_rollback_slot(state: &AppState, project: &Project) -> Result<Option<String>, AppError>
+{
+    let previous = project_service::get_previous_image(&state.db_pool, project.id).await?;
+    Ok(previous.map(|image| image.tag).filter(|tag| *tag != project.deployed_image_tag))
+}
 
-    Ok(())
+/// Paramètre modifié par une recréation du conteneur sur la même image.
+enum RecreationChange<'a>
+{
+    EnvVars(&'a HashMap<String, String>),
+    Command(&'a ContainerCommand),
+    Port(u16),
+    ResourceProfile(&'a str),
+    /// Aucun paramètre du projet : les identifiants fournis par la plateforme ou les domaines vérifiés ont changé.
+    Platform,
 }
 
-async fn create_new_container_for_deployment(
+/// Recrée le conteneur sur l'image déployée avec le paramètre modifié. Renvoie le nom du nouveau conteneur.
+async fn recreate_container_with_events(
     state: &AppState,
-    project: &crate::model::project::Project,
-    deployment: &BlueGreenDeployment,
-    env_vars: Option<&HashMap<String, String>>,
-) -> Result<(), AppError>
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project: &Project,
+    change: RecreationChange<'_>,
+) -> Result<String, AppError>
 {
-    let owned_env_vars: Option<HashMap<String, String>> = env_vars.cloned();
-
-    return match docker_service::create_project_container(
-        &state.docker_client,
-        &deployment.new_container_name,
-        &project.name,
-        &deployment.new_image_digest,
-        &state.config,
-        &owned_env_vars,
-        &project.persistent_volume_path,
-    ).await
+    let mut resource_profile = project.resource_profile.clone();
+    let (env_vars, command, container_port, update) = match change
     {
-        Ok(_) => Ok(()),
-        Err(e) =>
+        RecreationChange::EnvVars(env_vars) =>
         {
-            error!("Failed to create new container for project '{}'. Aborting update.", project.name);
-            let _ = docker_service::remove_image(&state.docker_client, &deployment.new_image_tag).await;
-            Err(e)
+            (Some(env_vars.clone()), project.container_command(), project.http_port(), Some(MetadataUpdate::EnvVars(env_vars)))
         }
-    };
-}
-
-async fn update_project_metadata(
-    state: &AppState,
-    project_id: i32,
-    deployment: &BlueGreenDeployment,
-    project_source: &ProjectSourceType,
-) -> Result<(), AppError>
-{
-    project_service::update_project_container_name(
-        &state.db_pool,
-        project_id,
-        &deployment.new_container_name,
-    ).await?;
-
-    project_service::update_project_image_and_digest(
-        &state.db_pool,
-        project_id,
-        &deployment.new_image_tag,
-        &deployment.new_image_digest,
-    ).await?;
+        RecreationChange::Command(command) =>
+        {
+            let env_vars = project_service::get_decrypted_env_vars(project, &state.config.encryption_key)?;
+            (env_vars, command.clone(), project.http_port(), Some(MetadataUpdate::Command(command)))
+        }
+        RecreationChange::Port(container_port) =>
+        {
+            let env_vars = project_service::get_decrypted_env_vars(project, &state.config.encryption_key)?;
+            (env_vars, project.container_command(), container_port, Some(MetadataUpdate::Port(container_port)))
+        }
+        RecreationChange::ResourceProfile(profile) =>
+        {
+            let env_vars = project_service::get_decrypted_env_vars(project, &state.config.encryption_key)?;
+            resource_profile = profile.to_string();
+            (env_vars, project.container_command(), project.http_port(), Some(MetadataUpdate::ResourceProfile(profile)))
+        }
+        RecreationChange::Platform =>
+        {
+            let env_vars = project_service::get_decrypted_env_vars(project, &state.config.encryption_key)?;
+            (env_vars, project.container_command(), project.http_port(), None)
+        }
+    };
 
-    if *project_source == ProjectSourceType::Direct
+    // L'empreinte désigne l'image réellement déployée, même si son tag a été réattribué ou supprimé.
+    let rebuilt = if docker_service::get_image_digest(&state.docker_client, &project.deployed_image_digest).await?.is_some()
     {
-        project_service::update_project_source_url(
-            &state.db_pool,
-            project_id,
-            &deployment.new_image_tag,
-        ).await?;
+        None
     }
+    else
+    {
+        Some(rebuild_missing_image_with_events(state, orchestrator, project).await?)
+    };
 
-    Ok(())
-}
-
-async fn cleanup_old_deployment(
-    state: &AppState,
-    old_container_name: &str,
-    old_image_tag: &str,
-)
-{
-    info!("Removing old container '{}'", old_container_name);
-    
-    if let Err(e) = docker_service::remove_container(&state.docker_client, old_container_name).await
+    let spec = NewContainerSpec
     {
-        warn!(
-            "Could not remove old container '{}', but update is successful. Manual cleanup may be needed. Error: {}",
-            old_container_name, e
-        );
-    }
+        container_name: blue_green::new_container_name(state, project),
+        image: rebuilt.as_ref().map_or_else(|| project.deployed_image_digest.clone(), |(_, digest)| digest.clone()),
+        env_vars: project_service::runtime_env_vars(state, project, env_vars).await?,
+        command,
+        container_port,
+        resource_profile,
+        // Une image reconstruite pour l'occasion n'a pas lieu d'être conservée si la bascule échoue.
+        rollback_image: rebuilt.as_ref().map(|(source, _)| source.image_tag.clone()),
+        replaced_image: None,
+    };
+    let new_container_name = spec.container_name.clone();
 
-    let docker_client = state.docker_client.clone();
-    let old_image_tag_clone = old_image_tag.to_string();
-    
-    tokio::spawn(async move
+    let mut updates: Vec<MetadataUpdate<'_>> = update.into_iter().collect();
+    if let Some((source, digest)) = &rebuilt
     {
-        if let Err(e) = docker_service::remove_image(&docker_client, &old_image_tag_clone).await
+        updates.push(MetadataUpdate::Image
         {
-            warn!("Could not remove old image '{}' in background: {}", old_image_tag_clone, e);
-        }
-    });
+            tag: &source.image_tag,
+            digest,
+            source_url: None,
+            commit_sha: source.commit_sha.as_deref(),
+        });
+    }
+
+    blue_green::execute(state, orchestrator, project, spec, &updates).await?;
+
+    Ok(new_container_name)
 }
 
-async fn execute_env_vars_blue_green_deployment_with_events(
+/// Recrée, sous son nom, le conteneur d'un projet archivé : variables, commande, volume et labels Traefik
+/// sont ceux du projet. Le conteneur est supprimé s'il ne devient pas sain ; le volume est conservé.
+async fn restore_container_with_events(
     state: &AppState,
     orchestrator: &DeploymentOrchestrator<'_>,
-    project: &crate::model::project::Project,
-    deployment: &BlueGreenDeployment,
-    env_vars: &HashMap<String, String>,
+    project: &Project,
+    image: &str,
 ) -> Result<(), AppError>
 {
-    info!(
-        "Creating new container '{}' for project '{}' with updated env vars",
-        deployment.new_container_name, project.name
-    );
+    let env_vars = project_service::get_decrypted_env_vars(project, &state.config.encryption_key)?;
+    let env_vars = project_service::runtime_env_vars(state, project, env_vars).await?;
+    let custom_domains = domain_service::verified_domains(&state.db_pool, project.id).await?;
+    let owners = project_service::routing_owners(&state.db_pool, &project.container_name).await;
 
     orchestrator.with_stages
     (
         DeploymentStage::CreatingContainer,
         DeploymentStage::ContainerCreated,
-        "New container creation",
+        "Container creation",
         docker_service::create_project_container(
             &state.docker_client,
-            &deployment.new_container_name,
+            project.id,
+            &project.container_name,
             &project.name,
-            &project.deployed_image_tag,
+            image,
             &state.config,
-            &Some(env_vars.clone()),
+            &env_vars,
             &project.persistent_volume_path,
+            &project.container_command(),
+            project.timezone.as_deref(),
+            project.http_port(),
+            &custom_domains,
+            state.config.resource_profile(&project.resource_profile),
+            &owners,
         ),
     ).await
-    .inspect_err(|_|
-    {
-        error!("Failed to recreate container for project '{}' during env update. Aborting.", project.name);
-    })?;
+    .inspect_err(|e| emit_if_network_missing(state, e))?;
 
-    orchestrator.with_stages
+    let health = orchestrator.with_stages
     (
         DeploymentStage::WaitingHealthCheck,
         DeploymentStage::HealthCheckPassed,
         "Health check",
-        wait_for_container_health(state, &deployment.new_container_name, 10),
-    ).await
-    .inspect_err(|_|
+        blue_green::wait_for_container_health(
+            state,
+            &project.container_name,
+            blue_green::HealthCheckPolicy::for_project(&state.config, project),
+            Some(orchestrator),
+        ),
+    ).await;
+
+    if health.is_err()
+        && let Err(e) = docker_service::remove_container(&state.docker_client, &project.container_name, docker_service::DEFAULT_STOP_GRACE_SECONDS).await
     {
-        let docker = state.docker_client.clone();
-        let container = deployment.new_container_name.clone();
-        
-        tokio::spawn(async move
-        {
-            let _ = docker_service::remove_container(&docker, &container).await;
-        });
-    })?;
+        warn!("ROLLBACK FAILED: Could not remove unhealthy container '{}': {}", project.container_name, e);
+    }
+    health
+}
 
-    project_service::update_project_container_name(
-        &state.db_pool,
-        project.id,
-        &deployment.new_container_name,
-    ).await?;
+/// Reproduit l'image d'un projet dont l'image déployée a disparu du démon (nettoyage, prune manuel).
+/// Renvoie la source préparée et l'empreinte de la nouvelle image.
+async fn rebuild_missing_image_with_events(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project: &Project,
+) -> Result<(DeploymentSource, String), AppError>
+{
+    warn!(
+        "Deployed image '{}' of project '{}' is missing, rebuilding it from source",
+        log_safe(&project.deployed_image_tag), project.name
+    );
 
-    project_service::update_project_env_vars(
-        &state.db_pool,
-        project.id,
-        env_vars,
-        &state.config.encryption_key,
-    ).await?;
+    let spec = match DeploymentSourceSpec::pinned_to_deployment(project)
+    {
+        Ok(spec) => spec,
+        Err(code) =>
+        {
+            orchestrator.emit_failed(code.to_string(), "Image rebuild".to_string()).await;
+            return Err(code.into());
+        }
+    };
+    let source = prepare_deployment_source_with_events(state, &project.name, spec, &project.scan_ignore_cves, orchestrator).await?;
 
-    orchestrator.emit_stage(DeploymentStage::CleaningUp).await;
+    let digest = orchestrator.with_stage
+    (
+        DeploymentStage::GettingImageDigest,
+        "Image digest retrieval",
+        get_image_digest(state, &source.image_tag),
+    ).await?;
+    orchestrator.record_digest(&digest);
 
-    info!("Removing old container '{}'", deployment.old_container_name);
-    
-    if let Err(e) = docker_service::remove_container(&state.docker_client, &deployment.old_container_name).await
+    // Le tag d'une image directe a pu être réattribué depuis le déploiement : une autre image ne la remplace pas.
+    if source.spec.source_type() == ProjectSourceType::Direct && digest != project.deployed_image_digest
     {
-        warn!(
-            "Could not remove old container '{}', but update is successful. Manual cleanup may be needed. Error: {}",
-            deployment.old_container_name, e
-        );
+        let code = ProjectErrorCode::DeployedImageUnavailable(format!(
+            "the image '{}' now points to a different image, redeploy the project to use it", log_safe(&source.image_tag)
+        ));
+        orchestrator.emit_failed(code.to_string(), "Image digest check".to_string()).await;
+        return Err(code.into());
     }
 
-    info!(
-        "Project '{}' environment variables updated successfully. New container is '{}'.",
-        project.name, deployment.new_container_name
-    );
-
-    Ok(())
+    Ok((source, digest))
 }
 
 // ============================================================================
 // Private Helper Functions - Encryption
 // ============================================================================
 
+/// Ne garde que les noms des variables d'environnement, pour un participant.
+fn redact_project_env_vars(project: &mut Project)
+{
+    if let Some(serde_json::Value::Object(vars)) = &mut project.env_vars
+    {
+        vars.values_mut().for_each(|value| *value = serde_json::Value::Null);
+    }
+}
+
 fn decrypt_project_env_vars(
     project: &mut crate::model::project::Project,
     encryption_key: &[u8],
@@ -1635,7 +3550,7 @@ fn decrypt_project_env_vars(
         let encrypted_vars: HashMap<String, String> = serde_json::from_value(env_vars_value.clone())
             .unwrap_or_default();
         
-        let decrypted_vars = decrypt_env_vars(&encrypted_vars, encryption_key)?;
+        let decrypted_vars = project_service::decrypt_env_vars(&encrypted_vars, encryption_key)?;
         
         project.env_vars = Some(serde_json::to_value(decrypted_vars).unwrap());
     }
@@ -1643,83 +3558,500 @@ fn decrypt_project_env_vars(
     Ok(())
 }
 
-fn get_decrypted_env_vars(
-    project: &crate::model::project::Project,
-    encryption_key: &[u8],
-) -> Result<Option<HashMap<String, String>>, AppError>
-{
-    if let Some(env_vars_value) = &project.env_vars
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures::executor::block_on;
+    use tempfile::Builder as TempBuilder;
+
+    use super::*;
+    use crate::{config::Config, model::project::Project, test_support};
+    use sqlx::PgPool;
+
+    #[derive(Default)]
+    struct MockCreation
     {
-        let encrypted_vars: HashMap<String, String> = serde_json::from_value(env_vars_value.clone())
-            .unwrap_or_default();
-        
-        Ok(Some(decrypt_env_vars(&encrypted_vars, encryption_key)?))
+        fail_create: bool,
+        fail_health: bool,
+        fail_persist: bool,
+        fail_volume_removal: bool,
+        calls: Mutex<Vec<String>>,
     }
-    else
+
+    impl MockCreation
     {
-        Ok(None)
+        fn record(&self, call: impl Into<String>)
+        {
+            self.calls.lock().unwrap().push(call.into());
+        }
+
+        fn calls(&self) -> Vec<String>
+        {
+            self.calls.lock().unwrap().clone()
+        }
     }
-}
 
-fn decrypt_env_vars(
-    encrypted_vars: &HashMap<String, String>,
-    key: &[u8],
-) -> Result<HashMap<String, String>, AppError>
-{
-    encrypted_vars
-        .iter()
-        .map(|(k, v_b64)|
+    impl CreationRuntime for MockCreation
+    {
+        async fn run_stage<T>(
+            &self,
+            before: DeploymentStage,
+            after: DeploymentStage,
+            _operation_name: &str,
+            step: impl Future<Output = Result<T, AppError>>,
+        ) -> Result<T, AppError>
         {
-            let encrypted_val = BASE64_STANDARD
-                .decode(v_b64)
-                .map_err(|_| AppError::InternalServerError)?;
-            
-            let decrypted_val = crypto_service::decrypt(&encrypted_val, key)?;
-            
-            Ok((k.clone(), decrypted_val))
-        })
-        .collect()
-}
+            self.record(format!("stage:{before:?}"));
+            let result = step.await;
+            self.record(if result.is_ok() { format!("stage:{after:?}") } else { "stage:Failed".to_string() });
+            result
+        }
 
-// ============================================================================
-// Private Helper Functions - Response Building
-// ============================================================================
+        async fn create_container(&self) -> Result<Option<String>, AppError>
+        {
+            self.record("create_container");
+            if self.fail_create { Err(ProjectErrorCode::ContainerCreationFailed.into()) } else { Ok(Some("volume".to_string())) }
+        }
 
-fn create_deploy_response(
-    new_project: crate::model::project::Project,
-    participants: Vec<String>,
-) -> (StatusCode, Json<serde_json::Value>)
-{
-    let mut project_json = serde_json::to_value(new_project).unwrap_or_else(|_| json!({}));
-    
-    if let Some(obj) = project_json.as_object_mut()
+        async fn wait_healthy(&self) -> Result<(), AppError>
+        {
+            if self.fail_health { Err(ProjectErrorCode::HealthCheckFailed.into()) } else { Ok(()) }
+        }
+
+        async fn persist(&self, _volume_name: &Option<String>) -> Result<Project, AppError>
+        {
+            self.record("persist");
+            if self.fail_persist
+            {
+                return Err(ProjectErrorCode::ProjectCreationFailedWithDatabaseError.into());
+            }
+
+            Ok(test_support::project(1, "alice"))
+        }
+
+        async fn remove_container(&self) -> Result<(), AppError>
+        {
+            self.record("remove_container");
+            Ok(())
+        }
+
+        async fn remove_volume(&self, volume_name: &str) -> Result<(), AppError>
+        {
+            self.record(format!("remove_volume:{volume_name}"));
+            if self.fail_volume_removal { Err(AppError::InternalServerError) } else { Ok(()) }
+        }
+
+        async fn remove_image(&self) -> Result<(), AppError>
+        {
+            self.record("remove_image");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_unhealthy_container_aborts_creation_and_removes_resources()
     {
-        obj.insert("participants".to_string(), json!(participants));
+        let runtime = MockCreation { fail_health: true, ..Default::default() };
+        let error = block_on(finish_creation(&runtime, "hangar-demo")).unwrap_err();
+
+        assert_eq!(error.error_code(), "HEALTH_CHECK_FAILED");
+        assert_eq!(error.into_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(runtime.calls(), [
+            "stage:CreatingContainer",
+            "create_container",
+            "stage:ContainerCreated",
+            "stage:WaitingHealthCheck",
+            "stage:Failed",
+            "remove_container",
+            "remove_volume:volume",
+            "remove_image",
+        ]);
     }
 
-    let response_body = json!({ "project": project_json });
-    
-    (StatusCode::CREATED, Json(response_body))
-}
+    #[test]
+    fn test_rollback_failure_does_not_mask_the_original_error()
+    {
+        let runtime = MockCreation { fail_health: true, fail_volume_removal: true, ..Default::default() };
+        let error = block_on(finish_creation(&runtime, "hangar-demo")).unwrap_err();
 
-fn create_no_change_response(message: &str) -> (StatusCode, Json<serde_json::Value>)
-{
-    (
-        StatusCode::OK,
-        Json(json!({
-            "status": "no_change",
-            "message": message
-        })),
-    )
-}
+        assert_eq!(error.error_code(), "HEALTH_CHECK_FAILED");
+        assert!(runtime.calls().contains(&"remove_image".to_string()));
+        assert!(!runtime.calls().contains(&"persist".to_string()));
+    }
 
-fn create_success_response(message: &str) -> (StatusCode, Json<serde_json::Value>)
-{
-    (
-        StatusCode::OK,
-        Json(json!({
-            "status": "success",
-            "message": message
-        })),
-    )
-}
\ No newline at end of file
+    #[test]
+    fn test_persistence_failure_removes_resources()
+    {
+        let runtime = MockCreation { fail_persist: true, ..Default::default() };
+        let error = block_on(finish_creation(&runtime, "hangar-demo")).unwrap_err();
+
+        assert_eq!(error.error_code(), "PROJECT_CREATION_FAILED_WITH_DATABASE_ERROR");
+        let calls = runtime.calls();
+        assert!(calls.ends_with(&["persist".to_string(), "remove_container".to_string(), "remove_volume:volume".to_string(), "remove_image".to_string()]));
+    }
+
+    #[test]
+    fn test_creation_failure_only_removes_the_image()
+    {
+        let runtime = MockCreation { fail_create: true, ..Default::default() };
+        let error = block_on(finish_creation(&runtime, "hangar-demo")).unwrap_err();
+
+        assert_eq!(error.error_code(), "CONTAINER_CREATION_FAILED");
+        assert_eq!(runtime.calls(), ["stage:CreatingContainer", "create_container", "stage:Failed", "remove_image"]);
+    }
+
+    #[test]
+    fn test_healthy_container_is_persisted()
+    {
+        let runtime = MockCreation::default();
+        assert_eq!(block_on(finish_creation(&runtime, "hangar-demo")).unwrap().id, 1);
+
+        let calls = runtime.calls();
+        assert!(calls.contains(&"stage:HealthCheckPassed".to_string()));
+        assert!(!calls.iter().any(|call| call.starts_with("remove_")));
+    }
+
+    fn logs_query(query: &str) -> LogsQuery
+    {
+        let uri: axum::http::Uri = format!("/api/projects/1/logs?{query}").parse().unwrap();
+        Query::<LogsQuery>::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn test_logs_query_defaults_to_last_200_timestamped_lines()
+    {
+        let filter = logs_query("").to_filter(5000).unwrap();
+        assert_eq!(filter, LogsFilter::default());
+        assert_eq!(filter.tail, 200);
+        assert!(filter.stdout && filter.stderr && filter.timestamps);
+        assert!(matches!(logs_query("format=text").format, LogsFormat::Text));
+    }
+
+    #[test]
+    fn test_logs_query_applies_filters()
+    {
+        let filter = logs_query("tail=50&since=2026-10-16T08:00:00Z&until=2026-10-16T09:00:00Z&stdout=false&timestamps=false")
+            .to_filter(5000)
+            .unwrap();
+
+        assert_eq!(filter.tail, 50);
+        assert_eq!(filter.since.unwrap().unix_timestamp(), 1_792_137_600);
+        assert_eq!(filter.until.unwrap() - filter.since.unwrap(), time::Duration::hours(1));
+        assert!(!filter.stdout && filter.stderr && !filter.timestamps);
+    }
+
+    #[test]
+    fn test_logs_query_rejects_invalid_combinations()
+    {
+        let cases = [
+            "tail=5001",
+            "stdout=false&stderr=false",
+            "since=2026-10-16T09:00:00Z&until=2026-10-16T08:00:00Z",
+        ];
+
+        for query in cases
+        {
+            let error = logs_query(query).to_filter(5000).unwrap_err();
+            assert!(matches!(error, AppError::BadRequest(_)), "{query} was accepted");
+        }
+    }
+
+    #[test]
+    fn test_repo_dockerfile_is_looked_up_under_root_dir()
+    {
+        let repo = TempBuilder::new().tempdir().unwrap();
+        fs::create_dir_all(repo.path().join("api")).unwrap();
+        fs::write(repo.path().join("api/Dockerfile"), "FROM alpine\n").unwrap();
+
+        let context = repo_build_context(repo.path(), Some("api")).unwrap();
+        assert!(context.ends_with("api"));
+
+        let error = repo_build_context(repo.path(), None).unwrap_err();
+        assert_eq!(error.error_code(), "REPO_DOCKERFILE_NOT_FOUND");
+        let error = repo_build_context(repo.path(), Some("missing")).unwrap_err();
+        assert_eq!(error.error_code(), "REPO_DOCKERFILE_NOT_FOUND");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_repo_dockerfile_symlinks_are_rejected()
+    {
+        let outside = TempBuilder::new().tempdir().unwrap();
+        fs::write(outside.path().join("Dockerfile"), "FROM alpine\n").unwrap();
+
+        let repo = TempBuilder::new().tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path().join("Dockerfile"), repo.path().join("Dockerfile")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), repo.path().join("escape")).unwrap();
+
+        assert_eq!(repo_build_context(repo.path(), None).unwrap_err().error_code(), "REPO_DOCKERFILE_NOT_FOUND");
+        assert_eq!(repo_build_context(repo.path(), Some("escape")).unwrap_err().error_code(), "INVALID_SOURCE_ROOT_DIR");
+    }
+
+    #[test]
+    fn test_missing_image_is_rebuilt_from_the_recorded_source()
+    {
+        let mut project = block_on(MockCreation::default().persist(&None)).unwrap();
+        assert_eq!(
+            DeploymentSourceSpec::from_project(&project),
+            DeploymentSourceSpec::Direct { image: "nginx:latest".into() }
+        );
+
+        project.source = ProjectSourceType::Github;
+        project.source_url = "https://github.com/acme/app".into();
+        project.source_branch = Some("main".into());
+        project.source_root_dir = Some("web".into());
+        project.use_repo_dockerfile = true;
+        assert_eq!(
+            DeploymentSourceSpec::from_project(&project),
+            DeploymentSourceSpec::Github
+            {
+                repo: "https://github.com/acme/app".into(),
+                branch: Some("main".into()),
+                git_ref: None,
+                root_dir: Some("web".into()),
+                use_repo_dockerfile: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_participants_only_see_env_var_names()
+    {
+        let mut project = block_on(MockCreation::default().persist(&None)).unwrap();
+        project.env_vars = Some(json!({ "DB_PASSWORD": "ciphertext", "APP_ENV": "ciphertext" }));
+
+        redact_project_env_vars(&mut project);
+
+        assert_eq!(project.env_vars, Some(json!({ "DB_PASSWORD": null, "APP_ENV": null })));
+    }
+
+    #[test]
+    fn test_participants_above_the_limit_are_rejected()
+    {
+        let participants = vec!["bob".to_string(), "carol".to_string(), "bob".to_string()];
+
+        assert_eq!(prepare_participants(participants.clone(), "alice", 2).unwrap().len(), 2);
+
+        let error = prepare_participants(participants, "alice", 1).unwrap_err();
+        assert_eq!(error.error_code(), "PARTICIPANT_LIMIT_REACHED");
+    }
+
+    async fn project_count(pool: &PgPool) -> i64
+    {
+        sqlx::query_scalar("SELECT COUNT(*) FROM projects").fetch_one(pool).await.unwrap()
+    }
+
+    async fn persist_demo(state: &AppState, project_id: i32, database_password: Option<&str>, participants: &[String]) -> Result<Project, AppError>
+    {
+        let payload = DeployPayload::from_image("demo", "nginx:1");
+        let orchestrator = DeploymentOrchestrator::for_creation(state, "demo".to_string(), "alice".to_string());
+        let source = DeploymentSource
+        {
+            spec: DeploymentSourceSpec::Direct { image: "nginx:1".to_string() },
+            image_tag: "nginx:1".to_string(),
+            commit_sha: None,
+        };
+
+        persist_project_with_events(state, &orchestrator, project_id, &payload, "alice", "hangar-demo", &source, "sha256:1111", &None, database_password, participants).await
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_failed_database_provisioning_creates_no_project(pool: PgPool)
+    {
+        test_support::insert_user(&pool, "alice").await;
+        let state = test_support::state_with(test_support::config(), pool.clone(), test_support::UNREACHABLE_DOCKER);
+        let project_id = project_service::reserve_project_id(&pool).await.unwrap();
+
+        // MariaDB est injoignable : le provisionnement échoue après l'insertion du projet.
+        assert!(persist_demo(&state, project_id, Some("s3cret"), &[]).await.is_err());
+        assert_eq!(project_count(&pool).await, 0);
+        let databases: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM databases").fetch_one(&pool).await.unwrap();
+        assert_eq!(databases, 0);
+
+        persist_demo(&state, project_id, None, &[]).await.unwrap();
+        assert_eq!(project_count(&pool).await, 1);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_failed_participants_insert_creates_no_project(pool: PgPool)
+    {
+        for login in ["alice", "bob", "carol"]
+        {
+            test_support::insert_user(&pool, login).await;
+        }
+        let config = Config { participants_max_per_project: 1, ..test_support::config() };
+        let state = test_support::state_with(config, pool.clone(), test_support::UNREACHABLE_DOCKER);
+        let project_id = project_service::reserve_project_id(&pool).await.unwrap();
+
+        let participants = ["bob".to_string(), "carol".to_string()];
+        let error = persist_demo(&state, project_id, None, &participants).await.unwrap_err();
+        assert_eq!(error.error_code(), "PARTICIPANT_LIMIT_REACHED");
+        assert_eq!(project_count(&pool).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_user_action_on_a_container_in_that_state_is_a_client_error()
+    {
+        let docker = test_support::MockDocker::start(vec![
+            ("POST", "/containers/hangar-demo/start", test_support::MockReply::Json(409, serde_json::json!({ "message": "container is paused" }))),
+            ("POST", "/containers/hangar-demo/stop", test_support::MockReply::Json(500, serde_json::json!({ "message": "daemon failure" }))),
+        ]).await;
+
+        let started = ProjectAction::Start.execute(docker.client(), "hangar-demo".to_string(), 10).await;
+        assert!(matches!(started, Err(AppError::BadRequest(_))));
+        let stopped = ProjectAction::Stop.execute(docker.client(), "hangar-demo".to_string(), 10).await;
+        assert!(matches!(stopped, Err(AppError::InternalServerError)));
+    }
+
+    /// Recrée le conteneur du projet `project_id` contre un démon simulé par `routes`. La création du
+    /// conteneur échoue toujours, ce qui arrête la bascule : renvoie le résultat, l'image demandée pour le
+    /// nouveau conteneur et les requêtes reçues par le démon.
+    async fn recreate_against(
+        pool: PgPool,
+        project_id: i32,
+        mut routes: Vec<(&'static str, &'static str, test_support::MockReply)>,
+    ) -> (Result<String, AppError>, Option<String>, Vec<String>)
+    {
+        routes.push(("GET", "/containers/json", test_support::MockReply::Json(200, serde_json::json!([]))));
+        routes.push(("POST", "/containers/create", test_support::MockReply::Json(500, serde_json::json!({ "message": "stopped by the test" }))));
+        let docker = test_support::MockDocker::start(routes).await;
+        let state = test_support::state_with(test_support::config(), pool.clone(), &docker.url);
+        let project = project_service::get_project_by_id(&pool, project_id).await.unwrap().unwrap();
+        let orchestrator = DeploymentOrchestrator::for_update(&state, project.name.clone(), project.owner.clone(), project.id);
+
+        let result = recreate_container_with_events(&state, &orchestrator, &project, RecreationChange::Platform).await;
+        let image = docker.body("POST /containers/create")
+            .and_then(|body| body["Image"].as_str().map(str::to_string));
+        (result, image, docker.requests())
+    }
+
+    fn image_inspect(id: &str) -> test_support::MockReply
+    {
+        test_support::MockReply::Json(200, serde_json::json!({ "Id": id }))
+    }
+
+    fn pulled() -> test_support::MockReply
+    {
+        test_support::MockReply::Json(200, serde_json::json!({ "status": "Downloaded newer image" }))
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_recreation_reuses_the_deployed_image(pool: PgPool)
+    {
+        let project_id = test_support::insert_project(&pool, "demo", "alice", &[]).await;
+
+        let (result, image, requests) = recreate_against(pool, project_id, vec![("GET", "/images/sha256:1111/", image_inspect("sha256:1111"))]).await;
+
+        assert!(result.is_err());
+        assert_eq!(image.as_deref(), Some("sha256:1111"));
+        assert!(!requests.iter().any(|request| request.starts_with("POST /images/create") || request.starts_with("POST /build")));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_missing_direct_image_is_pulled_again_if_unchanged(pool: PgPool)
+    {
+        let project_id = test_support::insert_project(&pool, "demo", "alice", &[]).await;
+
+        let (result, image, requests) = recreate_against(pool, project_id, vec![
+            ("POST", "/images/create", pulled()),
+            ("GET", "/images/docker.io/library/nginx:1/", image_inspect("sha256:1111")),
+        ]).await;
+
+        assert!(result.is_err());
+        assert!(requests.contains(&"POST /images/create".to_string()));
+        assert_eq!(image.as_deref(), Some("sha256:1111"));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_missing_direct_image_is_not_replaced_by_a_moved_tag(pool: PgPool)
+    {
+        let project_id = test_support::insert_project(&pool, "demo", "alice", &[]).await;
+
+        let (result, image, _) = recreate_against(pool, project_id, vec![
+            ("POST", "/images/create", pulled()),
+            ("GET", "/images/docker.io/library/nginx:1/", image_inspect("sha256:2222")),
+        ]).await;
+
+        assert_eq!(result.unwrap_err().error_code(), "DEPLOYED_IMAGE_UNAVAILABLE");
+        assert_eq!(image, None);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_missing_github_image_without_recorded_commit_is_not_rebuilt(pool: PgPool)
+    {
+        let project_id = test_support::insert_project(&pool, "demo", "alice", &[]).await;
+        sqlx::query("UPDATE projects SET source_type = 'github', source_url = 'https://github.com/garage-isep/demo', source_branch = 'main' WHERE id = $1")
+            .bind(project_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let (result, image, requests) = recreate_against(pool, project_id, Vec::new()).await;
+
+        assert_eq!(result.unwrap_err().error_code(), "DEPLOYED_IMAGE_UNAVAILABLE");
+        assert_eq!(image, None);
+        assert_eq!(requests, vec!["GET /images/sha256:1111/json".to_string()]);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_missing_github_image_is_rebuilt_from_the_recorded_commit(pool: PgPool)
+    {
+        let project_id = test_support::insert_project(&pool, "demo", "alice", &[]).await;
+        let commit_sha = "0123456789abcdef0123456789abcdef01234567";
+        sqlx::query(
+            "UPDATE projects SET source_type = 'github', source_url = 'https://github.com/garage-isep/demo', source_branch = 'main', \
+             source_commit_sha = $2 WHERE id = $1"
+        )
+            .bind(project_id)
+            .bind(commit_sha)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let project = project_service::get_project_by_id(&pool, project_id).await.unwrap().unwrap();
+
+        match DeploymentSourceSpec::pinned_to_deployment(&project).unwrap()
+        {
+            DeploymentSourceSpec::Github { branch, git_ref, .. } =>
+            {
+                assert_eq!(branch, None);
+                assert_eq!(git_ref.as_deref(), Some(commit_sha));
+            }
+            _ => panic!("expected a GitHub source"),
+        }
+    }
+
+    #[test]
+    fn test_archive_import_pins_the_exported_image_and_commit()
+    {
+        let digest = "nginx@sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        let image = test_support::project(1, "alice");
+        let metadata = ProjectArchiveMetadata::from_project(&image, Vec::new(), None, Some(digest.to_string()));
+
+        let payload = DeployPayload::from_archive(&metadata, "alice", false);
+        assert_eq!(payload.image_url.as_deref(), Some(digest));
+
+        let commit_sha = "0123456789abcdef0123456789abcdef01234567";
+        let github = Project
+        {
+            source: ProjectSourceType::Github,
+            source_url: "https://github.com/garage-isep/demo".into(),
+            source_branch: Some("main".into()),
+            source_commit_sha: Some(commit_sha.into()),
+            ..test_support::project(2, "alice")
+        };
+        let metadata = ProjectArchiveMetadata::from_project(&github, Vec::new(), None, None);
+
+        let payload = DeployPayload::from_archive(&metadata, "alice", false);
+        assert_eq!(payload.image_url, None);
+        assert_eq!(payload.github_branch, None);
+        assert_eq!(payload.github_ref.as_deref(), Some(commit_sha));
+    }
+}
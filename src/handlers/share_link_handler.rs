@@ -0,0 +1,144 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use serde_json::json;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::info;
+
+use crate::
+{
+    authz::{self, AccessContext, ProjectMutation, RequiredRole},
+    client_ip::ClientIp,
+    error::AppError,
+    model::share_link::{ProjectShareLink, PublicProjectSnapshot},
+    services::{audit_service, docker_service, project_service, share_link_service},
+    sse::types::ContainerStatus,
+    state::AppState,
+};
+
+#[derive(Deserialize, Default)]
+pub struct CreateShareLinkPayload
+{
+    /// Durée de validité en heures (24 par défaut, 7 jours au maximum).
+    expires_in_hours: Option<i64>,
+}
+
+pub async fn list_share_links_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Owner).await?;
+
+    let links = share_link_service::list_links(&state.db_pool, project.id).await?;
+
+    Ok(Json(json!({ "share_links": links })))
+}
+
+pub async fn create_share_link_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    payload: Option<Json<CreateShareLinkPayload>>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageShareLinks).await?;
+    let Json(payload) = payload.unwrap_or_default();
+
+    let ttl_hours = payload.expires_in_hours.unwrap_or(share_link_service::DEFAULT_TTL_HOURS);
+    if !(1..=share_link_service::MAX_TTL_HOURS).contains(&ttl_hours)
+    {
+        return Err(AppError::BadRequest(format!("expires_in_hours must be between 1 and {}.", share_link_service::MAX_TTL_HOURS)));
+    }
+
+    let created = share_link_service::create_link(&state.db_pool, &state.config.jwt_secret, project.id, &ctx.login, ttl_hours).await?;
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_SHARE_LINK_CREATED,
+        Some(project.id),
+        Some(json!({ "link_id": created.link.id, "expires_at": created.link.expires_at.format(&Rfc3339).unwrap_or_default() })),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+pub async fn revoke_share_link_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, link_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageShareLinks).await?;
+
+    if !share_link_service::revoke_link(&state.db_pool, project.id, link_id).await?
+    {
+        return Err(AppError::NotFound(format!("Share link with ID {link_id} not found.")));
+    }
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_SHARE_LINK_REVOKED,
+        Some(project.id),
+        Some(json!({ "link_id": link_id })),
+    ).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Vue publique d'un projet via un lien de partage, sans authentification.
+/// Endpoint: GET /`api/public/projects/{token}`
+pub async fn get_public_project_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    client_ip: ClientIp,
+) -> Result<impl IntoResponse, AppError>
+{
+    let link = share_link_service::resolve_token(&state.db_pool, &state.config.jwt_secret, &token).await?;
+    record_public_access(&state, &link, client_ip, "snapshot").await;
+
+    let project = project_service::get_project_by_id(&state.db_pool, link.project_id).await?
+        .ok_or_else(|| AppError::NotFound("Share link not found or expired.".to_string()))?;
+
+    let container_state = docker_service::inspect_container_details(&state.docker_client, &project.container_name).await?
+        .and_then(|details| details.state);
+
+    let status = container_state.as_ref()
+        .map_or(ContainerStatus::Unknown, |s| s.status.into());
+    let uptime_seconds = container_state
+        .filter(|s| s.running == Some(true))
+        .and_then(|s| s.started_at)
+        .and_then(|started_at| OffsetDateTime::parse(&started_at, &Rfc3339).ok())
+        .map(|started_at| (OffsetDateTime::now_utc() - started_at).whole_seconds());
+
+    Ok(Json(PublicProjectSnapshot
+    {
+        url: format!("https://{}.{}", project.name, state.config.app_domain_suffix),
+        name: project.name,
+        status,
+        uptime_seconds,
+        link_expires_at: link.expires_at,
+    }))
+}
+
+/// Journalise chaque consultation d'un lien de partage (audit et logs).
+pub async fn record_public_access(state: &AppState, link: &ProjectShareLink, client_ip: ClientIp, view: &str)
+{
+    info!("Share link ID {} of project ID {} accessed from {} ({})", link.id, link.project_id, client_ip, view);
+
+    audit_service::record_from(
+        state,
+        &format!("share-link:{}", link.id),
+        client_ip,
+        audit_service::ACTION_PROJECT_SHARE_LINK_ACCESSED,
+        Some(link.project_id),
+        Some(json!({ "link_id": link.id, "view": view, "created_by": link.created_by })),
+    ).await;
+}
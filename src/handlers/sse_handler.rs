@@ -9,12 +9,19 @@ use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tracing::{debug, error, warn};
 
+use crate::authz::{self, AccessContext, RequiredRole};
+use crate::client_ip::ClientIp;
 use crate::error::AppError;
-use crate::services::jwt::Claims;
-use crate::services::{docker_service, project_service};
-use crate::sse::emitter::{emit_container_status, emit_metrics};
+use crate::handlers::share_link_handler;
+use crate::services::{docker_service, missed_event_service, project_service, share_link_service};
+use crate::model::project::Project;
 use crate::state::AppState;
-use crate::sse::types::{SseEvent, SystemEvent, SystemEventLevel};
+use crate::sse::manager::SseConnectionGuard;
+use crate::sse::tasks;
+use crate::sse::types::{ContainerStatus, ContainerStatusEvent, DownProjectsEvent, MetricsEvent, SseEvent, SystemEvent, SystemEventLevel};
+
+/// Délai suggéré (`Retry-After`) aux clients refusés faute de place.
+const SSE_RETRY_AFTER_SECONDS: u64 = 30;
 
 /// Handler SSE pour les événements d'un projet spécifique
 ///
@@ -22,28 +29,57 @@ use crate::sse::types::{SseEvent, SystemEvent, SystemEventLevel};
 /// Endpoint: GET /`api/sse/projects/{project_id`}
 pub async fn sse_project_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
     Path(project_id): Path<i32>,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError>
 {
-    let user_login = claims.sub;
-
-    let project = project_service::get_project_by_id_for_user(
-        &state.db_pool,
-        project_id,
-        &user_login,
-        claims.is_admin,
-    ).await?.ok_or_else(|| 
-    {
-        AppError::NotFound(format!("Project {project_id} not found or you don't have access."))
-    })?;
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+    let user_login = ctx.login;
+    let connection = acquire_connection(&state)?;
 
     let client_id: u128 = rand::random();
+    let disconnected = state.sse_manager.user_disconnected(&user_login);
     let rx = state.sse_manager.subscribe_to_project(project_id).await;
-    let stream = create_sse_stream(rx, client_id);
     debug!("User '{}' connected to SSE stream for project '{}' (client: {})", user_login, project.name, client_id);
-    send_initial_project_state(state.clone(), project_id, project.clone());
-    Ok(Sse::new(stream).keep_alive(create_keep_alive()))
+
+    // Les événements émis sans abonné sont rejoués une fois, entre l'état initial et le direct.
+    let missed = missed_event_service::take_for_project(&state.db_pool, project_id).await.unwrap_or_default();
+
+    // L'état initial n'est envoyé qu'à ce client, avant les événements diffusés au projet.
+    let stream = create_initial_state_stream(state.clone(), project, client_id)
+        .chain(into_client_stream(futures::stream::iter(missed), client_id))
+        .chain(create_sse_stream(rx, client_id));
+    let stream = futures::StreamExt::take_until(stream, disconnected);
+    Ok(Sse::new(hold_connection(stream, connection)).keep_alive(create_keep_alive()))
+}
+
+/// Handler SSE des logs d'un projet, suivis en continu
+///
+/// L'utilisateur doit être owner ou participant du projet. Seules les lignes écrites après
+/// la connexion sont transmises ; l'historique reste disponible sur `/api/projects/{id}/logs`.
+/// Endpoint: GET /`api/sse/projects/{project_id}/logs`
+pub async fn sse_project_logs_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+    project.ensure_not_archived()?;
+    let user_login = ctx.login;
+    let connection = acquire_connection(&state)?;
+
+    let client_id: u128 = rand::random();
+    let disconnected = state.sse_manager.user_disconnected(&user_login);
+    let (rx, follower) = state.sse_manager.subscribe_to_logs(project_id).await;
+    if let Some(tx) = follower
+    {
+        tokio::spawn(tasks::follow_project_logs(state.clone(), project_id, tx));
+    }
+    debug!("User '{}' connected to log stream for project '{}' (client: {})", user_login, project.name, client_id);
+
+    let stream = futures::StreamExt::take_until(create_sse_stream(rx, client_id), disconnected);
+    Ok(Sse::new(hold_connection(stream, connection)).keep_alive(create_keep_alive()))
 }
 
 /// Handler SSE pour le canal de création temporaire
@@ -53,15 +89,96 @@ pub async fn sse_project_handler(
 /// Endpoint: GET /api/sse/creation
 pub async fn sse_creation_handler(
     State(state): State<AppState>,
-    claims: Claims,
+    ctx: AccessContext,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError>
 {
-    let user_login = claims.sub;
+    let user_login = ctx.login;
+    let connection = acquire_connection(&state)?;
     let client_id: u128 = rand::random();
+    let disconnected = state.sse_manager.user_disconnected(&user_login);
     let rx = state.sse_manager.subscribe_to_creation(&user_login).await;
-    let stream = create_sse_stream(rx, client_id);
+    let missed = missed_event_service::take_for_user(&state.db_pool, &user_login).await.unwrap_or_default();
+    let stream = into_client_stream(futures::stream::iter(missed), client_id).chain(create_sse_stream(rx, client_id));
+    let stream = futures::StreamExt::take_until(stream, disconnected);
     debug!("User '{}' connected to creation SSE stream (client: {})", user_login, client_id);
-    Ok(Sse::new(stream).keep_alive(create_keep_alive()))
+    Ok(Sse::new(hold_connection(stream, connection)).keep_alive(create_keep_alive()))
+}
+
+/// Handler SSE pour le canal des administrateurs
+///
+/// Reçoit les erreurs des tâches de fond (sauvegardes, ...) et l'évolution des projets arrêtés.
+/// Endpoint: GET /api/sse/admin
+pub async fn sse_admin_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError>
+{
+    let connection = acquire_connection(&state)?;
+    let client_id: u128 = rand::random();
+    let rx = state.sse_manager.subscribe_to_admins();
+
+    // La liste des projets arrêtés n'est envoyée qu'à ce client, avant les événements diffusés.
+    let initial = futures::stream::once(initial_admin_events(state.clone()));
+    let stream = into_client_stream(futures::StreamExt::flat_map(initial, futures::stream::iter), client_id)
+        .chain(create_sse_stream(rx, client_id));
+    let stream = futures::StreamExt::take_until(stream, state.sse_manager.user_disconnected(&ctx.login));
+    debug!("Admin '{}' connected to admin SSE stream (client: {})", ctx.login, client_id);
+    Ok(Sse::new(hold_connection(stream, connection)).keep_alive(create_keep_alive()))
+}
+
+/// Handler SSE public d'un projet partagé via un lien de partage.
+///
+/// Seuls le statut du conteneur et les métriques sont transmis ; le flux se ferme à l'expiration du lien.
+/// Endpoint: GET /`api/public/projects/{token}/events`
+pub async fn sse_public_project_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    client_ip: ClientIp,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError>
+{
+    let link = share_link_service::resolve_token(&state.db_pool, &state.config.jwt_secret, &token).await?;
+    share_link_handler::record_public_access(&state, &link, client_ip, "events").await;
+
+    let project = project_service::get_project_by_id(&state.db_pool, link.project_id).await?
+        .ok_or_else(|| AppError::NotFound("Share link not found or expired.".to_string()))?;
+    let connection = acquire_connection(&state)?;
+
+    let client_id: u128 = rand::random();
+    let rx = state.sse_manager.subscribe_to_project(project.id).await;
+    debug!("Share link ID {} connected to public SSE stream for project '{}' (client: {})", link.id, project.name, client_id);
+
+    let public_events = BroadcastStream::new(rx)
+        .filter_map(|result| result.ok().filter(|event| matches!(event, SseEvent::ContainerStatus(_) | SseEvent::Metrics(_))));
+    let remaining = (link.expires_at - time::OffsetDateTime::now_utc()).unsigned_abs();
+
+    let stream = create_initial_state_stream(state.clone(), project, client_id)
+        .chain(into_client_stream(public_events, client_id));
+    let stream = futures::StreamExt::take_until(stream, tokio::time::sleep(remaining));
+    Ok(Sse::new(hold_connection(stream, connection)).keep_alive(create_keep_alive()))
+}
+
+/// Réserve une place de connexion, ou refuse le flux (503) si la limite globale est atteinte.
+fn acquire_connection(state: &AppState) -> Result<SseConnectionGuard, AppError>
+{
+    state.sse_manager.try_acquire_connection().ok_or_else(||
+    {
+        warn!("SSE connection limit reached ({}), rejecting new stream", state.sse_manager.max_connections());
+        AppError::ServiceUnavailable
+        {
+            message: "Too many live connections, please retry later.".to_string(),
+            retry_after_seconds: SSE_RETRY_AFTER_SECONDS,
+        }
+    })
+}
+
+/// Garde la place de connexion tant que le flux existe : elle est rendue à sa fermeture ou à la déconnexion du client.
+fn hold_connection<S: Stream>(stream: S, connection: SseConnectionGuard) -> impl Stream<Item = S::Item>
+{
+    futures::StreamExt::map(stream, move |item|
+    {
+        let _connection = &connection;
+        item
+    })
 }
 
 /// Crée le stream SSE à partir d'un broadcast receiver
@@ -117,63 +234,93 @@ fn create_keep_alive() -> KeepAlive
     KeepAlive::new().interval(Duration::from_secs(5)).text("keep-alive")
 }
 
-fn send_initial_project_state(
+/// Stream contenant le statut et les métriques actuels du conteneur, destiné à un seul client.
+fn create_initial_state_stream(
     state: AppState,
-    project_id: i32,
-    project: crate::model::project::Project,
-)
+    project: Project,
+    client_id: u128,
+) -> impl Stream<Item = Result<Event, Infallible>>
 {
-    tokio::spawn(async move 
-    {   
-        // Petit délai pour laisser la connexion SSE s'établir
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-        
-        match docker_service::get_container_status(&state.docker_client, &project.container_name).await
+    let events = futures::stream::once(initial_project_events(state, project));
+
+    into_client_stream(futures::StreamExt::flat_map(events, futures::stream::iter), client_id)
+}
+
+/// Sérialise des événements destinés à un seul client.
+fn into_client_stream(
+    events: impl Stream<Item = SseEvent>,
+    client_id: u128,
+) -> impl Stream<Item = Result<Event, Infallible>>
+{
+    events.filter_map(move |sse_event|
+    {
+        match event_to_sse(sse_event)
         {
-            Ok(Some(status)) =>
-            {
-                debug!("Sending initial status {:?} for project '{}'", status, project.name);
-                emit_container_status(
-                    &state,
-                    project_id,
-                    project.name.clone(),
-                    project.container_name.clone(),
-                    status,
-                ).await;
-            }
-            Ok(None) =>
-            {
-                warn!("Container '{}' not found when sending initial status", project.container_name);
-                emit_container_status(
-                    &state,
-                    project_id,
-                    project.name.clone(),
-                    project.container_name.clone(),
-                    crate::sse::types::ContainerStatus::Unknown,
-                ).await;
-            }
+            Ok(event) => Some(Ok(event)),
             Err(e) =>
             {
-                error!("Failed to get initial status for '{}': {}", project.container_name, e);
+                error!("Failed to serialize initial SSE event for client {}: {}", client_id, e);
+                None
             }
         }
-        
-        match docker_service::get_container_metrics(&state.docker_client, &project.container_name).await
+    })
+}
+
+async fn initial_admin_events(state: AppState) -> Vec<SseEvent>
+{
+    match project_service::list_down_projects(&state).await
+    {
+        Ok(down_projects) => vec![SseEvent::DownProjectsChanged(DownProjectsEvent::snapshot(down_projects))],
+        Err(e) =>
         {
-            Ok(metrics) =>
-            {
-                debug!("Sending initial metrics for project '{}'", project.name);
-                emit_metrics(
-                    &state,
-                    project_id,
-                    project.name.clone(),
-                    metrics,
-                ).await;
-            }
-            Err(e) =>
-            {
-                debug!("Could not get initial metrics for '{}'. Maybe stopped? : {}", project.container_name, e);
-            }
+            error!("Failed to list down projects for initial admin state: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+async fn initial_project_events(state: AppState, project: Project) -> Vec<SseEvent>
+{
+    let mut events = Vec::with_capacity(2);
+
+    let status = match docker_service::get_container_status(&state.docker_client, &project.container_name).await
+    {
+        Ok(Some(status)) => Some(status),
+        Ok(None) =>
+        {
+            warn!("Container '{}' not found when sending initial status", project.container_name);
+            Some(ContainerStatus::Unknown)
+        }
+        Err(e) =>
+        {
+            error!("Failed to get initial status for '{}': {}", project.container_name, e);
+            None
         }
-    });
-}
\ No newline at end of file
+    };
+
+    if let Some(status) = status
+    {
+        debug!("Sending initial status {:?} for project '{}'", status, project.name);
+        events.push(SseEvent::ContainerStatus(ContainerStatusEvent::new(
+            project.id,
+            project.name.clone(),
+            project.container_name.clone(),
+            status,
+        )));
+    }
+
+    match docker_service::get_container_metrics(&state.docker_client, &project.container_name).await
+    {
+        Ok(metrics) =>
+        {
+            debug!("Sending initial metrics for project '{}'", project.name);
+            events.push(SseEvent::Metrics(MetricsEvent::new(project.id, project.name.clone(), metrics)));
+        }
+        Err(e) =>
+        {
+            debug!("Could not get initial metrics for '{}'. Maybe stopped? : {}", project.container_name, e);
+        }
+    }
+
+    events
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::info;
+
+use crate::
+{
+    authz::AccessContext,
+    error::AppError,
+    handlers::project_handler::{DeployPayload, perform_deploy},
+    model::{response::{DeployResponse, TemplateListResponse}, template::{Template, TemplateInput}},
+    services::{audit_service, template_service},
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct DeployFromTemplatePayload
+{
+    project_name: String,
+    #[serde(default)]
+    participants: Vec<String>,
+    /// Valeurs des champs `{{NOM}}` des variables du modèle.
+    #[serde(default)]
+    values: HashMap<String, String>,
+}
+
+async fn load_template(state: &AppState, template_id: i32) -> Result<Template, AppError>
+{
+    template_service::get_template(&state.db_pool, template_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Template with ID {template_id} not found.")))
+}
+
+/// Catalogue des modèles, avec les champs à renseigner pour chacun.
+pub async fn list_templates_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let templates: Vec<_> = template_service::list_templates(&state.db_pool).await?
+        .into_iter()
+        .map(template_service::with_placeholders)
+        .collect();
+
+    Ok(Json(TemplateListResponse { templates }))
+}
+
+pub async fn create_template_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Json(payload): Json<TemplateInput>,
+) -> Result<impl IntoResponse, AppError>
+{
+    template_service::validate_template(&payload).await?;
+
+    let template = template_service::create_template(&state.db_pool, &payload).await?;
+    info!("Admin '{}' created template '{}'", ctx.login, template.name);
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_TEMPLATE_CREATED,
+        None,
+        Some(json!({ "template_id": template.id, "name": template.name })),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(template_service::with_placeholders(template))))
+}
+
+pub async fn update_template_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(template_id): Path<i32>,
+    Json(payload): Json<TemplateInput>,
+) -> Result<impl IntoResponse, AppError>
+{
+    template_service::validate_template(&payload).await?;
+
+    let template = template_service::update_template(&state.db_pool, template_id, &payload).await?
+        .ok_or_else(|| AppError::NotFound(format!("Template with ID {template_id} not found.")))?;
+    info!("Admin '{}' updated template '{}'", ctx.login, template.name);
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_TEMPLATE_UPDATED,
+        None,
+        Some(json!({ "template_id": template.id, "name": template.name })),
+    ).await;
+
+    Ok(Json(template_service::with_placeholders(template)))
+}
+
+pub async fn delete_template_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(template_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    if !template_service::delete_template(&state.db_pool, template_id).await?
+    {
+        return Err(AppError::NotFound(format!("Template with ID {template_id} not found.")));
+    }
+    info!("Admin '{}' deleted template ID {}", ctx.login, template_id);
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_TEMPLATE_DELETED,
+        None,
+        Some(json!({ "template_id": template_id })),
+    ).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Déploie un modèle par le même pipeline (et les mêmes étapes SSE) qu'un déploiement classique.
+/// Endpoint: POST /api/projects/deploy-from-template/{template_id}
+pub async fn deploy_from_template_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(template_id): Path<i32>,
+    Json(payload): Json<DeployFromTemplatePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let template = load_template(&state, template_id).await?;
+    info!("User '{}' deploying project '{}' from template '{}'", ctx.login, payload.project_name, template.name);
+
+    let deploy_payload = DeployPayload::from_template(&template, payload.project_name, payload.participants, payload.values);
+    let project = perform_deploy(&state, ctx.login, deploy_payload).await?;
+
+    Ok((StatusCode::CREATED, Json(DeployResponse { project })))
+}
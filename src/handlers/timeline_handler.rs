@@ -0,0 +1,153 @@
+use axum::
+{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use time::{Duration, OffsetDateTime};
+
+use crate::
+{
+    authz::{self, AccessContext, RequiredRole},
+    error::AppError,
+    handlers::deployment_handler::split_page,
+    model::{response::TimelineListResponse, timeline::{TimelineCursor, TimelineEntryType}},
+    services::timeline_service,
+    state::AppState,
+};
+
+/// Fenêtre appliquée lorsque `from` est omis.
+const DEFAULT_TIMELINE_DAYS: i64 = 7;
+const DEFAULT_ENTRIES_PER_PAGE: u32 = 50;
+const MAX_ENTRIES_PER_PAGE: u32 = 200;
+
+/// Fenêtre, filtre et pagination de la chronologie d'un projet.
+#[derive(Deserialize)]
+pub struct TimelineQuery
+{
+    /// Début inclus, 7 jours avant `to` par défaut.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+    /// Fin exclue, maintenant par défaut.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    to: Option<OffsetDateTime>,
+    /// Types séparés par des virgules (`deployment,status,audit`) ; tous par défaut.
+    types: Option<String>,
+    /// `next_cursor` de la page précédente.
+    cursor: Option<String>,
+    /// 50 par défaut, 200 au maximum.
+    per_page: Option<u32>,
+}
+
+impl TimelineQuery
+{
+    fn window(&self, now: OffsetDateTime) -> Result<(OffsetDateTime, OffsetDateTime), AppError>
+    {
+        let to = self.to.unwrap_or(now);
+        let from = self.from.unwrap_or(to - Duration::days(DEFAULT_TIMELINE_DAYS));
+
+        if from >= to
+        {
+            return Err(AppError::BadRequest("from must be earlier than to.".to_string()));
+        }
+
+        Ok((from, to))
+    }
+
+    fn types(&self) -> Result<Vec<TimelineEntryType>, AppError>
+    {
+        let Some(types) = &self.types else { return Ok(TimelineEntryType::ALL.to_vec()) };
+
+        let mut parsed = Vec::new();
+        for value in types.split(',').map(str::trim).filter(|value| !value.is_empty())
+        {
+            let entry_type = TimelineEntryType::parse(value).ok_or_else(|| AppError::BadRequest(format!(
+                "Unknown timeline type '{value}'. Expected: deployment, status, audit."
+            )))?;
+            if !parsed.contains(&entry_type)
+            {
+                parsed.push(entry_type);
+            }
+        }
+
+        if parsed.is_empty()
+        {
+            return Err(AppError::BadRequest("types must list at least one timeline type.".to_string()));
+        }
+
+        Ok(parsed)
+    }
+
+    fn cursor(&self) -> Result<Option<TimelineCursor>, AppError>
+    {
+        self.cursor.as_deref()
+            .map(|cursor| cursor.parse().map_err(|()| AppError::BadRequest("Invalid timeline cursor.".to_string())))
+            .transpose()
+    }
+
+    fn per_page(&self) -> Result<u32, AppError>
+    {
+        let per_page = self.per_page.unwrap_or(DEFAULT_ENTRIES_PER_PAGE);
+        if per_page == 0 || per_page > MAX_ENTRIES_PER_PAGE
+        {
+            return Err(AppError::BadRequest(format!("per_page must be between 1 and {MAX_ENTRIES_PER_PAGE}.")));
+        }
+        Ok(per_page)
+    }
+}
+
+/// Déploiements, démarrages/arrêts et entrées d'audit du projet, des plus récents aux plus anciens.
+/// Endpoint: GET /`api/projects/{project_id}/timeline`
+pub async fn get_project_timeline_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Query(query): Query<TimelineQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let (from, to) = query.window(OffsetDateTime::now_utc())?;
+    let types = query.types()?;
+    let cursor = query.cursor()?;
+    let per_page = query.per_page()?;
+
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+
+    // Une entrée de plus est demandée pour savoir s'il reste une page.
+    let entries = timeline_service::list_entries(&state.db_pool, project.id, from, to, &types, cursor, i64::from(per_page) + 1).await?;
+    let (entries, has_more) = split_page(entries, per_page);
+    let next_cursor = has_more.then(|| entries.last().map(|entry| entry.cursor().to_string())).flatten();
+
+    Ok(Json(TimelineListResponse { entries, from, to, next_cursor }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(types: Option<&str>) -> TimelineQuery
+    {
+        TimelineQuery { from: None, to: None, types: types.map(str::to_string), cursor: None, per_page: None }
+    }
+
+    #[test]
+    fn test_default_window_spans_seven_days()
+    {
+        let now = OffsetDateTime::UNIX_EPOCH + Duration::days(30);
+        assert_eq!(query(None).window(now).unwrap(), (now - Duration::days(7), now));
+
+        let inverted = TimelineQuery { from: Some(now), to: Some(now - Duration::days(1)), ..query(None) };
+        assert!(inverted.window(now).is_err());
+    }
+
+    #[test]
+    fn test_types_filter()
+    {
+        assert_eq!(query(None).types().unwrap(), TimelineEntryType::ALL.to_vec());
+        assert_eq!(
+            query(Some("audit, deployment,audit")).types().unwrap(),
+            vec![TimelineEntryType::Audit, TimelineEntryType::Deployment]
+        );
+        assert!(query(Some("logs")).types().is_err());
+        assert!(query(Some(",")).types().is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use axum::
+{
+    extract::{multipart::{Multipart, MultipartError}, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use tracing::info;
+
+use crate::
+{
+    authz::{self, AccessContext, ProjectMutation},
+    error::{AppError, ProjectErrorCode},
+    handlers::project_handler::{self, DeployPayload},
+    model::response::DeployResponse,
+    services::upload_service::UploadedArchive,
+    state::AppState,
+};
+
+/// Champs reçus d'un formulaire multipart : `payload` (JSON) et `archive` (fichier).
+struct UploadForm
+{
+    payload: Option<String>,
+    archive: Option<UploadedArchive>,
+}
+
+async fn read_form(state: &AppState, mut multipart: Multipart) -> Result<UploadForm, AppError>
+{
+    let mut form = UploadForm { payload: None, archive: None };
+    let form_error = |e: MultipartError| multipart_error(state, &e);
+
+    while let Some(field) = multipart.next_field().await.map_err(form_error)?
+    {
+        match field.name()
+        {
+            Some("payload") => form.payload = Some(field.text().await.map_err(form_error)?),
+            Some("archive") =>
+            {
+                let file_name = field.file_name().unwrap_or_default().to_string();
+                let bytes = field.bytes().await.map_err(form_error)?;
+                form.archive = Some(UploadedArchive::new(&file_name, bytes)?);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(form)
+}
+
+fn multipart_error(state: &AppState, e: &MultipartError) -> AppError
+{
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE
+    {
+        return ProjectErrorCode::InvalidUploadArchive(format!("the archive exceeds {} MB", state.config.upload_max_mb)).into();
+    }
+    AppError::BadRequest(format!("Invalid multipart body: {}", e.body_text()))
+}
+
+fn require_archive(form: &mut UploadForm) -> Result<UploadedArchive, AppError>
+{
+    form.archive.take().ok_or_else(|| AppError::BadRequest("The 'archive' file is missing.".to_string()))
+}
+
+/// Déploie un site statique envoyé sous forme d'archive. Le formulaire contient `payload`,
+/// les options habituelles du déploiement sans source, et `archive`, le fichier `.zip` ou `.tar.gz`.
+/// Endpoint: POST /api/projects/deploy-upload
+pub async fn deploy_upload_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, AppError>
+{
+    let mut form = read_form(&state, multipart).await?;
+    let archive = require_archive(&mut form)?;
+
+    let payload = form.payload.ok_or_else(|| AppError::BadRequest("The 'payload' field is missing.".to_string()))?;
+    let payload: DeployPayload = serde_json::from_str(&payload)
+        .map_err(|e| AppError::BadRequest(format!("The 'payload' field is invalid: {e}.")))?;
+
+    info!("User '{}' deploying uploaded archive '{}'", ctx.login, archive.file_name);
+
+    let project = project_handler::perform_deploy(&state, ctx.login, payload.with_upload(archive)).await?;
+
+    Ok((StatusCode::CREATED, Json(DeployResponse { project })))
+}
+
+/// Reconstruit un projet `upload` à partir d'une nouvelle archive (champ `archive`), en blue-green.
+/// Endpoint: PUT /api/projects/{project_id}/upload
+pub async fn redeploy_upload_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::Rebuild).await?;
+
+    let mut form = read_form(&state, multipart).await?;
+    let archive = require_archive(&mut form)?;
+
+    info!("User '{}' redeploying project '{}' from uploaded archive '{}'", ctx.login, project.name, archive.file_name);
+
+    project_handler::perform_upload_rebuild(&state, &project, &ctx.login, &archive).await
+}
@@ -0,0 +1,46 @@
+use axum::
+{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+
+use crate::
+{
+    authz::{self, AccessContext, RequiredRole},
+    error::AppError,
+    services::uptime_service,
+    state::AppState,
+};
+
+/// Période appliquée lorsque `days` est omis.
+const DEFAULT_UPTIME_DAYS: i64 = 30;
+
+#[derive(Deserialize)]
+pub struct UptimeQuery
+{
+    days: Option<i64>,
+}
+
+/// Disponibilité du projet sur les derniers jours : pourcentage, arrêts et plus long arrêt.
+/// Endpoint: GET /`api/projects/{project_id}/uptime`
+pub async fn get_project_uptime_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Query(query): Query<UptimeQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    // Les transitions plus anciennes que USAGE_RETENTION_DAYS sont purgées.
+    let max_days = i64::from(state.config.usage_retention_days);
+    let days = query.days.unwrap_or(DEFAULT_UPTIME_DAYS);
+    if !(1..=max_days).contains(&days)
+    {
+        return Err(AppError::BadRequest(format!("days must be between 1 and {max_days}.")));
+    }
+
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Participant).await?;
+    let uptime = uptime_service::project_uptime(&state.db_pool, &project, days).await?;
+
+    Ok(Json(uptime))
+}
@@ -0,0 +1,111 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+use tracing::info;
+
+use crate::
+{
+    authz::{self, AccessContext, ProjectMutation, RequiredRole},
+    error::AppError,
+    model::{response::{WebhookDeliveryListResponse, WebhookListResponse}, webhook::{CreateWebhookPayload, WebhookEvent}},
+    services::{audit_service, validation_service, webhook_service},
+    state::AppState,
+};
+
+/// Webhooks du projet, sans leur secret.
+pub async fn list_webhooks_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Owner).await?;
+    let webhooks = webhook_service::list_webhooks(&state.db_pool, project.id).await?;
+
+    Ok(Json(WebhookListResponse { webhooks }))
+}
+
+/// Ajoute un webhook appelé sur les événements choisis (tous par défaut).
+pub async fn add_webhook_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path(project_id): Path<i32>,
+    Json(payload): Json<CreateWebhookPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let url = validation_service::validate_webhook_url(&payload.url)?;
+    webhook_service::validate_secret(&payload.secret)?;
+    let mut events = payload.events.unwrap_or_else(|| WebhookEvent::ALL.to_vec());
+    events.sort_by_key(|event| event.as_str());
+    events.dedup();
+    if events.is_empty()
+    {
+        return Err(AppError::BadRequest("At least one webhook event must be selected.".to_string()));
+    }
+
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageWebhooks).await?;
+
+    let webhook = webhook_service::add_webhook(
+        &state.db_pool,
+        &state.config.encryption_key,
+        project.id,
+        url.as_str(),
+        &payload.secret,
+        &events,
+        &ctx.login,
+    ).await?;
+    info!("User '{}' added webhook ID {} to project '{}'", ctx.login, webhook.id, project.name);
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_WEBHOOK_ADDED,
+        Some(project.id),
+        Some(json!({ "webhook_id": webhook.id, "url": webhook.url, "events": webhook.events })),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(webhook)))
+}
+
+pub async fn remove_webhook_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, webhook_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project_for(&state, &ctx, project_id, ProjectMutation::ManageWebhooks).await?;
+
+    let webhook = webhook_service::remove_webhook(&state.db_pool, project.id, webhook_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Webhook with ID {webhook_id} not found.")))?;
+    info!("User '{}' removed webhook ID {} from project '{}'", ctx.login, webhook.id, project.name);
+
+    audit_service::record(
+        &state,
+        &ctx.login,
+        audit_service::ACTION_PROJECT_WEBHOOK_REMOVED,
+        Some(project.id),
+        Some(json!({ "webhook_id": webhook.id, "url": webhook.url })),
+    ).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Dernières tentatives de livraison du webhook, réussies ou non.
+pub async fn list_deliveries_handler(
+    State(state): State<AppState>,
+    ctx: AccessContext,
+    Path((project_id, webhook_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = authz::load_project(&state, &ctx, project_id, RequiredRole::Owner).await?;
+    let webhook = webhook_service::get_webhook(&state.db_pool, project.id, webhook_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Webhook with ID {webhook_id} not found.")))?;
+
+    let deliveries = webhook_service::list_deliveries(&state.db_pool, webhook.id).await?;
+
+    Ok(Json(WebhookDeliveryListResponse { deliveries }))
+}
@@ -0,0 +1,349 @@
+//! Références d'images au format de la spécification OCI distribution :
+//! `[registre/]dépôt[:tag][@algorithme:digest]`.
+//!
+//! Les noms courts du Docker Hub sont normalisés (`nginx` devient `docker.io/library/nginx:latest`)
+//! afin que deux écritures d'une même image se comparent à l'identique.
+
+use std::{fmt, str::FromStr};
+
+/// Registre implicite des noms courts.
+const DEFAULT_REGISTRY: &str = "docker.io";
+/// Espace de noms implicite des images officielles du Docker Hub.
+const OFFICIAL_NAMESPACE: &str = "library";
+const DEFAULT_TAG: &str = "latest";
+/// Longueur maximale du nom complet (registre et dépôt).
+const MAX_NAME_LENGTH: usize = 255;
+const MAX_TAG_LENGTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReference
+{
+    registry: String,
+    repository: String,
+    tag: Option<String>,
+    digest: Option<String>,
+}
+
+/// Composant invalide d'une référence, avec la valeur fautive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReferenceError
+{
+    component: &'static str,
+    value: String,
+}
+
+impl ImageReferenceError
+{
+    fn new(component: &'static str, value: &str) -> Self
+    {
+        Self { component, value: value.to_string() }
+    }
+}
+
+impl fmt::Display for ImageReferenceError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "invalid {} '{}'", self.component, self.value)
+    }
+}
+
+impl std::error::Error for ImageReferenceError {}
+
+impl FromStr for ImageReference
+{
+    type Err = ImageReferenceError;
+
+    fn from_str(reference: &str) -> Result<Self, Self::Err>
+    {
+        if reference.is_empty()
+        {
+            return Err(ImageReferenceError::new("reference", reference));
+        }
+
+        let (name_and_tag, digest) = match reference.split_once('@')
+        {
+            Some((name, digest)) => (name, Some(parse_digest(digest)?)),
+            None => (reference, None),
+        };
+
+        let last_slash = name_and_tag.rfind('/').map_or(0, |index| index + 1);
+        let (name, tag) = match name_and_tag[last_slash..].rfind(':')
+        {
+            Some(index) =>
+            {
+                let (name, tag) = name_and_tag.split_at(last_slash + index);
+                (name, Some(parse_tag(&tag[1..])?))
+            }
+            None => (name_and_tag, None),
+        };
+
+        if name.len() > MAX_NAME_LENGTH
+        {
+            return Err(ImageReferenceError::new("name (too long)", name));
+        }
+
+        let (registry, repository) = match name.split_once('/')
+        {
+            Some((first, rest)) if is_registry_like(first) => (parse_registry(first)?, rest),
+            _ => (DEFAULT_REGISTRY.to_string(), name),
+        };
+
+        for component in repository.split('/')
+        {
+            if !is_valid_path_component(component)
+            {
+                return Err(ImageReferenceError::new("repository component", component));
+            }
+        }
+
+        let repository = if registry == DEFAULT_REGISTRY && !repository.contains('/')
+        {
+            format!("{OFFICIAL_NAMESPACE}/{repository}")
+        }
+        else
+        {
+            repository.to_string()
+        };
+
+        // Le digest fait foi : le tag ne sert plus qu'à l'affichage et n'est pas conservé.
+        let tag = match (&digest, tag)
+        {
+            (Some(_), _) => None,
+            (None, tag) => Some(tag.unwrap_or_else(|| DEFAULT_TAG.to_string())),
+        };
+
+        Ok(Self { registry, repository, tag, digest })
+    }
+}
+
+impl fmt::Display for ImageReference
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}/{}", self.registry, self.repository)?;
+        if let Some(tag) = &self.tag
+        {
+            write!(f, ":{tag}")?;
+        }
+        if let Some(digest) = &self.digest
+        {
+            write!(f, "@{digest}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ImageReference
+{
+    #[must_use]
+    pub fn registry(&self) -> &str
+    {
+        &self.registry
+    }
+
+    #[must_use]
+    pub fn tag(&self) -> Option<&str>
+    {
+        self.tag.as_deref()
+    }
+
+    #[must_use]
+    pub fn digest(&self) -> Option<&str>
+    {
+        self.digest.as_deref()
+    }
+
+    /// Indique si la référence est épinglée sur un digest : elle ne suivra jamais un nouveau tag.
+    #[must_use]
+    pub const fn is_pinned(&self) -> bool
+    {
+        self.digest.is_some()
+    }
+
+    /// Même dépôt avec un autre tag (un éventuel digest est retiré).
+    ///
+    /// # Errors
+    /// Renvoie une erreur si le tag est invalide.
+    pub fn with_tag(&self, tag: &str) -> Result<Self, ImageReferenceError>
+    {
+        Ok(Self
+        {
+            registry: self.registry.clone(),
+            repository: self.repository.clone(),
+            tag: Some(parse_tag(tag)?),
+            digest: None,
+        })
+    }
+}
+
+/// Le premier composant désigne un registre s'il contient un point, un port ou vaut `localhost`.
+fn is_registry_like(component: &str) -> bool
+{
+    component.contains('.') || component.contains(':') || component == "localhost"
+}
+
+fn parse_registry(registry: &str) -> Result<String, ImageReferenceError>
+{
+    let invalid = || ImageReferenceError::new("registry", registry);
+
+    let (host, port) = match registry.split_once(':')
+    {
+        Some((host, port)) => (host, Some(port)),
+        None => (registry, None),
+    };
+
+    let is_valid_label = |label: &str| !label.is_empty()
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !label.starts_with('-')
+        && !label.ends_with('-');
+
+    if !host.split('.').all(is_valid_label)
+    {
+        return Err(invalid());
+    }
+    if let Some(port) = port && (port.is_empty() || port.parse::<u16>().is_err())
+    {
+        return Err(invalid());
+    }
+
+    let host = host.to_ascii_lowercase();
+    // Alias historiques du Docker Hub.
+    if port.is_none() && matches!(host.as_str(), "index.docker.io" | "registry-1.docker.io")
+    {
+        return Ok(DEFAULT_REGISTRY.to_string());
+    }
+
+    Ok(match port
+    {
+        Some(port) => format!("{host}:{port}"),
+        None => host,
+    })
+}
+
+/// `[a-z0-9]+` séparés par `.`, `_`, `__` ou une suite de `-`.
+fn is_valid_path_component(component: &str) -> bool
+{
+    let bytes = component.as_bytes();
+    let (Some(first), Some(last)) = (bytes.first(), bytes.last()) else
+    {
+        return false;
+    };
+    if !first.is_ascii_alphanumeric() || !last.is_ascii_alphanumeric()
+    {
+        return false;
+    }
+
+    let mut separator = String::new();
+    for &byte in bytes
+    {
+        if byte.is_ascii_digit() || byte.is_ascii_lowercase()
+        {
+            if !matches!(separator.as_str(), "" | "." | "_" | "__") && !separator.bytes().all(|b| b == b'-')
+            {
+                return false;
+            }
+            separator.clear();
+        }
+        else if matches!(byte, b'.' | b'_' | b'-')
+        {
+            separator.push(char::from(byte));
+        }
+        else
+        {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn parse_tag(tag: &str) -> Result<String, ImageReferenceError>
+{
+    let is_valid = tag.len() <= MAX_TAG_LENGTH
+        && tag.chars().next().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        && tag.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+
+    if is_valid { Ok(tag.to_string()) } else { Err(ImageReferenceError::new("tag", tag)) }
+}
+
+fn parse_digest(digest: &str) -> Result<String, ImageReferenceError>
+{
+    let invalid = || ImageReferenceError::new("digest", digest);
+
+    let (algorithm, encoded) = digest.split_once(':').ok_or_else(invalid)?;
+
+    let is_valid_algorithm = !algorithm.is_empty()
+        && algorithm.split(['+', '.', '_', '-']).all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+    let is_valid_encoded = encoded.len() >= 32
+        && encoded.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '=' | '_' | '-'));
+
+    if !is_valid_algorithm || !is_valid_encoded
+    {
+        return Err(invalid());
+    }
+
+    if algorithm == "sha256" && (encoded.len() != 64 || !encoded.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)))
+    {
+        return Err(invalid());
+    }
+
+    Ok(digest.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    fn canonical(reference: &str) -> String
+    {
+        reference.parse::<ImageReference>().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_docker_hub_short_names_are_normalized()
+    {
+        assert_eq!(canonical("nginx"), "docker.io/library/nginx:latest");
+        assert_eq!(canonical("nginx:1.27"), "docker.io/library/nginx:1.27");
+        assert_eq!(canonical("bitnami/redis:7"), "docker.io/bitnami/redis:7");
+        assert_eq!(canonical("index.docker.io/library/nginx"), "docker.io/library/nginx:latest");
+    }
+
+    #[test]
+    fn test_registries_and_ports()
+    {
+        assert_eq!(canonical("ghcr.io/owner/repo:v1.0.0"), "ghcr.io/owner/repo:v1.0.0");
+        assert_eq!(canonical("localhost:5000/app"), "localhost:5000/app:latest");
+        assert_eq!(canonical("localhost/app"), "localhost/app:latest");
+    }
+
+    #[test]
+    fn test_digest_pins()
+    {
+        let pinned: ImageReference = format!("nginx:1.27@{DIGEST}").parse().unwrap();
+        assert!(pinned.is_pinned());
+        assert_eq!(pinned.to_string(), format!("docker.io/library/nginx@{DIGEST}"));
+        assert_eq!(canonical(&format!("nginx@{DIGEST}")), pinned.to_string());
+
+        let retagged = pinned.with_tag("1.28").unwrap();
+        assert!(!retagged.is_pinned());
+        assert_eq!(retagged.to_string(), "docker.io/library/nginx:1.28");
+    }
+
+    #[test]
+    fn test_errors_point_at_the_broken_component()
+    {
+        let component = |reference: &str| reference.parse::<ImageReference>().unwrap_err().component;
+
+        assert_eq!(component(""), "reference");
+        assert_eq!(component("Nginx"), "repository component");
+        assert_eq!(component("image; rm -rf /"), "repository component");
+        assert_eq!(component("nginx:-bad"), "tag");
+        assert_eq!(component("nginx:a$b"), "tag");
+        assert_eq!(component("nginx@sha256:abc"), "digest");
+        assert_eq!(component("bad_host.io:99999/app"), "registry");
+        assert_eq!(component("ghcr.io/owner//repo"), "repository component");
+    }
+}
@@ -1,9 +1,20 @@
+pub mod authz;
 pub mod config;
 pub mod error;
 pub mod handlers;
+pub mod logging;
 pub mod router;
 pub mod state;
 pub mod services;
 pub mod model;
 pub mod middleware;
-pub mod sse;
\ No newline at end of file
+pub mod sse;
+pub mod cron;
+pub mod image_reference;
+pub mod timezones;
+pub mod units;
+pub mod client_ip;
+pub mod safe_http;
+pub mod rate_limit;
+#[cfg(test)]
+mod test_support;
\ No newline at end of file
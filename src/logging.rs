@@ -0,0 +1,237 @@
+//! Utilitaires de journalisation.
+//!
+//! Les valeurs contrôlées par l'utilisateur (URLs, noms, sorties d'outils externes)
+//! doivent passer par [`log_safe`] avant d'être écrites dans les logs, afin d'éviter
+//! l'injection de fausses lignes et l'explosion du volume de logs.
+//!
+//! Le filtre `RUST_LOG` est rechargeable à chaud via [`LogFilter`].
+
+use std::borrow::Cow;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use time::OffsetDateTime;
+use tracing_subscriber::{EnvFilter, Registry, filter::ParseError, layer::SubscriberExt, reload, util::SubscriberInitExt};
+
+/// Longueur maximale par défaut (en caractères) d'une valeur journalisée.
+pub const DEFAULT_MAX_LOG_FIELD_LENGTH: usize = 256;
+
+const TRUNCATION_MARKER: &str = "...[truncated]";
+
+static MAX_LOG_FIELD_LENGTH: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_LOG_FIELD_LENGTH);
+
+/// Définit la longueur maximale des valeurs passées à [`log_safe`].
+/// Appelé une seule fois au démarrage à partir de la configuration.
+pub fn set_max_log_field_length(length: usize)
+{
+    MAX_LOG_FIELD_LENGTH.store(length, Ordering::Relaxed);
+}
+
+/// Neutralise une valeur avant de la journaliser.
+///
+/// Les caractères de contrôle (retours à la ligne, séquences ANSI, ...) sont échappés
+/// et la valeur est tronquée à la longueur configurée.
+///
+/// # Examples
+/// ```
+/// # use hangar_back::logging::log_safe;
+/// assert_eq!(log_safe("nginx:latest"), "nginx:latest");
+/// assert_eq!(log_safe("evil\nINFO forged"), "evil\\nINFO forged");
+/// ```
+#[must_use]
+pub fn log_safe(value: &str) -> Cow<'_, str>
+{
+    truncate_and_escape(value, MAX_LOG_FIELD_LENGTH.load(Ordering::Relaxed))
+}
+
+fn truncate_and_escape(value: &str, max_length: usize) -> Cow<'_, str>
+{
+    let has_control = value.chars().any(char::is_control);
+    let too_long = value.chars().nth(max_length).is_some();
+
+    if !has_control && !too_long
+    {
+        return Cow::Borrowed(value);
+    }
+
+    let mut sanitized = String::with_capacity(value.len().min(max_length) + TRUNCATION_MARKER.len());
+    for (index, c) in value.chars().enumerate()
+    {
+        if index >= max_length
+        {
+            sanitized.push_str(TRUNCATION_MARKER);
+            break;
+        }
+
+        if c.is_control()
+        {
+            sanitized.extend(c.escape_default());
+        }
+        else
+        {
+            sanitized.push(c);
+        }
+    }
+
+    Cow::Owned(sanitized)
+}
+
+/// Filtre de logs actif, remplaçable à chaud par un administrateur.
+///
+/// Toute modification revient au filtre du démarrage après un délai ; chaque modification
+/// reçoit un numéro de génération pour qu'un retour programmé n'écrase pas une modification plus récente.
+pub struct LogFilter
+{
+    handle: reload::Handle<EnvFilter, Registry>,
+    startup: String,
+    generation: AtomicU64,
+    /// Génération de la modification en cours et date de son retour, `None` si le filtre du démarrage est actif.
+    pending_revert: Mutex<Option<(u64, OffsetDateTime)>>,
+}
+
+/// Installe le subscriber global avec un filtre rechargeable, initialisé depuis `RUST_LOG`.
+#[must_use]
+pub fn init_subscriber() -> LogFilter
+{
+    let startup = std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_default();
+    let (filter_layer, handle) = reload::Layer::new(startup_filter(&startup));
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    LogFilter { handle, startup, generation: AtomicU64::new(0), pending_revert: Mutex::new(None) }
+}
+
+/// Même interprétation que `EnvFilter::from_default_env` : directives invalides ignorées, `error` par défaut.
+fn startup_filter(directives: &str) -> EnvFilter
+{
+    EnvFilter::builder().parse_lossy(directives)
+}
+
+/// Valide un filtre saisi par un administrateur ; contrairement au démarrage, aucune directive invalide n'est ignorée.
+pub fn parse_filter(directives: &str) -> Result<EnvFilter, ParseError>
+{
+    EnvFilter::try_new(directives)
+}
+
+impl LogFilter
+{
+    /// Filtre rattaché à aucun subscriber, pour les tests : le subscriber global n'est pas installé.
+    #[cfg(test)]
+    pub(crate) fn detached() -> Self
+    {
+        let (_, handle) = reload::Layer::<EnvFilter, Registry>::new(startup_filter(""));
+        Self { handle, startup: String::new(), generation: AtomicU64::new(0), pending_revert: Mutex::new(None) }
+    }
+
+    #[must_use]
+    pub fn current(&self) -> String
+    {
+        self.handle.with_current(ToString::to_string).unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn startup(&self) -> &str
+    {
+        &self.startup
+    }
+
+    /// Date du retour automatique au filtre du démarrage, si une modification est en cours.
+    #[must_use]
+    pub fn revert_at(&self) -> Option<OffsetDateTime>
+    {
+        self.pending_revert.lock().unwrap().map(|(_, at)| at)
+    }
+
+    /// Active `filter` et renvoie la génération à passer à [`LogFilter::revert`] une fois `revert_after` écoulé.
+    pub fn apply(&self, filter: EnvFilter, revert_after: Duration) -> Result<u64, reload::Error>
+    {
+        let mut pending = self.pending_revert.lock().unwrap();
+        self.handle.reload(filter)?;
+
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        *pending = Some((generation, OffsetDateTime::now_utc() + revert_after));
+        Ok(generation)
+    }
+
+    /// Revient au filtre du démarrage si `generation` est toujours la dernière modification.
+    /// Renvoie `true` si le filtre a été rétabli.
+    pub fn revert(&self, generation: u64) -> bool
+    {
+        let mut pending = self.pending_revert.lock().unwrap();
+        if !matches!(*pending, Some((current, _)) if current == generation)
+        {
+            return false;
+        }
+
+        if let Err(e) = self.handle.reload(startup_filter(&self.startup))
+        {
+            tracing::error!("Failed to restore startup log filter: {}", e);
+            return false;
+        }
+
+        *pending = None;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_value_is_borrowed()
+    {
+        assert!(matches!(truncate_and_escape("ghcr.io/owner/repo:v1", 64), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_newlines_are_neutralized()
+    {
+        let forged = "repo\n2025-01-01T00:00:00Z  INFO hangar_back: admin logged in";
+        let sanitized = truncate_and_escape(forged, 256);
+
+        assert!(!sanitized.contains('\n'));
+        assert!(sanitized.starts_with("repo\\n2025"));
+    }
+
+    #[test]
+    fn test_ansi_escapes_are_neutralized()
+    {
+        let sanitized = truncate_and_escape("\u{1b}[31mRED\u{1b}[0m\r", 256);
+
+        assert!(!sanitized.chars().any(char::is_control));
+        assert_eq!(sanitized, "\\u{1b}[31mRED\\u{1b}[0m\\r");
+    }
+
+    #[test]
+    fn test_long_values_are_truncated()
+    {
+        let long = "a".repeat(1000);
+        let sanitized = truncate_and_escape(&long, 10);
+
+        assert_eq!(sanitized, format!("{}{}", "a".repeat(10), TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn test_exact_length_is_not_truncated()
+    {
+        assert_eq!(truncate_and_escape("abcdef", 6), "abcdef");
+    }
+
+    #[test]
+    fn test_parse_filter_rejects_invalid_directives()
+    {
+        assert!(parse_filter("info,hangar_back=debug,tower_http=warn").is_ok());
+        assert!(parse_filter("hangar_back=loud").is_err());
+    }
+
+    #[test]
+    fn test_startup_filter_ignores_invalid_directives()
+    {
+        assert_eq!(startup_filter("hangar_back=loud,info").to_string(), "info");
+    }
+}
@@ -1,4 +1,19 @@
 use hangar_back::config::Config;
+use hangar_back::logging;
+use hangar_back::services::backup_service::start_backup_scheduler;
+use hangar_back::services::base_image_service::start_base_image_keeper;
+use hangar_back::services::job_service::start_job_scheduler;
+use hangar_back::services::deployment_service::start_artifacts_cleanup;
+use hangar_back::services::orphan_service::start_image_gc;
+use hangar_back::services::build_workspace_service::start_workspace_sweeper;
+use hangar_back::services::docker_service;
+use hangar_back::services::domain_service::start_unverified_domains_sweeper;
+use hangar_back::services::cleanup_service::start_archive_scheduler;
+use hangar_back::services::usage_service::start_usage_sampler;
+use hangar_back::services::uptime_service::start_backend_heartbeat;
+use hangar_back::services::idle_service::start_idle_stopper;
+use hangar_back::services::crypto_audit_service::start_crypto_verifier;
+use hangar_back::services::missed_event_service::start_missed_events_writer;
 use hangar_back::sse::manager::start_cleanup_task;
 use hangar_back::sse::tasks::{start_docker_events_listener, start_metrics_collector};
 use hangar_back::state::InnerState;
@@ -16,7 +31,7 @@ async fn main()
 {
     dotenvy::dotenv().ok();
 
-    tracing_subscriber::fmt().with_env_filter(tracing_subscriber::EnvFilter::from_default_env()).init();
+    let log_filter = logging::init_subscriber();
 
     let config = match Config::from_env() 
     {
@@ -28,6 +43,8 @@ async fn main()
         }
     };
 
+    logging::set_max_log_field_length(config.log_max_field_length);
+
     let db_pool = match PgPoolOptions::new().max_connections(config.db_max_connections).connect(&config.db_url).await
     {
         Ok(pool) => 
@@ -78,7 +95,18 @@ async fn main()
         }
     };
 
-    let app_state = InnerState::new(config.clone(), docker_client, db_pool, mariadb_pool);
+    match docker_service::network_exists(&docker_client, &config.docker_network).await
+    {
+        Ok(true) => info!("✅ Docker network '{}' found.", config.docker_network),
+        Ok(false) =>
+        {
+            tracing::error!("❌ Configuration error: the Docker network '{}' (DOCKER_NETWORK) does not exist", config.docker_network);
+            std::process::exit(1);
+        }
+        Err(e) => warn!("⚠️ Could not check the Docker network '{}': {}", config.docker_network, e),
+    }
+
+    let app_state = InnerState::new(config.clone(), docker_client, db_pool, mariadb_pool, log_filter);
 
     let (shutdown_tx, _) = tokio::sync::broadcast::channel::<()>(1);
 
@@ -97,6 +125,85 @@ async fn main()
         shutdown_tx.subscribe()
     ));
 
+    if config.backup_enabled
+    {
+        tokio::spawn(start_backup_scheduler(
+            app_state.clone(),
+            shutdown_tx.subscribe()
+        ));
+    }
+
+    if config.base_image_warm_enabled
+    {
+        tokio::spawn(start_base_image_keeper(
+            app_state.clone(),
+            shutdown_tx.subscribe()
+        ));
+    }
+
+    tokio::spawn(start_job_scheduler(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    tokio::spawn(start_artifacts_cleanup(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    tokio::spawn(start_image_gc(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    tokio::spawn(start_workspace_sweeper(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    tokio::spawn(start_archive_scheduler(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    tokio::spawn(start_unverified_domains_sweeper(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    tokio::spawn(start_usage_sampler(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    tokio::spawn(start_backend_heartbeat(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    tokio::spawn(start_crypto_verifier(
+        app_state.clone(),
+        shutdown_tx.subscribe()
+    ));
+
+    let missed_events = app_state.missed_events.lock().ok().and_then(|mut receiver| receiver.take());
+    if let Some(missed_events) = missed_events
+    {
+        tokio::spawn(start_missed_events_writer(
+            app_state.clone(),
+            missed_events,
+            shutdown_tx.subscribe()
+        ));
+    }
+
+    if config.idle_stop_after_hours > 0
+    {
+        tokio::spawn(start_idle_stopper(
+            app_state.clone(),
+            shutdown_tx.subscribe()
+        ));
+    }
+
     let app = router::create_router(app_state);
 
     let addr = SocketAddr::from((config.host.parse::<Ipv4Addr>().unwrap(), config.port));
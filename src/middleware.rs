@@ -1,15 +1,17 @@
 use axum::
 {
-    extract::{Request, State, FromRequestParts},
+    extract::{ConnectInfo, Request, State, FromRequestParts},
     http::request::Parts,
     middleware::Next,
     response::Response,
 };
 use axum_extra::extract::CookieJar;
+use std::net::SocketAddr;
 
 use crate::
 {
-    error::AppError,
+    client_ip::{self, ClientIp},
+    error::{AppError, TokenErrorCode},
     services::jwt::{self, Claims},
     state::AppState,
 };
@@ -18,7 +20,7 @@ pub async fn auth(State(state): State<AppState>,jar: CookieJar, mut req: Request
 {
    
     let token = jar.get("auth_token").map(axum_extra::extract::cookie::Cookie::value)
-        .ok_or_else(|| AppError::Unauthorized("Authentication token missing.".to_string()))?;
+        .ok_or(AppError::TokenError(TokenErrorCode::Missing))?;
 
     let token_data = jwt::validate_jwt(token, &state.config.jwt_secret)?;
 
@@ -27,6 +29,15 @@ pub async fn auth(State(state): State<AppState>,jar: CookieJar, mut req: Request
     Ok(next.run(req).await)
 }
 
+/// Dépose l'adresse réelle du client ([`ClientIp`]) dans les extensions de la requête.
+pub async fn client_ip(State(state): State<AppState>, ConnectInfo(peer): ConnectInfo<SocketAddr>, mut req: Request, next: Next) -> Response
+{
+    let ip = client_ip::resolve(peer.ip(), req.headers(), &state.config.trusted_proxies);
+    req.extensions_mut().insert(ClientIp(ip));
+
+    next.run(req).await
+}
+
 pub async fn admin_auth(claims: Claims, req: Request, next: Next) -> Result<Response, AppError> 
 {
     if !claims.is_admin 
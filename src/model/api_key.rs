@@ -0,0 +1,30 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectApiKey
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub key_prefix: String,
+
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+
+    pub created_by: String,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_used_at: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Réponse à la création d'une clé : la clé en clair n'est renvoyée qu'une seule fois.
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey
+{
+    #[serde(flatten)]
+    pub key: ProjectApiKey,
+    pub secret: String,
+}
@@ -0,0 +1,17 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct Bucket
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub bucket_name: String,
+    pub access_key: String,
+
+    #[serde(skip_serializing)]
+    pub encrypted_secret_key: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
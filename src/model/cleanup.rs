@@ -0,0 +1,42 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// Signalement d'un projet pour nettoyage.
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectCleanup
+{
+    pub project_id: i32,
+    pub flagged_by: String,
+    pub reason: Option<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub flagged_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub archive_after: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub archived_at: Option<OffsetDateTime>,
+}
+
+/// Projet dont le propriétaire est inactif (ou inconnu), avec son éventuel signalement.
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct OwnerlessProject
+{
+    pub project_id: i32,
+    pub project_name: String,
+    pub owner: String,
+
+    /// `None` : le propriétaire ne s'est jamais connecté depuis le suivi des connexions.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub owner_last_login_at: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub flagged_at: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub archive_after: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub archived_at: Option<OffsetDateTime>,
+}
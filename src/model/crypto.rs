@@ -0,0 +1,45 @@
+use serde::Serialize;
+
+/// Colonne chiffrée vérifiée.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptedSource
+{
+    ProjectEnvVars,
+    DatabasePassword,
+    BucketSecretKey,
+}
+
+/// Résultat du déchiffrement d'une ligne, sans aucune valeur en clair.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct EncryptionCheck
+{
+    pub source: EncryptedSource,
+    pub id: i32,
+    pub passed: bool,
+    /// Variables d'environnement qui ne se déchiffrent pas, ou motif de l'échec de la ligne.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+}
+
+/// Vérification de tous les secrets chiffrés avec la clé configurée.
+#[derive(Debug, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct EncryptionReport
+{
+    pub checked: u64,
+    pub failed: u64,
+    pub rows: Vec<EncryptionCheck>,
+}
+
+impl EncryptionReport
+{
+    pub fn push(&mut self, check: EncryptionCheck)
+    {
+        self.checked += 1;
+        if !check.passed
+        {
+            self.failed += 1;
+        }
+        self.rows.push(check);
+    }
+}
@@ -10,11 +10,34 @@ pub struct Database
     pub username: String,
     pub encrypted_password: String,
     pub project_id: Option<i32>,
+    pub max_user_connections: i32,
+    pub max_queries_per_hour: i32,
 
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
 }
 
+impl Database
+{
+    #[must_use]
+    pub fn limits(&self) -> DatabaseLimits
+    {
+        DatabaseLimits
+        {
+            max_user_connections: u32::try_from(self.max_user_connections).unwrap_or_default(),
+            max_queries_per_hour: u32::try_from(self.max_queries_per_hour).unwrap_or_default(),
+        }
+    }
+}
+
+/// Limites de ressources de l'utilisateur MariaDB. `0` signifie "illimité".
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct DatabaseLimits
+{
+    pub max_user_connections: u32,
+    pub max_queries_per_hour: u32,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct DatabaseDetailsResponse
 {
@@ -23,10 +46,51 @@ pub struct DatabaseDetailsResponse
     pub project_id: Option<i32>,
     pub database_name: String,
     pub username: String,
-    pub password: String, // Mot de passe en clair
+    pub password: Option<String>, // Mot de passe en clair, uniquement sur demande explicite de révélation
     pub host: String,
     pub port: u16,
+    pub limits: DatabaseLimits,
     
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
-}
\ No newline at end of file
+}
+
+/// Session MariaDB active, telle que remontée par `information_schema.PROCESSLIST`.
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct DatabaseSession
+{
+    pub id: u64,
+    pub user: String,
+    pub database_name: Option<String>,
+    pub command: String,
+    pub state: Option<String>,
+    pub duration_seconds: i64,
+    pub query: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "database_backup_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BackupStatus
+{
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct DatabaseBackup
+{
+    pub id: i32,
+    pub database_id: i32,
+    pub status: BackupStatus,
+
+    /// Chemin sur l'hôte, jamais exposé aux clients.
+    #[serde(skip_serializing)]
+    pub file_path: Option<String>,
+
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::model::project::ProjectSourceType;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "deployment_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentKind
+{
+    ImageUpdate,
+    Rebuild,
+    Creation,
+    EnvUpdate,
+    Rollback,
+}
+
+impl DeploymentKind
+{
+    /// Seules les mises à jour d'image et les reconstructions peuvent être relancées.
+    #[must_use]
+    pub const fn is_retryable(self) -> bool
+    {
+        matches!(self, Self::ImageUpdate | Self::Rebuild)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "deployment_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentStatus
+{
+    Running,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct Deployment
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub kind: DeploymentKind,
+    pub status: DeploymentStatus,
+    pub triggered_by: String,
+    pub source_type: Option<ProjectSourceType>,
+    pub requested_image: Option<String>,
+    pub retry_of: Option<i32>,
+    pub failed_stage: Option<String>,
+    pub image_tag: Option<String>,
+    pub image_digest: Option<String>,
+    pub commit_sha: Option<String>,
+    /// Répertoire de build sur l'hôte : n'est pas exposé par l'API.
+    #[serde(skip)]
+    pub workspace_path: Option<String>,
+    pub image_scanned: bool,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub artifacts_expire_at: Option<OffsetDateTime>,
+
+    /// Début du déploiement.
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+}
+
+/// Étape à partir de laquelle un déploiement échoué peut reprendre.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResumeStage
+{
+    /// L'image est présente mais n'a pas passé le scan : le scan est relancé.
+    Scan,
+    /// L'image est prête : seul le conteneur est recréé.
+    Deploy,
+}
+
+impl ResumeStage
+{
+    #[must_use]
+    pub const fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::Scan => "scan",
+            Self::Deploy => "deploy",
+        }
+    }
+}
+
+/// Déploiement accompagné de l'étape de reprise possible (`None` : relance complète).
+#[derive(Debug, Serialize)]
+pub struct DeploymentResponse
+{
+    #[serde(flatten)]
+    pub deployment: Deployment,
+    pub resume_from: Option<ResumeStage>,
+}
+
+/// Déploiement listé par l'administration, avec le nom de son projet.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AdminDeployment
+{
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub deployment: Deployment,
+    pub project_name: String,
+}
@@ -0,0 +1,533 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use bollard::models::ContainerInspectResponse;
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// Sous-ensemble de `docker info` / `docker version` exposé aux administrateurs.
+/// Les champs sensibles (configuration des registres, proxys, labels du démon) ne sont jamais repris.
+#[derive(Debug, Serialize, Clone)]
+pub struct DockerDaemonInfo
+{
+    pub server_version: Option<String>,
+    pub api_version: Option<String>,
+    pub storage_driver: Option<String>,
+    pub cgroup_version: Option<String>,
+    pub kernel_version: Option<String>,
+    pub operating_system: Option<String>,
+    pub architecture: Option<String>,
+    pub total_containers: Option<i64>,
+    pub running_containers: Option<i64>,
+    pub total_images: Option<i64>,
+    pub disk_usage: DockerDiskUsage,
+    pub base_images: Vec<BaseImageStatus>,
+}
+
+/// Présence d'une image de base des builds sur le démon.
+#[derive(Debug, Serialize, Clone)]
+pub struct BaseImageStatus
+{
+    pub image: String,
+    pub present: bool,
+    pub digest: Option<String>,
+}
+
+/// Espace disque occupé dans le data-root Docker, en octets.
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+pub struct DockerDiskUsage
+{
+    pub images_bytes: i64,
+    pub containers_bytes: i64,
+    pub volumes_bytes: i64,
+    pub build_cache_bytes: i64,
+    pub total_bytes: i64,
+}
+
+/// Conteneur portant le label `app={APP_PREFIX}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformContainer
+{
+    pub name: String,
+    /// Label `hangar.project-id`, absent des conteneurs antérieurs au label.
+    pub project_id: Option<i32>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+/// Routeur Traefik déclaré par les labels d'un conteneur de la plateforme.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct TraefikRoute
+{
+    pub router: String,
+    pub container_name: String,
+    /// Label `hangar.project-id`, absent des conteneurs antérieurs au label.
+    pub project_id: Option<i32>,
+    pub rule: String,
+    /// Noms d'hôte des `Host(...)` de la règle.
+    pub hostnames: Vec<String>,
+}
+
+/// Volume de données d'un projet (`hangar-data-*`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformVolume
+{
+    pub name: String,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+/// Image construite par la plateforme, avec ses tags `hangar-local/*` (aucun pour une couche sans tag).
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct PlatformImage
+{
+    pub id: String,
+    pub tags: Vec<String>,
+    #[serde(skip)]
+    pub created_at: Option<OffsetDateTime>,
+}
+
+/// Ressources de la plateforme qui ne correspondent à aucun projet.
+#[derive(Debug, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct OrphanedResources
+{
+    pub containers: Vec<String>,
+    pub volumes: Vec<String>,
+    pub images: Vec<PlatformImage>,
+}
+
+impl OrphanedResources
+{
+    #[must_use]
+    pub fn count(&self) -> usize
+    {
+        self.containers.len() + self.volumes.len() + self.images.len()
+    }
+}
+
+/// Équivalent épuré de `docker inspect` pour le conteneur d'un projet.
+/// Ne contient ni chemins de l'hôte ni informations sur les réseaux des autres projets.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ContainerInspectSummary
+{
+    pub image_digest: Option<String>,
+    pub created_at: Option<String>,
+    pub started_at: Option<String>,
+    pub status: Option<String>,
+    pub restart_count: Option<i64>,
+    pub restart_policy: Option<String>,
+    pub memory_limit_bytes: Option<i64>,
+    pub cpu_quota: Option<i64>,
+    pub pids_limit: Option<i64>,
+    pub mounts: Vec<ContainerMountSummary>,
+    pub network: Option<ContainerNetworkSummary>,
+    pub labels: HashMap<String, String>,
+    /// Routeur Traefik réellement déclaré par le conteneur : `{prefix}-{projet}`, ou `{projet}` pour un
+    /// conteneur pas encore recréé depuis l'ajout du préfixe.
+    pub router_name: Option<String>,
+    pub health: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ContainerMountSummary
+{
+    pub target: Option<String>,
+    pub volume_name: Option<String>,
+    pub read_write: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ContainerNetworkSummary
+{
+    pub name: String,
+    pub ip_address: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+impl ContainerInspectSummary
+{
+    /// Construit le résumé en ne gardant que le réseau de la plateforme et les labels posés par Hangar.
+    #[must_use]
+    pub fn from_inspect(details: ContainerInspectResponse, network_name: &str, app_prefix: &str) -> Self
+    {
+        let state = details.state.unwrap_or_default();
+        let host_config = details.host_config.unwrap_or_default();
+
+        let mounts = details.mounts.unwrap_or_default()
+            .into_iter()
+            .map(|mount| ContainerMountSummary
+            {
+                target: mount.destination,
+                volume_name: mount.name,
+                read_write: mount.rw,
+            })
+            .collect();
+
+        let network = details.network_settings
+            .and_then(|settings| settings.networks)
+            .and_then(|mut networks| networks.remove(network_name))
+            .map(|endpoint| ContainerNetworkSummary
+            {
+                name: network_name.to_string(),
+                ip_address: endpoint.ip_address.filter(|ip| !ip.is_empty()),
+                aliases: endpoint.aliases.unwrap_or_default(),
+            });
+
+        let labels: HashMap<String, String> = details.config
+            .and_then(|config| config.labels)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(key, _)| key == "app" || key.starts_with("traefik.") || key.starts_with("hangar.") || key.starts_with(&format!("{app_prefix}.")))
+            .collect();
+
+        let router_name = labels.keys()
+            .find_map(|key| key.strip_prefix("traefik.http.routers.")?.strip_suffix(".rule"))
+            .map(str::to_string);
+
+        Self
+        {
+            image_digest: details.image,
+            created_at: details.created,
+            started_at: state.started_at,
+            status: state.status.map(|s| s.to_string()),
+            restart_count: details.restart_count,
+            restart_policy: host_config.restart_policy.and_then(|p| p.name).map(|n| n.to_string()),
+            memory_limit_bytes: host_config.memory,
+            cpu_quota: host_config.cpu_quota,
+            pids_limit: host_config.pids_limit,
+            mounts,
+            network,
+            labels,
+            router_name,
+            health: state.health.and_then(|h| h.status).map(|s| s.to_string()),
+        }
+    }
+}
+
+/// Sélection des logs d'un conteneur, validée par l'appelant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogsFilter
+{
+    /// Dernières lignes renvoyées.
+    pub tail: u32,
+    pub since: Option<OffsetDateTime>,
+    pub until: Option<OffsetDateTime>,
+    pub stdout: bool,
+    pub stderr: bool,
+    pub timestamps: bool,
+}
+
+impl Default for LogsFilter
+{
+    fn default() -> Self
+    {
+        Self { tail: 200, since: None, until: None, stdout: true, stderr: true, timestamps: true }
+    }
+}
+
+/// Flux d'origine d'une ligne de logs. `console` correspond aux conteneurs lancés avec un TTY,
+/// pour lesquels Docker ne sépare pas stdout et stderr.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream
+{
+    Stdout,
+    Stderr,
+    Stdin,
+    Console,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ContainerLogLine
+{
+    pub stream: LogStream,
+    pub timestamp: Option<String>,
+    pub line: String,
+    /// La ligne contenait de l'UTF-8 invalide, remplacé par U+FFFD.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossy: bool,
+}
+
+/// Logs d'un conteneur reconstitués ligne par ligne à partir des trames Docker.
+///
+/// Une ligne trop longue est découpée par Docker en plusieurs trames, chacune préfixée de son
+/// horodatage : les morceaux sont recollés par flux jusqu'au saut de ligne, et le décodage UTF-8
+/// n'a lieu qu'une fois la ligne complète pour ne pas casser un caractère à cheval sur deux trames.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ContainerLogs
+{
+    pub logs: Vec<ContainerLogLine>,
+    pub truncated: bool,
+    #[serde(skip)]
+    pending: Vec<(LogStream, Option<String>, Vec<u8>)>,
+    #[serde(skip)]
+    size: usize,
+}
+
+impl ContainerLogs
+{
+    /// Ajoute une trame du flux `stream`. Renvoie `false` une fois `max_size` octets dépassés.
+    pub fn push_frame(&mut self, stream: LogStream, frame: &[u8], max_size: usize) -> bool
+    {
+        if self.truncated
+        {
+            return false;
+        }
+
+        self.size += frame.len();
+        if self.size > max_size
+        {
+            self.truncated = true;
+            return false;
+        }
+
+        let mut rest = frame;
+        while !rest.is_empty()
+        {
+            let (segment, complete) = match rest.iter().position(|&b| b == b'\n')
+            {
+                Some(end) => (&rest[..end], true),
+                None => (rest, false),
+            };
+            rest = if complete { &rest[segment.len() + 1..] } else { &[] };
+
+            let (timestamp, content) = split_timestamp(segment);
+            match self.pending.iter().position(|(s, _, _)| *s == stream)
+            {
+                Some(index) => self.pending[index].2.extend_from_slice(content),
+                None => self.pending.push((stream, timestamp, content.to_vec())),
+            }
+
+            if complete
+            {
+                self.flush_stream(stream);
+            }
+        }
+
+        true
+    }
+
+    /// Termine les lignes restées sans saut de ligne final.
+    #[must_use]
+    pub fn finish(mut self) -> Self
+    {
+        for (stream, timestamp, content) in std::mem::take(&mut self.pending)
+        {
+            self.logs.push(decode_line(stream, timestamp, &content));
+        }
+        self
+    }
+
+    /// Retire les lignes complètes reconstituées jusqu'ici, pour un suivi en continu (`follow`).
+    /// Les morceaux sans saut de ligne final restent en attente de la trame suivante.
+    pub fn take_lines(&mut self) -> Vec<ContainerLogLine>
+    {
+        std::mem::take(&mut self.logs)
+    }
+
+    /// Rendu texte historique : les lignes horodatées concaténées, comme `docker logs -t`.
+    #[must_use]
+    pub fn to_text(&self) -> String
+    {
+        let mut text = String::new();
+        for entry in &self.logs
+        {
+            if let Some(timestamp) = &entry.timestamp
+            {
+                text.push_str(timestamp);
+                text.push(' ');
+            }
+            text.push_str(&entry.line);
+            text.push('\n');
+        }
+        if self.truncated
+        {
+            text.push_str("[...] Logs truncated (exceeded 10MB)");
+        }
+        text
+    }
+
+    fn flush_stream(&mut self, stream: LogStream)
+    {
+        if let Some(index) = self.pending.iter().position(|(s, _, _)| *s == stream)
+        {
+            let (stream, timestamp, content) = self.pending.remove(index);
+            self.logs.push(decode_line(stream, timestamp, &content));
+        }
+    }
+}
+
+/// Sépare l'horodatage RFC 3339 ajouté par Docker (`timestamps: true`) du contenu de la ligne.
+fn split_timestamp(segment: &[u8]) -> (Option<String>, &[u8])
+{
+    let Some(space) = segment.iter().position(|&b| b == b' ') else { return (None, segment) };
+    match std::str::from_utf8(&segment[..space])
+    {
+        Ok(candidate) if OffsetDateTime::parse(candidate, &Rfc3339).is_ok() =>
+            (Some(candidate.to_string()), &segment[space + 1..]),
+        _ => (None, segment),
+    }
+}
+
+fn decode_line(stream: LogStream, timestamp: Option<String>, content: &[u8]) -> ContainerLogLine
+{
+    let content = content.strip_suffix(b"\r").unwrap_or(content);
+    let decoded = String::from_utf8_lossy(content);
+    ContainerLogLine
+    {
+        stream,
+        timestamp,
+        lossy: matches!(decoded, Cow::Owned(_)),
+        line: decoded.into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::models::{ContainerConfig, EndpointSettings, MountPoint, MountPointTypeEnum, NetworkSettings};
+
+    #[test]
+    fn test_summary_never_exposes_host_paths_or_foreign_networks()
+    {
+        let details = ContainerInspectResponse
+        {
+            mounts: Some(vec![
+                MountPoint
+                {
+                    typ: Some(MountPointTypeEnum::VOLUME),
+                    name: Some("hangar-data-demo".to_string()),
+                    source: Some("/var/lib/docker/volumes/hangar-data-demo/_data".to_string()),
+                    destination: Some("/data".to_string()),
+                    rw: Some(true),
+                    ..Default::default()
+                },
+                MountPoint
+                {
+                    typ: Some(MountPointTypeEnum::BIND),
+                    source: Some("/srv/secret-host-dir".to_string()),
+                    destination: Some("/bind".to_string()),
+                    ..Default::default()
+                },
+            ]),
+            network_settings: Some(NetworkSettings
+            {
+                networks: Some(HashMap::from([
+                    ("hangar".to_string(), EndpointSettings { ip_address: Some("172.18.0.5".to_string()), ..Default::default() }),
+                    ("other-tenant".to_string(), EndpointSettings { ip_address: Some("10.9.9.9".to_string()), ..Default::default() }),
+                ])),
+                ..Default::default()
+            }),
+            config: Some(ContainerConfig
+            {
+                labels: Some(HashMap::from([
+                    ("app".to_string(), "hangar".to_string()),
+                    ("com.docker.compose.project".to_string(), "host".to_string()),
+                    ("traefik.http.routers.hangar-demo.rule".to_string(), "Host(`demo.hangar.test`)".to_string()),
+                ])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let summary = ContainerInspectSummary::from_inspect(details, "hangar", "hangar");
+        assert_eq!(summary.router_name.as_deref(), Some("hangar-demo"));
+        let serialized = serde_json::to_string(&summary).unwrap();
+
+        assert!(!serialized.contains("/var/lib/docker"));
+        assert!(!serialized.contains("/srv/secret-host-dir"));
+        assert!(!serialized.contains("10.9.9.9"));
+        assert!(!serialized.contains("com.docker.compose.project"));
+        assert!(serialized.contains("hangar-data-demo"));
+        assert!(serialized.contains("172.18.0.5"));
+    }
+
+    const TS: &str = "2026-10-16T08:00:00.123456789Z";
+
+    fn logs_of(frames: &[(LogStream, Vec<u8>)]) -> ContainerLogs
+    {
+        let mut logs = ContainerLogs::default();
+        for (stream, frame) in frames
+        {
+            assert!(logs.push_frame(*stream, frame, usize::MAX));
+        }
+        logs.finish()
+    }
+
+    #[test]
+    fn test_logs_keep_stream_and_timestamp_per_line()
+    {
+        let logs = logs_of(&[
+            (LogStream::Stdout, format!("{TS} hello\n").into_bytes()),
+            (LogStream::Stderr, format!("{TS} boom\n").into_bytes()),
+        ]);
+
+        assert_eq!(logs.logs.len(), 2);
+        assert_eq!(logs.logs[0].stream, LogStream::Stdout);
+        assert_eq!(logs.logs[0].timestamp.as_deref(), Some(TS));
+        assert_eq!(logs.logs[0].line, "hello");
+        assert_eq!(logs.logs[1].stream, LogStream::Stderr);
+        assert_eq!(logs.logs[1].line, "boom");
+        assert_eq!(logs.to_text(), format!("{TS} hello\n{TS} boom\n"));
+    }
+
+    #[test]
+    fn test_partial_frames_are_joined_without_inner_timestamps()
+    {
+        let logs = logs_of(&[
+            (LogStream::Stdout, format!("{TS} first half ").into_bytes()),
+            (LogStream::Stderr, format!("{TS} interleaved\n").into_bytes()),
+            (LogStream::Stdout, format!("{TS} second half\n").into_bytes()),
+        ]);
+
+        let lines: Vec<_> = logs.logs.iter().map(|l| (l.stream, l.line.as_str())).collect();
+        assert_eq!(lines, vec![(LogStream::Stderr, "interleaved"), (LogStream::Stdout, "first half second half")]);
+    }
+
+    #[test]
+    fn test_console_frames_split_lines_and_utf8_across_frames()
+    {
+        let mut first = format!("{TS} caf").into_bytes();
+        first.push(0xC3);
+        let mut second = format!("{TS} ").into_bytes();
+        second.extend_from_slice(&[0xA9, b'\r', b'\n']);
+        second.extend_from_slice(format!("{TS} bad ").as_bytes());
+        second.push(0xFF);
+
+        let logs = logs_of(&[(LogStream::Console, first), (LogStream::Console, second)]);
+
+        assert_eq!(logs.logs.len(), 2);
+        assert_eq!(logs.logs[0].line, "café");
+        assert!(!logs.logs[0].lossy);
+        assert_eq!(logs.logs[1].line, "bad \u{FFFD}");
+        assert!(logs.logs[1].lossy);
+    }
+
+    #[test]
+    fn test_logs_stop_past_size_limit()
+    {
+        let mut logs = ContainerLogs::default();
+        assert!(logs.push_frame(LogStream::Stdout, b"12345\n", 10));
+        assert!(!logs.push_frame(LogStream::Stdout, b"67890\n", 10));
+        let logs = logs.finish();
+
+        assert!(logs.truncated);
+        assert_eq!(logs.logs.len(), 1);
+        assert!(logs.to_text().ends_with("[...] Logs truncated (exceeded 10MB)"));
+    }
+
+    #[test]
+    fn test_take_lines_keeps_partial_line_pending()
+    {
+        let mut logs = ContainerLogs::default();
+        logs.push_frame(LogStream::Stdout, format!("{TS} ready\n{TS} half").as_bytes(), usize::MAX);
+
+        let lines = logs.take_lines();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].line, "ready");
+
+        logs.push_frame(LogStream::Stdout, format!("{TS}  done\n").as_bytes(), usize::MAX);
+        let lines = logs.take_lines();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].line, "half done");
+        assert!(logs.take_lines().is_empty());
+    }
+}
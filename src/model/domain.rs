@@ -0,0 +1,39 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectDomain
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub fqdn: String,
+
+    #[serde(skip_serializing)]
+    pub verification_token: String,
+
+    pub verified: bool,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub verified_at: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Enregistrements DNS acceptés pour prouver la possession d'un domaine : l'un ou l'autre suffit.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct DomainVerification
+{
+    pub txt_name: String,
+    pub txt_value: String,
+    /// Cible d'un CNAME posé sur le domaine lui-même : le nom d'hôte principal du projet.
+    pub cname_target: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectDomainResponse
+{
+    #[serde(flatten)]
+    pub domain: ProjectDomain,
+    pub verification: DomainVerification,
+}
@@ -0,0 +1,19 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// Invitation à participer à un projet, en attente de la réponse de l'invité.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ProjectInvitation
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub project_name: String,
+    pub invitee: String,
+    pub invited_by: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectJob
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub schedule: String,
+    pub command: Vec<String>,
+    pub enabled: bool,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_run_at: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "job_run_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunStatus
+{
+    Running,
+    Succeeded,
+    Failed,
+    TimedOut,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct JobRun
+{
+    pub id: i32,
+    pub job_id: i32,
+    pub status: JobRunStatus,
+    pub exit_code: Option<i32>,
+    pub output: Option<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+}
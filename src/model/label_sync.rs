@@ -0,0 +1,37 @@
+use serde::Serialize;
+
+/// Label du conteneur qui diffère de celui que poserait une création aujourd'hui.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct LabelDiff
+{
+    pub label: String,
+    /// Absent du conteneur actuel.
+    pub current: Option<String>,
+    /// Ne serait plus posé (routeur d'un ancien nommage...).
+    pub expected: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelResyncStatus
+{
+    InSync,
+    /// Labels différents, conteneur laissé tel quel (`dry_run`).
+    OutOfSync,
+    Recreated,
+    Failed,
+    /// Projet archivé ou sans conteneur : ses labels seront posés à sa prochaine création.
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LabelResyncResult
+{
+    pub project_id: i32,
+    pub project_name: String,
+    pub status: LabelResyncStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diffs: Vec<LabelDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
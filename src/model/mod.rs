@@ -1,3 +1,26 @@
 pub mod user;
 pub mod project;
-pub mod database;
\ No newline at end of file
+pub mod database;
+#[cfg(feature = "object_storage")]
+pub mod bucket;
+pub mod job;
+pub mod docker;
+pub mod api_key;
+pub mod deployment;
+pub mod cleanup;
+pub mod notification;
+pub mod share_link;
+pub mod offboarding;
+pub mod usage;
+pub mod timeline;
+pub mod invitation;
+pub mod scan;
+pub mod response;
+pub mod platform;
+pub mod crypto;
+pub mod domain;
+pub mod template;
+pub mod label_sync;
+pub mod webhook;
+#[cfg(test)]
+mod serialization_tests;
\ No newline at end of file
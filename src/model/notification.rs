@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Canal de livraison d'une notification.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, sqlx::Type)]
+#[sqlx(type_name = "notification_channel", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel
+{
+    /// Canal SSE personnel de l'utilisateur.
+    #[default]
+    Sse,
+    /// Webhook personnel de l'utilisateur.
+    Webhook,
+    /// E-mail à l'adresse du compte, si le SMTP est configuré.
+    Email,
+    None,
+}
+
+/// Types de notifications adressées aux membres d'un projet.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind
+{
+    /// Fin d'exécution d'une tâche planifiée ou ponctuelle.
+    JobRun,
+    /// Signalement, annulation ou archivage d'un projet sans propriétaire actif.
+    ProjectCleanup,
+    /// Arrêt automatique d'un projet inactif.
+    IdleStop,
+    /// Échec d'un déploiement suivi (propriétaire seulement).
+    DeployFailed,
+    /// Arrêt inattendu du conteneur (propriétaire seulement).
+    ContainerCrashed,
+}
+
+impl NotificationKind
+{
+    pub const ALL: [Self; 5] = [Self::JobRun, Self::ProjectCleanup, Self::IdleStop, Self::DeployFailed, Self::ContainerCrashed];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::JobRun => "job_run",
+            Self::ProjectCleanup => "project_cleanup",
+            Self::IdleStop => "idle_stop",
+            Self::DeployFailed => "deploy_failed",
+            Self::ContainerCrashed => "container_crashed",
+        }
+    }
+
+    /// Canal sans préférence enregistrée : le SSE, comportement historique, sauf pour les échecs,
+    /// les arrêts inattendus et le nettoyage des projets, envoyés par e-mail.
+    #[must_use]
+    pub const fn default_channel(self) -> NotificationChannel
+    {
+        match self
+        {
+            Self::DeployFailed | Self::ContainerCrashed | Self::ProjectCleanup => NotificationChannel::Email,
+            Self::JobRun | Self::IdleStop => NotificationChannel::Sse,
+        }
+    }
+
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self>
+    {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+/// Préférences de notification d'un utilisateur (`GET/PUT /api/me/notifications`).
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NotificationSettings
+{
+    pub webhook_url: Option<String>,
+    /// Canal par type de notification ; un type absent utilise son canal par défaut.
+    #[serde(default)]
+    pub preferences: BTreeMap<NotificationKind, NotificationChannel>,
+    /// Indique si la plateforme envoie des e-mails (SMTP configuré) ; ignoré en écriture.
+    #[serde(default, skip_deserializing)]
+    pub email_enabled: bool,
+}
+
+impl NotificationSettings
+{
+    #[must_use]
+    pub fn channel(&self, kind: NotificationKind) -> NotificationChannel
+    {
+        self.preferences.get(&kind).copied().unwrap_or_else(|| kind.default_channel())
+    }
+
+    /// Complète les préférences avec le canal par défaut pour chaque type connu.
+    #[must_use]
+    pub fn with_defaults(mut self) -> Self
+    {
+        for kind in NotificationKind::ALL
+        {
+            self.preferences.entry(kind).or_insert_with(|| kind.default_channel());
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_names_match_serde()
+    {
+        for kind in NotificationKind::ALL
+        {
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.as_str());
+            assert_eq!(NotificationKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(NotificationKind::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_missing_preferences_default_to_sse()
+    {
+        let settings: NotificationSettings = serde_json::from_str(r#"{"webhook_url": null, "preferences": {"job_run": "none"}}"#).unwrap();
+
+        assert_eq!(settings.channel(NotificationKind::JobRun), NotificationChannel::None);
+        assert_eq!(settings.channel(NotificationKind::IdleStop), NotificationChannel::Sse);
+        assert_eq!(settings.channel(NotificationKind::ProjectCleanup), NotificationChannel::Email);
+        assert_eq!(settings.channel(NotificationKind::DeployFailed), NotificationChannel::Email);
+        assert!(!settings.email_enabled);
+        assert_eq!(settings.with_defaults().preferences.len(), NotificationKind::ALL.len());
+    }
+}
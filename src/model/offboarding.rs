@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::model::{api_key::ProjectApiKey, notification::NotificationSettings, project::Project, share_link::ProjectShareLink};
+
+/// Base de données d'un utilisateur, sans ses identifiants.
+#[derive(Debug, Serialize, Clone)]
+pub struct FootprintDatabase
+{
+    pub id: i32,
+    pub database_name: String,
+    pub project_id: Option<i32>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Jeton à renvoyer pour confirmer le départ d'un utilisateur.
+#[derive(Debug, Serialize, Clone)]
+pub struct OffboardingConfirmation
+{
+    pub token: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+/// Tout ce qui est rattaché à un login, tel que vu par un administrateur.
+#[derive(Debug, Serialize, Clone)]
+pub struct UserFootprint
+{
+    pub login: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+
+    /// `None` : l'utilisateur ne s'est jamais connecté depuis le suivi des connexions.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_login_at: Option<OffsetDateTime>,
+
+    pub owned_projects: Vec<Project>,
+    pub participations: Vec<Project>,
+    pub database: Option<FootprintDatabase>,
+    /// Clés d'API créées par l'utilisateur, quel que soit le projet.
+    pub api_keys: Vec<ProjectApiKey>,
+    /// Liens de partage actifs créés par l'utilisateur.
+    pub share_links: Vec<ProjectShareLink>,
+    pub notifications: NotificationSettings,
+    pub confirmation: OffboardingConfirmation,
+}
+
+/// Devenir du projet possédé par l'utilisateur qui part.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ProjectDisposition
+{
+    /// Le projet (et la base qui lui est liée) change de propriétaire.
+    Transfer { new_owner: String },
+    /// Le conteneur est arrêté, le volume et l'image sont conservés.
+    Archive,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OffboardPayload
+{
+    pub confirmation_token: String,
+    /// Obligatoire si l'utilisateur possède un projet.
+    pub project: Option<ProjectDisposition>,
+}
+
+/// Étapes du départ, exécutées dans cet ordre.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OffboardingStep
+{
+    RevokeApiKeys,
+    RevokeShareLinks,
+    RemoveParticipations,
+    OwnedProject,
+    Database,
+    NotificationSettings,
+    DisconnectSse,
+}
+
+impl OffboardingStep
+{
+    pub const ALL: [Self; 7] = [
+        Self::RevokeApiKeys,
+        Self::RevokeShareLinks,
+        Self::RemoveParticipations,
+        Self::OwnedProject,
+        Self::Database,
+        Self::NotificationSettings,
+        Self::DisconnectSse,
+    ];
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus
+{
+    Done,
+    /// Rien à faire pour cet utilisateur.
+    Skipped,
+    Failed,
+    /// Non exécutée : une étape précédente a échoué.
+    Pending,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StepResult
+{
+    pub step: OffboardingStep,
+    pub status: StepStatus,
+    pub detail: Option<String>,
+}
+
+/// Résultat étape par étape. Les étapes `pending` restent à traiter après un échec.
+#[derive(Debug, Serialize, Clone)]
+pub struct OffboardingReport
+{
+    pub login: String,
+    pub completed: bool,
+    pub steps: Vec<StepResult>,
+}
+
+impl OffboardingReport
+{
+    #[must_use]
+    pub fn new(login: &str) -> Self
+    {
+        Self
+        {
+            login: login.to_string(),
+            completed: false,
+            steps: OffboardingStep::ALL.iter()
+                .map(|&step| StepResult { step, status: StepStatus::Pending, detail: None })
+                .collect(),
+        }
+    }
+
+    pub fn record(&mut self, step: OffboardingStep, status: StepStatus, detail: Option<String>)
+    {
+        if let Some(result) = self.steps.iter_mut().find(|result| result.step == step)
+        {
+            result.status = status;
+            result.detail = detail;
+        }
+
+        self.completed = self.steps.iter().all(|result| matches!(result.status, StepStatus::Done | StepStatus::Skipped));
+    }
+
+    /// Étapes restant à traiter (échouée ou non exécutées).
+    #[must_use]
+    pub fn remaining(&self) -> Vec<OffboardingStep>
+    {
+        self.steps.iter()
+            .filter(|result| matches!(result.status, StepStatus::Failed | StepStatus::Pending))
+            .map(|result| result.step)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_lists_remaining_steps_after_failure()
+    {
+        let mut report = OffboardingReport::new("alice");
+        report.record(OffboardingStep::RevokeApiKeys, StepStatus::Done, None);
+        report.record(OffboardingStep::RevokeShareLinks, StepStatus::Skipped, None);
+        report.record(OffboardingStep::RemoveParticipations, StepStatus::Failed, Some("boom".into()));
+
+        assert!(!report.completed);
+        assert_eq!(report.remaining(), &OffboardingStep::ALL[2..]);
+    }
+
+    #[test]
+    fn test_report_completes_when_every_step_is_done_or_skipped()
+    {
+        let mut report = OffboardingReport::new("alice");
+        for step in OffboardingStep::ALL
+        {
+            report.record(step, StepStatus::Skipped, None);
+        }
+
+        assert!(report.completed);
+        assert!(report.remaining().is_empty());
+    }
+
+    #[test]
+    fn test_project_disposition_payload()
+    {
+        let payload: OffboardPayload = serde_json::from_str(
+            r#"{"confirmation_token": "t", "project": {"action": "transfer", "new_owner": "bob"}}"#
+        ).unwrap();
+        assert_eq!(payload.project, Some(ProjectDisposition::Transfer { new_owner: "bob".into() }));
+
+        let payload: OffboardPayload = serde_json::from_str(r#"{"confirmation_token": "t", "project": {"action": "archive"}}"#).unwrap();
+        assert_eq!(payload.project, Some(ProjectDisposition::Archive));
+    }
+}
@@ -0,0 +1,209 @@
+use serde::Serialize;
+
+use crate::
+{
+    config::Config,
+    model::project::DEFAULT_CONTAINER_PORT,
+    services::validation_service::{FORBIDDEN_ENV_VARS, FORBIDDEN_ENV_VAR_PREFIXES, MAX_SCAN_IGNORE_CVES},
+};
+
+/// Projets qu'un utilisateur peut posséder.
+pub const PROJECTS_PER_OWNER: u32 = 1;
+/// Bases de données qu'un utilisateur peut posséder.
+pub const DATABASES_PER_OWNER: u32 = 1;
+
+/// Limites et capacités de l'instance, pour le frontend.
+///
+/// Construite champ par champ à partir de la configuration, jamais en sérialisant `Config` :
+/// un secret ne peut y figurer qu'en l'ajoutant explicitement ici.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PublicLimits
+{
+    pub app_domain_suffix: String,
+    pub container: ContainerLimits,
+    pub env_vars: EnvVarLimits,
+    pub scanner: ScannerSettings,
+    pub quotas: Quotas,
+    pub build: BuildSettings,
+    pub object_storage_enabled: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ContainerLimits
+{
+    pub memory_mb: u64,
+    pub cpu_cores: f64,
+    pub default_port: u16,
+    pub max_stop_grace_seconds: i32,
+    /// Fuseau appliqué aux projets qui n'en définissent pas.
+    pub default_timezone: Option<String>,
+    /// Profils de ressources qu'un administrateur peut attribuer à un projet.
+    pub resource_profiles: Vec<String>,
+    /// Heures d'inactivité avant l'arrêt automatique d'un projet sans `keep_alive` ; 0 : jamais.
+    pub idle_stop_after_hours: u64,
+    /// Vérification de santé appliquée aux projets qui ne la surchargent pas.
+    pub health_check_max_attempts: u32,
+    pub health_check_interval_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct EnvVarLimits
+{
+    /// Noms refusés, sans distinction de casse.
+    pub forbidden: Vec<String>,
+    pub forbidden_prefixes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct ScannerSettings
+{
+    pub enabled: bool,
+    /// Sévérité à partir de laquelle une image est refusée.
+    pub fail_on_severity: String,
+    pub max_ignored_vulnerabilities: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Quotas
+{
+    pub projects_per_owner: u32,
+    pub databases_per_owner: u32,
+    pub max_jobs_per_project: i64,
+    pub max_participants_per_project: i64,
+    pub min_job_interval_minutes: u64,
+    pub job_timeout_seconds: u64,
+    pub project_archive_max_mb: u64,
+    pub upload_max_mb: u64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct BuildSettings
+{
+    /// Image de base du Dockerfile généré ; un dépôt peut aussi fournir son propre Dockerfile.
+    pub base_image: String,
+}
+
+impl PublicLimits
+{
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self
+    {
+        Self
+        {
+            app_domain_suffix: config.app_domain_suffix.clone(),
+            container: ContainerLimits
+            {
+                memory_mb: config.container_memory_mb.get(),
+                cpu_cores: config.container_cpu_quota.cores(),
+                default_port: DEFAULT_CONTAINER_PORT,
+                max_stop_grace_seconds: config.max_stop_grace_seconds,
+                default_timezone: config.default_timezone.clone(),
+                resource_profiles: config.resource_profiles.keys().cloned().collect(),
+                idle_stop_after_hours: config.idle_stop_after_hours,
+                health_check_max_attempts: config.health_check_max_attempts,
+                health_check_interval_ms: config.health_check_interval_ms,
+            },
+            env_vars: EnvVarLimits
+            {
+                forbidden: FORBIDDEN_ENV_VARS.iter().map(|name| (*name).to_string()).collect(),
+                forbidden_prefixes: FORBIDDEN_ENV_VAR_PREFIXES.iter().map(|prefix| (*prefix).to_string()).collect(),
+            },
+            scanner: ScannerSettings
+            {
+                enabled: config.grype_enabled,
+                fail_on_severity: config.grype_fail_on_severity.clone(),
+                max_ignored_vulnerabilities: MAX_SCAN_IGNORE_CVES,
+            },
+            quotas: Quotas
+            {
+                projects_per_owner: PROJECTS_PER_OWNER,
+                databases_per_owner: DATABASES_PER_OWNER,
+                max_jobs_per_project: config.jobs_max_per_project,
+                max_participants_per_project: config.participants_max_per_project,
+                min_job_interval_minutes: config.jobs_min_interval_minutes,
+                job_timeout_seconds: config.jobs_timeout_seconds.get(),
+                project_archive_max_mb: config.project_archive_max_mb,
+                upload_max_mb: config.upload_max_mb,
+            },
+            build: BuildSettings { base_image: config.build_base_image.clone() },
+            object_storage_enabled: object_storage_enabled(config),
+        }
+    }
+}
+
+#[cfg(feature = "object_storage")]
+const fn object_storage_enabled(config: &Config) -> bool
+{
+    config.object_storage.is_some()
+}
+
+#[cfg(not(feature = "object_storage"))]
+const fn object_storage_enabled(_config: &Config) -> bool
+{
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fragments trahissant un secret de la configuration (`jwt_secret`, `encryption_key`, `db_url`...).
+    const SECRET_MARKERS: [&str; 8] = ["secret", "password", "token", "key", "jwt", "encryption", "url", "private"];
+
+    fn keys(value: &serde_json::Value, found: &mut Vec<String>)
+    {
+        if let serde_json::Value::Object(map) = value
+        {
+            for (key, nested) in map
+            {
+                found.push(key.clone());
+                keys(nested, found);
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_secret_field_is_exposed()
+    {
+        let limits = PublicLimits
+        {
+            app_domain_suffix: "garageisep.com".to_string(),
+            container: ContainerLimits
+            {
+                memory_mb: 512,
+                cpu_cores: 0.5,
+                default_port: DEFAULT_CONTAINER_PORT,
+                max_stop_grace_seconds: 300,
+                default_timezone: Some("Europe/Paris".to_string()),
+                resource_profiles: vec!["default".to_string()],
+                idle_stop_after_hours: 72,
+                health_check_max_attempts: 10,
+                health_check_interval_ms: 1000,
+            },
+            env_vars: EnvVarLimits { forbidden: vec!["PATH".to_string()], forbidden_prefixes: vec!["TRAEFIK_".to_string()] },
+            scanner: ScannerSettings { enabled: true, fail_on_severity: "high".to_string(), max_ignored_vulnerabilities: MAX_SCAN_IGNORE_CVES },
+            quotas: Quotas
+            {
+                projects_per_owner: PROJECTS_PER_OWNER,
+                databases_per_owner: DATABASES_PER_OWNER,
+                max_jobs_per_project: 5,
+                max_participants_per_project: 20,
+                min_job_interval_minutes: 10,
+                job_timeout_seconds: 300,
+                project_archive_max_mb: 1024,
+                upload_max_mb: 50,
+            },
+            build: BuildSettings { base_image: "node:22-alpine".to_string() },
+            object_storage_enabled: false,
+        };
+
+        let mut found = Vec::new();
+        keys(&serde_json::to_value(&limits).unwrap(), &mut found);
+
+        for key in &found
+        {
+            assert!(!SECRET_MARKERS.iter().any(|marker| key.contains(marker)), "'{key}' looks like a secret");
+        }
+        assert!(found.contains(&"app_domain_suffix".to_string()));
+    }
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
-use crate::model::database::DatabaseDetailsResponse;
+use crate::{config::ResourceProfile, error::ProjectErrorCode, model::{database::DatabaseDetailsResponse, invitation::ProjectInvitation, user::UserDisplay}, units::MemoryBytes};
+
+/// Port HTTP du conteneur lorsque le projet n'en précise pas.
+pub const DEFAULT_CONTAINER_PORT: u16 = 80;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
 #[sqlx(type_name = "project_source_type", rename_all = "lowercase")]
@@ -10,6 +13,8 @@ pub enum ProjectSourceType
 {
     Direct,
     Github,
+    /// Site statique construit à partir d'une archive envoyée (`.zip`, `.tar.gz`).
+    Upload,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
@@ -29,6 +34,9 @@ pub struct Project
     pub source_branch: Option<String>,
     #[sqlx(default)]
     pub source_root_dir: Option<String>,
+    /// Projet `github` construit avec le Dockerfile du dépôt au lieu du Dockerfile généré.
+    #[sqlx(default)]
+    pub use_repo_dockerfile: bool,
     pub deployed_image_tag: String,
     pub deployed_image_digest: String,
 
@@ -39,25 +47,250 @@ pub struct Project
     #[sqlx(default)]
     pub volume_name: Option<String>,
 
+    /// Exposé dans la section `runtime` des détails du projet.
+    #[serde(skip_serializing)]
+    pub stop_grace_seconds: i32,
+
+    /// Exposés dans la section `runtime` des détails du projet.
+    #[sqlx(default)]
+    #[serde(skip_serializing)]
+    pub entrypoint: Option<Vec<String>>,
+    #[sqlx(default)]
+    #[serde(skip_serializing)]
+    pub command: Option<Vec<String>>,
+
+    /// Exposé dans la section `runtime` des détails du projet.
+    #[sqlx(default)]
+    #[serde(skip_serializing)]
+    pub timezone: Option<String>,
+
+    /// Exposé dans la section `runtime` des détails du projet.
+    #[serde(skip_serializing)]
+    pub container_port: i32,
+
+    /// Vulnérabilités ignorées par le scan grype, exposées dans les détails du projet
+    /// et dans la liste d'administration.
+    #[sqlx(default)]
+    #[serde(skip_serializing)]
+    pub scan_ignore_cves: Vec<String>,
+
+    /// Commit dont est issue l'image déployée (projets `github`).
+    #[sqlx(default)]
+    pub source_commit_sha: Option<String>,
+
+    /// Tag ou SHA de commit auquel le projet est épinglé, à la place de la tête de sa branche.
+    #[sqlx(default)]
+    pub source_ref: Option<String>,
+
+    /// Profil de ressources du conteneur, exposé dans la section `runtime` des détails du projet.
+    #[sqlx(default)]
+    #[serde(skip_serializing)]
+    pub resource_profile: String,
+
+    /// Projet mis en pause : son conteneur a été supprimé, le volume, la base et l'image sont conservés.
+    #[sqlx(default)]
+    pub archived: bool,
+
+    /// Projet exclu de l'arrêt automatique des projets inactifs.
+    #[sqlx(default)]
+    pub keep_alive: bool,
+
+    /// Identifiants de la base liée injectés dans le conteneur (`HANGAR_DB_*`).
+    #[sqlx(default)]
+    pub inject_database_env: bool,
+
+    /// Surcharges de la vérification de santé, exposées dans la section `runtime` des détails du projet.
+    #[sqlx(default)]
+    #[serde(skip_serializing)]
+    pub health_check_max_attempts: Option<i32>,
+    #[sqlx(default)]
+    #[serde(skip_serializing)]
+    pub health_check_interval_ms: Option<i32>,
+
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
 }
 
+impl Project
+{
+    #[must_use]
+    pub fn container_command(&self) -> ContainerCommand
+    {
+        ContainerCommand
+        {
+            entrypoint: self.entrypoint.clone(),
+            command: self.command.clone(),
+        }
+    }
+
+    /// Port vers lequel Traefik redirige le trafic du projet.
+    #[must_use]
+    pub fn http_port(&self) -> u16
+    {
+        u16::try_from(self.container_port).unwrap_or(DEFAULT_CONTAINER_PORT)
+    }
+
+    /// Refuse les opérations sur le conteneur d'un projet archivé : il n'existe plus.
+    pub const fn ensure_not_archived(&self) -> Result<(), ProjectErrorCode>
+    {
+        if self.archived
+        {
+            return Err(ProjectErrorCode::ProjectArchived);
+        }
+        Ok(())
+    }
+}
+
+/// Image remplacée par la dernière mise à jour, cible d'un retour arrière.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct PreviousImage
+{
+    pub tag: String,
+    pub digest: String,
+}
+
+/// Surcharge de l'entrypoint et de la commande de l'image (`None` : valeur de l'image).
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ContainerCommand
+{
+    pub entrypoint: Option<Vec<String>>,
+    pub command: Option<Vec<String>>,
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct ProjectDetailsResponse 
 {
     #[serde(flatten)]
     pub project: Project,
-    pub participants: Vec<String>,
+    /// Nom affichable du propriétaire ; `owner` reste le login.
+    pub owner_display: String,
+    /// Participants ayant accepté l'invitation, ou ajoutés directement : la première page seulement,
+    /// la suite s'obtient par `GET /api/projects/{id}/participants?page=`.
+    pub participants: Vec<UserDisplay>,
+    pub participants_total: i64,
+    /// Invitations non expirées en attente de réponse (`PARTICIPANT_INVITATIONS_ENABLED`).
+    pub pending_participants: Vec<ProjectInvitation>,
+    /// Vulnérabilités ignorées par le scan de l'image.
+    pub scan_ignore_cves: Vec<String>,
     pub database: Option<DatabaseDetailsResponse>,
+    pub runtime: ProjectRuntime,
 }
 
+/// Résultat de la reprise d'un conteneur existant sous gestion.
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectImportReport
+{
+    pub project: Project,
+    pub container_name: String,
+    /// Éléments du conteneur d'origine qui n'ont pas pu être repris (montages, ports, variables...).
+    pub unmapped: Vec<String>,
+}
+
+/// Paramètres d'exécution du conteneur du projet.
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectRuntime
+{
+    /// Délai entre SIGTERM et SIGKILL lors de l'arrêt du conteneur.
+    pub stop_grace_seconds: i32,
+    pub max_stop_grace_seconds: i32,
+    #[serde(flatten)]
+    pub command: ContainerCommand,
+    pub timezone: Option<String>,
+    pub default_timezone: Option<String>,
+    /// Port HTTP écouté par le conteneur, cible de Traefik.
+    pub container_port: u16,
+    /// Surcharges de la vérification de santé (`None` : valeurs de l'instance).
+    pub health_check_max_attempts: Option<i32>,
+    pub health_check_interval_ms: Option<i32>,
+    /// Profil de ressources attribué par un administrateur, et les limites qu'il applique.
+    pub resource_profile: String,
+    pub resources: ResourceProfile,
+    /// Écart en millisecondes entre l'horloge de l'hôte Docker et celle de l'API
+    /// (`None` si le conteneur est arrêté). Un écart important casse la validation TLS de certaines applications.
+    pub clock_drift_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(into = "ProjectMetricsWire", from = "ProjectMetricsWire")]
 pub struct ProjectMetrics 
 {
-    pub cpu_usage: f64,
-    pub memory_usage: f64,
-    pub memory_limit: f64,
+    pub cpu_usage_percent: f64,
+    pub memory_usage_bytes: MemoryBytes,
+    pub memory_limit_bytes: MemoryBytes,
+    /// Compteurs cumulés depuis le démarrage du conteneur, toutes interfaces confondues.
+    pub network_rx_bytes: u64,
+    pub network_tx_bytes: u64,
+    /// Compteurs cumulés depuis le démarrage du conteneur, tous périphériques blocs confondus.
+    pub block_read_bytes: u64,
+    pub block_write_bytes: u64,
+}
+
+/// Forme JSON de [`ProjectMetrics`] : les anciens noms sans unité (`cpu_usage`, `memory_usage`,
+/// `memory_limit`) sont encore émis pour une version, puis seront retirés.
+#[derive(Serialize, Deserialize)]
+struct ProjectMetricsWire
+{
+    #[serde(default)]
+    cpu_usage_percent: Option<f64>,
+    #[serde(default)]
+    memory_usage_bytes: Option<MemoryBytes>,
+    #[serde(default)]
+    memory_limit_bytes: Option<MemoryBytes>,
+    #[serde(default)]
+    network_rx_bytes: u64,
+    #[serde(default)]
+    network_tx_bytes: u64,
+    #[serde(default)]
+    block_read_bytes: u64,
+    #[serde(default)]
+    block_write_bytes: u64,
+
+    #[serde(default)]
+    cpu_usage: Option<f64>,
+    #[serde(default)]
+    memory_usage: Option<f64>,
+    #[serde(default)]
+    memory_limit: Option<f64>,
+}
+
+impl From<ProjectMetrics> for ProjectMetricsWire
+{
+    fn from(metrics: ProjectMetrics) -> Self
+    {
+        Self
+        {
+            cpu_usage_percent: Some(metrics.cpu_usage_percent),
+            memory_usage_bytes: Some(metrics.memory_usage_bytes),
+            memory_limit_bytes: Some(metrics.memory_limit_bytes),
+            network_rx_bytes: metrics.network_rx_bytes,
+            network_tx_bytes: metrics.network_tx_bytes,
+            block_read_bytes: metrics.block_read_bytes,
+            block_write_bytes: metrics.block_write_bytes,
+            cpu_usage: Some(metrics.cpu_usage_percent),
+            memory_usage: Some(metrics.memory_usage_bytes.get() as f64),
+            memory_limit: Some(metrics.memory_limit_bytes.get() as f64),
+        }
+    }
+}
+
+impl From<ProjectMetricsWire> for ProjectMetrics
+{
+    fn from(wire: ProjectMetricsWire) -> Self
+    {
+        // Les anciens champs étaient déjà en octets.
+        let legacy_bytes = |value: Option<f64>| MemoryBytes::new(value.unwrap_or_default() as u64);
+
+        Self
+        {
+            cpu_usage_percent: wire.cpu_usage_percent.or(wire.cpu_usage).unwrap_or_default(),
+            memory_usage_bytes: wire.memory_usage_bytes.unwrap_or_else(|| legacy_bytes(wire.memory_usage)),
+            memory_limit_bytes: wire.memory_limit_bytes.unwrap_or_else(|| legacy_bytes(wire.memory_limit)),
+            network_rx_bytes: wire.network_rx_bytes,
+            network_tx_bytes: wire.network_tx_bytes,
+            block_read_bytes: wire.block_read_bytes,
+            block_write_bytes: wire.block_write_bytes,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -67,6 +300,20 @@ pub struct GlobalMetrics
     pub running_containers: u64,
     pub total_cpu_usage: f64,
     pub total_memory_usage_mb: f64,
+    pub total_network_rx_bytes: u64,
+    pub total_network_tx_bytes: u64,
+}
+
+/// Moyenne des échantillons de la plateforme sur un pas de l'historique, commençant à `timestamp`.
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct MetricsHistoryPoint
+{
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub running_containers: f64,
+    pub total_cpu_usage: f64,
+    pub total_memory_usage_mb: f64,
+    pub total_projects: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -74,6 +321,56 @@ pub struct DownProjectInfo
 {
     #[serde(flatten)]
     pub project: Project,
-    pub stopped_at: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub stopped_at: OffsetDateTime,
     pub downtime_seconds: i64,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_serialize_new_and_legacy_fields()
+    {
+        let metrics = ProjectMetrics
+        {
+            cpu_usage_percent: 12.5,
+            memory_usage_bytes: MemoryBytes::new(1024),
+            memory_limit_bytes: MemoryBytes::new(4096),
+            network_rx_bytes: 10,
+            network_tx_bytes: 20,
+            block_read_bytes: 30,
+            block_write_bytes: 40,
+        };
+        let json = serde_json::to_value(&metrics).unwrap();
+
+        assert_eq!(json["memory_usage_bytes"], 1024);
+        assert_eq!(json["memory_limit_bytes"], 4096);
+        assert_eq!(json["cpu_usage_percent"], 12.5);
+        assert_eq!(json["memory_usage"], 1024.0);
+        assert_eq!(json["memory_limit"], 4096.0);
+        assert_eq!(json["cpu_usage"], 12.5);
+        assert_eq!(json["network_rx_bytes"], 10);
+        assert_eq!(json["block_write_bytes"], 40);
+
+        assert_eq!(serde_json::from_value::<ProjectMetrics>(json).unwrap(), metrics);
+    }
+
+    #[test]
+    fn test_metrics_read_legacy_fields()
+    {
+        let metrics: ProjectMetrics = serde_json::from_str(r#"{"cpu_usage": 3.0, "memory_usage": 2048.0, "memory_limit": 8192.0}"#).unwrap();
+
+        assert_eq!(metrics, ProjectMetrics
+        {
+            cpu_usage_percent: 3.0,
+            memory_usage_bytes: MemoryBytes::new(2048),
+            memory_limit_bytes: MemoryBytes::new(8192),
+            network_rx_bytes: 0,
+            network_tx_bytes: 0,
+            block_read_bytes: 0,
+            block_write_bytes: 0,
+        });
+    }
+}
@@ -0,0 +1,634 @@
+//! Corps de réponse des handlers de projets, de bases de données et d'administration.
+//!
+//! Le format JSON est figé par les tests de ce module : un changement de forme doit y être visible.
+
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::authz::ProjectRole;
+use crate::sse::types::SseEvent;
+use crate::model::
+{
+    cleanup::{OwnerlessProject, ProjectCleanup},
+    database::{DatabaseBackup, DatabaseDetailsResponse, DatabaseLimits, DatabaseSession},
+    docker::{ContainerInspectSummary, OrphanedResources, TraefikRoute},
+    domain::{ProjectDomain, ProjectDomainResponse},
+    label_sync::LabelResyncResult,
+    project::{DownProjectInfo, MetricsHistoryPoint, Project},
+    template::TemplateResponse,
+    timeline::TimelineEntry,
+    webhook::{ProjectWebhook, WebhookDelivery},
+    user::UserDisplay,
+};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseStatus
+{
+    Success,
+    /// La requête était valide mais ne changeait rien.
+    NoChange,
+}
+
+/// Réponse d'une action sans autre contenu : `{"status": "success", "message": "..."}`.
+#[derive(Debug, Serialize, Clone)]
+pub struct ActionResponse
+{
+    pub status: ResponseStatus,
+    pub message: String,
+}
+
+impl ActionResponse
+{
+    #[must_use]
+    pub fn success(message: impl Into<String>) -> Self
+    {
+        Self { status: ResponseStatus::Success, message: message.into() }
+    }
+
+    #[must_use]
+    pub fn no_change(message: impl Into<String>) -> Self
+    {
+        Self { status: ResponseStatus::NoChange, message: message.into() }
+    }
+}
+
+/// Page de l'historique des déploiements, d'un projet ou de toute la plateforme.
+#[derive(Debug, Serialize, Clone)]
+pub struct DeploymentPage<T>
+{
+    pub deployments: Vec<T>,
+    pub page: u32,
+    pub per_page: u32,
+    /// Indique s'il existe une page suivante.
+    pub has_more: bool,
+}
+
+/// Page de la chronologie d'un projet ; `next_cursor` est absent sur la dernière page.
+#[derive(Debug, Serialize, Clone)]
+pub struct TimelineListResponse
+{
+    pub entries: Vec<TimelineEntry>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub to: OffsetDateTime,
+    pub next_cursor: Option<String>,
+}
+
+/// Catalogue des modèles, avec les champs à renseigner pour chacun.
+#[derive(Debug, Serialize, Clone)]
+pub struct TemplateListResponse
+{
+    pub templates: Vec<TemplateResponse>,
+}
+
+/// Webhooks d'un projet, sans leur secret.
+#[derive(Debug, Serialize, Clone)]
+pub struct WebhookListResponse
+{
+    pub webhooks: Vec<ProjectWebhook>,
+}
+
+/// Dernières tentatives de livraison d'un webhook.
+#[derive(Debug, Serialize, Clone)]
+pub struct WebhookDeliveryListResponse
+{
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+/// Domaines personnalisés d'un projet, avec les enregistrements DNS attendus.
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectDomainListResponse
+{
+    pub domains: Vec<ProjectDomainResponse>,
+}
+
+/// Domaine vérifié ou supprimé ; `project_redeployed` indique si le conteneur a été recréé pour
+/// ajouter ou retirer sa règle Traefik.
+#[derive(Debug, Serialize, Clone)]
+pub struct DomainChangeResponse
+{
+    #[serde(flatten)]
+    pub domain: ProjectDomain,
+    pub project_redeployed: bool,
+}
+
+/// Projet créé, avec ses participants au même niveau que ses champs.
+#[derive(Debug, Serialize, Clone)]
+pub struct DeployedProject
+{
+    #[serde(flatten)]
+    pub project: Project,
+    pub participants: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DeployResponse
+{
+    pub project: DeployedProject,
+}
+
+/// Projet recréé à partir d'une archive, avec ce qui n'a pas pu être restauré.
+#[derive(Debug, Serialize, Clone)]
+pub struct ArchiveImportResponse
+{
+    pub project: DeployedProject,
+    pub database_restored: bool,
+    pub volume_restored: bool,
+    /// Variables exportées sans leur valeur, à redéfinir.
+    pub missing_env_vars: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Un projet sous la clé `project`.
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectResponse<T>
+{
+    pub project: T,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectListResponse
+{
+    pub projects: Vec<Project>,
+}
+
+/// Projet possédé par l'appelant ou auquel il participe.
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectMembership
+{
+    #[serde(flatten)]
+    pub project: Project,
+    pub role: ProjectRole,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MembershipsResponse
+{
+    pub memberships: Vec<ProjectMembership>,
+}
+
+/// Une page des participants d'un projet, par ordre de login.
+#[derive(Debug, Serialize, Clone)]
+pub struct ParticipantsPageResponse
+{
+    pub participants: Vec<UserDisplay>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: i64,
+}
+
+/// Projet de la liste d'administration, avec les noms affichables de ses membres.
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectWithMembers
+{
+    #[serde(flatten)]
+    pub project: Project,
+    pub owner_display: String,
+    pub participants: Vec<UserDisplay>,
+    /// Vulnérabilités ignorées par le scan de l'image du projet.
+    pub scan_ignore_cves: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AdminProjectListResponse
+{
+    pub projects: Vec<ProjectWithMembers>,
+    /// Projets mis en pause par leur owner, sans conteneur.
+    pub archived_projects: Vec<ProjectWithMembers>,
+}
+
+/// Résultat d'une vérification de déploiement à blanc.
+#[derive(Debug, Serialize, Clone)]
+pub struct DeploymentCheckResult
+{
+    pub check: &'static str,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DryRunResponse
+{
+    pub valid: bool,
+    pub project_name: String,
+    pub container_name: String,
+    pub participants: Vec<String>,
+    pub checks: Vec<DeploymentCheckResult>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SelftestOutcome
+{
+    Passed,
+    Failed,
+    /// Non exécutée après l'échec d'une étape dont elle dépend.
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SelftestStep
+{
+    pub step: &'static str,
+    pub outcome: SelftestOutcome,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Rapport du selftest d'administration, étape par étape.
+#[derive(Debug, Serialize, Clone)]
+pub struct SelftestResponse
+{
+    pub passed: bool,
+    pub image: String,
+    pub duration_ms: u64,
+    pub steps: Vec<SelftestStep>,
+}
+
+/// Logs au format texte (`?format=text`).
+#[derive(Debug, Serialize, Clone)]
+pub struct TextLogsResponse
+{
+    pub logs: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StopGraceResponse
+{
+    pub stop_grace_seconds: i32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MissedEventsResponse
+{
+    pub events: Vec<SseEvent>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KeepAliveResponse
+{
+    pub keep_alive: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HealthCheckResponse
+{
+    pub health_check_max_attempts: Option<i32>,
+    pub health_check_interval_ms: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScanIgnoresResponse
+{
+    pub scan_ignore_cves: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ContainerResponse
+{
+    pub container: ContainerInspectSummary,
+}
+
+/// Identifiants d'une base créée : le mot de passe n'est renvoyé qu'à cette occasion.
+#[derive(Debug, Serialize, Clone)]
+pub struct CreatedDatabase
+{
+    pub id: i32,
+    pub database_name: String,
+    pub username: String,
+    pub password: String,
+    pub host: String,
+    pub port: u16,
+    pub limits: DatabaseLimits,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DatabaseCreatedResponse
+{
+    pub message: String,
+    pub database: CreatedDatabase,
+}
+
+/// Nouveau mot de passe d'une base : il n'est renvoyé qu'à cette occasion.
+#[derive(Debug, Serialize, Clone)]
+pub struct DatabasePasswordRotatedResponse
+{
+    pub message: String,
+    pub password: String,
+    /// Le conteneur du projet lié, qui reçoit les identifiants (`HANGAR_DB_*`), a été recréé avec le nouveau mot de passe.
+    pub project_redeployed: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DatabaseResponse
+{
+    pub database: DatabaseDetailsResponse,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DatabaseSessionsResponse
+{
+    pub sessions: Vec<DatabaseSession>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DatabaseBackupsResponse
+{
+    pub backups: Vec<DatabaseBackup>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DatabaseLimitsResponse
+{
+    pub database_id: i32,
+    pub limits: DatabaseLimits,
+}
+
+/// Instructions MariaDB qu'exécuterait une opération d'administration en `dry_run`, mots de passe masqués.
+#[derive(Debug, Serialize, Clone)]
+pub struct DatabaseDryRunResponse
+{
+    pub dry_run: bool,
+    pub statements: Vec<String>,
+}
+
+/// Conteneur de la plateforme sans label `hangar.project-id`, avec le projet correspondant s'il existe.
+#[derive(Debug, Serialize, Clone)]
+pub struct UnlabeledContainer
+{
+    pub container_name: String,
+    pub project_id: Option<i32>,
+    pub project_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UnlabeledContainersResponse
+{
+    pub containers: Vec<UnlabeledContainer>,
+}
+
+/// Routeurs Traefik déclarés par les conteneurs ; `conflicts` liste ceux déclarés par plusieurs projets.
+#[derive(Debug, Serialize, Clone)]
+pub struct TraefikRoutesResponse
+{
+    pub routes: Vec<TraefikRoute>,
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MetricsHistoryResponse
+{
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub to: OffsetDateTime,
+    pub step_seconds: i64,
+    pub points: Vec<MetricsHistoryPoint>,
+}
+
+/// Résultat de la resynchronisation des labels : aucun conteneur n'est recréé en `dry_run`.
+#[derive(Debug, Serialize, Clone)]
+pub struct LabelResyncResponse
+{
+    pub dry_run: bool,
+    pub projects: Vec<LabelResyncResult>,
+}
+
+/// Réponse `202` au lancement d'une resynchronisation des labels de tous les projets ; la progression
+/// arrive ensuite par les événements SSE admin portant le même `resync_id`.
+#[derive(Debug, Serialize, Clone)]
+pub struct LabelResyncStartedResponse
+{
+    pub resync_id: String,
+    pub projects: usize,
+}
+
+/// Résultat de `DELETE /api/admin/orphans` ; `removed` et `failed` restent vides en `dry_run`.
+#[derive(Debug, Serialize, Clone)]
+pub struct OrphanCleanupResponse
+{
+    pub dry_run: bool,
+    pub orphans: OrphanedResources,
+    pub removed: OrphanedResources,
+    pub failed: OrphanedResources,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DownProjectsResponse
+{
+    pub down_projects: Vec<DownProjectInfo>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OwnerlessProjectsResponse
+{
+    pub inactivity_days: i32,
+    pub projects: Vec<OwnerlessProject>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CleanupFlaggedResponse
+{
+    pub cleanup: ProjectCleanup,
+    /// Participants prévenus du signalement.
+    pub notified: Vec<String>,
+}
+
+/// Filtre de logs actif et filtre du démarrage.
+#[derive(Debug, Serialize, Clone)]
+pub struct LogFilterResponse
+{
+    pub filter: String,
+    pub startup_filter: String,
+    /// Retour automatique au filtre du démarrage ; `None` si celui-ci est actif.
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revert_at: Option<OffsetDateTime>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{model::database::DatabaseLimits, test_support};
+
+    #[test]
+    fn test_action_responses()
+    {
+        assert_eq!(
+            serde_json::to_value(ActionResponse::success("Participant added.")).unwrap(),
+            json!({ "status": "success", "message": "Participant added." })
+        );
+        assert_eq!(
+            serde_json::to_value(ActionResponse::no_change("Nothing to update.")).unwrap(),
+            json!({ "status": "no_change", "message": "Nothing to update." })
+        );
+    }
+
+    #[test]
+    fn test_deploy_response_nests_participants_in_project()
+    {
+        let response = DeployResponse { project: DeployedProject { project: test_support::project(1, "alice"), participants: vec!["bob".into()] } };
+
+        let mut expected = serde_json::to_value(test_support::project(1, "alice")).unwrap();
+        expected["participants"] = json!(["bob"]);
+        assert_eq!(serde_json::to_value(response).unwrap(), json!({ "project": expected }));
+    }
+
+    #[test]
+    fn test_memberships_flatten_project_with_role()
+    {
+        let response = MembershipsResponse { memberships: vec![ProjectMembership { project: test_support::project(1, "alice"), role: ProjectRole::Participant }] };
+
+        let json = serde_json::to_value(response).unwrap();
+        assert_eq!(json["memberships"][0]["owner"], "alice");
+        assert_eq!(json["memberships"][0]["role"], "participant");
+    }
+
+    #[test]
+    fn test_admin_project_list_includes_display_names()
+    {
+        let response = AdminProjectListResponse
+        {
+            projects: vec![ProjectWithMembers
+            {
+                project: test_support::project(1, "alice"),
+                owner_display: "Alice Martin".into(),
+                participants: vec![UserDisplay { login: "bob42".into(), name: "bob42".into() }],
+                scan_ignore_cves: vec!["CVE-2024-1234".into()],
+            }],
+            archived_projects: Vec::new(),
+        };
+
+        let json = serde_json::to_value(response).unwrap();
+        assert_eq!(json["projects"][0]["owner"], "alice");
+        assert_eq!(json["projects"][0]["owner_display"], "Alice Martin");
+        assert_eq!(json["projects"][0]["participants"], json!([{ "login": "bob42", "name": "bob42" }]));
+        assert_eq!(json["projects"][0]["scan_ignore_cves"], json!(["CVE-2024-1234"]));
+        assert_eq!(json["archived_projects"], json!([]));
+    }
+
+    #[test]
+    fn test_dry_run_response()
+    {
+        let response = DryRunResponse
+        {
+            valid: false,
+            project_name: "demo".into(),
+            container_name: "hangar-demo".into(),
+            participants: Vec::new(),
+            checks: vec![
+                DeploymentCheckResult { check: "name", passed: true, error_code: None, message: None },
+                DeploymentCheckResult { check: "quota", passed: false, error_code: Some("OWNER_ALREADY_EXISTS"), message: Some("taken".into()) },
+            ],
+        };
+
+        assert_eq!(serde_json::to_value(response).unwrap(), json!({
+            "valid": false,
+            "project_name": "demo",
+            "container_name": "hangar-demo",
+            "participants": [],
+            "checks": [
+                { "check": "name", "passed": true },
+                { "check": "quota", "passed": false, "error_code": "OWNER_ALREADY_EXISTS", "message": "taken" },
+            ],
+        }));
+    }
+
+    #[test]
+    fn test_wrapped_responses()
+    {
+        assert_eq!(serde_json::to_value(ProjectListResponse { projects: Vec::new() }).unwrap(), json!({ "projects": [] }));
+        assert_eq!(serde_json::to_value(TextLogsResponse { logs: "line".into() }).unwrap(), json!({ "logs": "line" }));
+        assert_eq!(serde_json::to_value(StopGraceResponse { stop_grace_seconds: 30 }).unwrap(), json!({ "stop_grace_seconds": 30 }));
+        assert_eq!(serde_json::to_value(MissedEventsResponse { events: Vec::new() }).unwrap(), json!({ "events": [] }));
+        assert_eq!(serde_json::to_value(DatabaseSessionsResponse { sessions: Vec::new() }).unwrap(), json!({ "sessions": [] }));
+        assert_eq!(serde_json::to_value(DatabaseBackupsResponse { backups: Vec::new() }).unwrap(), json!({ "backups": [] }));
+        assert_eq!(serde_json::to_value(DownProjectsResponse { down_projects: Vec::new() }).unwrap(), json!({ "down_projects": [] }));
+        assert_eq!(
+            serde_json::to_value(OwnerlessProjectsResponse { inactivity_days: 365, projects: Vec::new() }).unwrap(),
+            json!({ "inactivity_days": 365, "projects": [] })
+        );
+        assert_eq!(
+            serde_json::to_value(UnlabeledContainersResponse
+            {
+                containers: vec![UnlabeledContainer { container_name: "hangar-demo".into(), project_id: None, project_name: None }],
+            }).unwrap(),
+            json!({ "containers": [{ "container_name": "hangar-demo", "project_id": null, "project_name": null }] })
+        );
+    }
+
+    #[test]
+    fn test_database_responses()
+    {
+        let limits = DatabaseLimits { max_user_connections: 10, max_queries_per_hour: 1000 };
+
+        assert_eq!(
+            serde_json::to_value(DatabaseLimitsResponse { database_id: 3, limits }).unwrap(),
+            json!({ "database_id": 3, "limits": { "max_user_connections": 10, "max_queries_per_hour": 1000 } })
+        );
+
+        assert_eq!(
+            serde_json::to_value(DatabaseDryRunResponse { dry_run: true, statements: vec!["FLUSH PRIVILEGES".into()] }).unwrap(),
+            json!({ "dry_run": true, "statements": ["FLUSH PRIVILEGES"] })
+        );
+
+        let created = DatabaseCreatedResponse
+        {
+            message: "Database created successfully.".into(),
+            database: CreatedDatabase
+            {
+                id: 3,
+                database_name: "db".into(),
+                username: "user".into(),
+                password: "secret".into(),
+                host: "db.example.com".into(),
+                port: 3306,
+                limits,
+            },
+        };
+        assert_eq!(serde_json::to_value(created).unwrap(), json!({
+            "message": "Database created successfully.",
+            "database": {
+                "id": 3,
+                "database_name": "db",
+                "username": "user",
+                "password": "secret",
+                "host": "db.example.com",
+                "port": 3306,
+                "limits": { "max_user_connections": 10, "max_queries_per_hour": 1000 },
+            },
+        }));
+    }
+
+    #[test]
+    fn test_selftest_response_omits_missing_messages()
+    {
+        let response = SelftestResponse
+        {
+            passed: false,
+            image: "traefik/whoami:latest".into(),
+            duration_ms: 1200,
+            steps: vec![
+                SelftestStep { step: "deploy", outcome: SelftestOutcome::Failed, duration_ms: 1200, message: Some("pull failed".into()) },
+                SelftestStep { step: "purge", outcome: SelftestOutcome::Skipped, duration_ms: 0, message: None },
+            ],
+        };
+
+        assert_eq!(serde_json::to_value(response).unwrap(), json!({
+            "passed": false,
+            "image": "traefik/whoami:latest",
+            "duration_ms": 1200,
+            "steps": [
+                { "step": "deploy", "outcome": "failed", "duration_ms": 1200, "message": "pull failed" },
+                { "step": "purge", "outcome": "skipped", "duration_ms": 0 },
+            ],
+        }));
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+/// Sévérité d'une vulnérabilité, de la moins à la plus grave.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum VulnerabilitySeverity
+{
+    Unknown,
+    Negligible,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl VulnerabilitySeverity
+{
+    /// Sévérité telle qu'écrite par grype (`Critical`, `High`...), sans tenir compte de la casse.
+    #[must_use]
+    pub fn parse(value: &str) -> Self
+    {
+        match value.to_ascii_lowercase().as_str()
+        {
+            "critical" => Self::Critical,
+            "high" => Self::High,
+            "medium" => Self::Medium,
+            "low" => Self::Low,
+            "negligible" => Self::Negligible,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Vulnérabilité trouvée par grype dans un paquet de l'image.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Vulnerability
+{
+    /// Identifiant de la vulnérabilité (`CVE-...`, `GHSA-...`).
+    pub id: String,
+    pub severity: VulnerabilitySeverity,
+    pub package: String,
+    pub version: String,
+    /// Versions du paquet corrigeant la vulnérabilité.
+    pub fixed_in: Vec<String>,
+}
+
+/// Résultat d'un scan refusé : les vulnérabilités, des plus graves aux moins graves,
+/// ou la sortie brute de grype si elle n'a pas pu être lue.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ScanFindings
+{
+    Vulnerabilities(Vec<Vulnerability>),
+    Raw(String),
+}
+
+impl ScanFindings
+{
+    /// Trie les vulnérabilités par sévérité décroissante, puis par identifiant.
+    #[must_use]
+    pub fn from_vulnerabilities(mut vulnerabilities: Vec<Vulnerability>) -> Self
+    {
+        vulnerabilities.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.id.cmp(&b.id)));
+        Self::Vulnerabilities(vulnerabilities)
+    }
+
+    /// Les `limit` vulnérabilités les plus graves ; aucune pour une sortie brute.
+    #[must_use]
+    pub fn top(&self, limit: usize) -> Vec<Vulnerability>
+    {
+        match self
+        {
+            Self::Vulnerabilities(vulnerabilities) => vulnerabilities.iter().take(limit).cloned().collect(),
+            Self::Raw(_) => Vec::new(),
+        }
+    }
+
+    /// Retire les vulnérabilités ignorées par le projet (identifiants comparés sans tenir compte de la casse).
+    #[must_use]
+    pub fn without_ignored(self, ignored: &[String]) -> Self
+    {
+        match self
+        {
+            Self::Vulnerabilities(vulnerabilities) => Self::Vulnerabilities(vulnerabilities.into_iter()
+                .filter(|v| !ignored.iter().any(|id| id.eq_ignore_ascii_case(&v.id)))
+                .collect()),
+            raw @ Self::Raw(_) => raw,
+        }
+    }
+
+    /// Le scan doit-il être refusé au seuil `fail_on` ? Une sortie brute, illisible, est toujours bloquante.
+    #[must_use]
+    pub fn blocks(&self, fail_on: VulnerabilitySeverity) -> bool
+    {
+        match self
+        {
+            Self::Vulnerabilities(vulnerabilities) => vulnerabilities.iter().any(|v| v.severity >= fail_on),
+            Self::Raw(_) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vulnerability(id: &str, severity: VulnerabilitySeverity) -> Vulnerability
+    {
+        Vulnerability { id: id.to_string(), severity, package: "openssl".to_string(), version: "1.1.1".to_string(), fixed_in: Vec::new() }
+    }
+
+    #[test]
+    fn test_findings_are_sorted_by_severity()
+    {
+        let findings = ScanFindings::from_vulnerabilities(vec![
+            vulnerability("CVE-3", VulnerabilitySeverity::Low),
+            vulnerability("CVE-2", VulnerabilitySeverity::Critical),
+            vulnerability("CVE-1", VulnerabilitySeverity::Critical),
+            vulnerability("CVE-4", VulnerabilitySeverity::Unknown),
+        ]);
+
+        let ids: Vec<String> = findings.top(3).into_iter().map(|v| v.id).collect();
+        assert_eq!(ids, vec!["CVE-1", "CVE-2", "CVE-3"]);
+        assert!(ScanFindings::Raw("table".to_string()).top(10).is_empty());
+    }
+
+    #[test]
+    fn test_ignored_vulnerabilities_do_not_block()
+    {
+        let findings = ScanFindings::from_vulnerabilities(vec![
+            vulnerability("CVE-1", VulnerabilitySeverity::Critical),
+            vulnerability("CVE-2", VulnerabilitySeverity::Low),
+        ]);
+        assert!(findings.blocks(VulnerabilitySeverity::High));
+
+        let remaining = findings.without_ignored(&["cve-1".to_string()]);
+        assert_eq!(remaining.top(10), vec![vulnerability("CVE-2", VulnerabilitySeverity::Low)]);
+        assert!(!remaining.blocks(VulnerabilitySeverity::High));
+        assert!(remaining.blocks(VulnerabilitySeverity::Low));
+
+        assert!(ScanFindings::Raw("error".to_string()).without_ignored(&["CVE-1".to_string()]).blocks(VulnerabilitySeverity::Critical));
+    }
+
+    #[test]
+    fn test_findings_serialization()
+    {
+        let findings = ScanFindings::from_vulnerabilities(vec![vulnerability("CVE-1", VulnerabilitySeverity::High)]);
+        let json = serde_json::to_value(&findings).unwrap();
+        assert_eq!(json[0]["severity"], "high");
+        assert_eq!(json[0]["fixed_in"], serde_json::json!([]));
+
+        assert_eq!(serde_json::to_value(ScanFindings::Raw("report".to_string())).unwrap(), "report");
+    }
+}
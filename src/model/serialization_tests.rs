@@ -0,0 +1,318 @@
+//! Format des horodatages dans les réponses : RFC 3339, en UTC (`Z`), relisible à l'identique.
+
+use serde::Serialize;
+use serde_json::Value;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::handlers::health::{ComponentHealth, HealthCheckResponse, HealthComponents, HealthStatus};
+use crate::model::
+{
+    api_key::ProjectApiKey,
+    cleanup::{OwnerlessProject, ProjectCleanup},
+    database::Database,
+    domain::ProjectDomain,
+    invitation::ProjectInvitation,
+    deployment::{AdminDeployment, Deployment, DeploymentKind, DeploymentResponse, DeploymentStatus, ResumeStage},
+    job::{JobRun, JobRunStatus, ProjectJob},
+    notification::NotificationSettings,
+    offboarding::{FootprintDatabase, OffboardingConfirmation, UserFootprint},
+    project::{DownProjectInfo, Project},
+    response::{DeploymentPage, DomainChangeResponse, TimelineListResponse},
+    timeline::{TimelineEntry, TimelineEntryType},
+    share_link::{ProjectShareLink, PublicProjectSnapshot},
+    webhook::{ProjectWebhook, WebhookDelivery},
+};
+use crate::sse::types::{ContainerStatus, DownProjectsEvent, SseEvent, SystemEvent};
+use crate::test_support;
+
+fn instant() -> OffsetDateTime
+{
+    OffsetDateTime::from_unix_timestamp_nanos(1_760_601_600_123_456_789).unwrap()
+}
+
+/// Vérifie chaque champ désigné par un pointeur JSON.
+fn assert_timestamps(value: &impl Serialize, pointers: &[&str])
+{
+    let json = serde_json::to_value(value).unwrap();
+    for pointer in pointers
+    {
+        let text = json.pointer(pointer).and_then(Value::as_str)
+            .unwrap_or_else(|| panic!("{pointer} is not a string in {json}"));
+        assert!(text.ends_with('Z'), "{pointer} is not in UTC: {text}");
+        assert_eq!(OffsetDateTime::parse(text, &Rfc3339).unwrap(), instant(), "{pointer} does not round-trip");
+    }
+}
+
+fn deployment() -> Deployment
+{
+    Deployment
+    {
+        id: 1,
+        project_id: 1,
+        kind: DeploymentKind::Rebuild,
+        status: DeploymentStatus::Succeeded,
+        triggered_by: "alice".into(),
+        source_type: None,
+        requested_image: None,
+        retry_of: None,
+        failed_stage: None,
+        image_tag: None,
+        image_digest: None,
+        commit_sha: None,
+        workspace_path: None,
+        image_scanned: true,
+        artifacts_expire_at: Some(instant()),
+        created_at: instant(),
+        finished_at: Some(instant()),
+    }
+}
+
+fn project() -> Project
+{
+    Project { created_at: instant(), ..test_support::project(1, "alice") }
+}
+
+#[test]
+fn test_project_responses()
+{
+    assert_timestamps(&project(), &["/created_at"]);
+
+    let down = DownProjectInfo { project: project(), stopped_at: instant(), downtime_seconds: 60 };
+    assert_timestamps(&down, &["/created_at", "/stopped_at"]);
+}
+
+#[test]
+fn test_deployment_pages()
+{
+    let page = DeploymentPage
+    {
+        deployments: vec![DeploymentResponse { deployment: deployment(), resume_from: Some(ResumeStage::Deploy) }],
+        page: 2,
+        per_page: 20,
+        has_more: false,
+    };
+    assert_timestamps(&page, &["/deployments/0/created_at", "/deployments/0/finished_at"]);
+
+    let json = serde_json::to_value(&page).unwrap();
+    assert_eq!((&json["page"], &json["per_page"], &json["has_more"]), (&Value::from(2), &Value::from(20), &Value::from(false)));
+    assert_eq!(json["deployments"][0]["id"], 1);
+    assert_eq!(json["deployments"][0]["resume_from"], "deploy");
+
+    let page = DeploymentPage
+    {
+        deployments: vec![AdminDeployment { deployment: deployment(), project_name: "demo".into() }],
+        page: 1,
+        per_page: 20,
+        has_more: true,
+    };
+    assert_timestamps(&page, &["/deployments/0/created_at"]);
+
+    let json = serde_json::to_value(&page).unwrap();
+    assert_eq!(json["deployments"][0]["project_name"], "demo");
+    assert_eq!(json["deployments"][0]["status"], "succeeded");
+    assert_eq!(json["has_more"], true);
+}
+
+#[test]
+fn test_timeline_page()
+{
+    let page = TimelineListResponse
+    {
+        entries: vec![TimelineEntry { entry_type: TimelineEntryType::Deployment, id: 1, occurred_at: instant(), data: Value::Null }],
+        from: instant(),
+        to: instant(),
+        next_cursor: None,
+    };
+    assert_timestamps(&page, &["/entries/0/occurred_at", "/from", "/to"]);
+    assert_eq!(serde_json::to_value(&page).unwrap()["next_cursor"], Value::Null);
+}
+
+#[test]
+fn test_project_resources()
+{
+    let domain = ProjectDomain
+    {
+        id: 1,
+        project_id: 1,
+        fqdn: "demo.example.com".into(),
+        verification_token: "secret".into(),
+        verified: true,
+        verified_at: Some(instant()),
+        created_at: instant(),
+    };
+    let change = DomainChangeResponse { domain, project_redeployed: true };
+    assert_timestamps(&change, &["/verified_at", "/created_at"]);
+    let json = serde_json::to_value(&change).unwrap();
+    assert_eq!((&json["fqdn"], &json["verified"], &json["project_redeployed"]), (&Value::from("demo.example.com"), &Value::from(true), &Value::from(true)));
+    assert!(json.get("verification_token").is_none());
+
+    assert_timestamps(&ProjectApiKey
+    {
+        id: 1,
+        project_id: 1,
+        key_prefix: "hk_abc".into(),
+        key_hash: String::new(),
+        created_by: "alice".into(),
+        last_used_at: Some(instant()),
+        created_at: instant(),
+    }, &["/last_used_at", "/created_at"]);
+
+    assert_timestamps(&ProjectShareLink
+    {
+        id: 1,
+        project_id: 1,
+        jti: String::new(),
+        created_by: "alice".into(),
+        expires_at: instant(),
+        revoked_at: Some(instant()),
+        last_accessed_at: Some(instant()),
+        access_count: 1,
+        created_at: instant(),
+    }, &["/expires_at", "/revoked_at", "/last_accessed_at", "/created_at"]);
+
+    assert_timestamps(&ProjectInvitation
+    {
+        id: 1,
+        project_id: 1,
+        project_name: "demo".into(),
+        invitee: "bob".into(),
+        invited_by: "alice".into(),
+        expires_at: instant(),
+        created_at: instant(),
+    }, &["/expires_at", "/created_at"]);
+
+    assert_timestamps(&PublicProjectSnapshot
+    {
+        name: "demo".into(),
+        status: ContainerStatus::Running,
+        uptime_seconds: Some(1),
+        url: "https://demo.example.com".into(),
+        link_expires_at: instant(),
+    }, &["/link_expires_at"]);
+
+    assert_timestamps(&Database
+    {
+        id: 1,
+        owner_login: "alice".into(),
+        database_name: "db".into(),
+        username: "user".into(),
+        encrypted_password: String::new(),
+        project_id: Some(1),
+        max_user_connections: 10,
+        max_queries_per_hour: 1000,
+        created_at: instant(),
+    }, &["/created_at"]);
+
+    assert_timestamps(&deployment(), &["/artifacts_expire_at", "/created_at", "/finished_at"]);
+
+    assert_timestamps(&ProjectJob
+    {
+        id: 1,
+        project_id: 1,
+        schedule: "0 * * * *".into(),
+        command: vec!["true".into()],
+        enabled: true,
+        last_run_at: Some(instant()),
+        created_at: instant(),
+    }, &["/last_run_at", "/created_at"]);
+
+    assert_timestamps(&JobRun
+    {
+        id: 1,
+        job_id: 1,
+        status: JobRunStatus::Succeeded,
+        exit_code: Some(0),
+        output: None,
+        started_at: instant(),
+        finished_at: Some(instant()),
+    }, &["/started_at", "/finished_at"]);
+
+    let webhook = ProjectWebhook
+    {
+        id: 1,
+        project_id: 1,
+        url: "https://hooks.example.com/hangar".into(),
+        encrypted_secret: "c2VjcmV0".into(),
+        events: vec!["deployment_failed".into()],
+        created_by: "alice".into(),
+        created_at: instant(),
+    };
+    assert_timestamps(&webhook, &["/created_at"]);
+    assert!(serde_json::to_value(&webhook).unwrap().get("encrypted_secret").is_none());
+
+    assert_timestamps(&WebhookDelivery
+    {
+        id: 1,
+        webhook_id: 1,
+        event_type: "deployment_failed".into(),
+        attempt: 1,
+        status_code: Some(500),
+        success: false,
+        error: Some("unexpected status 500".into()),
+        attempted_at: instant(),
+    }, &["/attempted_at"]);
+}
+
+#[test]
+fn test_admin_responses()
+{
+    assert_timestamps(&ProjectCleanup
+    {
+        project_id: 1,
+        flagged_by: "admin".into(),
+        reason: None,
+        flagged_at: instant(),
+        archive_after: instant(),
+        archived_at: Some(instant()),
+    }, &["/flagged_at", "/archive_after", "/archived_at"]);
+
+    assert_timestamps(&OwnerlessProject
+    {
+        project_id: 1,
+        project_name: "demo".into(),
+        owner: "alice".into(),
+        owner_last_login_at: Some(instant()),
+        flagged_at: Some(instant()),
+        archive_after: Some(instant()),
+        archived_at: Some(instant()),
+    }, &["/owner_last_login_at", "/flagged_at", "/archive_after", "/archived_at"]);
+
+    assert_timestamps(&UserFootprint
+    {
+        login: "alice".into(),
+        name: None,
+        email: None,
+        last_login_at: Some(instant()),
+        owned_projects: vec![project()],
+        participations: Vec::new(),
+        database: Some(FootprintDatabase { id: 1, database_name: "db".into(), project_id: Some(1), created_at: instant() }),
+        api_keys: Vec::new(),
+        share_links: Vec::new(),
+        notifications: NotificationSettings::default(),
+        confirmation: OffboardingConfirmation { token: String::new(), expires_at: instant() },
+    }, &["/last_login_at", "/owned_projects/0/created_at", "/database/created_at", "/confirmation/expires_at"]);
+
+    let healthy = ComponentHealth { status: HealthStatus::Healthy, response_time_us: 1, details: None, error: None };
+    assert_timestamps(&HealthCheckResponse
+    {
+        status: HealthStatus::Healthy,
+        timestamp: instant(),
+        components: HealthComponents { postgres: healthy.clone(), mariadb: healthy.clone(), docker: healthy.clone(), sse: healthy },
+    }, &["/timestamp"]);
+}
+
+#[test]
+fn test_sse_events()
+{
+    let mut system = SystemEvent::info("hello".into());
+    system.timestamp = instant();
+    let event = SseEvent::System(system);
+    assert_timestamps(&event, &["/timestamp"]);
+
+    let parsed: SseEvent = serde_json::from_str(&serde_json::to_string(&event).unwrap()).unwrap();
+    assert_timestamps(&parsed, &["/timestamp"]);
+
+    let mut snapshot = DownProjectsEvent::snapshot(vec![DownProjectInfo { project: project(), stopped_at: instant(), downtime_seconds: 60 }]);
+    snapshot.timestamp = instant();
+    assert_timestamps(&snapshot, &["/timestamp", "/down_projects/0/stopped_at", "/down_projects/0/created_at"]);
+}
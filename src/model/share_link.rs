@@ -0,0 +1,53 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::sse::types::ContainerStatus;
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectShareLink
+{
+    pub id: i32,
+    pub project_id: i32,
+
+    #[serde(skip_serializing)]
+    pub jti: String,
+
+    pub created_by: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_accessed_at: Option<OffsetDateTime>,
+
+    pub access_count: i32,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Réponse à la création d'un lien : le jeton n'est renvoyé qu'une seule fois.
+#[derive(Debug, Serialize)]
+pub struct CreatedShareLink
+{
+    #[serde(flatten)]
+    pub link: ProjectShareLink,
+    pub token: String,
+}
+
+/// Vue publique d'un projet partagé. Ne contient volontairement ni variables d'environnement,
+/// ni logs, ni informations de base de données, ni participants.
+#[derive(Debug, Serialize)]
+pub struct PublicProjectSnapshot
+{
+    pub name: String,
+    pub status: ContainerStatus,
+    pub uptime_seconds: Option<i64>,
+    pub url: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub link_expires_at: OffsetDateTime,
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use time::OffsetDateTime;
+
+use crate::model::project::ProjectSourceType;
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct Template
+{
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub source_type: ProjectSourceType,
+    /// Image (`direct`) ou URL du dépôt (`github`).
+    pub source_url: String,
+    pub source_branch: Option<String>,
+    /// Les valeurs peuvent contenir des champs `{{NOM}}`, renseignés au déploiement.
+    pub default_env_vars: Json<HashMap<String, String>>,
+    pub create_database: bool,
+    pub persistent_volume_path: Option<String>,
+    pub container_port: Option<i32>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// Création ou remplacement d'un modèle par un administrateur.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TemplateInput
+{
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub source_type: ProjectSourceType,
+    pub source_url: String,
+    pub source_branch: Option<String>,
+    #[serde(default)]
+    pub default_env_vars: HashMap<String, String>,
+    #[serde(default)]
+    pub create_database: bool,
+    pub persistent_volume_path: Option<String>,
+    pub container_port: Option<u16>,
+}
+
+/// Modèle du catalogue, avec les champs à renseigner pour le déployer.
+#[derive(Debug, Serialize, Clone)]
+pub struct TemplateResponse
+{
+    #[serde(flatten)]
+    pub template: Template,
+    pub placeholders: Vec<String>,
+}
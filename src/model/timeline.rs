@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Historique d'origine d'une entrée de la chronologie d'un projet.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEntryType
+{
+    /// Déploiement (création, mise à jour, reconstruction, ...), daté de son début.
+    Deployment,
+    /// Démarrage ou arrêt du conteneur du projet.
+    Status,
+    /// Entrée du journal d'audit concernant le projet.
+    Audit,
+}
+
+impl TimelineEntryType
+{
+    pub const ALL: [Self; 3] = [Self::Deployment, Self::Status, Self::Audit];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::Deployment => "deployment",
+            Self::Status => "status",
+            Self::Audit => "audit",
+        }
+    }
+
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self>
+    {
+        Self::ALL.into_iter().find(|entry_type| entry_type.as_str() == value)
+    }
+}
+
+/// Entrée de la chronologie d'un projet ; le contenu de `data` dépend de `type`.
+///
+/// La même forme est renvoyée par `GET /api/projects/{id}/timeline` et diffusée sur le canal SSE du projet.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TimelineEntry
+{
+    #[serde(rename = "type")]
+    pub entry_type: TimelineEntryType,
+    /// Identifiant de la ligne d'origine, unique pour un type donné.
+    pub id: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub occurred_at: OffsetDateTime,
+    pub data: serde_json::Value,
+}
+
+impl TimelineEntry
+{
+    /// Position de l'entrée, à transmettre pour obtenir les entrées plus anciennes.
+    #[must_use]
+    pub const fn cursor(&self) -> TimelineCursor
+    {
+        TimelineCursor { occurred_at: self.occurred_at, entry_type: self.entry_type, id: self.id }
+    }
+}
+
+/// Position dans la chronologie, triée par date puis par type et identifiant pour départager les entrées simultanées.
+///
+/// Sérialisée en `{nanosecondes unix}_{type}_{id}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineCursor
+{
+    pub occurred_at: OffsetDateTime,
+    pub entry_type: TimelineEntryType,
+    pub id: i64,
+}
+
+impl fmt::Display for TimelineCursor
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}_{}_{}", self.occurred_at.unix_timestamp_nanos(), self.entry_type.as_str(), self.id)
+    }
+}
+
+impl FromStr for TimelineCursor
+{
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err>
+    {
+        let mut parts = value.splitn(3, '_');
+        let nanos: i128 = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let entry_type = TimelineEntryType::parse(parts.next().ok_or(())?).ok_or(())?;
+        let id = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+
+        Ok(Self
+        {
+            occurred_at: OffsetDateTime::from_unix_timestamp_nanos(nanos).map_err(|_| ())?,
+            entry_type,
+            id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip()
+    {
+        let cursor = TimelineCursor
+        {
+            occurred_at: OffsetDateTime::from_unix_timestamp_nanos(1_760_000_000_123_456_000).unwrap(),
+            entry_type: TimelineEntryType::Status,
+            id: 42,
+        };
+
+        assert_eq!(cursor.to_string(), "1760000000123456000_status_42");
+        assert_eq!(cursor.to_string().parse::<TimelineCursor>(), Ok(cursor));
+        assert!("1760000000123456000_unknown_42".parse::<TimelineCursor>().is_err());
+        assert!("not-a-cursor".parse::<TimelineCursor>().is_err());
+    }
+
+    #[test]
+    fn test_entry_serializes_its_type()
+    {
+        let entry = TimelineEntry
+        {
+            entry_type: TimelineEntryType::Deployment,
+            id: 7,
+            occurred_at: OffsetDateTime::UNIX_EPOCH,
+            data: serde_json::json!({ "status": "failed" }),
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+
+        assert_eq!(json["type"], "deployment");
+        assert_eq!(json["occurred_at"], "1970-01-01T00:00:00Z");
+        assert_eq!(json["data"]["status"], "failed");
+    }
+}
@@ -0,0 +1,77 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// Consommation d'un propriétaire sur la période d'un rapport, tous ses projets confondus.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct OwnerUsage
+{
+    pub owner: String,
+    /// Temps CPU consommé, en secondes de cœur.
+    pub cpu_seconds: f64,
+    /// Mémoire moyenne pendant que les conteneurs tournaient.
+    pub avg_memory_bytes: i64,
+    /// Temps cumulé pendant lequel les conteneurs étaient démarrés.
+    pub container_hours: f64,
+    /// Taille moyenne des volumes persistants relevée sur la période.
+    pub avg_volume_bytes: i64,
+    /// Taille moyenne de la base MariaDB relevée sur la période.
+    pub avg_database_bytes: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UsageReport
+{
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub to: OffsetDateTime,
+    pub owners: Vec<OwnerUsage>,
+}
+
+/// Agrégat des échantillons d'un propriétaire, avant l'ajout des heures de conteneur.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OwnerSamples
+{
+    pub owner: String,
+    pub cpu_seconds: f64,
+    pub avg_memory_bytes: i64,
+    pub avg_volume_bytes: i64,
+    pub avg_database_bytes: i64,
+}
+
+/// Transition démarré/arrêté du conteneur d'un projet.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StatusTransition
+{
+    pub project_id: i32,
+    pub owner: String,
+    pub is_running: bool,
+    pub occurred_at: OffsetDateTime,
+}
+
+/// Période de fonctionnement du backend, close à son dernier battement.
+#[derive(Debug, Clone, Copy, sqlx::FromRow)]
+pub struct BackendSession
+{
+    pub started_at: OffsetDateTime,
+    pub last_seen_at: OffsetDateTime,
+}
+
+/// Disponibilité d'un projet sur `[from, to)`. Les périodes où le backend était arrêté ne comptent
+/// ni comme disponibles ni comme indisponibles (`unknown_seconds`).
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ProjectUptime
+{
+    #[serde(with = "time::serde::rfc3339")]
+    pub from: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub to: OffsetDateTime,
+    /// Part du temps connu pendant laquelle le conteneur était démarré ; absente sans temps connu.
+    pub uptime_percent: Option<f64>,
+    pub up_seconds: i64,
+    pub down_seconds: i64,
+    pub unknown_seconds: i64,
+    /// Arrêts du conteneur ; une interruption du backend clôt l'arrêt en cours.
+    pub outages: u32,
+    pub longest_outage_seconds: i64,
+}
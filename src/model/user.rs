@@ -1,6 +1,17 @@
+use serde::Serialize;
+
 #[derive(Debug)]
 pub struct User {
     pub email: String,
     pub name: String,
     pub login: String,
 }
+
+/// Login et nom affichable d'un utilisateur. Le nom retombe sur le login
+/// pour un utilisateur qui ne s'est jamais connecté.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct UserDisplay
+{
+    pub login: String,
+    pub name: String,
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Événements transmis aux webhooks d'un projet.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent
+{
+    /// Déploiement suivi terminé avec succès.
+    DeploymentSucceeded,
+    /// Déploiement suivi en échec.
+    DeploymentFailed,
+    /// Arrêt inattendu du conteneur (code de sortie non nul ou manque de mémoire).
+    ContainerDied,
+}
+
+impl WebhookEvent
+{
+    pub const ALL: [Self; 3] = [Self::DeploymentSucceeded, Self::DeploymentFailed, Self::ContainerDied];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::DeploymentSucceeded => "deployment_succeeded",
+            Self::DeploymentFailed => "deployment_failed",
+            Self::ContainerDied => "container_died",
+        }
+    }
+
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self>
+    {
+        Self::ALL.into_iter().find(|event| event.as_str() == value)
+    }
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectWebhook
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub url: String,
+
+    #[serde(skip_serializing)]
+    pub encrypted_secret: String,
+
+    pub events: Vec<String>,
+    pub created_by: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl ProjectWebhook
+{
+    #[must_use]
+    pub fn accepts(&self, event: WebhookEvent) -> bool
+    {
+        self.events.iter().any(|e| e == event.as_str())
+    }
+}
+
+/// Webhook à ajouter (`POST /api/projects/{id}/webhooks`).
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookPayload
+{
+    pub url: String,
+    /// Secret partagé avec le destinataire pour vérifier l'en-tête `X-Hangar-Signature`.
+    pub secret: String,
+    /// Événements transmis ; tous si absent.
+    #[serde(default)]
+    pub events: Option<Vec<WebhookEvent>>,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct WebhookDelivery
+{
+    pub id: i64,
+    pub webhook_id: i32,
+    pub event_type: String,
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub success: bool,
+    pub error: Option<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub attempted_at: OffsetDateTime,
+}
@@ -0,0 +1,49 @@
+//! Limite d'appels par clé sur une fenêtre glissante (deploy hooks par clé d'API, e-mails par utilisateur).
+
+use std::{collections::{HashMap, VecDeque}, hash::Hash, sync::Mutex, time::{Duration, Instant}};
+
+/// Enregistre un appel pour `key` et indique s'il reste sous `max_calls` sur les `window` précédentes.
+pub fn check_sliding_window<K: Eq + Hash>(
+    calls: &Mutex<HashMap<K, VecDeque<Instant>>>,
+    key: K,
+    max_calls: usize,
+    window: Duration,
+    now: Instant,
+) -> bool
+{
+    let mut calls = calls.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+    let history = calls.entry(key).or_default();
+
+    while history.front().is_some_and(|&at| now.duration_since(at) >= window)
+    {
+        history.pop_front();
+    }
+
+    if history.len() >= max_calls
+    {
+        return false;
+    }
+
+    history.push_back(now);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sliding_window()
+    {
+        let calls = Mutex::new(HashMap::new());
+        let window = Duration::from_secs(60);
+        let start = Instant::now();
+
+        assert!(check_sliding_window(&calls, 1, 2, window, start));
+        assert!(check_sliding_window(&calls, 1, 2, window, start));
+        assert!(!check_sliding_window(&calls, 1, 2, window, start));
+        assert!(check_sliding_window(&calls, 2, 2, window, start));
+        assert!(!check_sliding_window(&calls, 1, 2, window, start + Duration::from_secs(59)));
+        assert!(check_sliding_window(&calls, 1, 2, window, start + window));
+    }
+}
@@ -1,84 +1,420 @@
 use crate::{handlers, state::AppState, middleware};
-use axum::{error_handling::HandleErrorLayer, http::StatusCode, middleware as axum_middleware, routing::{delete, get, post, put}, BoxError, Router};
+use axum::{error_handling::HandleErrorLayer, extract::DefaultBodyLimit, http::StatusCode, middleware as axum_middleware, response::IntoResponse, routing::{delete, get, patch, post, put}, BoxError, Json, Router};
+use serde_json::json;
 use tower::{timeout::TimeoutLayer, ServiceBuilder};
-use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
+use tower_http::{compression::{predicate::{NotForContentType, Predicate, SizeAbove}, CompressionLayer}, cors::CorsLayer, trace::TraceLayer};
 use std::time::Duration;
 
-pub fn create_router(state: AppState) -> Router 
+pub fn create_router(state: AppState) -> Router
 {
-    let common_layer = ServiceBuilder::new()
+    let http_layer = ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
                 .layer(CorsLayer::permissive())
-                .layer(CompressionLayer::new())
-                .layer(HandleErrorLayer::new(|_: BoxError| async {StatusCode::REQUEST_TIMEOUT}))
-                .layer(TimeoutLayer::new(Duration::from_secs(state.config.timeout_normal)));
+                .layer(compression_layer());
 
-    let long_running_layer = ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
-                .layer(CorsLayer::permissive())
-                .layer(CompressionLayer::new())
-                .layer(HandleErrorLayer::new(|_: BoxError| async {StatusCode::REQUEST_TIMEOUT}))
-                .layer(TimeoutLayer::new(Duration::from_secs(state.config.timeout_long)));
-    
     let sse_layer = ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
                 .layer(CorsLayer::permissive());
-    
+
+    let timeouts = &state.config;
+
     let sse_routes = Router::new()
         .route("/api/sse/projects/{project_id}", get(handlers::sse_handler::sse_project_handler))
+        .route("/api/sse/projects/{project_id}/logs", get(handlers::sse_handler::sse_project_logs_handler))
         .route("/api/sse/creation", get(handlers::sse_handler::sse_creation_handler))
         .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
+        .layer(sse_layer.clone());
+
+    // Authentifiées par le jeton du lien de partage.
+    let public_sse_routes = Router::new()
+        .route("/api/public/projects/{token}/events", get(handlers::sse_handler::sse_public_project_handler))
+        .layer(sse_layer.clone());
+
+    let admin_sse_routes = Router::new()
+        .route("/api/sse/admin", get(handlers::sse_handler::sse_admin_handler))
+        .route_layer(axum_middleware::from_fn(middleware::admin_auth))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
         .layer(sse_layer);
 
     let admin_routes = Router::new()
         .route("/api/admin/projects", get(handlers::admin_handler::list_all_projects_handler))
         .route("/api/admin/metrics", get(handlers::admin_handler::get_global_metrics_handler))
+        .route("/api/admin/metrics/history", get(handlers::admin_handler::get_metrics_history_handler))
         .route("/api/admin/projects/down", get(handlers::admin_handler::get_down_projects_handler))
+        .route("/api/admin/projects/ownerless", get(handlers::admin_handler::list_ownerless_projects_handler))
+        .route("/api/admin/projects/{project_id}/flag-for-cleanup", post(handlers::admin_handler::flag_project_for_cleanup_handler).delete(handlers::admin_handler::cancel_project_cleanup_handler))
+        .route("/api/admin/reports/usage", get(handlers::admin_handler::get_usage_report_handler))
+        .route("/api/admin/deployments", get(handlers::admin_handler::list_all_deployments_handler))
+        .route("/api/admin/logging", get(handlers::admin_handler::get_log_filter_handler).put(handlers::admin_handler::update_log_filter_handler))
+        .route("/api/admin/docker/info", get(handlers::admin_handler::get_docker_info_handler))
+        .route("/api/admin/projects/import", post(handlers::admin_handler::import_project_handler))
+        .route("/api/admin/users/{login}/footprint", get(handlers::admin_handler::get_user_footprint_handler))
+        .route("/api/admin/users/{login}/offboard", post(handlers::admin_handler::offboard_user_handler))
+        .route("/api/admin/containers/unlabeled", get(handlers::admin_handler::list_unlabeled_containers_handler))
+        .route("/api/admin/routes", get(handlers::admin_handler::list_traefik_routes_handler))
+        .route("/api/admin/orphans", get(handlers::admin_handler::list_orphans_handler).delete(handlers::admin_handler::remove_orphans_handler))
+        .route("/api/admin/databases/sessions", get(handlers::admin_handler::list_all_database_sessions_handler))
+        .route("/api/admin/databases/sessions/{session_id}", delete(handlers::admin_handler::kill_any_database_session_handler))
+        .route("/api/admin/databases/{db_id}/limits", put(handlers::admin_handler::update_database_limits_handler))
+        .route("/api/admin/templates", get(handlers::template_handler::list_templates_handler).post(handlers::template_handler::create_template_handler))
+        .route("/api/admin/templates/{template_id}", put(handlers::template_handler::update_template_handler).delete(handlers::template_handler::delete_template_handler))
         .route_layer(axum_middleware::from_fn(middleware::admin_auth))
-        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
-        .route_layer(common_layer.clone());
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let admin_routes = with_timeout(admin_routes, timeouts.timeout_normal).route_layer(http_layer.clone());
+
+    // Déploiement puis purge complets : le délai couvre les deux.
+    let admin_selftest_routes = Router::new()
+        .route("/api/admin/selftest", post(handlers::admin_handler::run_selftest_handler))
+        .route_layer(axum_middleware::from_fn(middleware::admin_auth))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let admin_selftest_routes = with_timeout(admin_selftest_routes, timeouts.timeout_deploy + timeouts.timeout_long).route_layer(http_layer.clone());
+
+    // Parcourt tous les secrets chiffrés de l'instance.
+    let admin_long_routes = Router::new()
+        .route("/api/admin/crypto/verify", post(handlers::admin_handler::verify_encryption_handler))
+        .route("/api/admin/projects/resync-labels", post(handlers::admin_handler::resync_all_labels_handler))
+        .route_layer(axum_middleware::from_fn(middleware::admin_auth))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let admin_long_routes = with_timeout(admin_long_routes, timeouts.timeout_long).route_layer(http_layer.clone());
+
+    // Recréation blue-green du conteneur : même délai qu'une mise à jour des variables.
+    let admin_recreation_routes = Router::new()
+        .route("/api/admin/projects/{project_id}/resource-profile", put(handlers::project_handler::update_resource_profile_handler))
+        .route("/api/admin/projects/{project_id}/domains/{domain_id}/verify", post(handlers::domain_handler::force_verify_domain_handler))
+        .route("/api/admin/projects/{project_id}/resync-labels", post(handlers::admin_handler::resync_project_labels_handler))
+        .route_layer(axum_middleware::from_fn(middleware::admin_auth))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let admin_recreation_routes = with_timeout(admin_recreation_routes, timeouts.timeout_env_update).route_layer(http_layer.clone());
 
     let public_routes = Router::new()
         .route("/api/health", get(handlers::health::health_check_handler))
         .route("/api/auth/callback", get(handlers::auth_handler::auth_callback_handler))
-        .route_layer(common_layer.clone());
+        .route("/api/public/projects/{token}", get(handlers::share_link_handler::get_public_project_handler));
+    let public_routes = with_timeout(public_routes, timeouts.timeout_normal).route_layer(http_layer.clone());
 
     let protected_routes = Router::new()
         .route("/api/auth/me", get(handlers::auth_handler::get_current_user_handler))
         .route("/api/auth/logout", get(handlers::auth_handler::logout_handler))
+        .route("/api/me/notifications", get(handlers::notification_handler::get_notification_settings_handler).put(handlers::notification_handler::update_notification_settings_handler))
+        .route("/api/me/invitations", get(handlers::invitation_handler::list_my_invitations_handler))
+        .route("/api/me/memberships", get(handlers::project_handler::list_my_memberships_handler))
+        .route("/api/me/invitations/{invitation_id}/accept", post(handlers::invitation_handler::accept_invitation_handler))
+        .route("/api/me/invitations/{invitation_id}/decline", post(handlers::invitation_handler::decline_invitation_handler))
         .route("/api/projects/owned", get(handlers::project_handler::list_owned_projects_handler))
         .route("/api/projects/participations", get(handlers::project_handler::list_participating_projects_handler))
+        .route("/api/projects/deploy/dry-run", post(handlers::project_handler::deploy_dry_run_handler))
+        .route("/api/github/branches", get(handlers::github_handler::list_branches_handler))
+        .route("/api/platform/limits", get(handlers::platform_handler::get_platform_limits_handler))
+        .route("/api/templates", get(handlers::template_handler::list_templates_handler))
         .route("/api/projects/{project_id}", get(handlers::project_handler::get_project_details_handler))
+        .route("/api/projects/{project_id}/permissions", get(handlers::project_handler::get_project_permissions_handler))
         .route("/api/projects/{project_id}/start", post(handlers::project_handler::start_project_handler))
         .route("/api/projects/{project_id}/stop", post(handlers::project_handler::stop_project_handler))
         .route("/api/projects/{project_id}/restart", post(handlers::project_handler::restart_project_handler))
         .route("/api/projects/{project_id}/logs", get(handlers::project_handler::get_project_logs_handler))
-        .route("/api/projects/{project_id}/participants", post(handlers::project_handler::add_participant_handler))
+        .route("/api/projects/{project_id}/container", get(handlers::project_handler::get_project_container_handler))
+        .route("/api/projects/{project_id}/stop-grace", put(handlers::project_handler::update_stop_grace_handler))
+        .route("/api/projects/{project_id}/keep-alive", put(handlers::project_handler::update_keep_alive_handler))
+        .route("/api/projects/{project_id}/health-check", put(handlers::project_handler::update_health_check_handler))
+        .route("/api/projects/{project_id}/scan-ignores", put(handlers::project_handler::update_scan_ignores_handler))
+        .route("/api/projects/{project_id}/participants", get(handlers::project_handler::list_participants_handler).post(handlers::project_handler::add_participant_handler))
         .route("/api/projects/{project_id}/participants/{participant_id}", delete(handlers::project_handler::remove_participant_handler))
         .route("/api/databases/mine", get(handlers::database_handler::get_my_database_handler))
         .route("/api/databases", post(handlers::database_handler::create_database_handler))
         .route("/api/databases/{db_id}", delete(handlers::database_handler::delete_my_database_handler))
+        .route("/api/databases/{db_id}/credentials", post(handlers::database_handler::reveal_database_credentials_handler))
+        .route("/api/databases/{db_id}/sessions", get(handlers::database_handler::list_database_sessions_handler))
+        .route("/api/databases/{db_id}/sessions/{session_id}", delete(handlers::database_handler::kill_database_session_handler))
+        .route("/api/databases/{db_id}/backups", get(handlers::database_handler::list_database_backups_handler))
         .route("/api/projects/{project_id}/database/{db_id}", put(handlers::database_handler::link_database_handler))
         .route("/api/projects/{project_id}/database", delete(handlers::database_handler::unlink_database_handler))
+        .route("/api/projects/{project_id}/database/credentials", post(handlers::database_handler::reveal_linked_database_credentials_handler))
         .route("/api/projects/{project_id}/database/delete", delete(handlers::database_handler::delete_linked_database_handler))
-        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
-        .route_layer(common_layer.clone());
+        .route("/api/projects/{project_id}/jobs", get(handlers::job_handler::list_jobs_handler).post(handlers::job_handler::create_job_handler))
+        .route("/api/projects/{project_id}/jobs/{job_id}", put(handlers::job_handler::update_job_handler).delete(handlers::job_handler::delete_job_handler))
+        .route("/api/projects/{project_id}/jobs/{job_id}/runs", get(handlers::job_handler::list_job_runs_handler))
+        .route("/api/projects/{project_id}/api-keys", get(handlers::api_key_handler::list_api_keys_handler).post(handlers::api_key_handler::create_api_key_handler))
+        .route("/api/projects/{project_id}/api-keys/{key_id}", delete(handlers::api_key_handler::revoke_api_key_handler))
+        .route("/api/projects/{project_id}/share-links", get(handlers::share_link_handler::list_share_links_handler).post(handlers::share_link_handler::create_share_link_handler))
+        .route("/api/projects/{project_id}/share-links/{link_id}", delete(handlers::share_link_handler::revoke_share_link_handler))
+        .route("/api/projects/{project_id}/domains", get(handlers::domain_handler::list_domains_handler).post(handlers::domain_handler::add_domain_handler))
+        .route("/api/projects/{project_id}/webhooks", get(handlers::webhook_handler::list_webhooks_handler).post(handlers::webhook_handler::add_webhook_handler))
+        .route("/api/projects/{project_id}/webhooks/{webhook_id}", delete(handlers::webhook_handler::remove_webhook_handler))
+        .route("/api/projects/{project_id}/webhooks/{webhook_id}/deliveries", get(handlers::webhook_handler::list_deliveries_handler))
+        .route("/api/projects/{project_id}/deployments", get(handlers::deployment_handler::list_deployments_handler))
+        .route("/api/projects/{project_id}/timeline", get(handlers::timeline_handler::get_project_timeline_handler))
+        .route("/api/projects/{project_id}/uptime", get(handlers::uptime_handler::get_project_uptime_handler))
+        .route("/api/projects/{project_id}/events/missed", get(handlers::missed_event_handler::list_missed_events_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let protected_routes = with_timeout(protected_routes, timeouts.timeout_normal).route_layer(http_layer.clone());
 
-    let long_running_protected_routes = Router::new()
+    #[cfg(feature = "object_storage")]
+    let protected_routes = protected_routes.merge(
+        with_timeout(
+            Router::new()
+                .route("/api/projects/{project_id}/bucket", post(handlers::bucket_handler::create_project_bucket_handler))
+                .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth)),
+            timeouts.timeout_normal,
+        ).route_layer(http_layer.clone())
+    );
+
+    let deploy_routes = Router::new()
         .route("/api/projects/deploy", post(handlers::project_handler::deploy_project_handler))
-        .route("/api/projects/{project_id}", delete(handlers::project_handler::purge_project_handler))
+        .route("/api/projects/deploy-from-template/{template_id}", post(handlers::template_handler::deploy_from_template_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let deploy_routes = with_timeout(deploy_routes, timeouts.timeout_deploy).route_layer(http_layer.clone());
+
+    let rebuild_routes = Router::new()
+        .route("/api/projects/{project_id}/rebuild", put(handlers::project_handler::rebuild_project_handler))
+        .route("/api/projects/{project_id}/unarchive", post(handlers::project_handler::unarchive_project_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let rebuild_routes = with_timeout(rebuild_routes, timeouts.timeout_rebuild).route_layer(http_layer.clone());
+
+    let image_update_routes = Router::new()
         .route("/api/projects/{project_id}/image", put(handlers::project_handler::update_project_image_handler))
+        .route("/api/projects/{project_id}/rollback", post(handlers::project_handler::rollback_project_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let image_update_routes = with_timeout(image_update_routes, timeouts.timeout_image_update).route_layer(http_layer.clone());
+
+    let retry_routes = Router::new()
+        .route("/api/deployments/{deployment_id}/retry", post(handlers::deployment_handler::retry_deployment_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let retry_routes = with_timeout(retry_routes, timeouts.timeout_rebuild.max(timeouts.timeout_image_update)).route_layer(http_layer.clone());
+
+    // Authentifiées par clé d'API de projet (en-tête X-Hangar-Key) et non par session.
+    let hook_routes = Router::new()
+        .route("/api/hooks/projects/{project_id}/redeploy", post(handlers::api_key_handler::hook_redeploy_handler));
+    let hook_routes = with_timeout(hook_routes, timeouts.timeout_rebuild.max(timeouts.timeout_image_update)).route_layer(http_layer.clone());
+
+    let env_update_routes = Router::new()
         .route("/api/projects/{project_id}/env", put(handlers::project_handler::update_env_vars_handler))
-        .route("/api/projects/{project_id}/rebuild", put(handlers::project_handler::rebuild_project_handler))
+        .route("/api/projects/{project_id}/command", patch(handlers::project_handler::update_container_command_handler))
+        .route("/api/projects/{project_id}/port", put(handlers::project_handler::update_container_port_handler))
+        // Recrée le conteneur du projet lié qui reçoit les identifiants de la base.
+        .route("/api/databases/{db_id}/rotate-password", post(handlers::database_handler::rotate_database_password_handler))
+        // Recrée le conteneur quand l'ensemble des domaines servis change.
+        .route("/api/projects/{project_id}/domains/{domain_id}", delete(handlers::domain_handler::remove_domain_handler))
+        .route("/api/projects/{project_id}/domains/{domain_id}/verify", post(handlers::domain_handler::verify_domain_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let env_update_routes = with_timeout(env_update_routes, timeouts.timeout_env_update).route_layer(http_layer.clone());
+
+    let long_running_protected_routes = Router::new()
+        .route("/api/projects/{project_id}", delete(handlers::project_handler::purge_project_handler))
+        .route("/api/projects/{project_id}/archive", post(handlers::project_handler::archive_project_handler))
+        .route("/api/databases/{db_id}/backups/{backup_id}/restore", post(handlers::database_handler::restore_database_backup_handler))
+        .route("/api/databases/{db_id}/reset", post(handlers::database_handler::reset_database_handler))
+        // Le délai couvre le démarrage de mysqldump ; la suite du dump est bornée par DATABASE_DUMP_TIMEOUT_SECONDS.
+        .route("/api/databases/{db_id}/dump", get(handlers::database_handler::download_database_dump_handler))
+        .route("/api/projects/{project_id}/run", post(handlers::job_handler::run_project_command_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+    let long_running_protected_routes = with_timeout(long_running_protected_routes, timeouts.timeout_long).route_layer(http_layer.clone());
+
+    // Le corps de l'import est l'archive elle-même : la limite par défaut d'axum (2 Mo) est remplacée.
+    let archive_body_limit = usize::try_from(timeouts.project_archive_max_mb.saturating_mul(1024 * 1024)).unwrap_or(usize::MAX);
+    let archive_routes = Router::new()
+        .route("/api/projects/{project_id}/export", get(handlers::project_archive_handler::export_project_handler))
+        .route("/api/projects/import", post(handlers::project_archive_handler::import_project_archive_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
+        .layer(DefaultBodyLimit::max(archive_body_limit));
+    let archive_routes = with_timeout(archive_routes, timeouts.timeout_long.max(timeouts.timeout_deploy)).route_layer(http_layer.clone());
+
+    // Le formulaire contient l'archive du site : 1 Mo de marge pour le payload et l'enveloppe multipart.
+    let upload_body_limit = usize::try_from(timeouts.upload_max_mb.saturating_add(1).saturating_mul(1024 * 1024)).unwrap_or(usize::MAX);
+    let upload_routes = Router::new()
+        .route("/api/projects/deploy-upload", post(handlers::upload_handler::deploy_upload_handler))
+        .route("/api/projects/{project_id}/upload", put(handlers::upload_handler::redeploy_upload_handler))
         .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
-        .route_layer(long_running_layer);
+        .layer(DefaultBodyLimit::max(upload_body_limit));
+    let upload_routes = with_timeout(upload_routes, timeouts.timeout_deploy.max(timeouts.timeout_rebuild)).route_layer(http_layer);
 
     Router::new()
         .merge(public_routes)
         .merge(sse_routes)
+        .merge(public_sse_routes)
+        .merge(admin_sse_routes)
         .merge(protected_routes)
         .merge(admin_routes)
+        .merge(admin_selftest_routes)
+        .merge(admin_long_routes)
+        .merge(admin_recreation_routes)
+        .merge(deploy_routes)
+        .merge(rebuild_routes)
+        .merge(image_update_routes)
+        .merge(retry_routes)
+        .merge(hook_routes)
+        .merge(env_update_routes)
         .merge(long_running_protected_routes)
+        .merge(archive_routes)
+        .merge(upload_routes)
+        .layer(axum_middleware::from_fn_with_state(state.clone(), middleware::client_ip))
         .with_state(state)
 }
 
+/// Taille en dessous de laquelle une réponse n'est pas compressée.
+const MIN_COMPRESSED_SIZE: u16 = 32;
+
+/// Compression gzip des réponses JSON. Les flux `text/event-stream` ne sont jamais compressés :
+/// certains reverse proxies mettent en mémoire tampon les réponses compressées, ce qui retarderait
+/// les événements. Les routes SSE ont en plus leur propre pile de layers, sans compression.
+/// Les archives de projet, déjà compressées, sont envoyées telles quelles.
+fn compression_layer() -> CompressionLayer<impl Predicate>
+{
+    CompressionLayer::new().compress_when(
+        SizeAbove::new(MIN_COMPRESSED_SIZE)
+            .and(NotForContentType::SSE)
+            .and(NotForContentType::GRPC)
+            .and(NotForContentType::IMAGES)
+            .and(NotForContentType::const_new("application/gzip"))
+    )
+}
+
+/// Applique un `TimeoutLayer` au groupe de routes, avec une réponse JSON
+/// indiquant au client la durée maximale autorisée pour l'opération.
+fn with_timeout(router: Router<AppState>, seconds: u64) -> Router<AppState>
+{
+    router.route_layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(move |_: BoxError| async move { timeout_response(seconds) }))
+            .layer(TimeoutLayer::new(Duration::from_secs(seconds)))
+    )
+}
+
+fn timeout_response(seconds: u64) -> axum::response::Response
+{
+    (
+        StatusCode::REQUEST_TIMEOUT,
+        Json(json!({
+            "error_code": "REQUEST_TIMEOUT",
+            "message": format!("This operation is limited to {seconds} seconds."),
+            "timeout_seconds": seconds,
+        })),
+    ).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, io::Read};
+
+    use std::net::SocketAddr;
+
+    use axum::{body::Body, extract::ConnectInfo, http::{header, Request, StatusCode}, response::sse::{Event, Sse}};
+    use flate2::read::GzDecoder;
+    use futures::{executor::block_on, stream, FutureExt, StreamExt};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{config::Config, model::docker::{ContainerLogs, LogStream}, services::docker_service::MAX_LOG_SIZE, test_support};
+
+    fn gzip_request(uri: &str) -> Request<Body>
+    {
+        Request::builder().uri(uri).header(header::ACCEPT_ENCODING, "gzip").body(Body::empty()).unwrap()
+    }
+
+    fn compressed_router() -> Router
+    {
+        Router::new()
+            .route("/events", get(|| async
+            {
+                // Un premier événement, puis un flux qui reste ouvert comme un vrai flux SSE.
+                let events = stream::iter([Ok::<_, Infallible>(Event::default().data("x".repeat(4096)))]).chain(stream::pending());
+                Sse::new(events)
+            }))
+            .route("/logs", get(|| async
+            {
+                let mut logs = ContainerLogs::default();
+                let frame = format!("{}\n", "a".repeat(1023));
+                while logs.push_frame(LogStream::Stdout, frame.as_bytes(), MAX_LOG_SIZE) {}
+                Json(logs.finish())
+            }))
+            .layer(compression_layer())
+    }
+
+    #[test]
+    fn test_sse_responses_are_never_compressed_nor_buffered()
+    {
+        let response = block_on(compressed_router().oneshot(gzip_request("/events"))).unwrap();
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+
+        // Le premier événement est disponible sans attendre la fin du flux.
+        let mut body = response.into_body().into_data_stream();
+        let first = body.next().now_or_never().flatten().unwrap().unwrap();
+        assert!(first.starts_with(b"data: xxx"));
+    }
+
+    #[test]
+    fn test_large_logs_are_compressed_within_the_cap()
+    {
+        let response = block_on(compressed_router().oneshot(gzip_request("/logs"))).unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+
+        let compressed = block_on(axum::body::to_bytes(response.into_body(), usize::MAX)).unwrap();
+        let mut json = Vec::new();
+        GzDecoder::new(compressed.as_ref()).read_to_end(&mut json).unwrap();
+        assert!(compressed.len() < MAX_LOG_SIZE);
+
+        let logs: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(logs["truncated"], true);
+        let content: usize = logs["logs"].as_array().unwrap().iter().map(|line| line["line"].as_str().unwrap().len() + 1).sum();
+        assert!(content <= MAX_LOG_SIZE);
+    }
+
+    async fn status(router: &Router, method: &str, uri: &str, login: &str) -> StatusCode
+    {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::COOKIE, test_support::auth_cookie(login, false))
+            .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40_000))))
+            .body(Body::empty())
+            .unwrap();
+
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    /// Détails et arrêt ouverts aux participants, purge réservée au propriétaire ; un inconnu reçoit
+    /// `denied`, comme pour un projet inexistant si `EXPOSE_FORBIDDEN` est désactivé.
+    async fn assert_project_route_roles(pool: PgPool, expose_forbidden: bool)
+    {
+        let project_id = test_support::insert_project(&pool, "demo", "alice", &["bob"]).await;
+        let config = Config { expose_forbidden, ..test_support::config() };
+        let router = create_router(test_support::state_with(config, pool, test_support::UNREACHABLE_DOCKER));
+        let denied = if expose_forbidden { StatusCode::FORBIDDEN } else { StatusCode::NOT_FOUND };
+        let allowed = |status: StatusCode| status != StatusCode::FORBIDDEN && status != StatusCode::NOT_FOUND && status != StatusCode::UNAUTHORIZED;
+
+        let project = format!("/api/projects/{project_id}");
+        let stop = format!("/api/projects/{project_id}/stop");
+        let routes = [("GET", &project), ("POST", &stop), ("DELETE", &project)];
+
+        for (method, uri) in routes
+        {
+            assert_eq!(status(&router, method, uri, "eve").await, denied, "eve: {method} {uri}");
+            let missing = uri.replace(&project_id.to_string(), "999999");
+            assert_eq!(status(&router, method, &missing, "eve").await, StatusCode::NOT_FOUND, "eve: {method} {missing}");
+        }
+
+        for (method, uri) in &routes[..2]
+        {
+            assert!(allowed(status(&router, method, uri, "bob").await), "bob: {method} {uri}");
+            assert!(allowed(status(&router, method, uri, "alice").await), "alice: {method} {uri}");
+        }
+        assert_eq!(status(&router, "DELETE", &project, "bob").await, denied, "bob: DELETE {project}");
+        assert!(allowed(status(&router, "DELETE", &project, "alice").await), "alice: DELETE {project}");
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_project_routes_hide_denied_projects(pool: PgPool)
+    {
+        assert_project_route_roles(pool, false).await;
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_project_routes_expose_forbidden(pool: PgPool)
+    {
+        assert_project_route_roles(pool, true).await;
+    }
+}
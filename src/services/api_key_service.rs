@@ -0,0 +1,159 @@
+use std::{collections::{HashMap, VecDeque}, sync::Mutex, time::{Duration, Instant}};
+
+use rand::distr::{Alphanumeric, SampleString};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::{error::AppError, model::api_key::{CreatedApiKey, ProjectApiKey}, rate_limit};
+
+/// Préfixe des clés, pour les reconnaître facilement dans un fichier de configuration CI.
+const KEY_PREFIX: &str = "hk_";
+/// Nombre de caractères aléatoires d'une clé.
+const KEY_RANDOM_LENGTH: usize = 40;
+/// Nombre de caractères conservés en clair pour identifier une clé.
+const DISPLAY_PREFIX_LENGTH: usize = 10;
+/// Nombre maximal de clés actives par projet.
+pub const MAX_KEYS_PER_PROJECT: i64 = 10;
+/// Fenêtre glissante du rate-limit des deploy hooks.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(3600);
+
+fn generate_key() -> String
+{
+    format!("{KEY_PREFIX}{}", Alphanumeric.sample_string(&mut rand::rng(), KEY_RANDOM_LENGTH))
+}
+
+fn hash_key(key: &str) -> String
+{
+    Sha256::digest(key.as_bytes()).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub async fn list_keys(pool: &PgPool, project_id: i32) -> Result<Vec<ProjectApiKey>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_api_keys WHERE project_id = $1 ORDER BY id")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list API keys of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn create_key(pool: &PgPool, project_id: i32, created_by: &str) -> Result<CreatedApiKey, AppError>
+{
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM project_api_keys WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count API keys of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    if count >= MAX_KEYS_PER_PROJECT
+    {
+        return Err(AppError::BadRequest(format!("A project cannot have more than {MAX_KEYS_PER_PROJECT} API keys.")));
+    }
+
+    let secret = generate_key();
+    let key = sqlx::query_as(
+        "INSERT INTO project_api_keys (project_id, key_prefix, key_hash, created_by) VALUES ($1, $2, $3, $4) RETURNING *"
+    )
+    .bind(project_id)
+    .bind(&secret[..DISPLAY_PREFIX_LENGTH])
+    .bind(hash_key(&secret))
+    .bind(created_by)
+    .fetch_one(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to create API key for project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(CreatedApiKey { key, secret })
+}
+
+/// Révoque une clé. Renvoie `false` si elle n'appartient pas au projet.
+pub async fn revoke_key(pool: &PgPool, project_id: i32, key_id: i32) -> Result<bool, AppError>
+{
+    sqlx::query("DELETE FROM project_api_keys WHERE id = $1 AND project_id = $2")
+        .bind(key_id)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(|e|
+        {
+            error!("Failed to revoke API key ID {}: {}", key_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Vérifie qu'une clé en clair est valide pour ce projet et met à jour sa date de dernière utilisation.
+pub async fn verify_key(pool: &PgPool, project_id: i32, key: &str) -> Result<Option<ProjectApiKey>, AppError>
+{
+    if !key.starts_with(KEY_PREFIX)
+    {
+        return Ok(None);
+    }
+
+    sqlx::query_as(
+        "UPDATE project_api_keys SET last_used_at = NOW() WHERE key_hash = $1 AND project_id = $2 RETURNING *"
+    )
+    .bind(hash_key(key))
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to verify API key for project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    })
+}
+
+/// Enregistre un appel de la clé et indique s'il reste sous la limite de la fenêtre glissante.
+pub fn check_rate_limit(
+    calls: &Mutex<HashMap<i32, VecDeque<Instant>>>,
+    key_id: i32,
+    max_calls: usize,
+    now: Instant,
+) -> bool
+{
+    rate_limit::check_sliding_window(calls, key_id, max_calls, RATE_LIMIT_WINDOW, now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_are_distinct_and_hashed_as_hex()
+    {
+        let key = generate_key();
+        assert!(key.starts_with(KEY_PREFIX));
+        assert_eq!(key.len(), KEY_PREFIX.len() + KEY_RANDOM_LENGTH);
+        assert_ne!(key, generate_key());
+
+        let hash = hash_key(&key);
+        assert_eq!(hash.len(), 64);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(hash, hash_key(&key));
+    }
+
+    #[test]
+    fn test_rate_limit_window()
+    {
+        let calls = Mutex::new(HashMap::new());
+        let start = Instant::now();
+
+        assert!(check_rate_limit(&calls, 1, 2, start));
+        assert!(check_rate_limit(&calls, 1, 2, start));
+        assert!(!check_rate_limit(&calls, 1, 2, start));
+        assert!(check_rate_limit(&calls, 2, 2, start));
+        assert!(check_rate_limit(&calls, 1, 2, start + RATE_LIMIT_WINDOW));
+    }
+}
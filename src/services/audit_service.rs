@@ -0,0 +1,105 @@
+use tracing::{debug, error};
+
+use crate::client_ip::ClientIp;
+use crate::model::timeline::TimelineEntryType;
+use crate::services::timeline_service;
+use crate::state::AppState;
+
+pub const ACTION_DB_CREDENTIALS_REVEALED: &str = "database.credentials_revealed";
+pub const ACTION_DB_BACKUP_RESTORED: &str = "database.backup_restored";
+pub const ACTION_DB_RESET: &str = "database.reset";
+pub const ACTION_DB_PASSWORD_ROTATED: &str = "database.password_rotated";
+pub const ACTION_DB_DUMPED: &str = "database.dumped";
+pub const ACTION_PROJECT_COMMAND_RUN: &str = "project.command_run";
+pub const ACTION_PROJECT_API_KEY_CREATED: &str = "project.api_key_created";
+pub const ACTION_PROJECT_API_KEY_REVOKED: &str = "project.api_key_revoked";
+pub const ACTION_PROJECT_HOOK_REDEPLOY: &str = "project.hook_redeploy";
+pub const ACTION_PROJECT_CLEANUP_FLAGGED: &str = "project.cleanup_flagged";
+pub const ACTION_PROJECT_CLEANUP_CANCELLED: &str = "project.cleanup_cancelled";
+pub const ACTION_PROJECT_ARCHIVED: &str = "project.archived";
+pub const ACTION_PROJECT_IMPORTED: &str = "project.imported";
+pub const ACTION_PROJECT_EXPORTED: &str = "project.exported";
+pub const ACTION_PROJECT_RESTORED: &str = "project.restored";
+pub const ACTION_PROJECT_SHARE_LINK_CREATED: &str = "project.share_link_created";
+pub const ACTION_PROJECT_SHARE_LINK_REVOKED: &str = "project.share_link_revoked";
+pub const ACTION_PROJECT_SHARE_LINK_ACCESSED: &str = "project.share_link_accessed";
+pub const ACTION_PROJECT_DOMAIN_ADDED: &str = "project.domain_added";
+pub const ACTION_PROJECT_DOMAIN_VERIFIED: &str = "project.domain_verified";
+pub const ACTION_PROJECT_DOMAIN_REMOVED: &str = "project.domain_removed";
+pub const ACTION_PROJECT_WEBHOOK_ADDED: &str = "project.webhook_added";
+pub const ACTION_PROJECT_WEBHOOK_REMOVED: &str = "project.webhook_removed";
+pub const ACTION_USER_OFFBOARDING_STEP: &str = "user.offboarding_step";
+pub const ACTION_USER_OFFBOARDED: &str = "user.offboarded";
+pub const ACTION_LOG_FILTER_CHANGED: &str = "admin.log_filter_changed";
+pub const ACTION_SELFTEST_RUN: &str = "admin.selftest_run";
+pub const ACTION_ORPHANS_REMOVED: &str = "admin.orphans_removed";
+pub const ACTION_CRYPTO_VERIFIED: &str = "admin.crypto_verified";
+pub const ACTION_TEMPLATE_CREATED: &str = "admin.template_created";
+pub const ACTION_TEMPLATE_UPDATED: &str = "admin.template_updated";
+pub const ACTION_TEMPLATE_DELETED: &str = "admin.template_deleted";
+pub const ACTION_LABELS_RESYNCED: &str = "admin.labels_resynced";
+/// Opération d'un administrateur sur le projet d'un autre utilisateur.
+pub const ACTION_ADMIN_ACTION: &str = "admin_action";
+
+/// Enregistre une entrée dans le journal d'audit.
+///
+/// L'audit est "best-effort" : un échec d'écriture est journalisé mais ne fait
+/// jamais échouer l'opération métier qui l'a déclenché.
+pub async fn record(
+    state: &AppState,
+    actor_login: &str,
+    action: &str,
+    project_id: Option<i32>,
+    details: Option<serde_json::Value>,
+)
+{
+    insert(state, actor_login, None, action, project_id, details).await;
+}
+
+/// Comme [`record`], avec l'adresse du client pour les appels sans session utilisateur
+/// (deploy hooks, liens de partage).
+pub async fn record_from(
+    state: &AppState,
+    actor_login: &str,
+    client_ip: ClientIp,
+    action: &str,
+    project_id: Option<i32>,
+    details: Option<serde_json::Value>,
+)
+{
+    insert(state, actor_login, Some(client_ip), action, project_id, details).await;
+}
+
+async fn insert(
+    state: &AppState,
+    actor_login: &str,
+    client_ip: Option<ClientIp>,
+    action: &str,
+    project_id: Option<i32>,
+    details: Option<serde_json::Value>,
+)
+{
+    let result = sqlx::query_scalar::<_, i64>(
+        "INSERT INTO audit_logs (actor_login, action, project_id, details, client_ip) VALUES ($1, $2, $3, $4, $5) RETURNING id"
+    )
+    .bind(actor_login)
+    .bind(action)
+    .bind(project_id)
+    .bind(details)
+    .bind(client_ip.map(|ip| ip.to_string()))
+    .fetch_one(&state.db_pool)
+    .await;
+
+    match result
+    {
+        Ok(id) =>
+        {
+            debug!("Audit: '{}' performed '{}' (project: {:?})", actor_login, action, project_id);
+            if project_id.is_some()
+            {
+                timeline_service::publish(state, TimelineEntryType::Audit, id).await;
+            }
+        }
+        Err(e) => error!("Failed to record audit entry '{}' for '{}': {}", action, actor_login, e),
+    }
+}
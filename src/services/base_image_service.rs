@@ -0,0 +1,128 @@
+//! Maintien au chaud des images de base des builds GitHub.
+//!
+//! Les images sont tirées au démarrage puis à intervalle régulier, et de nouveau dès qu'une image
+//! est supprimée sur le démon (`docker image rm`, `docker image prune`) : un build n'a ainsi
+//! presque jamais à télécharger les couches de son image de base.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bollard::query_parameters::EventsOptions;
+use futures::StreamExt;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::
+{
+    config::Config,
+    error::AppError,
+    logging::log_safe,
+    model::docker::BaseImageStatus,
+    services::docker_service,
+    state::AppState,
+};
+
+/// Attente avant de se réabonner aux événements d'images après une interruption du flux.
+const EVENTS_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Images de base utilisées par les builds générés.
+#[must_use]
+pub fn base_images(config: &Config) -> Vec<String>
+{
+    vec![config.build_base_image.clone()]
+}
+
+/// Présence et identifiant de chaque image de base sur le démon.
+pub async fn base_image_statuses(state: &AppState) -> Result<Vec<BaseImageStatus>, AppError>
+{
+    let mut statuses = Vec::new();
+    for image in base_images(&state.config)
+    {
+        let digest = docker_service::get_image_digest(&state.docker_client, &image).await?;
+        statuses.push(BaseImageStatus { present: digest.is_some(), image, digest });
+    }
+    Ok(statuses)
+}
+
+/// Indique si l'image est déjà présente sur le démon (un build n'aura pas à la télécharger).
+pub async fn is_warm(state: &AppState, image: &str) -> bool
+{
+    matches!(docker_service::get_image_digest(&state.docker_client, image).await, Ok(Some(_)))
+}
+
+/// Tâche de fond : tire les images de base au démarrage, périodiquement et après chaque suppression d'image.
+pub async fn start_base_image_keeper(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting base image warm-keeper task");
+    let mut interval = tokio::time::interval(Duration::from_secs(state.config.base_image_refresh_minutes * 60));
+
+    // Les suppressions sont les seuls événements d'images qui peuvent retirer une image de base.
+    let filters = HashMap::from([
+        ("type".to_string(), vec!["image".to_string()]),
+        ("event".to_string(), vec!["delete".to_string(), "prune".to_string()]),
+    ]);
+    let options = EventsOptions { filters: Some(filters), ..Default::default() };
+    let mut events = state.docker_client.events(Some(options.clone()));
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Base image warm-keeper task shutting down");
+                return;
+            }
+            _ = interval.tick() => refresh_base_images(&state).await,
+            event = events.next() =>
+            {
+                match event
+                {
+                    Some(Ok(_)) => ensure_base_images(&state).await,
+                    Some(Err(e)) =>
+                    {
+                        warn!("Image events stream error: {}. Resubscribing in {:?}", e, EVENTS_RECONNECT_DELAY);
+                        tokio::time::sleep(EVENTS_RECONNECT_DELAY).await;
+                        events = state.docker_client.events(Some(options.clone()));
+                    }
+                    None =>
+                    {
+                        warn!("Image events stream ended. Resubscribing in {:?}", EVENTS_RECONNECT_DELAY);
+                        tokio::time::sleep(EVENTS_RECONNECT_DELAY).await;
+                        events = state.docker_client.events(Some(options.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Tire de nouveau toutes les images de base, pour suivre les mises à jour de leur tag.
+async fn refresh_base_images(state: &AppState)
+{
+    for image in base_images(&state.config)
+    {
+        pull_base_image(state, &image).await;
+    }
+}
+
+/// Tire uniquement les images de base absentes du démon.
+async fn ensure_base_images(state: &AppState)
+{
+    for image in base_images(&state.config)
+    {
+        if !is_warm(state, &image).await
+        {
+            info!("Base image '{}' was removed from the daemon, pulling it again", log_safe(&image));
+            pull_base_image(state, &image).await;
+        }
+    }
+}
+
+async fn pull_base_image(state: &AppState, image: &str)
+{
+    if let Err(e) = docker_service::pull_image(&state.docker_client, image, None).await
+    {
+        warn!("Failed to pull base image '{}': {}", log_safe(image), e);
+    }
+}
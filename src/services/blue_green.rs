@@ -0,0 +1,826 @@
+//! Bascule blue-green commune aux mises à jour d'image, aux reconstructions et aux recréations
+//! (variables d'environnement, commande, port).
+//!
+//! Le nouveau conteneur est créé à côté de l'ancien, vérifié, puis les métadonnées du projet
+//! basculent en une transaction avant la suppression de l'ancien conteneur. Toute erreur avant la
+//! bascule supprime le nouveau conteneur (et sa nouvelle image si demandé) : l'ancien reste en service.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rand::distr::{Alphanumeric, SampleString};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    config::Config,
+    error::{AppError, ProjectErrorCode},
+    model::project::{ContainerCommand, Project},
+    services::{deployment_orchestrator::DeploymentOrchestrator, docker_service, domain_service, project_service},
+    sse::{emitter::emit_if_network_missing, types::DeploymentStage},
+    state::AppState,
+};
+
+/// Délai maximal entre deux vérifications de santé, atteint par doublements successifs.
+const HEALTH_CHECK_MAX_INTERVAL: Duration = Duration::from_secs(5);
+/// Une attente de santé est signalée (log et flux SSE) toutes les `HEALTH_CHECK_PROGRESS_EVERY` vérifications.
+const HEALTH_CHECK_PROGRESS_EVERY: u32 = 5;
+/// Marge ajoutée au délai d'arrêt pour recevoir les événements `stop`/`die` d'un arrêt attendu.
+const EXPECTED_STOP_MARGIN: Duration = Duration::from_secs(30);
+/// Nouvelles tentatives de création lorsque le nom du nouveau conteneur est déjà pris.
+const NAME_CONFLICT_RETRIES: u32 = 3;
+/// Longueur du suffixe aléatoire des noms de conteneur.
+const NAME_SUFFIX_LENGTH: usize = 4;
+
+/// Conteneur à créer pour remplacer celui du projet.
+pub struct NewContainerSpec
+{
+    pub container_name: String,
+    /// Image (tag ou digest) à partir de laquelle le conteneur est créé.
+    pub image: String,
+    pub env_vars: Option<HashMap<String, String>>,
+    pub command: ContainerCommand,
+    /// Port HTTP du conteneur, cible du service Traefik.
+    pub container_port: u16,
+    /// Nom du profil de ressources appliqué au conteneur.
+    pub resource_profile: String,
+    /// Image supprimée si la bascule échoue (`None` : image déjà en service ou conservée pour une reprise).
+    pub rollback_image: Option<String>,
+    /// Image remplacée, supprimée après une bascule réussie.
+    pub replaced_image: Option<String>,
+}
+
+/// Métadonnées du projet modifiées avec le nom du conteneur lors de la bascule.
+pub enum MetadataUpdate<'a>
+{
+    /// Nouvelle image ; `source_url` n'est renseignée que pour les projets `direct` et `upload`.
+    /// `commit_sha` remplace le commit enregistré : `None` l'efface, l'image ne correspondant plus à un commit connu.
+    Image { tag: &'a str, digest: &'a str, source_url: Option<&'a str>, commit_sha: Option<&'a str> },
+    /// Nouvelle référence épinglée, demandée lors d'une reconstruction.
+    SourceRef(&'a str),
+    EnvVars(&'a HashMap<String, String>),
+    Command(&'a ContainerCommand),
+    Port(u16),
+    ResourceProfile(&'a str),
+}
+
+/// Nom unique du conteneur qui remplacera celui du projet : horodatage en millisecondes et suffixe
+/// aléatoire, pour que deux bascules rapprochées ne se disputent pas le même nom.
+#[must_use]
+pub fn new_container_name(state: &AppState, project: &Project) -> String
+{
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+
+    format!("{}-{}-{}-{}", state.config.app_prefix, project.name, timestamp, random_suffix())
+}
+
+fn random_suffix() -> String
+{
+    Alphanumeric.sample_string(&mut rand::rng(), NAME_SUFFIX_LENGTH).to_lowercase()
+}
+
+/// Remplace le conteneur du projet par celui décrit par `spec`, puis applique `updates` dans une même transaction.
+pub async fn execute(
+    state: &AppState,
+    orchestrator: &DeploymentOrchestrator<'_>,
+    project: &Project,
+    mut spec: NewContainerSpec,
+    updates: &[MetadataUpdate<'_>],
+) -> Result<(), AppError>
+{
+    // Un projet archivé n'a plus de conteneur : seul le désarchivage en recrée un.
+    project.ensure_not_archived()?;
+
+    let runtime = DockerRuntime { state, orchestrator, project };
+    let current = CurrentContainer
+    {
+        project_id: project.id,
+        container_name: &project.container_name,
+        stop_grace_seconds: project.stop_grace_seconds,
+    };
+
+    switch(&runtime, &current, &mut spec, updates).await?;
+
+    info!(
+        "Project '{}' switched to new container '{}'.",
+        project.name, spec.container_name
+    );
+    Ok(())
+}
+
+/// Nombre de vérifications de santé d'un nouveau conteneur et délai avant la deuxième.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthCheckPolicy
+{
+    pub max_attempts: u32,
+    pub interval: Duration,
+}
+
+impl HealthCheckPolicy
+{
+    #[must_use]
+    pub const fn from_config(config: &Config) -> Self
+    {
+        Self
+        {
+            max_attempts: config.health_check_max_attempts,
+            interval: Duration::from_millis(config.health_check_interval_ms),
+        }
+    }
+
+    /// Valeurs de l'instance, remplacées par celles que le projet surcharge.
+    #[must_use]
+    pub fn for_project(config: &Config, project: &Project) -> Self
+    {
+        let default = Self::from_config(config);
+        Self
+        {
+            max_attempts: project.health_check_max_attempts
+                .and_then(|attempts| u32::try_from(attempts).ok())
+                .unwrap_or(default.max_attempts),
+            interval: project.health_check_interval_ms
+                .and_then(|interval| u64::try_from(interval).ok())
+                .map_or(default.interval, Duration::from_millis),
+        }
+    }
+
+    /// Attente après l'échec de la vérification `attempt` (à partir de 0) : le délai initial est
+    /// doublé à chaque tentative, sans dépasser [`HEALTH_CHECK_MAX_INTERVAL`] (ou le délai initial s'il est plus long).
+    fn delay_after(&self, attempt: u32) -> Duration
+    {
+        let cap = HEALTH_CHECK_MAX_INTERVAL.max(self.interval);
+        self.interval.saturating_mul(2_u32.saturating_pow(attempt)).min(cap)
+    }
+}
+
+/// Attend que le conteneur soit démarré, selon `policy`. Une attente prolongée est signalée
+/// sur le canal du déploiement de `orchestrator`, s'il est fourni.
+pub async fn wait_for_container_health(
+    state: &AppState,
+    container_name: &str,
+    policy: HealthCheckPolicy,
+    orchestrator: Option<&DeploymentOrchestrator<'_>>,
+) -> Result<(), AppError>
+{
+    info!("Waiting for new container '{}' to be healthy...", container_name);
+    let started = Instant::now();
+
+    for attempt in 0..policy.max_attempts
+    {
+        if is_container_healthy(state, container_name).await?
+        {
+            info!("Container '{}' is healthy", container_name);
+            return Ok(());
+        }
+        if attempt + 1 == policy.max_attempts
+        {
+            break;
+        }
+
+        if (attempt + 1) % HEALTH_CHECK_PROGRESS_EVERY == 0
+        {
+            let elapsed_seconds = started.elapsed().as_secs();
+            info!("Still waiting for container '{}', {}s elapsed", container_name, elapsed_seconds);
+            if let Some(orchestrator) = orchestrator
+            {
+                orchestrator.emit_stage(DeploymentStage::HealthCheckProgress { elapsed_seconds }).await;
+            }
+        }
+        sleep(policy.delay_after(attempt)).await;
+    }
+
+    error!("Container '{}' did not become healthy in time", container_name);
+    Err(ProjectErrorCode::HealthCheckFailed.into())
+}
+
+async fn is_container_healthy(state: &AppState, container_name: &str) -> Result<bool, AppError>
+{
+    if let Ok(Some(details)) = docker_service::inspect_container_details(&state.docker_client, container_name).await
+        && let Some(container_state) = details.state
+        {
+            return Ok(container_state.running.unwrap_or(false));
+        }
+    Ok(false)
+}
+
+/// Déclare l'arrêt volontaire de `container_name` jusqu'à `now + stop_grace + marge`.
+pub fn expect_stop(stops: &Mutex<HashMap<String, Instant>>, container_name: &str, stop_grace_seconds: i32, now: Instant)
+{
+    let grace = Duration::from_secs(u64::try_from(stop_grace_seconds).unwrap_or(0));
+    if let Ok(mut stops) = stops.lock()
+    {
+        stops.insert(container_name.to_string(), now + grace + EXPECTED_STOP_MARGIN);
+    }
+}
+
+/// Indique si l'arrêt de `container_name` était attendu, en oubliant les fenêtres expirées.
+pub fn is_expected_stop(stops: &Mutex<HashMap<String, Instant>>, container_name: &str, now: Instant) -> bool
+{
+    let Ok(mut stops) = stops.lock() else { return false };
+    stops.retain(|_, until| *until > now);
+    stops.contains_key(container_name)
+}
+
+struct CurrentContainer<'a>
+{
+    project_id: i32,
+    container_name: &'a str,
+    stop_grace_seconds: i32,
+}
+
+/// Opérations d'une bascule, séparées de Docker et de la base pour être testées.
+trait SwitchRuntime
+{
+    async fn run_stage<T>(
+        &self,
+        before: DeploymentStage,
+        after: DeploymentStage,
+        operation_name: &str,
+        step: impl Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError>;
+    async fn emit_stage(&self, stage: DeploymentStage);
+    async fn create_container(&self, spec: &NewContainerSpec) -> Result<(), AppError>;
+    async fn wait_healthy(&self, container_name: &str) -> Result<(), AppError>;
+    async fn apply_metadata(&self, project_id: i32, container_name: &str, updates: &[MetadataUpdate<'_>]) -> Result<(), AppError>;
+    async fn remove_container(&self, container_name: &str, stop_grace_seconds: i32) -> Result<(), AppError>;
+    async fn remove_image(&self, image: &str) -> Result<(), AppError>;
+}
+
+/// Le nom du conteneur de `spec` est celui sous lequel il a finalement été créé.
+async fn switch(
+    runtime: &impl SwitchRuntime,
+    current: &CurrentContainer<'_>,
+    spec: &mut NewContainerSpec,
+    updates: &[MetadataUpdate<'_>],
+) -> Result<(), AppError>
+{
+    // Un conteneur dont le démarrage échoue est supprimé par `create_project_container`.
+    if let Err(e) = runtime.run_stage
+    (
+        DeploymentStage::CreatingContainer,
+        DeploymentStage::ContainerCreated,
+        "New container creation",
+        create_container_with_retries(runtime, spec),
+    ).await
+    {
+        rollback(runtime, spec, false).await;
+        return Err(e);
+    }
+
+    if let Err(e) = runtime.run_stage
+    (
+        DeploymentStage::WaitingHealthCheck,
+        DeploymentStage::HealthCheckPassed,
+        "Health check",
+        runtime.wait_healthy(&spec.container_name),
+    ).await
+    {
+        rollback(runtime, spec, true).await;
+        return Err(e);
+    }
+
+    if let Err(e) = runtime.apply_metadata(current.project_id, &spec.container_name, updates).await
+    {
+        error!("Failed to update project metadata. Rolling back new container '{}'...", spec.container_name);
+        rollback(runtime, spec, true).await;
+        return Err(e);
+    }
+
+    runtime.emit_stage(DeploymentStage::CleaningUp).await;
+
+    info!("Removing old container '{}'", current.container_name);
+    if let Err(e) = runtime.remove_container(current.container_name, current.stop_grace_seconds).await
+    {
+        warn!(
+            "Could not remove old container '{}', but update is successful. Manual cleanup may be needed. Error: {}",
+            current.container_name, e
+        );
+    }
+
+    if let Some(image) = &spec.replaced_image
+        && let Err(e) = runtime.remove_image(image).await
+    {
+        warn!("Could not remove old image '{}': {}", image, e);
+    }
+
+    Ok(())
+}
+
+/// Crée le nouveau conteneur ; un nom déjà pris est retenté avec un autre suffixe aléatoire.
+async fn create_container_with_retries(runtime: &impl SwitchRuntime, spec: &mut NewContainerSpec) -> Result<(), AppError>
+{
+    let base_name = spec.container_name.clone();
+    let mut retries = 0;
+    loop
+    {
+        match runtime.create_container(spec).await
+        {
+            Err(AppError::ProjectError(ProjectErrorCode::ContainerNameConflict)) if retries < NAME_CONFLICT_RETRIES =>
+            {
+                retries += 1;
+                spec.container_name = format!("{base_name}-{}", random_suffix());
+                warn!("Container name '{}' is already in use, retrying as '{}'", base_name, spec.container_name);
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn rollback(runtime: &impl SwitchRuntime, spec: &NewContainerSpec, container_created: bool)
+{
+    if container_created
+        && let Err(e) = runtime.remove_container(&spec.container_name, docker_service::DEFAULT_STOP_GRACE_SECONDS).await
+    {
+        warn!("ROLLBACK FAILED: Could not remove new container '{}': {}", spec.container_name, e);
+    }
+
+    if let Some(image) = &spec.rollback_image
+        && let Err(e) = runtime.remove_image(image).await
+    {
+        warn!("ROLLBACK FAILED: Could not remove new image '{}': {}", image, e);
+    }
+}
+
+struct DockerRuntime<'a>
+{
+    state: &'a AppState,
+    orchestrator: &'a DeploymentOrchestrator<'a>,
+    project: &'a Project,
+}
+
+impl SwitchRuntime for DockerRuntime<'_>
+{
+    async fn run_stage<T>(
+        &self,
+        before: DeploymentStage,
+        after: DeploymentStage,
+        operation_name: &str,
+        step: impl Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError>
+    {
+        self.orchestrator.with_stages(before, after, operation_name, step).await
+    }
+
+    async fn emit_stage(&self, stage: DeploymentStage)
+    {
+        self.orchestrator.emit_stage(stage).await;
+    }
+
+    async fn create_container(&self, spec: &NewContainerSpec) -> Result<(), AppError>
+    {
+        info!("Creating new container '{}' for project '{}'", spec.container_name, self.project.name);
+        let custom_domains = domain_service::verified_domains(&self.state.db_pool, self.project.id).await?;
+        let owners = project_service::routing_owners(&self.state.db_pool, &self.project.container_name).await;
+
+        docker_service::create_project_container(
+            &self.state.docker_client,
+            self.project.id,
+            &spec.container_name,
+            &self.project.name,
+            &spec.image,
+            &self.state.config,
+            &spec.env_vars,
+            &self.project.persistent_volume_path,
+            &spec.command,
+            self.project.timezone.as_deref(),
+            spec.container_port,
+            &custom_domains,
+            self.state.config.resource_profile(&spec.resource_profile),
+            &owners,
+        ).await
+        .inspect_err(|e| emit_if_network_missing(self.state, e))
+        .map(|_| ())
+    }
+
+    async fn wait_healthy(&self, container_name: &str) -> Result<(), AppError>
+    {
+        let policy = HealthCheckPolicy::for_project(&self.state.config, self.project);
+        wait_for_container_health(self.state, container_name, policy, Some(self.orchestrator)).await
+    }
+
+    async fn apply_metadata(&self, project_id: i32, container_name: &str, updates: &[MetadataUpdate<'_>]) -> Result<(), AppError>
+    {
+        let mut tx = self.state.db_pool.begin().await.map_err(|e|
+        {
+            error!("Failed to start metadata transaction for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+        project_service::update_project_container_name(&mut *tx, project_id, container_name).await?;
+
+        for update in updates
+        {
+            match update
+            {
+                MetadataUpdate::Image { tag, digest, source_url, commit_sha } =>
+                {
+                    project_service::update_project_image(&mut *tx, project_id, tag, digest, *commit_sha, *source_url).await?;
+                }
+                MetadataUpdate::SourceRef(source_ref) =>
+                {
+                    project_service::update_project_source_ref(&mut *tx, project_id, source_ref).await?;
+                }
+                MetadataUpdate::EnvVars(env_vars) =>
+                {
+                    project_service::update_project_env_vars(&mut *tx, project_id, env_vars, &self.state.config.encryption_key).await?;
+                }
+                MetadataUpdate::Command(command) =>
+                {
+                    project_service::update_project_command(&mut *tx, project_id, command).await?;
+                }
+                MetadataUpdate::Port(container_port) =>
+                {
+                    project_service::update_project_port(&mut *tx, project_id, *container_port).await?;
+                }
+                MetadataUpdate::ResourceProfile(resource_profile) =>
+                {
+                    project_service::update_project_resource_profile(&mut *tx, project_id, resource_profile).await?;
+                }
+            }
+        }
+
+        tx.commit().await.map_err(|e|
+        {
+            error!("Failed to commit metadata of project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+    }
+
+    async fn remove_container(&self, container_name: &str, stop_grace_seconds: i32) -> Result<(), AppError>
+    {
+        expect_stop(&self.state.expected_stops, container_name, stop_grace_seconds, Instant::now());
+        docker_service::remove_container(&self.state.docker_client, container_name, stop_grace_seconds).await.map_err(AppError::from)
+    }
+
+    async fn remove_image(&self, image: &str) -> Result<(), AppError>
+    {
+        docker_service::remove_image(&self.state.docker_client, image).await.map_err(AppError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures::executor::block_on;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockRuntime
+    {
+        /// Créations refusées pour conflit de nom avant la première qui réussit.
+        name_conflicts: Mutex<u32>,
+        fail_health: bool,
+        fail_metadata: bool,
+        fail_old_removal: bool,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl MockRuntime
+    {
+        fn record(&self, call: String)
+        {
+            self.calls.lock().unwrap().push(call);
+        }
+
+        fn calls(&self) -> Vec<String>
+        {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl SwitchRuntime for MockRuntime
+    {
+        async fn run_stage<T>(
+            &self,
+            before: DeploymentStage,
+            after: DeploymentStage,
+            _operation_name: &str,
+            step: impl Future<Output = Result<T, AppError>>,
+        ) -> Result<T, AppError>
+        {
+            self.record(format!("stage:{before:?}"));
+            let result = step.await;
+            if result.is_ok()
+            {
+                self.record(format!("stage:{after:?}"));
+            }
+            result
+        }
+
+        async fn emit_stage(&self, stage: DeploymentStage)
+        {
+            self.record(format!("stage:{stage:?}"));
+        }
+
+        async fn create_container(&self, spec: &NewContainerSpec) -> Result<(), AppError>
+        {
+            self.record(format!("create:{}", spec.container_name));
+            let mut conflicts = self.name_conflicts.lock().unwrap();
+            if *conflicts > 0
+            {
+                *conflicts -= 1;
+                return Err(ProjectErrorCode::ContainerNameConflict.into());
+            }
+            Ok(())
+        }
+
+        async fn wait_healthy(&self, _container_name: &str) -> Result<(), AppError>
+        {
+            if self.fail_health { Err(AppError::InternalServerError) } else { Ok(()) }
+        }
+
+        async fn apply_metadata(&self, _project_id: i32, container_name: &str, updates: &[MetadataUpdate<'_>]) -> Result<(), AppError>
+        {
+            if self.fail_metadata
+            {
+                return Err(AppError::InternalServerError);
+            }
+            self.record(format!("metadata:{container_name}"));
+            for update in updates
+            {
+                if let MetadataUpdate::Image { digest, .. } = update
+                {
+                    self.record(format!("metadata_image:{digest}"));
+                }
+            }
+            Ok(())
+        }
+
+        async fn remove_container(&self, container_name: &str, _stop_grace_seconds: i32) -> Result<(), AppError>
+        {
+            self.record(format!("remove_container:{container_name}"));
+            if self.fail_old_removal && container_name == "old" { Err(AppError::InternalServerError) } else { Ok(()) }
+        }
+
+        async fn remove_image(&self, image: &str) -> Result<(), AppError>
+        {
+            self.record(format!("remove_image:{image}"));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_health_check_delay_doubles_up_to_the_cap()
+    {
+        let policy = HealthCheckPolicy { max_attempts: 10, interval: Duration::from_millis(500) };
+        let delays: Vec<u128> = (0..6).map(|attempt| policy.delay_after(attempt).as_millis()).collect();
+        assert_eq!(delays, [500, 1000, 2000, 4000, 5000, 5000]);
+        assert_eq!(policy.delay_after(u32::MAX), HEALTH_CHECK_MAX_INTERVAL);
+
+        // Un délai initial plus long que le plafond reste constant.
+        let slow = HealthCheckPolicy { max_attempts: 3, interval: Duration::from_secs(8) };
+        assert_eq!(slow.delay_after(0), Duration::from_secs(8));
+        assert_eq!(slow.delay_after(4), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_expected_stop_window_expires()
+    {
+        let stops = Mutex::new(HashMap::new());
+        let now = Instant::now();
+        expect_stop(&stops, "old", 10, now);
+
+        assert!(is_expected_stop(&stops, "old", now));
+        assert!(is_expected_stop(&stops, "old", now + Duration::from_secs(39)));
+        assert!(!is_expected_stop(&stops, "other", now));
+        assert!(!is_expected_stop(&stops, "old", now + Duration::from_secs(40)));
+        assert!(stops.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expected_stop_window_is_renewed()
+    {
+        let stops = Mutex::new(HashMap::new());
+        let now = Instant::now();
+        expect_stop(&stops, "old", 0, now);
+        expect_stop(&stops, "old", 60, now + Duration::from_secs(20));
+
+        assert!(is_expected_stop(&stops, "old", now + Duration::from_secs(100)));
+        assert!(!is_expected_stop(&stops, "old", now + Duration::from_secs(110)));
+    }
+
+    const CURRENT: CurrentContainer<'static> = CurrentContainer { project_id: 1, container_name: "old", stop_grace_seconds: 10 };
+
+    /// Entrées de la mise à jour d'image, de la reconstruction et de la recréation.
+    fn entry_points(env_vars: &HashMap<String, String>) -> Vec<(&'static str, NewContainerSpec, MetadataUpdate<'_>)>
+    {
+        let spec = |rollback_image: Option<&str>, replaced_image: Option<&str>| NewContainerSpec
+        {
+            container_name: "new".to_string(),
+            image: "image@sha256:new".to_string(),
+            env_vars: None,
+            command: ContainerCommand::default(),
+            container_port: 80,
+            resource_profile: "default".to_string(),
+            rollback_image: rollback_image.map(str::to_string),
+            replaced_image: replaced_image.map(str::to_string),
+        };
+
+        vec![
+            ("image_update", spec(Some("nginx:1.27"), Some("nginx:1.27")),
+                MetadataUpdate::Image { tag: "nginx:1.27", digest: "sha256:new", source_url: Some("nginx:1.27"), commit_sha: None }),
+            ("rebuild", spec(Some("hangar-local/app:2"), Some("hangar-local/app:1")),
+                MetadataUpdate::Image { tag: "hangar-local/app:2", digest: "sha256:new", source_url: None, commit_sha: Some("4f2c9e1") }),
+            ("env_update", spec(None, None), MetadataUpdate::EnvVars(env_vars)),
+        ]
+    }
+
+    #[test]
+    fn test_health_check_failure_removes_new_container_and_keeps_old()
+    {
+        let env_vars = HashMap::new();
+        for (entry_point, mut spec, update) in entry_points(&env_vars)
+        {
+            let runtime = MockRuntime { fail_health: true, ..Default::default() };
+            assert!(block_on(switch(&runtime, &CURRENT, &mut spec, std::slice::from_ref(&update))).is_err(), "{entry_point}");
+
+            let calls = runtime.calls();
+            assert!(calls.contains(&"remove_container:new".to_string()), "{entry_point}");
+            assert!(!calls.contains(&"remove_container:old".to_string()), "{entry_point}");
+            assert!(!calls.iter().any(|call| call.starts_with("metadata:")), "{entry_point}");
+            assert!(!calls.contains(&"stage:HealthCheckPassed".to_string()), "{entry_point}");
+            match &spec.rollback_image
+            {
+                Some(image) => assert!(calls.contains(&format!("remove_image:{image}")), "{entry_point}"),
+                None => assert!(!calls.iter().any(|call| call.starts_with("remove_image:")), "{entry_point}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_metadata_failure_removes_new_container_and_keeps_old()
+    {
+        let env_vars = HashMap::new();
+        for (entry_point, mut spec, update) in entry_points(&env_vars)
+        {
+            let runtime = MockRuntime { fail_metadata: true, ..Default::default() };
+            assert!(block_on(switch(&runtime, &CURRENT, &mut spec, std::slice::from_ref(&update))).is_err(), "{entry_point}");
+
+            let calls = runtime.calls();
+            assert!(calls.contains(&"stage:HealthCheckPassed".to_string()), "{entry_point}");
+            assert!(calls.contains(&"remove_container:new".to_string()), "{entry_point}");
+            assert!(!calls.contains(&"remove_container:old".to_string()), "{entry_point}");
+            assert!(!calls.contains(&"stage:CleaningUp".to_string()), "{entry_point}");
+            if let Some(image) = &spec.rollback_image
+            {
+                assert!(calls.contains(&format!("remove_image:{image}")), "{entry_point}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_old_resources_are_removed_only_after_the_metadata_commit()
+    {
+        let env_vars = HashMap::new();
+        for (entry_point, mut spec, update) in entry_points(&env_vars)
+        {
+            let runtime = MockRuntime::default();
+            assert!(block_on(switch(&runtime, &CURRENT, &mut spec, std::slice::from_ref(&update))).is_ok(), "{entry_point}");
+
+            let calls = runtime.calls();
+            let position = |call: &str| calls.iter().position(|recorded| recorded == call).unwrap();
+            assert!(position("metadata:new") < position("remove_container:old"), "{entry_point}");
+            if let Some(image) = &spec.replaced_image
+            {
+                assert!(position("remove_container:old") < position(&format!("remove_image:{image}")), "{entry_point}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_old_container_cleanup_failure_keeps_the_switch()
+    {
+        let env_vars = HashMap::new();
+        for (entry_point, mut spec, update) in entry_points(&env_vars)
+        {
+            let runtime = MockRuntime { fail_old_removal: true, ..Default::default() };
+            assert!(block_on(switch(&runtime, &CURRENT, &mut spec, std::slice::from_ref(&update))).is_ok(), "{entry_point}");
+
+            let calls = runtime.calls();
+            let stages: Vec<&str> = calls.iter().map(String::as_str).filter(|call| call.starts_with("stage:")).collect();
+            assert_eq!(
+                stages,
+                ["stage:CreatingContainer", "stage:ContainerCreated", "stage:WaitingHealthCheck", "stage:HealthCheckPassed", "stage:CleaningUp"],
+                "{entry_point}"
+            );
+            assert!(calls.contains(&"metadata:new".to_string()), "{entry_point}");
+            assert!(calls.contains(&"remove_container:old".to_string()), "{entry_point}");
+            assert!(!calls.contains(&"remove_container:new".to_string()), "{entry_point}");
+            if let Some(image) = &spec.replaced_image
+            {
+                assert!(calls.contains(&format!("remove_image:{image}")), "{entry_point}");
+            }
+        }
+    }
+    #[test]
+    fn test_name_conflict_is_retried_under_another_name()
+    {
+        let env_vars = HashMap::new();
+        for (entry_point, mut spec, update) in entry_points(&env_vars)
+        {
+            let runtime = MockRuntime { name_conflicts: Mutex::new(2), ..Default::default() };
+            assert!(block_on(switch(&runtime, &CURRENT, &mut spec, std::slice::from_ref(&update))).is_ok(), "{entry_point}");
+
+            let calls = runtime.calls();
+            let creations: Vec<&String> = calls.iter().filter(|call| call.starts_with("create:")).collect();
+            assert_eq!(creations.len(), 3, "{entry_point}");
+            assert!(spec.container_name.starts_with("new-"), "{entry_point}");
+            assert!(calls.contains(&format!("metadata:{}", spec.container_name)), "{entry_point}");
+        }
+    }
+
+    #[test]
+    fn test_persistent_name_conflict_is_reported()
+    {
+        let env_vars = HashMap::new();
+        for (entry_point, mut spec, update) in entry_points(&env_vars)
+        {
+            let runtime = MockRuntime { name_conflicts: Mutex::new(NAME_CONFLICT_RETRIES + 1), ..Default::default() };
+            let result = block_on(switch(&runtime, &CURRENT, &mut spec, std::slice::from_ref(&update)));
+
+            assert!(matches!(result, Err(AppError::ProjectError(ProjectErrorCode::ContainerNameConflict))), "{entry_point}");
+            assert!(!runtime.calls().iter().any(|call| call.starts_with("remove_container:")), "{entry_point}");
+        }
+    }
+
+    #[test]
+    fn test_env_update_with_rebuilt_image_records_the_new_image()
+    {
+        let env_vars = HashMap::new();
+        let mut spec = NewContainerSpec
+        {
+            container_name: "new".to_string(),
+            image: "sha256:rebuilt".to_string(),
+            env_vars: None,
+            command: ContainerCommand::default(),
+            container_port: 80,
+            resource_profile: "default".to_string(),
+            rollback_image: Some("hangar-local/app:2".to_string()),
+            replaced_image: None,
+        };
+        let updates = [
+            MetadataUpdate::EnvVars(&env_vars),
+            MetadataUpdate::Image { tag: "hangar-local/app:2", digest: "sha256:rebuilt", source_url: None, commit_sha: Some("4f2c9e1") },
+        ];
+
+        let runtime = MockRuntime::default();
+        assert!(block_on(switch(&runtime, &CURRENT, &mut spec, &updates)).is_ok());
+
+        let calls = runtime.calls();
+        assert!(calls.contains(&"metadata:new".to_string()));
+        assert!(calls.contains(&"metadata_image:sha256:rebuilt".to_string()));
+        assert!(!calls.iter().any(|call| call.starts_with("remove_image:")));
+    }
+
+    /// Nom du conteneur et image enregistrés du projet `project_id`.
+    async fn recorded_deployment(pool: &sqlx::PgPool, project_id: i32) -> (String, String, String, Option<String>, Option<String>)
+    {
+        sqlx::query_as("SELECT container_name, deployed_image_tag, deployed_image_digest, previous_image_tag, source_commit_sha FROM projects WHERE id = $1")
+            .bind(project_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_failed_image_update_leaves_the_project_untouched(pool: sqlx::PgPool)
+    {
+        let project_id = crate::test_support::insert_project(&pool, "demo", "alice", &[]).await;
+        // Fait échouer la mise à jour de l'image, après celle du nom du conteneur dans la même transaction.
+        sqlx::raw_sql(
+            "CREATE FUNCTION reject_failing_image() RETURNS trigger AS $$ BEGIN \
+                 IF NEW.deployed_image_tag = 'fail:1' THEN RAISE EXCEPTION 'rejected image'; END IF; RETURN NEW; \
+             END $$ LANGUAGE plpgsql; \
+             CREATE TRIGGER reject_failing_image BEFORE UPDATE ON projects FOR EACH ROW EXECUTE FUNCTION reject_failing_image();"
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+        let before = recorded_deployment(&pool, project_id).await;
+
+        let state = crate::test_support::state_with(crate::test_support::config(), pool.clone(), crate::test_support::UNREACHABLE_DOCKER);
+        let project = project_service::get_project_by_id(&pool, project_id).await.unwrap().unwrap();
+        let orchestrator = DeploymentOrchestrator::for_update(&state, project.name.clone(), project.owner.clone(), project.id);
+        let runtime = DockerRuntime { state: &state, orchestrator: &orchestrator, project: &project };
+
+        let failing = [MetadataUpdate::Image { tag: "fail:1", digest: "sha256:2222", source_url: Some("fail:1"), commit_sha: Some("4f2c9e1") }];
+        assert!(runtime.apply_metadata(project_id, "hangar-demo-2", &failing).await.is_err());
+        assert_eq!(recorded_deployment(&pool, project_id).await, before);
+
+        let applied = [MetadataUpdate::Image { tag: "nginx:2", digest: "sha256:2222", source_url: Some("nginx:2"), commit_sha: None }];
+        runtime.apply_metadata(project_id, "hangar-demo-2", &applied).await.unwrap();
+        assert_eq!(
+            recorded_deployment(&pool, project_id).await,
+            ("hangar-demo-2".to_string(), "nginx:2".to_string(), "sha256:2222".to_string(), Some("nginx:1".to_string()), None)
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use tempfile::{Builder as TempBuilder, TempDir};
+use tokio::process::Command;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    error::AppError,
+    services::deployment_service,
+    state::AppState,
+};
+
+/// Préfixe des espaces de travail : le balayage ne touche à aucun autre répertoire de `BUILD_WORKDIR`.
+const WORKSPACE_PREFIX: &str = "hangar-build-";
+/// Intervalle entre deux balayages des espaces de travail.
+const WORKSPACE_SWEEP_INTERVAL: Duration = Duration::from_secs(600);
+/// Délai suggéré au client lorsque l'espace disque manque pour un build.
+const LOW_SPACE_RETRY_AFTER_SECONDS: u64 = 300;
+
+/// Préfixe du répertoire d'un build : le déploiement suivi, ou le projet en cours de création.
+#[must_use]
+pub fn workspace_prefix(deployment_id: Option<i32>, project_name: &str) -> String
+{
+    match deployment_id
+    {
+        Some(id) => format!("{WORKSPACE_PREFIX}deployment-{id}-"),
+        None => format!("{WORKSPACE_PREFIX}creation-{project_name}-"),
+    }
+}
+
+/// Déploiement auquel appartient un espace de travail, d'après son nom.
+fn workspace_deployment_id(name: &str) -> Option<i32>
+{
+    name.strip_prefix(WORKSPACE_PREFIX)?
+        .strip_prefix("deployment-")?
+        .split('-')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Crée l'espace de travail d'un build sous `BUILD_WORKDIR`, après avoir vérifié l'espace libre.
+///
+/// Le répertoire est supprimé à la fin du build ; le balayage rattrape ceux qui survivent à un arrêt brutal.
+pub async fn create_workspace(state: &AppState, deployment_id: Option<i32>, project_name: &str) -> Result<TempDir, AppError>
+{
+    let workdir = Path::new(&state.config.build_workdir);
+    tokio::fs::create_dir_all(workdir).await.map_err(|e|
+    {
+        error!("Failed to create build workdir '{}': {}", workdir.display(), e);
+        AppError::InternalServerError
+    })?;
+
+    ensure_free_space(workdir, state.config.build_workdir_min_free_mb).await?;
+
+    TempBuilder::new()
+        .prefix(&workspace_prefix(deployment_id, project_name))
+        .tempdir_in(workdir)
+        .map_err(|e|
+        {
+            error!("Failed to create build workspace in '{}': {}", workdir.display(), e);
+            AppError::InternalServerError
+        })
+}
+
+async fn ensure_free_space(workdir: &Path, min_free_mb: u64) -> Result<(), AppError>
+{
+    if min_free_mb == 0
+    {
+        return Ok(());
+    }
+
+    // Une mesure impossible ne bloque pas les builds : le clone échouera de lui-même si le disque est plein.
+    let available_kb = match available_space_kb(workdir).await
+    {
+        Ok(available_kb) => available_kb,
+        Err(e) =>
+        {
+            warn!("Could not measure free space in build workdir '{}': {}", workdir.display(), e);
+            return Ok(());
+        }
+    };
+
+    if available_kb / 1024 < min_free_mb
+    {
+        warn!(
+            "Refusing build: only {} MB free in '{}' (minimum {} MB)",
+            available_kb / 1024, workdir.display(), min_free_mb
+        );
+        return Err(AppError::ServiceUnavailable
+        {
+            message: "Not enough disk space to build the project, please retry later".to_string(),
+            retry_after_seconds: LOW_SPACE_RETRY_AFTER_SECONDS,
+        });
+    }
+
+    Ok(())
+}
+
+async fn available_space_kb(dir: &Path) -> Result<u64, String>
+{
+    let output = Command::new("df")
+        .arg("-Pk")
+        .arg(dir)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("failed to run df: {e}"))?;
+
+    if !output.status.success()
+    {
+        return Err(format!("df exited with {}", output.status));
+    }
+
+    parse_df_available_kb(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "unexpected df output".to_string())
+}
+
+/// Colonne `Available` de la sortie POSIX de `df -Pk`.
+fn parse_df_available_kb(output: &str) -> Option<u64>
+{
+    output.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()
+}
+
+/// Indique si un espace de travail doit être supprimé.
+///
+/// `running` vaut `None` lorsque la base est injoignable : seul l'âge est alors pris en compte.
+fn is_stale(
+    age: Option<Duration>,
+    max_age: Duration,
+    deployment_id: Option<i32>,
+    running: Option<&HashSet<i32>>,
+) -> bool
+{
+    let expired = age.is_some_and(|age| age > max_age);
+    let finished = deployment_id.zip(running).is_some_and(|(id, running)| !running.contains(&id));
+    expired || finished
+}
+
+/// Tâche de fond : supprime les espaces de travail orphelins au démarrage, puis périodiquement.
+pub async fn start_workspace_sweeper(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting build workspace sweeper");
+    // Aucun build ne tourne encore : tout espace de travail restant provient d'une exécution précédente.
+    sweep_workspaces(&state, true).await;
+
+    let mut interval = tokio::time::interval(WORKSPACE_SWEEP_INTERVAL);
+    interval.tick().await;
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Build workspace sweeper shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        sweep_workspaces(&state, false).await;
+    }
+}
+
+async fn sweep_workspaces(state: &AppState, at_startup: bool)
+{
+    let workdir = Path::new(&state.config.build_workdir);
+    let mut entries = match tokio::fs::read_dir(workdir).await
+    {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) =>
+        {
+            warn!("Could not read build workdir '{}': {}", workdir.display(), e);
+            return;
+        }
+    };
+
+    let mut workspaces: Vec<(PathBuf, Option<i32>, Option<Duration>)> = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await
+    {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with(WORKSPACE_PREFIX)
+        {
+            continue;
+        }
+
+        let age = entry.metadata().await.ok()
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| modified.elapsed().ok());
+        workspaces.push((entry.path(), workspace_deployment_id(&name), age));
+    }
+
+    if workspaces.is_empty()
+    {
+        return;
+    }
+
+    let max_age = Duration::from_secs(state.config.build_workspace_max_age_minutes * 60);
+    let running = if at_startup
+    {
+        None
+    }
+    else
+    {
+        let deployment_ids: Vec<i32> = workspaces.iter().filter_map(|(_, id, _)| *id).collect();
+        deployment_service::running_deployment_ids(&state.db_pool, &deployment_ids).await
+            .ok()
+            .map(|ids| ids.into_iter().collect::<HashSet<i32>>())
+    };
+
+    for (path, deployment_id, age) in workspaces
+    {
+        if !at_startup && !is_stale(age, max_age, deployment_id, running.as_ref())
+        {
+            continue;
+        }
+
+        info!("Removing stale build workspace '{}'", path.display());
+        if let Err(e) = tokio::fs::remove_dir_all(&path).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("Failed to remove build workspace '{}': {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_name_identifies_deployment()
+    {
+        let name = format!("{}a1B2c3", workspace_prefix(Some(42), "my-app"));
+        assert_eq!(workspace_deployment_id(&name), Some(42));
+
+        let name = format!("{}a1B2c3", workspace_prefix(None, "my-app"));
+        assert_eq!(workspace_deployment_id(&name), None);
+    }
+
+    #[test]
+    fn test_parse_df_available_kb()
+    {
+        let output = "Filesystem     1024-blocks     Used Available Capacity Mounted on\n\
+                      /dev/sda1         41152736 30564520   8474784      79% /\n";
+        assert_eq!(parse_df_available_kb(output), Some(8_474_784));
+        assert_eq!(parse_df_available_kb("Filesystem\n"), None);
+    }
+
+    #[test]
+    fn test_is_stale()
+    {
+        let max_age = Duration::from_secs(3600);
+        let running: HashSet<i32> = [7].into_iter().collect();
+        let young = Some(Duration::from_secs(60));
+
+        assert!(!is_stale(young, max_age, Some(7), Some(&running)));
+        assert!(is_stale(young, max_age, Some(8), Some(&running)));
+        assert!(!is_stale(young, max_age, Some(8), None));
+        assert!(!is_stale(young, max_age, None, Some(&running)));
+        assert!(is_stale(Some(Duration::from_secs(7200)), max_age, Some(7), Some(&running)));
+    }
+}
@@ -0,0 +1,242 @@
+//! Nettoyage des projets dont le propriétaire a quitté l'école (compte CAS désactivé).
+//!
+//! Un administrateur signale le projet, les participants sont prévenus, puis le projet est
+//! archivé (conteneur arrêté) à la fin du délai de grâce. Le signalement reste annulable
+//! jusqu'à l'archivage.
+
+use std::time::Duration;
+
+use serde_json::json;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    error::AppError,
+    model::{cleanup::{OwnerlessProject, ProjectCleanup}, notification::NotificationKind},
+    services::{audit_service, docker_service, notification_service, project_service},
+    sse::types::SystemEvent,
+    state::AppState,
+};
+
+/// Intervalle entre deux recherches de projets à archiver.
+const ARCHIVE_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+/// Acteur enregistré dans le journal d'audit pour l'archivage automatique.
+const SYSTEM_ACTOR: &str = "system";
+
+/// Projets dont le propriétaire ne s'est pas connecté depuis `inactivity_days` jours,
+/// ainsi que tous les projets déjà signalés.
+pub async fn list_ownerless_projects(pool: &PgPool, inactivity_days: i32) -> Result<Vec<OwnerlessProject>, AppError>
+{
+    sqlx::query_as(
+        "SELECT p.id AS project_id, p.name AS project_name, p.owner, u.last_login_at AS owner_last_login_at, \
+                c.flagged_at, c.archive_after, c.archived_at \
+         FROM projects p \
+         LEFT JOIN users u ON u.login = p.owner \
+         LEFT JOIN project_cleanups c ON c.project_id = p.id \
+         WHERE u.last_login_at IS NULL \
+            OR u.last_login_at < NOW() - make_interval(days => $1) \
+            OR c.project_id IS NOT NULL \
+         ORDER BY u.last_login_at ASC NULLS FIRST, p.id"
+    )
+    .bind(inactivity_days)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to list ownerless projects: {}", e);
+        AppError::InternalServerError
+    })
+}
+
+/// Signale un projet : il sera archivé après `grace_days` jours.
+pub async fn flag_project(
+    pool: &PgPool,
+    project_id: i32,
+    flagged_by: &str,
+    reason: Option<&str>,
+    grace_days: i32,
+) -> Result<ProjectCleanup, AppError>
+{
+    let cleanup: Option<ProjectCleanup> = sqlx::query_as(
+        "INSERT INTO project_cleanups (project_id, flagged_by, reason, archive_after) \
+         VALUES ($1, $2, $3, NOW() + make_interval(days => $4)) \
+         ON CONFLICT (project_id) DO NOTHING RETURNING *"
+    )
+    .bind(project_id)
+    .bind(flagged_by)
+    .bind(reason)
+    .bind(grace_days)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to flag project ID {} for cleanup: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+
+    cleanup.ok_or_else(|| AppError::BadRequest("This project is already flagged for cleanup.".to_string()))
+}
+
+/// Annule le signalement d'un projet, tant qu'il n'a pas été archivé.
+pub async fn cancel_flag(pool: &PgPool, project_id: i32) -> Result<ProjectCleanup, AppError>
+{
+    let cancelled: Option<ProjectCleanup> = sqlx::query_as(
+        "DELETE FROM project_cleanups WHERE project_id = $1 AND archived_at IS NULL RETURNING *"
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to cancel cleanup of project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+
+    if let Some(cleanup) = cancelled
+    {
+        return Ok(cleanup);
+    }
+
+    let archived: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM project_cleanups WHERE project_id = $1)")
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch cleanup of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    if archived
+    {
+        Err(AppError::BadRequest("This project has already been archived.".to_string()))
+    }
+    else
+    {
+        Err(AppError::NotFound(format!("Project with ID {project_id} is not flagged for cleanup.")))
+    }
+}
+
+/// Tâche de fond : archive toutes les heures les projets dont le délai de grâce est écoulé.
+pub async fn start_archive_scheduler(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting ownerless projects archive task");
+    let mut interval = tokio::time::interval(ARCHIVE_CHECK_INTERVAL);
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Ownerless projects archive task shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        archive_due_projects(&state).await;
+    }
+}
+
+async fn archive_due_projects(state: &AppState)
+{
+    let due: Vec<i32> = match sqlx::query_scalar(
+        "SELECT c.project_id FROM project_cleanups c JOIN projects p ON p.id = c.project_id \
+         WHERE c.archived_at IS NULL AND c.archive_after <= NOW() AND NOT p.archived"
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(due) => due,
+        Err(e) =>
+        {
+            error!("Failed to list projects due for archiving: {}", e);
+            return;
+        }
+    };
+
+    for project_id in due
+    {
+        if let Err(e) = archive_project(state, project_id, SYSTEM_ACTOR).await
+        {
+            error!("Failed to archive project ID {}: {}", project_id, e);
+        }
+    }
+}
+
+/// Archive immédiatement un projet, sans délai de grâce.
+///
+/// Renvoie `false` si le projet était déjà archivé.
+pub async fn archive_now(state: &AppState, project_id: i32, archived_by: &str, reason: &str) -> Result<bool, AppError>
+{
+    let flagged: Option<i32> = sqlx::query_scalar(
+        "INSERT INTO project_cleanups (project_id, flagged_by, reason, archive_after) VALUES ($1, $2, $3, NOW()) \
+         ON CONFLICT (project_id) DO UPDATE SET archive_after = NOW(), updated_at = NOW() \
+         WHERE project_cleanups.archived_at IS NULL RETURNING project_id"
+    )
+    .bind(project_id)
+    .bind(archived_by)
+    .bind(reason)
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to flag project ID {} for immediate archiving: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+
+    if flagged.is_none()
+    {
+        return Ok(false);
+    }
+
+    archive_project(state, project_id, archived_by).await?;
+    Ok(true)
+}
+
+/// Archivage définitif : le conteneur est arrêté, les données (volume, base, image) sont conservées.
+async fn archive_project(state: &AppState, project_id: i32, actor: &str) -> Result<(), AppError>
+{
+    let Some(project) = project_service::get_project_by_id(&state.db_pool, project_id).await?
+    else
+    {
+        return Ok(());
+    };
+
+    if let Err(e) = docker_service::stop_container_by_name(&state.docker_client, &project.container_name, project.stop_grace_seconds).await
+    {
+        warn!("Could not stop container '{}' while archiving project '{}': {}", project.container_name, project.name, e);
+    }
+
+    sqlx::query("UPDATE project_cleanups SET archived_at = NOW(), updated_at = NOW() WHERE project_id = $1 AND archived_at IS NULL")
+        .bind(project_id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to mark project ID {} as archived: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    info!("Project '{}' (owner '{}') archived by '{}'", project.name, project.owner, actor);
+
+    audit_service::record(
+        state,
+        actor,
+        audit_service::ACTION_PROJECT_ARCHIVED,
+        Some(project_id),
+        Some(json!({ "owner": project.owner, "container_name": project.container_name })),
+    ).await;
+
+    notification_service::notify_project_members(
+        state,
+        &project,
+        NotificationKind::ProjectCleanup,
+        SystemEvent::warning(format!("Project '{}' has been archived: its owner is no longer active.", project.name)),
+    ).await;
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Vérification des secrets chiffrés (variables d'environnement, mots de passe des bases, clés des
+//! buckets) : chaque valeur doit se déchiffrer avec `APP_ENCRYPTION_KEY`. Changer la clé impose un
+//! redémarrage ; la vérification tourne donc au démarrage puis chaque semaine, et à la demande.
+//! Aucune valeur en clair n'est conservée ni renvoyée.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use base64::prelude::*;
+use serde_json::json;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tracing::{error, info};
+
+use crate::
+{
+    error::AppError,
+    model::crypto::{EncryptedSource, EncryptionCheck, EncryptionReport},
+    services::crypto_service,
+    sse::{emitter::emit_admin_event, types::SystemEvent},
+    state::AppState,
+};
+
+/// Lignes lues par requête : la mémoire reste bornée quel que soit le nombre de projets.
+const VERIFY_BATCH_SIZE: i64 = 200;
+/// Intervalle entre deux vérifications automatiques ; la première a lieu au démarrage.
+const VERIFY_INTERVAL: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Lignes en échec détaillées dans l'événement administrateur.
+const MAX_REPORTED_FAILURES: usize = 20;
+
+pub async fn start_crypto_verifier(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting encrypted secrets verification task");
+    let mut interval = tokio::time::interval(VERIFY_INTERVAL);
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Encrypted secrets verification task shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        match verify_all(&state.db_pool, &state.config.encryption_key).await
+        {
+            Ok(report) => notify_failures(&state, &report),
+            Err(e) => error!("Failed to verify encrypted secrets: {}", e),
+        }
+    }
+}
+
+/// Vérifie toutes les colonnes chiffrées, par lots de `VERIFY_BATCH_SIZE` lignes.
+pub async fn verify_all(pool: &PgPool, key: &[u8]) -> Result<EncryptionReport, AppError>
+{
+    let mut report = EncryptionReport::default();
+
+    let mut last_id = 0;
+    loop
+    {
+        let rows: Vec<(i32, sqlx::types::Json<HashMap<String, String>>)> = sqlx::query_as(
+            "SELECT id, env_vars FROM projects WHERE id > $1 AND env_vars IS NOT NULL ORDER BY id LIMIT $2"
+        )
+        .bind(last_id)
+        .bind(VERIFY_BATCH_SIZE)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to read project environment variables for verification: {}", e);
+            AppError::InternalServerError
+        })?;
+
+        let Some(last) = rows.last().map(|(id, _)| *id) else { break };
+        last_id = last;
+        for (id, vars) in rows
+        {
+            report.push(check_env_vars(id, &vars.0, key));
+        }
+    }
+
+    for (source, query) in [
+        (EncryptedSource::DatabasePassword, "SELECT id, encrypted_password FROM databases WHERE id > $1 ORDER BY id LIMIT $2"),
+        (EncryptedSource::BucketSecretKey, "SELECT id, encrypted_secret_key FROM buckets WHERE id > $1 ORDER BY id LIMIT $2"),
+    ]
+    {
+        let mut last_id = 0;
+        loop
+        {
+            let rows: Vec<(i32, String)> = sqlx::query_as(query)
+                .bind(last_id)
+                .bind(VERIFY_BATCH_SIZE)
+                .fetch_all(pool)
+                .await
+                .map_err(|e|
+                {
+                    error!("Failed to read {:?} values for verification: {}", source, e);
+                    AppError::InternalServerError
+                })?;
+
+            let Some(last) = rows.last().map(|(id, _)| *id) else { break };
+            last_id = last;
+            for (id, value) in rows
+            {
+                report.push(check_value(source, id, &value, key));
+            }
+        }
+    }
+
+    if report.failed > 0
+    {
+        error!("{} of {} encrypted secrets failed to decrypt", report.failed, report.checked);
+    }
+    else
+    {
+        info!("All {} encrypted secrets decrypt with the configured key", report.checked);
+    }
+
+    Ok(report)
+}
+
+fn decrypts_b64(value: &str, key: &[u8]) -> bool
+{
+    BASE64_STANDARD.decode(value).is_ok_and(|bytes| crypto_service::decrypts(&bytes, key))
+}
+
+fn check_value(source: EncryptedSource, id: i32, value: &str, key: &[u8]) -> EncryptionCheck
+{
+    let passed = decrypts_b64(value, key);
+    EncryptionCheck
+    {
+        source,
+        id,
+        passed,
+        failures: if passed { Vec::new() } else { vec!["decryption failed".to_string()] },
+    }
+}
+
+fn check_env_vars(project_id: i32, vars: &HashMap<String, String>, key: &[u8]) -> EncryptionCheck
+{
+    let mut failures: Vec<String> = vars.iter()
+        .filter(|(_, value)| !decrypts_b64(value, key))
+        .map(|(name, _)| name.clone())
+        .collect();
+    failures.sort();
+
+    EncryptionCheck
+    {
+        source: EncryptedSource::ProjectEnvVars,
+        id: project_id,
+        passed: failures.is_empty(),
+        failures,
+    }
+}
+
+/// Alerte les administrateurs quand des lignes ne se déchiffrent plus.
+pub fn notify_failures(state: &AppState, report: &EncryptionReport)
+{
+    if report.failed == 0
+    {
+        return;
+    }
+
+    let failed: Vec<_> = report.rows.iter()
+        .filter(|row| !row.passed)
+        .take(MAX_REPORTED_FAILURES)
+        .map(|row| json!({ "source": row.source, "id": row.id }))
+        .collect();
+
+    emit_admin_event(state, SystemEvent::error(format!(
+        "{} of {} encrypted secrets no longer decrypt with the configured key",
+        report.failed, report.checked
+    )).with_context(json!({ "failed": report.failed, "checked": report.checked, "rows": failed })));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypted(value: &str, key: &[u8]) -> String
+    {
+        BASE64_STANDARD.encode(crypto_service::encrypt(value, key).unwrap())
+    }
+
+    #[test]
+    fn test_check_env_vars_reports_failing_names_only()
+    {
+        let key = [1u8; 32];
+        let other = [2u8; 32];
+        let vars = HashMap::from([
+            ("OK".to_string(), encrypted("value", &key)),
+            ("STALE".to_string(), encrypted("value", &other)),
+            ("BROKEN".to_string(), "not base64!".to_string()),
+        ]);
+
+        let check = check_env_vars(7, &vars, &key);
+        assert!(!check.passed);
+        assert_eq!(check.failures, vec!["BROKEN".to_string(), "STALE".to_string()]);
+
+        let serialized = serde_json::to_string(&check).unwrap();
+        assert!(!serialized.contains("value"));
+    }
+
+    #[test]
+    fn test_check_value()
+    {
+        let key = [1u8; 32];
+        assert!(check_value(EncryptedSource::DatabasePassword, 1, &encrypted("pw", &key), &key).passed);
+        assert!(!check_value(EncryptedSource::DatabasePassword, 1, &encrypted("pw", &key), &[3u8; 32]).passed);
+    }
+
+    #[test]
+    fn test_report_counts()
+    {
+        let mut report = EncryptionReport::default();
+        report.push(EncryptionCheck { source: EncryptedSource::BucketSecretKey, id: 1, passed: true, failures: Vec::new() });
+        report.push(EncryptionCheck { source: EncryptedSource::BucketSecretKey, id: 2, passed: false, failures: vec!["decryption failed".into()] });
+        assert_eq!((report.checked, report.failed), (2, 1));
+    }
+}
@@ -95,6 +95,24 @@ pub fn decrypt(ciphertext_with_nonce: &[u8], key: &[u8]) -> Result<String, AppEr
         .map_err(|_| AppError::InternalServerError)
 }
 
+/// Indique si un message produit par [`encrypt`] se déchiffre avec `key`, sans rien journaliser
+/// ni renvoyer du texte en clair : utilisé pour vérifier en masse les secrets enregistrés.
+#[must_use]
+pub fn decrypts(ciphertext_with_nonce: &[u8], key: &[u8]) -> bool
+{
+    if ciphertext_with_nonce.len() < NONCE_SIZE
+    {
+        return false;
+    }
+
+    let key: &Key<Aes256Gcm> = key.into();
+    let cipher = Aes256Gcm::new(key);
+
+    let (nonce_bytes, ciphertext) = ciphertext_with_nonce.split_at(NONCE_SIZE);
+    cipher.decrypt(nonce_bytes.into(), ciphertext)
+        .is_ok_and(|plaintext| std::str::from_utf8(&plaintext).is_ok())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,6 +222,16 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_decrypts_checks_without_plaintext()
+    {
+        let encrypted = encrypt("secret", &test_key()).expect("Encryption failed");
+
+        assert!(decrypts(&encrypted, &test_key()));
+        assert!(!decrypts(&encrypted, &wrong_key()));
+        assert!(!decrypts(&encrypted[..NONCE_SIZE - 1], &test_key()));
+    }
+
     #[test]
     fn test_decrypt_too_short_data()
     {
@@ -2,32 +2,19 @@ use crate::
 {
     config::Config,
     error::{AppError, DatabaseErrorCode, ProjectErrorCode},
-    model::database::{Database, DatabaseDetailsResponse},
-    services::crypto_service,
+    model::database::{Database, DatabaseDetailsResponse, DatabaseLimits, DatabaseSession},
+    services::{crypto_service, mariadb_admin::{valid_identifier, AdminStatement, MariaDbAdmin, MariaDbAdminError, RecordingMariaDbAdmin, SqlxMariaDbAdmin}, rollback::RollbackGuard},
 };
 use rand::distr::{Alphanumeric, SampleString};
 use sqlx::{MySqlPool, PgPool, Postgres, Transaction};
 use tracing::{error, info, warn};
 use base64::prelude::*;
-use std::collections::HashSet;
+use std::{collections::{HashMap, HashSet}, sync::Mutex, time::{Duration, Instant}};
 
 const DB_PREFIX: &str = "hangardb";
+const MAX_SESSION_QUERY_LENGTH: usize = 256;
 
 
-fn valid_identifier(s: &str) -> bool 
-{
-    if s.is_empty() || s.len() > 64 { return false; }
-    
-    // Ne doit pas commencer par un chiffre
-    if s.chars().next().unwrap().is_ascii_digit() { return false; }
-    
-    const RESERVED: &[&str] = &["SELECT", "DROP", "INSERT", "UPDATE", "DELETE", "TABLE", "DATABASE"];
-    if RESERVED.contains(&s.to_uppercase().as_str()) { return false; }
-    
-    let allowed: HashSet<char> = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_".chars().collect();
-    s.chars().all(|c| allowed.contains(&c))
-}
-
 pub async fn check_database_exists_for_owner(pool: &PgPool, owner: &str) -> Result<bool, AppError>
 {
     let count: (i64, ) = sqlx::query_as("SELECT COUNT(*) FROM databases WHERE owner_login = $1")
@@ -42,17 +29,36 @@ pub async fn check_database_exists_for_owner(pool: &PgPool, owner: &str) -> Resu
     Ok(count.0 > 0)
 }
 
-fn generate_password() -> String
+/// Limites appliquées par défaut aux nouveaux utilisateurs MariaDB.
+#[must_use]
+pub const fn default_limits(config: &Config) -> DatabaseLimits
+{
+    DatabaseLimits
+    {
+        max_user_connections: config.mariadb_max_user_connections,
+        max_queries_per_hour: config.mariadb_max_queries_per_hour,
+    }
+}
+
+pub fn generate_password() -> String
 {
     
     Alphanumeric.sample_string(&mut rand::rng(), 24)
 }
 
+/// Nom de la base (et de son utilisateur) créée avec le projet de `owner_login`.
+#[must_use]
+pub fn project_database_name(owner_login: &str) -> String
+{
+    format!("{DB_PREFIX}_{owner_login}")
+}
+
 pub async fn provision_database(
     pg_pool: &PgPool,
     mariadb_pool: &MySqlPool,
     owner_login: &str,
     encryption_key: &[u8],
+    limits: DatabaseLimits,
 ) -> Result<(Database, String), AppError>
 {
     if check_database_exists_for_owner(pg_pool, owner_login).await?
@@ -64,47 +70,39 @@ pub async fn provision_database(
     let username = owner_login.to_string();
     let password = generate_password();
 
-    if let Err(e) = execute_mariadb_provisioning(mariadb_pool, &db_name, &username, &password).await
-    {
-        warn!("MariaDB provisioning failed for user '{}'. Attempting rollback. Error: {}", owner_login, e);
-        if let Err(e) = execute_mariadb_deprovisioning(mariadb_pool, &db_name, &username).await
-        {
-            error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
-        }
-        return Err(e);
-    }
+    provision_mariadb(&SqlxMariaDbAdmin::new(mariadb_pool), owner_login, &db_name, &username, &password, limits).await?;
 
     let encrypted_password_vec = crypto_service::encrypt(&password, encryption_key)?;
     let encrypted_password = BASE64_STANDARD.encode(encrypted_password_vec);
 
     let db_record = sqlx::query_as::<_, Database>(
-        "INSERT INTO databases (owner_login, database_name, username, encrypted_password)
-         VALUES ($1, $2, $3, $4)
-         RETURNING id, owner_login, database_name, username, encrypted_password, project_id, created_at",
+        "INSERT INTO databases (owner_login, database_name, username, encrypted_password, max_user_connections, max_queries_per_hour)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, owner_login, database_name, username, encrypted_password, project_id, max_user_connections, max_queries_per_hour, created_at",
     )
     .bind(owner_login)
     .bind(&db_name)
     .bind(&username)
     .bind(&encrypted_password)
+    .bind(limits.max_user_connections.cast_signed())
+    .bind(limits.max_queries_per_hour.cast_signed())
     .fetch_one(pg_pool)
-    .await
-    .map_err(|e|
+    .await;
+
+    let db_record = match db_record
     {
-        error!("Failed to persist database metadata for user '{}' after successful MariaDB provisioning: {}", owner_login, e);
-        let mariadb_pool = mariadb_pool.clone();
-        let db_name = db_name.clone();
-        let username = username.clone();
-        let owner_login = owner_login.to_string();
-        tokio::spawn(async move
+        Ok(db_record) => db_record,
+        Err(e) =>
         {
+            error!("Failed to persist database metadata for user '{}' after successful MariaDB provisioning: {}", owner_login, e);
             warn!("CRITICAL: Rolling back MariaDB provisioning for {} due to PostgreSQL failure.", owner_login);
-            if let Err(e) = execute_mariadb_deprovisioning(&mariadb_pool, &db_name, &username).await
-            {
-                error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
-            }
-        });
-        AppError::InternalServerError
-    })?;
+            let admin = SqlxMariaDbAdmin::new(mariadb_pool);
+            let mut rollback = RollbackGuard::new();
+            rollback.push(format!("MariaDB database '{db_name}'"), execute_mariadb_deprovisioning(&admin, &db_name, &username));
+            rollback.run().await;
+            return Err(AppError::InternalServerError);
+        }
+    };
 
     info!("Database for user '{}' provisioned successfully.", owner_login);
     Ok((db_record, password))
@@ -113,132 +111,347 @@ pub async fn provision_database(
 pub async fn deprovision_database(
     pg_pool: &PgPool,
     mariadb_pool: &MySqlPool,
-    db_id: i32,
-    owner_login: &str,
-    is_admin: bool
+    db_record: &Database,
 ) -> Result<(), AppError>
 {
-    let db_record = get_database_by_id_and_owner(pg_pool, db_id, owner_login, is_admin).await?
-        .ok_or(DatabaseErrorCode::NotFound)?;
-
-    execute_mariadb_deprovisioning(mariadb_pool, &db_record.database_name, &db_record.username).await?;
+    execute_mariadb_deprovisioning(&SqlxMariaDbAdmin::new(mariadb_pool), &db_record.database_name, &db_record.username).await?;
 
     sqlx::query("DELETE FROM databases WHERE id = $1")
-        .bind(db_id)
+        .bind(db_record.id)
         .execute(pg_pool)
         .await
         .map_err(|e|
         {
-            error!("Failed to delete database metadata for ID {}: {}", db_id, e);
+            error!("Failed to delete database metadata for ID {}: {}", db_record.id, e);
             AppError::InternalServerError // La DB a été supprimée mais pas la métadonnée.
         })?;
 
-    info!("Database ID {} for user '{}' deprovisioned successfully.", db_id, db_record.owner_login);
+    info!("Database ID {} for user '{}' deprovisioned successfully.", db_record.id, db_record.owner_login);
     Ok(())
 }
 
-async fn execute_mariadb_provisioning(
-    pool: &MySqlPool,
+/// Erreur renvoyée pour une opération MariaDB : un identifiant invalide est une requête invalide,
+/// toute autre erreur devient `failure`.
+fn admin_error(failure: impl Into<AppError>) -> impl FnOnce(MariaDbAdminError) -> AppError
+{
+    move |e| match e
+    {
+        MariaDbAdminError::InvalidIdentifier(identifier) =>
+        {
+            error!("Invalid MariaDB identifier: '{}'", identifier);
+            AppError::BadRequest("Invalid identifier".into())
+        }
+        MariaDbAdminError::Failed(_) => failure.into(),
+    }
+}
+
+/// Crée la base et son utilisateur ; en cas d'échec, supprime ce qui a déjà été créé.
+async fn provision_mariadb(
+    admin: &impl MariaDbAdmin,
+    owner_login: &str,
     db_name: &str,
     username: &str,
     password: &str,
-) -> Result<(), AppError> 
+    limits: DatabaseLimits,
+) -> Result<(), AppError>
 {
-    if !valid_identifier(db_name) || !valid_identifier(username) 
+    if let Err(e) = execute_mariadb_provisioning(admin, db_name, username, password, limits).await
     {
-        error!("Invalid database or username identifier: db_name='{}', username='{}'", db_name, username);
-        return Err(AppError::BadRequest("Invalid identifier".into()));
+        warn!("MariaDB provisioning failed for user '{}'. Attempting rollback. Error: {}", owner_login, e);
+        if let Err(e) = execute_mariadb_deprovisioning(admin, db_name, username).await
+        {
+            error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
+        }
+        return Err(e);
     }
 
-    let mut conn = pool.acquire().await.map_err(|e| 
+    Ok(())
+}
+
+async fn execute_mariadb_provisioning(
+    admin: &impl MariaDbAdmin,
+    db_name: &str,
+    username: &str,
+    password: &str,
+    limits: DatabaseLimits,
+) -> Result<(), AppError> 
+{
+    admin.run(&[
+        AdminStatement::CreateDatabase { db_name },
+        AdminStatement::CreateUser { username, password },
+        AdminStatement::SetLimits { username, limits },
+        AdminStatement::Grant { db_name, username },
+        AdminStatement::FlushPrivileges,
+    ]).await.map_err(admin_error(DatabaseErrorCode::ProvisioningFailed))
+}
+
+/// Modifie les limites d'une base existante, côté MariaDB puis dans les métadonnées.
+pub async fn update_database_limits(
+    pg_pool: &PgPool,
+    mariadb_pool: &MySqlPool,
+    db_record: &Database,
+    limits: DatabaseLimits,
+) -> Result<Database, AppError>
+{
+    apply_database_limits(&SqlxMariaDbAdmin::new(mariadb_pool), db_record, limits).await?;
+
+    let updated = sqlx::query_as::<_, Database>(
+        "UPDATE databases SET max_user_connections = $1, max_queries_per_hour = $2 WHERE id = $3 RETURNING *",
+    )
+    .bind(limits.max_user_connections.cast_signed())
+    .bind(limits.max_queries_per_hour.cast_signed())
+    .bind(db_record.id)
+    .fetch_one(pg_pool)
+    .await
+    .map_err(|e|
     {
-        error!("Failed to acquire MariaDB connection: {}", e);
-        DatabaseErrorCode::ProvisioningFailed
+        error!("Failed to persist resource limits for database ID {}: {}", db_record.id, e);
+        AppError::InternalServerError
     })?;
 
-    let create_db_sql = format!(
-        "CREATE DATABASE `{db_name}` CHARACTER SET utf8mb4 COLLATE utf8mb4_general_ci"
+    info!(
+        "Resource limits of database ID {} updated: {} connections, {} queries/hour.",
+        db_record.id, limits.max_user_connections, limits.max_queries_per_hour
     );
-    sqlx::query(&create_db_sql)
-        .execute(&mut *conn)
-        .await
-        .map_err(|e| 
-        {
-            error!("Failed to create database '{}': {}", db_name, e);
-            DatabaseErrorCode::ProvisioningFailed
-        })?;
+    Ok(updated)
+}
 
-    let escaped_password = password.replace('\'', "\\'");
-    let create_user_sql = format!("CREATE USER `{username}`@'%' IDENTIFIED BY '{escaped_password}'");
-    sqlx::query(&create_user_sql)
-        .execute(&mut *conn)
-        .await
-        .map_err(|_|
-        {
-            error!("Failed to create user '{}' (details hidden for security)", username);
-            DatabaseErrorCode::ProvisioningFailed
-        })?;
+/// Instructions qu'exécuterait [`update_database_limits`], mots de passe masqués, sans rien modifier.
+pub async fn preview_database_limits(db_record: &Database, limits: DatabaseLimits) -> Result<Vec<String>, AppError>
+{
+    let recorder = RecordingMariaDbAdmin::default();
+    apply_database_limits(&recorder, db_record, limits).await?;
+    Ok(recorder.statements())
+}
 
-    let grant_sql = format!(
-        "GRANT SELECT, INSERT, UPDATE, DELETE, CREATE, DROP, INDEX, ALTER, CREATE TEMPORARY TABLES, LOCK TABLES ON `{db_name}`.* TO `{username}`@'%'"
-    );
-    sqlx::query(&grant_sql)
-        .execute(&mut *conn)
-        .await
-        .map_err(|e| 
-        {
-            error!("Failed to grant privileges on database '{}' to user '{}': {}", db_name, username, e);
-            DatabaseErrorCode::ProvisioningFailed
-        })?;
+async fn apply_database_limits(admin: &impl MariaDbAdmin, db_record: &Database, limits: DatabaseLimits) -> Result<(), AppError>
+{
+    if i32::try_from(limits.max_user_connections).is_err() || i32::try_from(limits.max_queries_per_hour).is_err()
+    {
+        return Err(AppError::BadRequest(format!("Database limits must not exceed {}.", i32::MAX)));
+    }
 
-    sqlx::query("FLUSH PRIVILEGES")
-        .execute(&mut *conn)
-        .await
-        .map_err(|e| 
+    admin.set_limits(&db_record.username, limits).await.map_err(admin_error(AppError::InternalServerError))
+}
+
+/// Remplace le mot de passe de l'utilisateur MariaDB, puis celui des métadonnées. Si ces dernières ne
+/// peuvent être mises à jour, l'ancien mot de passe est rétabli côté MariaDB. Renvoie le nouveau mot de passe.
+pub async fn rotate_database_password(
+    pg_pool: &PgPool,
+    mariadb_pool: &MySqlPool,
+    db_record: &Database,
+    encryption_key: &[u8],
+) -> Result<String, AppError>
+{
+    let previous_password = decrypt_password(db_record, encryption_key)?;
+    let password = generate_password();
+    let encrypted_password = BASE64_STANDARD.encode(crypto_service::encrypt(&password, encryption_key)?);
+
+    let admin = SqlxMariaDbAdmin::new(mariadb_pool);
+    admin.alter_password(&db_record.username, &password).await.map_err(admin_error(AppError::InternalServerError))?;
+
+    let persisted = sqlx::query("UPDATE databases SET encrypted_password = $1 WHERE id = $2")
+        .bind(&encrypted_password)
+        .bind(db_record.id)
+        .execute(pg_pool)
+        .await;
+
+    if let Err(e) = persisted
+    {
+        error!("Failed to persist the rotated password of database ID {}: {}", db_record.id, e);
+        warn!("Restoring the previous MariaDB password of database ID {}", db_record.id);
+        if let Err(e) = admin.alter_password(&db_record.username, &previous_password).await
         {
-            error!("Failed to flush privileges: {}", e);
-            DatabaseErrorCode::ProvisioningFailed
-        })?;
+            error!("CRITICAL: Failed to restore the previous MariaDB password of database ID {}: {}", db_record.id, e);
+        }
+        return Err(AppError::InternalServerError);
+    }
+
+    info!("Password of database ID {} for user '{}' rotated successfully.", db_record.id, db_record.owner_login);
+    Ok(password)
+}
 
+/// Vide une base en la supprimant puis en la recréant à l'identique.
+/// L'utilisateur, ses droits (attachés au nom de la base) et les métadonnées sont conservés.
+pub async fn reset_database(mariadb_pool: &MySqlPool, db_record: &Database) -> Result<(), AppError>
+{
+    execute_mariadb_reset(&SqlxMariaDbAdmin::new(mariadb_pool), &db_record.database_name).await?;
+
+    info!("Database ID {} for user '{}' reset successfully.", db_record.id, db_record.owner_login);
     Ok(())
 }
 
+/// Réserve une réinitialisation de la base si la précédente date d'au moins `cooldown`,
+/// sinon renvoie le temps restant. Une tentative échouée compte aussi.
+pub fn claim_reset_slot(
+    resets: &Mutex<HashMap<i32, Instant>>,
+    db_id: i32,
+    cooldown: Duration,
+    now: Instant,
+) -> Result<(), Duration>
+{
+    let mut resets = resets.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
 
+    if let Some(&last) = resets.get(&db_id)
+    {
+        let elapsed = now.duration_since(last);
+        if elapsed < cooldown
+        {
+            return Err(cooldown - elapsed);
+        }
+    }
+
+    resets.insert(db_id, now);
+    Ok(())
+}
+
+async fn execute_mariadb_reset(admin: &impl MariaDbAdmin, db_name: &str) -> Result<(), AppError>
+{
+    admin.run(&[
+        AdminStatement::DropDatabase { db_name },
+        AdminStatement::CreateDatabase { db_name },
+    ]).await.map_err(admin_error(DatabaseErrorCode::ResetFailed))
+}
 
 async fn execute_mariadb_deprovisioning(
-    pool: &MySqlPool,
+    admin: &impl MariaDbAdmin,
     db_name: &str,
     username: &str,
 ) -> Result<(), AppError>
 {
-    if !valid_identifier(db_name) || !valid_identifier(username) 
+    admin.run(&[
+        AdminStatement::DropDatabase { db_name },
+        AdminStatement::DropUser { username },
+    ]).await.map_err(admin_error(DatabaseErrorCode::DeprovisioningFailed))
+}
+
+/// Liste les sessions MariaDB ouvertes par les utilisateurs donnés.
+pub async fn list_mariadb_sessions(pool: &MySqlPool, usernames: &[String]) -> Result<Vec<DatabaseSession>, AppError>
+{
+    if let Some(invalid) = usernames.iter().find(|u| !valid_identifier(u))
     {
+        error!("Refusing to list sessions for invalid username identifier '{}'", invalid);
         return Err(AppError::BadRequest("Invalid identifier".into()));
     }
 
-    let mut conn = pool.acquire().await.map_err(|_| DatabaseErrorCode::DeprovisioningFailed)?;
-    
-    sqlx::query(&format!("DROP DATABASE IF EXISTS `{db_name}`"))
-    .execute(&mut *conn)
+    let sessions: Vec<DatabaseSession> = sqlx::query_as(
+        "SELECT CAST(ID AS UNSIGNED) AS id, USER AS user, DB AS database_name, COMMAND AS command,
+                STATE AS state, CAST(TIME AS SIGNED) AS duration_seconds, INFO AS query
+         FROM information_schema.PROCESSLIST
+         ORDER BY TIME DESC",
+    )
+    .fetch_all(pool)
     .await
-    .map_err(|e| 
+    .map_err(|e|
     {
-        error!("Failed to drop database '{}': {}", db_name, e);
-        DatabaseErrorCode::DeprovisioningFailed
+        error!("Failed to read MariaDB processlist: {}", e);
+        AppError::InternalServerError
     })?;
 
-    sqlx::query(&format!("DROP USER IF EXISTS `{username}`@'%'"))
-    .execute(&mut *conn)
-    .await
-    .map_err(|e| 
+    let managed: HashSet<&str> = usernames.iter().map(String::as_str).collect();
+
+    Ok(sessions
+        .into_iter()
+        .filter(|s| managed.contains(s.user.as_str()))
+        .map(|mut s|
+        {
+            s.query = s.query.map(|q| truncate_query(q, MAX_SESSION_QUERY_LENGTH));
+            s
+        })
+        .collect())
+}
+
+/// Termine une session MariaDB après avoir vérifié qu'elle appartient à l'un des utilisateurs donnés.
+pub async fn kill_mariadb_session(pool: &MySqlPool, session_id: u64, usernames: &[String]) -> Result<(), AppError>
+{
+    let owner: Option<(String,)> = sqlx::query_as("SELECT USER FROM information_schema.PROCESSLIST WHERE ID = ?")
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to look up MariaDB session {}: {}", session_id, e);
+            AppError::InternalServerError
+        })?;
+
+    let Some((session_user,)) = owner else
     {
-        error!("Failed to drop user '{}': {}", username, e);
-        DatabaseErrorCode::DeprovisioningFailed
-    })?;
+        return Err(DatabaseErrorCode::SessionNotFound.into());
+    };
+
+    if !valid_identifier(&session_user) || !usernames.iter().any(|u| u == &session_user)
+    {
+        return Err(DatabaseErrorCode::SessionNotFound.into());
+    }
+
+    // `session_id` est un entier : aucune donnée utilisateur n'est interpolée.
+    sqlx::query(&format!("KILL CONNECTION {session_id}"))
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to kill MariaDB session {} of user '{}': {}", session_id, session_user, e);
+            AppError::InternalServerError
+        })?;
 
+    info!("MariaDB session {} of user '{}' killed.", session_id, session_user);
     Ok(())
 }
 
+fn truncate_query(query: String, max_length: usize) -> String
+{
+    match query.char_indices().nth(max_length)
+    {
+        Some((index, _)) => format!("{}...", &query[..index]),
+        None => query,
+    }
+}
+
+pub async fn get_all_database_usernames(pool: &PgPool) -> Result<Vec<String>, AppError>
+{
+    sqlx::query_scalar("SELECT username FROM databases")
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch managed database usernames: {}", e);
+            AppError::InternalServerError
+        })
+}
+
+/// Taille de la base de chaque propriétaire (`login -> octets`), lue dans `information_schema`.
+pub async fn get_database_sizes_by_owner(pool: &PgPool, mariadb_pool: &MySqlPool) -> Result<HashMap<String, i64>, AppError>
+{
+    let databases: Vec<(String, String)> = sqlx::query_as("SELECT owner_login, database_name FROM databases")
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch managed databases: {}", e);
+            AppError::InternalServerError
+        })?;
+
+    let sizes: HashMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+        "SELECT TABLE_SCHEMA, CAST(COALESCE(SUM(DATA_LENGTH + INDEX_LENGTH), 0) AS SIGNED)
+         FROM information_schema.TABLES
+         GROUP BY TABLE_SCHEMA",
+    )
+    .fetch_all(mariadb_pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to read MariaDB database sizes: {}", e);
+        AppError::InternalServerError
+    })?
+    .into_iter()
+    .collect();
+
+    // Une base sans table n'apparaît pas dans information_schema.TABLES.
+    Ok(databases.into_iter()
+        .map(|(owner, database_name)| (owner, sizes.get(&database_name).copied().unwrap_or(0)))
+        .collect())
+}
+
 pub async fn get_database_by_owner(pool: &PgPool, owner: &str) -> Result<Option<Database>, AppError>
 {
     sqlx::query_as("SELECT * FROM databases WHERE owner_login = $1")
@@ -252,20 +465,10 @@ pub async fn get_database_by_owner(pool: &PgPool, owner: &str) -> Result<Option<
         })
 }
 
-pub async fn get_database_by_id_and_owner(pool: &PgPool, db_id: i32, owner: &str, is_admin: bool) -> Result<Option<Database>, AppError>
+pub async fn get_database_by_id(pool: &PgPool, db_id: i32) -> Result<Option<Database>, AppError>
 {
-    if is_admin 
-    {
-        return sqlx::query_as("SELECT * FROM databases WHERE id = $1")
-            .bind(db_id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|_| AppError::InternalServerError);
-    }
-
-    sqlx::query_as("SELECT * FROM databases WHERE id = $1 AND owner_login = $2")
+    sqlx::query_as("SELECT * FROM databases WHERE id = $1")
         .bind(db_id)
-        .bind(owner)
         .fetch_optional(pool)
         .await
         .map_err(|_| AppError::InternalServerError)
@@ -311,48 +514,45 @@ pub async fn unlink_database_from_project(pool: &PgPool, project_id: i32, owner:
     Ok(())
 }
 
+/// `password` : généré par l'appelant, qui a pu l'injecter dans le conteneur avant l'enregistrement.
 pub async fn provision_and_link_database_tx<'a>(
     tx: &mut Transaction<'a, Postgres>,
     mariadb_pool: &MySqlPool,
     owner_login: &str,
     project_id: i32,
+    password: &str,
     encryption_key: &[u8],
+    limits: DatabaseLimits,
 ) -> Result<(), AppError>
 {
 
-    let db_name = format!("{DB_PREFIX}_{owner_login}");
+    let db_name = project_database_name(owner_login);
     let username = db_name.clone();
-    let password = generate_password();
 
-    if let Err(e) = execute_mariadb_provisioning(mariadb_pool, &db_name, &username, &password).await
-    {
-        warn!("MariaDB provisioning failed during transaction for user '{}'. Error: {}", owner_login, e);
-        if let Err(e) = execute_mariadb_deprovisioning(mariadb_pool, &db_name, &username).await 
-        {
-            error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
-        }
-        return Err(e);
-    }
+    let admin = SqlxMariaDbAdmin::new(mariadb_pool);
+    provision_mariadb(&admin, owner_login, &db_name, &username, password, limits).await?;
     
-    let encrypted_password_vec = crypto_service::encrypt(&password, encryption_key)?;
+    let encrypted_password_vec = crypto_service::encrypt(password, encryption_key)?;
     let encrypted_password = BASE64_STANDARD.encode(encrypted_password_vec);
 
     let insert_result = sqlx::query(
-        "INSERT INTO databases (owner_login, database_name, username, encrypted_password, project_id)
-         VALUES ($1, $2, $3, $4, $5)",
+        "INSERT INTO databases (owner_login, database_name, username, encrypted_password, project_id, max_user_connections, max_queries_per_hour)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
     )
     .bind(owner_login)
     .bind(&db_name)
     .bind(&username)
     .bind(&encrypted_password)
     .bind(project_id)
+    .bind(limits.max_user_connections.cast_signed())
+    .bind(limits.max_queries_per_hour.cast_signed())
     .execute(&mut **tx)
     .await;
 
     if let Err(db_error) = insert_result
     {
         error!("Failed to persist database metadata for user '{}' in transaction: {}", owner_login, db_error);
-        if let Err(e) = execute_mariadb_deprovisioning(mariadb_pool, &db_name, &username).await 
+        if let Err(e) = execute_mariadb_deprovisioning(&admin, &db_name, &username).await 
         {
             error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
         }
@@ -362,10 +562,42 @@ pub async fn provision_and_link_database_tx<'a>(
     Ok(())
 }
 
-pub fn create_db_details_response(db: Database, config: &Config, encryption_key: &[u8]) -> Result<DatabaseDetailsResponse, AppError>
+fn decrypt_password(db: &Database, encryption_key: &[u8]) -> Result<String, AppError>
 {
     let encrypted_pass_vec = BASE64_STANDARD.decode(&db.encrypted_password).map_err(|_| AppError::InternalServerError)?;
-    let password = crypto_service::decrypt(&encrypted_pass_vec, encryption_key)?;
+    crypto_service::decrypt(&encrypted_pass_vec, encryption_key)
+}
+
+/// Variables `HANGAR_DB_*` injectées dans le conteneur d'un projet qui l'a demandé.
+pub fn database_env_vars(db: &Database, config: &Config) -> Result<HashMap<String, String>, AppError>
+{
+    let password = decrypt_password(db, &config.encryption_key)?;
+    Ok(credentials_env_vars(config, &db.database_name, &db.username, password))
+}
+
+#[must_use]
+pub fn credentials_env_vars(config: &Config, database_name: &str, username: &str, password: String) -> HashMap<String, String>
+{
+    HashMap::from([
+        ("HANGAR_DB_HOST".to_string(), config.mariadb_public_host.clone()),
+        ("HANGAR_DB_PORT".to_string(), config.mariadb_public_port.to_string()),
+        ("HANGAR_DB_NAME".to_string(), database_name.to_string()),
+        ("HANGAR_DB_USER".to_string(), username.to_string()),
+        ("HANGAR_DB_PASSWORD".to_string(), password),
+    ])
+}
+
+pub fn create_db_details_response(db: Database, config: &Config, encryption_key: &[u8], reveal_password: bool) -> Result<DatabaseDetailsResponse, AppError>
+{
+    let password = if reveal_password
+    {
+        Some(decrypt_password(&db, encryption_key)?)
+    }
+    else
+    {
+        None
+    };
+    let limits = db.limits();
 
     Ok(DatabaseDetailsResponse 
     {
@@ -377,6 +609,77 @@ pub fn create_db_details_response(db: Database, config: &Config, encryption_key:
         project_id: db.project_id,
         host: config.mariadb_public_host.clone(),
         port: config.mariadb_public_port,
+        limits,
         created_at: db.created_at,
     })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    #[test]
+    fn test_truncate_query()
+    {
+        assert_eq!(truncate_query("SELECT 1".to_string(), 64), "SELECT 1");
+        assert_eq!(truncate_query("SELECT * FROM users".to_string(), 6), "SELECT...");
+        assert_eq!(truncate_query("éééé".to_string(), 2), "éé...");
+    }
+
+    #[test]
+    fn test_provisioning_statements()
+    {
+        let admin = RecordingMariaDbAdmin::default();
+        let limits = DatabaseLimits { max_user_connections: 10, max_queries_per_hour: 0 };
+
+        block_on(provision_mariadb(&admin, "jdoe", "hangardb_jdoe", "jdoe", "s3cret", limits)).unwrap();
+
+        assert_eq!(admin.statements(), vec![
+            "CREATE DATABASE `hangardb_jdoe` CHARACTER SET utf8mb4 COLLATE utf8mb4_general_ci",
+            "CREATE USER `jdoe`@'%' IDENTIFIED BY '***'",
+            "ALTER USER `jdoe`@'%' WITH MAX_USER_CONNECTIONS 10 MAX_QUERIES_PER_HOUR 0",
+            "GRANT SELECT, INSERT, UPDATE, DELETE, CREATE, DROP, INDEX, ALTER, CREATE TEMPORARY TABLES, LOCK TABLES ON `hangardb_jdoe`.* TO `jdoe`@'%'",
+            "FLUSH PRIVILEGES",
+        ]);
+    }
+
+    #[test]
+    fn test_failed_provisioning_is_rolled_back()
+    {
+        let admin = RecordingMariaDbAdmin::failing_on("GRANT");
+        let limits = DatabaseLimits { max_user_connections: 10, max_queries_per_hour: 0 };
+
+        let result = block_on(provision_mariadb(&admin, "jdoe", "hangardb_jdoe", "jdoe", "s3cret", limits));
+
+        assert!(matches!(result, Err(AppError::DatabaseError(DatabaseErrorCode::ProvisioningFailed))));
+        let statements = admin.statements();
+        assert_eq!(&statements[statements.len() - 2..], ["DROP DATABASE IF EXISTS `hangardb_jdoe`", "DROP USER IF EXISTS `jdoe`@'%'"]);
+    }
+
+    #[test]
+    fn test_invalid_identifier_runs_nothing()
+    {
+        let admin = RecordingMariaDbAdmin::default();
+        let limits = DatabaseLimits { max_user_connections: 10, max_queries_per_hour: 0 };
+
+        let result = block_on(provision_mariadb(&admin, "jdoe", "hangardb_jdoe", "jdoe`@'%'; --", "s3cret", limits));
+
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        assert!(admin.statements().is_empty());
+    }
+
+    #[test]
+    fn test_reset_slot_enforces_cooldown_per_database()
+    {
+        let resets = Mutex::new(HashMap::new());
+        let cooldown = Duration::from_secs(300);
+        let start = Instant::now();
+
+        assert!(claim_reset_slot(&resets, 1, cooldown, start).is_ok());
+        assert_eq!(claim_reset_slot(&resets, 1, cooldown, start + Duration::from_secs(100)), Err(Duration::from_secs(200)));
+        assert!(claim_reset_slot(&resets, 2, cooldown, start).is_ok());
+        assert!(claim_reset_slot(&resets, 1, cooldown, start + cooldown).is_ok());
+    }
+}
@@ -1,12 +1,65 @@
 use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
 
+use serde_json::json;
+use time::OffsetDateTime;
 use tracing::{debug, error, info};
 
-use crate::error::AppError;
-use crate::sse::emitter::{emit_creation_deployment_stage, emit_deployment_stage};
-use crate::sse::types::DeploymentStage;
+use crate::error::{AppError, ProjectErrorCode};
+use crate::logging::log_safe;
+use crate::model::deployment::{DeploymentKind, ResumeStage};
+use crate::model::notification::NotificationKind;
+use crate::model::timeline::TimelineEntryType;
+use crate::model::webhook::WebhookEvent;
+use crate::services::deployment_service::{self, DeploymentProgress};
+use crate::services::{notification_dispatch_service, notification_service, timeline_service};
+use crate::sse::emitter::{emit_creation_deployment_stage, emit_creation_event, emit_deployment_stage, emit_project_event};
+use crate::sse::manager::SseManager;
+use crate::sse::types::{DeploymentEvent, DeploymentStage, SseEvent, SystemEvent};
 use crate::state::AppState;
 
+/// Nombre de vulnérabilités reprises dans l'étape `Failed` d'un scan refusé ; la liste complète est dans l'erreur HTTP.
+const FAILED_SCAN_SUMMARY_SIZE: usize = 10;
+
+/// Étape `Failed` d'une opération en erreur ; un scan refusé y joint ses vulnérabilités les plus graves.
+fn failed_stage(error: &AppError, operation_name: &str) -> DeploymentStage
+{
+    let vulnerabilities = match error
+    {
+        AppError::ProjectError(ProjectErrorCode::ImageScanFailed(findings)) => findings.top(FAILED_SCAN_SUMMARY_SIZE),
+        _ => Vec::new(),
+    };
+
+    DeploymentStage::Failed { error: error.to_string(), stage: operation_name.to_string(), vulnerabilities }
+}
+
+/// Envoie l'étape `Completed` sur le canal suivi par le client à l'origine du déploiement :
+/// - mise à jour (`updated_project_id` = Some) → canal du projet, comme les étapes précédentes ;
+/// - création → canal "creation" de l'utilisateur, avec l'identifiant du projet créé.
+async fn send_completed(
+    sse_manager: &SseManager,
+    updated_project_id: Option<i32>,
+    user_login: &str,
+    project_name: String,
+    project_id: i32,
+    container_name: String,
+)
+{
+    let event = SseEvent::Deployment(DeploymentEvent::new(
+        project_id,
+        project_name,
+        DeploymentStage::Completed { container_name },
+    ));
+
+    match updated_project_id
+    {
+        Some(id) => sse_manager.emit_to_project(id, event).await,
+        None => sse_manager.emit_to_creation(user_login, event).await,
+    }
+}
+
 /// Orchestrateur de déploiement pour un projet.
 ///
 /// Gère automatiquement l'émission d'événements SSE selon le contexte :
@@ -18,12 +71,16 @@ pub struct DeploymentOrchestrator<'a>
     project_name: String,
     user_login: String,
     project_id: Option<i32>,
+    started_at: OffsetDateTime,
+    /// Identifiant du déploiement de mise à jour suivi en base, pour permettre sa reprise.
+    tracking: Option<i32>,
+    progress: Mutex<DeploymentProgress>,
 }
 
 impl<'a> DeploymentOrchestrator<'a>
 {
     #[must_use] 
-    pub const fn for_creation(state: &'a AppState, project_name: String, user_login: String) -> Self
+    pub fn for_creation(state: &'a AppState, project_name: String, user_login: String) -> Self
     {
         Self 
         {
@@ -31,11 +88,14 @@ impl<'a> DeploymentOrchestrator<'a>
             project_name,
             user_login,
             project_id: None,
+            started_at: OffsetDateTime::now_utc(),
+            tracking: None,
+            progress: Mutex::new(DeploymentProgress::default()),
         }
     }
 
     #[must_use] 
-    pub const fn for_update(
+    pub fn for_update(
         state: &'a AppState,
         project_name: String,
         user_login: String,
@@ -48,6 +108,9 @@ impl<'a> DeploymentOrchestrator<'a>
             project_name,
             user_login,
             project_id: Some(project_id),
+            started_at: OffsetDateTime::now_utc(),
+            tracking: None,
+            progress: Mutex::new(DeploymentProgress::default()),
         }
     }
 
@@ -56,6 +119,158 @@ impl<'a> DeploymentOrchestrator<'a>
         self.project_id = Some(project_id);
     }
 
+    /// Enregistre le déploiement en base. Renvoie son identifiant.
+    ///
+    /// Tant que le suivi est actif, les images intermédiaires ne sont pas supprimées en cas
+    /// d'échec : elles le seront à l'expiration de leur délai de conservation.
+    pub async fn start_tracking(
+        &mut self,
+        kind: DeploymentKind,
+        requested_image: Option<&str>,
+        retry_of: Option<i32>,
+    ) -> Result<i32, AppError>
+    {
+        let project_id = self.project_id.ok_or(AppError::InternalServerError)?;
+        let deployment_id = deployment_service::create_deployment(
+            &self.state.db_pool,
+            project_id,
+            kind,
+            &self.user_login,
+            requested_image,
+            retry_of,
+        ).await?;
+        timeline_service::publish(self.state, TimelineEntryType::Deployment, i64::from(deployment_id)).await;
+
+        self.tracking = Some(deployment_id);
+        Ok(deployment_id)
+    }
+
+    /// Identifiant du déploiement suivi en base, le cas échéant.
+    #[must_use]
+    pub const fn deployment_id(&self) -> Option<i32>
+    {
+        self.tracking
+    }
+
+    /// Rattache l'espace de travail du build au déploiement.
+    ///
+    /// Un déploiement suivi l'enregistre immédiatement ; une création l'enregistre avec son historique.
+    pub async fn record_workspace(&self, workspace_path: &Path)
+    {
+        let workspace_path = workspace_path.to_string_lossy().into_owned();
+        if let Some(deployment_id) = self.tracking
+        {
+            deployment_service::set_workspace_path(&self.state.db_pool, deployment_id, &workspace_path).await;
+        }
+
+        if let Ok(mut progress) = self.progress.lock()
+        {
+            progress.workspace_path = Some(workspace_path);
+        }
+    }
+
+    /// Indique si les images intermédiaires doivent être conservées en cas d'échec.
+    #[must_use]
+    pub const fn keeps_artifacts(&self) -> bool
+    {
+        self.tracking.is_some()
+    }
+
+    /// Mémorise l'image produite par le déploiement et si elle a passé le scan.
+    pub fn record_image(&self, image_tag: &str, scanned: bool)
+    {
+        if let Ok(mut progress) = self.progress.lock()
+        {
+            progress.image_tag = Some(image_tag.to_string());
+            progress.image_scanned = scanned;
+        }
+    }
+
+    /// Mémorise l'identifiant de l'image déployée.
+    pub fn record_digest(&self, image_digest: &str)
+    {
+        if let Ok(mut progress) = self.progress.lock()
+        {
+            progress.image_digest = Some(image_digest.to_string());
+        }
+    }
+
+    /// Mémorise le commit construit, pour une source GitHub.
+    pub fn record_commit(&self, commit_sha: &str)
+    {
+        if let Ok(mut progress) = self.progress.lock()
+        {
+            progress.commit_sha = Some(commit_sha.to_string());
+        }
+    }
+
+    fn record_failed_stage(&self, operation_name: &str)
+    {
+        if let Ok(mut progress) = self.progress.lock()
+        {
+            progress.failed_stage = Some(operation_name.to_string());
+        }
+    }
+
+    fn take_progress(&self) -> DeploymentProgress
+    {
+        match self.progress.lock()
+        {
+            Ok(mut progress) => std::mem::take(&mut *progress),
+            Err(_) => DeploymentProgress::default(),
+        }
+    }
+
+    /// Enregistre l'issue du déploiement suivi.
+    pub async fn finish_tracking(&self, succeeded: bool)
+    {
+        let Some(deployment_id) = self.tracking else { return };
+
+        let progress = self.take_progress();
+        let failed_stage = progress.failed_stage.clone();
+        let ttl = Duration::from_secs(self.state.config.deployment_artifacts_ttl_minutes * 60);
+        deployment_service::finish_deployment(&self.state.db_pool, deployment_id, succeeded, &progress, ttl).await;
+        timeline_service::publish(self.state, TimelineEntryType::Deployment, i64::from(deployment_id)).await;
+
+        if let Some(project_id) = self.project_id
+        {
+            let event = if succeeded { WebhookEvent::DeploymentSucceeded } else { WebhookEvent::DeploymentFailed };
+            notification_dispatch_service::dispatch(
+                self.state,
+                project_id,
+                &self.project_name,
+                event,
+                json!({ "deployment_id": deployment_id, "triggered_by": self.user_login, "failed_stage": failed_stage }),
+            );
+
+            if !succeeded
+            {
+                let stage = failed_stage.as_deref().unwrap_or("unknown");
+                notification_service::notify_owner(
+                    self.state,
+                    project_id,
+                    NotificationKind::DeployFailed,
+                    SystemEvent::error(format!("Deployment #{deployment_id}, triggered by {}, failed at stage '{stage}'.", self.user_login))
+                        .with_context(json!({ "deployment_id": deployment_id, "failed_stage": stage })),
+                );
+            }
+        }
+    }
+
+    /// Ajoute la création réussie du projet à son historique de déploiements.
+    pub async fn record_creation(&self, project_id: i32)
+    {
+        let progress = self.take_progress();
+        deployment_service::record_creation(&self.state.db_pool, project_id, &self.user_login, self.started_at, &progress).await;
+    }
+
+    /// Signale la reprise d'un déploiement échoué à partir d'une étape donnée.
+    pub async fn emit_resuming(&self, from_stage: ResumeStage)
+    {
+        info!("Resuming deployment of project '{}' from stage '{}'", self.project_name, from_stage.as_str());
+        self.emit_stage(DeploymentStage::Resuming { from_stage: from_stage.as_str().to_string() }).await;
+    }
+
     pub async fn emit_stage(&self, stage: DeploymentStage)
     {
         if let Some(id) = self.project_id {
@@ -67,7 +282,7 @@ impl<'a> DeploymentOrchestrator<'a>
         } else {
             debug!(
                 "Emitting creation stage {:?} for project '{}' (user: {})",
-                stage, self.project_name, self.user_login
+                stage, log_safe(&self.project_name), self.user_login
             );
             emit_creation_deployment_stage(
                 self.state,
@@ -80,6 +295,16 @@ impl<'a> DeploymentOrchestrator<'a>
         }
     }
 
+    /// Émet un événement système sur le même canal que les étapes du déploiement.
+    pub async fn emit_event(&self, event: SystemEvent)
+    {
+        match self.project_id
+        {
+            Some(id) => emit_project_event(self.state, id, event).await,
+            None => emit_creation_event(self.state, &self.user_login, event).await,
+        }
+    }
+
     /// Exécute une opération en l'encadrant avec une étape de déploiement.
     ///
     /// Si l'opération réussit, l'étape est émise avant l'exécution.
@@ -114,16 +339,11 @@ impl<'a> DeploymentOrchestrator<'a>
             {
                 error!(
                     "Operation '{}' failed for project '{}': {}",
-                    operation_name, self.project_name, e
+                    operation_name, log_safe(&self.project_name), e
                 );
 
-                let error_message = format!("{e}");
-                self.emit_stage(DeploymentStage::Failed 
-                {
-                    error: error_message,
-                    stage: operation_name.to_string(),
-                })
-                .await;
+                self.record_failed_stage(operation_name);
+                self.emit_stage(failed_stage(&e, operation_name)).await;
 
                 Err(e)
             }
@@ -161,36 +381,31 @@ impl<'a> DeploymentOrchestrator<'a>
             {
                 error!(
                     "Operation '{}' failed for project '{}': {}",
-                    operation_name, self.project_name, e
+                    operation_name, log_safe(&self.project_name), e
                 );
 
-                let error_message = format!("{e}");
-                self.emit_stage(DeploymentStage::Failed {
-                    error: error_message,
-                    stage: operation_name.to_string(),
-                })
-                .await;
+                self.record_failed_stage(operation_name);
+                self.emit_stage(failed_stage(&e, operation_name)).await;
 
                 Err(e)
             }
         }
     }
 
-    /// Émet l'étape de complétion avec les informations du container.
+    /// Émet l'étape de complétion avec les informations du container, sur le canal des étapes précédentes.
     pub async fn emit_completed(&self, container_name: String, project_id: i32)
     {
         info!("Deployment completed for project '{}' (container: {})", self.project_name, container_name);
-        
-        let stage = DeploymentStage::Completed { container_name };
-        
+
         debug!("Emitting completion for project '{}' (ID: {}, user: {})", self.project_name, project_id, self.user_login);
-        emit_creation_deployment_stage
+        send_completed
         (
-            self.state,
+            &self.state.sse_manager,
+            self.project_id,
             &self.user_login,
             self.project_name.clone(),
-            stage,
-            Some(project_id),
+            project_id,
+            container_name,
         ).await;
     }
 
@@ -199,8 +414,51 @@ impl<'a> DeploymentOrchestrator<'a>
     {
         error!(
             "Deployment failed for project '{}' at stage '{}': {}",
-            self.project_name, stage, error
+            log_safe(&self.project_name), stage, log_safe(&error)
         );
-        self.emit_stage(DeploymentStage::Failed { error, stage }).await;
+        self.record_failed_stage(&stage);
+        self.emit_stage(DeploymentStage::Failed { error, stage, vulnerabilities: Vec::new() }).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn completed_project(event: SseEvent) -> (i32, DeploymentStage)
+    {
+        match event
+        {
+            SseEvent::Deployment(event) => (event.project_id, event.stage),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_update_completion_goes_to_the_project_channel()
+    {
+        let manager = SseManager::new(10);
+        let mut project = block_on(manager.subscribe_to_project(7));
+        let mut creation = block_on(manager.subscribe_to_creation("alice"));
+
+        block_on(send_completed(&manager, Some(7), "alice", "demo".into(), 7, "hangar-demo-2".into()));
+
+        assert_eq!(completed_project(project.try_recv().unwrap()), (7, DeploymentStage::Completed { container_name: "hangar-demo-2".into() }));
+        assert!(creation.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_creation_completion_goes_to_the_creation_channel()
+    {
+        let manager = SseManager::new(10);
+        let mut project = block_on(manager.subscribe_to_project(7));
+        let mut creation = block_on(manager.subscribe_to_creation("alice"));
+
+        block_on(send_completed(&manager, None, "alice", "demo".into(), 7, "hangar-demo".into()));
+
+        assert_eq!(completed_project(creation.try_recv().unwrap()), (7, DeploymentStage::Completed { container_name: "hangar-demo".into() }));
+        assert!(project.try_recv().is_err());
     }
 }
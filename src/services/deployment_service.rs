@@ -0,0 +1,395 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    error::AppError,
+    model::deployment::{AdminDeployment, Deployment, DeploymentKind, DeploymentStatus, ResumeStage},
+    services::docker_service,
+    state::AppState,
+};
+
+/// Nombre de déploiements conservés par projet.
+const DEPLOYMENTS_RETENTION: i64 = 100;
+/// Intervalle entre deux suppressions des artefacts expirés.
+const ARTIFACTS_CLEANUP_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Progression d'un déploiement, enregistrée en base à sa fin.
+#[derive(Debug, Default)]
+pub struct DeploymentProgress
+{
+    pub image_tag: Option<String>,
+    pub image_scanned: bool,
+    pub image_digest: Option<String>,
+    pub commit_sha: Option<String>,
+    pub workspace_path: Option<String>,
+    pub failed_stage: Option<String>,
+}
+
+pub async fn create_deployment(
+    pool: &PgPool,
+    project_id: i32,
+    kind: DeploymentKind,
+    triggered_by: &str,
+    requested_image: Option<&str>,
+    retry_of: Option<i32>,
+) -> Result<i32, AppError>
+{
+    let id = sqlx::query_scalar(
+        "INSERT INTO deployments (project_id, kind, triggered_by, requested_image, retry_of, source_type) \
+         SELECT $1, $2, $3, $4, $5, source_type FROM projects WHERE id = $1 RETURNING id"
+    )
+    .bind(project_id)
+    .bind(kind)
+    .bind(triggered_by)
+    .bind(requested_image)
+    .bind(retry_of)
+    .fetch_one(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to record deployment for project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+
+    if let Err(e) = prune_deployments(pool, project_id).await
+    {
+        warn!("Failed to prune old deployments of project ID {}: {}", project_id, e);
+    }
+
+    Ok(id)
+}
+
+/// Enregistre l'issue d'un déploiement. En cas d'échec, les artefacts sont conservés `artifacts_ttl`.
+pub async fn finish_deployment(
+    pool: &PgPool,
+    deployment_id: i32,
+    succeeded: bool,
+    progress: &DeploymentProgress,
+    artifacts_ttl: Duration,
+)
+{
+    let (status, expire_at) = if succeeded
+    {
+        (DeploymentStatus::Succeeded, None)
+    }
+    else
+    {
+        let expire_at = progress.image_tag.as_ref().map(|_| OffsetDateTime::now_utc() + artifacts_ttl);
+        (DeploymentStatus::Failed, expire_at)
+    };
+
+    let result = sqlx::query(
+        "UPDATE deployments SET status = $1, failed_stage = $2, image_tag = $3, image_scanned = $4, \
+         artifacts_expire_at = $5, image_digest = $6, commit_sha = $7, finished_at = NOW() WHERE id = $8"
+    )
+    .bind(status)
+    .bind(if succeeded { None } else { progress.failed_stage.as_deref() })
+    .bind(progress.image_tag.as_deref())
+    .bind(progress.image_scanned)
+    .bind(expire_at)
+    .bind(progress.image_digest.as_deref())
+    .bind(progress.commit_sha.as_deref())
+    .bind(deployment_id)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result
+    {
+        error!("Failed to record outcome of deployment ID {}: {}", deployment_id, e);
+    }
+}
+
+/// Enregistre la création réussie d'un projet. Son échec n'est pas conservé : le projet n'existe plus.
+pub async fn record_creation(
+    pool: &PgPool,
+    project_id: i32,
+    triggered_by: &str,
+    started_at: OffsetDateTime,
+    progress: &DeploymentProgress,
+)
+{
+    let result = sqlx::query(
+        "INSERT INTO deployments (project_id, kind, status, triggered_by, source_type, image_tag, image_scanned, \
+         image_digest, commit_sha, workspace_path, created_at, finished_at) \
+         SELECT $1, $2, $3, $4, source_type, $5, $6, $7, $8, $9, $10, NOW() FROM projects WHERE id = $1"
+    )
+    .bind(project_id)
+    .bind(DeploymentKind::Creation)
+    .bind(DeploymentStatus::Succeeded)
+    .bind(triggered_by)
+    .bind(progress.image_tag.as_deref())
+    .bind(progress.image_scanned)
+    .bind(progress.image_digest.as_deref())
+    .bind(progress.commit_sha.as_deref())
+    .bind(progress.workspace_path.as_deref())
+    .bind(started_at)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result
+    {
+        error!("Failed to record creation of project ID {}: {}", project_id, e);
+    }
+}
+
+/// Rattache l'espace de travail d'un build au déploiement en cours.
+pub async fn set_workspace_path(pool: &PgPool, deployment_id: i32, workspace_path: &str)
+{
+    let result = sqlx::query("UPDATE deployments SET workspace_path = $1 WHERE id = $2")
+        .bind(workspace_path)
+        .bind(deployment_id)
+        .execute(pool)
+        .await;
+
+    if let Err(e) = result
+    {
+        warn!("Failed to record workspace of deployment ID {}: {}", deployment_id, e);
+    }
+}
+
+/// Parmi `deployment_ids`, ceux dont le déploiement est toujours en cours.
+pub async fn running_deployment_ids(pool: &PgPool, deployment_ids: &[i32]) -> Result<Vec<i32>, AppError>
+{
+    sqlx::query_scalar("SELECT id FROM deployments WHERE id = ANY($1) AND status = $2")
+        .bind(deployment_ids)
+        .bind(DeploymentStatus::Running)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list running deployments: {}", e);
+            AppError::InternalServerError
+        })
+}
+
+/// Tags et empreintes des images conservées pour la reprise de déploiements échoués.
+pub async fn list_retained_artifact_images(pool: &PgPool) -> Result<Vec<String>, AppError>
+{
+    let rows: Vec<(Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT image_tag, image_digest FROM deployments WHERE artifacts_expire_at > NOW()"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to list retained deployment images: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(rows.into_iter().flat_map(|(tag, digest)| [tag, digest]).flatten().collect())
+}
+
+/// Une page de déploiements, du plus récent au plus ancien.
+pub async fn list_deployments(pool: &PgPool, project_id: i32, limit: i64, offset: i64) -> Result<Vec<Deployment>, AppError>
+{
+    sqlx::query_as("SELECT * FROM deployments WHERE project_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3")
+        .bind(project_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list deployments of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Une page des déploiements de tous les projets, du plus récent au plus ancien.
+pub async fn list_all_deployments(pool: &PgPool, limit: i64, offset: i64) -> Result<Vec<AdminDeployment>, AppError>
+{
+    sqlx::query_as(
+        "SELECT d.*, p.name AS project_name FROM deployments d JOIN projects p ON p.id = d.project_id \
+         ORDER BY d.created_at DESC, d.id DESC LIMIT $1 OFFSET $2"
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to list deployments of all projects: {}", e);
+        AppError::InternalServerError
+    })
+}
+
+pub async fn get_deployment(pool: &PgPool, deployment_id: i32) -> Result<Option<Deployment>, AppError>
+{
+    sqlx::query_as("SELECT * FROM deployments WHERE id = $1")
+        .bind(deployment_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch deployment ID {}: {}", deployment_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Marque les artefacts d'un déploiement comme consommés par sa reprise.
+pub async fn release_artifacts(pool: &PgPool, deployment_id: i32) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE deployments SET image_tag = NULL, artifacts_expire_at = NULL WHERE id = $1")
+        .bind(deployment_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to release artifacts of deployment ID {}: {}", deployment_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Étape de reprise d'un déploiement échoué, si ses artefacts sont encore valides.
+#[must_use]
+pub fn resume_stage(deployment: &Deployment, image_present: bool, now: OffsetDateTime) -> Option<ResumeStage>
+{
+    let artifacts_valid = deployment.status == DeploymentStatus::Failed
+        && deployment.image_tag.is_some()
+        && deployment.artifacts_expire_at.is_some_and(|expire_at| expire_at > now);
+
+    if !artifacts_valid || !image_present
+    {
+        return None;
+    }
+
+    Some(if deployment.image_scanned { ResumeStage::Deploy } else { ResumeStage::Scan })
+}
+
+/// Comme [`resume_stage`], en vérifiant que l'image est toujours présente sur le démon Docker.
+pub async fn resolve_resume_stage(state: &AppState, deployment: &Deployment) -> Option<ResumeStage>
+{
+    let now = OffsetDateTime::now_utc();
+    // Évite un appel Docker lorsque la reprise est de toute façon impossible.
+    resume_stage(deployment, true, now)?;
+
+    let image_tag = deployment.image_tag.as_deref()?;
+    let image_present = matches!(docker_service::get_image_digest(&state.docker_client, image_tag).await, Ok(Some(_)));
+
+    resume_stage(deployment, image_present, now)
+}
+
+/// Tâche de fond : supprime les images intermédiaires des déploiements échoués expirés.
+pub async fn start_artifacts_cleanup(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting deployment artifacts cleanup task");
+    let mut interval = tokio::time::interval(ARTIFACTS_CLEANUP_INTERVAL);
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Deployment artifacts cleanup task shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        cleanup_expired_artifacts(&state).await;
+    }
+}
+
+async fn cleanup_expired_artifacts(state: &AppState)
+{
+    // Une image encore déployée par un projet (image publique partagée, par exemple) n'est jamais supprimée.
+    let expired: Vec<(i32, String, bool)> = match sqlx::query_as(
+        "SELECT d.id, d.image_tag, EXISTS (SELECT 1 FROM projects p WHERE p.deployed_image_tag = d.image_tag) \
+         FROM deployments d WHERE d.artifacts_expire_at < NOW() AND d.image_tag IS NOT NULL"
+    )
+    .fetch_all(&state.db_pool)
+    .await
+    {
+        Ok(expired) => expired,
+        Err(e) =>
+        {
+            error!("Failed to list expired deployment artifacts: {}", e);
+            return;
+        }
+    };
+
+    for (deployment_id, image_tag, in_use) in expired
+    {
+        if !in_use
+        {
+            info!("Removing expired image '{}' of deployment ID {}", image_tag, deployment_id);
+            let _ = docker_service::remove_image(&state.docker_client, &image_tag).await;
+        }
+
+        if let Err(e) = release_artifacts(&state.db_pool, deployment_id).await
+        {
+            warn!("Could not release artifacts of deployment ID {}: {:?}", deployment_id, e);
+        }
+    }
+}
+
+/// Ne conserve que les `DEPLOYMENTS_RETENTION` déploiements les plus récents, hors artefacts en attente.
+async fn prune_deployments(pool: &PgPool, project_id: i32) -> Result<(), sqlx::Error>
+{
+    sqlx::query(
+        "DELETE FROM deployments WHERE project_id = $1 AND artifacts_expire_at IS NULL AND id NOT IN \
+         (SELECT id FROM deployments WHERE project_id = $1 ORDER BY created_at DESC LIMIT $2)",
+    )
+    .bind(project_id)
+    .bind(DEPLOYMENTS_RETENTION)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed_deployment(image_scanned: bool, expires_in: time::Duration) -> Deployment
+    {
+        let now = OffsetDateTime::now_utc();
+        Deployment
+        {
+            id: 1,
+            project_id: 1,
+            kind: DeploymentKind::Rebuild,
+            status: DeploymentStatus::Failed,
+            triggered_by: "owner".to_string(),
+            source_type: None,
+            requested_image: None,
+            retry_of: None,
+            failed_stage: Some("Health check".to_string()),
+            image_tag: Some("hangar-app:123".to_string()),
+            image_digest: None,
+            commit_sha: None,
+            workspace_path: None,
+            image_scanned,
+            artifacts_expire_at: Some(now + expires_in),
+            created_at: now,
+            finished_at: Some(now),
+        }
+    }
+
+    #[test]
+    fn test_resume_stage_depends_on_scan()
+    {
+        let now = OffsetDateTime::now_utc();
+        assert_eq!(resume_stage(&failed_deployment(true, time::Duration::HOUR), true, now), Some(ResumeStage::Deploy));
+        assert_eq!(resume_stage(&failed_deployment(false, time::Duration::HOUR), true, now), Some(ResumeStage::Scan));
+    }
+
+    #[test]
+    fn test_resume_stage_falls_back_to_full_rerun()
+    {
+        let now = OffsetDateTime::now_utc();
+        assert_eq!(resume_stage(&failed_deployment(true, time::Duration::HOUR), false, now), None);
+        assert_eq!(resume_stage(&failed_deployment(true, -time::Duration::HOUR), true, now), None);
+
+        let mut succeeded = failed_deployment(true, time::Duration::HOUR);
+        succeeded.status = DeploymentStatus::Succeeded;
+        assert_eq!(resume_stage(&succeeded, true, now), None);
+    }
+}
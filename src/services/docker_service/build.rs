@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use bollard::Docker;
+use bollard::query_parameters::BuildImageOptions;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use futures::stream::StreamExt;
+use tar::Builder;
+use tracing::{debug, error, info};
+
+use crate::error::AppError;
+use crate::logging::log_safe;
+
+use super::error::DockerError;
+
+pub fn create_tarball(path: &Path) -> Result<Vec<u8>, AppError>
+{
+    let enc = GzEncoder::new(Vec::new(), Compression::default());
+    let mut tar = Builder::new(enc);
+    
+    tar.append_dir_all(".", path).map_err(|e| 
+    {
+        error!("Failed to append directory to tarball: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    let tar_data = tar.into_inner().and_then(flate2::write::GzEncoder::finish).map_err(|e| 
+    {
+        error!("Failed to finish tarball creation: {}", e);
+        AppError::InternalServerError
+    })?;
+    
+    Ok(tar_data)
+}
+
+pub async fn build_image_from_tar(
+    docker: &Docker,
+    tar_stream: Vec<u8>,
+    image_tag: &str,
+) -> Result<(), AppError>
+{
+    let options = BuildImageOptions 
+    {
+        dockerfile: "Dockerfile".to_string(),
+        t: Some(image_tag.to_string()),
+        rm: true,
+        ..Default::default()
+    };
+
+    let mut stream = docker.build_image(options, None, Some(bollard::body_full(tar_stream.into())));
+
+    while let Some(result) = stream.next().await
+    {
+        match result
+        {
+            Ok(info) =>
+            {
+                if let Some(error_detail) = info.error_detail
+                {
+                    error!("Failed to build image '{}': {}", image_tag, log_safe(&error_detail.message.unwrap_or_default()));
+                    return Err(AppError::BadRequest("Failed to build Docker image from source.".to_string()));
+                }
+                if let Some(stream_content) = info.stream
+                {
+                    debug!("Build > {}", log_safe(stream_content.trim()));
+                }
+            }
+            Err(e) => return Err(DockerError::new("build image", image_tag, e).into()),
+        }
+    }
+
+    info!("Image '{}' built successfully.", image_tag);
+    Ok(())
+}
@@ -0,0 +1,1019 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use bollard::Docker;
+use bollard::container::LogOutput;
+use bollard::errors::Error as BollardError;
+use bollard::models::{ContainerCreateBody, ContainerInspectResponse, HostConfig, VolumeCreateOptions};
+use bollard::query_parameters::
+{
+    CreateContainerOptionsBuilder, DownloadFromContainerOptionsBuilder, InspectContainerOptions, KillContainerOptions, ListContainersOptions, LogsOptions, RemoveContainerOptions, RenameContainerOptions, RestartContainerOptions, StartContainerOptions, StopContainerOptions, UploadToContainerOptionsBuilder, WaitContainerOptions
+};
+use bollard::secret::{Mount, MountTypeEnum, ResourcesUlimits, RestartPolicy};
+use futures::stream::{Stream, StreamExt};
+use time::OffsetDateTime;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, error, info, warn};
+
+use crate::config::ResourceProfile;
+use crate::error::{AppError, ProjectErrorCode};
+use crate::model::docker::{ContainerLogLine, ContainerLogs, LogStream, LogsFilter, PlatformContainer, TraefikRoute};
+use crate::model::project::ContainerCommand;
+use crate::services::rollback::RollbackGuard;
+use crate::sse::types::ContainerStatus;
+
+use super::error::{DockerError, docker_error};
+use super::{PROJECT_ID_LABEL, PROJECT_VOLUME_PREFIX, remove_volume_by_name, traefik_router_name};
+
+/// Variable lue par la libc pour déterminer le fuseau horaire du conteneur.
+const TIMEZONE_ENV_VAR: &str = "TZ";
+
+/// Échec de création ou de démarrage d'un conteneur dû à un réseau introuvable.
+fn is_network_not_found(error: &BollardError) -> bool
+{
+    match error
+    {
+        BollardError::DockerResponseServerError { message, .. } =>
+        {
+            let message = message.to_lowercase();
+            message.contains("network") && message.contains("not found")
+        }
+        _ => false,
+    }
+}
+
+/// Erreur renvoyée pour un conteneur de projet qui n'a pu être créé ou démarré.
+fn container_failure(error: &BollardError) -> ProjectErrorCode
+{
+    if is_network_not_found(error)
+    {
+        ProjectErrorCode::DockerNetworkNotFound
+    }
+    else if matches!(error, BollardError::DockerResponseServerError { status_code: 409, .. })
+    {
+        ProjectErrorCode::ContainerNameConflict
+    }
+    else
+    {
+        ProjectErrorCode::ContainerCreationFailed
+    }
+}
+
+pub async fn create_project_container(
+    docker: &Docker,
+    project_id: i32,
+    container_name: &str,
+    project_name: &str,
+    image_identifier: &str,
+    config: &crate::config::Config,
+    env_vars: &Option<HashMap<String, String>>,
+    persistent_volume_path: &Option<String>,
+    command: &ContainerCommand,
+    timezone: Option<&str>,
+    container_port: u16,
+    custom_domains: &[String],
+    resources: &ResourceProfile,
+    owners: &RoutingOwners<'_>,
+) -> Result<Option<String>, AppError>
+{
+    ensure_no_routing_conflict(docker, &config.app_prefix, project_id, project_name, owners).await?;
+
+    let mut mounts = vec![];
+    let mut volume_name_created: Option<String> = None;
+    // Seul un volume créé ici est supprimé en cas d'échec : un volume existant (projet désarchivé) garde ses données.
+    let mut volume_to_cleanup: Option<String> = None;
+    if let Some(path) = persistent_volume_path
+    {
+        let volume_name = format!("{PROJECT_VOLUME_PREFIX}{project_name}");
+        let volume_existed = docker.inspect_volume(&volume_name).await.is_ok();
+
+        let options = VolumeCreateOptions
+        {
+            name: Some(volume_name.clone()),
+            driver: Some("local".to_string()),
+            ..Default::default()
+        };
+        docker.create_volume(options).await.map_err(|e|
+        {
+            error!("Failed to create Docker volume '{}': {}", volume_name, e);
+            ProjectErrorCode::ContainerCreationFailed
+        })?;
+
+        volume_name_created = Some(volume_name.clone());
+        if !volume_existed
+        {
+            volume_to_cleanup = Some(volume_name.clone());
+        }
+
+        mounts.push(Mount
+        {
+            target: Some(path.clone()),
+            source: Some(volume_name),
+            typ: Some(MountTypeEnum::VOLUME),
+            ..Default::default()
+        });
+    }
+
+    let host_config = HostConfig 
+    {
+        restart_policy: Some(RestartPolicy 
+        {
+            name: Some(bollard::secret::RestartPolicyNameEnum::UNLESS_STOPPED),
+            maximum_retry_count: None,
+        }),
+        readonly_rootfs: Some(false),
+        mounts: Some(mounts),
+        ..limited_host_config(config, resources)
+    };
+
+    let env = container_env(env_vars.as_ref(), timezone.or(config.default_timezone.as_deref()));
+
+    let labels = project_container_labels(config, project_id, project_name, container_port, custom_domains);
+
+    let config = ContainerCreateBody 
+    {
+        image: Some(image_identifier.to_string()),
+        host_config: Some(host_config),
+        labels: Some(labels),
+        env,
+        entrypoint: command.entrypoint.clone(),
+        cmd: command.command.clone(),
+        ..Default::default()
+    };
+
+    let options = Some(CreateContainerOptionsBuilder::new().name(container_name).build());
+
+    // Le volume et le conteneur créés ici sont supprimés avant de renvoyer l'erreur.
+    let mut rollback = RollbackGuard::new();
+    if let Some(volume) = &volume_to_cleanup
+    {
+        rollback.push(format!("volume '{volume}'"), async move { remove_volume_by_name(docker, volume).await.map_err(AppError::from) });
+    }
+
+    let response = match docker.create_container(options, config).await
+    {
+        Ok(response) => response,
+        Err(e) =>
+        {
+            error!("Failed to create container '{}': {}", container_name, e);
+            rollback.run().await;
+            return Err(container_failure(&e).into());
+        }
+    };
+
+    if let Err(e) = docker.start_container(container_name, None::<StartContainerOptions>).await
+    {
+        error!("Failed to start container '{}': {}", container_name, e);
+        warn!("Attempting rollback for failed container start: {}", container_name);
+        rollback.push(format!("container '{container_name}'"), async move
+        {
+            docker.remove_container(container_name, None::<RemoveContainerOptions>).await
+                .map_err(|e| DockerError::new("remove container", container_name, e).into())
+        });
+        rollback.run().await;
+        return Err(container_failure(&e).into());
+    }
+
+    info!("Container '{}' created and started with ID: {}", container_name, response.id);
+    Ok(volume_name_created)
+}
+
+/// Labels posés sur le conteneur d'un projet : identification par Hangar et routage Traefik.
+#[must_use]
+pub fn project_container_labels(
+    config: &crate::config::Config,
+    project_id: i32,
+    project_name: &str,
+    container_port: u16,
+    custom_domains: &[String],
+) -> HashMap<String, String>
+{
+    let hostname = format!("{}.{}", project_name, &config.app_domain_suffix);
+    let router_name = traefik_router_name(&config.app_prefix, project_name);
+
+    HashMap::from([
+        ("app".to_string(), config.app_prefix.clone()),
+        (PROJECT_ID_LABEL.to_string(), project_id.to_string()),
+        ("traefik.enable".to_string(), "true".to_string()),
+        (format!("traefik.http.routers.{router_name}.rule"), traefik_host_rule(&hostname, custom_domains)),
+        (format!("traefik.http.routers.{router_name}.entrypoints"), config.traefik_entrypoint.clone()),
+        (format!("traefik.http.routers.{router_name}.tls.certresolver"), config.traefik_cert_resolver.clone()),
+        (format!("traefik.http.services.{router_name}.loadbalancer.server.port"), container_port.to_string()),
+    ])
+}
+
+/// Limites et isolation communes aux conteneurs des projets et à leurs conteneurs éphémères.
+fn limited_host_config(config: &crate::config::Config, resources: &ResourceProfile) -> HostConfig
+{
+    HostConfig
+    {
+        memory: Some(config.container_memory_mb.to_bytes().to_docker()),
+        cpu_quota: Some(config.container_cpu_quota.to_docker()),
+        network_mode: Some(config.docker_network.clone()),
+        security_opt: Some(vec![
+            "no-new-privileges:true".to_string(),
+            "apparmor:docker-default".to_string()
+        ]),
+        privileged: Some(false),
+        pids_limit: Some(resources.pids_limit),
+        ulimits: Some(vec![
+            ResourcesUlimits { name: Some("nofile".to_string()), soft: Some(resources.nofile_soft), hard: Some(resources.nofile_hard) },
+            ResourcesUlimits { name: Some("nproc".to_string()), soft: Some(resources.nproc_soft), hard: Some(resources.nproc_hard) }
+        ]),
+        tmpfs: Some(HashMap::from([
+            ("/tmp".to_string(), format!("rw,noexec,nosuid,size={}m", resources.tmp_size_mb))
+        ])),
+        oom_kill_disable: Some(false),
+        memory_swappiness: Some(0),
+        ..Default::default()
+    }
+}
+
+/// Règle Traefik du nom d'hôte principal et des domaines personnalisés vérifiés du projet.
+fn traefik_host_rule(hostname: &str, custom_domains: &[String]) -> String
+{
+    std::iter::once(hostname)
+        .chain(custom_domains.iter().map(String::as_str))
+        .map(|host| format!("Host(`{host}`)"))
+        .collect::<Vec<_>>()
+        .join(" || ")
+}
+
+/// Propriétaires des conteneurs sans label `hangar.project-id` (créés avant son introduction).
+#[derive(Debug, Clone)]
+pub struct RoutingOwners<'a>
+{
+    /// `container_name` actuel du projet, différent du conteneur créé pendant un blue-green.
+    pub own_container_name: &'a str,
+    /// Projet de chaque `projects.container_name` ; `None` si la base n'a pas pu être lue.
+    pub projects_by_container: Option<HashMap<String, i32>>,
+}
+
+impl RoutingOwners<'_>
+{
+    fn project_of(&self, container_name: &str) -> Option<i32>
+    {
+        self.projects_by_container.as_ref()?.get(container_name).copied()
+    }
+}
+
+/// Refuse de créer un conteneur dont le routeur Traefik est déjà déclaré par le conteneur d'un autre
+/// projet : Traefik n'en servirait qu'un, l'autre projet répondrait en 404. Un conteneur sans label
+/// `hangar.project-id` est attribué par son nom ; s'il n'appartient à aucun projet connu, il est en
+/// conflit sauf s'il porte le nom du conteneur actuel du projet.
+async fn ensure_no_routing_conflict(
+    docker: &Docker,
+    app_prefix: &str,
+    project_id: i32,
+    project_name: &str,
+    owners: &RoutingOwners<'_>,
+) -> Result<(), AppError>
+{
+    let router = traefik_router_name(app_prefix, project_name);
+    let routes = list_traefik_routes(docker, app_prefix).await?;
+
+    match find_routing_conflict(&routes, &router, project_id, owners)
+    {
+        Some(route) =>
+        {
+            warn!("Traefik router '{}' of project {} is already declared by container '{}'", router, project_id, route.container_name);
+            Err(ProjectErrorCode::RoutingConflict { router, container: route.container_name.clone() }.into())
+        }
+        None => Ok(()),
+    }
+}
+
+fn find_routing_conflict<'a>(routes: &'a [TraefikRoute], router: &str, project_id: i32, owners: &RoutingOwners<'_>) -> Option<&'a TraefikRoute>
+{
+    routes.iter().find(|route|
+    {
+        route.router == router && match route.project_id.or_else(|| owners.project_of(&route.container_name))
+        {
+            Some(id) => id != project_id,
+            None => route.container_name != owners.own_container_name,
+        }
+    })
+}
+
+/// Routeurs déclarés par les conteneurs de plusieurs projets, triés.
+#[must_use]
+pub fn conflicting_routers(routes: &[TraefikRoute]) -> Vec<String>
+{
+    let mut projects_by_router: HashMap<&str, Vec<i32>> = HashMap::new();
+    for route in routes
+    {
+        if let Some(project_id) = route.project_id
+        {
+            projects_by_router.entry(&route.router).or_default().push(project_id);
+        }
+    }
+
+    let mut routers: Vec<String> = projects_by_router.into_iter()
+        .filter(|(_, ids)| ids.iter().any(|id| *id != ids[0]))
+        .map(|(router, _)| router.to_string())
+        .collect();
+    routers.sort();
+    routers
+}
+
+/// Routeurs Traefik déclarés par les conteneurs de la plateforme, triés par routeur puis conteneur.
+pub async fn list_traefik_routes(docker: &Docker, app_prefix: &str) -> Result<Vec<TraefikRoute>, DockerError>
+{
+    let filters = HashMap::from([("label".to_string(), vec![format!("app={app_prefix}")])]);
+
+    let options = Some(ListContainersOptions
+    {
+        all: true,
+        filters: Some(filters),
+        ..Default::default()
+    });
+
+    let containers = docker.list_containers(options).await.map_err(docker_error("list containers", app_prefix))?;
+
+    let mut routes: Vec<TraefikRoute> = containers
+        .into_iter()
+        .filter_map(|c| Some((c.names?.into_iter().next()?.trim_start_matches('/').to_string(), c.labels?)))
+        .flat_map(|(name, labels)| traefik_routes(&name, &labels))
+        .collect();
+    routes.sort_by(|a, b| a.router.cmp(&b.router).then_with(|| a.container_name.cmp(&b.container_name)));
+    Ok(routes)
+}
+
+/// Routeurs déclarés par les labels `traefik.http.routers.{routeur}.rule` d'un conteneur.
+fn traefik_routes(container_name: &str, labels: &HashMap<String, String>) -> Vec<TraefikRoute>
+{
+    let project_id = labels.get(PROJECT_ID_LABEL).and_then(|id| id.parse().ok());
+
+    labels.iter()
+        .filter_map(|(label, rule)|
+        {
+            let router = label.strip_prefix("traefik.http.routers.")?.strip_suffix(".rule")?;
+            Some(TraefikRoute
+            {
+                router: router.to_string(),
+                container_name: container_name.to_string(),
+                project_id,
+                rule: rule.clone(),
+                hostnames: rule_hostnames(rule),
+            })
+        })
+        .collect()
+}
+
+/// Noms d'hôte des ``Host(`...`)`` d'une règle Traefik.
+fn rule_hostnames(rule: &str) -> Vec<String>
+{
+    rule.split("Host(`")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('`').map(|(host, _)| host.to_string()))
+        .collect()
+}
+
+/// Variables d'environnement au format `CLE=valeur`. `TZ` est ajoutée en dernier et remplace
+/// celle de l'utilisateur : le fuseau configuré du projet fait foi.
+fn container_env(env_vars: Option<&HashMap<String, String>>, timezone: Option<&str>) -> Option<Vec<String>>
+{
+    if env_vars.is_none() && timezone.is_none()
+    {
+        return None;
+    }
+
+    let mut env: Vec<String> = env_vars.into_iter().flatten()
+        .filter(|(key, _)| timezone.is_none() || key.as_str() != TIMEZONE_ENV_VAR)
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+
+    if let Some(timezone) = timezone
+    {
+        env.push(format!("{TIMEZONE_ENV_VAR}={timezone}"));
+    }
+
+    Some(env)
+}
+
+/// Conteneur éphémère lancé à partir de l'image d'un projet (tâches planifiées, commandes ponctuelles).
+pub struct OneOffTask<'a>
+{
+    pub container_name: &'a str,
+    pub image: &'a str,
+    pub command: &'a [String],
+    pub env_vars: Option<&'a HashMap<String, String>>,
+    /// Fuseau horaire du projet (`None` : fuseau par défaut de l'instance).
+    pub timezone: Option<&'a str>,
+    /// Volume du projet (nom du volume Docker, chemin dans le conteneur).
+    pub volume: Option<(&'a str, &'a str)>,
+    /// Profil de ressources du projet, appliqué comme à son conteneur.
+    pub resources: &'a ResourceProfile,
+    pub timeout: Duration,
+    /// Nombre de dernières lignes de sortie conservées.
+    pub output_tail_lines: usize,
+}
+
+#[derive(Debug)]
+pub struct OneOffOutcome
+{
+    pub exit_code: Option<i64>,
+    pub timed_out: bool,
+    pub output_tail: Vec<String>,
+}
+
+/// Exécute une commande dans un conteneur éphémère avec les limites de ressources du projet,
+/// sans exposition Traefik, puis supprime systématiquement le conteneur.
+/// Chaque ligne de sortie est transmise à `line_sink` si fourni.
+pub async fn run_one_off_container(
+    docker: &Docker,
+    config: &crate::config::Config,
+    task: &OneOffTask<'_>,
+    line_sink: Option<UnboundedSender<String>>,
+) -> Result<OneOffOutcome, AppError>
+{
+    let mounts = task.volume.map(|(volume_name, target)| vec![Mount
+    {
+        target: Some(target.to_string()),
+        source: Some(volume_name.to_string()),
+        typ: Some(MountTypeEnum::VOLUME),
+        ..Default::default()
+    }]);
+
+    let host_config = HostConfig
+    {
+        mounts,
+        ..limited_host_config(config, task.resources)
+    };
+
+    // Label distinct de celui des projets pour ne pas être suivi par l'écouteur d'événements Docker.
+    let labels = HashMap::from([
+        ("app".to_string(), format!("{}-one-off", config.app_prefix)),
+    ]);
+
+    let body = ContainerCreateBody
+    {
+        image: Some(task.image.to_string()),
+        cmd: Some(task.command.to_vec()),
+        env: container_env(task.env_vars, task.timezone.or(config.default_timezone.as_deref())),
+        host_config: Some(host_config),
+        labels: Some(labels),
+        ..Default::default()
+    };
+
+    let options = Some(CreateContainerOptionsBuilder::new().name(task.container_name).build());
+    docker.create_container(options, body).await.map_err(|e|
+    {
+        error!("Failed to create one-off container '{}': {}", task.container_name, e);
+        ProjectErrorCode::ContainerCreationFailed
+    })?;
+
+    let outcome = match docker.start_container(task.container_name, None::<StartContainerOptions>).await
+    {
+        Ok(()) =>
+        {
+            // Le tampon survit à l'expiration du délai : la fin de la sortie reste disponible.
+            let mut tail = VecDeque::with_capacity(task.output_tail_lines);
+            let run = collect_one_off_output(docker, task, line_sink, &mut tail);
+            match tokio::time::timeout(task.timeout, run).await
+            {
+                Ok(exit_code) => Ok(OneOffOutcome { exit_code, timed_out: false, output_tail: tail.into() }),
+                Err(_) =>
+                {
+                    warn!("One-off container '{}' exceeded its {}s timeout, killing it.", task.container_name, task.timeout.as_secs());
+                    if let Err(e) = docker.kill_container(task.container_name, None::<KillContainerOptions>).await
+                    {
+                        error!("Failed to kill one-off container '{}': {}", task.container_name, e);
+                    }
+                    Ok(OneOffOutcome { exit_code: None, timed_out: true, output_tail: tail.into() })
+                }
+            }
+        }
+        Err(e) =>
+        {
+            error!("Failed to start one-off container '{}': {}", task.container_name, e);
+            Err(ProjectErrorCode::ContainerCreationFailed.into())
+        }
+    };
+
+    let remove_options = Some(RemoveContainerOptions { force: true, ..Default::default() });
+    if let Err(e) = docker.remove_container(task.container_name, remove_options).await
+    {
+        error!("Failed to remove one-off container '{}': {}", task.container_name, e);
+    }
+
+    outcome
+}
+
+/// Relaie la sortie du conteneur jusqu'à sa fin et renvoie son code de sortie ; les dernières lignes
+/// sont conservées dans `tail`.
+async fn collect_one_off_output(
+    docker: &Docker,
+    task: &OneOffTask<'_>,
+    line_sink: Option<UnboundedSender<String>>,
+    tail: &mut VecDeque<String>,
+) -> Option<i64>
+{
+    let options = Some(LogsOptions
+    {
+        follow: true,
+        stdout: true,
+        stderr: true,
+        ..Default::default()
+    });
+
+    let mut stream = docker.logs(task.container_name, options);
+
+    while let Some(chunk) = stream.next().await
+    {
+        let Ok(chunk) = chunk else { break };
+        for line in chunk.to_string().lines()
+        {
+            if let Some(sink) = &line_sink
+            {
+                let _ = sink.send(line.to_string());
+            }
+            if tail.len() == task.output_tail_lines
+            {
+                tail.pop_front();
+            }
+            if task.output_tail_lines > 0
+            {
+                tail.push_back(line.to_string());
+            }
+        }
+    }
+
+    let mut wait = std::pin::pin!(docker.wait_container(task.container_name, None::<WaitContainerOptions>));
+    match wait.next().await
+    {
+        Some(Ok(response)) => Some(response.status_code),
+        Some(Err(BollardError::DockerContainerWaitError { code, .. })) => Some(code),
+        Some(Err(e)) =>
+        {
+            error!("Failed to wait for one-off container '{}': {}", task.container_name, e);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Délai d'arrêt Docker par défaut, utilisé pour les conteneurs qui n'ont pas encore servi de trafic.
+pub const DEFAULT_STOP_GRACE_SECONDS: i32 = 10;
+
+/// Options d'arrêt : SIGTERM, puis SIGKILL après `stop_grace_seconds`.
+const fn stop_options(stop_grace_seconds: i32) -> StopContainerOptions
+{
+    StopContainerOptions { signal: None, t: Some(stop_grace_seconds) }
+}
+
+pub async fn remove_container(docker: &Docker, container_name: &str, stop_grace_seconds: i32) -> Result<(), DockerError> 
+{
+    info!("Attempting to stop and remove container: {}", container_name);
+
+    match docker.stop_container(container_name, Some(stop_options(stop_grace_seconds))).await 
+    {
+        Ok(()) => (),
+        Err(BollardError::DockerResponseServerError { status_code, .. }) if status_code == 404 || status_code == 304 =>
+        {
+            warn!("Container {} not found or already stopped. No action taken.", container_name);
+        },
+        Err(e) => 
+        {
+            error!("Error stopping container {}: {}", container_name, e);
+        }
+    }
+
+    match docker.remove_container(container_name, None::<RemoveContainerOptions>).await 
+    {
+        Ok(()) => (),
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) => 
+        {
+            warn!("Container {} not found during removal. It might have been deleted already.", container_name);
+        },
+        Err(e) => return Err(DockerError::new("remove container", container_name, e)),
+    }
+
+    info!("Container {} has been successfully removed.", container_name);
+    Ok(())
+}
+
+pub async fn start_container_by_name(docker: &Docker, container_name: &str) -> Result<(), DockerError> 
+{
+    docker.start_container(container_name, None::<StartContainerOptions>).await.map_err(docker_error("start container", container_name))?;
+    Ok(())
+}
+
+pub async fn stop_container_by_name(docker: &Docker, container_name: &str, stop_grace_seconds: i32) -> Result<(), DockerError> 
+{
+    docker.stop_container(container_name, Some(stop_options(stop_grace_seconds))).await.map_err(docker_error("stop container", container_name))?;
+    Ok(())
+}
+
+pub async fn restart_container_by_name(docker: &Docker, container_name: &str, stop_grace_seconds: i32) -> Result<(), DockerError>
+{
+    let options = RestartContainerOptions { signal: None, t: Some(stop_grace_seconds) };
+    docker.restart_container(container_name, Some(options)).await.map_err(docker_error("restart container", container_name))?;
+    Ok(())
+}
+
+/// Copie `path` depuis le conteneur, sous forme d'archive tar dont la racine est le dernier segment de `path`.
+pub async fn download_container_path(docker: &Docker, container_name: &str, path: &str) -> Result<Vec<u8>, DockerError>
+{
+    let options = DownloadFromContainerOptionsBuilder::new().path(path).build();
+    let mut stream = docker.download_from_container(container_name, Some(options));
+
+    let mut archive = Vec::new();
+    while let Some(chunk) = stream.next().await
+    {
+        archive.extend_from_slice(&chunk.map_err(docker_error("download archive from container", container_name))?);
+    }
+    Ok(archive)
+}
+
+/// Extrait une archive tar dans le répertoire `path` du conteneur.
+pub async fn upload_container_archive(docker: &Docker, container_name: &str, path: &str, archive: Vec<u8>) -> Result<(), DockerError>
+{
+    let options = UploadToContainerOptionsBuilder::new().path(path).build();
+    docker.upload_to_container(container_name, Some(options), bollard::body_full(archive.into())).await
+        .map_err(docker_error("upload archive to container", container_name))
+}
+
+/// Volume maximal de logs renvoyé par `GET /api/projects/{id}/logs`, compté sur les trames brutes de Docker :
+/// la réponse compressée par `CompressionLayer` est toujours plus petite.
+pub const MAX_LOG_SIZE: usize = 10 * 1024 * 1024; // 10 MB
+
+pub async fn get_container_logs(docker: &Docker, container_name: &str, filter: &LogsFilter) -> Result<ContainerLogs, AppError> 
+{
+    info!("Fetching logs for container '{}' with {:?}", container_name, filter);
+
+    let options = Some(LogsOptions 
+    {
+        stdout: filter.stdout,
+        stderr: filter.stderr,
+        since: unix_seconds(filter.since)?,
+        until: unix_seconds(filter.until)?,
+        tail: filter.tail.to_string(),
+        timestamps: filter.timestamps,
+        ..Default::default()
+    });
+
+    let mut stream = docker.logs(container_name, options);
+    let mut logs = ContainerLogs::default();
+
+    while let Some(log_result) = stream.next().await 
+    {
+        match log_result 
+        {
+            Ok(log_output) => 
+            {
+                let (log_stream, message) = log_frame(log_output);
+                if !logs.push_frame(log_stream, &message, MAX_LOG_SIZE)
+                {
+                    break;
+                }
+            }
+            Err(e) => 
+            {
+                error!("Error streaming logs for container '{}': {}", container_name, e);
+            }
+        }
+    }
+
+    Ok(logs.finish())
+}
+
+/// Suit les logs d'un conteneur à partir de maintenant (`follow`, sans historique) : chaque ligne
+/// est renvoyée dès qu'elle est complète. Le flux se termine quand le conteneur s'arrête ou est
+/// supprimé, ou à la première erreur Docker.
+pub fn follow_container_logs(docker: &Docker, container_name: &str) -> impl Stream<Item = ContainerLogLine> + Send + 'static
+{
+    debug!("Following logs for container '{}'", container_name);
+
+    let options = Some(LogsOptions
+    {
+        stdout: true,
+        stderr: true,
+        follow: true,
+        tail: "0".to_string(),
+        timestamps: true,
+        ..Default::default()
+    });
+
+    let container_name = container_name.to_string();
+    docker.logs(&container_name, options)
+        .scan(ContainerLogs::default(), move |logs, log_result|
+        {
+            let lines = match log_result
+            {
+                Ok(log_output) =>
+                {
+                    let (log_stream, message) = log_frame(log_output);
+                    logs.push_frame(log_stream, &message, usize::MAX);
+                    Some(futures::stream::iter(logs.take_lines()))
+                }
+                Err(e) =>
+                {
+                    warn!("Log stream for container '{}' ended with an error: {}", container_name, e);
+                    None
+                }
+            };
+            futures::future::ready(lines)
+        })
+        .flatten()
+}
+
+/// Docker attend des secondes Unix sur 32 bits, 0 signifiant « sans borne ».
+fn unix_seconds(instant: Option<OffsetDateTime>) -> Result<i32, AppError>
+{
+    instant.map_or(Ok(0), |instant| i32::try_from(instant.unix_timestamp())
+        .map_err(|_| AppError::BadRequest(format!("Log timestamp {instant} is out of range."))))
+}
+
+fn log_frame(log_output: LogOutput) -> (LogStream, axum::body::Bytes)
+{
+    match log_output
+    {
+        LogOutput::StdOut { message } => (LogStream::Stdout, message),
+        LogOutput::StdErr { message } => (LogStream::Stderr, message),
+        LogOutput::StdIn { message } => (LogStream::Stdin, message),
+        LogOutput::Console { message } => (LogStream::Console, message),
+    }
+}
+
+// Used only for initial status checks
+pub async fn get_container_status(docker: &Docker, container_name: &str) -> Result<Option<ContainerStatus>, DockerError> 
+{
+    match docker.inspect_container(container_name, None::<InspectContainerOptions>).await 
+    {
+        Ok(details) => 
+        {
+            let status = details.state.and_then(|s| s.status).into();
+            Ok(Some(status))
+        },
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) => 
+        {
+            Ok(None)
+        },
+        Err(e) => Err(DockerError::new("inspect container", container_name, e)),
+    }
+}
+
+pub async fn inspect_container_details(docker: &Docker, container_name: &str) -> Result<Option<ContainerInspectResponse>, DockerError> 
+{
+    match docker.inspect_container(container_name, None::<InspectContainerOptions>).await 
+    {
+        Ok(details) => Ok(Some(details)),
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) => 
+        {
+            Ok(None)
+        },
+        Err(e) => Err(DockerError::new("inspect container", container_name, e)),
+    }
+}
+
+pub async fn rename_container(docker: &Docker, container_name: &str, new_name: &str) -> Result<(), DockerError>
+{
+    docker.rename_container(container_name, RenameContainerOptions { name: new_name.to_string() }).await.map_err(docker_error("rename container", container_name))?;
+    Ok(())
+}
+
+/// Conteneurs de la plateforme créés avant l'ajout du label `hangar.project-id`.
+/// Ils sont réétiquetés au prochain redéploiement du projet (les labels Docker sont immuables).
+pub async fn list_unlabeled_project_containers(docker: &Docker, app_prefix: &str) -> Result<Vec<String>, DockerError>
+{
+    let filters = HashMap::from([("label".to_string(), vec![format!("app={app_prefix}")])]);
+
+    let options = Some(ListContainersOptions
+    {
+        all: true,
+        filters: Some(filters),
+        ..Default::default()
+    });
+
+    let containers = docker.list_containers(options).await.map_err(docker_error("list containers", app_prefix))?;
+
+    Ok(containers
+        .into_iter()
+        .filter(|c| c.labels.as_ref().is_none_or(|labels| !labels.contains_key(PROJECT_ID_LABEL)))
+        .filter_map(|c| c.names.and_then(|names| names.into_iter().next()))
+        .map(|name| name.trim_start_matches('/').to_string())
+        .collect())
+}
+
+/// Conteneurs portant le label `app={app_prefix}`, hors conteneurs ponctuels des tâches.
+pub async fn list_platform_containers(docker: &Docker, app_prefix: &str) -> Result<Vec<PlatformContainer>, DockerError>
+{
+    let filters = HashMap::from([("label".to_string(), vec![format!("app={app_prefix}")])]);
+
+    let options = Some(ListContainersOptions
+    {
+        all: true,
+        filters: Some(filters),
+        ..Default::default()
+    });
+
+    let containers = docker.list_containers(options).await.map_err(docker_error("list containers", app_prefix))?;
+
+    Ok(containers
+        .into_iter()
+        .filter_map(|c|
+        {
+            let name = c.names?.into_iter().next()?.trim_start_matches('/').to_string();
+            let project_id = c.labels.as_ref()
+                .and_then(|labels| labels.get(PROJECT_ID_LABEL))
+                .and_then(|id| id.parse().ok());
+            let created_at = c.created.and_then(|created| OffsetDateTime::from_unix_timestamp(created).ok());
+            Some(PlatformContainer { name, project_id, created_at })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, MockDocker, MockReply};
+
+    #[tokio::test]
+    async fn test_timed_out_one_off_keeps_its_output_tail()
+    {
+        let mock = MockDocker::start(vec![
+            ("POST", "/containers/create", MockReply::Json(201, serde_json::json!({ "Id": "job", "Warnings": [] }))),
+            ("POST", "/containers/hangar-job/start", MockReply::Empty(204)),
+            ("GET", "/containers/hangar-job/logs", MockReply::HangingLogs(vec!["one", "two", "three"])),
+            ("POST", "/containers/hangar-job/kill", MockReply::Empty(204)),
+            ("DELETE", "/containers/hangar-job", MockReply::Empty(204)),
+        ]).await;
+        let command = ["sleep".to_string(), "infinity".to_string()];
+        let task = OneOffTask
+        {
+            container_name: "hangar-job",
+            image: "nginx:1",
+            command: &command,
+            env_vars: None,
+            timezone: None,
+            volume: None,
+            resources: &ResourceProfile::default(),
+            timeout: Duration::from_millis(500),
+            output_tail_lines: 2,
+        };
+
+        let outcome = run_one_off_container(&mock.client(), &test_support::config(), &task, None).await.unwrap();
+
+        assert!(outcome.timed_out);
+        assert_eq!(outcome.exit_code, None);
+        assert_eq!(outcome.output_tail, ["two", "three"]);
+        let requests = mock.requests();
+        assert!(requests.contains(&"POST /containers/hangar-job/kill".to_string()));
+        assert!(requests.contains(&"DELETE /containers/hangar-job".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_refused_actions_keep_the_daemon_status()
+    {
+        let mock = MockDocker::start(vec![
+            ("POST", "/containers/hangar-app/start", MockReply::Json(409, serde_json::json!({ "message": "container is paused" }))),
+            ("POST", "/containers/hangar-app/stop", MockReply::Json(409, serde_json::json!({ "message": "container is restarting" }))),
+        ]).await;
+
+        let error = start_container_by_name(&mock.client(), "hangar-app").await.unwrap_err();
+        assert_eq!(error.status(), Some(409));
+        assert!(matches!(error.into_user_error(), AppError::BadRequest(message) if message.contains("conflicts with its current state")));
+
+        let error = stop_container_by_name(&mock.client(), "hangar-app", 10).await.unwrap_err();
+        assert_eq!(error.status(), Some(409));
+        assert!(matches!(AppError::from(error), AppError::InternalServerError));
+    }
+
+    #[tokio::test]
+    async fn test_remove_container_tolerates_a_stopped_or_missing_container()
+    {
+        let mock = MockDocker::start(vec![
+            ("POST", "/containers/hangar-app/stop", MockReply::Empty(304)),
+            ("DELETE", "/containers/hangar-app", MockReply::Empty(404)),
+            ("POST", "/containers/hangar-busy/stop", MockReply::Empty(204)),
+            ("DELETE", "/containers/hangar-busy", MockReply::Json(409, serde_json::json!({ "message": "removal in progress" }))),
+        ]).await;
+
+        remove_container(&mock.client(), "hangar-app", 10).await.unwrap();
+        assert_eq!(mock.requests(), ["POST /containers/hangar-app/stop", "DELETE /containers/hangar-app"]);
+
+        let error = remove_container(&mock.client(), "hangar-busy", 10).await.unwrap_err();
+        assert_eq!(error.status(), Some(409));
+    }
+
+    #[tokio::test]
+    async fn test_missing_container_inspects_as_none()
+    {
+        let mock = MockDocker::start(vec![
+            ("GET", "/containers/hangar-app/json", MockReply::Json(200, serde_json::json!({ "Name": "/hangar-app", "State": { "Status": "running" } }))),
+            ("GET", "/containers/hangar-broken/json", MockReply::Json(500, serde_json::json!({ "message": "daemon failure" }))),
+        ]).await;
+
+        assert_eq!(get_container_status(&mock.client(), "hangar-app").await.unwrap(), Some(ContainerStatus::Running));
+        assert!(inspect_container_details(&mock.client(), "hangar-gone").await.unwrap().is_none());
+        assert_eq!(get_container_status(&mock.client(), "hangar-gone").await.unwrap(), None);
+        assert_eq!(get_container_status(&mock.client(), "hangar-broken").await.unwrap_err().status(), Some(500));
+    }
+
+    #[tokio::test]
+    async fn test_platform_containers_are_read_from_their_labels()
+    {
+        let mock = MockDocker::start(vec![
+            ("GET", "/containers/json", MockReply::Json(200, serde_json::json!([
+                { "Names": ["/hangar-blog"], "Labels": { "app": "hangar", PROJECT_ID_LABEL: "7" }, "Created": 0 },
+                { "Names": ["/hangar-old"], "Labels": { "app": "hangar" }, "Created": 0 },
+            ]))),
+        ]).await;
+
+        let containers = list_platform_containers(&mock.client(), "hangar").await.unwrap();
+        assert_eq!(containers.iter().map(|c| (c.name.as_str(), c.project_id)).collect::<Vec<_>>(), [("hangar-blog", Some(7)), ("hangar-old", None)]);
+        assert_eq!(list_unlabeled_project_containers(&mock.client(), "hangar").await.unwrap(), ["hangar-old"]);
+    }
+
+    #[test]
+    fn test_host_config_applies_the_resource_profile()
+    {
+        let resources = ResourceProfile { pids_limit: 64, tmp_size_mb: 20, ..ResourceProfile::default() };
+
+        let host_config = limited_host_config(&test_support::config(), &resources);
+
+        assert_eq!(host_config.pids_limit, Some(64));
+        assert_eq!(host_config.tmpfs.unwrap()["/tmp"], "rw,noexec,nosuid,size=20m");
+        assert_eq!(host_config.memory, Some(512 * 1024 * 1024));
+        assert_eq!(host_config.cpu_quota, Some(50_000));
+    }
+
+    #[test]
+    fn test_traefik_host_rule()
+    {
+        assert_eq!(traefik_host_rule("app.hangar.garageisep.com", &[]), "Host(`app.hangar.garageisep.com`)");
+        assert_eq!(
+            traefik_host_rule("app.hangar.garageisep.com", &["app.garageisep.com".to_string(), "club.fr".to_string()]),
+            "Host(`app.hangar.garageisep.com`) || Host(`app.garageisep.com`) || Host(`club.fr`)"
+        );
+    }
+
+    #[test]
+    fn test_routing_conflicts_ignore_the_same_project_and_resolve_unlabeled_containers()
+    {
+        let labels = |project_id: Option<&str>| -> HashMap<String, String>
+        {
+            let mut labels = HashMap::from([
+                ("traefik.http.routers.hangar-blog.rule".to_string(), "Host(`blog.hangar.fr`) || Host(`blog.club.fr`)".to_string()),
+                ("traefik.http.routers.hangar-blog.entrypoints".to_string(), "websecure".to_string()),
+            ]);
+            if let Some(id) = project_id
+            {
+                labels.insert(PROJECT_ID_LABEL.to_string(), id.to_string());
+            }
+            labels
+        };
+
+        let routes = traefik_routes("hangar-blog", &labels(Some("7")));
+        assert_eq!(routes, vec![TraefikRoute
+        {
+            router: "hangar-blog".to_string(),
+            container_name: "hangar-blog".to_string(),
+            project_id: Some(7),
+            rule: "Host(`blog.hangar.fr`) || Host(`blog.club.fr`)".to_string(),
+            hostnames: vec!["blog.hangar.fr".to_string(), "blog.club.fr".to_string()],
+        }]);
+
+        let owners = |own_container_name, known: &[(&str, i32)]| RoutingOwners
+        {
+            own_container_name,
+            projects_by_container: Some(known.iter().map(|(name, id)| ((*name).to_string(), *id)).collect()),
+        };
+        let unknown = owners("hangar-blog-x1", &[]);
+
+        assert!(find_routing_conflict(&routes, "hangar-blog", 7, &unknown).is_none());
+        assert!(find_routing_conflict(&routes, "hangar-other", 8, &unknown).is_none());
+        assert_eq!(find_routing_conflict(&routes, "hangar-blog", 8, &unknown).map(|r| r.container_name.as_str()), Some("hangar-blog"));
+
+        // Sans label, le conteneur est attribué par `projects.container_name`...
+        let unlabeled = traefik_routes("hangar-blog-old", &labels(None));
+        assert!(find_routing_conflict(&unlabeled, "hangar-blog", 8, &owners("hangar-blog", &[("hangar-blog-old", 8)])).is_none());
+        assert!(find_routing_conflict(&unlabeled, "hangar-blog", 8, &owners("hangar-blog", &[("hangar-blog-old", 7)])).is_some());
+
+        // ... et, faute de propriétaire connu, n'est toléré que s'il est le conteneur actuel du projet.
+        assert!(find_routing_conflict(&unlabeled, "hangar-blog", 8, &owners("hangar-blog-old", &[])).is_none());
+        assert!(find_routing_conflict(&unlabeled, "hangar-blog", 8, &unknown).is_some());
+        let unreadable = RoutingOwners { own_container_name: "hangar-blog", projects_by_container: None };
+        assert!(find_routing_conflict(&unlabeled, "hangar-blog", 8, &unreadable).is_some());
+
+        let blue_green = [routes.clone(), routes.clone(), unlabeled].concat();
+        assert!(conflicting_routers(&blue_green).is_empty());
+        let conflicting = [routes, traefik_routes("hangar-blog-2", &labels(Some("8")))].concat();
+        assert_eq!(conflicting_routers(&conflicting), ["hangar-blog"]);
+    }
+
+    #[test]
+    fn test_container_failures_are_told_apart()
+    {
+        let error = |status_code, message: &str| BollardError::DockerResponseServerError { status_code, message: message.to_string() };
+
+        assert_eq!(container_failure(&error(404, "network hangar_net not found")), ProjectErrorCode::DockerNetworkNotFound);
+        assert_eq!(container_failure(&error(404, "No such image: app:1")), ProjectErrorCode::ContainerCreationFailed);
+        assert_eq!(container_failure(&error(500, "driver failed programming external connectivity")), ProjectErrorCode::ContainerCreationFailed);
+        assert_eq!(
+            container_failure(&error(409, "Conflict. The container name \"/hangar-app-1\" is already in use")),
+            ProjectErrorCode::ContainerNameConflict
+        );
+    }
+}
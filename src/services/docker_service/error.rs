@@ -0,0 +1,154 @@
+use bollard::errors::Error as BollardError;
+use thiserror::Error;
+use tracing::{error, warn};
+
+use crate::{error::AppError, logging::log_safe};
+
+/// Échec d'un appel au démon Docker, avec l'opération, sa cible et le code HTTP renvoyé par Docker.
+#[derive(Debug, Error)]
+pub enum DockerError
+{
+    /// Docker a répondu, en refusant l'opération.
+    #[error("Docker refused to {operation} '{target}' (status {status}): {message}")]
+    Rejected
+    {
+        operation: &'static str,
+        target: String,
+        status: u16,
+        message: String,
+    },
+
+    /// Docker est injoignable, ou sa réponse est illisible.
+    #[error("Failed to {operation} '{target}': {source}")]
+    Unavailable
+    {
+        operation: &'static str,
+        target: String,
+        #[source]
+        source: BollardError,
+    },
+
+    /// Docker a répondu sans rien renvoyer (flux de statistiques vide).
+    #[error("Docker returned nothing to {operation} '{target}'")]
+    Empty
+    {
+        operation: &'static str,
+        target: String,
+    },
+}
+
+impl DockerError
+{
+    /// `operation` est un verbe et son objet (`"start container"`), `target` le nom concerné.
+    pub fn new(operation: &'static str, target: impl Into<String>, error: BollardError) -> Self
+    {
+        let target = target.into();
+        match error
+        {
+            BollardError::DockerResponseServerError { status_code, message } => Self::Rejected { operation, target, status: status_code, message },
+            source => Self::Unavailable { operation, target, source },
+        }
+    }
+
+    /// Code HTTP de la réponse de Docker, `None` s'il n'a pas répondu.
+    #[must_use]
+    pub const fn status(&self) -> Option<u16>
+    {
+        match self
+        {
+            Self::Rejected { status, .. } => Some(*status),
+            Self::Unavailable { .. } | Self::Empty { .. } => None,
+        }
+    }
+}
+
+/// Pour `map_err` : `docker.start_container(..).await.map_err(docker_error("start container", name))?`.
+pub(super) fn docker_error(operation: &'static str, target: &str) -> impl FnOnce(BollardError) -> DockerError
+{
+    move |error| DockerError::new(operation, target, error)
+}
+
+impl DockerError
+{
+    /// Conversion pour une opération que l'utilisateur demande sur sa propre ressource (démarrer ou arrêter
+    /// son conteneur, lire ses métriques) : un refus dû à l'état de la ressource lui est renvoyé en 4xx, avec
+    /// l'opération et sa cible mais sans le message du démon. Les autres échecs suivent la conversion par défaut.
+    #[must_use]
+    pub fn into_user_error(self) -> AppError
+    {
+        match &self
+        {
+            Self::Rejected { operation, target, status: 404, .. } | Self::Empty { operation, target } =>
+            {
+                warn!("{}", log_safe(&self.to_string()));
+                AppError::NotFound(format!("Could not {operation} '{target}': it does not exist."))
+            }
+            Self::Rejected { operation, target, status: 304, .. } =>
+            {
+                warn!("{}", log_safe(&self.to_string()));
+                AppError::BadRequest(format!("Could not {operation} '{target}': it is already in the requested state."))
+            }
+            Self::Rejected { operation, target, status: 409, .. } =>
+            {
+                warn!("{}", log_safe(&self.to_string()));
+                AppError::BadRequest(format!("Could not {operation} '{target}': it conflicts with its current state."))
+            }
+            Self::Rejected { .. } | Self::Unavailable { .. } => self.into(),
+        }
+    }
+}
+
+/// Conversion par défaut, pour les opérations menées par la plateforme (déploiement, nettoyage, tâches de
+/// fond) : un refus de Docker y est une erreur du serveur, jamais du client. La cause complète est journalisée.
+impl From<DockerError> for AppError
+{
+    fn from(error: DockerError) -> Self
+    {
+        error!("{}", log_safe(&error.to_string()));
+        Self::InternalServerError
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejected(status_code: u16) -> DockerError
+    {
+        DockerError::new("start container", "hangar-app", BollardError::DockerResponseServerError { status_code, message: "daemon message".to_string() })
+    }
+
+    #[test]
+    fn test_status_code_is_kept()
+    {
+        assert_eq!(rejected(409).status(), Some(409));
+        assert_eq!(DockerError::new("list containers", "hangar", BollardError::RequestTimeoutError).status(), None);
+    }
+
+    #[test]
+    fn test_user_operation_conversion()
+    {
+        assert!(matches!(rejected(404).into_user_error(), AppError::NotFound(message) if message == "Could not start container 'hangar-app': it does not exist."));
+        assert!(matches!(rejected(304).into_user_error(), AppError::BadRequest(message) if message.contains("already in the requested state")));
+        assert!(matches!(rejected(409).into_user_error(), AppError::BadRequest(_)));
+        assert!(matches!(rejected(500).into_user_error(), AppError::InternalServerError));
+        assert!(matches!(DockerError::new("list containers", "hangar", BollardError::RequestTimeoutError).into_user_error(), AppError::InternalServerError));
+    }
+
+    #[test]
+    fn test_platform_operation_conversion_is_a_server_error()
+    {
+        for status in [304, 404, 409, 500]
+        {
+            assert!(matches!(AppError::from(rejected(status)), AppError::InternalServerError));
+        }
+        assert!(matches!(AppError::from(DockerError::Empty { operation: "read stats of container", target: "hangar-app".to_string() }), AppError::InternalServerError));
+    }
+
+    #[test]
+    fn test_daemon_message_is_not_sent_to_client()
+    {
+        let AppError::NotFound(message) = rejected(404).into_user_error() else { panic!("expected NotFound") };
+        assert!(!message.contains("daemon message"));
+    }
+}
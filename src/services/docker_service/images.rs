@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+
+use bollard::Docker;
+use bollard::auth::DockerCredentials;
+use bollard::errors::Error as BollardError;
+use bollard::query_parameters::{CreateImageOptions, ListImagesOptions, RemoveImageOptions};
+use futures::stream::StreamExt;
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tokio::process::Command;
+use tracing::{error, info, warn};
+
+use crate::error::{AppError, ProjectErrorCode};
+use crate::logging::log_safe;
+use crate::model::docker::PlatformImage;
+use crate::model::scan::{ScanFindings, Vulnerability, VulnerabilitySeverity};
+
+use super::LOCAL_IMAGE_REPOSITORY;
+use super::error::{DockerError, docker_error};
+
+pub async fn pull_image(docker: &Docker, image_url: &str, credentials: Option<DockerCredentials>) -> Result<(), DockerError> 
+{
+    let options = Some(CreateImageOptions 
+    {
+        from_image: Some(image_url.to_string()),
+        ..Default::default()
+    });
+
+    let mut stream = docker.create_image(options, None, credentials);
+
+    info!("Pulling image {}", log_safe(image_url));
+    while let Some(result) = stream.next().await 
+    {
+        match result 
+        {
+            Ok(info) => 
+            {
+                if let Some(error_detail) = info.error_detail
+                    && let Some(message) = error_detail.message
+                        && (message.to_lowercase().contains("unauthorized") || message.to_lowercase().contains("authentication required")) 
+                        {
+                            warn!("Authentication error during image pull for '{}': {}", log_safe(image_url), log_safe(&message));
+                        }
+            }
+            Err(e) => 
+            {
+                return Err(DockerError::new("pull image", image_url, e));
+            }
+        }
+    }
+    info!("Image '{}' pulled successfully.", log_safe(image_url));
+    Ok(())
+}
+
+/// Scanne l'image avec grype. Les vulnérabilités de `ignored_cves` (liste du projet) ne comptent pas :
+/// le scan passe si toutes celles qui atteignent `GRYPE_FAIL_ON_SEVERITY` sont ignorées.
+pub async fn scan_image_with_grype(image_url: &str, ignored_cves: &[String], config: &crate::config::Config) -> Result<(), AppError> 
+{
+    if !config.grype_enabled 
+    {
+        warn!("Grype scan is disabled via GRYPE_ENABLED=false. Skipping security scan for image '{}'.", log_safe(image_url));
+        return Ok(());
+    }
+
+    info!("Scanning image '{}' with Grype...", log_safe(image_url));
+
+    let mut command = Command::new("grype");
+    command
+        .arg(image_url)
+        .arg("-o")
+        .arg("json")
+        .arg("--only-fixed")
+        .arg("--fail-on")
+        .arg(&config.grype_fail_on_severity)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let output = command.output().await.map_err(|e| 
+    {
+        error!("Failed to execute grype command: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    if !output.status.success() 
+    {
+        let findings = parse_grype_report(&output.stdout, &output.stderr).without_ignored(ignored_cves);
+
+        if ignored_cves.is_empty() || findings.blocks(VulnerabilitySeverity::parse(&config.grype_fail_on_severity))
+        {
+            warn!("Grype found vulnerabilities in image '{}'", log_safe(image_url));
+            return Err(ProjectErrorCode::ImageScanFailed(findings).into());
+        }
+
+        info!("Grype scan passed for image '{}': every blocking vulnerability is in the project's ignore list.", log_safe(image_url));
+        return Ok(());
+    }
+
+    info!("Grype scan passed for image '{}'.", log_safe(image_url));
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct GrypeReport
+{
+    #[serde(default)]
+    matches: Vec<GrypeMatch>,
+}
+
+#[derive(Deserialize)]
+struct GrypeMatch
+{
+    vulnerability: GrypeVulnerability,
+    artifact: GrypeArtifact,
+}
+
+#[derive(Deserialize)]
+struct GrypeVulnerability
+{
+    id: String,
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    fix: Option<GrypeFix>,
+}
+
+#[derive(Deserialize)]
+struct GrypeFix
+{
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct GrypeArtifact
+{
+    name: String,
+    #[serde(default)]
+    version: String,
+}
+
+/// Lit la sortie `-o json` de grype ; si elle n'est pas lisible (grype a échoué avant le scan, par exemple),
+/// la sortie brute est conservée.
+fn parse_grype_report(stdout: &[u8], stderr: &[u8]) -> ScanFindings
+{
+    match serde_json::from_slice::<GrypeReport>(stdout)
+    {
+        Ok(report) => ScanFindings::from_vulnerabilities(report.matches.into_iter()
+            .map(|m| Vulnerability
+            {
+                id: m.vulnerability.id,
+                severity: VulnerabilitySeverity::parse(&m.vulnerability.severity),
+                package: m.artifact.name,
+                version: m.artifact.version,
+                fixed_in: m.vulnerability.fix.map(|fix| fix.versions).unwrap_or_default(),
+            })
+            .collect()),
+        Err(e) =>
+        {
+            warn!("Could not parse grype JSON output, keeping the raw report: {}", e);
+            let stdout = String::from_utf8_lossy(stdout).trim().to_string();
+            ScanFindings::Raw(if stdout.is_empty() { String::from_utf8_lossy(stderr).trim().to_string() } else { stdout })
+        }
+    }
+}
+
+pub async fn remove_image(docker: &Docker, image_url: &str) -> Result<(), DockerError>
+{
+    info!("Attempting to remove image: {}", log_safe(image_url));
+
+    let options = Some(RemoveImageOptions 
+    {
+        force: true,
+        ..Default::default()
+    });
+    docker.remove_image(image_url, options, None).await.map_err(docker_error("remove image", image_url))?;
+
+    info!("Image {} successfully removed", log_safe(image_url));
+    Ok(())
+}
+
+pub async fn get_image_digest(docker: &Docker, image_tag: &str) -> Result<Option<String>, DockerError> 
+{
+    match docker.inspect_image(image_tag).await 
+    {
+        Ok(details) => 
+        {
+            if let Some(id) = details.id 
+            {
+                Ok(Some(id))
+            } 
+            else 
+            {
+                warn!("No ID found for image '{}'", image_tag);
+                Ok(None)
+            }
+        },
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) => 
+        {
+            warn!("Image '{}' not found for inspection.", image_tag);
+            Ok(None)
+        },
+        Err(e) => Err(DockerError::new("inspect image", image_tag, e)),
+    }
+}
+
+/// Référence épinglée (`image@sha256:…`) de l'image telle que tirée du registre. Une image construite
+/// localement n'en a pas.
+pub async fn get_image_repo_digest(docker: &Docker, image_tag: &str) -> Result<Option<String>, DockerError>
+{
+    match docker.inspect_image(image_tag).await
+    {
+        Ok(details) => Ok(details.repo_digests.unwrap_or_default().into_iter().next()),
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) => Ok(None),
+        Err(e) => Err(DockerError::new("inspect image", image_tag, e)),
+    }
+}
+
+/// Chemins où l'image place son contenu : répertoire de travail et `HANGAR_WEBROOT_DIR` s'il est défini.
+pub async fn get_image_content_paths(docker: &Docker, image_tag: &str) -> Result<Vec<String>, DockerError>
+{
+    let details = docker.inspect_image(image_tag).await.map_err(docker_error("inspect image", image_tag))?;
+
+    let Some(config) = details.config else { return Ok(Vec::new()) };
+
+    let working_dir = config.working_dir.filter(|dir| !dir.is_empty() && dir != "/");
+    let webroot = config.env.unwrap_or_default().into_iter()
+        .find_map(|var| var.strip_prefix("HANGAR_WEBROOT_DIR=").map(str::to_string));
+
+    Ok(working_dir.into_iter().chain(webroot).collect())
+}
+
+/// Valeurs par défaut d'une image, pour distinguer ce qui a été ajouté au lancement d'un conteneur.
+#[derive(Debug, Default, Clone)]
+pub struct ImageDefaults
+{
+    pub env: Vec<String>,
+    pub entrypoint: Option<Vec<String>>,
+    pub cmd: Option<Vec<String>>,
+}
+
+pub async fn get_image_defaults(docker: &Docker, image: &str) -> Result<ImageDefaults, DockerError>
+{
+    let details = docker.inspect_image(image).await.map_err(docker_error("inspect image", image))?;
+
+    let Some(config) = details.config else { return Ok(ImageDefaults::default()) };
+
+    Ok(ImageDefaults
+    {
+        env: config.env.unwrap_or_default(),
+        entrypoint: config.entrypoint,
+        cmd: config.cmd,
+    })
+}
+
+/// Images construites par la plateforme, reconnues à leurs tags `hangar-local/*`.
+pub async fn list_local_images(docker: &Docker) -> Result<Vec<PlatformImage>, DockerError>
+{
+    let images = docker.list_images(None::<ListImagesOptions>).await.map_err(docker_error("list images", LOCAL_IMAGE_REPOSITORY))?;
+
+    Ok(images
+        .into_iter()
+        .filter_map(|image|
+        {
+            let tags: Vec<String> = image.repo_tags.into_iter().filter(|tag| tag.starts_with(LOCAL_IMAGE_REPOSITORY)).collect();
+            (!tags.is_empty()).then(|| PlatformImage
+            {
+                id: image.id,
+                tags,
+                created_at: OffsetDateTime::from_unix_timestamp(image.created).ok(),
+            })
+        })
+        .collect())
+}
+
+/// Images sans tag (couches laissées par un build ou une image retaguée), qui n'apparaissent pas dans [`list_local_images`].
+pub async fn list_dangling_images(docker: &Docker) -> Result<Vec<PlatformImage>, DockerError>
+{
+    let filters = HashMap::from([("dangling".to_string(), vec!["true".to_string()])]);
+    let options = Some(ListImagesOptions { filters: Some(filters), ..Default::default() });
+
+    let images = docker.list_images(options).await.map_err(docker_error("list images", "dangling"))?;
+
+    Ok(images
+        .into_iter()
+        .map(|image| PlatformImage
+        {
+            id: image.id,
+            tags: Vec::new(),
+            created_at: OffsetDateTime::from_unix_timestamp(image.created).ok(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockDocker, MockReply};
+
+    #[tokio::test]
+    async fn test_missing_image_has_no_digest()
+    {
+        let mock = MockDocker::start(vec![
+            ("GET", "/images/nginx:1/json", MockReply::Json(200, serde_json::json!({ "Id": "sha256:1111" }))),
+            ("GET", "/images/broken:1/json", MockReply::Json(500, serde_json::json!({ "message": "daemon failure" }))),
+        ]).await;
+
+        assert_eq!(get_image_digest(&mock.client(), "nginx:1").await.unwrap().as_deref(), Some("sha256:1111"));
+        assert_eq!(get_image_digest(&mock.client(), "gone:1").await.unwrap(), None);
+        assert_eq!(get_image_digest(&mock.client(), "broken:1").await.unwrap_err().status(), Some(500));
+    }
+
+    #[tokio::test]
+    async fn test_refused_pull_keeps_the_registry_status()
+    {
+        let mock = MockDocker::start(vec![
+            ("POST", "/images/create", MockReply::Json(403, serde_json::json!({ "message": "denied" }))),
+        ]).await;
+
+        let error = pull_image(&mock.client(), "ghcr.io/garage-isep/private:1", None).await.unwrap_err();
+        assert_eq!(error.status(), Some(403));
+    }
+
+    #[test]
+    fn test_parse_grype_report()
+    {
+        let json = br#"{"matches": [
+            {"vulnerability": {"id": "CVE-2024-1", "severity": "Medium", "fix": {"versions": ["1.2.4"], "state": "fixed"}},
+             "artifact": {"name": "zlib", "version": "1.2.3"}},
+            {"vulnerability": {"id": "CVE-2024-2", "severity": "Critical", "fix": {"versions": ["3.0.8", "3.1.1"], "state": "fixed"}},
+             "artifact": {"name": "openssl", "version": "3.0.7"}}
+        ]}"#;
+
+        let ScanFindings::Vulnerabilities(vulnerabilities) = parse_grype_report(json, b"") else { panic!("expected parsed vulnerabilities") };
+        assert_eq!(vulnerabilities[0].id, "CVE-2024-2");
+        assert_eq!(vulnerabilities[0].severity, VulnerabilitySeverity::Critical);
+        assert_eq!(vulnerabilities[0].fixed_in, vec!["3.0.8", "3.1.1"]);
+        assert_eq!((vulnerabilities[1].package.as_str(), vulnerabilities[1].version.as_str()), ("zlib", "1.2.3"));
+    }
+
+    #[test]
+    fn test_unparsable_grype_output_is_kept_raw()
+    {
+        assert_eq!(parse_grype_report(b"NAME  INSTALLED  FIXED-IN\n", b""), ScanFindings::Raw("NAME  INSTALLED  FIXED-IN".to_string()));
+        assert_eq!(parse_grype_report(b"", b"image not found\n"), ScanFindings::Raw("image not found".to_string()));
+    }
+}
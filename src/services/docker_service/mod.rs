@@ -0,0 +1,49 @@
+//! Accès au démon Docker, découpé par domaine. Les fonctions restent exposées sous
+//! `docker_service::*` : les appelants n'ont pas à connaître le sous-module.
+
+mod build;
+mod containers;
+mod error;
+mod images;
+mod stats;
+mod volumes;
+
+use bollard::Docker;
+use bollard::errors::Error as BollardError;
+use bollard::query_parameters::InspectNetworkOptions;
+
+pub use build::*;
+pub use containers::*;
+pub use error::DockerError;
+pub use images::*;
+pub use stats::*;
+pub use volumes::*;
+
+/// Label portant l'identifiant du projet, utilisé pour corréler les conteneurs sans se fier à leur nom.
+pub const PROJECT_ID_LABEL: &str = "hangar.project-id";
+
+/// Préfixe des volumes de données des projets (`hangar-data-{projet}`).
+pub const PROJECT_VOLUME_PREFIX: &str = "hangar-data-";
+
+/// Dépôt des images construites depuis GitHub (`hangar-local/{projet}:{horodatage}`).
+pub const LOCAL_IMAGE_REPOSITORY: &str = "hangar-local/";
+
+/// Nom du routeur et du service Traefik d'un projet, préfixé pour ne pas entrer en collision avec les
+/// routeurs statiques. Les conteneurs créés avant ce préfixe gardent leurs labels `{project}` jusqu'à
+/// leur prochaine recréation.
+#[must_use]
+pub fn traefik_router_name(app_prefix: &str, project_name: &str) -> String
+{
+    format!("{app_prefix}-{project_name}")
+}
+
+/// Le réseau Docker des projets (`DOCKER_NETWORK`) existe-t-il ?
+pub async fn network_exists(docker: &Docker, network: &str) -> Result<bool, DockerError>
+{
+    match docker.inspect_network(network, None::<InspectNetworkOptions>).await
+    {
+        Ok(_) => Ok(true),
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) => Ok(false),
+        Err(e) => Err(DockerError::new("inspect network", network, e)),
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use bollard::Docker;
+use bollard::errors::Error as BollardError;
+use bollard::query_parameters::{DataUsageOptions, ListContainersOptions, StatsOptions};
+use bollard::secret::{ContainerCpuStats, ContainerStatsResponse};
+use futures::stream::StreamExt;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::{debug, warn};
+
+use crate::model::docker::{BaseImageStatus, DockerDaemonInfo, DockerDiskUsage};
+use crate::model::project::{GlobalMetrics, ProjectMetrics};
+use crate::units::MemoryBytes;
+
+use super::PROJECT_ID_LABEL;
+use super::error::{DockerError, docker_error};
+
+/// Délai maximal d'attente du second échantillon de [`get_container_metrics`].
+const SECOND_STATS_SAMPLE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Métriques ponctuelles d'un conteneur (API REST, premier événement SSE).
+///
+/// Sur un premier échantillon, Docker renvoie souvent un `precpu_stats` vide : le CPU est donc
+/// calculé entre deux échantillons consécutifs du flux de statistiques (environ une seconde
+/// d'écart). Si le second n'arrive pas à temps, le calcul se fait sur le premier seul.
+pub async fn get_container_metrics(docker: &Docker, container_name: &str) -> Result<ProjectMetrics, DockerError> 
+{
+    let mut stream = docker.stats(container_name, Some(StatsOptions 
+    { 
+        stream: true, 
+        one_shot: false,
+    }));
+
+    let first = next_stats_sample(&mut stream, container_name).await?;
+
+    let second = match tokio::time::timeout(SECOND_STATS_SAMPLE_TIMEOUT, stream.next()).await
+    {
+        Ok(Some(Ok(stats))) => Some(stats),
+        Ok(Some(Err(e))) =>
+        {
+            debug!("Second stats sample of container '{}' failed, using a single sample: {}", container_name, e);
+            None
+        }
+        Ok(None) | Err(_) =>
+        {
+            debug!("Second stats sample of container '{}' timed out, using a single sample", container_name);
+            None
+        }
+    };
+
+    Ok(metrics_from_samples(&first, second.as_ref()))
+}
+
+/// Métriques à partir d'un seul échantillon, pour le collecteur périodique.
+///
+/// Moins coûteux que [`get_container_metrics`] : le `precpu_stats` de l'échantillon sert de lecture
+/// précédente. Il peut être vide au premier relevé, ce que corrigent les relevés suivants.
+pub async fn sample_container_metrics(docker: &Docker, container_name: &str) -> Result<ProjectMetrics, DockerError>
+{
+    let mut stream = docker.stats(container_name, Some(StatsOptions
+    {
+        stream: false,
+        ..Default::default()
+    }));
+
+    let stats = next_stats_sample(&mut stream, container_name).await?;
+    Ok(metrics_from_samples(&stats, None))
+}
+
+async fn next_stats_sample(
+    stream: &mut (impl futures::Stream<Item = Result<ContainerStatsResponse, BollardError>> + Unpin),
+    container_name: &str,
+) -> Result<ContainerStatsResponse, DockerError>
+{
+    match stream.next().await
+    {
+        Some(Ok(stats)) => Ok(stats),
+        Some(Err(e)) => Err(DockerError::new("read stats of container", container_name, e)),
+        None => Err(DockerError::Empty { operation: "read stats of container", target: container_name.to_string() }),
+    }
+}
+
+/// CPU entre deux échantillons si le second est disponible, sinon depuis le `precpu_stats` du premier.
+/// La mémoire est celle de l'échantillon le plus récent.
+fn metrics_from_samples(first: &ContainerStatsResponse, second: Option<&ContainerStatsResponse>) -> ProjectMetrics
+{
+    let (cpu_usage_percent, latest) = match second
+    {
+        Some(second) => (cpu_percent_between(first.cpu_stats.as_ref(), second.cpu_stats.as_ref()), second),
+        None => (calculate_cpu_percent(first), first),
+    };
+    let (memory_usage_bytes, memory_limit_bytes) = calculate_memory(latest);
+    let (network_rx_bytes, network_tx_bytes) = calculate_network(latest);
+    let (block_read_bytes, block_write_bytes) = calculate_block_io(latest);
+
+    ProjectMetrics
+    {
+        cpu_usage_percent,
+        memory_usage_bytes,
+        memory_limit_bytes,
+        network_rx_bytes,
+        network_tx_bytes,
+        block_read_bytes,
+        block_write_bytes,
+    }
+}
+
+/// Écart entre l'horloge du démon Docker, lue dans l'horodatage des statistiques du conteneur,
+/// et celle de l'API. Les conteneurs partagent l'horloge du noyau de l'hôte : cet écart est celui
+/// que voit l'application. `None` si le conteneur est arrêté.
+pub async fn get_container_clock_drift(docker: &Docker, container_name: &str) -> Result<Option<i64>, DockerError>
+{
+    let sent_at = OffsetDateTime::now_utc();
+    let mut stream = docker.stats(container_name, Some(StatsOptions
+    {
+        stream: false,
+        one_shot: true,
+    }));
+
+    let stats = match stream.next().await
+    {
+        Some(Ok(stats)) => stats,
+        Some(Err(e)) => return Err(DockerError::new("read stats of container", container_name, e)),
+        None => return Ok(None),
+    };
+    let received_at = OffsetDateTime::now_utc();
+
+    // Un conteneur arrêté renvoie l'horodatage nul "0001-01-01T00:00:00Z".
+    let Some(read_at) = stats.read
+        .and_then(|read| OffsetDateTime::parse(&read, &Rfc3339).ok())
+        .filter(|read| read.year() > 1)
+    else
+    {
+        return Ok(None);
+    };
+
+    let host_now = sent_at + (received_at - sent_at) / 2_i32;
+    Ok(i64::try_from((read_at - host_now).whole_milliseconds()).ok())
+}
+
+fn calculate_cpu_percent(stats: &ContainerStatsResponse) -> f64 
+{
+    cpu_percent_between(stats.precpu_stats.as_ref(), stats.cpu_stats.as_ref())
+}
+
+/// Pourcentage CPU (100 % = un cœur) entre une lecture précédente et une lecture courante.
+fn cpu_percent_between(previous: Option<&ContainerCpuStats>, current: Option<&ContainerCpuStats>) -> f64
+{
+    let calculation = || -> Option<f64> 
+    {
+        let cpu_stats = current?;
+        let precpu_stats = previous?;
+
+        let cpu_usage = cpu_stats.cpu_usage.as_ref()?;
+        let precpu_usage = precpu_stats.cpu_usage.as_ref()?;
+
+        let total_usage = cpu_usage.total_usage?;
+        let pre_total_usage = precpu_usage.total_usage?;
+
+        let cpu_delta = total_usage as f64 - pre_total_usage as f64;
+
+        let system_cpu_delta = (cpu_stats.system_cpu_usage? as f64) - (precpu_stats.system_cpu_usage? as f64);
+
+        let number_of_cpus = f64::from(cpu_stats.online_cpus.unwrap_or(1));
+
+        if system_cpu_delta > 0.0 && cpu_delta > 0.0 
+        {
+            Some((cpu_delta / system_cpu_delta) * number_of_cpus * 100.0)
+        } 
+        else 
+        {
+            Some(0.0)
+        }
+    }();
+
+    calculation.unwrap_or(0.0)
+}
+
+/// Mémoire utilisée (hors cache) et limite du conteneur.
+fn calculate_memory(stats: &ContainerStatsResponse) -> (MemoryBytes, MemoryBytes) 
+{
+    if let Some(mem_stats) = stats.memory_stats.as_ref() 
+    {
+        let usage = MemoryBytes::new(mem_stats.usage.unwrap_or(0));
+        let limit = MemoryBytes::new(mem_stats.limit.unwrap_or(0));
+
+        let cache = MemoryBytes::new(mem_stats.stats.as_ref()
+            .and_then(|s| s.get("cache"))
+            .map_or(0, |v| *v));
+
+        (usage.saturating_sub(cache), limit)
+    } 
+    else 
+    {
+        (MemoryBytes::default(), MemoryBytes::default())
+    }
+}
+
+/// Octets reçus et émis, sommés sur toutes les interfaces.
+/// `networks` est absent pour les conteneurs en `network_mode` `host` ou `none`.
+fn calculate_network(stats: &ContainerStatsResponse) -> (u64, u64)
+{
+    stats.networks.as_ref().map_or((0, 0), |networks|
+    {
+        networks.values().fold((0, 0), |(rx, tx), network|
+        {
+            (rx + network.rx_bytes.unwrap_or(0), tx + network.tx_bytes.unwrap_or(0))
+        })
+    })
+}
+
+/// Octets lus et écrits, sommés sur tous les périphériques.
+/// L'opération vaut `Read`/`Write` en cgroup v1 et `read`/`write` en cgroup v2.
+fn calculate_block_io(stats: &ContainerStatsResponse) -> (u64, u64)
+{
+    let entries = stats.blkio_stats.as_ref().and_then(|blkio| blkio.io_service_bytes_recursive.as_ref());
+
+    entries.into_iter().flatten().fold((0, 0), |(read, write), entry|
+    {
+        let value = entry.value.unwrap_or(0);
+        match entry.op.as_deref()
+        {
+            Some(op) if op.eq_ignore_ascii_case("read") => (read + value, write),
+            Some(op) if op.eq_ignore_ascii_case("write") => (read, write + value),
+            _ => (read, write),
+        }
+    })
+}
+
+pub async fn get_global_container_stats(docker: &Docker, app_prefix: &str) -> Result<GlobalMetrics, DockerError> 
+{
+    let mut filters = HashMap::new();
+    filters.insert("label".to_string(), vec![format!("app={}", app_prefix), PROJECT_ID_LABEL.to_string()]);
+
+    let options = Some(ListContainersOptions 
+    {
+        all: true,
+        filters: Some(filters),
+        ..Default::default()
+    });
+
+    let containers = docker.list_containers(options).await.map_err(docker_error("list containers", app_prefix))?;
+
+    let mut running_containers = 0;
+    let mut total_cpu_usage = 0.0;
+    let mut total_memory_usage = MemoryBytes::default();
+    let mut total_network_rx_bytes: u64 = 0;
+    let mut total_network_tx_bytes: u64 = 0;
+
+    for container_summary in containers 
+    {
+        if let Some(state) = container_summary.state
+            && state.to_string() == "running"
+                && let Some(id) = container_summary.id 
+                {
+                    let mut stream = docker.stats(&id, Some(StatsOptions { stream: false, ..Default::default() }));
+                    if let Some(stats_result) = stream.next().await 
+                    {
+                        match stats_result 
+                        {
+                            Ok(stats) => 
+                            {
+                                running_containers += 1;
+                                total_cpu_usage += calculate_cpu_percent(&stats);
+                                let (mem_usage, _) = calculate_memory(&stats);
+                                total_memory_usage = total_memory_usage.saturating_add(mem_usage);
+                                let (rx_bytes, tx_bytes) = calculate_network(&stats);
+                                total_network_rx_bytes = total_network_rx_bytes.saturating_add(rx_bytes);
+                                total_network_tx_bytes = total_network_tx_bytes.saturating_add(tx_bytes);
+                            }
+                            Err(e) => {
+                                warn!("Could not get stats for running container {}: {}", id, e);
+                            }
+                        }
+                    }
+                }
+    }
+    
+    Ok(GlobalMetrics 
+    {
+        total_projects: 0,
+        running_containers,
+        total_cpu_usage,
+        total_memory_usage_mb: total_memory_usage.as_mb_f64(),
+        total_network_rx_bytes,
+        total_network_tx_bytes,
+    })
+}
+
+/// Projets dont un conteneur est démarré, d'après le label `hangar.project-id`.
+pub async fn list_running_project_ids(docker: &Docker, app_prefix: &str) -> Result<HashSet<i32>, DockerError>
+{
+    let filters = HashMap::from([
+        ("label".to_string(), vec![format!("app={app_prefix}"), PROJECT_ID_LABEL.to_string()]),
+        ("status".to_string(), vec!["running".to_string()]),
+    ]);
+
+    let options = Some(ListContainersOptions
+    {
+        filters: Some(filters),
+        ..Default::default()
+    });
+
+    let containers = docker.list_containers(options).await.map_err(docker_error("list containers", app_prefix))?;
+
+    Ok(containers
+        .into_iter()
+        .filter_map(|c| c.labels?.get(PROJECT_ID_LABEL)?.parse().ok())
+        .collect())
+}
+
+/// Informations sur le démon Docker, sans l'utilisation disque (voir `get_disk_usage`).
+pub async fn get_daemon_info(docker: &Docker, disk_usage: DockerDiskUsage, base_images: Vec<BaseImageStatus>) -> Result<DockerDaemonInfo, DockerError>
+{
+    let info = docker.info().await.map_err(docker_error("read info of", "daemon"))?;
+
+    let version = docker.version().await.map_err(docker_error("read version of", "daemon"))?;
+
+    Ok(DockerDaemonInfo
+    {
+        server_version: version.version.or(info.server_version),
+        api_version: version.api_version,
+        storage_driver: info.driver,
+        cgroup_version: info.cgroup_version.map(|v| v.to_string()),
+        kernel_version: info.kernel_version,
+        operating_system: info.operating_system,
+        architecture: version.arch.or(info.architecture),
+        total_containers: info.containers,
+        running_containers: info.containers_running,
+        total_images: info.images,
+        disk_usage,
+        base_images,
+    })
+}
+
+/// Équivalent de `docker system df`. Appel coûteux, à mettre en cache côté appelant.
+pub async fn get_disk_usage(docker: &Docker) -> Result<DockerDiskUsage, DockerError>
+{
+    let df = docker.df(None::<DataUsageOptions>).await.map_err(docker_error("read disk usage of", "daemon"))?;
+
+    let images_bytes = df.layers_size.unwrap_or(0);
+    let containers_bytes = df.containers.unwrap_or_default().iter().filter_map(|c| c.size_rw).sum();
+    let volumes_bytes = df.volumes.unwrap_or_default().iter()
+        .filter_map(|v| v.usage_data.as_ref().map(|u| u.size.max(0)))
+        .sum();
+    let build_cache_bytes = df.build_cache.unwrap_or_default().iter().filter_map(|b| b.size).sum();
+
+    Ok(DockerDiskUsage
+    {
+        images_bytes,
+        containers_bytes,
+        volumes_bytes,
+        build_cache_bytes,
+        total_bytes: images_bytes + containers_bytes + volumes_bytes + build_cache_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::secret::{ContainerBlkioStatEntry, ContainerBlkioStats, ContainerCpuUsage, ContainerMemoryStats, ContainerNetworkStats};
+
+    fn cpu(total_usage: u64, system_cpu_usage: u64) -> ContainerCpuStats
+    {
+        ContainerCpuStats
+        {
+            cpu_usage: Some(ContainerCpuUsage { total_usage: Some(total_usage), ..Default::default() }),
+            system_cpu_usage: Some(system_cpu_usage),
+            online_cpus: Some(2),
+            ..Default::default()
+        }
+    }
+
+    /// Échantillon tel que renvoyé en premier par Docker : `precpu_stats` vide.
+    fn sample(total_usage: u64, system_cpu_usage: u64, memory_usage: u64) -> ContainerStatsResponse
+    {
+        ContainerStatsResponse
+        {
+            cpu_stats: Some(cpu(total_usage, system_cpu_usage)),
+            precpu_stats: Some(ContainerCpuStats::default()),
+            memory_stats: Some(ContainerMemoryStats { usage: Some(memory_usage), limit: Some(4096), ..Default::default() }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_first_sample_alone_reports_no_cpu()
+    {
+        let metrics = metrics_from_samples(&sample(1_000, 10_000, 100), None);
+
+        assert!(metrics.cpu_usage_percent.abs() < f64::EPSILON);
+        assert_eq!(metrics.memory_usage_bytes, MemoryBytes::new(100));
+    }
+
+    #[test]
+    fn test_cpu_is_computed_across_two_samples()
+    {
+        // 50 unités de CPU sur 1000 unités système, 2 cœurs : 10 %.
+        let metrics = metrics_from_samples(&sample(1_000, 10_000, 100), Some(&sample(1_050, 11_000, 200)));
+
+        assert!((metrics.cpu_usage_percent - 10.0).abs() < 1e-9);
+        assert_eq!(metrics.memory_usage_bytes, MemoryBytes::new(200));
+        assert_eq!(metrics.memory_limit_bytes, MemoryBytes::new(4096));
+    }
+
+    #[test]
+    fn test_single_sample_uses_precpu_when_present()
+    {
+        let mut stats = sample(1_050, 11_000, 100);
+        stats.precpu_stats = Some(cpu(1_000, 10_000));
+
+        assert!((metrics_from_samples(&stats, None).cpu_usage_percent - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_counter_reset_between_samples_reports_no_cpu()
+    {
+        let metrics = metrics_from_samples(&sample(1_050, 11_000, 100), Some(&sample(10, 12_000, 100)));
+
+        assert!(metrics.cpu_usage_percent.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_network_and_block_io_are_summed()
+    {
+        let mut stats = sample(1_000, 10_000, 100);
+        stats.networks = Some(HashMap::from([
+            ("eth0".to_string(), ContainerNetworkStats { rx_bytes: Some(100), tx_bytes: Some(10), ..Default::default() }),
+            ("eth1".to_string(), ContainerNetworkStats { rx_bytes: Some(50), tx_bytes: None, ..Default::default() }),
+        ]));
+        let entry = |op: &str, value: u64| ContainerBlkioStatEntry { op: Some(op.to_string()), value: Some(value), ..Default::default() };
+        stats.blkio_stats = Some(ContainerBlkioStats
+        {
+            io_service_bytes_recursive: Some(vec![entry("Read", 300), entry("write", 40), entry("read", 5), entry("Total", 345)]),
+            ..Default::default()
+        });
+
+        let metrics = metrics_from_samples(&stats, None);
+        assert_eq!((metrics.network_rx_bytes, metrics.network_tx_bytes), (150, 10));
+        assert_eq!((metrics.block_read_bytes, metrics.block_write_bytes), (305, 40));
+    }
+
+    #[test]
+    fn test_missing_networks_report_zero_traffic()
+    {
+        let metrics = metrics_from_samples(&sample(1_000, 10_000, 100), None);
+        assert_eq!((metrics.network_rx_bytes, metrics.network_tx_bytes), (0, 0));
+        assert_eq!((metrics.block_read_bytes, metrics.block_write_bytes), (0, 0));
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use bollard::Docker;
+use bollard::errors::Error as BollardError;
+use bollard::query_parameters::{DataUsageOptions, ListVolumesOptions, RemoveVolumeOptions};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::{info, warn};
+
+use crate::model::docker::PlatformVolume;
+
+use super::PROJECT_VOLUME_PREFIX;
+use super::error::{DockerError, docker_error};
+
+pub async fn remove_volume_by_name(docker: &Docker, volume_name: &str) -> Result<(), DockerError>
+{
+    info!("Attempting to remove volume: {}", volume_name);
+    let options = Some(RemoveVolumeOptions { force: true });
+    match docker.remove_volume(volume_name, options).await
+    {
+        Ok(()) =>
+        {
+            info!("Volume {} successfully removed.", volume_name);
+            Ok(())
+        }
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) =>
+        {
+            warn!("Volume {} not found during removal. It might have been deleted already.", volume_name);
+            Ok(())
+        }
+        Err(e) => Err(DockerError::new("remove volume", volume_name, e)),
+    }
+}
+
+/// Taille de chaque volume Docker (`nom -> octets`), issue de `docker system df`. Appel coûteux.
+pub async fn get_volume_sizes(docker: &Docker) -> Result<HashMap<String, i64>, DockerError>
+{
+    let df = docker.df(None::<DataUsageOptions>).await.map_err(docker_error("read disk usage of", "volumes"))?;
+
+    // Docker renvoie -1 pour une taille qu'il n'a pas calculée.
+    Ok(df.volumes.unwrap_or_default().into_iter()
+        .filter_map(|volume| volume.usage_data.filter(|usage| usage.size >= 0).map(|usage| (volume.name, usage.size)))
+        .collect())
+}
+
+/// Volumes de données des projets, reconnus à leur préfixe `hangar-data-`.
+pub async fn list_project_volumes(docker: &Docker) -> Result<Vec<PlatformVolume>, DockerError>
+{
+    let volumes = docker.list_volumes(None::<ListVolumesOptions>).await.map_err(docker_error("list volumes", PROJECT_VOLUME_PREFIX))?;
+
+    Ok(volumes.volumes.unwrap_or_default()
+        .into_iter()
+        .filter(|volume| volume.name.starts_with(PROJECT_VOLUME_PREFIX))
+        .map(|volume| PlatformVolume
+        {
+            created_at: volume.created_at.as_deref().and_then(|created| OffsetDateTime::parse(created, &Rfc3339).ok()),
+            name: volume.name,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockDocker, MockReply};
+
+    #[tokio::test]
+    async fn test_remove_volume_tolerates_a_missing_volume()
+    {
+        let mock = MockDocker::start(vec![
+            ("DELETE", "/volumes/hangar-data-gone", MockReply::Empty(404)),
+            ("DELETE", "/volumes/hangar-data-used", MockReply::Json(409, serde_json::json!({ "message": "volume is in use" }))),
+        ]).await;
+
+        remove_volume_by_name(&mock.client(), "hangar-data-gone").await.unwrap();
+        assert_eq!(remove_volume_by_name(&mock.client(), "hangar-data-used").await.unwrap_err().status(), Some(409));
+    }
+}
@@ -0,0 +1,350 @@
+//! Domaines personnalisés des projets (`monprojet.garageisep.com`), servis en plus du nom d'hôte
+//! principal une fois leur possession prouvée.
+//!
+//! La preuve est un enregistrement TXT `_hangar-verification.{domaine}` contenant le jeton du domaine,
+//! ou un CNAME du domaine vers le nom d'hôte principal du projet. Les enregistrements sont lus par le
+//! backend auprès d'un résolveur DNS-over-HTTPS (`DNS_RESOLVER_URL`) ; un administrateur peut aussi
+//! valider un domaine sans vérification.
+//!
+//! Plusieurs projets peuvent revendiquer le même domaine, mais un seul peut le faire vérifier. Les
+//! revendications restées non vérifiées plus de `UNVERIFIED_DOMAIN_TTL_DAYS` jours sont supprimées.
+
+use std::time::Duration;
+
+use rand::distr::{Alphanumeric, SampleString};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    error::{AppError, ProjectErrorCode},
+    model::domain::{DomainVerification, ProjectDomain, ProjectDomainResponse},
+    state::AppState,
+};
+
+/// Nombre maximal de domaines personnalisés par projet.
+pub const MAX_DOMAINS_PER_PROJECT: i64 = 5;
+/// Sous-domaine portant l'enregistrement TXT de vérification.
+const TXT_RECORD_PREFIX: &str = "_hangar-verification";
+/// Préfixe de la valeur TXT attendue, suivi du jeton du domaine.
+const TXT_VALUE_PREFIX: &str = "hangar-verification=";
+const TOKEN_LENGTH: usize = 32;
+/// Durée de vie d'un domaine ajouté mais jamais vérifié.
+const UNVERIFIED_DOMAIN_TTL_DAYS: i32 = 7;
+/// Fréquence de la suppression des domaines non vérifiés expirés.
+const UNVERIFIED_DOMAINS_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Délai maximal d'une requête au résolveur.
+const DNS_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Types d'enregistrement DNS (RFC 1035).
+const DNS_TYPE_CNAME: u16 = 5;
+const DNS_TYPE_TXT: u16 = 16;
+
+/// Réponse d'un résolveur DNS-over-HTTPS au format `application/dns-json`.
+#[derive(Deserialize)]
+struct DnsJsonResponse
+{
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DnsJsonAnswer>,
+}
+
+#[derive(Deserialize)]
+struct DnsJsonAnswer
+{
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+pub async fn list_domains(pool: &PgPool, project_id: i32) -> Result<Vec<ProjectDomain>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_domains WHERE project_id = $1 ORDER BY id")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list domains of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Domaines vérifiés d'un projet, ajoutés à la règle Traefik de son conteneur.
+pub async fn verified_domains(pool: &PgPool, project_id: i32) -> Result<Vec<String>, AppError>
+{
+    sqlx::query_scalar("SELECT fqdn FROM project_domains WHERE project_id = $1 AND verified ORDER BY id")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list verified domains of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn get_domain(pool: &PgPool, project_id: i32, domain_id: i32) -> Result<Option<ProjectDomain>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_domains WHERE id = $1 AND project_id = $2")
+        .bind(domain_id)
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch domain ID {} of project ID {}: {}", domain_id, project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Ajoute un domaine non vérifié. `fqdn` doit avoir été normalisé par `validate_custom_domain`.
+pub async fn add_domain(pool: &PgPool, project_id: i32, fqdn: &str) -> Result<ProjectDomain, AppError>
+{
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to add domain to project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    // Verrou sur le projet : deux ajouts simultanés ne peuvent pas dépasser la limite.
+    sqlx::query("SELECT id FROM projects WHERE id = $1 FOR UPDATE")
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM project_domains WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if count >= MAX_DOMAINS_PER_PROJECT
+    {
+        return Err(ProjectErrorCode::DomainLimitReached(MAX_DOMAINS_PER_PROJECT).into());
+    }
+
+    let verified_elsewhere: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM project_domains WHERE fqdn = $1 AND verified)")
+        .bind(fqdn)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if verified_elsewhere
+    {
+        return Err(ProjectErrorCode::DomainAlreadyClaimed.into());
+    }
+
+    let token = Alphanumeric.sample_string(&mut rand::rng(), TOKEN_LENGTH);
+    let domain: ProjectDomain = sqlx::query_as(
+        "INSERT INTO project_domains (project_id, fqdn, verification_token) VALUES ($1, $2, $3) RETURNING *"
+    )
+        .bind(project_id)
+        .bind(fqdn)
+        .bind(&token)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            if let Some(db_err) = e.as_database_error()
+                && db_err.is_unique_violation()
+            {
+                return AppError::from(ProjectErrorCode::DomainAlreadyClaimed);
+            }
+            db_error(e)
+        })?;
+
+    tx.commit().await.map_err(db_error)?;
+    Ok(domain)
+}
+
+/// Supprime un domaine. Renvoie `None` s'il n'existe pas.
+pub async fn remove_domain(pool: &PgPool, project_id: i32, domain_id: i32) -> Result<Option<ProjectDomain>, AppError>
+{
+    sqlx::query_as("DELETE FROM project_domains WHERE id = $1 AND project_id = $2 RETURNING *")
+        .bind(domain_id)
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to remove domain ID {} of project ID {}: {}", domain_id, project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Marque un domaine comme vérifié, sauf si un autre projet l'a déjà fait vérifier.
+pub async fn mark_verified(pool: &PgPool, domain_id: i32) -> Result<ProjectDomain, AppError>
+{
+    sqlx::query_as("UPDATE project_domains SET verified = TRUE, verified_at = COALESCE(verified_at, NOW()) WHERE id = $1 RETURNING *")
+        .bind(domain_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            // Index unique partiel sur `fqdn WHERE verified` : le domaine est déjà servi par un autre projet.
+            if let Some(db_err) = e.as_database_error()
+                && db_err.is_unique_violation()
+            {
+                return AppError::from(ProjectErrorCode::DomainAlreadyClaimed);
+            }
+            error!("Failed to mark domain ID {} as verified: {}", domain_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Tâche de fond : supprime périodiquement les domaines jamais vérifiés, pour qu'une revendication
+/// abandonnée n'encombre pas la limite de domaines du projet.
+pub async fn start_unverified_domains_sweeper(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting unverified domains sweeper");
+    let mut interval = tokio::time::interval(UNVERIFIED_DOMAINS_SWEEP_INTERVAL);
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Unverified domains sweeper shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        match sqlx::query("DELETE FROM project_domains WHERE NOT verified AND created_at < NOW() - make_interval(days => $1)")
+            .bind(UNVERIFIED_DOMAIN_TTL_DAYS)
+            .execute(&state.db_pool)
+            .await
+        {
+            Ok(result) if result.rows_affected() > 0 => info!("Removed {} expired unverified domain(s)", result.rows_affected()),
+            Ok(_) => {}
+            Err(e) => error!("Failed to remove expired unverified domains: {}", e),
+        }
+    }
+}
+
+/// Enregistrements à créer pour prouver la possession de `domain`.
+#[must_use]
+pub fn verification_for(domain: &ProjectDomain, primary_hostname: &str) -> DomainVerification
+{
+    DomainVerification
+    {
+        txt_name: format!("{TXT_RECORD_PREFIX}.{}", domain.fqdn),
+        txt_value: format!("{TXT_VALUE_PREFIX}{}", domain.verification_token),
+        cname_target: primary_hostname.to_string(),
+    }
+}
+
+#[must_use]
+pub fn with_verification(domain: ProjectDomain, primary_hostname: &str) -> ProjectDomainResponse
+{
+    let verification = verification_for(&domain, primary_hostname);
+    ProjectDomainResponse { domain, verification }
+}
+
+/// Interroge le résolveur et vérifie que le DNS du domaine prouve sa possession.
+pub async fn check_dns(state: &AppState, domain: &ProjectDomain, primary_hostname: &str) -> Result<(), AppError>
+{
+    let verification = verification_for(domain, primary_hostname);
+
+    let txt = resolve(state, &verification.txt_name, "TXT", DNS_TYPE_TXT).await?;
+    if txt_matches(&txt, &verification.txt_value)
+    {
+        return Ok(());
+    }
+
+    let cname = resolve(state, &domain.fqdn, "CNAME", DNS_TYPE_CNAME).await?;
+    if cname_matches(&cname, primary_hostname)
+    {
+        return Ok(());
+    }
+
+    Err(ProjectErrorCode::DomainVerificationFailed(format!(
+        "no TXT record '{}' with the expected value nor CNAME to '{}' was found",
+        verification.txt_name, primary_hostname
+    )).into())
+}
+
+/// Valeurs des enregistrements `record_type` de `name`. Un nom inexistant n'a aucune valeur.
+async fn resolve(state: &AppState, name: &str, record_type: &str, record_type_code: u16) -> Result<Vec<String>, AppError>
+{
+    let unavailable = |e: reqwest::Error|
+    {
+        warn!("DNS resolver query for {} {} failed: {}", record_type, name, e);
+        AppError::from(ProjectErrorCode::DomainVerificationFailed("the DNS resolver could not be reached, try again later".to_string()))
+    };
+
+    let url = reqwest::Url::parse_with_params(&state.config.dns_resolver_url, &[("name", name), ("type", record_type)]).map_err(|e|
+    {
+        error!("DNS_RESOLVER_URL is not a valid URL: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    let response: DnsJsonResponse = state.http_client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/dns-json")
+        .timeout(DNS_QUERY_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(unavailable)?
+        .json()
+        .await
+        .map_err(unavailable)?;
+
+    // 0 : NOERROR, 3 : NXDOMAIN. Les autres codes (SERVFAIL...) ne permettent pas de conclure.
+    match response.status
+    {
+        0 => Ok(response.answer.into_iter()
+            .filter(|answer| answer.record_type == record_type_code)
+            .map(|answer| answer.data)
+            .collect()),
+        3 => Ok(Vec::new()),
+        status => Err(ProjectErrorCode::DomainVerificationFailed(format!("the DNS lookup of '{name}' failed with code {status}")).into()),
+    }
+}
+
+/// Une valeur TXT peut être découpée en chaînes entre guillemets (`"a" "b"`), à recoller.
+fn txt_matches(records: &[String], expected: &str) -> bool
+{
+    records.iter().any(|record|
+    {
+        let value: String = record.split('"').skip(1).step_by(2).collect();
+        let value = if value.is_empty() { record.as_str() } else { value.as_str() };
+        value.trim() == expected
+    })
+}
+
+fn cname_matches(records: &[String], primary_hostname: &str) -> bool
+{
+    let expected = primary_hostname.trim_end_matches('.');
+    records.iter().any(|record| record.trim().trim_end_matches('.').eq_ignore_ascii_case(expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_txt_matches()
+    {
+        let expected = "hangar-verification=abc123";
+        assert!(txt_matches(&["\"hangar-verification=abc123\"".to_string()], expected));
+        assert!(txt_matches(&["\"hangar-verification=\" \"abc123\"".to_string()], expected));
+        assert!(txt_matches(&["hangar-verification=abc123".to_string()], expected));
+        assert!(!txt_matches(&["\"hangar-verification=other\"".to_string(), "\"v=spf1 -all\"".to_string()], expected));
+        assert!(!txt_matches(&[], expected));
+    }
+
+    #[test]
+    fn test_cname_matches()
+    {
+        let records = ["My-App.Hangar.GarageIsep.com.".to_string()];
+        assert!(cname_matches(&records, "my-app.hangar.garageisep.com"));
+        assert!(!cname_matches(&records, "other.hangar.garageisep.com"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Canal e-mail des notifications, utilisé par `notification_service` pour les destinataires qui
+//! l'ont choisi (ou dont c'est le canal par défaut).
+//!
+//! Les e-mails ne sont envoyés que si `SMTP_HOST` est configuré et dans la limite de
+//! `SMTP_MAX_EMAILS_PER_HOUR` par utilisateur. L'envoi se fait en arrière-plan : un échec est
+//! journalisé et n'affecte jamais l'opération à l'origine de la notification.
+
+use std::time::{Duration, Instant};
+
+use lettre::
+{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use tracing::{error, info, warn};
+
+use crate::
+{
+    config::{Config, SmtpConfig, SmtpTls},
+    model::notification::NotificationKind,
+    rate_limit,
+    state::AppState,
+};
+
+/// Délai maximal d'un échange avec le serveur SMTP.
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Fenêtre glissante de `SMTP_MAX_EMAILS_PER_HOUR`.
+const EMAIL_RATE_LIMIT_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Transport SMTP et expéditeur des notifications.
+pub struct Mailer
+{
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    max_emails_per_hour: usize,
+}
+
+impl Mailer
+{
+    /// Transport construit à partir de la configuration ; `None` si les e-mails sont désactivés ou
+    /// si le serveur configuré est inutilisable.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Option<Self>
+    {
+        let smtp = config.smtp.as_ref()?;
+        match Self::new(smtp)
+        {
+            Ok(mailer) =>
+            {
+                info!("Email notifications enabled through '{}:{}'", smtp.host, smtp.port);
+                Some(mailer)
+            }
+            Err(reason) =>
+            {
+                error!("Email notifications disabled, invalid SMTP configuration: {}", reason);
+                None
+            }
+        }
+    }
+
+    fn new(smtp: &SmtpConfig) -> Result<Self, String>
+    {
+        let builder = match smtp.tls
+        {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host).map_err(|e| e.to_string())?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host).map_err(|e| e.to_string())?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host),
+        };
+        let mut builder = builder.port(smtp.port).timeout(Some(SMTP_TIMEOUT));
+        if let (Some(username), Some(password)) = (&smtp.username, &smtp.password)
+        {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        let from = smtp.from.parse::<Mailbox>().map_err(|e| e.to_string())?;
+        Ok(Self { transport: builder.build(), from, max_emails_per_hour: smtp.max_emails_per_hour })
+    }
+}
+
+/// Envoie par e-mail à `login` la notification `kind` du projet, en arrière-plan. `detail`
+/// complète le message (étape en échec, code de sortie, date d'archivage...).
+pub fn send_in_background(state: &AppState, login: String, project_id: i32, project_name: String, kind: NotificationKind, detail: String)
+{
+    if state.mailer.is_none()
+    {
+        return;
+    }
+
+    let state = state.clone();
+    tokio::spawn(async move
+    {
+        if let Err(reason) = send(&state, &login, project_id, &project_name, kind, &detail).await
+        {
+            warn!("Email notification ({}) for project ID {} not sent to '{}': {}", kind.as_str(), project_id, login, reason);
+        }
+    });
+}
+
+async fn send(state: &AppState, login: &str, project_id: i32, project_name: &str, kind: NotificationKind, detail: &str) -> Result<(), String>
+{
+    let Some(mailer) = &state.mailer else { return Ok(()) };
+
+    let recipient: Option<(Option<String>, Option<String>)> = sqlx::query_as("SELECT name, email FROM users WHERE login = $1")
+        .bind(login)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some((name, email)) = recipient else { return Err("user not found".to_string()) };
+
+    let Some(address) = email.as_deref().filter(|email| !email.is_empty()) else
+    {
+        return Err("no email address".to_string());
+    };
+    let to = Mailbox::new(name, address.parse().map_err(|e| format!("invalid address '{address}': {e}"))?);
+
+    if !rate_limit::check_sliding_window(&state.email_sends, login.to_string(), mailer.max_emails_per_hour, EMAIL_RATE_LIMIT_WINDOW, Instant::now())
+    {
+        return Err("hourly email limit reached".to_string());
+    }
+
+    let link = project_link(&state.config.public_address, project_id);
+    let (subject, body) = render(kind, project_name, detail, &link);
+    let message = Message::builder()
+        .from(mailer.from.clone())
+        .to(to)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)
+        .map_err(|e| e.to_string())?;
+
+    mailer.transport.send(message).await.map_err(|e| e.to_string())?;
+    info!("Sent {} email for project '{}' to '{}'", kind.as_str(), project_name, login);
+    Ok(())
+}
+
+/// Lien vers la page du projet dans le tableau de bord.
+fn project_link(public_address: &str, project_id: i32) -> String
+{
+    format!("{}/projects/{}", public_address.trim_end_matches('/'), project_id)
+}
+
+/// Objet et corps en texte brut de l'e-mail.
+fn render(kind: NotificationKind, project_name: &str, detail: &str, link: &str) -> (String, String)
+{
+    let (subject, headline) = match kind
+    {
+        NotificationKind::DeployFailed => (
+            format!("[Hangar] Deployment of {project_name} failed"),
+            format!("The latest deployment of your project {project_name} failed. The previous version is still running, if there was one."),
+        ),
+        NotificationKind::ContainerCrashed => (
+            format!("[Hangar] {project_name} stopped unexpectedly"),
+            format!("The container of your project {project_name} stopped unexpectedly."),
+        ),
+        NotificationKind::ProjectCleanup => (
+            format!("[Hangar] Cleanup notice for {project_name}"),
+            format!("The project {project_name} is part of the cleanup of projects whose owner is no longer active."),
+        ),
+        NotificationKind::IdleStop => (
+            format!("[Hangar] {project_name} was stopped for inactivity"),
+            format!("The project {project_name} was stopped because it was idle."),
+        ),
+        NotificationKind::JobRun => (
+            format!("[Hangar] Job run of {project_name}"),
+            format!("A job of the project {project_name} finished."),
+        ),
+    };
+
+    let body = format!(
+        "{headline}\n\n{detail}\n\nOpen the project: {link}\n\n--\n\
+         You receive this email because you are a member of this project on Hangar. \
+         You can choose how you receive each notification in your preferences."
+    );
+    (subject, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_links_to_the_project_and_carries_the_detail()
+    {
+        let link = project_link("https://hangar.garageisep.com/", 42);
+        assert_eq!(link, "https://hangar.garageisep.com/projects/42");
+
+        let (subject, body) = render(NotificationKind::DeployFailed, "blog", "Failed stage: build", &link);
+
+        assert_eq!(subject, "[Hangar] Deployment of blog failed");
+        assert!(body.contains("Failed stage: build"));
+        assert!(body.contains("Open the project: https://hangar.garageisep.com/projects/42"));
+    }
+}
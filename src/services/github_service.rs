@@ -1,11 +1,12 @@
 use std::path::Path;
 
-use crate::{config::Config, error::{AppError, ProjectErrorCode}};
+use crate::{config::Config, error::{AppError, ProjectErrorCode}, logging::log_safe, safe_http::{self, SafeHttpPolicy}};
+use reqwest::{header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, USER_AGENT}, Method, Url};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tracing::{debug, error, info, warn};
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
-use git2::{Cred, FetchOptions, RemoteCallbacks, build::RepoBuilder};
+use git2::{Cred, FetchOptions, Oid, RemoteCallbacks, Repository, build::{CheckoutBuilder, RepoBuilder}};
 
 #[derive(Debug, Deserialize)]
 struct Installation
@@ -35,6 +36,58 @@ struct InstallationTokenResponse
     token: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct RepositoryResponse
+{
+    default_branch: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchResponse
+{
+    name: String,
+}
+
+/// Branches renvoyées par page de l'API GitHub (maximum autorisé).
+const BRANCHES_PER_PAGE: usize = 100;
+/// Pages de branches lues au plus : au-delà, la liste est tronquée.
+const MAX_BRANCH_PAGES: usize = 10;
+
+/// Révision d'un dépôt à construire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checkout<'a>
+{
+    /// Dernier commit de la branche (branche par défaut si `None`).
+    Branch(Option<&'a str>),
+    /// Tag ou SHA de commit complet, validé par `validation_service::validate_git_ref`.
+    Pinned(&'a str),
+}
+
+impl<'a> Checkout<'a>
+{
+    /// Une référence épinglée prime sur la branche.
+    #[must_use]
+    pub fn new(branch: Option<&'a str>, git_ref: Option<&'a str>) -> Self
+    {
+        git_ref.map_or(Self::Branch(branch), Self::Pinned)
+    }
+}
+
+/// La référence désigne-t-elle un commit précis (SHA complet) plutôt qu'un tag ?
+#[must_use]
+pub fn is_commit_sha(git_ref: &str) -> bool
+{
+    git_ref.len() == 40 && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Branches d'un dépôt GitHub et sa branche par défaut.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct RepoBranches
+{
+    pub default_branch: String,
+    pub branches: Vec<String>,
+}
+
 
 pub async fn extract_repo_owner_and_name(repo_url: &str) -> Result<(String, String), AppError>
 {
@@ -68,38 +121,35 @@ pub async fn extract_repo_owner_and_name(repo_url: &str) -> Result<(String, Stri
         return Err(ProjectErrorCode::InvalidGithubUrl.into());
     }
     
-    info!("Extracted GitHub owner '{}' and repo '{}' from URL '{}'", owner, repo_name, repo_url);
+    info!("Extracted GitHub owner '{}' and repo '{}' from URL '{}'", log_safe(owner), log_safe(repo_name), log_safe(repo_url));
     Ok((owner.to_string(), repo_name.to_string()))
 }
 
+/// Vérifie que l'installation GitHub a accès au dépôt `owner/repo`, issus de l'URL fournie par l'utilisateur.
+///
+/// La requête passe par [`safe_http`] ; `owner` et `repo` sont encodés comme segments de chemin.
 pub async fn check_repo_accessibility(
-    http_client: &reqwest::Client,
+    policy: &SafeHttpPolicy,
     token: &str,
     owner: &str,
     repo: &str,
 ) -> Result<(), AppError> 
 {
-    let url = format!("https://api.github.com/repos/{owner}/{repo}");
-    info!("Checking repository accessibility at: {}", url);
+    let url = repo_api_url(owner, repo, &[])?;
+    info!("Checking repository accessibility at: {}", log_safe(url.as_str()));
 
-    let response = http_client
-        .get(&url)
-        .header("Authorization", format!("Bearer {token}"))
-        .header("Accept", "application/vnd.github+json")
-        .header("User-Agent", "Hangar App")
-        .send()
-        .await?;
+    let response = safe_http::send(policy, Method::GET, url.as_str(), &api_headers(Some(token))?, None).await?;
 
     if response.status().is_success() 
     {
-        info!("Access to repository '{}/{}' confirmed.", owner, repo);
+        info!("Access to repository '{}/{}' confirmed.", log_safe(owner), log_safe(repo));
         Ok(())
     } 
     else if response.status() == reqwest::StatusCode::NOT_FOUND 
     {
         warn!(
             "Access check for repo '{}/{}' failed with 404. The App likely lacks permission.",
-            owner, repo
+            log_safe(owner), log_safe(repo)
         );
         Err(ProjectErrorCode::GithubRepoNotAccessible.into())
     } 
@@ -108,13 +158,91 @@ pub async fn check_repo_accessibility(
         let error_body = response.text().await.unwrap_or_default();
         error!(
             "GitHub API request to check repo accessibility failed: {}",
-            error_body
+            log_safe(&error_body)
         );
         Err(AppError::InternalServerError)
     }
 }
 
 
+/// Branches du dépôt `owner/repo`, lues page par page ; sans `token`, seuls les dépôts publics sont visibles.
+pub async fn list_repo_branches(
+    policy: &SafeHttpPolicy,
+    token: Option<&str>,
+    owner: &str,
+    repo: &str,
+) -> Result<RepoBranches, AppError>
+{
+    let headers = api_headers(token)?;
+
+    let repository: RepositoryResponse = get_repo_json(policy, &headers, repo_api_url(owner, repo, &[])?).await?;
+
+    let mut branches = Vec::new();
+    for page in 1..=MAX_BRANCH_PAGES
+    {
+        let mut url = repo_api_url(owner, repo, &["branches"])?;
+        url.query_pairs_mut()
+            .append_pair("per_page", &BRANCHES_PER_PAGE.to_string())
+            .append_pair("page", &page.to_string());
+
+        let entries: Vec<BranchResponse> = get_repo_json(policy, &headers, url).await?;
+        let last_page = entries.len() < BRANCHES_PER_PAGE;
+        branches.extend(entries.into_iter().map(|entry| entry.name));
+        if last_page
+        {
+            return Ok(RepoBranches { default_branch: repository.default_branch, branches });
+        }
+    }
+
+    warn!(
+        "Repository '{}/{}' has more than {} branches, the list is truncated",
+        log_safe(owner), log_safe(repo), BRANCHES_PER_PAGE * MAX_BRANCH_PAGES
+    );
+    Ok(RepoBranches { default_branch: repository.default_branch, branches })
+}
+
+/// `https://api.github.com/repos/{owner}/{repo}/...`, chaque partie encodée comme segment de chemin.
+fn repo_api_url(owner: &str, repo: &str, segments: &[&str]) -> Result<Url, AppError>
+{
+    let mut url = Url::parse("https://api.github.com/repos").map_err(|_| AppError::InternalServerError)?;
+    url.path_segments_mut()
+        .map_err(|()| AppError::InternalServerError)?
+        .push(owner)
+        .push(repo)
+        .extend(segments);
+    Ok(url)
+}
+
+fn api_headers(token: Option<&str>) -> Result<HeaderMap, AppError>
+{
+    let mut headers = HeaderMap::new();
+    if let Some(token) = token
+    {
+        headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {token}")).map_err(|_| AppError::InternalServerError)?);
+    }
+    headers.insert(ACCEPT, HeaderValue::from_static("application/vnd.github+json"));
+    headers.insert(USER_AGENT, HeaderValue::from_static("Hangar App"));
+    Ok(headers)
+}
+
+/// GET sur l'API d'un dépôt : un 404 (dépôt inexistant, privé ou hors de l'installation) devient `GithubRepoNotAccessible`.
+async fn get_repo_json<T: serde::de::DeserializeOwned>(policy: &SafeHttpPolicy, headers: &HeaderMap, url: Url) -> Result<T, AppError>
+{
+    let response = safe_http::send(policy, Method::GET, url.as_str(), headers, None).await?;
+
+    match response.status()
+    {
+        status if status.is_success() => Ok(response.json().await?),
+        reqwest::StatusCode::NOT_FOUND => Err(ProjectErrorCode::GithubRepoNotAccessible.into()),
+        status =>
+        {
+            let error_body = response.text().await.unwrap_or_default();
+            error!("GitHub API request to '{}' failed with {}: {}", log_safe(url.as_str()), status, log_safe(&error_body));
+            Err(AppError::InternalServerError)
+        }
+    }
+}
+
 async fn generate_app_jwt(config: &Config) -> Result<String, AppError>
 {
     let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
@@ -163,7 +291,7 @@ pub async fn get_installation_id_by_user(http_client: &reqwest::Client, config:
     {
         if inst.account.login.eq_ignore_ascii_case(github_username)
         {
-            debug!("Found matching GitHub App installation with ID: {} for user {}", inst.id, github_username);
+            debug!("Found matching GitHub App installation with ID: {} for user {}", inst.id, log_safe(github_username));
             return Ok(inst.id);
         }
     }
@@ -187,7 +315,7 @@ pub async fn get_installation_token(installation_id: u64, http_client: &reqwest:
     if !response.status().is_success()
     {
         let error_body = response.text().await.unwrap_or_default();
-        error!("GitHub installation token request failed: {}", error_body);
+        error!("GitHub installation token request failed: {}", log_safe(&error_body));
         return Err(AppError::InternalServerError);
     }
 
@@ -195,12 +323,110 @@ pub async fn get_installation_token(installation_id: u64, http_client: &reqwest:
     Ok(token_response.token)
 }
 
-pub async fn clone_repo(repo_url: &str, target_dir: &Path, token: Option<&str>, branch: Option<&str>) -> Result<(), AppError>
+/// Références du dépôt distant et leur commit (équivalent de `git ls-remote`).
+async fn list_remote_refs(repo_url: &str, token: Option<&str>) -> Result<Vec<(String, String)>, AppError>
+{
+    let repo_url_owned = repo_url.to_string();
+    let token = token.map(std::string::ToString::to_string);
+
+    let listing = tokio::task::spawn_blocking(move ||
+    {
+        let mut callbacks = RemoteCallbacks::new();
+
+        if let Some(t) = &token
+        {
+            callbacks.credentials(move |_url, _username_from_url, _allowed_types|
+            {
+                Cred::userpass_plaintext("x-access-token", t)
+            });
+        }
+
+        let mut remote = git2::Remote::create_detached(repo_url_owned.as_str())?;
+        let connection = remote.connect_auth(git2::Direction::Fetch, Some(callbacks), None)?;
+        let refs = connection.list()?
+            .iter()
+            .map(|head| (head.name().to_string(), head.oid().to_string()))
+            .collect::<Vec<_>>();
+        Ok::<_, git2::Error>(refs)
+    })
+    .await
+    .map_err(|_| AppError::InternalServerError)?;
+
+    listing.map_err(|e|
+    {
+        let msg = e.message().to_lowercase();
+        if msg.contains("authentication required") || msg.contains("credentials callback returned an error")
+        {
+            ProjectErrorCode::GithubAccountNotLinked.into()
+        }
+        else
+        {
+            debug!("git ls-remote failed for repo '{}': {}", log_safe(repo_url), log_safe(&msg));
+            ProjectErrorCode::InvalidGithubUrl.into()
+        }
+    })
+}
+
+/// Vérifie, sans cloner, que le dépôt est joignable et que la branche demandée existe (équivalent de `git ls-remote`).
+pub async fn check_remote_branch(repo_url: &str, token: Option<&str>, branch: Option<&str>) -> Result<(), AppError>
+{
+    let refs = list_remote_refs(repo_url, token).await?;
+
+    match branch
+    {
+        Some(name) if !refs.iter().any(|(reference, _)| *reference == format!("refs/heads/{name}")) =>
+        {
+            Err(AppError::BadRequest(format!("The branch '{name}' does not exist in the repository.")))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// SHA du dernier commit de `branch` (branche par défaut si `None`) sur le dépôt distant, sans cloner.
+/// `None` si la référence n'existe pas.
+pub async fn remote_head_sha(repo_url: &str, token: Option<&str>, branch: Option<&str>) -> Result<Option<String>, AppError>
+{
+    let wanted = branch.map_or_else(|| "HEAD".to_string(), |b| format!("refs/heads/{b}"));
+    let refs = list_remote_refs(repo_url, token).await?;
+
+    Ok(refs.into_iter().find(|(reference, _)| *reference == wanted).map(|(_, sha)| sha))
+}
+
+/// Récupère uniquement `git_ref` (tag ou SHA) dans un dépôt vide, puis détache HEAD sur son commit.
+fn fetch_pinned(repo_url: &str, target_dir: &Path, git_ref: &str, fo: &mut FetchOptions<'_>) -> Result<String, git2::Error>
+{
+    let repo = Repository::init(target_dir)?;
+    let mut remote = repo.remote_anonymous(repo_url)?;
+
+    let commit = if is_commit_sha(git_ref)
+    {
+        remote.fetch(&[git_ref], Some(fo), None)?;
+        repo.find_commit(Oid::from_str(git_ref)?)?
+    }
+    else
+    {
+        let tag_ref = format!("refs/tags/{git_ref}");
+        remote.fetch(&[format!("+{tag_ref}:{tag_ref}")], Some(fo), None)?;
+        repo.find_reference(&tag_ref)?.peel_to_commit()?
+    };
+
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))?;
+    repo.set_head_detached(commit.id())?;
+    Ok(commit.id().to_string())
+}
+
+/// Clone le dépôt (historique limité au commit demandé) et renvoie le SHA du commit récupéré.
+pub async fn clone_repo(repo_url: &str, target_dir: &Path, token: Option<&str>, checkout: Checkout<'_>) -> Result<String, AppError>
 {
     let repo_url_owned = repo_url.to_string();
     let target_dir = target_dir.to_path_buf();
     let token = token.map(std::string::ToString::to_string);
-    let branch = branch.map(std::string::ToString::to_string);
+    let (branch, pinned) = match checkout
+    {
+        Checkout::Branch(branch) => (branch.map(std::string::ToString::to_string), None),
+        Checkout::Pinned(git_ref) => (None, Some(git_ref.to_string())),
+    };
+    let pinned_for_error = pinned.clone();
 
     let repo_url_for_log = repo_url_owned.clone();
 
@@ -220,6 +446,11 @@ pub async fn clone_repo(repo_url: &str, target_dir: &Path, token: Option<&str>,
         fo.remote_callbacks(callbacks);
         fo.depth(1);
 
+        if let Some(git_ref) = &pinned
+        {
+            return fetch_pinned(&repo_url_owned, &target_dir, git_ref, &mut fo);
+        }
+
         let mut builder = RepoBuilder::new();
         builder.fetch_options(fo);
 
@@ -229,23 +460,29 @@ pub async fn clone_repo(repo_url: &str, target_dir: &Path, token: Option<&str>,
         }
 
         builder.clone(&repo_url_owned, &target_dir)
+            .and_then(|repo| repo.head()?.peel_to_commit().map(|commit| commit.id().to_string()))
     })
     .await
     .map_err(|_| AppError::InternalServerError)?;
 
-    clone_result.map_err(|e|
+    let commit_sha = clone_result.map_err(|e|
     {
         let msg = e.message().to_lowercase();
         if msg.contains("authentication required") || msg.contains("credentials callback returned an error")
         {
             AppError::ProjectError(ProjectErrorCode::GithubAccountNotLinked)
         }
+        else if let Some(git_ref) = &pinned_for_error
+            && (e.code() == git2::ErrorCode::NotFound || msg.contains("not our ref") || msg.contains("couldn't find remote ref"))
+        {
+            AppError::BadRequest(format!("The reference '{git_ref}' does not exist in the repository."))
+        }
         else
-        {   error!("git2 clone failed for repo '{}': {}", repo_url_for_log, msg);
+        {   error!("git2 clone failed for repo '{}': {}", log_safe(&repo_url_for_log), log_safe(&msg));
             AppError::ProjectError(ProjectErrorCode::InvalidGithubUrl)
         }
     })?;
 
-    info!("Repository {} cloned successfully.", repo_url_for_log);
-    Ok(())
+    info!("Repository {} cloned successfully at commit {}.", log_safe(&repo_url_for_log), commit_sha);
+    Ok(commit_sha)
 }
\ No newline at end of file
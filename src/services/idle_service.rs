@@ -0,0 +1,242 @@
+//! Arrêt automatique des projets inactifs (démos que plus personne ne visite après le forum).
+//!
+//! Un projet est inactif tant que, à chaque relevé, son CPU reste sous `IDLE_CPU_THRESHOLD_PERCENT`
+//! et que son conteneur ne reçoit aucun trafic : les requêtes relayées par Traefik font évoluer ses
+//! octets reçus. Après `IDLE_STOP_AFTER_HOURS` heures d'inactivité, le conteneur est arrêté ; le
+//! démarrage habituel le relance. Les relevés sont gardés en mémoire : un redémarrage de l'API
+//! repart d'une période d'activité.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use serde_json::json;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tracing::{debug, error, info};
+
+use crate::
+{
+    model::{notification::NotificationKind, project::Project},
+    services::{docker_service, notification_service, project_service},
+    sse::{emitter::emit_admin_event, types::{ContainerStatus, SystemEvent}},
+    state::AppState,
+};
+
+/// Intervalle entre deux relevés d'activité.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(900);
+/// Octets reçus tolérés entre deux relevés (DNS, ARP) sans être comptés comme du trafic.
+const IDLE_NETWORK_TOLERANCE_BYTES: u64 = 16 * 1024;
+
+/// Dernier relevé d'un conteneur démarré.
+#[derive(Debug, Clone, PartialEq)]
+struct Activity
+{
+    container_name: String,
+    network_rx_bytes: u64,
+    last_active_at: OffsetDateTime,
+}
+
+pub async fn start_idle_stopper(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting idle projects stopper task (after {} hours)", state.config.idle_stop_after_hours);
+    let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+    let mut activity: HashMap<i32, Activity> = HashMap::new();
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Idle projects stopper task shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        stop_idle_projects(&state, &mut activity).await;
+    }
+}
+
+async fn stop_idle_projects(state: &AppState, activity: &mut HashMap<i32, Activity>)
+{
+    let projects = match project_service::get_all_projects(&state.db_pool).await
+    {
+        Ok(projects) => projects,
+        Err(e) =>
+        {
+            error!("Failed to list projects for idle detection: {}", e);
+            return;
+        }
+    };
+
+    let idle_limit = idle_limit(state.config.idle_stop_after_hours);
+    let mut running = HashSet::new();
+
+    for project in projects
+    {
+        if project.keep_alive || project.archived || project.owner == project_service::SELFTEST_OWNER
+        {
+            continue;
+        }
+        if !matches!(docker_service::get_container_status(&state.docker_client, &project.container_name).await, Ok(Some(ContainerStatus::Running)))
+        {
+            continue;
+        }
+        running.insert(project.id);
+
+        let metrics = match docker_service::sample_container_metrics(&state.docker_client, &project.container_name).await
+        {
+            Ok(metrics) => metrics,
+            Err(e) =>
+            {
+                debug!("Could not sample activity of container '{}': {}", project.container_name, e);
+                continue;
+            }
+        };
+
+        let now = OffsetDateTime::now_utc();
+        let current = next_activity(
+            activity.get(&project.id),
+            &project.container_name,
+            metrics.cpu_usage_percent,
+            metrics.network_rx_bytes,
+            state.config.idle_cpu_threshold_percent,
+            now,
+        );
+
+        if now - current.last_active_at >= idle_limit
+        {
+            stop_idle_project(state, &project, current.last_active_at).await;
+            activity.remove(&project.id);
+        }
+        else
+        {
+            activity.insert(project.id, current);
+        }
+    }
+
+    // Un conteneur arrêté ou remplacé repart d'une période d'activité à son prochain démarrage.
+    activity.retain(|project_id, _| running.contains(project_id));
+}
+
+/// Délai d'inactivité avant l'arrêt, saturé plutôt que de déborder.
+fn idle_limit(hours: u64) -> time::Duration
+{
+    i64::try_from(hours).ok()
+        .and_then(|hours| hours.checked_mul(3600))
+        .map_or(time::Duration::MAX, time::Duration::seconds)
+}
+
+/// Relevé suivant d'un conteneur : actif si son CPU dépasse le seuil ou s'il a reçu du trafic depuis le relevé précédent.
+fn next_activity(
+    previous: Option<&Activity>,
+    container_name: &str,
+    cpu_usage_percent: f64,
+    network_rx_bytes: u64,
+    cpu_threshold_percent: f64,
+    now: OffsetDateTime,
+) -> Activity
+{
+    let last_active_at = match previous
+    {
+        // Un autre conteneur (déploiement) ou un compteur revenu en arrière repart d'une période d'activité.
+        Some(previous) if previous.container_name == container_name && network_rx_bytes >= previous.network_rx_bytes =>
+        {
+            let received = network_rx_bytes - previous.network_rx_bytes;
+            if cpu_usage_percent >= cpu_threshold_percent || received > IDLE_NETWORK_TOLERANCE_BYTES
+            {
+                now
+            }
+            else
+            {
+                previous.last_active_at
+            }
+        }
+        _ => now,
+    };
+
+    Activity { container_name: container_name.to_string(), network_rx_bytes, last_active_at }
+}
+
+async fn stop_idle_project(state: &AppState, project: &Project, idle_since: OffsetDateTime)
+{
+    if let Err(e) = docker_service::stop_container_by_name(&state.docker_client, &project.container_name, project.stop_grace_seconds).await
+    {
+        error!("Failed to stop idle project '{}': {}", project.name, e);
+        return;
+    }
+
+    let hours = state.config.idle_stop_after_hours;
+    info!("Project '{}' (owner '{}') stopped after {} hours without activity", project.name, project.owner, hours);
+
+    let context = json!({ "project_id": project.id, "project_name": project.name, "owner": project.owner, "idle_since": idle_since.unix_timestamp() });
+
+    notification_service::notify_project_members(
+        state,
+        project,
+        NotificationKind::IdleStop,
+        SystemEvent::info(format!(
+            "Project '{}' has been stopped after {hours} hours without activity. Start it again to bring it back.",
+            project.name
+        )).with_context(context.clone()),
+    ).await;
+
+    emit_admin_event(state, SystemEvent::info(format!(
+        "Project '{}' of '{}' has been stopped after {hours} hours without activity.",
+        project.name, project.owner
+    )).with_context(context));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: f64 = 1.0;
+
+    fn at(minutes: i64) -> OffsetDateTime
+    {
+        OffsetDateTime::UNIX_EPOCH + time::Duration::minutes(minutes)
+    }
+
+    fn sample(previous: &Activity, cpu: f64, rx: u64, minutes: i64) -> Activity
+    {
+        next_activity(Some(previous), "hangar-demo", cpu, rx, THRESHOLD, at(minutes))
+    }
+
+    #[test]
+    fn test_quiet_container_stays_idle_since_last_activity()
+    {
+        let first = next_activity(None, "hangar-demo", 0.1, 1000, THRESHOLD, at(0));
+        assert_eq!(first.last_active_at, at(0));
+
+        let second = sample(&first, 0.2, 1000 + IDLE_NETWORK_TOLERANCE_BYTES, 15);
+        assert_eq!(second.last_active_at, at(0));
+    }
+
+    #[test]
+    fn test_traffic_or_cpu_marks_activity()
+    {
+        let first = next_activity(None, "hangar-demo", 0.1, 1000, THRESHOLD, at(0));
+
+        assert_eq!(sample(&first, 0.1, 1000 + IDLE_NETWORK_TOLERANCE_BYTES + 1, 15).last_active_at, at(15));
+        assert_eq!(sample(&first, 5.0, 1000, 15).last_active_at, at(15));
+    }
+
+    #[test]
+    fn test_new_container_or_reset_counter_restarts_the_clock()
+    {
+        let first = next_activity(None, "hangar-demo", 0.1, 50_000, THRESHOLD, at(0));
+
+        assert_eq!(sample(&first, 0.1, 10, 15).last_active_at, at(15));
+        assert_eq!(next_activity(Some(&first), "hangar-demo-2", 0.1, 50_000, THRESHOLD, at(15)).last_active_at, at(15));
+    }
+
+    #[test]
+    fn test_idle_limit_saturates()
+    {
+        assert_eq!(idle_limit(2), time::Duration::hours(2));
+        assert_eq!(idle_limit(u64::MAX), time::Duration::MAX);
+        assert_eq!(idle_limit(u64::try_from(i64::MAX).unwrap()), time::Duration::MAX);
+    }
+}
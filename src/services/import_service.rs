@@ -0,0 +1,487 @@
+//! Reprise sous gestion d'un conteneur lancé à la main sur le réseau de la plateforme.
+//!
+//! Le conteneur est analysé (image, variables ajoutées au lancement, volume nommé, commande, port),
+//! le projet est enregistré, puis le conteneur est recréé par `create_project_container` pour
+//! recevoir les labels de Hangar. Tout ce qui ne peut pas être repris est signalé dans le rapport.
+
+use std::collections::HashMap;
+
+use bollard::models::{ContainerInspectResponse, MountPointTypeEnum};
+use tracing::{error, info, warn};
+
+use crate::
+{
+    config::DEFAULT_RESOURCE_PROFILE,
+    error::{AppError, ProjectErrorCode},
+    model::project::{ContainerCommand, DEFAULT_CONTAINER_PORT, ProjectImportReport, ProjectSourceType},
+    services::{docker_service::{self, ImageDefaults}, project_service, validation_service},
+    sse::emitter::emit_if_network_missing,
+    state::AppState,
+};
+
+/// Délai d'arrêt de l'ancien conteneur, dont l'application n'a pas de délai configuré.
+const IMPORT_STOP_GRACE_SECONDS: i32 = 10;
+/// Variable de fuseau horaire, reprise dans le fuseau du projet plutôt que dans ses variables.
+const TIMEZONE_ENV_VAR: &str = "TZ";
+/// Suffixe du label Traefik portant le port du service.
+const TRAEFIK_PORT_LABEL_SUFFIX: &str = ".loadbalancer.server.port";
+
+/// Ce qui peut être repris d'un conteneur existant.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImportPlan
+{
+    pub project_name: String,
+    pub image: String,
+    pub image_digest: String,
+    pub env_vars: Option<HashMap<String, String>>,
+    pub timezone: Option<String>,
+    pub persistent_volume_path: Option<String>,
+    pub command: ContainerCommand,
+    pub container_port: u16,
+    pub unmapped: Vec<String>,
+}
+
+/// Dérive un nom de projet valide du nom du conteneur (préfixe de l'application retiré).
+#[must_use]
+pub fn derive_project_name(container_name: &str, app_prefix: &str) -> Option<String>
+{
+    let name = container_name.trim_start_matches('/');
+    let name = name.strip_prefix(&format!("{app_prefix}-")).unwrap_or(name).to_lowercase();
+
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars()
+    {
+        let c = if c.is_ascii_alphanumeric() { c } else { '-' };
+        if !(c == '-' && normalized.ends_with('-'))
+        {
+            normalized.push(c);
+        }
+    }
+
+    let normalized: String = normalized.trim_matches('-').chars().take(63).collect();
+    validation_service::validate_project_name(normalized.trim_end_matches('-')).ok()
+}
+
+/// Analyse le conteneur sans rien modifier. Échoue si le conteneur ne peut pas être repris.
+pub fn plan_import(
+    details: &ContainerInspectResponse,
+    image_defaults: &ImageDefaults,
+    network_name: &str,
+    app_prefix: &str,
+    project_name: Option<&str>,
+) -> Result<ImportPlan, AppError>
+{
+    let container_name = details.name.as_deref().unwrap_or_default().trim_start_matches('/');
+    let config = details.config.clone().unwrap_or_default();
+    let mut unmapped = Vec::new();
+
+    let networks = details.network_settings.as_ref()
+        .and_then(|settings| settings.networks.as_ref())
+        .map(|networks| networks.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    if !networks.iter().any(|name| name == network_name)
+    {
+        return Err(AppError::BadRequest(format!("Container '{container_name}' is not attached to the '{network_name}' network.")));
+    }
+    unmapped.extend(networks.into_iter().filter(|name| name != network_name).map(|name| format!("network '{name}'")));
+
+    let image = config.image.clone().unwrap_or_default();
+    if image.starts_with("sha256:") || validation_service::validate_image_url(&image).is_err()
+    {
+        return Err(AppError::BadRequest(format!("Container '{container_name}' uses an image without a pullable reference ('{image}').")));
+    }
+    let image_digest = details.image.clone().ok_or_else(|| AppError::BadRequest(format!("Container '{container_name}' has no image ID.")))?;
+
+    let project_name = match project_name
+    {
+        Some(name) => validation_service::validate_project_name(name)?,
+        None => derive_project_name(container_name, app_prefix)
+            .ok_or_else(|| AppError::BadRequest(format!("No valid project name can be derived from '{container_name}', please provide one.")))?,
+    };
+
+    // Un conteneur déjà routé par Traefik garde son port ; les autres reçoivent le port par défaut.
+    let container_port = config.labels.as_ref()
+        .and_then(|labels| labels.iter().find(|(key, _)| key.starts_with("traefik.http.services.") && key.ends_with(TRAEFIK_PORT_LABEL_SUFFIX)))
+        .and_then(|(_, port)| port.parse::<u16>().ok())
+        .filter(|port| validation_service::validate_container_port(*port).is_ok())
+        .unwrap_or(DEFAULT_CONTAINER_PORT);
+
+    // Seules les variables ajoutées au lancement sont reprises : celles de l'image restent dans l'image.
+    let mut env_vars = HashMap::new();
+    let mut timezone = None;
+    for var in config.env.unwrap_or_default().into_iter().filter(|var| !image_defaults.env.contains(var))
+    {
+        let Some((key, value)) = var.split_once('=') else { continue };
+        if key == TIMEZONE_ENV_VAR && validation_service::validate_timezone(value).is_ok()
+        {
+            timezone = Some(value.to_string());
+            continue;
+        }
+
+        let single = HashMap::from([(key.to_string(), value.to_string())]);
+        match validation_service::validate_env_vars(&single)
+        {
+            Ok(()) => { env_vars.insert(key.to_string(), value.to_string()); }
+            Err(_) => unmapped.push(format!("environment variable '{key}' (not allowed)")),
+        }
+    }
+
+    let volumes: Vec<_> = details.mounts.clone().unwrap_or_default();
+    let named_volumes: Vec<_> = volumes.iter()
+        .filter(|mount| mount.typ == Some(MountPointTypeEnum::VOLUME) && mount.name.is_some())
+        .collect();
+    let expected_volume = format!("{}{project_name}", docker_service::PROJECT_VOLUME_PREFIX);
+    let mut persistent_volume_path = None;
+    for mount in &volumes
+    {
+        let target = mount.destination.clone().unwrap_or_default();
+        let is_named_volume = mount.typ == Some(MountPointTypeEnum::VOLUME) && mount.name.is_some();
+        if !is_named_volume
+        {
+            unmapped.push(format!("mount at '{target}' (only named volumes are supported)"));
+        }
+        else if named_volumes.len() > 1
+        {
+            unmapped.push(format!("volume '{}' at '{target}' (only a single volume is supported)", mount.name.as_deref().unwrap_or_default()));
+        }
+        else if mount.name.as_deref() != Some(expected_volume.as_str())
+        {
+            unmapped.push(format!(
+                "volume '{}' at '{target}' (copy its data into '{expected_volume}' and set the volume path)",
+                mount.name.as_deref().unwrap_or_default()
+            ));
+        }
+        else if validation_service::validate_volume_path(&target).is_err()
+        {
+            unmapped.push(format!("volume '{expected_volume}' at '{target}' (path not allowed)"));
+        }
+        else
+        {
+            persistent_volume_path = Some(target);
+        }
+    }
+
+    let command = ContainerCommand
+    {
+        entrypoint: config.entrypoint.filter(|entrypoint| Some(entrypoint) != image_defaults.entrypoint.as_ref()),
+        command: config.cmd.filter(|cmd| Some(cmd) != image_defaults.cmd.as_ref()),
+    };
+    if validation_service::validate_container_command(&command).is_err()
+    {
+        return Err(AppError::BadRequest(format!("Container '{container_name}' uses a command that cannot be managed.")));
+    }
+
+    let ports = details.host_config.as_ref()
+        .and_then(|host_config| host_config.port_bindings.as_ref())
+        .map(|bindings| bindings.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    unmapped.extend(ports.into_iter().map(|port| format!("published port '{port}' (traffic goes through Traefik)")));
+
+    Ok(ImportPlan
+    {
+        project_name,
+        image,
+        image_digest,
+        env_vars: (!env_vars.is_empty()).then_some(env_vars),
+        timezone,
+        persistent_volume_path,
+        command,
+        container_port,
+        unmapped,
+    })
+}
+
+/// Reprend `container_name` sous gestion au nom de `owner`.
+///
+/// Les conflits (conteneur déjà géré, nom pris, propriétaire ayant déjà un projet) sont détectés
+/// avant toute modification Docker. En cas d'échec de la recréation, l'ancien conteneur est redémarré
+/// et le projet n'est pas enregistré.
+pub async fn import_container(
+    state: &AppState,
+    container_name: &str,
+    owner: &str,
+    project_name: Option<&str>,
+) -> Result<ProjectImportReport, AppError>
+{
+    let docker = &state.docker_client;
+    let details = docker_service::inspect_container_details(docker, container_name).await?
+        .ok_or_else(|| AppError::NotFound(format!("Container '{container_name}' not found.")))?;
+
+    let is_labeled = details.config.as_ref()
+        .and_then(|config| config.labels.as_ref())
+        .is_some_and(|labels| labels.contains_key(docker_service::PROJECT_ID_LABEL));
+    if is_labeled || project_service::get_project_by_container_name(&state.db_pool, container_name).await?.is_some()
+    {
+        return Err(AppError::BadRequest(format!("Container '{container_name}' is already managed by a project.")));
+    }
+
+    let image_id = details.image.clone().unwrap_or_default();
+    let image_defaults = docker_service::get_image_defaults(docker, &image_id).await?;
+    let plan = plan_import(&details, &image_defaults, &state.config.docker_network, &state.config.app_prefix, project_name)?;
+
+    if project_service::check_owner_exists(&state.db_pool, owner).await?
+    {
+        return Err(ProjectErrorCode::OwnerAlreadyExists.into());
+    }
+    if project_service::check_project_name_exists(&state.db_pool, &plan.project_name).await?
+    {
+        return Err(ProjectErrorCode::ProjectNameTaken.into());
+    }
+
+    let new_container_name = format!("{}-{}", state.config.app_prefix, plan.project_name);
+    let replaces_in_place = new_container_name == container_name;
+    if !replaces_in_place && docker_service::inspect_container_details(docker, &new_container_name).await?.is_some()
+    {
+        return Err(ProjectErrorCode::ProjectNameTaken.into());
+    }
+
+    let project_id = project_service::reserve_project_id(&state.db_pool).await?;
+    let volume_name = plan.persistent_volume_path.as_ref().map(|_| format!("{}{}", docker_service::PROJECT_VOLUME_PREFIX, plan.project_name));
+
+    let mut tx = state.db_pool.begin().await.map_err(|e|
+    {
+        error!("Failed to start transaction for import of '{}': {}", container_name, e);
+        AppError::InternalServerError
+    })?;
+
+    let project = project_service::create_project(
+        &mut tx,
+        project_id,
+        &plan.project_name,
+        owner,
+        &new_container_name,
+        ProjectSourceType::Direct,
+        &plan.image,
+        &None,
+        &None,
+        false,
+        &plan.image,
+        &plan.image_digest,
+        &plan.env_vars,
+        &plan.persistent_volume_path,
+        &volume_name,
+        &plan.command,
+        &plan.timezone,
+        plan.container_port,
+        &[],
+        None,
+        None,
+        false,
+        &state.config.encryption_key,
+    ).await?;
+
+    // L'ancien conteneur est conservé (arrêté) jusqu'au démarrage du nouveau, pour pouvoir revenir en arrière.
+    docker_service::stop_container_by_name(docker, container_name, IMPORT_STOP_GRACE_SECONDS).await?;
+    let previous_name = if replaces_in_place
+    {
+        let renamed = format!("{container_name}-pre-import");
+        docker_service::rename_container(docker, container_name, &renamed).await?;
+        renamed
+    }
+    else
+    {
+        container_name.to_string()
+    };
+
+    let owners = project_service::routing_owners(&state.db_pool, &previous_name).await;
+    let created = docker_service::create_project_container(
+        docker,
+        project_id,
+        &new_container_name,
+        &plan.project_name,
+        &plan.image_digest,
+        &state.config,
+        &plan.env_vars,
+        &plan.persistent_volume_path,
+        &plan.command,
+        plan.timezone.as_deref(),
+        plan.container_port,
+        &[],
+        state.config.resource_profile(DEFAULT_RESOURCE_PROFILE),
+        &owners,
+    ).await;
+
+    if let Err(e) = created
+    {
+        error!("Failed to recreate container '{}' as '{}' during import: {:?}", container_name, new_container_name, e);
+        emit_if_network_missing(state, &e);
+        restore_original(state, &previous_name, container_name).await;
+        return Err(e);
+    }
+
+    if let Err(e) = tx.commit().await
+    {
+        error!("Failed to commit import of container '{}': {}", container_name, e);
+        if let Err(e) = docker_service::remove_container(docker, &new_container_name, IMPORT_STOP_GRACE_SECONDS).await
+        {
+            error!("Failed to remove container '{}' after failed import: {:?}", new_container_name, e);
+        }
+        restore_original(state, &previous_name, container_name).await;
+        return Err(AppError::InternalServerError);
+    }
+
+    if let Err(e) = docker_service::remove_container(docker, &previous_name, IMPORT_STOP_GRACE_SECONDS).await
+    {
+        warn!("Imported project '{}' but could not remove the original container '{}': {:?}", project.name, previous_name, e);
+    }
+
+    info!("Container '{}' imported as project '{}' (owner '{}')", container_name, project.name, owner);
+
+    Ok(ProjectImportReport
+    {
+        project,
+        container_name: new_container_name,
+        unmapped: plan.unmapped,
+    })
+}
+
+/// Remet l'ancien conteneur sous son nom d'origine et le redémarre après un import avorté.
+async fn restore_original(state: &AppState, previous_name: &str, original_name: &str)
+{
+    let docker = &state.docker_client;
+    if previous_name != original_name
+        && let Err(e) = docker_service::rename_container(docker, previous_name, original_name).await
+    {
+        error!("Failed to restore name of container '{}' after aborted import: {:?}", previous_name, e);
+        return;
+    }
+
+    if let Err(e) = docker_service::start_container_by_name(docker, original_name).await
+    {
+        error!("Failed to restart container '{}' after aborted import: {:?}", original_name, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::models::{ContainerConfig, EndpointSettings, HostConfig, MountPoint, NetworkSettings, PortBinding};
+
+    fn container(env: &[&str], mounts: Vec<MountPoint>, networks: &[&str]) -> ContainerInspectResponse
+    {
+        ContainerInspectResponse
+        {
+            name: Some("/hangar-Event_Site".to_string()),
+            image: Some("sha256:abc".to_string()),
+            config: Some(ContainerConfig
+            {
+                image: Some("nginx:1.27".to_string()),
+                env: Some(env.iter().map(|var| (*var).to_string()).collect()),
+                cmd: Some(vec!["nginx".to_string(), "-g".to_string(), "daemon off;".to_string()]),
+                ..Default::default()
+            }),
+            mounts: Some(mounts),
+            network_settings: Some(NetworkSettings
+            {
+                networks: Some(networks.iter().map(|name| ((*name).to_string(), EndpointSettings::default())).collect()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn nginx_defaults() -> ImageDefaults
+    {
+        ImageDefaults
+        {
+            env: vec!["PATH=/usr/local/sbin:/usr/bin".to_string()],
+            entrypoint: None,
+            cmd: Some(vec!["nginx".to_string(), "-g".to_string(), "daemon off;".to_string()]),
+        }
+    }
+
+    fn volume(name: &str, target: &str) -> MountPoint
+    {
+        MountPoint
+        {
+            typ: Some(MountPointTypeEnum::VOLUME),
+            name: Some(name.to_string()),
+            destination: Some(target.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_derive_project_name()
+    {
+        assert_eq!(derive_project_name("/hangar-Event_Site", "hangar").as_deref(), Some("event-site"));
+        assert_eq!(derive_project_name("my..app--", "hangar").as_deref(), Some("my-app"));
+        assert_eq!(derive_project_name("___", "hangar"), None);
+    }
+
+    #[test]
+    fn test_plan_keeps_only_runtime_env_and_maps_timezone()
+    {
+        let details = container(&["PATH=/usr/local/sbin:/usr/bin", "API_URL=https://example.com", "TZ=Europe/Paris", "HOSTNAME=x"], vec![], &["hangar"]);
+
+        let plan = plan_import(&details, &nginx_defaults(), "hangar", "hangar", None).unwrap();
+
+        assert_eq!(plan.project_name, "event-site");
+        assert_eq!(plan.image, "nginx:1.27");
+        assert_eq!(plan.image_digest, "sha256:abc");
+        assert_eq!(plan.env_vars, Some(HashMap::from([("API_URL".to_string(), "https://example.com".to_string())])));
+        assert_eq!(plan.timezone.as_deref(), Some("Europe/Paris"));
+        assert_eq!(plan.command, ContainerCommand::default());
+        assert_eq!(plan.container_port, DEFAULT_CONTAINER_PORT);
+        assert_eq!(plan.unmapped, vec!["environment variable 'HOSTNAME' (not allowed)".to_string()]);
+    }
+
+    #[test]
+    fn test_plan_maps_single_matching_volume_only()
+    {
+        let details = container(&[], vec![volume("hangar-data-event-site", "/data")], &["hangar"]);
+        let plan = plan_import(&details, &nginx_defaults(), "hangar", "hangar", None).unwrap();
+        assert_eq!(plan.persistent_volume_path.as_deref(), Some("/data"));
+        assert!(plan.unmapped.is_empty());
+
+        let details = container(&[], vec![volume("event-data", "/data")], &["hangar"]);
+        let plan = plan_import(&details, &nginx_defaults(), "hangar", "hangar", None).unwrap();
+        assert_eq!(plan.persistent_volume_path, None);
+        assert_eq!(plan.unmapped.len(), 1);
+
+        let details = container(&[], vec![volume("a", "/a"), volume("b", "/b")], &["hangar"]);
+        let plan = plan_import(&details, &nginx_defaults(), "hangar", "hangar", None).unwrap();
+        assert_eq!(plan.persistent_volume_path, None);
+        assert_eq!(plan.unmapped.len(), 2);
+    }
+
+    #[test]
+    fn test_plan_reports_extra_networks_and_ports()
+    {
+        let mut details = container(&[], vec![], &["hangar", "bridge"]);
+        details.host_config = Some(HostConfig
+        {
+            port_bindings: Some(HashMap::from([("80/tcp".to_string(), Some(vec![PortBinding::default()]))])),
+            ..Default::default()
+        });
+
+        let plan = plan_import(&details, &nginx_defaults(), "hangar", "hangar", Some("event")).unwrap();
+
+        assert_eq!(plan.project_name, "event");
+        assert!(plan.unmapped.contains(&"network 'bridge'".to_string()));
+        assert!(plan.unmapped.iter().any(|item| item.contains("80/tcp")));
+    }
+
+    #[test]
+    fn test_plan_keeps_traefik_service_port()
+    {
+        let mut details = container(&[], vec![], &["hangar"]);
+        details.config.as_mut().unwrap().labels = Some(HashMap::from([
+            ("traefik.http.services.event.loadbalancer.server.port".to_string(), "3000".to_string()),
+        ]));
+
+        let plan = plan_import(&details, &nginx_defaults(), "hangar", "hangar", None).unwrap();
+        assert_eq!(plan.container_port, 3000);
+    }
+
+    #[test]
+    fn test_plan_rejects_foreign_network_and_unlocatable_image()
+    {
+        let details = container(&[], vec![], &["bridge"]);
+        assert!(plan_import(&details, &nginx_defaults(), "hangar", "hangar", None).is_err());
+
+        let mut details = container(&[], vec![], &["hangar"]);
+        details.config.as_mut().unwrap().image = Some("sha256:abc".to_string());
+        assert!(plan_import(&details, &nginx_defaults(), "hangar", "hangar", None).is_err());
+    }
+}
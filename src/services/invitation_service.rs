@@ -0,0 +1,154 @@
+//! Invitations à participer à un projet (`PARTICIPANT_INVITATIONS_ENABLED`).
+//!
+//! L'owner invite, l'invité accepte ou refuse : seule l'acceptation l'ajoute à `project_participants`.
+//! Une invitation expirée n'est plus listée ni acceptable ; elle est remplacée si l'owner invite de nouveau.
+
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use tracing::error;
+
+use crate::{error::AppError, model::invitation::ProjectInvitation, services::project_service};
+
+const INVITATION_COLUMNS: &str =
+    "i.id, i.project_id, p.name AS project_name, i.invitee, i.invited_by, i.expires_at, i.created_at";
+
+fn not_found() -> AppError
+{
+    AppError::NotFound("Invitation not found or expired.".to_string())
+}
+
+/// Crée l'invitation de `invitee`, ou renouvelle celle déjà envoyée pour ce projet.
+pub async fn invite(pool: &PgPool, project_id: i32, invitee: &str, invited_by: &str, ttl_days: i64) -> Result<ProjectInvitation, AppError>
+{
+    let expires_at = OffsetDateTime::now_utc() + Duration::days(ttl_days);
+
+    sqlx::query_as(&format!(
+        "WITH i AS ( \
+             INSERT INTO project_invitations (project_id, invitee, invited_by, expires_at) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (project_id, invitee) DO UPDATE SET invited_by = EXCLUDED.invited_by, expires_at = EXCLUDED.expires_at, created_at = NOW() \
+             RETURNING * \
+         ) \
+         SELECT {INVITATION_COLUMNS} FROM i JOIN projects p ON p.id = i.project_id"
+    ))
+        .bind(project_id)
+        .bind(invitee)
+        .bind(invited_by)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to invite '{}' to project {}: {}", invitee, project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Invitations non expirées reçues par `login`.
+pub async fn list_for_invitee(pool: &PgPool, login: &str) -> Result<Vec<ProjectInvitation>, AppError>
+{
+    sqlx::query_as(&format!(
+        "SELECT {INVITATION_COLUMNS} FROM project_invitations i JOIN projects p ON p.id = i.project_id \
+         WHERE i.invitee = $1 AND i.expires_at > NOW() ORDER BY i.created_at DESC"
+    ))
+        .bind(login)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list invitations of '{}': {}", login, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Invitations non expirées d'un projet, en attente de réponse.
+pub async fn list_for_project(pool: &PgPool, project_id: i32) -> Result<Vec<ProjectInvitation>, AppError>
+{
+    sqlx::query_as(&format!(
+        "SELECT {INVITATION_COLUMNS} FROM project_invitations i JOIN projects p ON p.id = i.project_id \
+         WHERE i.project_id = $1 AND i.expires_at > NOW() ORDER BY i.invitee"
+    ))
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list invitations of project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Accepte l'invitation `invitation_id` de `login` : elle est supprimée et `login` devient participant.
+/// Un projet déjà à `max_participants` participants refuse l'acceptation et garde l'invitation.
+/// Renvoie l'identifiant du projet.
+pub async fn accept(pool: &PgPool, invitation_id: i32, login: &str, max_participants: i64) -> Result<i32, AppError>
+{
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to accept invitation {} for '{}': {}", invitation_id, login, e);
+        AppError::InternalServerError
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let project_id: i32 = sqlx::query_scalar(
+        "DELETE FROM project_invitations WHERE id = $1 AND invitee = $2 AND expires_at > NOW() RETURNING project_id"
+    )
+        .bind(invitation_id)
+        .bind(login)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(not_found)?;
+
+    project_service::ensure_participant_capacity(&mut tx, project_id, &[login.to_string()], max_participants).await?;
+
+    sqlx::query("INSERT INTO project_participants (project_id, participant_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(project_id)
+        .bind(login)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(project_id)
+}
+
+/// Refuse l'invitation `invitation_id` de `login`, qui est supprimée.
+pub async fn decline(pool: &PgPool, invitation_id: i32, login: &str) -> Result<(), AppError>
+{
+    let result = sqlx::query("DELETE FROM project_invitations WHERE id = $1 AND invitee = $2 AND expires_at > NOW()")
+        .bind(invitation_id)
+        .bind(login)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to decline invitation {} for '{}': {}", invitation_id, login, e);
+            AppError::InternalServerError
+        })?;
+
+    if result.rows_affected() == 0
+    {
+        return Err(not_found());
+    }
+
+    Ok(())
+}
+
+/// Retire l'invitation en attente de `invitee` sur le projet ; renvoie `false` s'il n'y en avait pas.
+pub async fn cancel(pool: &PgPool, project_id: i32, invitee: &str) -> Result<bool, AppError>
+{
+    let result = sqlx::query("DELETE FROM project_invitations WHERE project_id = $1 AND invitee = $2")
+        .bind(project_id)
+        .bind(invitee)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to cancel invitation of '{}' to project {}: {}", invitee, project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    Ok(result.rows_affected() > 0)
+}
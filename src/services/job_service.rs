@@ -0,0 +1,456 @@
+use std::time::Duration;
+
+use serde_json::json;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tokio::sync::{broadcast, mpsc::UnboundedSender};
+use tracing::{error, info, warn};
+
+use crate::
+{
+    cron::Schedule,
+    error::{AppError, ProjectErrorCode},
+    model::{job::{JobRun, JobRunStatus, ProjectJob}, notification::NotificationKind, project::Project},
+    services::{docker_service::{self, OneOffTask}, notification_service, project_service},
+    sse::{emitter::emit_project_event, types::SystemEvent},
+    state::AppState,
+};
+
+/// Nombre maximal d'arguments d'une commande de tâche.
+const MAX_COMMAND_ARGS: usize = 64;
+/// Longueur maximale d'un argument de commande.
+const MAX_COMMAND_ARG_LENGTH: usize = 4096;
+/// Nombre de lignes de sortie conservées pour chaque exécution.
+const OUTPUT_TAIL_LINES: usize = 50;
+/// Nombre d'exécutions conservées par tâche.
+const RUNS_RETENTION: i64 = 50;
+
+/// Tâche de fond : vérifie chaque minute les tâches planifiées à exécuter.
+pub async fn start_job_scheduler(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting project job scheduler");
+
+    loop
+    {
+        let now = OffsetDateTime::now_utc();
+        let wait = Duration::from_secs(u64::from(60 - now.second()));
+
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Project job scheduler shutting down");
+                return;
+            }
+            () = tokio::time::sleep(wait) => {}
+        }
+
+        run_due_jobs(&state, OffsetDateTime::now_utc()).await;
+    }
+}
+
+/// Lance en parallèle les tâches actives dont l'expression correspond à la minute courante.
+pub async fn run_due_jobs(state: &AppState, now: OffsetDateTime)
+{
+    // Les tâches d'un projet archivé sont suspendues jusqu'à son désarchivage.
+    let jobs: Vec<ProjectJob> = match sqlx::query_as(
+        "SELECT j.* FROM project_jobs j JOIN projects p ON p.id = j.project_id WHERE j.enabled = TRUE AND NOT p.archived"
+    )
+        .fetch_all(&state.db_pool)
+        .await
+    {
+        Ok(jobs) => jobs,
+        Err(e) =>
+        {
+            error!("Failed to list project jobs: {}", e);
+            return;
+        }
+    };
+
+    let Ok(minute) = now.replace_second(0).and_then(|t| t.replace_nanosecond(0)) else { return };
+
+    for job in jobs
+    {
+        let Ok(schedule) = job.schedule.parse::<Schedule>() else
+        {
+            warn!("Skipping job ID {} with an invalid schedule '{}'", job.id, job.schedule);
+            continue;
+        };
+
+        if !schedule.matches(minute)
+        {
+            continue;
+        }
+
+        match claim_job(&state.db_pool, job.id, minute).await
+        {
+            Ok(true) =>
+            {
+                let state = state.clone();
+                tokio::spawn(async move { run_job(&state, &job).await });
+            }
+            Ok(false) => {}
+            Err(e) => error!("Failed to claim job ID {}: {}", job.id, e),
+        }
+    }
+}
+
+/// Marque la tâche comme déclenchée pour cette minute. Renvoie `false` si elle l'a déjà été,
+/// ce qui évite une double exécution si plusieurs instances du planificateur tournent.
+async fn claim_job(pool: &PgPool, job_id: i32, minute: OffsetDateTime) -> Result<bool, sqlx::Error>
+{
+    let result = sqlx::query(
+        "UPDATE project_jobs SET last_run_at = $1 WHERE id = $2 AND (last_run_at IS NULL OR last_run_at < $1)",
+    )
+    .bind(minute)
+    .bind(job_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Exécute une tâche dans un conteneur éphémère et enregistre le résultat.
+async fn run_job(state: &AppState, job: &ProjectJob)
+{
+    let project = match project_service::get_project_by_id(&state.db_pool, job.project_id).await
+    {
+        Ok(Some(project)) => project,
+        Ok(None) => return,
+        Err(e) =>
+        {
+            error!("Failed to load project ID {} for job ID {}: {:?}", job.project_id, job.id, e);
+            return;
+        }
+    };
+
+    let run_id = match start_run(&state.db_pool, job.id).await
+    {
+        Ok(id) => id,
+        Err(e) =>
+        {
+            error!("Failed to record start of job ID {}: {}", job.id, e);
+            return;
+        }
+    };
+
+    info!("Running job ID {} of project '{}' (run ID {})", job.id, project.name, run_id);
+    emit_project_event(
+        state,
+        project.id,
+        SystemEvent::info(format!("Job #{} started.", job.id)).with_context(json!({ "job_id": job.id, "run_id": run_id })),
+    ).await;
+
+    let (status, exit_code, output) = match execute(state, &project, job, run_id).await
+    {
+        Ok(outcome) if outcome.timed_out => (JobRunStatus::TimedOut, None, outcome.output_tail.join("\n")),
+        Ok(outcome) =>
+        {
+            let status = if outcome.exit_code == Some(0) { JobRunStatus::Succeeded } else { JobRunStatus::Failed };
+            let exit_code = outcome.exit_code.and_then(|code| i32::try_from(code).ok());
+            (status, exit_code, outcome.output_tail.join("\n"))
+        }
+        Err(e) =>
+        {
+            error!("Job ID {} of project '{}' could not be started: {:?}", job.id, project.name, e);
+            (JobRunStatus::Failed, None, "The job container could not be started.".to_string())
+        }
+    };
+
+    if let Err(e) = finish_run(&state.db_pool, run_id, status, exit_code, &output).await
+    {
+        error!("Failed to record outcome of job run ID {}: {}", run_id, e);
+    }
+
+    if let Err(e) = prune_runs(&state.db_pool, job.id).await
+    {
+        warn!("Failed to prune runs of job ID {}: {}", job.id, e);
+    }
+
+    let context = json!({ "job_id": job.id, "run_id": run_id, "status": status, "exit_code": exit_code });
+    let event = match status
+    {
+        JobRunStatus::Succeeded => SystemEvent::info(format!("Job #{} succeeded.", job.id)),
+        JobRunStatus::TimedOut => SystemEvent::error(format!("Job #{} timed out.", job.id)),
+        _ => SystemEvent::error(format!("Job #{} failed.", job.id)),
+    };
+    notification_service::notify_project_members(state, &project, NotificationKind::JobRun, event.with_context(context)).await;
+}
+
+async fn execute(
+    state: &AppState,
+    project: &Project,
+    job: &ProjectJob,
+    run_id: i32,
+) -> Result<docker_service::OneOffOutcome, AppError>
+{
+    let container_name = format!("{}-{}-job-{}", state.config.app_prefix, project.name, run_id);
+    let timeout = state.config.jobs_timeout_seconds.as_duration();
+
+    run_in_project_image(state, project, &container_name, &job.command, timeout, None).await
+}
+
+/// Exécute une commande dans un conteneur éphémère créé à partir de l'image déployée du projet,
+/// avec ses variables d'environnement et son volume persistant.
+pub async fn run_in_project_image(
+    state: &AppState,
+    project: &Project,
+    container_name: &str,
+    command: &[String],
+    timeout: Duration,
+    line_sink: Option<UnboundedSender<String>>,
+) -> Result<docker_service::OneOffOutcome, AppError>
+{
+    let env_vars = project_service::get_decrypted_env_vars(project, &state.config.encryption_key)?;
+    let env_vars = project_service::runtime_env_vars(state, project, env_vars).await?;
+
+    let volume = project.volume_name.as_deref().zip(project.persistent_volume_path.as_deref());
+
+    let task = OneOffTask
+    {
+        container_name,
+        image: &project.deployed_image_digest,
+        command,
+        env_vars: env_vars.as_ref(),
+        timezone: project.timezone.as_deref(),
+        volume,
+        resources: state.config.resource_profile(&project.resource_profile),
+        timeout,
+        output_tail_lines: OUTPUT_TAIL_LINES,
+    };
+
+    docker_service::run_one_off_container(&state.docker_client, &state.config, &task, line_sink).await
+}
+
+async fn start_run(pool: &PgPool, job_id: i32) -> Result<i32, sqlx::Error>
+{
+    sqlx::query_scalar("INSERT INTO job_runs (job_id) VALUES ($1) RETURNING id")
+        .bind(job_id)
+        .fetch_one(pool)
+        .await
+}
+
+async fn finish_run(
+    pool: &PgPool,
+    run_id: i32,
+    status: JobRunStatus,
+    exit_code: Option<i32>,
+    output: &str,
+) -> Result<(), sqlx::Error>
+{
+    sqlx::query("UPDATE job_runs SET status = $1, exit_code = $2, output = $3, finished_at = NOW() WHERE id = $4")
+        .bind(status)
+        .bind(exit_code)
+        .bind(output)
+        .bind(run_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+}
+
+/// Ne conserve que les `RUNS_RETENTION` exécutions les plus récentes de la tâche.
+async fn prune_runs(pool: &PgPool, job_id: i32) -> Result<(), sqlx::Error>
+{
+    sqlx::query(
+        "DELETE FROM job_runs WHERE job_id = $1 AND id NOT IN \
+         (SELECT id FROM job_runs WHERE job_id = $1 ORDER BY started_at DESC LIMIT $2)",
+    )
+    .bind(job_id)
+    .bind(RUNS_RETENTION)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+/// Vérifie l'expression cron et l'intervalle minimal entre deux exécutions.
+pub fn validate_schedule(expression: &str, min_interval_minutes: u64) -> Result<Schedule, AppError>
+{
+    let schedule: Schedule = expression
+        .parse()
+        .map_err(|e: crate::cron::CronParseError| ProjectErrorCode::InvalidJobSchedule(e.to_string()))?;
+
+    match schedule.min_interval(OffsetDateTime::now_utc())
+    {
+        None => Err(ProjectErrorCode::InvalidJobSchedule("expression never fires".to_string()).into()),
+        Some(interval) if interval < Duration::from_secs(min_interval_minutes.saturating_mul(60)) =>
+        {
+            Err(ProjectErrorCode::JobIntervalTooShort.into())
+        }
+        Some(_) => Ok(schedule),
+    }
+}
+
+pub fn validate_command(command: &[String]) -> Result<(), AppError>
+{
+    let valid = !command.is_empty()
+        && command.len() <= MAX_COMMAND_ARGS
+        && command.iter().all(|arg| !arg.is_empty() && arg.len() <= MAX_COMMAND_ARG_LENGTH && !arg.contains('\0'));
+
+    if valid { Ok(()) } else { Err(ProjectErrorCode::InvalidJobCommand.into()) }
+}
+
+pub async fn list_jobs(pool: &PgPool, project_id: i32) -> Result<Vec<ProjectJob>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_jobs WHERE project_id = $1 ORDER BY id")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list jobs of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn get_job(pool: &PgPool, project_id: i32, job_id: i32) -> Result<Option<ProjectJob>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_jobs WHERE id = $1 AND project_id = $2")
+        .bind(job_id)
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch job ID {}: {}", job_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn create_job(
+    pool: &PgPool,
+    project_id: i32,
+    schedule: &Schedule,
+    command: &[String],
+    enabled: bool,
+    max_per_project: i64,
+) -> Result<ProjectJob, AppError>
+{
+    let mut tx = pool.begin().await.map_err(|e|
+    {
+        error!("Failed to begin transaction for job creation: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    // Verrouille le projet pour que deux créations simultanées ne dépassent pas la limite.
+    sqlx::query("SELECT id FROM projects WHERE id = $1 FOR UPDATE")
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to lock project ID {} for job creation: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM project_jobs WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count jobs of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    if count >= max_per_project
+    {
+        return Err(ProjectErrorCode::JobLimitReached.into());
+    }
+
+    let job = sqlx::query_as(
+        "INSERT INTO project_jobs (project_id, schedule, command, enabled) VALUES ($1, $2, $3, $4) RETURNING *",
+    )
+    .bind(project_id)
+    .bind(schedule.to_string())
+    .bind(command)
+    .bind(enabled)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to create job for project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+
+    tx.commit().await.map_err(|e|
+    {
+        error!("Failed to commit job creation for project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(job)
+}
+
+pub async fn update_job(
+    pool: &PgPool,
+    job_id: i32,
+    schedule: &Schedule,
+    command: &[String],
+    enabled: bool,
+) -> Result<ProjectJob, AppError>
+{
+    sqlx::query_as("UPDATE project_jobs SET schedule = $1, command = $2, enabled = $3 WHERE id = $4 RETURNING *")
+        .bind(schedule.to_string())
+        .bind(command)
+        .bind(enabled)
+        .bind(job_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update job ID {}: {}", job_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn delete_job(pool: &PgPool, job_id: i32) -> Result<(), AppError>
+{
+    sqlx::query("DELETE FROM project_jobs WHERE id = $1")
+        .bind(job_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to delete job ID {}: {}", job_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn list_runs(pool: &PgPool, job_id: i32) -> Result<Vec<JobRun>, AppError>
+{
+    sqlx::query_as("SELECT * FROM job_runs WHERE job_id = $1 ORDER BY started_at DESC")
+        .bind(job_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list runs of job ID {}: {}", job_id, e);
+            AppError::InternalServerError
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_schedule_enforces_min_interval()
+    {
+        assert!(validate_schedule("*/5 * * * *", 10).is_err());
+        assert!(validate_schedule("*/10 * * * *", 10).is_ok());
+        assert!(validate_schedule("0 4 * * *", 10).is_ok());
+        assert!(validate_schedule("not a cron", 10).is_err());
+        assert!(validate_schedule("* * 1 * *", 10).is_err());
+        assert!(validate_schedule("* * * 12 *", 10).is_err());
+        assert!(validate_schedule("0 0 31 2 *", 10).is_err());
+    }
+
+    #[test]
+    fn test_validate_command()
+    {
+        assert!(validate_command(&[]).is_err());
+        assert!(validate_command(&[String::new()]).is_err());
+        assert!(validate_command(&["php".to_string(), "artisan".to_string(), "schedule:run".to_string()]).is_ok());
+    }
+}
@@ -1,8 +1,8 @@
-use jsonwebtoken::{encode, decode, Header, Validation, EncodingKey, DecodingKey, TokenData};
+use jsonwebtoken::{encode, decode, errors::ErrorKind, Header, Validation, EncodingKey, DecodingKey, TokenData};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::error::AppError;
+use crate::error::{AppError, TokenErrorCode};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims 
@@ -32,5 +32,79 @@ pub fn generate_jwt(secret: &str, jwt_expiration_seconds : u64, login: &str, nam
 pub fn validate_jwt(token: &str, secret: &str) -> Result<TokenData<Claims>, AppError> 
 {
     decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default())
-    .map_err(|_| AppError::Unauthorized("Invalid token".to_string()))
+    .map_err(|e| match e.kind()
+    {
+        ErrorKind::ExpiredSignature => TokenErrorCode::Expired.into(),
+        _ => TokenErrorCode::Invalid.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::to_bytes, http::{StatusCode, header::WWW_AUTHENTICATE}, response::IntoResponse};
+    use futures::executor::block_on;
+
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    /// Statut, défi `WWW-Authenticate` et `error_code` de la réponse produite par l'erreur.
+    fn unauthorized_response(error: AppError) -> (StatusCode, String, String)
+    {
+        let response = error.into_response();
+        let status = response.status();
+        let challenge = response.headers()[WWW_AUTHENTICATE].to_str().unwrap().to_string();
+        let body = block_on(to_bytes(response.into_body(), usize::MAX)).unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        (status, challenge, body["error_code"].as_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_valid_token_is_accepted()
+    {
+        let token = generate_jwt(SECRET, 3600, "alice", "Alice", "alice@example.com", false).unwrap();
+        let data = validate_jwt(&token, SECRET).unwrap();
+        assert_eq!(data.claims.sub, "alice");
+    }
+
+    #[test]
+    fn test_missing_token_has_a_bare_challenge()
+    {
+        let (status, challenge, code) = unauthorized_response(TokenErrorCode::Missing.into());
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(challenge, "Bearer realm=\"hangar\"");
+        assert_eq!(code, "TOKEN_MISSING");
+    }
+
+    #[test]
+    fn test_expired_token_is_reported_as_expired()
+    {
+        let claims = Claims
+        {
+            sub: "alice".into(),
+            name: "Alice".into(),
+            email: "alice@example.com".into(),
+            exp: 1_000,
+            is_admin: false,
+        };
+        let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET.as_bytes())).unwrap();
+
+        let (status, challenge, code) = unauthorized_response(validate_jwt(&token, SECRET).unwrap_err());
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(challenge.contains("error=\"invalid_token\""));
+        assert_eq!(code, "TOKEN_EXPIRED");
+    }
+
+    #[test]
+    fn test_tampered_token_is_reported_as_invalid()
+    {
+        let token = generate_jwt("other-secret", 3600, "alice", "Alice", "alice@example.com", false).unwrap();
+        for token in [token.as_str(), "not-a-jwt"]
+        {
+            let (status, challenge, code) = unauthorized_response(validate_jwt(token, SECRET).unwrap_err());
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert!(challenge.contains("error=\"invalid_token\""));
+            assert_eq!(code, "TOKEN_INVALID");
+        }
+    }
 }
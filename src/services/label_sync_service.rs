@@ -0,0 +1,125 @@
+//! Écart entre les labels des conteneurs existants et ceux que poserait une création aujourd'hui.
+//!
+//! Les labels Docker sont immuables : après un changement de `APP_PREFIX`, de point d'entrée ou de
+//! résolveur de certificats Traefik, les conteneurs déjà créés gardent l'ancienne configuration
+//! jusqu'à leur recréation. Les labels hérités de l'image sont ignorés ; seuls les labels Traefik
+//! inattendus (routeur d'un ancien nommage) sont signalés en plus des labels attendus.
+
+use std::collections::HashMap;
+
+use crate::
+{
+    error::AppError,
+    model::{label_sync::LabelDiff, project::Project},
+    services::{docker_service, domain_service},
+    state::AppState,
+};
+
+/// Préfixe des labels lus par Traefik.
+const TRAEFIK_LABEL_PREFIX: &str = "traefik.";
+
+/// Labels attendus absents ou différents, puis labels Traefik qui ne seraient plus posés, triés par nom.
+#[must_use]
+pub fn diff_labels(current: &HashMap<String, String>, expected: &HashMap<String, String>) -> Vec<LabelDiff>
+{
+    let changed = expected.iter()
+        .filter(|(label, value)| current.get(*label) != Some(*value))
+        .map(|(label, value)| LabelDiff
+        {
+            label: label.clone(),
+            current: current.get(label).cloned(),
+            expected: Some(value.clone()),
+        });
+
+    let stale = current.iter()
+        .filter(|(label, _)| label.starts_with(TRAEFIK_LABEL_PREFIX) && !expected.contains_key(*label))
+        .map(|(label, value)| LabelDiff
+        {
+            label: label.clone(),
+            current: Some(value.clone()),
+            expected: None,
+        });
+
+    let mut diffs: Vec<LabelDiff> = changed.chain(stale).collect();
+    diffs.sort_by(|a, b| a.label.cmp(&b.label));
+    diffs
+}
+
+/// Écart de labels du conteneur du projet, `None` si le projet est archivé ou n'a pas de conteneur.
+pub async fn label_diff(state: &AppState, project: &Project) -> Result<Option<Vec<LabelDiff>>, AppError>
+{
+    if project.archived
+    {
+        return Ok(None);
+    }
+
+    let Some(details) = docker_service::inspect_container_details(&state.docker_client, &project.container_name).await?
+    else
+    {
+        return Ok(None);
+    };
+
+    let current = details.config.and_then(|config| config.labels).unwrap_or_default();
+    let custom_domains = domain_service::verified_domains(&state.db_pool, project.id).await?;
+    let expected = docker_service::project_container_labels(
+        &state.config,
+        project.id,
+        &project.name,
+        project.http_port(),
+        &custom_domains,
+    );
+
+    Ok(Some(diff_labels(&current, &expected)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> HashMap<String, String>
+    {
+        pairs.iter().map(|(k, v)| ((*k).to_string(), (*v).to_string())).collect()
+    }
+
+    #[test]
+    fn test_diff_reports_changed_missing_and_stale_traefik_labels_only()
+    {
+        let current = labels(&[
+            ("app", "hangar"),
+            ("traefik.enable", "true"),
+            ("traefik.http.routers.old-blog.rule", "Host(`blog.example.com`)"),
+            ("traefik.http.routers.hangar-blog.entrypoints", "web"),
+            ("org.opencontainers.image.source", "https://github.com/foo/blog"),
+        ]);
+        let expected = labels(&[
+            ("app", "hangar"),
+            ("traefik.enable", "true"),
+            ("traefik.http.routers.hangar-blog.entrypoints", "websecure"),
+            ("traefik.http.routers.hangar-blog.rule", "Host(`blog.example.com`)"),
+        ]);
+
+        let diffs = diff_labels(&current, &expected);
+
+        assert_eq!(diffs, vec![
+            LabelDiff
+            {
+                label: "traefik.http.routers.hangar-blog.entrypoints".into(),
+                current: Some("web".into()),
+                expected: Some("websecure".into()),
+            },
+            LabelDiff
+            {
+                label: "traefik.http.routers.hangar-blog.rule".into(),
+                current: None,
+                expected: Some("Host(`blog.example.com`)".into()),
+            },
+            LabelDiff
+            {
+                label: "traefik.http.routers.old-blog.rule".into(),
+                current: Some("Host(`blog.example.com`)".into()),
+                expected: None,
+            },
+        ]);
+        assert!(diff_labels(&expected, &expected).is_empty());
+    }
+}
@@ -0,0 +1,321 @@
+//! Opérations d'administration sur le serveur MariaDB partagé : bases, utilisateurs, droits et limites.
+//!
+//! Toutes les instructions passent par [`AdminStatement`], seul endroit où des identifiants sont
+//! interpolés dans du SQL : ils y sont validés par [`valid_identifier`]. [`MariaDbAdmin`] est
+//! implémenté par [`SqlxMariaDbAdmin`] (serveur réel) et par [`RecordingMariaDbAdmin`], qui se contente
+//! d'enregistrer les instructions (mode `dry_run` des routes d'administration, tests).
+
+use std::{collections::HashSet, future::Future, sync::Mutex};
+
+use sqlx::MySqlPool;
+use thiserror::Error;
+use tracing::error;
+
+use crate::model::database::DatabaseLimits;
+
+/// Remplace les mots de passe dans les instructions affichées.
+const REDACTED: &str = "***";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MariaDbAdminError
+{
+    #[error("Invalid identifier '{0}'")]
+    InvalidIdentifier(String),
+    #[error("MariaDB statement failed: {0}")]
+    Failed(String),
+}
+
+/// Nom de base ou d'utilisateur MariaDB utilisable entre backticks.
+pub fn valid_identifier(s: &str) -> bool
+{
+    if s.is_empty() || s.len() > 64 { return false; }
+
+    // Ne doit pas commencer par un chiffre
+    if s.chars().next().unwrap().is_ascii_digit() { return false; }
+
+    const RESERVED: &[&str] = &["SELECT", "DROP", "INSERT", "UPDATE", "DELETE", "TABLE", "DATABASE"];
+    if RESERVED.contains(&s.to_uppercase().as_str()) { return false; }
+
+    let allowed: HashSet<char> = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_".chars().collect();
+    s.chars().all(|c| allowed.contains(&c))
+}
+
+fn checked(identifier: &str) -> Result<&str, MariaDbAdminError>
+{
+    if valid_identifier(identifier)
+    {
+        Ok(identifier)
+    }
+    else
+    {
+        Err(MariaDbAdminError::InvalidIdentifier(identifier.to_string()))
+    }
+}
+
+/// Instruction d'administration MariaDB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminStatement<'a>
+{
+    CreateDatabase { db_name: &'a str },
+    CreateUser { username: &'a str, password: &'a str },
+    Grant { db_name: &'a str, username: &'a str },
+    DropDatabase { db_name: &'a str },
+    DropUser { username: &'a str },
+    AlterPassword { username: &'a str, password: &'a str },
+    SetLimits { username: &'a str, limits: DatabaseLimits },
+    FlushPrivileges,
+}
+
+impl AdminStatement<'_>
+{
+    /// SQL à exécuter, après validation des identifiants.
+    pub fn sql(&self) -> Result<String, MariaDbAdminError>
+    {
+        self.render(|password| password.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+
+    /// SQL affichable : identique à [`Self::sql`], mots de passe masqués.
+    pub fn redacted(&self) -> Result<String, MariaDbAdminError>
+    {
+        self.render(|_| REDACTED.to_string())
+    }
+
+    /// L'instruction contient-elle un mot de passe ? Ses erreurs ne sont alors pas journalisées en détail.
+    #[must_use]
+    pub const fn has_secret(&self) -> bool
+    {
+        matches!(self, Self::CreateUser { .. } | Self::AlterPassword { .. })
+    }
+
+    fn render(&self, password: impl Fn(&str) -> String) -> Result<String, MariaDbAdminError>
+    {
+        Ok(match *self
+        {
+            Self::CreateDatabase { db_name } =>
+            {
+                format!("CREATE DATABASE `{}` CHARACTER SET utf8mb4 COLLATE utf8mb4_general_ci", checked(db_name)?)
+            }
+            Self::CreateUser { username, password: secret } =>
+            {
+                format!("CREATE USER `{}`@'%' IDENTIFIED BY '{}'", checked(username)?, password(secret))
+            }
+            Self::Grant { db_name, username } => format!(
+                "GRANT SELECT, INSERT, UPDATE, DELETE, CREATE, DROP, INDEX, ALTER, CREATE TEMPORARY TABLES, LOCK TABLES ON `{}`.* TO `{}`@'%'",
+                checked(db_name)?, checked(username)?
+            ),
+            Self::DropDatabase { db_name } => format!("DROP DATABASE IF EXISTS `{}`", checked(db_name)?),
+            Self::DropUser { username } => format!("DROP USER IF EXISTS `{}`@'%'", checked(username)?),
+            Self::AlterPassword { username, password: secret } =>
+            {
+                format!("ALTER USER `{}`@'%' IDENTIFIED BY '{}'", checked(username)?, password(secret))
+            }
+            Self::SetLimits { username, limits } => format!(
+                "ALTER USER `{}`@'%' WITH MAX_USER_CONNECTIONS {} MAX_QUERIES_PER_HOUR {}",
+                checked(username)?, limits.max_user_connections, limits.max_queries_per_hour
+            ),
+            Self::FlushPrivileges => "FLUSH PRIVILEGES".to_string(),
+        })
+    }
+}
+
+/// Exécution des instructions d'administration MariaDB.
+pub trait MariaDbAdmin: Sync
+{
+    /// Exécute une instruction ; ses identifiants sont validés avant tout envoi au serveur.
+    fn execute(&self, statement: &AdminStatement<'_>) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send;
+
+    /// Exécute les instructions dans l'ordre, après les avoir toutes validées : un identifiant invalide
+    /// n'en laisse aucune à moitié appliquée.
+    fn run(&self, statements: &[AdminStatement<'_>]) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send
+    {
+        async move
+        {
+            for statement in statements
+            {
+                statement.sql()?;
+            }
+            for statement in statements
+            {
+                self.execute(statement).await?;
+            }
+            Ok(())
+        }
+    }
+
+    fn create_database(&self, db_name: &str) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send
+    {
+        async move { self.execute(&AdminStatement::CreateDatabase { db_name }).await }
+    }
+
+    fn create_user(&self, username: &str, password: &str) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send
+    {
+        async move { self.execute(&AdminStatement::CreateUser { username, password }).await }
+    }
+
+    /// Accorde à `username` les droits d'usage courant sur `db_name`, puis recharge les droits.
+    fn grant(&self, db_name: &str, username: &str) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send
+    {
+        async move { self.run(&[AdminStatement::Grant { db_name, username }, AdminStatement::FlushPrivileges]).await }
+    }
+
+    fn drop_database(&self, db_name: &str) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send
+    {
+        async move { self.execute(&AdminStatement::DropDatabase { db_name }).await }
+    }
+
+    fn drop_user(&self, username: &str) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send
+    {
+        async move { self.execute(&AdminStatement::DropUser { username }).await }
+    }
+
+    fn alter_password(&self, username: &str, password: &str) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send
+    {
+        async move { self.execute(&AdminStatement::AlterPassword { username, password }).await }
+    }
+
+    fn set_limits(&self, username: &str, limits: DatabaseLimits) -> impl Future<Output = Result<(), MariaDbAdminError>> + Send
+    {
+        async move { self.execute(&AdminStatement::SetLimits { username, limits }).await }
+    }
+}
+
+/// Exécution sur le serveur MariaDB partagé.
+pub struct SqlxMariaDbAdmin<'a>
+{
+    pool: &'a MySqlPool,
+}
+
+impl<'a> SqlxMariaDbAdmin<'a>
+{
+    #[must_use]
+    pub const fn new(pool: &'a MySqlPool) -> Self
+    {
+        Self { pool }
+    }
+}
+
+impl MariaDbAdmin for SqlxMariaDbAdmin<'_>
+{
+    async fn execute(&self, statement: &AdminStatement<'_>) -> Result<(), MariaDbAdminError>
+    {
+        let sql = statement.sql()?;
+        let redacted = statement.redacted()?;
+
+        sqlx::query(&sql)
+            .execute(self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|e|
+            {
+                if statement.has_secret()
+                {
+                    error!("MariaDB statement '{}' failed (details hidden for security)", redacted);
+                }
+                else
+                {
+                    error!("MariaDB statement '{}' failed: {}", redacted, e);
+                }
+                MariaDbAdminError::Failed(redacted)
+            })
+    }
+}
+
+/// Enregistre les instructions, mots de passe masqués, sans rien exécuter.
+#[derive(Default)]
+pub struct RecordingMariaDbAdmin
+{
+    statements: Mutex<Vec<String>>,
+    /// Préfixe des instructions à faire échouer, pour simuler une erreur du serveur.
+    fail_on: Option<&'static str>,
+}
+
+impl RecordingMariaDbAdmin
+{
+    /// Fait échouer les instructions commençant par `prefix` (`"GRANT"`, `"CREATE USER"`...).
+    #[must_use]
+    pub fn failing_on(prefix: &'static str) -> Self
+    {
+        Self { statements: Mutex::default(), fail_on: Some(prefix) }
+    }
+
+    /// Instructions enregistrées, dans l'ordre.
+    #[must_use]
+    pub fn statements(&self) -> Vec<String>
+    {
+        self.statements.lock().unwrap_or_else(std::sync::PoisonError::into_inner).clone()
+    }
+}
+
+impl MariaDbAdmin for RecordingMariaDbAdmin
+{
+    async fn execute(&self, statement: &AdminStatement<'_>) -> Result<(), MariaDbAdminError>
+    {
+        let redacted = statement.redacted()?;
+        self.statements.lock().unwrap_or_else(std::sync::PoisonError::into_inner).push(redacted.clone());
+
+        match self.fail_on
+        {
+            Some(prefix) if redacted.starts_with(prefix) => Err(MariaDbAdminError::Failed(redacted)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    #[test]
+    fn test_valid_identifier()
+    {
+        assert!(valid_identifier("hangardb_jdoe"));
+        assert!(!valid_identifier("jdoe`; DROP USER root"));
+        assert!(!valid_identifier("1abc"));
+        assert!(!valid_identifier(""));
+    }
+
+    #[test]
+    fn test_statements_redact_passwords()
+    {
+        let statement = AdminStatement::CreateUser { username: "jdoe", password: "it's" };
+        assert_eq!(statement.sql().unwrap(), "CREATE USER `jdoe`@'%' IDENTIFIED BY 'it\\'s'");
+        assert_eq!(statement.redacted().unwrap(), "CREATE USER `jdoe`@'%' IDENTIFIED BY '***'");
+
+        let rotation = AdminStatement::AlterPassword { username: "hangardb_jdoe", password: "n3w" };
+        assert_eq!(rotation.sql().unwrap(), "ALTER USER `hangardb_jdoe`@'%' IDENTIFIED BY 'n3w'");
+        assert_eq!(rotation.redacted().unwrap(), "ALTER USER `hangardb_jdoe`@'%' IDENTIFIED BY '***'");
+
+        let limits = DatabaseLimits { max_user_connections: 10, max_queries_per_hour: 0 };
+        assert_eq!(
+            AdminStatement::SetLimits { username: "hangardb_jdoe", limits }.sql().unwrap(),
+            "ALTER USER `hangardb_jdoe`@'%' WITH MAX_USER_CONNECTIONS 10 MAX_QUERIES_PER_HOUR 0"
+        );
+    }
+
+    #[test]
+    fn test_run_validates_every_statement_first()
+    {
+        let admin = RecordingMariaDbAdmin::default();
+        let result = block_on(admin.run(&[
+            AdminStatement::CreateDatabase { db_name: "hangardb_jdoe" },
+            AdminStatement::DropUser { username: "root`@'%'; --" },
+        ]));
+
+        assert_eq!(result, Err(MariaDbAdminError::InvalidIdentifier("root`@'%'; --".to_string())));
+        assert!(admin.statements().is_empty());
+    }
+
+    #[test]
+    fn test_grant_flushes_privileges()
+    {
+        let admin = RecordingMariaDbAdmin::default();
+        block_on(admin.grant("hangardb_jdoe", "jdoe")).unwrap();
+
+        let statements = admin.statements();
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].starts_with("GRANT SELECT"));
+        assert_eq!(statements[1], "FLUSH PRIVILEGES");
+    }
+}
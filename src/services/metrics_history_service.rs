@@ -0,0 +1,190 @@
+//! Historique des métriques de la plateforme (conteneurs démarrés, CPU et mémoire cumulés, nombre
+//! de projets) pour le graphe d'administration.
+//!
+//! Le collecteur de métriques (`sse::tasks`) écrit un échantillon agrégé toutes les
+//! `METRICS_HISTORY_INTERVAL_MINUTES` ; un seul par relevé, quel que soit le nombre de projets.
+//! Les échantillons sont purgés après `METRICS_HISTORY_RETENTION_DAYS` jours et rééchantillonnés
+//! à la lecture.
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{debug, error, info};
+
+use crate::
+{
+    error::AppError,
+    model::project::{MetricsHistoryPoint, ProjectMetrics},
+    units::MemoryBytes,
+};
+
+/// Nombre maximal de points renvoyés pour une période.
+const MAX_HISTORY_POINTS: i64 = 1000;
+/// Nombre de points visé quand le pas n'est pas précisé.
+const DEFAULT_HISTORY_POINTS: i64 = 288;
+
+/// Période des relevés de l'historique.
+#[must_use]
+pub const fn sample_interval(interval_minutes: u64) -> Duration
+{
+    Duration::from_secs(interval_minutes.saturating_mul(60))
+}
+
+/// Agrégat de la plateforme, construit par le collecteur de métriques à partir des relevés
+/// qu'il fait déjà pour chaque conteneur démarré.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PlatformSample
+{
+    pub running_containers: u64,
+    pub total_cpu_usage: f64,
+    pub total_memory_usage: MemoryBytes,
+}
+
+impl PlatformSample
+{
+    pub fn add(&mut self, metrics: &ProjectMetrics)
+    {
+        self.running_containers += 1;
+        self.total_cpu_usage += metrics.cpu_usage_percent;
+        self.total_memory_usage = self.total_memory_usage.saturating_add(metrics.memory_usage_bytes);
+    }
+}
+
+/// Enregistre un échantillon puis purge ceux qui dépassent la rétention.
+pub async fn record_sample(pool: &PgPool, sample: &PlatformSample, retention_days: i32) -> Result<(), AppError>
+{
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to insert platform metrics sample: {}", e);
+        AppError::InternalServerError
+    };
+
+    let total_projects: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM projects")
+        .fetch_one(pool)
+        .await
+        .map_err(db_error)?;
+
+    sqlx::query(
+        "INSERT INTO metrics_history (running_containers, total_cpu_usage, total_memory_usage_mb, total_projects) \
+         VALUES ($1, $2, $3, $4)"
+    )
+    .bind(i32::try_from(sample.running_containers).unwrap_or(i32::MAX))
+    .bind(sample.total_cpu_usage)
+    .bind(sample.total_memory_usage.as_mb_f64())
+    .bind(i32::try_from(total_projects).unwrap_or(i32::MAX))
+    .execute(pool)
+    .await
+    .map_err(db_error)?;
+
+    debug!("Recorded platform metrics sample ({} running container(s))", sample.running_containers);
+    prune_history(pool, retention_days).await;
+    Ok(())
+}
+
+async fn prune_history(pool: &PgPool, retention_days: i32)
+{
+    match sqlx::query("DELETE FROM metrics_history WHERE sampled_at < NOW() - make_interval(days => $1)")
+        .bind(retention_days)
+        .execute(pool)
+        .await
+    {
+        Ok(result) if result.rows_affected() > 0 => info!("Pruned {} row(s) from metrics_history", result.rows_affected()),
+        Ok(_) => {}
+        Err(e) => error!("Failed to prune metrics_history: {}", e),
+    }
+}
+
+/// Pas de rééchantillonnage, en secondes : celui demandé, au moins l'intervalle d'échantillonnage,
+/// ou par défaut de quoi couvrir la période en [`DEFAULT_HISTORY_POINTS`] points. Un pas plus long que
+/// la période est refusé.
+pub fn resolve_step(span_seconds: i64, requested: Option<i64>, sample_interval_seconds: i64) -> Result<i64, AppError>
+{
+    let step = match requested
+    {
+        Some(step) if step < sample_interval_seconds =>
+        {
+            return Err(AppError::BadRequest(format!("step must be at least {sample_interval_seconds} seconds (the sampling interval).")));
+        }
+        Some(step) if step > span_seconds.max(sample_interval_seconds) =>
+        {
+            return Err(AppError::BadRequest("step must not exceed the requested period.".to_string()));
+        }
+        Some(step) => step,
+        None => ceil_div(span_seconds, DEFAULT_HISTORY_POINTS).max(sample_interval_seconds),
+    };
+
+    if ceil_div(span_seconds, step) > MAX_HISTORY_POINTS
+    {
+        return Err(AppError::BadRequest(format!("The requested period would return more than {MAX_HISTORY_POINTS} points; increase step.")));
+    }
+
+    Ok(step)
+}
+
+/// Division arrondie au supérieur de deux valeurs positives, sans débordement.
+fn ceil_div(a: i64, b: i64) -> i64
+{
+    a / b + i64::from(a % b != 0)
+}
+
+/// Moyenne des échantillons de `[from, to)` par pas de `step_seconds`, alignés sur `from`.
+/// Les pas sans échantillon (plateforme arrêtée) sont absents.
+pub async fn history(pool: &PgPool, from: OffsetDateTime, to: OffsetDateTime, step_seconds: i64) -> Result<Vec<MetricsHistoryPoint>, AppError>
+{
+    let points = sqlx::query_as::<_, MetricsHistoryPoint>(
+        "SELECT date_bin(make_interval(secs => $3), sampled_at, $1) AS timestamp, \
+                AVG(running_containers)::DOUBLE PRECISION AS running_containers, \
+                AVG(total_cpu_usage) AS total_cpu_usage, \
+                AVG(total_memory_usage_mb) AS total_memory_usage_mb, \
+                AVG(total_projects)::DOUBLE PRECISION AS total_projects \
+         FROM metrics_history \
+         WHERE sampled_at >= $1 AND sampled_at < $2 \
+         GROUP BY 1 \
+         ORDER BY 1"
+    )
+    .bind(from)
+    .bind(to)
+    .bind(step_seconds as f64)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to read metrics history: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_step_defaults_to_the_sampling_interval_or_a_bounded_point_count()
+    {
+        assert_eq!(resolve_step(DAY, None, 300).unwrap(), 300);
+        assert_eq!(resolve_step(3600, None, 300).unwrap(), 300);
+        assert_eq!(resolve_step(30 * DAY, None, 300).unwrap(), 9000);
+    }
+
+    #[test]
+    fn test_requested_step_is_bounded_by_the_interval_and_point_count()
+    {
+        assert_eq!(resolve_step(DAY, Some(3600), 300).unwrap(), 3600);
+        assert!(matches!(resolve_step(DAY, Some(60), 300), Err(AppError::BadRequest(_))));
+        assert!(matches!(resolve_step(30 * DAY, Some(300), 300), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_step_longer_than_the_period_is_rejected()
+    {
+        assert_eq!(resolve_step(DAY, Some(DAY), 300).unwrap(), DAY);
+        assert_eq!(resolve_step(60, Some(300), 300).unwrap(), 300);
+        assert!(matches!(resolve_step(DAY, Some(DAY + 1), 300), Err(AppError::BadRequest(_))));
+        assert!(matches!(resolve_step(DAY, Some(i64::MAX), 300), Err(AppError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,118 @@
+//! Événements émis sans abonné (résultats de scan, pannes, fin de déploiement) : conservés pour
+//! l'owner hors ligne, puis livrés une seule fois, par `GET /api/projects/{id}/events/missed` ou
+//! au début du prochain flux SSE.
+
+use sqlx::{PgPool, types::Json};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{error, info};
+
+use crate::
+{
+    error::AppError,
+    sse::{manager::{MissedEvent, MissedEventTarget}, types::SseEvent},
+    state::AppState,
+};
+
+/// Événements en attente d'écriture ; au-delà, les suivants sont perdus comme auparavant.
+pub const MISSED_EVENTS_QUEUE: usize = 256;
+
+pub async fn start_missed_events_writer(
+    state: AppState,
+    mut events: mpsc::Receiver<MissedEvent>,
+    mut shutdown_signal: broadcast::Receiver<()>,
+)
+{
+    info!("Starting missed events writer task");
+
+    loop
+    {
+        let missed = tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Missed events writer task shutting down");
+                return;
+            }
+            missed = events.recv() => match missed
+            {
+                Some(missed) => missed,
+                None => return,
+            },
+        };
+
+        if let Err(e) = store(&state.db_pool, &missed, state.config.missed_events_max).await
+        {
+            error!("Failed to store missed '{}' event for {:?}: {}", missed.event.event_type(), missed.target, e);
+        }
+    }
+}
+
+/// Conserve l'événement, puis ne garde que les `max_per_target` plus récents de son destinataire.
+async fn store(pool: &PgPool, missed: &MissedEvent, max_per_target: i64) -> Result<(), sqlx::Error>
+{
+    let (project_id, login) = match &missed.target
+    {
+        MissedEventTarget::Project(project_id) => (Some(*project_id), None),
+        MissedEventTarget::User(login) => (None, Some(login.as_str())),
+    };
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("INSERT INTO missed_events (project_id, login, event) VALUES ($1, $2, $3)")
+        .bind(project_id)
+        .bind(login)
+        .bind(Json(&missed.event))
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        "DELETE FROM missed_events WHERE id IN ( \
+             SELECT id FROM missed_events \
+             WHERE project_id IS NOT DISTINCT FROM $1 AND login IS NOT DISTINCT FROM $2 \
+             ORDER BY id DESC OFFSET $3 \
+         )"
+    )
+    .bind(project_id)
+    .bind(login)
+    .bind(max_per_target)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Retire et renvoie, du plus ancien au plus récent, les événements manqués d'un projet.
+pub async fn take_for_project(pool: &PgPool, project_id: i32) -> Result<Vec<SseEvent>, AppError>
+{
+    sqlx::query_scalar::<_, Json<SseEvent>>(
+        "WITH taken AS (DELETE FROM missed_events WHERE project_id = $1 RETURNING id, event) \
+         SELECT event FROM taken ORDER BY id"
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .map(|events| events.into_iter().map(|Json(event)| event).collect())
+    .map_err(|e|
+    {
+        error!("Failed to take missed events of project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    })
+}
+
+/// Retire et renvoie, du plus ancien au plus récent, les événements manqués du canal personnel d'un utilisateur.
+pub async fn take_for_user(pool: &PgPool, login: &str) -> Result<Vec<SseEvent>, AppError>
+{
+    sqlx::query_scalar::<_, Json<SseEvent>>(
+        "WITH taken AS (DELETE FROM missed_events WHERE login = $1 RETURNING id, event) \
+         SELECT event FROM taken ORDER BY id"
+    )
+    .bind(login)
+    .fetch_all(pool)
+    .await
+    .map(|events| events.into_iter().map(|Json(event)| event).collect())
+    .map_err(|e|
+    {
+        error!("Failed to take missed events of user '{}': {}", login, e);
+        AppError::InternalServerError
+    })
+}
@@ -6,4 +6,39 @@ pub mod validation_service;
 pub mod github_service;
 pub mod crypto_service;
 pub mod database_service;
-pub mod deployment_orchestrator;
\ No newline at end of file
+pub mod deployment_orchestrator;
+pub mod audit_service;
+pub mod backup_service;
+#[cfg(feature = "object_storage")]
+pub mod object_storage_service;
+pub mod job_service;
+pub mod api_key_service;
+pub mod deployment_service;
+pub mod user_service;
+pub mod cleanup_service;
+pub mod blue_green;
+pub mod notification_service;
+pub mod share_link_service;
+pub mod import_service;
+pub mod offboarding_service;
+pub mod usage_service;
+pub mod build_workspace_service;
+pub mod base_image_service;
+pub mod timeline_service;
+pub mod orphan_service;
+pub mod invitation_service;
+pub mod mariadb_admin;
+pub mod project_archive_service;
+pub mod idle_service;
+pub mod missed_event_service;
+pub mod crypto_audit_service;
+pub mod domain_service;
+pub mod template_service;
+pub mod upload_service;
+pub mod rollback;
+pub mod label_sync_service;
+pub mod metrics_history_service;
+pub mod uptime_service;
+pub mod webhook_service;
+pub mod notification_dispatch_service;
+pub mod email_service;
\ No newline at end of file
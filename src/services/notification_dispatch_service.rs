@@ -0,0 +1,300 @@
+//! Livraison des événements d'un projet à ses webhooks (déploiements, arrêts inattendus).
+//!
+//! Le corps JSON est signé en HMAC-SHA256 avec le secret du webhook ; la signature est envoyée dans
+//! l'en-tête `X-Hangar-Signature: sha256=<hex>`. Une livraison échouée est retentée avec un délai
+//! croissant, et chaque tentative est enregistrée. Après [`FAILURES_BEFORE_OPEN`] livraisons
+//! échouées d'affilée vers une URL, le circuit de cette URL s'ouvre : les livraisons sont écartées
+//! pendant [`OPEN_DURATION`], puis une seule est tentée pour le refermer.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use hmac::{Hmac, Mac};
+use reqwest::{header::{HeaderMap, HeaderValue}, Method};
+use serde_json::json;
+use sha2::Sha256;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tracing::{error, info, warn};
+
+use crate::
+{
+    model::webhook::{ProjectWebhook, WebhookEvent},
+    safe_http::{self, SafeHttpPolicy},
+    services::{validation_service, webhook_service::{self, DeliveryOutcome}},
+    state::AppState,
+};
+
+/// Délai maximal d'un appel de webhook.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+/// Tentatives par livraison, la première comprise.
+const MAX_ATTEMPTS: i32 = 3;
+/// Délai avant la première nouvelle tentative, doublé à chaque échec.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+/// Livraisons échouées d'affilée vers une URL avant l'ouverture de son circuit.
+pub const FAILURES_BEFORE_OPEN: u32 = 5;
+/// Durée pendant laquelle un circuit ouvert écarte les livraisons.
+pub const OPEN_DURATION: Duration = Duration::from_secs(300);
+
+const SIGNATURE_HEADER: &str = "x-hangar-signature";
+const EVENT_HEADER: &str = "x-hangar-event";
+
+/// Circuits des URL de webhook en échec. Une URL absente est fermée.
+#[derive(Default)]
+pub struct WebhookCircuits
+{
+    circuits: HashMap<String, Circuit>,
+}
+
+#[derive(Default)]
+struct Circuit
+{
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl WebhookCircuits
+{
+    /// Indique si une livraison vers `url` peut être tentée. À l'expiration d'un circuit ouvert, la
+    /// livraison d'essai est autorisée et le circuit reste ouvert pour les autres jusqu'à son résultat.
+    pub fn try_acquire(&mut self, url: &str, now: Instant) -> bool
+    {
+        let Some(circuit) = self.circuits.get_mut(url) else { return true };
+        match circuit.open_until
+        {
+            Some(until) if now < until => false,
+            Some(_) =>
+            {
+                circuit.open_until = Some(now + OPEN_DURATION);
+                true
+            }
+            None => true,
+        }
+    }
+
+    pub fn record_success(&mut self, url: &str)
+    {
+        self.circuits.remove(url);
+    }
+
+    pub fn record_failure(&mut self, url: &str, now: Instant)
+    {
+        let circuit = self.circuits.entry(url.to_string()).or_default();
+        circuit.consecutive_failures += 1;
+        if circuit.consecutive_failures >= FAILURES_BEFORE_OPEN
+        {
+            circuit.open_until = Some(now + OPEN_DURATION);
+        }
+    }
+}
+
+/// Transmet `event` aux webhooks du projet qui y sont abonnés, en arrière-plan.
+pub fn dispatch(state: &AppState, project_id: i32, project_name: &str, event: WebhookEvent, data: serde_json::Value)
+{
+    let state = state.clone();
+    let payload = json!({
+        "event": event,
+        "project_id": project_id,
+        "project_name": project_name,
+        "timestamp": OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default(),
+        "data": data,
+    });
+
+    tokio::spawn(async move
+    {
+        let webhooks = match webhook_service::list_webhooks(&state.db_pool, project_id).await
+        {
+            Ok(webhooks) => webhooks,
+            Err(e) =>
+            {
+                error!("Failed to list webhooks of project ID {} for {}: {:?}", project_id, event.as_str(), e);
+                return;
+            }
+        };
+
+        for webhook in webhooks.into_iter().filter(|webhook| webhook.accepts(event))
+        {
+            let state = state.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move { deliver(&state, &webhook, event, &payload).await });
+        }
+    });
+}
+
+async fn deliver(state: &AppState, webhook: &ProjectWebhook, event: WebhookEvent, payload: &serde_json::Value)
+{
+    let headers = match signed_headers(state, webhook, event, payload)
+    {
+        Ok(headers) => headers,
+        Err(reason) =>
+        {
+            error!("Cannot sign delivery of webhook ID {}: {}", webhook.id, reason);
+            return;
+        }
+    };
+
+    let acquired = state.webhook_circuits.lock()
+        .map(|mut circuits| circuits.try_acquire(&webhook.url, Instant::now()))
+        .unwrap_or(true);
+    if !acquired
+    {
+        let outcome = DeliveryOutcome { attempt: 1, status_code: None, error: Some("circuit open: too many consecutive failures".to_string()) };
+        webhook_service::record_delivery(&state.db_pool, webhook.id, event, &outcome).await;
+        return;
+    }
+
+    let policy = SafeHttpPolicy::new(state.config.safe_http_allowed_hosts.clone()).with_timeout(WEBHOOK_TIMEOUT);
+    let mut delay = RETRY_BASE_DELAY;
+    for attempt in 1..=MAX_ATTEMPTS
+    {
+        let (outcome, retryable) = post(&policy, webhook, &headers, payload, attempt).await;
+        webhook_service::record_delivery(&state.db_pool, webhook.id, event, &outcome).await;
+
+        if outcome.succeeded()
+        {
+            if let Ok(mut circuits) = state.webhook_circuits.lock()
+            {
+                circuits.record_success(&webhook.url);
+            }
+            info!("Delivered {} to webhook ID {} of project ID {}", event.as_str(), webhook.id, webhook.project_id);
+            return;
+        }
+        if !retryable || attempt == MAX_ATTEMPTS
+        {
+            break;
+        }
+
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+
+    warn!("Delivery of {} to webhook ID {} of project ID {} failed", event.as_str(), webhook.id, webhook.project_id);
+    if let Ok(mut circuits) = state.webhook_circuits.lock()
+    {
+        circuits.record_failure(&webhook.url, Instant::now());
+    }
+}
+
+fn signed_headers(state: &AppState, webhook: &ProjectWebhook, event: WebhookEvent, payload: &serde_json::Value) -> Result<HeaderMap, String>
+{
+    let secret = webhook_service::decrypt_secret(webhook, &state.config.encryption_key).map_err(|e| format!("{e:?}"))?;
+    // `safe_http` envoie le corps avec `serde_json::to_vec` : la signature porte sur les mêmes octets.
+    let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(EVENT_HEADER, HeaderValue::from_static(event.as_str()));
+    let signature = HeaderValue::from_str(&format!("sha256={}", sign(secret.as_bytes(), &body)?)).map_err(|e| e.to_string())?;
+    headers.insert(SIGNATURE_HEADER, signature);
+    Ok(headers)
+}
+
+/// Signature HMAC-SHA256 de `body`, en hexadécimal.
+fn sign(secret: &[u8], body: &[u8]) -> Result<String, String>
+{
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret).map_err(|e| e.to_string())?;
+    mac.update(body);
+    Ok(mac.finalize().into_bytes().iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Une tentative de livraison et si elle peut être retentée (erreur réseau, 429 ou 5xx).
+async fn post(policy: &SafeHttpPolicy, webhook: &ProjectWebhook, headers: &HeaderMap, payload: &serde_json::Value, attempt: i32) -> (DeliveryOutcome, bool)
+{
+    let failed = |status_code: Option<i32>, error: String| DeliveryOutcome { attempt, status_code, error: Some(error) };
+
+    if let Err(e) = validation_service::validate_webhook_url(&webhook.url)
+    {
+        return (failed(None, format!("{e:?}")), false);
+    }
+
+    match safe_http::send(policy, Method::POST, &webhook.url, headers, Some(payload)).await
+    {
+        Ok(response) =>
+        {
+            let status = response.status();
+            let status_code = Some(i32::from(status.as_u16()));
+            if status.is_success()
+            {
+                (DeliveryOutcome { attempt, status_code, error: None }, false)
+            }
+            else
+            {
+                let retryable = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                (failed(status_code, format!("unexpected status {status}")), retryable)
+            }
+        }
+        Err(e) => (failed(None, e.to_string()), true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://hooks.example.com/hangar";
+
+    #[test]
+    fn test_signature_is_hmac_sha256_in_hex()
+    {
+        // RFC 4231, cas de test 2.
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?").unwrap(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_circuit_opens_after_consecutive_failures()
+    {
+        let mut circuits = WebhookCircuits::default();
+        let now = Instant::now();
+
+        for _ in 1..FAILURES_BEFORE_OPEN
+        {
+            circuits.record_failure(URL, now);
+            assert!(circuits.try_acquire(URL, now));
+        }
+        circuits.record_failure(URL, now);
+
+        assert!(!circuits.try_acquire(URL, now));
+        assert!(!circuits.try_acquire(URL, now + OPEN_DURATION - Duration::from_secs(1)));
+        assert!(circuits.try_acquire("https://other.example.com/", now));
+    }
+
+    #[test]
+    fn test_success_resets_the_failure_count()
+    {
+        let mut circuits = WebhookCircuits::default();
+        let now = Instant::now();
+
+        for _ in 1..FAILURES_BEFORE_OPEN
+        {
+            circuits.record_failure(URL, now);
+        }
+        circuits.record_success(URL);
+        circuits.record_failure(URL, now);
+
+        assert!(circuits.try_acquire(URL, now));
+    }
+
+    #[test]
+    fn test_expired_circuit_lets_a_single_trial_through()
+    {
+        let mut circuits = WebhookCircuits::default();
+        let now = Instant::now();
+        for _ in 0..FAILURES_BEFORE_OPEN
+        {
+            circuits.record_failure(URL, now);
+        }
+
+        let later = now + OPEN_DURATION;
+        assert!(circuits.try_acquire(URL, later));
+        assert!(!circuits.try_acquire(URL, later));
+
+        // L'essai échoue : le circuit reste ouvert pour une nouvelle période.
+        circuits.record_failure(URL, later);
+        assert!(!circuits.try_acquire(URL, later + OPEN_DURATION - Duration::from_secs(1)));
+        assert!(circuits.try_acquire(URL, later + OPEN_DURATION));
+
+        circuits.record_success(URL);
+        assert!(circuits.try_acquire(URL, later + OPEN_DURATION));
+    }
+}
@@ -0,0 +1,268 @@
+//! Point d'entrée unique des notifications adressées aux membres d'un projet.
+//!
+//! L'événement est toujours publié sur le flux SSE du projet ; chaque membre le reçoit en plus
+//! sur le canal choisi dans ses préférences (SSE personnel, webhook personnel, e-mail ou rien).
+//! Les événements critiques réservés au propriétaire passent par [`notify_owner`].
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reqwest::{header::HeaderMap, Method};
+use serde_json::json;
+use sqlx::PgPool;
+use time::format_description::well_known::Rfc3339;
+use tracing::{debug, error, warn};
+
+use crate::
+{
+    error::AppError,
+    model::{notification::{NotificationChannel, NotificationKind, NotificationSettings}, project::Project},
+    safe_http::{self, SafeHttpPolicy},
+    services::{email_service, project_service, validation_service},
+    sse::{emitter::{emit_creation_event, emit_project_event}, types::SystemEvent},
+    state::AppState,
+};
+
+/// Délai maximal d'un appel de webhook.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub async fn get_settings(pool: &PgPool, login: &str) -> Result<NotificationSettings, AppError>
+{
+    let webhook_url: Option<String> = sqlx::query_scalar("SELECT url FROM notification_webhooks WHERE login = $1")
+        .bind(login)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch notification webhook of user '{}': {}", login, e);
+            AppError::InternalServerError
+        })?;
+
+    let rows: Vec<(String, NotificationChannel)> = sqlx::query_as(
+        "SELECT event_type, channel FROM notification_preferences WHERE login = $1"
+    )
+        .bind(login)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch notification preferences of user '{}': {}", login, e);
+            AppError::InternalServerError
+        })?;
+
+    let preferences = rows.into_iter()
+        .filter_map(|(event_type, channel)| NotificationKind::parse(&event_type).map(|kind| (kind, channel)))
+        .collect();
+
+    Ok(NotificationSettings { webhook_url, preferences, email_enabled: false }.with_defaults())
+}
+
+/// Remplace les préférences de l'utilisateur. L'URL du webhook doit avoir été validée.
+pub async fn update_settings(pool: &PgPool, login: &str, settings: &NotificationSettings) -> Result<(), AppError>
+{
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to update notification preferences of user '{}': {}", login, e);
+        AppError::InternalServerError
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    sqlx::query("DELETE FROM notification_preferences WHERE login = $1")
+        .bind(login)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    for (kind, channel) in &settings.preferences
+    {
+        sqlx::query("INSERT INTO notification_preferences (login, event_type, channel) VALUES ($1, $2, $3)")
+            .bind(login)
+            .bind(kind.as_str())
+            .bind(channel)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+    }
+
+    match &settings.webhook_url
+    {
+        Some(url) =>
+        {
+            sqlx::query(
+                "INSERT INTO notification_webhooks (login, url) VALUES ($1, $2) \
+                 ON CONFLICT (login) DO UPDATE SET url = EXCLUDED.url, updated_at = NOW()"
+            )
+                .bind(login)
+                .bind(url)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+        }
+        None =>
+        {
+            sqlx::query("DELETE FROM notification_webhooks WHERE login = $1")
+                .bind(login)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+        }
+    }
+
+    tx.commit().await.map_err(db_error)
+}
+
+/// Publie `event` sur le flux du projet puis le livre au propriétaire et aux participants
+/// selon leurs préférences. Best-effort : les échecs sont journalisés.
+pub async fn notify_project_members(state: &AppState, project: &Project, kind: NotificationKind, event: SystemEvent)
+{
+    emit_project_event(state, project.id, event.clone()).await;
+
+    let mut recipients: Vec<String> = match project_service::get_project_participants(&state.db_pool, project.id).await
+    {
+        Ok(participants) => participants.into_iter().map(|participant| participant.login).collect(),
+        Err(e) =>
+        {
+            warn!("Failed to list participants of project '{}' for notification: {:?}", project.name, e);
+            Vec::new()
+        }
+    };
+    recipients.push(project.owner.clone());
+    recipients.sort();
+    recipients.dedup();
+
+    let routes = match load_routes(&state.db_pool, &recipients, kind).await
+    {
+        Ok(routes) => routes,
+        Err(e) =>
+        {
+            error!("Failed to load notification preferences for project '{}': {}", project.name, e);
+            HashMap::new()
+        }
+    };
+
+    for login in recipients
+    {
+        let route = routes.get(&login).cloned().unwrap_or((kind.default_channel(), None));
+        deliver(state, project, login, kind, &event, route).await;
+    }
+}
+
+/// Livre `event` au seul propriétaire du projet, sur le canal de ses préférences, en arrière-plan.
+/// L'événement n'est pas publié sur le flux du projet : l'appelant l'y signale déjà.
+pub fn notify_owner(state: &AppState, project_id: i32, kind: NotificationKind, event: SystemEvent)
+{
+    let state = state.clone();
+    tokio::spawn(async move
+    {
+        let project = match project_service::get_project_by_id(&state.db_pool, project_id).await
+        {
+            Ok(Some(project)) => project,
+            Ok(None) => return,
+            Err(e) =>
+            {
+                error!("Failed to load project ID {} for {} notification: {:?}", project_id, kind.as_str(), e);
+                return;
+            }
+        };
+
+        let owner = project.owner.clone();
+        let route = match load_routes(&state.db_pool, std::slice::from_ref(&owner), kind).await
+        {
+            Ok(mut routes) => routes.remove(&owner),
+            Err(e) =>
+            {
+                error!("Failed to load notification preferences for project '{}': {}", project.name, e);
+                None
+            }
+        };
+
+        deliver(&state, &project, owner, kind, &event, route.unwrap_or((kind.default_channel(), None))).await;
+    });
+}
+
+async fn deliver(
+    state: &AppState,
+    project: &Project,
+    login: String,
+    kind: NotificationKind,
+    event: &SystemEvent,
+    route: (NotificationChannel, Option<String>),
+)
+{
+    match route
+    {
+        (NotificationChannel::Sse, _) => emit_creation_event(state, &login, event.clone()).await,
+        (NotificationChannel::Webhook, Some(url)) =>
+        {
+            let payload = json!({
+                "event_type": kind,
+                "project_id": project.id,
+                "project_name": project.name,
+                "level": event.level,
+                "message": event.message,
+                "context": event.context,
+                "timestamp": event.timestamp.format(&Rfc3339).unwrap_or_default(),
+            });
+            let policy = SafeHttpPolicy::new(state.config.safe_http_allowed_hosts.clone()).with_timeout(WEBHOOK_TIMEOUT);
+            tokio::spawn(async move
+            {
+                if let Err(reason) = post_webhook(&policy, &url, &payload).await
+                {
+                    warn!("Notification webhook of user '{}' failed: {}", login, reason);
+                }
+            });
+        }
+        (NotificationChannel::Webhook, None) =>
+        {
+            warn!("User '{}' chose webhook notifications without a webhook URL", login);
+        }
+        (NotificationChannel::Email, _) if state.mailer.is_some() =>
+        {
+            email_service::send_in_background(state, login, project.id, project.name.clone(), kind, event.message.clone());
+        }
+        (NotificationChannel::Email, _) =>
+        {
+            // Sans SMTP, la notification reste visible sur le canal SSE de l'utilisateur.
+            debug!("Email is disabled, sending {} notification to '{}' over SSE", kind.as_str(), login);
+            emit_creation_event(state, &login, event.clone()).await;
+        }
+        (NotificationChannel::None, _) => debug!("User '{}' muted {} notifications", login, kind.as_str()),
+    }
+}
+
+/// Canal et webhook de chaque destinataire ayant des préférences enregistrées.
+async fn load_routes(pool: &PgPool, logins: &[String], kind: NotificationKind) -> Result<HashMap<String, (NotificationChannel, Option<String>)>, sqlx::Error>
+{
+    let rows: Vec<(String, Option<NotificationChannel>, Option<String>)> = sqlx::query_as(
+        "SELECT l.login, p.channel, w.url \
+         FROM UNNEST($1::varchar[]) AS l(login) \
+         LEFT JOIN notification_preferences p ON p.login = l.login AND p.event_type = $2 \
+         LEFT JOIN notification_webhooks w ON w.login = l.login"
+    )
+        .bind(logins)
+        .bind(kind.as_str())
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter()
+        .map(|(login, channel, url)| (login, (channel.unwrap_or_else(|| kind.default_channel()), url)))
+        .collect())
+}
+
+/// Envoie la notification au webhook via [`safe_http`] : le nom d'hôte, puis chaque cible de
+/// redirection, ne doivent résoudre que vers des adresses publiques.
+async fn post_webhook(policy: &SafeHttpPolicy, url: &str, payload: &serde_json::Value) -> Result<(), String>
+{
+    let url = validation_service::validate_webhook_url(url).map_err(|e| format!("{e:?}"))?;
+
+    let response = safe_http::send(policy, Method::POST, url.as_str(), &HeaderMap::new(), Some(payload))
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success()
+    {
+        return Err(format!("unexpected status {}", response.status()));
+    }
+
+    Ok(())
+}
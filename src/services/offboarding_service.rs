@@ -0,0 +1,432 @@
+//! Vue d'ensemble de ce qui est rattaché à un utilisateur et départ en une opération.
+//!
+//! La vue renvoie un jeton de confirmation (JWT HS256, audience [`OFFBOARDING_AUDIENCE`]) lié
+//! au login et à l'administrateur qui l'a demandée. Le départ exécute les étapes dans l'ordre de
+//! [`OffboardingStep::ALL`] et s'arrête à la première erreur : le rapport indique alors les
+//! étapes restantes, qui peuvent être relancées avec un nouveau jeton.
+
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use tracing::{error, info, warn};
+
+use crate::
+{
+    error::AppError,
+    model::
+    {
+        api_key::ProjectApiKey,
+        offboarding::{FootprintDatabase, OffboardingConfirmation, OffboardingReport, OffboardingStep, ProjectDisposition, StepStatus, UserFootprint},
+        project::Project,
+        share_link::ProjectShareLink,
+    },
+    services::{audit_service, cleanup_service, database_service, notification_service, project_service},
+    state::AppState,
+};
+
+/// Audience des jetons de confirmation.
+const OFFBOARDING_AUDIENCE: &str = "hangar-offboarding";
+/// Durée de validité d'un jeton de confirmation.
+const CONFIRMATION_TTL: Duration = Duration::minutes(10);
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConfirmationClaims
+{
+    /// Login de l'utilisateur qui part.
+    pub sub: String,
+    /// Administrateur ayant consulté la vue d'ensemble.
+    pub admin: String,
+    pub exp: i64,
+    pub aud: String,
+}
+
+pub fn sign_confirmation(secret: &str, login: &str, admin: &str, expires_at: OffsetDateTime) -> Result<String, AppError>
+{
+    let claims = ConfirmationClaims
+    {
+        sub: login.to_string(),
+        admin: admin.to_string(),
+        exp: expires_at.unix_timestamp(),
+        aud: OFFBOARDING_AUDIENCE.to_string(),
+    };
+
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).map_err(|_| AppError::InternalServerError)
+}
+
+/// Vérifie que le jeton a été émis pour ce login et pour cet administrateur.
+pub fn verify_confirmation(secret: &str, token: &str, login: &str, admin: &str) -> Result<(), AppError>
+{
+    let mut validation = Validation::default();
+    validation.set_audience(&[OFFBOARDING_AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "aud", "sub"]);
+
+    let invalid = || AppError::BadRequest("Invalid or expired confirmation token. Fetch the user footprint again.".to_string());
+
+    let claims = decode::<ConfirmationClaims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        .map_err(|_| invalid())?
+        .claims;
+
+    if claims.sub != login || claims.admin != admin
+    {
+        return Err(invalid());
+    }
+
+    Ok(())
+}
+
+pub async fn get_footprint(state: &AppState, login: &str, admin: &str) -> Result<UserFootprint, AppError>
+{
+    let pool = &state.db_pool;
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to fetch footprint of user '{}': {}", login, e);
+        AppError::InternalServerError
+    };
+
+    let user: Option<(Option<String>, Option<String>, OffsetDateTime)> = sqlx::query_as(
+        "SELECT name, email, last_login_at FROM users WHERE login = $1"
+    )
+        .bind(login)
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?;
+
+    let owned_projects = project_service::get_projects_by_owner(pool, login).await?;
+    let participations = project_service::get_participating_projects(pool, login).await?;
+    let database = database_service::get_database_by_owner(pool, login).await?;
+
+    let api_keys: Vec<ProjectApiKey> = sqlx::query_as("SELECT * FROM project_api_keys WHERE created_by = $1 ORDER BY id")
+        .bind(login)
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+
+    let share_links: Vec<ProjectShareLink> = sqlx::query_as(
+        "SELECT * FROM project_share_links WHERE created_by = $1 AND revoked_at IS NULL AND expires_at > NOW() ORDER BY id"
+    )
+        .bind(login)
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+
+    if user.is_none() && owned_projects.is_empty() && participations.is_empty() && database.is_none() && api_keys.is_empty() && share_links.is_empty()
+    {
+        return Err(AppError::NotFound(format!("User '{login}' not found.")));
+    }
+
+    let notifications = notification_service::get_settings(pool, login).await?;
+
+    let expires_at = (OffsetDateTime::now_utc() + CONFIRMATION_TTL).replace_nanosecond(0).unwrap_or_else(|_| OffsetDateTime::now_utc());
+    let token = sign_confirmation(&state.config.jwt_secret, login, admin, expires_at)?;
+
+    let (name, email, last_login_at) = match user
+    {
+        Some((name, email, last_login_at)) => (name, email, Some(last_login_at)),
+        None => (None, None, None),
+    };
+
+    Ok(UserFootprint
+    {
+        login: login.to_string(),
+        name,
+        email,
+        last_login_at,
+        owned_projects,
+        participations,
+        database: database.map(|db| FootprintDatabase
+        {
+            id: db.id,
+            database_name: db.database_name,
+            project_id: db.project_id,
+            created_at: db.created_at,
+        }),
+        api_keys,
+        share_links,
+        notifications,
+        confirmation: OffboardingConfirmation { token, expires_at },
+    })
+}
+
+/// Vérifie le devenir des projets possédés avant toute modification.
+async fn validate_disposition(pool: &PgPool, login: &str, owned: &[Project], disposition: Option<&ProjectDisposition>) -> Result<(), AppError>
+{
+    let Some(project) = owned.first()
+    else
+    {
+        return Ok(());
+    };
+
+    match disposition
+    {
+        None => Err(AppError::BadRequest("This user owns a project: choose whether to transfer or archive it.".to_string())),
+        Some(ProjectDisposition::Archive) => Ok(()),
+        Some(ProjectDisposition::Transfer { new_owner }) =>
+        {
+            let new_owner = new_owner.trim();
+            if new_owner.is_empty() || new_owner == login
+            {
+                return Err(AppError::BadRequest("The new owner must be another user.".to_string()));
+            }
+            if owned.len() > 1
+            {
+                return Err(AppError::BadRequest("This user owns several projects: they can only be archived.".to_string()));
+            }
+            if project_service::check_owner_exists(pool, new_owner).await?
+            {
+                return Err(AppError::BadRequest(format!("User '{new_owner}' already owns a project.")));
+            }
+
+            let linked_database = database_service::get_database_by_project_id(pool, project.id).await?
+                .is_some_and(|db| db.owner_login == login);
+            if linked_database && database_service::check_database_exists_for_owner(pool, new_owner).await?
+            {
+                return Err(AppError::BadRequest(format!("User '{new_owner}' already owns a database and cannot take over the project's database.")));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Exécute le départ de `login` pour le compte de `admin`.
+///
+/// Les erreurs de validation sont renvoyées avant toute modification ; une fois les étapes
+/// commencées, le rapport est toujours renvoyé, complet ou non.
+pub async fn offboard(
+    state: &AppState,
+    login: &str,
+    admin: &str,
+    confirmation_token: &str,
+    disposition: Option<&ProjectDisposition>,
+) -> Result<OffboardingReport, AppError>
+{
+    verify_confirmation(&state.config.jwt_secret, confirmation_token, login, admin)?;
+
+    let owned = project_service::get_projects_by_owner(&state.db_pool, login).await?;
+    validate_disposition(&state.db_pool, login, &owned, disposition).await?;
+
+    info!("Admin '{}' started offboarding of user '{}'", admin, login);
+    let mut report = OffboardingReport::new(login);
+
+    for step in OffboardingStep::ALL
+    {
+        let project_id = (step == OffboardingStep::OwnedProject).then(|| owned.first().map(|p| p.id)).flatten();
+
+        match run_step(state, step, login, admin, &owned, disposition).await
+        {
+            Ok((status, detail)) =>
+            {
+                if status == StepStatus::Done
+                {
+                    record_step(state, admin, login, step, status, detail.as_deref(), project_id).await;
+                }
+                report.record(step, status, detail);
+            }
+            Err(e) =>
+            {
+                error!("Offboarding of user '{}' stopped at step {:?}: {}", login, step, e);
+                let detail = Some(e.to_string());
+                record_step(state, admin, login, step, StepStatus::Failed, detail.as_deref(), project_id).await;
+                report.record(step, StepStatus::Failed, detail);
+                break;
+            }
+        }
+    }
+
+    if report.completed
+    {
+        info!("User '{}' offboarded by admin '{}'", login, admin);
+    }
+    else
+    {
+        warn!("Offboarding of user '{}' is incomplete, remaining steps: {:?}", login, report.remaining());
+    }
+
+    audit_service::record(
+        state,
+        admin,
+        audit_service::ACTION_USER_OFFBOARDED,
+        None,
+        Some(json!({ "login": login, "completed": report.completed, "remaining": report.remaining() })),
+    ).await;
+
+    Ok(report)
+}
+
+async fn record_step(state: &AppState, admin: &str, login: &str, step: OffboardingStep, status: StepStatus, detail: Option<&str>, project_id: Option<i32>)
+{
+    audit_service::record(
+        state,
+        admin,
+        audit_service::ACTION_USER_OFFBOARDING_STEP,
+        project_id,
+        Some(json!({ "login": login, "step": step, "status": status, "detail": detail })),
+    ).await;
+}
+
+async fn run_step(
+    state: &AppState,
+    step: OffboardingStep,
+    login: &str,
+    admin: &str,
+    owned: &[Project],
+    disposition: Option<&ProjectDisposition>,
+) -> Result<(StepStatus, Option<String>), AppError>
+{
+    let pool = &state.db_pool;
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Offboarding step {:?} failed for user '{}': {}", step, login, e);
+        AppError::InternalServerError
+    };
+
+    let affected = |count: u64, what: &str|
+    {
+        if count == 0
+        {
+            (StepStatus::Skipped, None)
+        }
+        else
+        {
+            (StepStatus::Done, Some(format!("{count} {what}")))
+        }
+    };
+
+    match step
+    {
+        OffboardingStep::RevokeApiKeys =>
+        {
+            let result = sqlx::query("DELETE FROM project_api_keys WHERE created_by = $1")
+                .bind(login)
+                .execute(pool)
+                .await
+                .map_err(db_error)?;
+            Ok(affected(result.rows_affected(), "API key(s) revoked"))
+        }
+        OffboardingStep::RevokeShareLinks =>
+        {
+            let result = sqlx::query("UPDATE project_share_links SET revoked_at = NOW(), updated_at = NOW() WHERE created_by = $1 AND revoked_at IS NULL")
+                .bind(login)
+                .execute(pool)
+                .await
+                .map_err(db_error)?;
+            Ok(affected(result.rows_affected(), "share link(s) revoked"))
+        }
+        OffboardingStep::RemoveParticipations =>
+        {
+            let result = sqlx::query("DELETE FROM project_participants WHERE participant_id = $1")
+                .bind(login)
+                .execute(pool)
+                .await
+                .map_err(db_error)?;
+            sqlx::query("DELETE FROM project_invitations WHERE invitee = $1")
+                .bind(login)
+                .execute(pool)
+                .await
+                .map_err(db_error)?;
+            Ok(affected(result.rows_affected(), "participation(s) removed"))
+        }
+        OffboardingStep::OwnedProject =>
+        {
+            if owned.is_empty()
+            {
+                return Ok((StepStatus::Skipped, None));
+            }
+
+            match disposition
+            {
+                Some(ProjectDisposition::Transfer { new_owner }) =>
+                {
+                    let project = &owned[0];
+                    project_service::transfer_ownership(pool, project.id, login, new_owner.trim()).await?;
+                    Ok((StepStatus::Done, Some(format!("Project '{}' transferred to '{}'", project.name, new_owner.trim()))))
+                }
+                _ =>
+                {
+                    let mut archived = Vec::new();
+                    for project in owned
+                    {
+                        if cleanup_service::archive_now(state, project.id, admin, "Owner offboarded").await?
+                        {
+                            archived.push(project.name.as_str());
+                        }
+                    }
+
+                    if archived.is_empty()
+                    {
+                        Ok((StepStatus::Skipped, Some("Already archived".to_string())))
+                    }
+                    else
+                    {
+                        Ok((StepStatus::Done, Some(format!("Archived: {}", archived.join(", ")))))
+                    }
+                }
+            }
+        }
+        OffboardingStep::Database =>
+        {
+            // Une base transférée avec le projet n'appartient plus à l'utilisateur.
+            let Some(database) = database_service::get_database_by_owner(pool, login).await?
+            else
+            {
+                return Ok((StepStatus::Skipped, None));
+            };
+
+            database_service::deprovision_database(pool, &state.mariadb_pool, &database).await?;
+            Ok((StepStatus::Done, Some(format!("Database '{}' deprovisioned", database.database_name))))
+        }
+        OffboardingStep::NotificationSettings =>
+        {
+            let preferences = sqlx::query("DELETE FROM notification_preferences WHERE login = $1")
+                .bind(login)
+                .execute(pool)
+                .await
+                .map_err(db_error)?;
+            let webhooks = sqlx::query("DELETE FROM notification_webhooks WHERE login = $1")
+                .bind(login)
+                .execute(pool)
+                .await
+                .map_err(db_error)?;
+            Ok(affected(preferences.rows_affected() + webhooks.rows_affected(), "notification setting(s) deleted"))
+        }
+        OffboardingStep::DisconnectSse =>
+        {
+            state.sse_manager.disconnect_user(login);
+            state.sse_manager.cleanup_creation_channel(login).await;
+            Ok((StepStatus::Done, Some("Open SSE streams closed".to_string())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    #[test]
+    fn test_confirmation_is_bound_to_login_and_admin()
+    {
+        let token = sign_confirmation(SECRET, "alice", "admin", OffsetDateTime::now_utc() + CONFIRMATION_TTL).unwrap();
+
+        assert!(verify_confirmation(SECRET, &token, "alice", "admin").is_ok());
+        assert!(verify_confirmation(SECRET, &token, "bob", "admin").is_err());
+        assert!(verify_confirmation(SECRET, &token, "alice", "other-admin").is_err());
+        assert!(verify_confirmation("other-secret", &token, "alice", "admin").is_err());
+    }
+
+    #[test]
+    fn test_confirmation_expires_and_is_not_a_session()
+    {
+        let expired = sign_confirmation(SECRET, "alice", "admin", OffsetDateTime::now_utc() - Duration::hours(1)).unwrap();
+        assert!(verify_confirmation(SECRET, &expired, "alice", "admin").is_err());
+
+        let session = crate::services::jwt::generate_jwt(SECRET, 3600, "alice", "Name", "mail@example.com", true).unwrap();
+        assert!(verify_confirmation(SECRET, &session, "alice", "admin").is_err());
+
+        let token = sign_confirmation(SECRET, "alice", "admin", OffsetDateTime::now_utc() + CONFIRMATION_TTL).unwrap();
+        assert!(crate::services::jwt::validate_jwt(&token, SECRET).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Ressources Docker laissées derrière elles par des déploiements interrompus : conteneurs de la
+//! plateforme, volumes `hangar-data-*` et images `hangar-local/*` qui ne correspondent à aucun projet.
+//!
+//! Les images sont en plus ramassées périodiquement par [`start_image_gc`], avec les couches sans tag.
+
+use std::collections::HashSet;
+
+use serde_json::json;
+use time::{Duration, OffsetDateTime};
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    error::AppError,
+    model::{docker::{OrphanedResources, PlatformContainer, PlatformImage, PlatformVolume}, project::Project},
+    services::{deployment_service, docker_service, project_service},
+    sse::{emitter::emit_admin_event, types::SystemEvent},
+    state::AppState,
+};
+
+/// Âge minimal d'une ressource orpheline : un projet en cours de création n'a pas encore sa ligne
+/// dans `projects`, et ses ressources ne doivent pas être prises pour des restes.
+const ORPHAN_MIN_AGE: Duration = Duration::hours(1);
+
+/// Ressources encore référencées par un projet ou par un déploiement repris.
+#[derive(Default)]
+struct References
+{
+    project_ids: HashSet<i32>,
+    container_names: HashSet<String>,
+    volume_names: HashSet<String>,
+    images: HashSet<String>,
+}
+
+impl References
+{
+    fn from_projects(projects: &[Project]) -> Self
+    {
+        let mut references = Self::default();
+        for project in projects
+        {
+            references.project_ids.insert(project.id);
+            references.container_names.insert(project.container_name.clone());
+            if let Some(volume_name) = &project.volume_name
+            {
+                references.volume_names.insert(volume_name.clone());
+            }
+            references.images.insert(project.deployed_image_tag.clone());
+            references.images.insert(project.deployed_image_digest.clone());
+        }
+        references
+    }
+
+    fn owns_container(&self, container: &PlatformContainer) -> bool
+    {
+        match container.project_id
+        {
+            Some(project_id) => self.project_ids.contains(&project_id),
+            None => self.container_names.contains(&container.name),
+        }
+    }
+
+    fn owns_image(&self, image: &PlatformImage) -> bool
+    {
+        self.images.contains(&image.id) || image.tags.iter().any(|tag| self.images.contains(tag))
+    }
+}
+
+fn is_settled(created_at: Option<OffsetDateTime>, now: OffsetDateTime, min_age: Duration) -> bool
+{
+    created_at.is_some_and(|created_at| now - created_at >= min_age)
+}
+
+fn select_unreferenced_images(references: &References, images: Vec<PlatformImage>, now: OffsetDateTime, min_age: Duration) -> Vec<PlatformImage>
+{
+    images.into_iter()
+        .filter(|image| is_settled(image.created_at, now, min_age) && !references.owns_image(image))
+        .collect()
+}
+
+fn select_orphans(
+    references: &References,
+    containers: Vec<PlatformContainer>,
+    volumes: Vec<PlatformVolume>,
+    images: Vec<PlatformImage>,
+    now: OffsetDateTime,
+) -> OrphanedResources
+{
+    OrphanedResources
+    {
+        containers: containers.into_iter()
+            .filter(|container| is_settled(container.created_at, now, ORPHAN_MIN_AGE) && !references.owns_container(container))
+            .map(|container| container.name)
+            .collect(),
+        volumes: volumes.into_iter()
+            .filter(|volume| is_settled(volume.created_at, now, ORPHAN_MIN_AGE) && !references.volume_names.contains(&volume.name))
+            .map(|volume| volume.name)
+            .collect(),
+        images: select_unreferenced_images(references, images, now, ORPHAN_MIN_AGE),
+    }
+}
+
+async fn load_references(state: &AppState) -> Result<References, AppError>
+{
+    // `get_all_projects` exclut le projet du selftest, dont les ressources ne sont pas orphelines pour autant.
+    let mut projects = project_service::get_all_projects(&state.db_pool).await?;
+    projects.extend(project_service::get_projects_by_owner(&state.db_pool, project_service::SELFTEST_OWNER).await?);
+    let mut references = References::from_projects(&projects);
+    references.images.extend(project_service::list_previous_image_references(&state.db_pool).await?);
+    references.images.extend(deployment_service::list_retained_artifact_images(&state.db_pool).await?);
+    Ok(references)
+}
+
+/// Ressources de la plateforme sans projet correspondant, créées depuis plus d'une heure.
+pub async fn find_orphans(state: &AppState) -> Result<OrphanedResources, AppError>
+{
+    let references = load_references(state).await?;
+
+    let containers = docker_service::list_platform_containers(&state.docker_client, &state.config.app_prefix).await?;
+    let volumes = docker_service::list_project_volumes(&state.docker_client).await?;
+    let images = docker_service::list_local_images(&state.docker_client).await?;
+
+    Ok(select_orphans(&references, containers, volumes, images, OffsetDateTime::now_utc()))
+}
+
+/// Supprime les ressources orphelines ; renvoie celles supprimées et celles en échec.
+///
+/// Les conteneurs partent en premier : un volume ou une image encore utilisés ne peuvent pas être supprimés.
+pub async fn remove_orphans(state: &AppState, orphans: OrphanedResources) -> (OrphanedResources, OrphanedResources)
+{
+    let docker = &state.docker_client;
+    let mut removed = OrphanedResources::default();
+    let mut failed = OrphanedResources::default();
+
+    for container in orphans.containers
+    {
+        match docker_service::remove_container(docker, &container, docker_service::DEFAULT_STOP_GRACE_SECONDS).await
+        {
+            Ok(()) => removed.containers.push(container),
+            Err(_) => failed.containers.push(container),
+        }
+    }
+
+    for volume in orphans.volumes
+    {
+        match docker_service::remove_volume_by_name(docker, &volume).await
+        {
+            Ok(()) => removed.volumes.push(volume),
+            Err(_) => failed.volumes.push(volume),
+        }
+    }
+
+    for image in orphans.images
+    {
+        match docker_service::remove_image(docker, &image.id).await
+        {
+            Ok(()) => removed.images.push(image),
+            Err(_) => failed.images.push(image),
+        }
+    }
+
+    if failed.count() > 0
+    {
+        warn!("Failed to remove {} orphaned Docker resource(s)", failed.count());
+    }
+    info!("Removed {} orphaned Docker resource(s)", removed.count());
+
+    (removed, failed)
+}
+
+/// Tâche de fond : supprime les images `hangar-local/*` et les couches sans tag qu'aucun projet ne référence,
+/// plus anciennes que `image_gc_max_age_hours`. Rattrape les suppressions best-effort des déploiements.
+pub async fn start_image_gc(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting image garbage collection task");
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(state.config.image_gc_interval_secs));
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Image garbage collection task shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        if let Err(e) = collect_unreferenced_images(&state).await
+        {
+            error!("Image garbage collection failed: {:?}", e);
+        }
+    }
+}
+
+async fn collect_unreferenced_images(state: &AppState) -> Result<(), AppError>
+{
+    let references = load_references(state).await?;
+    let mut images = docker_service::list_local_images(&state.docker_client).await?;
+    images.extend(docker_service::list_dangling_images(&state.docker_client).await?);
+
+    let max_age = Duration::hours(state.config.image_gc_max_age_hours);
+    let unreferenced = select_unreferenced_images(&references, images, OffsetDateTime::now_utc(), max_age);
+    if unreferenced.is_empty()
+    {
+        return Ok(());
+    }
+
+    let (removed, failed) = remove_orphans(state, OrphanedResources { images: unreferenced, ..Default::default() }).await;
+
+    let message = format!("Image garbage collection removed {} image(s), {} failed.", removed.count(), failed.count());
+    let event = if failed.count() > 0 { SystemEvent::warning(message) } else { SystemEvent::info(message) };
+    emit_admin_event(state, event.with_context(json!({ "removed": removed.images, "failed": failed.images })));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container(name: &str, project_id: Option<i32>, created_at: OffsetDateTime) -> PlatformContainer
+    {
+        PlatformContainer { name: name.to_string(), project_id, created_at: Some(created_at) }
+    }
+
+    #[test]
+    fn test_select_orphans()
+    {
+        let now = OffsetDateTime::UNIX_EPOCH + Duration::days(10);
+        let old = now - Duration::days(1);
+        let recent = now - Duration::minutes(5);
+
+        let references = References
+        {
+            project_ids: HashSet::from([1]),
+            container_names: HashSet::from(["hangar-legacy".to_string()]),
+            volume_names: HashSet::from(["hangar-data-app".to_string()]),
+            images: HashSet::from(["hangar-local/app:1".to_string(), "sha256:previous".to_string()]),
+        };
+
+        let containers = vec![
+            container("hangar-app-1", Some(1), old),
+            container("hangar-legacy", None, old),
+            container("hangar-gone-1", Some(2), old),
+            container("hangar-creating-1", Some(3), recent),
+        ];
+        let volumes = vec![
+            PlatformVolume { name: "hangar-data-app".to_string(), created_at: Some(old) },
+            PlatformVolume { name: "hangar-data-gone".to_string(), created_at: Some(old) },
+            PlatformVolume { name: "hangar-data-unknown".to_string(), created_at: None },
+        ];
+        let image = |id: &str, tag: &str| PlatformImage { id: id.to_string(), tags: vec![tag.to_string()], created_at: Some(old) };
+        let images = vec![
+            image("sha256:current", "hangar-local/app:1"),
+            image("sha256:previous", "hangar-local/app:0"),
+            image("sha256:gone", "hangar-local/gone:1"),
+        ];
+
+        let orphans = select_orphans(&references, containers, volumes, images, now);
+
+        assert_eq!(orphans.containers, vec!["hangar-gone-1".to_string()]);
+        assert_eq!(orphans.volumes, vec!["hangar-data-gone".to_string()]);
+        assert_eq!(orphans.images.iter().map(|image| image.id.as_str()).collect::<Vec<_>>(), vec!["sha256:gone"]);
+    }
+
+    #[test]
+    fn test_image_gc_keeps_referenced_and_recent_images()
+    {
+        let now = OffsetDateTime::UNIX_EPOCH + Duration::days(10);
+        let references = References { images: HashSet::from(["sha256:deployed".to_string()]), ..Default::default() };
+        let image = |id: &str, age: Duration| PlatformImage { id: id.to_string(), tags: Vec::new(), created_at: Some(now - age) };
+
+        let images = vec![
+            image("sha256:deployed", Duration::days(3)),
+            image("sha256:dangling", Duration::days(2)),
+            image("sha256:fresh", Duration::hours(2)),
+        ];
+
+        let unreferenced = select_unreferenced_images(&references, images, now, Duration::hours(24));
+
+        assert_eq!(unreferenced.iter().map(|image| image.id.as_str()).collect::<Vec<_>>(), vec!["sha256:dangling"]);
+    }
+}
@@ -0,0 +1,342 @@
+//! Archive portable d'un projet complet, pour l'exporter puis le recréer sur une instance.
+//!
+//! L'archive est un tar gzip contenant `manifest.json` (version du format et empreinte SHA-256
+//! de chaque fichier), `project.json` (métadonnées du projet), et selon le projet `volume.tar`
+//! (contenu du volume persistant) et `database.sql` (dump de la base liée). L'intégrité de
+//! chaque fichier est vérifiée avant toute restauration.
+
+use std::{collections::{BTreeMap, HashMap}, io::Read};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+
+use crate::
+{
+    error::{AppError, ProjectErrorCode},
+    model::project::{ContainerCommand, Project, ProjectSourceType},
+};
+
+/// Version du format, incrémentée à chaque changement incompatible.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const METADATA_FILE: &str = "project.json";
+const VOLUME_FILE: &str = "volume.tar";
+const DATABASE_FILE: &str = "database.sql";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveManifest
+{
+    format_version: u32,
+    project_name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    /// Empreinte SHA-256 (hexadécimale) de chaque fichier de l'archive, hors manifeste.
+    files: BTreeMap<String, String>,
+}
+
+/// Configuration du projet, suffisante pour le redéployer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProjectArchiveMetadata
+{
+    pub name: String,
+    pub owner: String,
+    pub source: ProjectSourceType,
+    pub source_url: String,
+    pub source_branch: Option<String>,
+    pub source_root_dir: Option<String>,
+    pub use_repo_dockerfile: bool,
+    pub source_ref: Option<String>,
+    pub source_commit_sha: Option<String>,
+    /// Image déployée épinglée par son digest (`image@sha256:…`), pour réimporter exactement la même.
+    #[serde(default)]
+    pub image_repo_digest: Option<String>,
+    /// Valeurs en clair, présentes uniquement si l'owner a demandé l'export des secrets.
+    pub env_vars: Option<HashMap<String, String>>,
+    /// Variables exportées sans leur valeur, à redéfinir après l'import.
+    #[serde(default)]
+    pub redacted_env_vars: Vec<String>,
+    pub persistent_volume_path: Option<String>,
+    #[serde(flatten)]
+    pub command: ContainerCommand,
+    pub timezone: Option<String>,
+    pub container_port: u16,
+    #[serde(default)]
+    pub scan_ignore_cves: Vec<String>,
+    #[serde(default)]
+    pub participants: Vec<String>,
+    #[serde(default)]
+    pub inject_database_env: bool,
+}
+
+impl ProjectArchiveMetadata
+{
+    /// `env_vars` : variables déchiffrées, `None` pour n'exporter que leurs noms.
+    #[must_use]
+    pub fn from_project(
+        project: &Project,
+        participants: Vec<String>,
+        env_vars: Option<HashMap<String, String>>,
+        image_repo_digest: Option<String>,
+    ) -> Self
+    {
+        let redacted_env_vars = match (&env_vars, &project.env_vars)
+        {
+            (None, Some(serde_json::Value::Object(vars))) =>
+            {
+                let mut names: Vec<String> = vars.keys().cloned().collect();
+                names.sort();
+                names
+            }
+            _ => Vec::new(),
+        };
+
+        Self
+        {
+            name: project.name.clone(),
+            owner: project.owner.clone(),
+            source: project.source,
+            source_url: project.source_url.clone(),
+            source_branch: project.source_branch.clone(),
+            source_root_dir: project.source_root_dir.clone(),
+            use_repo_dockerfile: project.use_repo_dockerfile,
+            source_ref: project.source_ref.clone(),
+            source_commit_sha: project.source_commit_sha.clone(),
+            image_repo_digest,
+            env_vars,
+            redacted_env_vars,
+            persistent_volume_path: project.persistent_volume_path.clone(),
+            command: project.container_command(),
+            timezone: project.timezone.clone(),
+            container_port: project.http_port(),
+            scan_ignore_cves: project.scan_ignore_cves.clone(),
+            participants,
+            inject_database_env: project.inject_database_env,
+        }
+    }
+}
+
+/// Contenu d'une archive de projet.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProjectArchive
+{
+    pub metadata: ProjectArchiveMetadata,
+    /// Archive tar du répertoire `persistent_volume_path`, racine comprise.
+    pub volume: Option<Vec<u8>>,
+    /// Dump SQL non compressé de la base liée.
+    pub database: Option<Vec<u8>>,
+}
+
+impl ProjectArchive
+{
+    /// Construit l'archive tar gzip, manifeste en tête.
+    pub fn pack(&self) -> Result<Vec<u8>, AppError>
+    {
+        let metadata = serde_json::to_vec_pretty(&self.metadata).map_err(|_| AppError::InternalServerError)?;
+
+        let mut files: Vec<(&str, &[u8])> = vec![(METADATA_FILE, metadata.as_slice())];
+        if let Some(volume) = &self.volume
+        {
+            files.push((VOLUME_FILE, volume.as_slice()));
+        }
+        if let Some(database) = &self.database
+        {
+            files.push((DATABASE_FILE, database.as_slice()));
+        }
+
+        let manifest = ArchiveManifest
+        {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            project_name: self.metadata.name.clone(),
+            created_at: OffsetDateTime::now_utc(),
+            files: files.iter().map(|(name, content)| ((*name).to_string(), sha256_hex(content))).collect(),
+        };
+        let manifest = serde_json::to_vec_pretty(&manifest).map_err(|_| AppError::InternalServerError)?;
+
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, content) in std::iter::once((MANIFEST_FILE, manifest.as_slice())).chain(files)
+        {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o600);
+            header.set_mtime(u64::try_from(OffsetDateTime::now_utc().unix_timestamp()).unwrap_or_default());
+            header.set_cksum();
+            builder.append_data(&mut header, name, content).map_err(|_| AppError::InternalServerError)?;
+        }
+
+        builder
+            .into_inner()
+            .and_then(GzEncoder::finish)
+            .map_err(|_| AppError::InternalServerError)
+    }
+
+    /// Lit et vérifie une archive : fichiers attendus uniquement, empreintes conformes au manifeste,
+    /// et au plus `max_unpacked_bytes` une fois décompressée.
+    pub fn unpack(bytes: &[u8], max_unpacked_bytes: u64) -> Result<Self, AppError>
+    {
+        let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+        let mut contents: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut unpacked: u64 = 0;
+
+        let entries = archive.entries().map_err(|_| invalid("not a gzip-compressed tar archive"))?;
+        for entry in entries
+        {
+            let mut entry = entry.map_err(|_| invalid("the archive is corrupted"))?;
+            let name = entry.path().map_err(|_| invalid("the archive is corrupted"))?.to_string_lossy().into_owned();
+            if ![MANIFEST_FILE, METADATA_FILE, VOLUME_FILE, DATABASE_FILE].contains(&name.as_str())
+            {
+                return Err(invalid(&format!("unexpected file '{name}'")));
+            }
+
+            unpacked = unpacked.saturating_add(entry.header().size().unwrap_or(u64::MAX));
+            if unpacked > max_unpacked_bytes
+            {
+                return Err(invalid("the archive is too large once decompressed"));
+            }
+
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).map_err(|_| invalid("the archive is corrupted"))?;
+            if contents.insert(name.clone(), content).is_some()
+            {
+                return Err(invalid(&format!("duplicate file '{name}'")));
+            }
+        }
+
+        let manifest: ArchiveManifest = contents.remove(MANIFEST_FILE)
+            .ok_or_else(|| invalid("the manifest is missing"))
+            .and_then(|manifest| serde_json::from_slice(&manifest).map_err(|_| invalid("the manifest is malformed")))?;
+        if manifest.format_version != ARCHIVE_FORMAT_VERSION
+        {
+            return Err(invalid(&format!("unsupported format version {}", manifest.format_version)));
+        }
+
+        if contents.len() != manifest.files.len()
+        {
+            return Err(invalid("the files do not match the manifest"));
+        }
+        for (name, expected) in &manifest.files
+        {
+            let content = contents.get(name).ok_or_else(|| invalid(&format!("'{name}' is listed in the manifest but missing")))?;
+            if sha256_hex(content) != *expected
+            {
+                return Err(invalid(&format!("checksum mismatch for '{name}'")));
+            }
+        }
+
+        let metadata: ProjectArchiveMetadata = contents.remove(METADATA_FILE)
+            .ok_or_else(|| invalid("the project metadata is missing"))
+            .and_then(|metadata| serde_json::from_slice(&metadata).map_err(|_| invalid("the project metadata is malformed")))?;
+
+        Ok(Self
+        {
+            metadata,
+            volume: contents.remove(VOLUME_FILE),
+            database: contents.remove(DATABASE_FILE),
+        })
+    }
+}
+
+/// Nom de fichier proposé au téléchargement.
+#[must_use]
+pub fn archive_file_name(project_name: &str, now: OffsetDateTime) -> String
+{
+    format!("{project_name}-{}.hangar.tar.gz", now.unix_timestamp())
+}
+
+fn sha256_hex(content: &[u8]) -> String
+{
+    Sha256::digest(content).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn invalid(reason: &str) -> AppError
+{
+    ProjectErrorCode::InvalidProjectArchive(reason.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Réécrit une archive tar en y remplaçant une entrée, pour les tests d'intégrité.
+    fn rewrite_entry(archive: &[u8], target: &str, content: &[u8]) -> Vec<u8>
+    {
+        let mut reader = tar::Archive::new(GzDecoder::new(archive));
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for entry in reader.entries().unwrap()
+        {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut original = Vec::new();
+            entry.read_to_end(&mut original).unwrap();
+            let data = if name == target { content.to_vec() } else { original };
+
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o600);
+            header.set_cksum();
+            builder.append_data(&mut header, name, data.as_slice()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn archive() -> ProjectArchive
+    {
+        ProjectArchive
+        {
+            metadata: ProjectArchiveMetadata
+            {
+                name: "blog".to_string(),
+                owner: "alice".to_string(),
+                source: ProjectSourceType::Github,
+                source_url: "https://github.com/alice/blog".to_string(),
+                source_branch: Some("main".to_string()),
+                source_root_dir: None,
+                use_repo_dockerfile: false,
+                source_ref: None,
+                source_commit_sha: Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+                image_repo_digest: None,
+                env_vars: None,
+                redacted_env_vars: vec!["API_TOKEN".to_string()],
+                persistent_volume_path: Some("/data".to_string()),
+                command: ContainerCommand::default(),
+                timezone: None,
+                container_port: 80,
+                scan_ignore_cves: Vec::new(),
+                participants: vec!["bob".to_string()],
+                inject_database_env: false,
+            },
+            volume: Some(b"volume".to_vec()),
+            database: Some(b"CREATE TABLE posts (id INT);".to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_archive_round_trip()
+    {
+        let original = archive();
+        let packed = original.pack().unwrap();
+
+        assert_eq!(ProjectArchive::unpack(&packed, u64::MAX).unwrap(), original);
+    }
+
+    #[test]
+    fn test_tampered_archive_is_rejected()
+    {
+        let packed = archive().pack().unwrap();
+        let tampered = rewrite_entry(&packed, DATABASE_FILE, b"DROP TABLE posts;");
+
+        let error = ProjectArchive::unpack(&tampered, u64::MAX).unwrap_err();
+        assert!(matches!(error, AppError::ProjectError(ProjectErrorCode::InvalidProjectArchive(reason)) if reason.contains("database.sql")));
+    }
+
+    #[test]
+    fn test_oversized_archive_is_rejected()
+    {
+        let packed = archive().pack().unwrap();
+
+        assert!(ProjectArchive::unpack(&packed, 16).is_err());
+        assert!(ProjectArchive::unpack(b"not an archive", u64::MAX).is_err());
+    }
+}
@@ -1,9 +1,17 @@
 use std::collections::HashMap;
-use sqlx::{PgPool, Postgres, Transaction};
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
 use tracing::{error, warn};
-use crate::{error::{AppError, ProjectErrorCode}, model::project::{Project, ProjectSourceType}, services::crypto_service};
+use crate::{error::{AppError, ProjectErrorCode}, model::{project::{ContainerCommand, DownProjectInfo, PreviousImage, Project, ProjectSourceType}, user::UserDisplay}, services::{crypto_service, database_service, docker_service::{self, RoutingOwners}}, state::AppState};
+use time::{OffsetDateTime, UtcOffset, format_description::well_known::Rfc3339};
+#[cfg(feature = "object_storage")]
+use crate::services::object_storage_service;
 use base64::prelude::*;
 
+/// Propriétaire réservé au selftest d'administration, exclu des listes, statistiques et rapports.
+pub const SELFTEST_OWNER: &str = "hangar-selftest";
+/// Nom de projet réservé au selftest d'administration.
+pub const SELFTEST_PROJECT_NAME: &str = "hangar-selftest";
+
 pub async fn check_project_name_exists(pool: &PgPool, name: &str) -> Result<bool, AppError> 
 {
     let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM projects WHERE name = $1")
@@ -24,8 +32,22 @@ pub async fn check_owner_exists(pool: &PgPool, owner: &str) -> Result<bool, AppE
     Ok(count.0 > 0)
 }
 
+/// Réserve l'identifiant du futur projet, afin de l'apposer sur le conteneur avant l'insertion.
+pub async fn reserve_project_id(pool: &PgPool) -> Result<i32, AppError>
+{
+    sqlx::query_scalar("SELECT nextval(pg_get_serial_sequence('projects', 'id'))::INTEGER")
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to reserve a project ID: {}", e);
+            AppError::InternalServerError
+        })
+}
+
 pub async fn create_project<'a>(
     tx: &mut Transaction<'a, Postgres>,
+    project_id: i32,
     name: &str,
     owner: &str,
     container_name: &str,
@@ -33,11 +55,19 @@ pub async fn create_project<'a>(
     source_url: &str,
     source_branch: &Option<String>,
     source_root_dir: &Option<String>,
+    use_repo_dockerfile: bool,
     deployed_image_tag: &str,
     deployed_image_digest: &str,
     env_vars: &Option<HashMap<String, String>>,
     persistent_volume_path: &Option<String>,
     volume_name: &Option<String>,
+    command: &ContainerCommand,
+    timezone: &Option<String>,
+    container_port: u16,
+    scan_ignore_cves: &[String],
+    source_commit_sha: Option<&str>,
+    source_ref: Option<&str>,
+    inject_database_env: bool,
     encryption_key: &[u8]
 ) -> Result<Project, AppError> 
 {
@@ -51,10 +81,11 @@ pub async fn create_project<'a>(
         .map_err(|_| AppError::InternalServerError)?;
 
     let project = sqlx::query_as::<_, Project>(
-        "INSERT INTO projects (name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, env_vars, persistent_volume_path, volume_name)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-         RETURNING id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, created_at, env_vars, persistent_volume_path, volume_name",
+        "INSERT INTO projects (id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, use_repo_dockerfile, deployed_image_tag, deployed_image_digest, env_vars, persistent_volume_path, volume_name, entrypoint, command, timezone, container_port, scan_ignore_cves, source_commit_sha, source_ref, inject_database_env)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22)
+         RETURNING id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, use_repo_dockerfile, deployed_image_tag, deployed_image_digest, created_at, env_vars, persistent_volume_path, volume_name, stop_grace_seconds, entrypoint, command, timezone, container_port, scan_ignore_cves, source_commit_sha, source_ref, resource_profile, archived, keep_alive, inject_database_env, health_check_max_attempts, health_check_interval_ms",
     )
+    .bind(project_id)
     .bind(name)
     .bind(owner)
     .bind(container_name)
@@ -62,11 +93,20 @@ pub async fn create_project<'a>(
     .bind(source_url)
     .bind(source_branch)
     .bind(source_root_dir)
+    .bind(use_repo_dockerfile)
     .bind(deployed_image_tag)
     .bind(deployed_image_digest)
     .bind(env_vars_json)
     .bind(persistent_volume_path)
     .bind(volume_name)
+    .bind(&command.entrypoint)
+    .bind(&command.command)
+    .bind(timezone)
+    .bind(i32::from(container_port))
+    .bind(scan_ignore_cves)
+    .bind(source_commit_sha)
+    .bind(source_ref)
+    .bind(inject_database_env)
     .fetch_one(&mut **tx)
     .await
     .map_err(|e: sqlx::Error| 
@@ -103,7 +143,7 @@ pub async fn delete_project_by_id(pool: &PgPool, project_id: i32) -> Result<(),
     Ok(())
 }
 
-const SELECT_PROJECT_FIELDS: &str = "SELECT id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, created_at, env_vars, persistent_volume_path, volume_name FROM projects";
+const SELECT_PROJECT_FIELDS: &str = "SELECT id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, use_repo_dockerfile, deployed_image_tag, deployed_image_digest, created_at, env_vars, persistent_volume_path, volume_name, stop_grace_seconds, entrypoint, command, timezone, container_port, scan_ignore_cves, source_commit_sha, source_ref, resource_profile, archived, keep_alive, inject_database_env, health_check_max_attempts, health_check_interval_ms FROM projects";
 
 pub async fn get_projects_by_owner(pool: &PgPool, owner: &str) -> Result<Vec<Project>, AppError> 
 {
@@ -119,44 +159,39 @@ pub async fn get_projects_by_owner(pool: &PgPool, owner: &str) -> Result<Vec<Pro
         })
 }
 
-pub async fn get_project_by_id_and_owner(
-    pool: &PgPool,
-    project_id: i32,
-    owner: &str,
-    is_admin: bool,
-) -> Result<Option<Project>, AppError> 
+pub async fn get_project_by_id(pool: &PgPool, project_id: i32) -> Result<Option<Project>, AppError> 
 {
-    if is_admin 
-    {
-        let query = format!("{SELECT_PROJECT_FIELDS} WHERE id = $1");
-        return sqlx::query_as::<_, Project>(&query)
-            .bind(project_id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|e| 
-            {
-                error!("Admin failed to fetch project by id {}: {}", project_id, e);
-                AppError::InternalServerError
-            });
-    }
-
-    let query = format!("{SELECT_PROJECT_FIELDS} WHERE id = $1 AND owner = $2");
+    let query = format!("{SELECT_PROJECT_FIELDS} WHERE id = $1");
     sqlx::query_as::<_, Project>(&query)
         .bind(project_id)
-        .bind(owner)
         .fetch_optional(pool)
         .await
         .map_err(|e| 
         {
-            error!("Failed to fetch project by id {} and owner '{}': {}", project_id, owner, e);
+            error!("Failed to fetch project by id {}: {}", project_id, e);
             AppError::InternalServerError
         })
 }
 
+pub async fn is_project_participant(pool: &PgPool, project_id: i32, participant_id: &str) -> Result<bool, AppError> 
+{
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM project_participants WHERE project_id = $1 AND participant_id = $2")
+        .bind(project_id)
+        .bind(participant_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| 
+        {
+            error!("Failed to check participation of '{}' in project {}: {}", participant_id, project_id, e);
+            AppError::InternalServerError
+        })?;
+    Ok(count.0 > 0)
+}
+
 pub async fn get_participating_projects(pool: &PgPool, participant_id: &str) -> Result<Vec<Project>, AppError> 
 {
     sqlx::query_as::<_, Project>(
-        "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.env_vars, p.persistent_volume_path, p.volume_name
+        "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.use_repo_dockerfile, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.env_vars, p.persistent_volume_path, p.volume_name, p.stop_grace_seconds, p.entrypoint, p.command, p.timezone, p.container_port, p.scan_ignore_cves, p.source_commit_sha, p.source_ref, p.resource_profile, p.archived, p.keep_alive, p.inject_database_env, p.health_check_max_attempts, p.health_check_interval_ms
          FROM projects p
          JOIN project_participants pp ON p.id = pp.project_id
          WHERE pp.participant_id = $1
@@ -172,60 +207,138 @@ pub async fn get_participating_projects(pool: &PgPool, participant_id: &str) ->
         })
 }
 
-pub async fn get_project_by_id_for_user(
-    pool: &PgPool,
-    project_id: i32,
-    user_login: &str,
-    is_admin: bool,
-) -> Result<Option<Project>, AppError> 
+pub async fn get_project_participants(pool: &PgPool, project_id: i32) -> Result<Vec<UserDisplay>, AppError> 
 {
-    if is_admin 
-    {
-        return sqlx::query_as::<_, Project>(&format!("{SELECT_PROJECT_FIELDS} WHERE id = $1"))
-            .bind(project_id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|e| 
-            {
-                error!("Failed to fetch project {} for admin '{}': {}", project_id, user_login, e);
-                AppError::InternalServerError
-            });
-    }
+    get_project_participants_page(pool, project_id, i64::MAX, 0).await
+}
 
-    sqlx::query_as::<_, Project>(
-        "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.env_vars, p.persistent_volume_path, p.volume_name
-         FROM projects p
-         LEFT JOIN project_participants pp ON p.id = pp.project_id
-         WHERE p.id = $1 AND (p.owner = $2 OR pp.participant_id = $2)"
+/// Participants d'un projet par ordre de login, `limit` au plus à partir de `offset`.
+pub async fn get_project_participants_page(pool: &PgPool, project_id: i32, limit: i64, offset: i64) -> Result<Vec<UserDisplay>, AppError>
+{
+    sqlx::query_as(
+        "SELECT pp.participant_id AS login, COALESCE(NULLIF(u.name, ''), pp.participant_id) AS name \
+         FROM project_participants pp \
+         LEFT JOIN users u ON u.login = pp.participant_id \
+         WHERE pp.project_id = $1 \
+         ORDER BY pp.participant_id \
+         LIMIT $2 OFFSET $3"
     )
         .bind(project_id)
-        .bind(user_login)
-        .fetch_optional(pool)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
         .await
         .map_err(|e| 
         {
-            error!("Failed to fetch project {} for user '{}': {}", project_id, user_login, e);
+            error!("Failed to fetch participants for project {}: {}", project_id, e);
             AppError::InternalServerError
         })
 }
 
-pub async fn get_project_participants(pool: &PgPool, project_id: i32) -> Result<Vec<String>, AppError> 
+pub async fn count_project_participants(pool: &PgPool, project_id: i32) -> Result<i64, AppError>
+{
+    sqlx::query_scalar("SELECT COUNT(*) FROM project_participants WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count participants of project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Verrouille le projet puis vérifie que l'ajout de `participants` ne porte pas leur nombre au-delà de `max_participants`.
+/// Un login déjà participant n'est pas compté deux fois.
+pub async fn ensure_participant_capacity(
+    tx: &mut Transaction<'_, Postgres>,
+    project_id: i32,
+    participants: &[String],
+    max_participants: i64,
+) -> Result<(), AppError>
 {
-    sqlx::query_scalar("SELECT participant_id FROM project_participants WHERE project_id = $1")
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to check participant capacity of project {}: {}", project_id, e);
+        AppError::InternalServerError
+    };
+
+    // Verrouille le projet pour que deux ajouts simultanés ne dépassent pas la limite.
+    sqlx::query("SELECT id FROM projects WHERE id = $1 FOR UPDATE")
+        .bind(project_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+    let others: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM project_participants WHERE project_id = $1 AND participant_id <> ALL($2)"
+    )
         .bind(project_id)
+        .bind(participants)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(db_error)?;
+
+    if others.saturating_add(i64::try_from(participants.len()).unwrap_or(i64::MAX)) > max_participants
+    {
+        return Err(ProjectErrorCode::ParticipantLimitReached(max_participants).into());
+    }
+
+    Ok(())
+}
+
+/// Projets possédés par `login` ou auxquels il participe, en une requête.
+pub async fn get_member_projects(pool: &PgPool, login: &str) -> Result<Vec<Project>, AppError>
+{
+    let query = format!(
+        "{SELECT_PROJECT_FIELDS} WHERE owner = $1 \
+         OR id IN (SELECT project_id FROM project_participants WHERE participant_id = $1) \
+         ORDER BY created_at DESC"
+    );
+    sqlx::query_as::<_, Project>(&query)
+        .bind(login)
         .fetch_all(pool)
         .await
-        .map_err(|e| 
+        .map_err(|e|
         {
-            error!("Failed to fetch participants for project {}: {}", project_id, e);
+            error!("Failed to fetch projects of member '{}': {}", login, e);
             AppError::InternalServerError
         })
 }
 
+/// Participants de plusieurs projets en une requête, indexés par projet.
+pub async fn get_participants_by_project(pool: &PgPool, project_ids: &[i32]) -> Result<HashMap<i32, Vec<UserDisplay>>, AppError>
+{
+    let rows: Vec<(i32, String, String)> = sqlx::query_as(
+        "SELECT pp.project_id, pp.participant_id, COALESCE(NULLIF(u.name, ''), pp.participant_id) \
+         FROM project_participants pp \
+         LEFT JOIN users u ON u.login = pp.participant_id \
+         WHERE pp.project_id = ANY($1) \
+         ORDER BY pp.project_id, pp.participant_id"
+    )
+        .bind(project_ids)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch participants of {} project(s): {}", project_ids.len(), e);
+            AppError::InternalServerError
+        })?;
+
+    let mut participants: HashMap<i32, Vec<UserDisplay>> = HashMap::new();
+    for (project_id, login, name) in rows
+    {
+        participants.entry(project_id).or_default().push(UserDisplay { login, name });
+    }
+    Ok(participants)
+}
+
+/// Tous les projets, hors projet du selftest d'administration.
 pub async fn get_all_projects(pool: &PgPool) -> Result<Vec<Project>, AppError> 
 {
-    let query = format!("{SELECT_PROJECT_FIELDS} ORDER BY created_at DESC");
+    let query = format!("{SELECT_PROJECT_FIELDS} WHERE owner <> $1 ORDER BY created_at DESC");
     sqlx::query_as::<_, Project>(&query)
+        .bind(SELFTEST_OWNER)
         .fetch_all(pool)
         .await
         .map_err(|e| 
@@ -240,6 +353,7 @@ pub async fn add_project_participants<'a>(
     tx: &mut Transaction<'a, Postgres>,
     project_id: i32,
     participants: &[String],
+    max_participants: i64,
 ) -> Result<(), AppError> 
 {
     if participants.is_empty() 
@@ -247,6 +361,8 @@ pub async fn add_project_participants<'a>(
         return Ok(());
     }
 
+    ensure_participant_capacity(tx, project_id, participants, max_participants).await?;
+
     let mut query_builder = sqlx::QueryBuilder::new(
         "INSERT INTO project_participants (project_id, participant_id) "
     );
@@ -273,21 +389,29 @@ pub async fn add_participant_to_project(
     pool: &PgPool,
     project_id: i32,
     participant_id: &str,
+    max_participants: i64,
 ) -> Result<(), AppError> 
 {
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to add participant '{}' to project {}: {}", participant_id, project_id, e);
+        AppError::InternalServerError
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    ensure_participant_capacity(&mut tx, project_id, &[participant_id.to_string()], max_participants).await?;
+
     sqlx::query(
         "INSERT INTO project_participants (project_id, participant_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
     )
     .bind(project_id)
     .bind(participant_id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await
-    .map_err(|e| 
-    {
-        error!("Failed to add participant '{}' to project {}: {}", participant_id, project_id, e);
-        AppError::InternalServerError
-    })?;
-    Ok(())
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)
 }
 
 pub async fn remove_participant_from_project(
@@ -317,6 +441,51 @@ pub async fn remove_participant_from_project(
     Ok(())
 }
 
+/// Transfère un projet à `new_owner`, avec la base de données qui lui est liée.
+///
+/// Le nouveau propriétaire perd son éventuel statut de participant. L'appelant vérifie
+/// au préalable que `new_owner` ne possède ni projet ni base de données.
+pub async fn transfer_ownership(pool: &PgPool, project_id: i32, old_owner: &str, new_owner: &str) -> Result<(), AppError>
+{
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to transfer project {} from '{}' to '{}': {}", project_id, old_owner, new_owner, e);
+        AppError::InternalServerError
+    };
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    let result = sqlx::query("UPDATE projects SET owner = $1 WHERE id = $2 AND owner = $3")
+        .bind(new_owner)
+        .bind(project_id)
+        .bind(old_owner)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    if result.rows_affected() == 0
+    {
+        return Err(AppError::NotFound(format!("Project with ID {project_id} is not owned by '{old_owner}'.")));
+    }
+
+    sqlx::query("DELETE FROM project_participants WHERE project_id = $1 AND participant_id = $2")
+        .bind(project_id)
+        .bind(new_owner)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    sqlx::query("UPDATE databases SET owner_login = $1 WHERE project_id = $2 AND owner_login = $3")
+        .bind(new_owner)
+        .bind(project_id)
+        .bind(old_owner)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)
+}
+
 fn encrypt_env_vars(
     env_vars: &HashMap<String, String>,
     key: &[u8],
@@ -332,7 +501,7 @@ fn encrypt_env_vars(
 }
 
 pub async fn update_project_env_vars(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     project_id: i32,
     env_vars: &HashMap<String, String>,
     encryption_key: &[u8],
@@ -344,7 +513,7 @@ pub async fn update_project_env_vars(
     sqlx::query("UPDATE projects SET env_vars = $1 WHERE id = $2")
         .bind(env_vars_json)
         .bind(project_id)
-        .execute(pool)
+        .execute(executor)
         .await
         .map_err(|e|
         {
@@ -355,7 +524,7 @@ pub async fn update_project_env_vars(
 }
 
 pub async fn update_project_container_name(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     project_id: i32,
     new_container_name: &str,
 ) -> Result<(), AppError>
@@ -363,7 +532,7 @@ pub async fn update_project_container_name(
     sqlx::query("UPDATE projects SET container_name = $1 WHERE id = $2")
         .bind(new_container_name)
         .bind(project_id)
-        .execute(pool)
+        .execute(executor)
         .await
         .map_err(|e|
         {
@@ -373,46 +542,169 @@ pub async fn update_project_container_name(
     Ok(())
 }
 
-pub async fn update_project_image_and_digest(
-    pool: &PgPool,
+/// Remplace l'image déployée et le commit dont elle est issue, en une seule requête ; l'ancienne
+/// image devient la cible d'un retour arrière. `source_url` n'est modifiée que si elle est fournie.
+pub async fn update_project_image(
+    executor: impl PgExecutor<'_>,
     project_id: i32,
     new_image_tag: &str,
     new_image_digest: &str,
-) -> Result<(), AppError> 
+    commit_sha: Option<&str>,
+    source_url: Option<&str>,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "UPDATE projects SET previous_image_tag = deployed_image_tag, previous_image_digest = deployed_image_digest, \
+         deployed_image_tag = $1, deployed_image_digest = $2, source_commit_sha = $3, source_url = COALESCE($4, source_url) \
+         WHERE id = $5"
+    )
+    .bind(new_image_tag)
+    .bind(new_image_digest)
+    .bind(commit_sha)
+    .bind(source_url)
+    .bind(project_id)
+    .execute(executor)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to update project {} with new image and digest: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+    Ok(())
+}
+
+pub async fn get_previous_image(pool: &PgPool, project_id: i32) -> Result<Option<PreviousImage>, AppError>
+{
+    sqlx::query_as::<_, PreviousImage>(
+        "SELECT previous_image_tag AS tag, previous_image_digest AS digest FROM projects \
+         WHERE id = $1 AND previous_image_tag IS NOT NULL AND previous_image_digest IS NOT NULL"
+    )
+    .bind(project_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to fetch previous image of project {}: {}", project_id, e);
+        AppError::InternalServerError
+    })
+}
+
+/// Tags et empreintes des images précédentes de tous les projets, cibles d'un retour arrière.
+pub async fn list_previous_image_references(pool: &PgPool) -> Result<Vec<String>, AppError>
 {
-    sqlx::query("UPDATE projects SET deployed_image_tag = $1, deployed_image_digest = $2 WHERE id = $3")
-        .bind(new_image_tag)
-        .bind(new_image_digest)
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT previous_image_tag, previous_image_digest FROM projects \
+         WHERE previous_image_tag IS NOT NULL AND previous_image_digest IS NOT NULL"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to list previous images of projects: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(rows.into_iter().flat_map(|(tag, digest)| [tag, digest]).collect())
+}
+
+/// `None` lorsque l'image déployée ne correspond plus à un commit connu.
+pub async fn update_project_source_commit(
+    executor: impl PgExecutor<'_>,
+    project_id: i32,
+    commit_sha: Option<&str>,
+) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET source_commit_sha = $1 WHERE id = $2")
+        .bind(commit_sha)
         .bind(project_id)
-        .execute(pool)
+        .execute(executor)
         .await
-        .map_err(|e| 
+        .map_err(|e|
         {
-            error!("Failed to update project {} with new image and digest: {}", project_id, e);
+            error!("Failed to update source commit for project {}: {}", project_id, e);
             AppError::InternalServerError
         })?;
     Ok(())
 }
 
-pub async fn update_project_source_url(
-    pool: &PgPool,
+pub async fn update_project_resource_profile(executor: impl PgExecutor<'_>, project_id: i32, resource_profile: &str) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET resource_profile = $1 WHERE id = $2")
+        .bind(resource_profile)
+        .bind(project_id)
+        .execute(executor)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to update resource profile for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn update_project_archived(executor: impl PgExecutor<'_>, project_id: i32, archived: bool) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET archived = $1 WHERE id = $2")
+        .bind(archived)
+        .bind(project_id)
+        .execute(executor)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to update archived flag for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn update_project_keep_alive(executor: impl PgExecutor<'_>, project_id: i32, keep_alive: bool) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET keep_alive = $1 WHERE id = $2")
+        .bind(keep_alive)
+        .bind(project_id)
+        .execute(executor)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to update keep-alive flag for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn update_project_source_ref(
+    executor: impl PgExecutor<'_>,
     project_id: i32,
-    new_source_url: &str,
+    source_ref: &str,
 ) -> Result<(), AppError>
 {
-    sqlx::query("UPDATE projects SET source_url = $1 WHERE id = $2")
-        .bind(new_source_url)
+    sqlx::query("UPDATE projects SET source_ref = $1 WHERE id = $2")
+        .bind(source_ref)
         .bind(project_id)
-        .execute(pool)
+        .execute(executor)
         .await
         .map_err(|e|
         {
-            error!("Failed to update source_url for project {}: {}", project_id, e);
+            error!("Failed to update source ref for project {}: {}", project_id, e);
             AppError::InternalServerError
         })?;
     Ok(())
 }
 
+/// Propriétaires des conteneurs pour la détection des conflits de routage. Une erreur de lecture est
+/// journalisée : les conteneurs sans label ne sont alors tolérés que sous le nom `own_container_name`.
+pub async fn routing_owners<'a>(pool: &PgPool, own_container_name: &'a str) -> RoutingOwners<'a>
+{
+    let projects_by_container = sqlx::query_as::<_, (String, i32)>("SELECT container_name, id FROM projects")
+        .fetch_all(pool)
+        .await
+        .map(|rows| rows.into_iter().collect())
+        .inspect_err(|e| error!("Failed to list project containers for routing conflict detection: {}", e))
+        .ok();
+
+    RoutingOwners { own_container_name, projects_by_container }
+}
+
 pub async fn get_project_by_container_name(
     pool: &PgPool,
     container_name: &str,
@@ -446,4 +738,206 @@ pub async fn get_projects_by_ids(pool: &PgPool, ids: &[i32]) -> Result<Vec<Proje
             error!("Failed to fetch projects by ids {:?}: {}", ids, e);
             AppError::InternalServerError
         })
-}
\ No newline at end of file
+}
+
+/// Variables d'environnement effectivement injectées dans le conteneur : celles de l'utilisateur,
+/// complétées par celles fournies par la plateforme (`HANGAR_S3_*`, `HANGAR_DB_*` si demandé).
+///
+/// Les identifiants sont relus à chaque appel : toute recréation reprend les valeurs courantes.
+pub async fn runtime_env_vars(
+    state: &AppState,
+    project: &Project,
+    user_env_vars: Option<HashMap<String, String>>,
+) -> Result<Option<HashMap<String, String>>, AppError>
+{
+    let mut platform_env_vars = bucket_env_vars(state, project.id).await?;
+
+    if project.inject_database_env
+        && let Some(database) = database_service::get_database_by_project_id(&state.db_pool, project.id).await?
+    {
+        platform_env_vars.extend(database_service::database_env_vars(&database, &state.config)?);
+    }
+
+    if platform_env_vars.is_empty()
+    {
+        return Ok(user_env_vars);
+    }
+
+    let mut env_vars = user_env_vars.unwrap_or_default();
+    env_vars.extend(platform_env_vars);
+    Ok(Some(env_vars))
+}
+
+#[cfg(feature = "object_storage")]
+async fn bucket_env_vars(state: &AppState, project_id: i32) -> Result<HashMap<String, String>, AppError>
+{
+    let Some(storage) = &state.config.object_storage else
+    {
+        return Ok(HashMap::new());
+    };
+
+    match object_storage_service::get_bucket_by_project_id(&state.db_pool, project_id).await?
+    {
+        Some(bucket) => object_storage_service::bucket_env_vars(&bucket, storage, &state.config.encryption_key),
+        None => Ok(HashMap::new()),
+    }
+}
+
+#[cfg(not(feature = "object_storage"))]
+#[allow(clippy::unused_async)]
+async fn bucket_env_vars(_state: &AppState, _project_id: i32) -> Result<HashMap<String, String>, AppError>
+{
+    Ok(HashMap::new())
+}
+
+pub fn get_decrypted_env_vars(
+    project: &Project,
+    encryption_key: &[u8],
+) -> Result<Option<HashMap<String, String>>, AppError>
+{
+    if let Some(env_vars_value) = &project.env_vars
+    {
+        let encrypted_vars: HashMap<String, String> = serde_json::from_value(env_vars_value.clone())
+            .unwrap_or_default();
+        
+        Ok(Some(decrypt_env_vars(&encrypted_vars, encryption_key)?))
+    }
+    else
+    {
+        Ok(None)
+    }
+}
+
+pub fn decrypt_env_vars(
+    encrypted_vars: &HashMap<String, String>,
+    key: &[u8],
+) -> Result<HashMap<String, String>, AppError>
+{
+    encrypted_vars
+        .iter()
+        .map(|(k, v_b64)|
+        {
+            let encrypted_val = BASE64_STANDARD
+                .decode(v_b64)
+                .map_err(|_| AppError::InternalServerError)?;
+            
+            let decrypted_val = crypto_service::decrypt(&encrypted_val, key)?;
+            
+            Ok((k.clone(), decrypted_val))
+        })
+        .collect()
+}
+
+pub async fn update_project_command(executor: impl PgExecutor<'_>, project_id: i32, command: &ContainerCommand) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET entrypoint = $1, command = $2 WHERE id = $3")
+        .bind(&command.entrypoint)
+        .bind(&command.command)
+        .bind(project_id)
+        .execute(executor)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to update container command for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn update_project_port(executor: impl PgExecutor<'_>, project_id: i32, container_port: u16) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET container_port = $1 WHERE id = $2")
+        .bind(i32::from(container_port))
+        .bind(project_id)
+        .execute(executor)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to update container port for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn update_project_scan_ignores(pool: &PgPool, project_id: i32, scan_ignore_cves: &[String]) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET scan_ignore_cves = $1 WHERE id = $2")
+        .bind(scan_ignore_cves)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to update scan ignore list for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn update_project_health_check(
+    pool: &PgPool,
+    project_id: i32,
+    max_attempts: Option<i32>,
+    interval_ms: Option<i32>,
+) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET health_check_max_attempts = $1, health_check_interval_ms = $2 WHERE id = $3")
+        .bind(max_attempts)
+        .bind(interval_ms)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to update health check settings for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn update_project_stop_grace(pool: &PgPool, project_id: i32, stop_grace_seconds: i32) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET stop_grace_seconds = $1 WHERE id = $2")
+        .bind(stop_grace_seconds)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e|
+        {
+            error!("Failed to update stop grace period for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Projets dont le conteneur est arrêté, du plus ancien arrêt au plus récent.
+pub async fn list_down_projects(state: &AppState) -> Result<Vec<DownProjectInfo>, AppError>
+{
+    let all_projects = get_all_projects(&state.db_pool).await?;
+    let mut down_projects: Vec<DownProjectInfo> = Vec::new();
+
+    let now = OffsetDateTime::now_utc();
+
+    for project in all_projects 
+    {
+        if let Some(details) = docker_service::inspect_container_details(&state.docker_client, &project.container_name).await?
+            && let Some(container_state) = details.state
+                && let Some(is_running) = container_state.running
+                    && !is_running
+                        && let Some(finished_at_str) = container_state.finished_at
+                            && let Ok(stopped_at) = OffsetDateTime::parse(&finished_at_str, &Rfc3339)
+                            {
+                                let downtime_seconds = (now - stopped_at).as_seconds_f64() as i64;
+                                down_projects.push(DownProjectInfo 
+                                {
+                                    project,
+                                    stopped_at: stopped_at.to_offset(UtcOffset::UTC),
+                                    downtime_seconds,
+                                });
+                            }
+    }
+
+    down_projects.sort_by_key(|project| std::cmp::Reverse(project.downtime_seconds));
+
+    Ok(down_projects)
+}
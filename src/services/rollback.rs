@@ -0,0 +1,103 @@
+//! Annulation des ressources créées par une opération qui échoue. Les actions sont attendues
+//! avant de renvoyer l'erreur, dans l'ordre inverse de leur enregistrement : la réponse HTTP ne
+//! devance jamais le nettoyage, et deux nettoyages ne se disputent pas la même ressource.
+//!
+//! L'annulation reste au mieux : une action en échec ou trop longue est journalisée, les suivantes
+//! sont tout de même exécutées.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use tokio::time::timeout;
+use tracing::{error, info};
+
+use crate::error::AppError;
+
+/// Délai accordé à chaque action d'annulation.
+const ROLLBACK_STEP_TIMEOUT: Duration = Duration::from_secs(30);
+
+type RollbackAction<'a> = Pin<Box<dyn Future<Output = Result<(), AppError>> + Send + 'a>>;
+
+/// Actions d'annulation accumulées au fil d'une opération, exécutées par [`RollbackGuard::run`].
+pub struct RollbackGuard<'a>
+{
+    actions: Vec<(String, RollbackAction<'a>)>,
+    step_timeout: Duration,
+}
+
+impl Default for RollbackGuard<'_>
+{
+    fn default() -> Self
+    {
+        Self { actions: Vec::new(), step_timeout: ROLLBACK_STEP_TIMEOUT }
+    }
+}
+
+impl<'a> RollbackGuard<'a>
+{
+    #[must_use]
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Enregistre l'annulation d'une ressource, désignée par `resource` dans les logs (`container 'x'`).
+    /// `action` n'est exécutée que par [`RollbackGuard::run`].
+    pub fn push(&mut self, resource: impl Into<String>, action: impl Future<Output = Result<(), AppError>> + Send + 'a)
+    {
+        self.actions.push((resource.into(), Box::pin(action)));
+    }
+
+    /// Exécute les actions, de la dernière enregistrée à la première.
+    pub async fn run(self)
+    {
+        for (resource, action) in self.actions.into_iter().rev()
+        {
+            match timeout(self.step_timeout, action).await
+            {
+                Ok(Ok(())) => info!("Rollback successful for {}", resource),
+                Ok(Err(e)) => error!("ROLLBACK FAILED: Could not remove {}: {}", resource, e),
+                Err(_) => error!("ROLLBACK FAILED: Removing {} timed out after {:?}", resource, self.step_timeout),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn block_on(future: impl Future<Output = ()>)
+    {
+        tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(future);
+    }
+
+    #[test]
+    fn test_actions_run_in_reverse_order_despite_failures_and_timeouts()
+    {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str, result: Result<(), AppError>|
+        {
+            let calls = Arc::clone(&calls);
+            async move
+            {
+                calls.lock().unwrap().push(name);
+                result
+            }
+        };
+
+        let mut guard = RollbackGuard { step_timeout: Duration::from_millis(10), ..RollbackGuard::new() };
+        guard.push("volume 'data'", record("volume", Ok(())));
+        guard.push("image 'app:2'", record("image", Err(AppError::InternalServerError)));
+        guard.push("container 'stuck'", std::future::pending());
+        guard.push("container 'new'", record("container", Ok(())));
+        assert!(calls.lock().unwrap().is_empty());
+
+        block_on(guard.run());
+
+        assert_eq!(*calls.lock().unwrap(), ["container", "image", "volume"]);
+    }
+}
@@ -0,0 +1,194 @@
+//! Liens de partage en lecture seule de la page de statut d'un projet.
+//!
+//! Le jeton est un JWT HS256 signé avec `jwt_secret`, limité à l'audience [`SHARE_AUDIENCE`] :
+//! il ne peut pas servir de jeton de session, et un jeton de session ne peut pas servir de lien.
+//! Son `jti` est enregistré en base pour permettre la révocation avant expiration.
+
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::distr::{Alphanumeric, SampleString};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use tracing::error;
+
+use crate::{error::AppError, model::share_link::{CreatedShareLink, ProjectShareLink}};
+
+/// Audience des jetons de partage.
+const SHARE_AUDIENCE: &str = "hangar-project-share";
+/// Longueur de l'identifiant aléatoire d'un jeton.
+const JTI_LENGTH: usize = 32;
+/// Durée de validité par défaut d'un lien.
+pub const DEFAULT_TTL_HOURS: i64 = 24;
+/// Durée de validité maximale d'un lien (7 jours).
+pub const MAX_TTL_HOURS: i64 = 24 * 7;
+/// Nombre maximal de liens actifs par projet.
+pub const MAX_ACTIVE_LINKS_PER_PROJECT: i64 = 20;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShareClaims
+{
+    pub project_id: i32,
+    pub jti: String,
+    pub exp: i64,
+    pub aud: String,
+}
+
+pub fn sign_token(secret: &str, project_id: i32, jti: &str, expires_at: OffsetDateTime) -> Result<String, AppError>
+{
+    let claims = ShareClaims
+    {
+        project_id,
+        jti: jti.to_string(),
+        exp: expires_at.unix_timestamp(),
+        aud: SHARE_AUDIENCE.to_string(),
+    };
+
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).map_err(|_| AppError::InternalServerError)
+}
+
+/// Vérifie la signature, l'audience et l'expiration d'un jeton de partage.
+pub fn verify_token(secret: &str, token: &str) -> Result<ShareClaims, AppError>
+{
+    let mut validation = Validation::default();
+    validation.set_audience(&[SHARE_AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "aud"]);
+
+    decode::<ShareClaims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        .map(|data| data.claims)
+        .map_err(|_| AppError::NotFound("Share link not found or expired.".to_string()))
+}
+
+pub async fn list_links(pool: &PgPool, project_id: i32) -> Result<Vec<ProjectShareLink>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_share_links WHERE project_id = $1 ORDER BY id")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list share links of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn create_link(pool: &PgPool, secret: &str, project_id: i32, created_by: &str, ttl_hours: i64) -> Result<CreatedShareLink, AppError>
+{
+    let active: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM project_share_links WHERE project_id = $1 AND revoked_at IS NULL AND expires_at > NOW()"
+    )
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count share links of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    if active >= MAX_ACTIVE_LINKS_PER_PROJECT
+    {
+        return Err(AppError::BadRequest(format!("A project cannot have more than {MAX_ACTIVE_LINKS_PER_PROJECT} active share links.")));
+    }
+
+    let jti = Alphanumeric.sample_string(&mut rand::rng(), JTI_LENGTH);
+    // Précision à la seconde, comme l'`exp` du jeton.
+    let expires_at = (OffsetDateTime::now_utc() + Duration::hours(ttl_hours)).replace_nanosecond(0).unwrap_or_else(|_| OffsetDateTime::now_utc());
+    let token = sign_token(secret, project_id, &jti, expires_at)?;
+
+    let link: ProjectShareLink = sqlx::query_as(
+        "INSERT INTO project_share_links (project_id, jti, created_by, expires_at) VALUES ($1, $2, $3, $4) RETURNING *"
+    )
+        .bind(project_id)
+        .bind(&jti)
+        .bind(created_by)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to create share link for project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    Ok(CreatedShareLink { link, token })
+}
+
+/// Révoque un lien. Renvoie `false` si le lien n'existe pas ou était déjà révoqué.
+pub async fn revoke_link(pool: &PgPool, project_id: i32, link_id: i32) -> Result<bool, AppError>
+{
+    let result = sqlx::query("UPDATE project_share_links SET revoked_at = NOW(), updated_at = NOW() WHERE id = $1 AND project_id = $2 AND revoked_at IS NULL")
+        .bind(link_id)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to revoke share link ID {} of project ID {}: {}", link_id, project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Résout un jeton en lien actif et comptabilise l'accès.
+///
+/// Toutes les causes de refus (signature, expiration, révocation) renvoient la même erreur.
+pub async fn resolve_token(pool: &PgPool, secret: &str, token: &str) -> Result<ProjectShareLink, AppError>
+{
+    let claims = verify_token(secret, token)?;
+
+    sqlx::query_as(
+        "UPDATE project_share_links SET last_accessed_at = NOW(), access_count = access_count + 1 \
+         WHERE jti = $1 AND project_id = $2 AND revoked_at IS NULL AND expires_at > NOW() \
+         RETURNING *"
+    )
+        .bind(&claims.jti)
+        .bind(claims.project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to resolve share link of project ID {}: {}", claims.project_id, e);
+            AppError::InternalServerError
+        })?
+        .ok_or_else(|| AppError::NotFound("Share link not found or expired.".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    #[test]
+    fn test_token_round_trip()
+    {
+        let expires_at = OffsetDateTime::now_utc() + Duration::hours(1);
+        let token = sign_token(SECRET, 42, "abc", expires_at).unwrap();
+
+        let claims = verify_token(SECRET, &token).unwrap();
+        assert_eq!(claims.project_id, 42);
+        assert_eq!(claims.jti, "abc");
+        assert_eq!(claims.exp, expires_at.unix_timestamp());
+    }
+
+    #[test]
+    fn test_token_rejects_wrong_secret_and_expiry()
+    {
+        let token = sign_token(SECRET, 42, "abc", OffsetDateTime::now_utc() + Duration::hours(1)).unwrap();
+        assert!(verify_token("other-secret", &token).is_err());
+
+        let expired = sign_token(SECRET, 42, "abc", OffsetDateTime::now_utc() - Duration::hours(1)).unwrap();
+        assert!(verify_token(SECRET, &expired).is_err());
+    }
+
+    #[test]
+    fn test_session_and_share_tokens_are_not_interchangeable()
+    {
+        let session = crate::services::jwt::generate_jwt(SECRET, 3600, "login", "Name", "mail@example.com", true).unwrap();
+        assert!(verify_token(SECRET, &session).is_err());
+
+        let share = sign_token(SECRET, 42, "abc", OffsetDateTime::now_utc() + Duration::hours(1)).unwrap();
+        assert!(crate::services::jwt::validate_jwt(&share, SECRET).is_err());
+    }
+}
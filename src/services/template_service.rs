@@ -0,0 +1,266 @@
+//! Catalogue de modèles de projets déployables en un clic.
+//!
+//! Les valeurs des variables par défaut d'un modèle peuvent contenir des champs `{{NOM}}`
+//! (majuscules, chiffres et `_`), renseignés par l'utilisateur au déploiement.
+
+use std::collections::{BTreeSet, HashMap};
+
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::
+{
+    error::{AppError, ProjectErrorCode},
+    model::{project::ProjectSourceType, template::{Template, TemplateInput, TemplateResponse}},
+    services::{github_service, validation_service},
+};
+
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_DESCRIPTION_LENGTH: usize = 2000;
+
+/// Vérifie un modèle avant son enregistrement : il doit pouvoir passer la validation du déploiement.
+pub async fn validate_template(input: &TemplateInput) -> Result<(), AppError>
+{
+    let name = input.name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH
+    {
+        return Err(ProjectErrorCode::InvalidTemplate(format!("the name must be between 1 and {MAX_NAME_LENGTH} characters")).into());
+    }
+    if input.description.len() > MAX_DESCRIPTION_LENGTH
+    {
+        return Err(ProjectErrorCode::InvalidTemplate(format!("the description cannot exceed {MAX_DESCRIPTION_LENGTH} characters")).into());
+    }
+
+    match input.source_type
+    {
+        ProjectSourceType::Direct =>
+        {
+            if input.source_branch.is_some()
+            {
+                return Err(ProjectErrorCode::InvalidTemplate("a branch can only be set for a 'github' template".to_string()).into());
+            }
+            validation_service::validate_image_url(&input.source_url)?;
+        }
+        ProjectSourceType::Github =>
+        {
+            github_service::extract_repo_owner_and_name(&input.source_url).await?;
+        }
+        ProjectSourceType::Upload =>
+        {
+            return Err(ProjectErrorCode::InvalidTemplate("a template needs an image or a repository".to_string()).into());
+        }
+    }
+
+    validation_service::validate_env_vars(&input.default_env_vars)?;
+    if let Some(path) = &input.persistent_volume_path
+    {
+        validation_service::validate_volume_path(path)?;
+    }
+    if let Some(port) = input.container_port
+    {
+        validation_service::validate_container_port(port)?;
+    }
+
+    Ok(())
+}
+
+pub async fn list_templates(pool: &PgPool) -> Result<Vec<Template>, AppError>
+{
+    sqlx::query_as("SELECT * FROM templates ORDER BY name")
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list templates: {}", e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn get_template(pool: &PgPool, template_id: i32) -> Result<Option<Template>, AppError>
+{
+    sqlx::query_as("SELECT * FROM templates WHERE id = $1")
+        .bind(template_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch template ID {}: {}", template_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn create_template(pool: &PgPool, input: &TemplateInput) -> Result<Template, AppError>
+{
+    sqlx::query_as(
+        "INSERT INTO templates (name, description, source_type, source_url, source_branch, default_env_vars, create_database, persistent_volume_path, container_port)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *"
+    )
+        .bind(input.name.trim())
+        .bind(&input.description)
+        .bind(input.source_type)
+        .bind(input.source_url.trim())
+        .bind(&input.source_branch)
+        .bind(sqlx::types::Json(&input.default_env_vars))
+        .bind(input.create_database)
+        .bind(&input.persistent_volume_path)
+        .bind(input.container_port.map(i32::from))
+        .fetch_one(pool)
+        .await
+        .map_err(|e| template_write_error(&e, "create template"))
+}
+
+/// Remplace un modèle. Renvoie `None` s'il n'existe pas.
+pub async fn update_template(pool: &PgPool, template_id: i32, input: &TemplateInput) -> Result<Option<Template>, AppError>
+{
+    sqlx::query_as(
+        "UPDATE templates SET name = $1, description = $2, source_type = $3, source_url = $4, source_branch = $5, default_env_vars = $6,
+         create_database = $7, persistent_volume_path = $8, container_port = $9, updated_at = NOW()
+         WHERE id = $10 RETURNING *"
+    )
+        .bind(input.name.trim())
+        .bind(&input.description)
+        .bind(input.source_type)
+        .bind(input.source_url.trim())
+        .bind(&input.source_branch)
+        .bind(sqlx::types::Json(&input.default_env_vars))
+        .bind(input.create_database)
+        .bind(&input.persistent_volume_path)
+        .bind(input.container_port.map(i32::from))
+        .bind(template_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| template_write_error(&e, "update template"))
+}
+
+/// Supprime un modèle. Les projets déployés à partir de lui ne sont pas concernés.
+pub async fn delete_template(pool: &PgPool, template_id: i32) -> Result<bool, AppError>
+{
+    sqlx::query("DELETE FROM templates WHERE id = $1")
+        .bind(template_id)
+        .execute(pool)
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(|e|
+        {
+            error!("Failed to delete template ID {}: {}", template_id, e);
+            AppError::InternalServerError
+        })
+}
+
+fn template_write_error(e: &sqlx::Error, operation: &str) -> AppError
+{
+    if let Some(db_err) = e.as_database_error()
+        && db_err.is_unique_violation()
+    {
+        return ProjectErrorCode::TemplateNameTaken.into();
+    }
+    error!("Failed to {}: {}", operation, e);
+    AppError::InternalServerError
+}
+
+#[must_use]
+pub fn with_placeholders(template: Template) -> TemplateResponse
+{
+    let placeholders = placeholders(&template.default_env_vars).into_iter().collect();
+    TemplateResponse { template, placeholders }
+}
+
+/// Noms des champs `{{NOM}}` présents dans les valeurs des variables.
+#[must_use]
+pub fn placeholders(env_vars: &HashMap<String, String>) -> BTreeSet<String>
+{
+    let mut names = BTreeSet::new();
+    for value in env_vars.values()
+    {
+        render(value, |name|
+        {
+            names.insert(name.to_string());
+            None
+        });
+    }
+    names
+}
+
+/// Remplace les champs `{{NOM}}` des valeurs par ceux de `values`. Tous les champs doivent être renseignés ;
+/// les valeurs insérées ne sont pas elles-mêmes interprétées.
+pub fn fill_placeholders(env_vars: &HashMap<String, String>, values: &HashMap<String, String>) -> Result<HashMap<String, String>, AppError>
+{
+    let missing: Vec<String> = placeholders(env_vars).into_iter()
+        .filter(|name| !values.contains_key(name))
+        .collect();
+    if !missing.is_empty()
+    {
+        return Err(ProjectErrorCode::MissingTemplateValues(missing.join(", ")).into());
+    }
+
+    Ok(env_vars.iter()
+        .map(|(key, value)| (key.clone(), render(value, |name| values.get(name).cloned())))
+        .collect())
+}
+
+fn is_placeholder_name(name: &str) -> bool
+{
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Parcourt les champs de `value` : chacun est remplacé par le résultat de `resolve`, ou laissé tel quel.
+fn render(value: &str, mut resolve: impl FnMut(&str) -> Option<String>) -> String
+{
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("{{")
+    {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = &after[..end];
+
+        output.push_str(&rest[..start]);
+        match is_placeholder_name(name).then(|| resolve(name)).flatten()
+        {
+            Some(replacement) => output.push_str(&replacement),
+            None => output.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(entries: &[(&str, &str)]) -> HashMap<String, String>
+    {
+        entries.iter().map(|(k, v)| ((*k).to_string(), (*v).to_string())).collect()
+    }
+
+    #[test]
+    fn test_placeholders()
+    {
+        let env_vars = vars(&[
+            ("WORDPRESS_TABLE_PREFIX", "wp_"),
+            ("SITE_URL", "https://{{SITE_HOST}}/{{PATH}}"),
+            ("ADMIN", "{{ADMIN_EMAIL}}"),
+            ("LITERAL", "{{not a field}} {{lower}} {{"),
+        ]);
+
+        let names: Vec<String> = placeholders(&env_vars).into_iter().collect();
+        assert_eq!(names, ["ADMIN_EMAIL", "PATH", "SITE_HOST"]);
+    }
+
+    #[test]
+    fn test_fill_placeholders()
+    {
+        let env_vars = vars(&[("SITE_URL", "https://{{SITE_HOST}}/"), ("LITERAL", "{{lower}}")]);
+
+        let filled = fill_placeholders(&env_vars, &vars(&[("SITE_HOST", "{{SITE_HOST}}.fr")])).unwrap();
+        assert_eq!(filled["SITE_URL"], "https://{{SITE_HOST}}.fr/");
+        assert_eq!(filled["LITERAL"], "{{lower}}");
+
+        let missing = fill_placeholders(&env_vars, &HashMap::new()).unwrap_err();
+        assert!(matches!(missing, AppError::ProjectError(ProjectErrorCode::MissingTemplateValues(ref names)) if names == "SITE_HOST"));
+    }
+}
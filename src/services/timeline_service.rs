@@ -0,0 +1,111 @@
+//! Chronologie d'un projet : déploiements, transitions démarré/arrêté et journal d'audit réunis
+//! dans une seule liste, de la plus récente à la plus ancienne entrée.
+
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{error, warn};
+
+use crate::
+{
+    error::AppError,
+    model::timeline::{TimelineCursor, TimelineEntry, TimelineEntryType},
+    sse::emitter::emit_timeline_entry,
+    state::AppState,
+};
+
+/// Colonnes communes (`entry_type`, `id`, `project_id`, `occurred_at`, `data`) produites par chaque historique.
+const DEPLOYMENT_ENTRIES: &str =
+    "SELECT 'deployment'::TEXT AS entry_type, id::BIGINT AS id, project_id, created_at AS occurred_at, \
+     jsonb_build_object('kind', kind, 'status', status, 'triggered_by', triggered_by, 'image_tag', image_tag, \
+     'failed_stage', failed_stage, 'finished_at', finished_at) AS data \
+     FROM deployments";
+
+const STATUS_ENTRIES: &str =
+    "SELECT 'status'::TEXT AS entry_type, id, project_id, occurred_at, jsonb_build_object('is_running', is_running) AS data \
+     FROM project_status_transitions";
+
+const AUDIT_ENTRIES: &str =
+    "SELECT 'audit'::TEXT AS entry_type, id, project_id, created_at AS occurred_at, \
+     jsonb_build_object('actor_login', actor_login, 'action', action, 'details', details) AS data \
+     FROM audit_logs";
+
+type EntryRow = (String, i64, Option<i32>, OffsetDateTime, serde_json::Value);
+
+const fn source_query(entry_type: TimelineEntryType) -> &'static str
+{
+    match entry_type
+    {
+        TimelineEntryType::Deployment => DEPLOYMENT_ENTRIES,
+        TimelineEntryType::Status => STATUS_ENTRIES,
+        TimelineEntryType::Audit => AUDIT_ENTRIES,
+    }
+}
+
+fn into_entry((entry_type, id, _, occurred_at, data): EntryRow) -> Option<TimelineEntry>
+{
+    Some(TimelineEntry { entry_type: TimelineEntryType::parse(&entry_type)?, id, occurred_at, data })
+}
+
+/// Entrées de `types` comprises dans `[from, to[`, strictement antérieures à `before`, les plus récentes d'abord.
+pub async fn list_entries(
+    pool: &PgPool,
+    project_id: i32,
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+    types: &[TimelineEntryType],
+    before: Option<TimelineCursor>,
+    limit: i64,
+) -> Result<Vec<TimelineEntry>, AppError>
+{
+    let query = format!(
+        "SELECT entry_type, id, project_id, occurred_at, data FROM ({DEPLOYMENT_ENTRIES} UNION ALL {STATUS_ENTRIES} UNION ALL {AUDIT_ENTRIES}) entries \
+         WHERE project_id = $1 AND occurred_at >= $2 AND occurred_at < $3 AND entry_type = ANY($4) \
+         AND ($5::TIMESTAMPTZ IS NULL OR (occurred_at, entry_type, id) < ($5, $6, $7)) \
+         ORDER BY occurred_at DESC, entry_type DESC, id DESC LIMIT $8"
+    );
+    let types: Vec<&str> = types.iter().map(|entry_type| entry_type.as_str()).collect();
+
+    let rows = sqlx::query_as::<_, EntryRow>(&query)
+        .bind(project_id)
+        .bind(from)
+        .bind(to)
+        .bind(&types)
+        .bind(before.map(|cursor| cursor.occurred_at))
+        .bind(before.map(|cursor| cursor.entry_type.as_str()))
+        .bind(before.map(|cursor| cursor.id))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list timeline of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })?;
+
+    Ok(rows.into_iter().filter_map(into_entry).collect())
+}
+
+/// Diffuse une entrée qui vient d'être enregistrée sur le canal SSE de son projet.
+///
+/// Best-effort, comme l'historique qui l'alimente : un échec est journalisé et ignoré.
+pub async fn publish(state: &AppState, entry_type: TimelineEntryType, id: i64)
+{
+    let query = format!("SELECT entry_type, id, project_id, occurred_at, data FROM ({}) entry WHERE id = $1", source_query(entry_type));
+
+    let row = match sqlx::query_as::<_, EntryRow>(&query).bind(id).fetch_optional(&state.db_pool).await
+    {
+        Ok(row) => row,
+        Err(e) =>
+        {
+            warn!("Failed to load {} timeline entry {}: {}", entry_type.as_str(), id, e);
+            return;
+        }
+    };
+
+    if let Some(row) = row
+        && let Some(project_id) = row.2
+        && let Some(entry) = into_entry(row)
+    {
+        emit_timeline_entry(state, project_id, entry).await;
+    }
+}
@@ -0,0 +1,331 @@
+//! Sites statiques envoyés sous forme d'archive (`.zip`, `.tar.gz`), extraits dans l'espace de build
+//! puis construits avec le même Dockerfile généré qu'un projet GitHub.
+//!
+//! Seuls les fichiers et dossiers ordinaires sont extraits : les liens et les chemins absolus ou
+//! sortant de la destination (`../`) font refuser l'archive.
+
+use std::
+{
+    fs,
+    io::{self, Cursor, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use axum::body::Bytes;
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+
+use crate::
+{
+    error::{AppError, ProjectErrorCode},
+    services::validation_service,
+};
+
+/// Nombre maximal d'entrées d'une archive.
+const MAX_ENTRIES: usize = 10_000;
+const MAX_FILE_NAME_LENGTH: usize = 100;
+/// Type de fichier Unix d'un lien symbolique, dans le mode d'une entrée zip.
+const UNIX_FILE_TYPE_MASK: u32 = 0o170_000;
+const UNIX_SYMLINK: u32 = 0o120_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat
+{
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat
+{
+    fn from_file_name(file_name: &str) -> Option<Self>
+    {
+        let lower = file_name.to_ascii_lowercase();
+        if lower.ends_with(".zip")
+        {
+            Some(Self::Zip)
+        }
+        else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz")
+        {
+            Some(Self::TarGz)
+        }
+        else
+        {
+            None
+        }
+    }
+}
+
+/// Archive reçue, identifiée par son nom d'origine et l'empreinte SHA-256 de son contenu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedArchive
+{
+    pub file_name: String,
+    pub format: ArchiveFormat,
+    pub sha256: String,
+    bytes: Bytes,
+}
+
+impl UploadedArchive
+{
+    pub fn new(file_name: &str, bytes: Bytes) -> Result<Self, AppError>
+    {
+        let file_name = sanitize_file_name(file_name);
+        let format = ArchiveFormat::from_file_name(&file_name)
+            .ok_or_else(|| invalid("only .zip, .tar.gz and .tgz archives are supported"))?;
+        if bytes.is_empty()
+        {
+            return Err(invalid("the archive is empty"));
+        }
+
+        let sha256 = Sha256::digest(&bytes).iter().map(|b| format!("{b:02x}")).collect();
+        Ok(Self { file_name, format, sha256, bytes })
+    }
+
+    /// Valeur enregistrée dans `source_url` : `site.zip#sha256:...`.
+    #[must_use]
+    pub fn source_label(&self) -> String
+    {
+        format!("{}#sha256:{}", self.file_name, self.sha256)
+    }
+}
+
+/// Extrait l'archive dans `destination`. Renvoie le dossier racine du site lorsque l'archive
+/// contient un unique dossier (`site/index.html`), à servir à la place de la racine.
+pub async fn extract_archive(archive: UploadedArchive, destination: PathBuf, max_unpacked_bytes: u64) -> Result<Option<String>, AppError>
+{
+    tokio::task::spawn_blocking(move ||
+    {
+        match archive.format
+        {
+            ArchiveFormat::Zip => extract_zip(&archive.bytes, &destination, max_unpacked_bytes)?,
+            ArchiveFormat::TarGz => extract_tar_gz(&archive.bytes, &destination, max_unpacked_bytes)?,
+        }
+        site_root(&destination)
+    })
+    .await
+    .map_err(|_| AppError::InternalServerError)?
+}
+
+fn extract_zip(bytes: &[u8], destination: &Path, max_unpacked_bytes: u64) -> Result<(), AppError>
+{
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|_| invalid("not a valid zip archive"))?;
+    if archive.len() > MAX_ENTRIES
+    {
+        return Err(invalid(&format!("the archive contains more than {MAX_ENTRIES} entries")));
+    }
+
+    let mut unpacked: u64 = 0;
+    for index in 0..archive.len()
+    {
+        let mut file = archive.by_index(index).map_err(|_| invalid("the archive is corrupted"))?;
+        if file.unix_mode().is_some_and(|mode| mode & UNIX_FILE_TYPE_MASK == UNIX_SYMLINK)
+        {
+            return Err(invalid(&format!("'{}' is a link", file.name())));
+        }
+
+        let relative = safe_relative_path(file.name())
+            .ok_or_else(|| invalid(&format!("'{}' is outside the archive root", file.name())))?;
+        if relative.as_os_str().is_empty()
+        {
+            continue;
+        }
+
+        if file.is_dir()
+        {
+            create_dir(&destination.join(relative))?;
+        }
+        else
+        {
+            write_file(&mut file, &destination.join(relative), &mut unpacked, max_unpacked_bytes)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_tar_gz(bytes: &[u8], destination: &Path, max_unpacked_bytes: u64) -> Result<(), AppError>
+{
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    let entries = archive.entries().map_err(|_| invalid("not a gzip-compressed tar archive"))?;
+
+    let mut unpacked: u64 = 0;
+    for (index, entry) in entries.enumerate()
+    {
+        if index >= MAX_ENTRIES
+        {
+            return Err(invalid(&format!("the archive contains more than {MAX_ENTRIES} entries")));
+        }
+
+        let mut entry = entry.map_err(|_| invalid("the archive is corrupted"))?;
+        let name = entry.path().map_err(|_| invalid("the archive is corrupted"))?.to_string_lossy().into_owned();
+
+        let relative = safe_relative_path(&name)
+            .ok_or_else(|| invalid(&format!("'{name}' is outside the archive root")))?;
+
+        match entry.header().entry_type()
+        {
+            tar::EntryType::Directory => create_dir(&destination.join(relative))?,
+            tar::EntryType::Regular | tar::EntryType::Continuous =>
+            {
+                if relative.as_os_str().is_empty()
+                {
+                    return Err(invalid(&format!("'{name}' is not a valid file path")));
+                }
+                write_file(&mut entry, &destination.join(relative), &mut unpacked, max_unpacked_bytes)?;
+            }
+            // En-têtes pax globaux ajoutés par certains outils (git archive...).
+            tar::EntryType::XGlobalHeader => {}
+            _ => return Err(invalid(&format!("'{name}' is not a regular file or directory"))),
+        }
+    }
+
+    Ok(())
+}
+
+/// Chemin relatif d'une entrée, sans composant `..`, absolu ou Windows. La racine (`./`) donne un chemin vide.
+fn safe_relative_path(name: &str) -> Option<PathBuf>
+{
+    if name.contains('\\') || name.contains('\0')
+    {
+        return None;
+    }
+
+    let mut relative = PathBuf::new();
+    for component in Path::new(name).components()
+    {
+        match component
+        {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    Some(relative)
+}
+
+fn create_dir(path: &Path) -> Result<(), AppError>
+{
+    fs::create_dir_all(path).map_err(|_| invalid("the archive could not be extracted"))
+}
+
+/// Écrit une entrée en comptant sa taille réelle : l'en-tête d'une entrée n'est pas fiable.
+fn write_file(reader: &mut impl Read, path: &Path, unpacked: &mut u64, max_unpacked_bytes: u64) -> Result<(), AppError>
+{
+    if let Some(parent) = path.parent()
+    {
+        create_dir(parent)?;
+    }
+
+    let remaining = max_unpacked_bytes.saturating_sub(*unpacked);
+    let mut file = fs::File::create(path).map_err(|_| invalid("the archive could not be extracted"))?;
+    let written = io::copy(&mut reader.take(remaining.saturating_add(1)), &mut file)
+        .map_err(|_| invalid("the archive is corrupted"))?;
+
+    *unpacked = unpacked.saturating_add(written);
+    if *unpacked > max_unpacked_bytes
+    {
+        return Err(invalid("the archive is too large once decompressed"));
+    }
+    Ok(())
+}
+
+/// Dossier unique à la racine de l'extraction, s'il peut servir de racine du site.
+fn site_root(destination: &Path) -> Result<Option<String>, AppError>
+{
+    let entries: Vec<fs::DirEntry> = fs::read_dir(destination)
+        .and_then(Iterator::collect)
+        .map_err(|_| AppError::InternalServerError)?;
+    if entries.is_empty()
+    {
+        return Err(invalid("the archive contains no files"));
+    }
+
+    let [entry] = entries.as_slice() else { return Ok(None) };
+    if !entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+    {
+        return Ok(None);
+    }
+
+    Ok(entry.file_name().to_str()
+        .filter(|name| validation_service::validate_source_root_dir(name).is_ok())
+        .map(str::to_string))
+}
+
+/// Nom d'origine, sans chemin ni caractère inattendu, pour l'enregistrer et l'afficher.
+fn sanitize_file_name(file_name: &str) -> String
+{
+    let base = file_name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let sanitized: String = base.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect();
+
+    if sanitized.is_empty() { "upload".to_string() } else { sanitized }
+}
+
+fn invalid(reason: &str) -> AppError
+{
+    ProjectErrorCode::InvalidUploadArchive(reason.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_relative_path()
+    {
+        assert_eq!(safe_relative_path("site/index.html"), Some(PathBuf::from("site/index.html")));
+        assert_eq!(safe_relative_path("./css/app.css"), Some(PathBuf::from("css/app.css")));
+        assert_eq!(safe_relative_path("./"), Some(PathBuf::new()));
+        assert_eq!(safe_relative_path("../etc/passwd"), None);
+        assert_eq!(safe_relative_path("site/../../etc/passwd"), None);
+        assert_eq!(safe_relative_path("/etc/passwd"), None);
+        assert_eq!(safe_relative_path("..\\windows\\system.ini"), None);
+    }
+
+    #[test]
+    fn test_uploaded_archive_name_and_format()
+    {
+        let archive = UploadedArchive::new("C:\\Users\\me\\Mon Site.TAR.GZ", Bytes::from_static(b"data")).unwrap();
+        assert_eq!(archive.file_name, "MonSite.TAR.GZ");
+        assert_eq!(archive.format, ArchiveFormat::TarGz);
+        assert!(archive.source_label().starts_with("MonSite.TAR.GZ#sha256:3a6eb079"));
+
+        assert!(UploadedArchive::new("site.rar", Bytes::from_static(b"data")).is_err());
+        assert!(UploadedArchive::new("site.zip", Bytes::new()).is_err());
+    }
+
+    #[test]
+    fn test_tar_gz_extraction_rejects_escaping_paths()
+    {
+        fn tar_gz(name: &str) -> Vec<u8>
+        {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(2);
+            header.set_mode(0o644);
+            header.set_entry_type(tar::EntryType::Regular);
+            // `set_path` refuse `..` : le nom est écrit directement, comme le ferait une archive forgée.
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_cksum();
+
+            let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+            builder.append(&header, &b"ok"[..]).unwrap();
+            builder.into_inner().unwrap().finish().unwrap()
+        }
+
+        let destination = tempfile::tempdir().unwrap();
+        let root = destination.path().join("root");
+        fs::create_dir(&root).unwrap();
+
+        assert!(extract_tar_gz(&tar_gz("../escaped.txt"), &root, 1024).is_err());
+        assert!(!destination.path().join("escaped.txt").exists());
+
+        extract_tar_gz(&tar_gz("site/index.html"), &root, 1024).unwrap();
+        assert_eq!(fs::read_to_string(root.join("site/index.html")).unwrap(), "ok");
+        assert_eq!(site_root(&root).unwrap().as_deref(), Some("site"));
+
+        assert!(extract_tar_gz(&tar_gz("big.html"), &root, 1).is_err());
+    }
+}
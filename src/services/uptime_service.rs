@@ -0,0 +1,313 @@
+//! Disponibilité des projets, calculée à partir des transitions démarré/arrêté relevées par
+//! l'écoute des événements Docker (`project_status_transitions`).
+//!
+//! Les transitions ne sont relevées que pendant que le backend tourne : chaque démarrage ouvre une
+//! session dont le dernier battement est enregistré toutes les minutes. Hors des sessions, l'état
+//! des projets est inconnu ; au début d'une session, l'état de chaque conteneur est relevé pour
+//! repartir d'un état connu.
+
+use std::time::Duration as StdDuration;
+
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use tokio::sync::broadcast;
+use tracing::{error, info};
+
+use crate::
+{
+    error::AppError,
+    model::{project::Project, usage::{BackendSession, ProjectUptime, StatusTransition}},
+    services::{docker_service, project_service, usage_service},
+    sse::types::ContainerStatus,
+    state::AppState,
+};
+
+/// Intervalle entre deux battements de la session du backend.
+const HEARTBEAT_INTERVAL: StdDuration = StdDuration::from_secs(60);
+/// Durée après le dernier battement pendant laquelle l'état des projets reste considéré comme connu.
+const HEARTBEAT_GRACE: Duration = Duration::seconds(120);
+
+pub async fn start_backend_heartbeat(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting backend heartbeat task");
+
+    let session_id: i64 = match sqlx::query_scalar("INSERT INTO backend_sessions DEFAULT VALUES RETURNING id")
+        .fetch_one(&state.db_pool)
+        .await
+    {
+        Ok(id) => id,
+        Err(e) =>
+        {
+            error!("Failed to open backend session, project uptime will count this run as unknown: {}", e);
+            return;
+        }
+    };
+
+    record_current_statuses(&state).await;
+
+    let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Backend heartbeat task shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        if let Err(e) = sqlx::query("UPDATE backend_sessions SET last_seen_at = NOW() WHERE id = $1")
+            .bind(session_id)
+            .execute(&state.db_pool)
+            .await
+        {
+            error!("Failed to record backend heartbeat: {}", e);
+        }
+        prune_sessions(&state.db_pool, state.config.usage_retention_days).await;
+    }
+}
+
+/// Relève l'état de chaque conteneur au démarrage : les transitions survenues pendant l'arrêt du
+/// backend n'ont pas été vues.
+async fn record_current_statuses(state: &AppState)
+{
+    let projects = match project_service::get_all_projects(&state.db_pool).await
+    {
+        Ok(projects) => projects,
+        Err(e) =>
+        {
+            error!("Failed to list projects for the startup status snapshot: {}", e);
+            return;
+        }
+    };
+
+    for project in projects
+    {
+        let Ok(status) = docker_service::get_container_status(&state.docker_client, &project.container_name).await else { continue };
+        usage_service::record_transition(&state.db_pool, &project, status == Some(ContainerStatus::Running)).await;
+    }
+}
+
+async fn prune_sessions(pool: &PgPool, retention_days: i32)
+{
+    match sqlx::query("DELETE FROM backend_sessions WHERE last_seen_at < NOW() - make_interval(days => $1)")
+        .bind(retention_days)
+        .execute(pool)
+        .await
+    {
+        Ok(result) if result.rows_affected() > 0 => info!("Pruned {} row(s) from backend_sessions", result.rows_affected()),
+        Ok(_) => {}
+        Err(e) => error!("Failed to prune backend_sessions: {}", e),
+    }
+}
+
+/// Disponibilité du projet sur les `days` derniers jours, à partir de sa création au plus tôt.
+pub async fn project_uptime(pool: &PgPool, project: &Project, days: i64) -> Result<ProjectUptime, AppError>
+{
+    let to = OffsetDateTime::now_utc();
+    let from = (to - Duration::days(days)).max(project.created_at);
+
+    let sessions: Vec<BackendSession> = sqlx::query_as(
+        "SELECT started_at, last_seen_at FROM backend_sessions \
+         WHERE last_seen_at >= $1 AND started_at < $2 \
+         ORDER BY started_at"
+    )
+    .bind(from - HEARTBEAT_GRACE)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to fetch backend sessions: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    // L'état au début de la première session est relevé dans cette session, éventuellement avant `from`.
+    let since = sessions.first().map_or(from, |session| session.started_at.min(from));
+    let transitions: Vec<StatusTransition> = sqlx::query_as(
+        "SELECT project_id, owner, is_running, occurred_at FROM project_status_transitions \
+         WHERE project_id = $1 AND occurred_at >= $2 AND occurred_at < $3 \
+         ORDER BY occurred_at"
+    )
+    .bind(project.id)
+    .bind(since)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to fetch status transitions of project ID {}: {}", project.id, e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(compute_uptime(&transitions, &sessions, from, to))
+}
+
+/// Temps démarré, arrêté et inconnu sur `[from, to)`. Dans une session, l'état est celui de la
+/// dernière transition relevée depuis son début ; il est inconnu avant la première.
+fn compute_uptime(transitions: &[StatusTransition], sessions: &[BackendSession], from: OffsetDateTime, to: OffsetDateTime) -> ProjectUptime
+{
+    let mut tally = UptimeTally::default();
+
+    for (index, session) in sessions.iter().enumerate()
+    {
+        let mut session_end = session.last_seen_at + HEARTBEAT_GRACE;
+        if let Some(next) = sessions.get(index + 1)
+        {
+            session_end = session_end.min(next.started_at);
+        }
+
+        let (start, end) = (session.started_at.max(from), session_end.min(to));
+        if start >= end
+        {
+            continue;
+        }
+
+        let mut is_running = transitions.iter()
+            .rfind(|t| t.occurred_at >= session.started_at && t.occurred_at <= start)
+            .map(|t| t.is_running);
+        let mut cursor = start;
+
+        for transition in transitions.iter().filter(|t| t.occurred_at > start && t.occurred_at < end)
+        {
+            tally.add(is_running, transition.occurred_at - cursor);
+            is_running = Some(transition.is_running);
+            cursor = transition.occurred_at;
+        }
+        tally.add(is_running, end - cursor);
+        tally.close_outage();
+    }
+
+    let known = tally.up + tally.down;
+    ProjectUptime
+    {
+        from,
+        to,
+        uptime_percent: (known > Duration::ZERO).then(|| tally.up / known * 100.0),
+        up_seconds: tally.up.whole_seconds(),
+        down_seconds: tally.down.whole_seconds(),
+        unknown_seconds: (to - from - known).whole_seconds(),
+        outages: tally.outages,
+        longest_outage_seconds: tally.longest_outage.whole_seconds(),
+    }
+}
+
+#[derive(Default)]
+struct UptimeTally
+{
+    up: Duration,
+    down: Duration,
+    outages: u32,
+    longest_outage: Duration,
+    current_outage: Option<Duration>,
+}
+
+impl UptimeTally
+{
+    /// Compte `length` dans l'état `is_running`, inconnu si `None`.
+    fn add(&mut self, is_running: Option<bool>, length: Duration)
+    {
+        if length <= Duration::ZERO
+        {
+            return;
+        }
+
+        match is_running
+        {
+            Some(true) =>
+            {
+                self.up += length;
+                self.close_outage();
+            }
+            Some(false) =>
+            {
+                self.down += length;
+                *self.current_outage.get_or_insert(Duration::ZERO) += length;
+            }
+            None => self.close_outage(),
+        }
+    }
+
+    fn close_outage(&mut self)
+    {
+        if let Some(outage) = self.current_outage.take()
+        {
+            self.outages += 1;
+            self.longest_outage = self.longest_outage.max(outage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hours: i64) -> OffsetDateTime
+    {
+        OffsetDateTime::from_unix_timestamp(1_790_000_000).unwrap() + Duration::hours(hours)
+    }
+
+    fn transition(hours: i64, is_running: bool) -> StatusTransition
+    {
+        StatusTransition { project_id: 1, owner: "alice".to_string(), is_running, occurred_at: at(hours) }
+    }
+
+    fn session(start_hours: i64, last_seen_hours: i64) -> BackendSession
+    {
+        BackendSession { started_at: at(start_hours), last_seen_at: at(last_seen_hours) - HEARTBEAT_GRACE }
+    }
+
+    #[test]
+    fn test_outages_are_counted_within_a_continuous_session()
+    {
+        let transitions = [transition(0, true), transition(10, false), transition(12, true), transition(20, false), transition(21, true)];
+
+        let uptime = compute_uptime(&transitions, &[session(0, 100)], at(0), at(100));
+
+        assert_eq!(uptime.up_seconds, 97 * 3600);
+        assert_eq!(uptime.down_seconds, 3 * 3600);
+        assert_eq!(uptime.unknown_seconds, 0);
+        assert_eq!(uptime.uptime_percent, Some(97.0));
+        assert_eq!(uptime.outages, 2);
+        assert_eq!(uptime.longest_outage_seconds, 2 * 3600);
+    }
+
+    #[test]
+    fn test_backend_downtime_is_unknown_rather_than_down()
+    {
+        // Backend arrêté de 40 h à 60 h ; l'état est relevé à nouveau au redémarrage.
+        let transitions = [transition(0, true), transition(30, false), transition(60, true)];
+
+        let uptime = compute_uptime(&transitions, &[session(0, 40), session(60, 100)], at(0), at(100));
+
+        assert_eq!(uptime.up_seconds, 70 * 3600);
+        assert_eq!(uptime.down_seconds, 10 * 3600);
+        assert_eq!(uptime.unknown_seconds, 20 * 3600);
+        assert_eq!(uptime.outages, 1);
+        assert_eq!(uptime.longest_outage_seconds, 10 * 3600);
+    }
+
+    #[test]
+    fn test_state_before_the_window_carries_over_only_within_its_session()
+    {
+        let transitions = [transition(0, true), transition(50, false)];
+
+        // Transition d'une session antérieure : état inconnu jusqu'au relevé suivant.
+        let uptime = compute_uptime(&transitions, &[session(0, 10), session(20, 100)], at(30), at(100));
+        assert_eq!((uptime.up_seconds, uptime.down_seconds, uptime.unknown_seconds), (0, 50 * 3600, 20 * 3600));
+
+        let uptime = compute_uptime(&transitions, &[session(0, 100)], at(30), at(100));
+        assert_eq!((uptime.up_seconds, uptime.down_seconds, uptime.unknown_seconds), (20 * 3600, 50 * 3600, 0));
+    }
+
+    #[test]
+    fn test_no_known_time_has_no_percentage()
+    {
+        let uptime = compute_uptime(&[], &[], at(0), at(24));
+
+        assert_eq!(uptime.uptime_percent, None);
+        assert_eq!(uptime.unknown_seconds, 24 * 3600);
+    }
+}
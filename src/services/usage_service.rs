@@ -0,0 +1,395 @@
+//! Rapports d'utilisation des ressources par propriétaire (refacturation, équité entre étudiants).
+//!
+//! Deux historiques alimentent les rapports : les transitions démarré/arrêté relevées par l'écoute
+//! des événements Docker (heures de conteneur) et des échantillons périodiques des projets démarrés
+//! (CPU, mémoire, stockage). Les deux sont purgés après `USAGE_RETENTION_DAYS` jours.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+use tracing::{debug, error, info, warn};
+
+use crate::
+{
+    error::AppError,
+    model::{project::Project, usage::{OwnerSamples, OwnerUsage, StatusTransition, UsageReport}},
+    services::{database_service, docker_service, project_service},
+    sse::types::ContainerStatus,
+    state::AppState,
+};
+
+/// Intervalle entre deux échantillons d'utilisation.
+const USAGE_SAMPLE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Enregistre le démarrage ou l'arrêt du conteneur courant d'un projet. Renvoie l'identifiant de la transition.
+pub async fn record_transition(pool: &PgPool, project: &Project, is_running: bool) -> Option<i64>
+{
+    if project.owner == project_service::SELFTEST_OWNER
+    {
+        return None;
+    }
+
+    sqlx::query_scalar("INSERT INTO project_status_transitions (project_id, owner, is_running) VALUES ($1, $2, $3) RETURNING id")
+        .bind(project.id)
+        .bind(&project.owner)
+        .bind(is_running)
+        .fetch_one(pool)
+        .await
+        .inspect_err(|e| error!("Failed to record status transition of project ID {}: {}", project.id, e))
+        .ok()
+}
+
+/// Clôt le temps de fonctionnement d'un projet supprimé, à partir de sa dernière transition connue.
+pub async fn record_project_removed(pool: &PgPool, project_id: i32)
+{
+    if let Err(e) = sqlx::query(
+        "INSERT INTO project_status_transitions (project_id, owner, is_running) \
+         SELECT project_id, owner, FALSE FROM project_status_transitions \
+         WHERE project_id = $1 ORDER BY occurred_at DESC LIMIT 1"
+    )
+    .bind(project_id)
+    .execute(pool)
+    .await
+    {
+        error!("Failed to record removal of project ID {}: {}", project_id, e);
+    }
+}
+
+pub async fn start_usage_sampler(state: AppState, mut shutdown_signal: broadcast::Receiver<()>)
+{
+    info!("Starting usage sampler task");
+    let mut interval = tokio::time::interval(USAGE_SAMPLE_INTERVAL);
+
+    loop
+    {
+        tokio::select!
+        {
+            _ = shutdown_signal.recv() =>
+            {
+                info!("Usage sampler task shutting down");
+                return;
+            }
+            _ = interval.tick() => {}
+        }
+
+        sample_usage(&state).await;
+        prune_usage(&state.db_pool, state.config.usage_retention_days).await;
+    }
+}
+
+async fn sample_usage(state: &AppState)
+{
+    let projects = match project_service::get_all_projects(&state.db_pool).await
+    {
+        Ok(projects) => projects,
+        Err(e) =>
+        {
+            error!("Failed to list projects for usage sampling: {}", e);
+            return;
+        }
+    };
+
+    // Le stockage est facultatif : un échec ne doit pas faire perdre le CPU et la mémoire.
+    let volume_sizes = docker_service::get_volume_sizes(&state.docker_client).await.unwrap_or_else(|e|
+    {
+        warn!("Usage samples will not include volume sizes: {}", e);
+        HashMap::new()
+    });
+    let database_sizes = database_service::get_database_sizes_by_owner(&state.db_pool, &state.mariadb_pool).await.unwrap_or_else(|e|
+    {
+        warn!("Usage samples will not include database sizes: {}", e);
+        HashMap::new()
+    });
+
+    let interval_seconds = i32::try_from(USAGE_SAMPLE_INTERVAL.as_secs()).unwrap_or(i32::MAX);
+    let mut sampled = 0;
+
+    for project in projects
+    {
+        if !matches!(docker_service::get_container_status(&state.docker_client, &project.container_name).await, Ok(Some(ContainerStatus::Running)))
+        {
+            continue;
+        }
+
+        let metrics = match docker_service::sample_container_metrics(&state.docker_client, &project.container_name).await
+        {
+            Ok(metrics) => metrics,
+            Err(e) =>
+            {
+                debug!("Could not sample usage of container '{}': {}", project.container_name, e);
+                continue;
+            }
+        };
+
+        let volume_bytes = project.volume_name.as_ref().and_then(|name| volume_sizes.get(name).copied());
+
+        let inserted = sqlx::query(
+            "INSERT INTO project_usage_samples \
+             (project_id, owner, cpu_percent, memory_bytes, volume_bytes, database_bytes, interval_seconds) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)"
+        )
+        .bind(project.id)
+        .bind(&project.owner)
+        .bind(metrics.cpu_usage_percent)
+        .bind(i64::try_from(metrics.memory_usage_bytes.get()).unwrap_or(i64::MAX))
+        .bind(volume_bytes)
+        .bind(database_sizes.get(&project.owner).copied())
+        .bind(interval_seconds)
+        .execute(&state.db_pool)
+        .await;
+
+        match inserted
+        {
+            Ok(_) => sampled += 1,
+            Err(e) => error!("Failed to record usage sample of project ID {}: {}", project.id, e),
+        }
+    }
+
+    debug!("Recorded {} usage sample(s)", sampled);
+}
+
+async fn prune_usage(pool: &PgPool, retention_days: i32)
+{
+    for table in ["project_usage_samples", "project_status_transitions"]
+    {
+        let column = if table == "project_usage_samples" { "sampled_at" } else { "occurred_at" };
+        let query = format!("DELETE FROM {table} WHERE {column} < NOW() - make_interval(days => $1)");
+
+        match sqlx::query(&query).bind(retention_days).execute(pool).await
+        {
+            Ok(result) if result.rows_affected() > 0 => info!("Pruned {} row(s) from {}", result.rows_affected(), table),
+            Ok(_) => {}
+            Err(e) => error!("Failed to prune {}: {}", table, e),
+        }
+    }
+}
+
+/// Agrège l'utilisation de chaque propriétaire sur `[from, to)`.
+pub async fn usage_report(pool: &PgPool, from: OffsetDateTime, to: OffsetDateTime) -> Result<UsageReport, AppError>
+{
+    // Agrégat par projet puis par propriétaire : la mémoire et les volumes de ses projets
+    // s'additionnent, sa base (une par propriétaire) n'est comptée qu'une fois.
+    let samples: Vec<OwnerSamples> = sqlx::query_as(
+        "SELECT owner, \
+                COALESCE(SUM(cpu_seconds), 0)::DOUBLE PRECISION AS cpu_seconds, \
+                COALESCE(SUM(avg_memory_bytes), 0)::BIGINT AS avg_memory_bytes, \
+                COALESCE(SUM(avg_volume_bytes), 0)::BIGINT AS avg_volume_bytes, \
+                COALESCE(MAX(avg_database_bytes), 0)::BIGINT AS avg_database_bytes \
+         FROM ( \
+             SELECT project_id, owner, \
+                    SUM(cpu_percent / 100 * interval_seconds) AS cpu_seconds, \
+                    AVG(memory_bytes) AS avg_memory_bytes, \
+                    AVG(volume_bytes) AS avg_volume_bytes, \
+                    AVG(database_bytes) AS avg_database_bytes \
+             FROM project_usage_samples \
+             WHERE sampled_at >= $1 AND sampled_at < $2 \
+             GROUP BY project_id, owner \
+         ) per_project \
+         GROUP BY owner"
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to aggregate usage samples: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    // L'état de chaque projet au début de la période est sa dernière transition antérieure.
+    let transitions: Vec<StatusTransition> = sqlx::query_as(
+        "SELECT project_id, owner, is_running, occurred_at FROM ( \
+             SELECT DISTINCT ON (project_id) project_id, owner, is_running, occurred_at \
+             FROM project_status_transitions WHERE occurred_at < $1 \
+             ORDER BY project_id, occurred_at DESC \
+         ) before_period \
+         UNION ALL \
+         SELECT project_id, owner, is_running, occurred_at \
+         FROM project_status_transitions WHERE occurred_at >= $1 AND occurred_at < $2 \
+         ORDER BY project_id, occurred_at"
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to fetch status transitions: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    let mut owners: BTreeMap<String, OwnerUsage> = samples.into_iter()
+        .map(|s| (s.owner.clone(), OwnerUsage
+        {
+            owner: s.owner,
+            cpu_seconds: s.cpu_seconds,
+            avg_memory_bytes: s.avg_memory_bytes,
+            container_hours: 0.0,
+            avg_volume_bytes: s.avg_volume_bytes,
+            avg_database_bytes: s.avg_database_bytes,
+        }))
+        .collect();
+
+    let end = to.min(OffsetDateTime::now_utc());
+    for project_transitions in transitions.chunk_by(|a, b| a.project_id == b.project_id)
+    {
+        let seconds = running_seconds(project_transitions, from, end);
+        if seconds <= 0.0
+        {
+            continue;
+        }
+
+        // Après un transfert de propriété, le projet est imputé à son propriétaire actuel.
+        let owner = &project_transitions[project_transitions.len() - 1].owner;
+        owners.entry(owner.clone())
+            .or_insert_with(|| OwnerUsage
+            {
+                owner: owner.clone(),
+                cpu_seconds: 0.0,
+                avg_memory_bytes: 0,
+                container_hours: 0.0,
+                avg_volume_bytes: 0,
+                avg_database_bytes: 0,
+            })
+            .container_hours += seconds / 3600.0;
+    }
+
+    Ok(UsageReport { from, to, owners: owners.into_values().collect() })
+}
+
+/// Durée, en secondes, pendant laquelle le conteneur était démarré sur `[from, end)`.
+///
+/// Les transitions sont triées par date ; celles antérieures à `from` donnent l'état initial.
+/// Les transitions répétées (deux démarrages de suite) sont ignorées.
+fn running_seconds(transitions: &[StatusTransition], from: OffsetDateTime, end: OffsetDateTime) -> f64
+{
+    if end <= from
+    {
+        return 0.0;
+    }
+
+    let mut total = time::Duration::ZERO;
+    let mut running_since: Option<OffsetDateTime> = None;
+
+    for transition in transitions
+    {
+        let at = transition.occurred_at.clamp(from, end);
+        match (transition.is_running, running_since)
+        {
+            (true, None) => running_since = Some(at),
+            (false, Some(since)) =>
+            {
+                total += at - since;
+                running_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(since) = running_since
+    {
+        total += end - since;
+    }
+
+    total.as_seconds_f64()
+}
+
+/// Rendu CSV du rapport, une ligne par propriétaire.
+#[must_use]
+pub fn report_to_csv(report: &UsageReport) -> String
+{
+    let mut csv = String::from("owner,cpu_seconds,avg_memory_bytes,container_hours,avg_volume_bytes,avg_database_bytes\n");
+    for usage in &report.owners
+    {
+        csv.push_str(&format!(
+            "{},{:.3},{},{:.3},{},{}\n",
+            csv_field(&usage.owner),
+            usage.cpu_seconds,
+            usage.avg_memory_bytes,
+            usage.container_hours,
+            usage.avg_volume_bytes,
+            usage.avg_database_bytes,
+        ));
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String
+{
+    if value.contains([',', '"', '\n', '\r'])
+    {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    }
+    else
+    {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `hours` heures après le 1er octobre 2026 à minuit UTC.
+    fn at(hours: i64) -> OffsetDateTime
+    {
+        OffsetDateTime::from_unix_timestamp(1_790_812_800).unwrap() + time::Duration::hours(hours)
+    }
+
+    fn transition(is_running: bool, occurred_at: OffsetDateTime) -> StatusTransition
+    {
+        StatusTransition { project_id: 1, owner: "alice".to_string(), is_running, occurred_at }
+    }
+
+    #[test]
+    fn test_running_time_is_clipped_to_the_period()
+    {
+        let transitions = [
+            // Démarré avant la période : compte à partir de `from`.
+            transition(true, at(-12)),
+            transition(false, at(2)),
+            transition(true, at(10)),
+            transition(true, at(11)),
+            transition(false, at(12)),
+            // Toujours démarré à la fin : compte jusqu'à `end`.
+            transition(true, at(22)),
+        ];
+
+        assert!((running_seconds(&transitions, at(0), at(24)) - 6.0 * 3600.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_stopped_before_period_counts_nothing()
+    {
+        let transitions = [transition(false, at(-12))];
+
+        assert!(running_seconds(&transitions, at(0), at(24)).abs() < f64::EPSILON);
+        assert!(running_seconds(&[transition(true, at(0))], at(24), at(0)).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_csv_escapes_owner()
+    {
+        let report = UsageReport
+        {
+            from: at(0),
+            to: at(24),
+            owners: vec![OwnerUsage
+            {
+                owner: "doe, \"jd\"".to_string(),
+                cpu_seconds: 12.5,
+                avg_memory_bytes: 1024,
+                container_hours: 1.5,
+                avg_volume_bytes: 0,
+                avg_database_bytes: 2048,
+            }],
+        };
+
+        let csv = report_to_csv(&report);
+        assert_eq!(csv.lines().nth(1), Some("\"doe, \"\"jd\"\"\",12.500,1024,1.500,0,2048"));
+    }
+}
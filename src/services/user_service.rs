@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::{error::AppError, model::user::{User, UserDisplay}};
+
+/// Enregistre la connexion d'un utilisateur (création ou mise à jour de `last_login_at`).
+///
+/// Best-effort : un échec est journalisé mais n'empêche pas la connexion.
+pub async fn record_login(pool: &PgPool, user: &User)
+{
+    let result = sqlx::query(
+        "INSERT INTO users (login, name, email, last_login_at) VALUES ($1, $2, $3, NOW()) \
+         ON CONFLICT (login) DO UPDATE SET name = EXCLUDED.name, email = EXCLUDED.email, last_login_at = NOW(), updated_at = NOW()"
+    )
+    .bind(&user.login)
+    .bind(&user.name)
+    .bind(&user.email)
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result
+    {
+        error!("Failed to record login of user '{}': {}", user.login, e);
+    }
+}
+
+/// Noms affichables de plusieurs logins en une requête ; un login inconnu est renvoyé tel quel.
+pub async fn get_display_names(pool: &PgPool, logins: &[String]) -> Result<HashMap<String, String>, AppError>
+{
+    let users: Vec<UserDisplay> = sqlx::query_as(
+        "SELECT l.login, COALESCE(NULLIF(u.name, ''), l.login) AS name \
+         FROM UNNEST($1::varchar[]) AS l(login) \
+         LEFT JOIN users u ON u.login = l.login"
+    )
+    .bind(logins)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to fetch display names: {}", e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(users.into_iter().map(|user| (user.login, user.name)).collect())
+}
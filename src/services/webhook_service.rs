@@ -0,0 +1,198 @@
+//! Webhooks des projets et historique de leurs livraisons. L'envoi est fait par
+//! [`notification_dispatch_service`](crate::services::notification_dispatch_service).
+
+use base64::prelude::*;
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::
+{
+    error::{AppError, ProjectErrorCode},
+    model::webhook::{ProjectWebhook, WebhookDelivery, WebhookEvent},
+    services::crypto_service,
+};
+
+/// Nombre maximal de webhooks par projet.
+pub const MAX_WEBHOOKS_PER_PROJECT: i64 = 5;
+/// Longueurs acceptées pour le secret de signature.
+pub const MIN_SECRET_LENGTH: usize = 16;
+pub const MAX_SECRET_LENGTH: usize = 256;
+/// Nombre de livraisons conservées par webhook.
+const DELIVERIES_KEPT: i64 = 100;
+
+pub fn validate_secret(secret: &str) -> Result<(), AppError>
+{
+    if !(MIN_SECRET_LENGTH..=MAX_SECRET_LENGTH).contains(&secret.chars().count())
+    {
+        return Err(AppError::BadRequest(format!("The webhook secret must be between {MIN_SECRET_LENGTH} and {MAX_SECRET_LENGTH} characters long.")));
+    }
+    Ok(())
+}
+
+pub async fn list_webhooks(pool: &PgPool, project_id: i32) -> Result<Vec<ProjectWebhook>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_webhooks WHERE project_id = $1 ORDER BY id")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list webhooks of project ID {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn get_webhook(pool: &PgPool, project_id: i32, webhook_id: i32) -> Result<Option<ProjectWebhook>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_webhooks WHERE id = $1 AND project_id = $2")
+        .bind(webhook_id)
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch webhook ID {} of project ID {}: {}", webhook_id, project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Ajoute un webhook. L'URL et le secret doivent avoir été validés ; le secret est chiffré.
+pub async fn add_webhook(
+    pool: &PgPool,
+    encryption_key: &[u8],
+    project_id: i32,
+    url: &str,
+    secret: &str,
+    events: &[WebhookEvent],
+    created_by: &str,
+) -> Result<ProjectWebhook, AppError>
+{
+    let db_error = |e: sqlx::Error|
+    {
+        error!("Failed to add webhook to project ID {}: {}", project_id, e);
+        AppError::InternalServerError
+    };
+
+    let encrypted_secret = BASE64_STANDARD.encode(crypto_service::encrypt(secret, encryption_key)?);
+    let events: Vec<&str> = events.iter().map(|event| event.as_str()).collect();
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+
+    // Verrou sur le projet : deux ajouts simultanés ne peuvent pas dépasser la limite.
+    sqlx::query("SELECT id FROM projects WHERE id = $1 FOR UPDATE")
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM project_webhooks WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if count >= MAX_WEBHOOKS_PER_PROJECT
+    {
+        return Err(ProjectErrorCode::WebhookLimitReached(MAX_WEBHOOKS_PER_PROJECT).into());
+    }
+
+    let webhook = sqlx::query_as(
+        "INSERT INTO project_webhooks (project_id, url, encrypted_secret, events, created_by) \
+         VALUES ($1, $2, $3, $4, $5) RETURNING *"
+    )
+        .bind(project_id)
+        .bind(url)
+        .bind(&encrypted_secret)
+        .bind(&events)
+        .bind(created_by)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+    Ok(webhook)
+}
+
+pub async fn remove_webhook(pool: &PgPool, project_id: i32, webhook_id: i32) -> Result<Option<ProjectWebhook>, AppError>
+{
+    sqlx::query_as("DELETE FROM project_webhooks WHERE id = $1 AND project_id = $2 RETURNING *")
+        .bind(webhook_id)
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to remove webhook ID {} of project ID {}: {}", webhook_id, project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub fn decrypt_secret(webhook: &ProjectWebhook, encryption_key: &[u8]) -> Result<String, AppError>
+{
+    let encrypted = BASE64_STANDARD.decode(&webhook.encrypted_secret).map_err(|_| AppError::InternalServerError)?;
+    crypto_service::decrypt(&encrypted, encryption_key)
+}
+
+/// Dernières livraisons du webhook, de la plus récente à la plus ancienne.
+pub async fn list_deliveries(pool: &PgPool, webhook_id: i32) -> Result<Vec<WebhookDelivery>, AppError>
+{
+    sqlx::query_as("SELECT * FROM project_webhook_deliveries WHERE webhook_id = $1 ORDER BY id DESC")
+        .bind(webhook_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to list deliveries of webhook ID {}: {}", webhook_id, e);
+            AppError::InternalServerError
+        })
+}
+
+/// Résultat d'une tentative de livraison.
+pub struct DeliveryOutcome
+{
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl DeliveryOutcome
+{
+    #[must_use]
+    pub const fn succeeded(&self) -> bool
+    {
+        self.error.is_none()
+    }
+}
+
+/// Enregistre une tentative et ne conserve que les [`DELIVERIES_KEPT`] dernières du webhook.
+/// Best-effort : les échecs sont journalisés.
+pub async fn record_delivery(pool: &PgPool, webhook_id: i32, event: WebhookEvent, outcome: &DeliveryOutcome)
+{
+    if let Err(e) = sqlx::query(
+        "INSERT INTO project_webhook_deliveries (webhook_id, event_type, attempt, status_code, success, error) \
+         VALUES ($1, $2, $3, $4, $5, $6)"
+    )
+        .bind(webhook_id)
+        .bind(event.as_str())
+        .bind(outcome.attempt)
+        .bind(outcome.status_code)
+        .bind(outcome.succeeded())
+        .bind(&outcome.error)
+        .execute(pool)
+        .await
+    {
+        error!("Failed to record delivery of webhook ID {}: {}", webhook_id, e);
+        return;
+    }
+
+    if let Err(e) = sqlx::query(
+        "DELETE FROM project_webhook_deliveries WHERE webhook_id = $1 AND id NOT IN \
+         (SELECT id FROM project_webhook_deliveries WHERE webhook_id = $1 ORDER BY id DESC LIMIT $2)"
+    )
+        .bind(webhook_id)
+        .bind(DELIVERIES_KEPT)
+        .execute(pool)
+        .await
+    {
+        error!("Failed to prune deliveries of webhook ID {}: {}", webhook_id, e);
+    }
+}
@@ -1,5 +1,7 @@
+use crate::error::{AppError, ProjectErrorCode};
 use crate::model::project::ProjectMetrics;
-use crate::sse::types::{ContainerStatus, ContainerStatusEvent, DeploymentEvent, DeploymentStage, MetricsEvent, SseEvent};
+use crate::model::timeline::TimelineEntry;
+use crate::sse::types::{ContainerStatusEvent, DeploymentEvent, DeploymentStage, DownProjectChange, DownProjectsEvent, MetricsEvent, SseEvent, SystemEvent, TimelineEvent};
 use crate::state::AppState;
 
 pub async fn emit_creation_deployment_stage(
@@ -40,22 +42,9 @@ pub async fn emit_deployment_stage(
 }
 
 
-pub async fn emit_container_status(
-    state: &AppState,
-    project_id: i32,
-    project_name: String,
-    container_name: String,
-    status: ContainerStatus,
-)
+pub async fn emit_container_status(state: &AppState, event: ContainerStatusEvent)
 {
-    let event = SseEvent::ContainerStatus(ContainerStatusEvent::new(
-        project_id,
-        project_name,
-        container_name,
-        status,
-    ));
-    
-    state.sse_manager.emit_to_project(project_id, event).await;
+    state.sse_manager.emit_to_project(event.project_id, SseEvent::ContainerStatus(event)).await;
 }
 
 pub async fn emit_metrics(
@@ -72,4 +61,43 @@ pub async fn emit_metrics(
     ));
     
     state.sse_manager.emit_to_project(project_id, event).await;
-}
\ No newline at end of file
+}
+
+pub async fn emit_timeline_entry(state: &AppState, project_id: i32, entry: TimelineEntry)
+{
+    state.sse_manager.emit_to_project(project_id, SseEvent::Timeline(TimelineEvent { project_id, entry })).await;
+}
+
+pub async fn emit_creation_event(state: &AppState, user_login: &str, event: SystemEvent)
+{
+    state.sse_manager.emit_to_creation(user_login, SseEvent::System(event)).await;
+}
+
+pub async fn emit_project_event(state: &AppState, project_id: i32, event: SystemEvent)
+{
+    state.sse_manager.emit_to_project(project_id, SseEvent::System(event)).await;
+}
+
+pub fn emit_admin_event(state: &AppState, event: SystemEvent)
+{
+    state.sse_manager.emit_to_admins(SseEvent::System(event));
+}
+
+/// Alerte les administrateurs lorsque `error` vient du réseau Docker introuvable : aucun conteneur
+/// de projet ne peut plus être créé tant qu'il n'est pas recréé.
+pub fn emit_if_network_missing(state: &AppState, error: &AppError)
+{
+    if matches!(error, AppError::ProjectError(ProjectErrorCode::DockerNetworkNotFound))
+    {
+        let network = &state.config.docker_network;
+        tracing::error!("Docker network '{}' does not exist, every deployment will fail until it is recreated", network);
+        emit_admin_event(state, SystemEvent::error(format!(
+            "The Docker network '{network}' does not exist: no project container can be created until it is recreated."
+        )).with_context(serde_json::json!({ "network": network })));
+    }
+}
+
+pub fn emit_down_projects_changed(state: &AppState, down_count: usize, change: DownProjectChange)
+{
+    state.sse_manager.emit_to_admins(SseEvent::DownProjectsChanged(DownProjectsEvent::changed(down_count, change)));
+}
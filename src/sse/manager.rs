@@ -1,10 +1,39 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
-use tokio::{sync::{RwLock, broadcast}, time::interval};
-use tracing::{debug, error, info};
+use std::{collections::{HashMap, HashSet}, future::Future, sync::Arc, time::Duration};
+use tokio::{sync::{OwnedSemaphorePermit, RwLock, Semaphore, broadcast::{self, error::RecvError}, mpsc}, time::interval};
+use tracing::{debug, error, info, warn};
 
 use crate::sse::types::SseEvent;
 
 const BROADCAST_CAPACITY: usize = 1000;
+/// Nombre maximal de flux SSE ouverts simultanément, par défaut.
+pub const DEFAULT_MAX_CONNECTIONS: usize = 1000;
+/// Types d'événements pouvant être conservés faute d'abonné ; métriques et logs, trop fréquents, n'en font pas partie.
+pub const PERSISTABLE_EVENT_TYPES: [&str; 4] = ["deployment", "container_status", "system", "timeline"];
+
+/// Destinataire d'un événement émis sans abonné.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MissedEventTarget
+{
+    Project(i32),
+    /// Canal de création (et notifications personnelles) d'un utilisateur.
+    User(String),
+}
+
+/// Événement qui aurait été perdu faute d'abonné, transmis à la tâche qui le conserve.
+#[derive(Debug, Clone)]
+pub struct MissedEvent
+{
+    pub target: MissedEventTarget,
+    pub event: SseEvent,
+}
+
+/// Conservation des événements émis sans abonné, pour les types configurés.
+#[derive(Clone)]
+struct MissedEventHook
+{
+    event_types: HashSet<String>,
+    tx: mpsc::Sender<MissedEvent>,
+}
 
 #[derive(Clone)]
 pub struct SseManager 
@@ -15,20 +44,95 @@ pub struct SseManager
     /// Canaux temporaires pour les créations en cours (`user_login` -> sender)
     /// Utilisé pendant /projects/create avant que le projet n'existe
     creation_channels: Arc<RwLock<HashMap<String, broadcast::Sender<SseEvent>>>>,
+
+    /// Logs suivis en continu par projet (`project_id` -> sender)
+    /// Chaque canal est alimenté par une seule tâche de suivi, qui le retire quand il n'a plus d'abonné
+    log_channels: Arc<RwLock<HashMap<i32, broadcast::Sender<SseEvent>>>>,
+
+    /// Canal unique partagé par les administrateurs (erreurs des tâches de fond, ...)
+    admin_channel: broadcast::Sender<SseEvent>,
+
+    /// Demandes de fermeture de tous les flux d'un utilisateur (`user_login`)
+    user_disconnects: broadcast::Sender<String>,
+
+    /// Places de connexion : une par flux SSE ouvert, tous canaux confondus
+    connections: Arc<Semaphore>,
+    max_connections: usize,
+
+    /// Événements émis sans abonné à conserver, si activé
+    missed_events: Option<MissedEventHook>,
+}
+
+/// Place de connexion SSE, rendue lorsque le flux qui la détient est fermé.
+pub struct SseConnectionGuard
+{
+    _permit: OwnedSemaphorePermit,
 }
 
 impl SseManager 
 {
     #[must_use] 
-    pub fn new() -> Self 
+    pub fn new(max_connections: usize) -> Self 
     {
         Self 
         {
             project_channels: Arc::new(RwLock::new(HashMap::new())),
             creation_channels: Arc::new(RwLock::new(HashMap::new())),
+            log_channels: Arc::new(RwLock::new(HashMap::new())),
+            admin_channel: broadcast::channel(BROADCAST_CAPACITY).0,
+            user_disconnects: broadcast::channel(BROADCAST_CAPACITY).0,
+            connections: Arc::new(Semaphore::new(max_connections)),
+            max_connections,
+            missed_events: None,
         }
     }
 
+    /// Transmet à `tx` les événements des types `event_types` émis sur un canal de projet ou de création
+    /// sans abonné, au lieu de les perdre.
+    #[must_use]
+    pub fn with_missed_events(mut self, event_types: HashSet<String>, tx: mpsc::Sender<MissedEvent>) -> Self
+    {
+        self.missed_events = Some(MissedEventHook { event_types, tx });
+        self
+    }
+
+    fn keep_missed_event(&self, target: MissedEventTarget, event: SseEvent)
+    {
+        let Some(hook) = &self.missed_events else { return };
+        if !event.is_persistable() || !hook.event_types.contains(event.event_type())
+        {
+            return;
+        }
+
+        // Jamais bloquant : l'émission ne doit pas attendre la base.
+        if let Err(e) = hook.tx.try_send(MissedEvent { target, event })
+        {
+            warn!("Missed event could not be queued for persistence: {}", e);
+        }
+    }
+
+    /// Réserve une place pour un nouveau flux ; `None` si la limite est atteinte.
+    ///
+    /// À appeler avant de s'abonner, et à garder dans le flux jusqu'à sa fermeture.
+    #[must_use]
+    pub fn try_acquire_connection(&self) -> Option<SseConnectionGuard>
+    {
+        Arc::clone(&self.connections).try_acquire_owned().ok().map(|permit| SseConnectionGuard { _permit: permit })
+    }
+
+    /// Nombre de flux SSE ouverts.
+    #[must_use]
+    pub fn active_connections(&self) -> usize
+    {
+        self.max_connections - self.connections.available_permits()
+    }
+
+    #[must_use]
+    pub const fn max_connections(&self) -> usize
+    {
+        self.max_connections
+    }
+
     pub async fn project_subscriber_count(&self, project_id: i32) -> usize 
     {
         let map = self.project_channels.read().await;
@@ -75,6 +179,7 @@ impl SseManager
 
             // Nettoyer le canal si personne n'écoute
             self.cleanup_project_channel(project_id).await;
+            self.keep_missed_event(MissedEventTarget::Project(project_id), event);
             return;
         }
 
@@ -121,6 +226,7 @@ impl SseManager
                 event.event_type()
             );
             self.cleanup_creation_channel(user_login).await;
+            self.keep_missed_event(MissedEventTarget::User(user_login.to_string()), event);
             return;
         }
         
@@ -142,6 +248,38 @@ impl SseManager
         }
     }
 
+    /// Émet un événement à destination des administrateurs connectés
+    ///
+    /// Cas d'usage :
+    /// - Échecs des tâches planifiées (sauvegardes, ...)
+    /// - Transitions des projets entre démarré et arrêté
+    pub fn emit_to_admins(&self, event: SseEvent)
+    {
+        if self.admin_channel.receiver_count() == 0
+        {
+            debug!("No admin subscribers, event dropped: {:?}", event.event_type());
+            return;
+        }
+
+        match self.admin_channel.send(event.clone())
+        {
+            Ok(count) =>
+            {
+                debug!("Admin event '{}' sent to {} client(s)", event.event_type(), count);
+            }
+            Err(e) =>
+            {
+                error!("Failed to send admin event: {:?}", e);
+            }
+        }
+    }
+
+    /// S'abonne au canal des administrateurs
+    pub fn subscribe_to_admins(&self) -> broadcast::Receiver<SseEvent>
+    {
+        self.admin_channel.subscribe()
+    }
+
     /// S'abonne aux événements d'un projet spécifique
     pub async fn subscribe_to_project(&self, project_id: i32) -> broadcast::Receiver<SseEvent> 
     {
@@ -184,6 +322,87 @@ impl SseManager
         rx
     }
 
+    /// S'abonne aux logs d'un projet
+    ///
+    /// Le sender n'est renvoyé qu'au premier abonné : l'appelant doit alors lancer la tâche
+    /// de suivi qui alimente le canal.
+    pub async fn subscribe_to_logs(&self, project_id: i32) -> (broadcast::Receiver<SseEvent>, Option<broadcast::Sender<SseEvent>>)
+    {
+        let mut map = self.log_channels.write().await;
+
+        if let Some(tx) = map.get(&project_id)
+        {
+            debug!("New log subscription for project {} (total: {})", project_id, tx.receiver_count() + 1);
+            return (tx.subscribe(), None);
+        }
+
+        let (tx, rx) = broadcast::channel(BROADCAST_CAPACITY);
+        map.insert(project_id, tx.clone());
+        info!("Opened log channel for project {}", project_id);
+        (rx, Some(tx))
+    }
+
+    /// Retire le canal de logs d'un projet s'il n'a plus d'abonné. Renvoie `true` si la tâche
+    /// de suivi doit s'arrêter ; la vérification et le retrait se font sous le même verrou qu'un
+    /// abonnement, pour qu'aucun nouvel abonné ne reste sur un canal orphelin.
+    pub async fn release_log_channel(&self, project_id: i32) -> bool
+    {
+        let mut map = self.log_channels.write().await;
+
+        match map.get(&project_id)
+        {
+            Some(tx) if tx.receiver_count() > 0 => false,
+            Some(_) =>
+            {
+                map.remove(&project_id);
+                info!("Closed log channel for project {}", project_id);
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Retire le canal de logs d'un projet supprimé, même s'il a encore des abonnés.
+    pub async fn close_log_channel(&self, project_id: i32)
+    {
+        if self.log_channels.write().await.remove(&project_id).is_some()
+        {
+            info!("Closed log channel for deleted project {}", project_id);
+        }
+    }
+
+    /// Ferme tous les flux SSE ouverts par `user_login` (projets, création, administration).
+    pub fn disconnect_user(&self, user_login: &str)
+    {
+        // Aucun flux ouvert : rien à fermer.
+        if self.user_disconnects.send(user_login.to_string()).is_ok()
+        {
+            info!("Requested disconnection of SSE streams of user '{}'", user_login);
+        }
+    }
+
+    /// Se termine lorsque les flux de `user_login` doivent être fermés.
+    ///
+    /// L'abonnement est pris à l'appel, pas au premier `poll`.
+    pub fn user_disconnected(&self, user_login: &str) -> impl Future<Output = ()> + Send + use<>
+    {
+        let mut rx = self.user_disconnects.subscribe();
+        let user_login = user_login.to_string();
+
+        async move
+        {
+            loop
+            {
+                match rx.recv().await
+                {
+                    Ok(target) if target == user_login => return,
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => std::future::pending::<()>().await,
+                }
+            }
+        }
+    }
+
     pub async fn cleanup_project_channel(&self, project_id: i32) 
     {
         let remove = 
@@ -286,7 +505,10 @@ impl SseManager
         {
             active_project_channels: self.active_project_channels().await,
             active_creation_channels: self.active_creation_channels().await,
+            active_log_channels: self.log_channels.read().await.len(),
             total_project_subscribers,
+            active_connections: self.active_connections(),
+            max_connections: self.max_connections,
         }
     }
 
@@ -305,7 +527,7 @@ impl Default for SseManager
 {
     fn default() -> Self 
     {
-        Self::new()
+        Self::new(DEFAULT_MAX_CONNECTIONS)
     }
 }
 
@@ -314,7 +536,10 @@ pub struct SseManagerStats
 {
     pub active_project_channels: usize,
     pub active_creation_channels: usize,
+    pub active_log_channels: usize,
     pub total_project_subscribers: usize,
+    pub active_connections: usize,
+    pub max_connections: usize,
 }
 
 pub async fn start_cleanup_task(manager: SseManager, mut shutdown_signal: tokio::sync::broadcast::Receiver<()>) 
@@ -335,3 +560,73 @@ pub async fn start_cleanup_task(manager: SseManager, mut shutdown_signal: tokio:
         manager.cleanup_empty_channels().await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_limit_is_released_on_drop()
+    {
+        let manager = SseManager::new(2);
+        let first = manager.try_acquire_connection();
+        let second = manager.try_acquire_connection();
+
+        assert!(first.is_some() && second.is_some());
+        assert!(manager.try_acquire_connection().is_none());
+        assert_eq!(manager.active_connections(), 2);
+
+        drop(first);
+        assert_eq!(manager.active_connections(), 1);
+        assert!(manager.try_acquire_connection().is_some());
+    }
+
+    #[test]
+    fn test_log_channel_has_a_single_follower()
+    {
+        futures::executor::block_on(async
+        {
+            let manager = SseManager::default();
+            let (first, follower) = manager.subscribe_to_logs(7).await;
+            let (second, no_follower) = manager.subscribe_to_logs(7).await;
+
+            assert!(follower.is_some());
+            assert!(no_follower.is_none());
+            assert!(!manager.release_log_channel(7).await);
+
+            drop(first);
+            drop(second);
+            assert!(manager.release_log_channel(7).await);
+            assert!(manager.subscribe_to_logs(7).await.1.is_some());
+        });
+    }
+
+    #[test]
+    fn test_only_configured_events_without_subscriber_are_kept()
+    {
+        use crate::sse::types::{DeploymentEvent, DeploymentStage, SystemEvent};
+
+        futures::executor::block_on(async
+        {
+            let (tx, mut rx) = mpsc::channel(10);
+            let manager = SseManager::default().with_missed_events(HashSet::from(["system".to_string(), "deployment".to_string()]), tx);
+            let stage = |stage| SseEvent::Deployment(DeploymentEvent { project_id: 3, project_name: "demo".into(), stage, timestamp: time::OffsetDateTime::UNIX_EPOCH });
+
+            manager.emit_to_project(3, SseEvent::System(SystemEvent::warning("crashed".into()))).await;
+            manager.emit_to_project(3, stage(DeploymentStage::Started)).await;
+            manager.emit_to_project(3, stage(DeploymentStage::Completed { container_name: "hangar-demo".into() })).await;
+            manager.emit_to_creation("alice", SseEvent::System(SystemEvent::info("done".into()))).await;
+
+            let _subscriber = manager.subscribe_to_project(3).await;
+            manager.emit_to_project(3, SseEvent::System(SystemEvent::info("live".into()))).await;
+
+            let missed: Vec<MissedEvent> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+            let targets: Vec<(MissedEventTarget, &str)> = missed.iter().map(|missed| (missed.target.clone(), missed.event.event_type())).collect();
+            assert_eq!(targets, vec![
+                (MissedEventTarget::Project(3), "system"),
+                (MissedEventTarget::Project(3), "deployment"),
+                (MissedEventTarget::User("alice".into()), "system"),
+            ]);
+        });
+    }
+}
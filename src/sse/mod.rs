@@ -1,4 +1,5 @@
 pub mod emitter;
 pub mod manager;
+pub mod status_tracker;
 pub mod types;
 pub mod tasks;
\ No newline at end of file
@@ -0,0 +1,227 @@
+//! Filtrage des statuts de conteneur publiés aux clients SSE.
+//!
+//! Docker émet une action par étape : un redémarrage produit `kill`, `die`, `stop`, `start` puis
+//! `restart`, et le badge de statut du frontend clignote. Seuls les changements du dernier statut
+//! émis pour un projet sont publiés ; un arrêt est retenu pendant `CONTAINER_RESTART_WINDOW_SECONDS`
+//! et, si le conteneur redémarre dans ce délai, publié comme `restarting` suivi de `running`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::sse::types::{ContainerStatus, ContainerStatusEvent};
+
+/// Dernier statut publié par projet et arrêts en attente d'un éventuel redémarrage.
+pub struct ContainerStatusTracker
+{
+    restart_window: Duration,
+    projects: HashMap<i32, TrackedProject>,
+}
+
+#[derive(Default)]
+struct TrackedProject
+{
+    last_emitted: Option<ContainerStatus>,
+    pending_stop: Option<PendingStop>,
+}
+
+/// Dernier événement d'arrêt reçu et son instant de réception.
+struct PendingStop
+{
+    event: ContainerStatusEvent,
+    at: Instant,
+}
+
+impl ContainerStatusTracker
+{
+    #[must_use]
+    pub fn new(restart_window: Duration) -> Self
+    {
+        Self { restart_window, projects: HashMap::new() }
+    }
+
+    /// Événements à publier après réception de `event`, éventuellement aucun.
+    pub fn observe(&mut self, event: ContainerStatusEvent, now: Instant) -> Vec<ContainerStatusEvent>
+    {
+        let restart_window = self.restart_window;
+        let project = self.projects.entry(event.project_id).or_default();
+
+        match event.status
+        {
+            ContainerStatus::Exited | ContainerStatus::Dead if !restart_window.is_zero() =>
+            {
+                project.pending_stop = Some(PendingStop { event, at: now });
+                Vec::new()
+            }
+            ContainerStatus::Running =>
+            {
+                let mut events = Vec::new();
+                match project.pending_stop.take()
+                {
+                    Some(pending) if now.duration_since(pending.at) < restart_window =>
+                    {
+                        let restarting = ContainerStatusEvent { status: ContainerStatus::Restarting, ..event.clone() };
+                        project.push(&mut events, restarting);
+                    }
+                    Some(pending) => project.push(&mut events, pending.event),
+                    None => {}
+                }
+                project.push(&mut events, event);
+                events
+            }
+            // L'action `restart` suit le `start` du conteneur : le redémarrage est déjà terminé.
+            // Un conteneur créé à côté d'un conteneur démarré en est le remplaçant blue-green.
+            ContainerStatus::Restarting | ContainerStatus::Created
+                if project.pending_stop.is_none() && project.last_emitted == Some(ContainerStatus::Running) =>
+            {
+                Vec::new()
+            }
+            _ =>
+            {
+                let mut events = Vec::new();
+                if let Some(pending) = project.pending_stop.take()
+                {
+                    project.push(&mut events, pending.event);
+                }
+                project.push(&mut events, event);
+                events
+            }
+        }
+    }
+
+    /// Arrêts sans redémarrage dans le délai, à publier.
+    pub fn flush_expired(&mut self, now: Instant) -> Vec<ContainerStatusEvent>
+    {
+        let mut events = Vec::new();
+        for project in self.projects.values_mut()
+        {
+            if project.pending_stop.as_ref().is_some_and(|pending| now.duration_since(pending.at) >= self.restart_window)
+                && let Some(pending) = project.pending_stop.take()
+            {
+                project.push(&mut events, pending.event);
+            }
+        }
+        events
+    }
+
+    /// Prochain instant où un arrêt retenu doit être publié.
+    #[must_use]
+    pub fn next_deadline(&self) -> Option<Instant>
+    {
+        self.projects.values()
+            .filter_map(|project| project.pending_stop.as_ref())
+            .map(|pending| pending.at + self.restart_window)
+            .min()
+    }
+}
+
+impl TrackedProject
+{
+    /// Ajoute `event` à `events` s'il change le dernier statut publié.
+    fn push(&mut self, events: &mut Vec<ContainerStatusEvent>, event: ContainerStatusEvent)
+    {
+        if self.last_emitted.as_ref() != Some(&event.status)
+        {
+            self.last_emitted = Some(event.status.clone());
+            events.push(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(5);
+
+    fn event(container_name: &str, status: ContainerStatus) -> ContainerStatusEvent
+    {
+        ContainerStatusEvent::new(1, "blog".to_string(), container_name.to_string(), status)
+    }
+
+    /// Statuts publiés pour une suite d'événements reçus à `(secondes, conteneur, statut)`.
+    fn published(tracker: &mut ContainerStatusTracker, start: Instant, events: &[(u64, &str, ContainerStatus)]) -> Vec<ContainerStatus>
+    {
+        let mut published = Vec::new();
+        for (seconds, container_name, status) in events
+        {
+            let now = start + Duration::from_secs(*seconds);
+            published.extend(tracker.flush_expired(now));
+            published.extend(tracker.observe(event(container_name, status.clone()), now));
+        }
+        published.into_iter().map(|event| event.status).collect()
+    }
+
+    #[test]
+    fn test_restart_is_published_once_as_restarting_then_running()
+    {
+        let mut tracker = ContainerStatusTracker::new(WINDOW);
+        let start = Instant::now();
+
+        let statuses = published(&mut tracker, start, &[
+            (0, "blog", ContainerStatus::Running),
+            (10, "blog", ContainerStatus::Dead),
+            (12, "blog", ContainerStatus::Exited),
+            (12, "blog", ContainerStatus::Exited),
+            (13, "blog", ContainerStatus::Running),
+            (13, "blog", ContainerStatus::Restarting),
+            (14, "blog", ContainerStatus::Running),
+        ]);
+
+        assert_eq!(statuses, [ContainerStatus::Running, ContainerStatus::Restarting, ContainerStatus::Running]);
+        assert!(tracker.next_deadline().is_none());
+    }
+
+    #[test]
+    fn test_stop_without_restart_is_published_after_the_window()
+    {
+        let mut tracker = ContainerStatusTracker::new(WINDOW);
+        let start = Instant::now();
+
+        let statuses = published(&mut tracker, start, &[
+            (0, "blog", ContainerStatus::Running),
+            (10, "blog", ContainerStatus::Dead),
+            (11, "blog", ContainerStatus::Exited),
+        ]);
+        assert_eq!(statuses, [ContainerStatus::Running]);
+        assert_eq!(tracker.next_deadline(), Some(start + Duration::from_secs(11) + WINDOW));
+
+        assert!(tracker.flush_expired(start + Duration::from_secs(15)).is_empty());
+        let flushed = tracker.flush_expired(start + Duration::from_secs(16));
+        assert_eq!(flushed.iter().map(|e| e.status.clone()).collect::<Vec<_>>(), [ContainerStatus::Exited]);
+
+        let statuses = published(&mut tracker, start, &[(60, "blog", ContainerStatus::Running)]);
+        assert_eq!(statuses, [ContainerStatus::Running]);
+    }
+
+    #[test]
+    fn test_blue_green_replacement_keeps_the_project_running()
+    {
+        let mut tracker = ContainerStatusTracker::new(WINDOW);
+        let start = Instant::now();
+
+        // Les arrêts de l'ancien conteneur sont écartés en amont (`blue_green::is_expected_stop`).
+        let statuses = published(&mut tracker, start, &[
+            (0, "blog", ContainerStatus::Running),
+            (5, "blog-next", ContainerStatus::Created),
+            (6, "blog-next", ContainerStatus::Running),
+        ]);
+
+        assert_eq!(statuses, [ContainerStatus::Running]);
+    }
+
+    #[test]
+    fn test_zero_window_only_suppresses_duplicates()
+    {
+        let mut tracker = ContainerStatusTracker::new(Duration::ZERO);
+        let start = Instant::now();
+
+        let statuses = published(&mut tracker, start, &[
+            (0, "blog", ContainerStatus::Running),
+            (1, "blog", ContainerStatus::Exited),
+            (1, "blog", ContainerStatus::Exited),
+            (1, "blog", ContainerStatus::Running),
+        ]);
+
+        assert_eq!(statuses, [ContainerStatus::Running, ContainerStatus::Exited, ContainerStatus::Running]);
+    }
+}
@@ -1,19 +1,25 @@
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use bollard::query_parameters::EventsOptions;
-use tokio::time::{interval, sleep};
+use tokio::time::{interval, sleep, sleep_until};
+use serde_json::json;
 use tokio_stream::StreamExt;
 use tracing::{info, warn};
 use tracing::{debug, error};
 
 use crate::sse::emitter::emit_container_status;
-use crate::sse::emitter::emit_metrics;
-use crate::sse::types::ContainerStatus;
-use crate::{services::project_service, state::AppState};
-use crate::services::docker_service;
+use crate::sse::emitter::{emit_down_projects_changed, emit_metrics};
+use crate::sse::types::{ContainerStatus, ContainerStatusEvent, DownProjectChange, LogEvent, SseEvent, SystemEvent};
+use crate::{model::{project::Project, timeline::TimelineEntryType}, services::project_service, state::AppState};
+use crate::services::{blue_green, docker_service, metrics_history_service::{self, PlatformSample}, notification_dispatch_service, notification_service, timeline_service, usage_service};
+use crate::model::{notification::NotificationKind, webhook::WebhookEvent};
 
 const EMIT_METRICS_INTERVAL_SECS: u64 = 5;
+/// Fréquence à laquelle un suivi de logs silencieux vérifie qu'il a encore des abonnés.
+const LOG_SUBSCRIBERS_CHECK_SECS: u64 = 10;
+/// Attente avant de se rattacher au conteneur d'un projet dont le flux de logs s'est terminé.
+const LOG_REATTACH_DELAY_SECS: u64 = 2;
 
 pub async fn start_docker_events_listener(state: AppState, mut shutdown_signal: tokio::sync::broadcast::Receiver<()>)
 {
@@ -29,12 +35,17 @@ pub async fn start_docker_events_listener(state: AppState, mut shutdown_signal:
         ..Default::default()
     });
 
+    // Projets arrêtés connus, pour ne notifier les administrateurs qu'aux transitions démarré/arrêté.
+    let mut down_projects = load_down_project_ids(&state).await;
+
     loop
     {
         let mut stream = docker.events(options.clone());
 
         loop 
         {
+            let flush_at = state.container_statuses.lock().ok().and_then(|tracker| tracker.next_deadline());
+
             tokio::select! 
             {
                 _ = shutdown_signal.recv() => 
@@ -42,13 +53,23 @@ pub async fn start_docker_events_listener(state: AppState, mut shutdown_signal:
                     info!("Shutdown signal received, stopping Docker events listener");
                     return;
                 }
+                () = wait_until(flush_at) =>
+                {
+                    let events = state.container_statuses.lock()
+                        .map(|mut tracker| tracker.flush_expired(Instant::now()))
+                        .unwrap_or_default();
+                    for event in events
+                    {
+                        emit_container_status(&state, event).await;
+                    }
+                }
                 event_result = stream.next() => 
                 {
                     match event_result 
                     {
                         Some(Ok(event)) => 
                         {
-                            handle_docker_event(&state, event).await;
+                            handle_docker_event(&state, event, &mut down_projects).await;
                         }
                         Some(Err(e)) => 
                         {
@@ -75,10 +96,34 @@ pub async fn start_docker_events_listener(state: AppState, mut shutdown_signal:
     }
 }
 
-async fn handle_docker_event(state: &AppState, event: bollard::models::EventMessage)
+/// Attend `deadline`, ou indéfiniment s'il n'y en a pas.
+async fn wait_until(deadline: Option<Instant>)
+{
+    match deadline
+    {
+        Some(deadline) => sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn load_down_project_ids(state: &AppState) -> HashSet<i32>
+{
+    match project_service::list_down_projects(state).await
+    {
+        Ok(projects) => projects.into_iter().map(|down| down.project.id).collect(),
+        Err(e) =>
+        {
+            error!("Failed to load down projects, admin notifications may be inaccurate: {}", e);
+            HashSet::new()
+        }
+    }
+}
+
+async fn handle_docker_event(state: &AppState, event: bollard::models::EventMessage, down_projects: &mut HashSet<i32>)
 {
     let action = match event.action.as_deref() 
     {
+        Some("destroy") => ContainerStatus::Removing,
         Some("create") => ContainerStatus::Created,
         Some("restart") => ContainerStatus::Restarting,
         Some("start" | "unpause") => ContainerStatus::Running,
@@ -88,80 +133,215 @@ async fn handle_docker_event(state: &AppState, event: bollard::models::EventMess
         _ => return,
     };
 
-    if let Some(actor) = event.actor 
+    let Some(actor) = event.actor else { return };
+    let attributes = actor.attributes.unwrap_or_default();
+
+    let container_name = attributes.get("name")
+        .map(|name| name.trim_start_matches('/').to_string())
+        .unwrap_or_default();
+
+    if container_name.is_empty() { return; }
+    let crash = crash_exit_code(event.action.as_deref(), attributes.get("exitCode").map(String::as_str));
+
+    // Le label est prioritaire ; la recherche par nom reste nécessaire pour les conteneurs antérieurs au label.
+    let project = match attributes.get(docker_service::PROJECT_ID_LABEL).and_then(|id| id.parse::<i32>().ok())
+    {
+        Some(project_id) => project_service::get_project_by_id(&state.db_pool, project_id).await,
+        None => project_service::get_project_by_container_name(&state.db_pool, &container_name).await,
+    };
+
+    if action == ContainerStatus::Removing
     {
-        let container_name = actor.attributes
-            .and_then(|attrs| attrs.get("name").cloned())
-            .map(|name| name.trim_start_matches('/').to_string())
-            .unwrap_or_default();
+        // La suppression d'un projet clôt son temps de fonctionnement ; seule celle d'un projet
+        // arrêté (purge) modifie la liste des projets arrêtés.
+        let Ok(None) = project else { return };
+        let Some(project_id) = attributes.get(docker_service::PROJECT_ID_LABEL).and_then(|id| id.parse::<i32>().ok()) else { return };
+
+        usage_service::record_project_removed(&state.db_pool, project_id).await;
+        if down_projects.remove(&project_id)
+        {
+            emit_down_projects_changed(state, down_projects.len(), DownProjectChange
+            {
+                project_id,
+                project_name: None,
+                container_name,
+                is_down: false,
+                status: action,
+            });
+        }
+        return;
+    }
 
-        if container_name.is_empty() { return; }
+    if matches!(action, ContainerStatus::Exited | ContainerStatus::Dead)
+        && blue_green::is_expected_stop(&state.expected_stops, &container_name, Instant::now())
+    {
+        info!("Container '{}' stopped as part of a blue-green switch ({:?}), not reported", container_name, action);
+        return;
+    }
+
+    if let Ok(Some(project)) = project
+    {
+        debug!("Container '{}' changed status to {:?}", container_name, action);
         
-        if let Ok(Some(project)) = project_service::get_project_by_container_name(&state.db_pool, &container_name).await
+        let event = ContainerStatusEvent::new(project.id, project.name.clone(), container_name.clone(), action.clone());
+        let events = state.container_statuses.lock()
+            .map(|mut tracker| tracker.observe(event, Instant::now()))
+            .unwrap_or_default();
+        for event in events
         {
-            debug!("Container '{}' changed status to {:?}", container_name, action);
-            
-            emit_container_status(
-                state,
-                project.id,
-                project.name.clone(),
-                container_name.clone(),
-                action.clone(),
-            ).await;
+            emit_container_status(state, event).await;
+        }
+
+        // Les événements de l'ancien conteneur d'un déploiement blue-green ne concernent plus le projet.
+        if container_name == project.container_name
+        {
+            if let Some(exit_code) = crash
+            {
+                warn!("Container '{}' of project '{}' died unexpectedly ({})", container_name, project.name, exit_code);
+                notification_dispatch_service::dispatch(
+                    state,
+                    project.id,
+                    &project.name,
+                    WebhookEvent::ContainerDied,
+                    json!({ "container_name": container_name, "exit_code": exit_code }),
+                );
+                notification_service::notify_owner(
+                    state,
+                    project.id,
+                    NotificationKind::ContainerCrashed,
+                    SystemEvent::error(format!("Container '{container_name}' stopped ({exit_code}). Check its logs from the dashboard."))
+                        .with_context(json!({ "container_name": container_name, "exit_code": exit_code })),
+                );
+            }
+            track_down_transition(state, down_projects, &project, action).await;
         }
     }
 }
 
+/// Cause d'un arrêt inattendu : manque de mémoire, ou sortie en erreur qui n'est pas la réponse à
+/// un `docker stop` (143 après SIGTERM, 137 après SIGKILL, aussi signalé par `oom` le cas échéant).
+fn crash_exit_code(action: Option<&str>, exit_code: Option<&str>) -> Option<String>
+{
+    match (action, exit_code)
+    {
+        (Some("oom"), _) => Some("out of memory".to_string()),
+        (Some("die"), Some(code)) if !matches!(code, "0" | "137" | "143") => Some(format!("exit code {code}")),
+        _ => None,
+    }
+}
+
+async fn track_down_transition(state: &AppState, down_projects: &mut HashSet<i32>, project: &Project, status: ContainerStatus)
+{
+    let is_down = match status
+    {
+        ContainerStatus::Running => false,
+        ContainerStatus::Exited | ContainerStatus::Dead => true,
+        _ => return,
+    };
+
+    // Chaque démarrage et arrêt est historisé, même s'il ne change pas l'état connu (redémarrage de l'API).
+    if let Some(transition_id) = usage_service::record_transition(&state.db_pool, project, !is_down).await
+    {
+        timeline_service::publish(state, TimelineEntryType::Status, transition_id).await;
+    }
+
+    // Un projet archivé est arrêté volontairement : il ne figure pas parmi les projets arrêtés.
+    let is_down = is_down && !project.archived;
+    let changed = if is_down { down_projects.insert(project.id) } else { down_projects.remove(&project.id) };
+    if !changed
+    {
+        return;
+    }
+
+    emit_down_projects_changed(state, down_projects.len(), DownProjectChange
+    {
+        project_id: project.id,
+        project_name: Some(project.name.clone()),
+        container_name: project.container_name.clone(),
+        is_down,
+        status,
+    });
+}
+
 /// Lance une tâche qui collecte périodiquement les métriques des containers
-/// et les émet via SSE
+/// et les émet via SSE. Toutes les `METRICS_HISTORY_INTERVAL_MINUTES`, le relevé couvre aussi
+/// les conteneurs démarrés sans abonné et alimente l'historique de la plateforme.
 pub async fn start_metrics_collector(state: AppState, mut shutdown_signal: tokio::sync::broadcast::Receiver<()>)
 {
-    let mut interval = interval(Duration::from_secs(EMIT_METRICS_INTERVAL_SECS));
+    let mut emit_interval = interval(Duration::from_secs(EMIT_METRICS_INTERVAL_SECS));
+    let mut history_interval = interval(metrics_history_service::sample_interval(state.config.metrics_history_interval_minutes));
     
     info!("Starting metrics collector task");
     
     loop
     {
-        tokio::select! 
+        let record_history = tokio::select! 
         {
             _ = shutdown_signal.recv() => 
             {
                 info!("Metrics collector task shutting down");
                 break;
             }
-            _ = interval.tick() => {}
-        }
+            _ = emit_interval.tick() => false,
+            _ = history_interval.tick() => true,
+        };
         
-        if let Err(e) = collect_all_metrics(&state).await
+        let sample = collect_all_metrics(&state, record_history).await.unwrap_or_else(|e|
         {
             error!("Error in metrics collector: {}", e);
+            None
+        });
+
+        if let Some(sample) = sample
+            && let Err(e) = metrics_history_service::record_sample(&state.db_pool, &sample, state.config.metrics_history_retention_days).await
+        {
+            error!("Failed to record platform metrics sample: {}", e);
         }
     }
 }
 
-async fn collect_all_metrics(state: &AppState) -> Result<(), Box<dyn std::error::Error>>
+/// Relève les projets suivis en SSE et, si `record_history`, tous les projets démarrés : chaque
+/// conteneur n'est interrogé qu'une fois, et son relevé sert à la fois au SSE et à l'agrégat.
+async fn collect_all_metrics(state: &AppState, record_history: bool) -> Result<Option<PlatformSample>, Box<dyn std::error::Error>>
 {
-    let active_ids = state.sse_manager.get_active_project_ids().await;
+    let active_ids: HashSet<i32> = state.sse_manager.get_active_project_ids().await.into_iter().collect();
+    let running_ids = if record_history
+    {
+        docker_service::list_running_project_ids(&state.docker_client, &state.config.app_prefix).await?
+    }
+    else
+    {
+        HashSet::new()
+    };
 
-    if active_ids.is_empty() 
+    if active_ids.is_empty() && !record_history
     {
-        return Ok(());
+        return Ok(None);
     }
 
-    let projects = project_service::get_projects_by_ids(&state.db_pool, &active_ids).await?;
+    let ids: Vec<i32> = active_ids.union(&running_ids).copied().collect();
+    let projects = project_service::get_projects_by_ids(&state.db_pool, &ids).await?;
+    let mut sample = PlatformSample::default();
     
-    for project in projects
+    for project in projects.into_iter().filter(|project| !project.archived)
     {        
-        match docker_service::get_container_metrics(&state.docker_client, &project.container_name).await
+        match docker_service::sample_container_metrics(&state.docker_client, &project.container_name).await
         {
             Ok(metrics) =>
             {
-                emit_metrics(
-                    state,
-                    project.id,
-                    project.name.clone(),
-                    metrics,
-                ).await;
+                if running_ids.contains(&project.id)
+                {
+                    sample.add(&metrics);
+                }
+                if active_ids.contains(&project.id)
+                {
+                    emit_metrics(
+                        state,
+                        project.id,
+                        project.name.clone(),
+                        metrics,
+                    ).await;
+                }
             }
             Err(e) =>
             {
@@ -170,5 +350,70 @@ async fn collect_all_metrics(state: &AppState) -> Result<(), Box<dyn std::error:
         }
     }
     
-    Ok(())
-}
\ No newline at end of file
+    Ok(record_history.then_some(sample))
+}
+
+/// Suit les logs du conteneur d'un projet et les diffuse sur son canal de logs, tant qu'il a des abonnés.
+///
+/// Le flux Docker se termine quand le conteneur s'arrête ou est supprimé : la ligne du projet est
+/// alors relue pour se rattacher au conteneur courant, qui change lors d'une mise à jour blue-green.
+/// Le suivi s'arrête avec le dernier abonné, ou si le projet a été supprimé.
+pub async fn follow_project_logs(state: AppState, project_id: i32, tx: tokio::sync::broadcast::Sender<SseEvent>)
+{
+    loop
+    {
+        let project = match project_service::get_project_by_id(&state.db_pool, project_id).await
+        {
+            Ok(Some(project)) => Some(project),
+            Ok(None) =>
+            {
+                // Les abonnés restants voient leur flux se fermer avec le canal.
+                info!("Project {} no longer exists, stopping log follower", project_id);
+                state.sse_manager.close_log_channel(project_id).await;
+                return;
+            }
+            Err(e) =>
+            {
+                error!("Failed to reload project {} for log follower: {}", project_id, e);
+                None
+            }
+        };
+
+        if let Some(project) = project
+        {
+            debug!("Attaching log follower of project {} to container '{}'", project_id, project.container_name);
+            let mut lines = std::pin::pin!(docker_service::follow_container_logs(&state.docker_client, &project.container_name));
+            let mut subscribers_check = interval(Duration::from_secs(LOG_SUBSCRIBERS_CHECK_SECS));
+
+            loop
+            {
+                tokio::select!
+                {
+                    line = lines.next() =>
+                    {
+                        let Some(line) = line else { break };
+                        let event = LogEvent::new(project.id, project.name.clone(), project.container_name.clone(), line);
+                        if tx.send(SseEvent::Log(event)).is_err()
+                        {
+                            break;
+                        }
+                    }
+                    _ = subscribers_check.tick() =>
+                    {
+                        if tx.receiver_count() == 0
+                        {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        if state.sse_manager.release_log_channel(project_id).await
+        {
+            debug!("Log follower of project {} stopped, no subscriber left", project_id);
+            return;
+        }
+        sleep(Duration::from_secs(LOG_REATTACH_DELAY_SECS)).await;
+    }
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
-use crate::model::project::ProjectMetrics;
+use crate::model::docker::ContainerLogLine;
+use crate::model::project::{DownProjectInfo, ProjectMetrics};
+use crate::model::scan::Vulnerability;
+use crate::model::timeline::TimelineEntry;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -11,6 +14,9 @@ pub enum SseEvent
     ContainerStatus(ContainerStatusEvent),
     Metrics(MetricsEvent),
     System(SystemEvent),
+    DownProjectsChanged(DownProjectsEvent),
+    Log(LogEvent),
+    Timeline(TimelineEvent),
 }
 
 impl SseEvent 
@@ -24,9 +30,27 @@ impl SseEvent
             Self::ContainerStatus(_) => "container_status",
             Self::Metrics(_) => "metrics",
             Self::System(_) => "system",
+            Self::DownProjectsChanged(_) => "down_projects_changed",
+            Self::Log(_) => "log",
+            Self::Timeline(_) => "timeline",
         }
     }
 
+    /// Événement ponctuel qu'un utilisateur hors ligne doit pouvoir retrouver : les métriques, logs
+    /// et étapes intermédiaires d'un déploiement, vite périmés, ne sont jamais conservés.
+    #[must_use]
+    pub const fn is_persistable(&self) -> bool
+    {
+        match self
+        {
+            Self::Deployment(event) => matches!(event.stage, DeploymentStage::Completed { .. } | DeploymentStage::Failed { .. }),
+            Self::ContainerStatus(_) | Self::System(_) | Self::Timeline(_) => true,
+            Self::Metrics(_) | Self::DownProjectsChanged(_) | Self::Log(_) => false,
+        }
+    }
+
+    /// Identifiant opaque de l'événement (champ `id` du SSE), à ne pas interpréter comme une date :
+    /// l'horodatage de l'événement est le champ `timestamp` de son contenu, en RFC 3339 UTC.
     #[must_use] 
     pub fn generate_id(&self) -> String 
     {
@@ -128,20 +152,32 @@ pub enum DeploymentStage
     ImageScanned,
     CloningRepository { repo_url: String },
     RepositoryCloned,
+    ExtractingArchive { file_name: String },
+    ArchiveExtracted,
     BuildingImage,
     ImageBuilt,
     GettingImageDigest,
     CreatingContainer,
     ContainerCreated,
     WaitingHealthCheck,
+    /// Le conteneur n'est toujours pas démarré, émis périodiquement pendant l'attente.
+    HealthCheckProgress { elapsed_seconds: u64 },
     HealthCheckPassed,
     ProvisioningDatabase,
     DatabaseProvisioned,
     LinkingDatabase,
     DatabaseLinked,
     CleaningUp,
+    Resuming { from_stage: String },
     Completed { container_name: String },
-    Failed { error: String, stage: String },
+    Failed
+    {
+        error: String,
+        stage: String,
+        /// Vulnérabilités les plus graves lorsqu'un scan d'image échoue.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        vulnerabilities: Vec<Vulnerability>,
+    },
 }
 
 impl DeploymentEvent 
@@ -261,4 +297,96 @@ impl MetricsEvent
             timestamp: OffsetDateTime::now_utc(),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Nouvelle entrée de la chronologie du projet, dans la forme renvoyée par `GET /api/projects/{id}/timeline`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEvent
+{
+    pub project_id: i32,
+    pub entry: TimelineEntry,
+}
+
+/// Ligne de logs d'un conteneur, diffusée en continu sur `/api/sse/projects/{id}/logs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEvent
+{
+    pub project_id: i32,
+    pub project_name: String,
+    /// Change lors d'une mise à jour blue-green, quand le suivi passe au nouveau conteneur.
+    pub container_name: String,
+    pub log: ContainerLogLine,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+impl LogEvent
+{
+    #[must_use]
+    pub fn new(project_id: i32, project_name: String, container_name: String, log: ContainerLogLine) -> Self
+    {
+        Self
+        {
+            project_id,
+            project_name,
+            container_name,
+            log,
+            timestamp: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+/// Évolution de la liste des projets arrêtés, diffusée sur le canal des administrateurs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownProjectsEvent
+{
+    pub down_count: usize,
+    /// Projet passé de l'état démarré à arrêté ou inversement (absent de l'état initial).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed: Option<DownProjectChange>,
+    /// Liste complète, envoyée uniquement à l'abonnement.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub down_projects: Option<Vec<DownProjectInfo>>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownProjectChange
+{
+    pub project_id: i32,
+    /// `None` lorsque le projet a été supprimé.
+    pub project_name: Option<String>,
+    pub container_name: String,
+    pub is_down: bool,
+    pub status: ContainerStatus,
+}
+
+impl DownProjectsEvent
+{
+    #[must_use]
+    pub fn changed(down_count: usize, change: DownProjectChange) -> Self
+    {
+        Self
+        {
+            down_count,
+            changed: Some(change),
+            down_projects: None,
+            timestamp: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[must_use]
+    pub fn snapshot(down_projects: Vec<DownProjectInfo>) -> Self
+    {
+        Self
+        {
+            down_count: down_projects.len(),
+            changed: None,
+            down_projects: Some(down_projects),
+            timestamp: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -1,7 +1,8 @@
-use std::sync::Arc;
+use std::{collections::{HashMap, HashSet, VecDeque}, sync::{Arc, Mutex}, time::{Duration, Instant}};
 use bollard::Docker;
+use tokio::sync::mpsc;
 use sqlx::{MySqlPool, PgPool};
-use crate::{config::Config, sse::manager::SseManager};
+use crate::{config::Config, logging::LogFilter, model::docker::DockerDiskUsage, services::{email_service::Mailer, missed_event_service::MISSED_EVENTS_QUEUE, notification_dispatch_service::WebhookCircuits}, sse::{manager::{MissedEvent, SseManager}, status_tracker::ContainerStatusTracker}};
 
 pub type AppState = Arc<InnerState>;
 
@@ -13,13 +14,48 @@ pub struct InnerState
     pub db_pool: PgPool,
     pub mariadb_pool: MySqlPool,
     pub sse_manager: SseManager,
+    /// Projets ayant une commande ponctuelle en cours d'exécution (une seule à la fois par projet).
+    pub running_tasks: Mutex<HashSet<i32>>,
+    /// Dernier résultat de `docker system df`, mis en cache car l'appel est coûteux.
+    pub docker_df_cache: Mutex<Option<(Instant, DockerDiskUsage)>>,
+    /// Appels récents des deploy hooks, par clé d'API, pour le rate-limit.
+    pub hook_calls: Mutex<HashMap<i32, VecDeque<Instant>>>,
+    /// Dernière réinitialisation de chaque base, pour espacer les réinitialisations successives.
+    pub database_resets: Mutex<HashMap<i32, Instant>>,
+    /// Conteneurs arrêtés volontairement par une bascule blue-green, avec la fin de leur fenêtre
+    /// d'arrêt attendu : leurs événements `stop`/`die` ne sont pas signalés comme des pannes.
+    pub expected_stops: Mutex<HashMap<String, Instant>>,
+    /// Dernier statut de conteneur publié par projet, pour ne publier que les changements.
+    pub container_statuses: Mutex<ContainerStatusTracker>,
+    /// Circuits des URL de webhook de projet en échec, pour cesser temporairement de les appeler.
+    pub webhook_circuits: Mutex<WebhookCircuits>,
+    /// Transport des notifications par e-mail ; `None` si le SMTP n'est pas configuré.
+    pub mailer: Option<Mailer>,
+    /// E-mails récents envoyés à chaque utilisateur, pour le rate-limit.
+    pub email_sends: Mutex<HashMap<String, VecDeque<Instant>>>,
+    /// Filtre de logs rechargeable, installé au démarrage.
+    pub log_filter: LogFilter,
+    /// Événements émis sans abonné, en attente de la tâche qui les conserve (retirés au démarrage de celle-ci).
+    pub missed_events: Mutex<Option<mpsc::Receiver<MissedEvent>>>,
 }
 
 impl InnerState 
 {
     #[must_use] 
-    pub fn new(config: Config, docker_client: Docker, db_pool: PgPool, mariadb_pool: MySqlPool) -> AppState 
+    pub fn new(config: Config, docker_client: Docker, db_pool: PgPool, mariadb_pool: MySqlPool, log_filter: LogFilter) -> AppState 
     {
+        let mut sse_manager = SseManager::new(config.max_sse_connections);
+        let mut missed_events = None;
+        if !config.missed_event_types.is_empty()
+        {
+            let (tx, rx) = mpsc::channel(MISSED_EVENTS_QUEUE);
+            sse_manager = sse_manager.with_missed_events(config.missed_event_types.clone(), tx);
+            missed_events = Some(rx);
+        }
+
+        let restart_window = Duration::from_secs(config.container_restart_window_seconds);
+        let mailer = Mailer::from_config(&config);
+
         Arc::new(Self 
         {
             config,
@@ -27,7 +63,18 @@ impl InnerState
             docker_client,
             db_pool,
             mariadb_pool,
-            sse_manager: SseManager::new(),
+            sse_manager,
+            running_tasks: Mutex::new(HashSet::new()),
+            docker_df_cache: Mutex::new(None),
+            hook_calls: Mutex::new(HashMap::new()),
+            database_resets: Mutex::new(HashMap::new()),
+            expected_stops: Mutex::new(HashMap::new()),
+            container_statuses: Mutex::new(ContainerStatusTracker::new(restart_window)),
+            webhook_circuits: Mutex::new(WebhookCircuits::default()),
+            mailer,
+            email_sends: Mutex::new(HashMap::new()),
+            log_filter,
+            missed_events: Mutex::new(missed_events),
         })
     }
 }
\ No newline at end of file
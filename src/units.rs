@@ -0,0 +1,174 @@
+//! Quantités de ressources typées par unité.
+//!
+//! La valeur brute est privée : toute conversion passe par une méthode nommée d'après
+//! l'unité d'arrivée, ce qui évite les `* 1024 * 1024` dispersés et les mélanges Mo/octets.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+/// Période CFS par défaut de Docker, en microsecondes : un quota égal correspond à un cœur.
+const DEFAULT_CPU_PERIOD_US: i64 = 100_000;
+
+/// Mémoire en mégaoctets (Mio), telle que configurée.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MemoryMb(u64);
+
+impl MemoryMb
+{
+    #[must_use]
+    pub const fn new(mb: u64) -> Self
+    {
+        Self(mb)
+    }
+
+    #[must_use]
+    pub const fn get(self) -> u64
+    {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn to_bytes(self) -> MemoryBytes
+    {
+        MemoryBytes(self.0.saturating_mul(BYTES_PER_MB))
+    }
+}
+
+/// Mémoire en octets, unité de l'API Docker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MemoryBytes(u64);
+
+impl MemoryBytes
+{
+    #[must_use]
+    pub const fn new(bytes: u64) -> Self
+    {
+        Self(bytes)
+    }
+
+    #[must_use]
+    pub const fn get(self) -> u64
+    {
+        self.0
+    }
+
+    /// Valeur attendue par les champs `i64` de l'API Docker, plafonnée à `i64::MAX`.
+    #[must_use]
+    pub fn to_docker(self) -> i64
+    {
+        i64::try_from(self.0).unwrap_or(i64::MAX)
+    }
+
+    #[must_use]
+    pub fn as_mb_f64(self) -> f64
+    {
+        self.0 as f64 / BYTES_PER_MB as f64
+    }
+
+    #[must_use]
+    pub const fn saturating_add(self, other: Self) -> Self
+    {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    #[must_use]
+    pub const fn saturating_sub(self, other: Self) -> Self
+    {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+/// Quota CPU CFS en microseconds par période de 100 ms (`50000` = un demi-cœur).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CpuQuota(i64);
+
+impl CpuQuota
+{
+    #[must_use]
+    pub const fn new(micros: i64) -> Self
+    {
+        Self(micros)
+    }
+
+    /// Valeur du champ `cpu_quota` de l'API Docker.
+    #[must_use]
+    pub const fn to_docker(self) -> i64
+    {
+        self.0
+    }
+
+    /// Nombre de cœurs équivalent avec la période par défaut.
+    #[must_use]
+    pub fn cores(self) -> f64
+    {
+        self.0 as f64 / DEFAULT_CPU_PERIOD_US as f64
+    }
+}
+
+/// Durée en secondes, telle que configurée.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Seconds(u64);
+
+impl Seconds
+{
+    #[must_use]
+    pub const fn new(seconds: u64) -> Self
+    {
+        Self(seconds)
+    }
+
+    #[must_use]
+    pub const fn get(self) -> u64
+    {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn as_duration(self) -> Duration
+    {
+        Duration::from_secs(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_conversions()
+    {
+        assert_eq!(MemoryMb::new(512).to_bytes(), MemoryBytes::new(536_870_912));
+        assert_eq!(MemoryMb::new(512).to_bytes().to_docker(), 536_870_912);
+        assert!((MemoryBytes::new(536_870_912).as_mb_f64() - 512.0).abs() < f64::EPSILON);
+        assert!((MemoryBytes::new(1_572_864).as_mb_f64() - 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_memory_conversions_saturate()
+    {
+        assert_eq!(MemoryMb::new(u64::MAX).to_bytes(), MemoryBytes::new(u64::MAX));
+        assert_eq!(MemoryBytes::new(u64::MAX).to_docker(), i64::MAX);
+        assert_eq!(MemoryBytes::new(1).saturating_sub(MemoryBytes::new(2)), MemoryBytes::default());
+    }
+
+    #[test]
+    fn test_cpu_and_seconds_conversions()
+    {
+        assert_eq!(CpuQuota::new(50_000).to_docker(), 50_000);
+        assert!((CpuQuota::new(50_000).cores() - 0.5).abs() < f64::EPSILON);
+        assert_eq!(Seconds::new(90).as_duration(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_serialized_as_raw_numbers()
+    {
+        assert_eq!(serde_json::to_string(&MemoryBytes::new(42)).unwrap(), "42");
+        assert_eq!(serde_json::from_str::<MemoryMb>("512").unwrap(), MemoryMb::new(512));
+    }
+}